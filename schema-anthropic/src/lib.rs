@@ -1,5 +1,6 @@
-use schema::SchemaType;
+use schema::{GenerateOptions, SchemaType, Tagging, TypeKind};
 use serde_json::{Value, json};
+use std::collections::BTreeSet;
 
 /// Convert a Schema to Anthropic-compatible JSON Schema
 ///
@@ -7,15 +8,109 @@ use serde_json::{Value, json};
 /// - Avoids oneOf for tagged unions
 /// - Uses discriminator pattern instead
 /// - Simpler enum representation
+///
+/// Recursive types are written as `$ref`s into a top-level `definitions` map.
 pub fn to_anthropic_schema(schema: &SchemaType) -> Value {
-    use schema::TypeKind;
+    with_definitions(schema, schema, convert)
+}
+
+/// Convert a SchemaType to Anthropic-compatible JSON Schema, bounded by `options`
+///
+/// Named types cut off by `max_depth` become `$ref`s, defined (with the
+/// same bound) from the full schema.
+pub fn to_anthropic_schema_with_options(schema: &SchemaType, options: &GenerateOptions) -> Value {
+    with_definitions(&options.apply(schema), schema, |definition| {
+        convert(&options.apply(definition))
+    })
+}
 
+/// Helper to create a full tool schema for Anthropic
+pub fn create_tool_schema(name: &str, description: &str, input_schema: &SchemaType) -> Value {
+    json!({
+        "name": name,
+        "description": description,
+        "input_schema": to_anthropic_schema(input_schema),
+    })
+}
+
+/// `root` converted, plus a `definitions` entry for every type its `$ref`s
+/// (and those of the definitions) point to, looked up in `full`
+fn with_definitions(
+    root: &SchemaType,
+    full: &SchemaType,
+    define: impl Fn(&SchemaType) -> Value,
+) -> Value {
+    let mut result = convert(root);
+    let mut definitions = serde_json::Map::new();
+    let mut pending = BTreeSet::new();
+    collect_refs(&result, &mut pending);
+    while let Some(name) = pending.pop_first() {
+        if definitions.contains_key(&name) {
+            continue;
+        }
+        let definition = match find_named(full, &name) {
+            Some(found) => define(found),
+            // Not part of this schema, so nothing narrower can be said
+            None => json!({}),
+        };
+        collect_refs(&definition, &mut pending);
+        definitions.insert(name, definition);
+    }
+    if !definitions.is_empty() {
+        result["definitions"] = Value::Object(definitions);
+    }
+    result
+}
+
+/// The type names of every `#/definitions/` reference in `value`
+fn collect_refs(value: &Value, names: &mut BTreeSet<String>) {
+    match value {
+        Value::Object(obj) => {
+            if let Some(name) = obj
+                .get("$ref")
+                .and_then(Value::as_str)
+                .and_then(|target| target.strip_prefix("#/definitions/"))
+            {
+                names.insert(name.to_string());
+            }
+            obj.values().for_each(|child| collect_refs(child, names));
+        }
+        Value::Array(items) => items.iter().for_each(|child| collect_refs(child, names)),
+        _ => {}
+    }
+}
+
+/// The first subtree named `name` that is written out rather than referenced
+fn find_named<'a>(schema: &'a SchemaType, name: &str) -> Option<&'a SchemaType> {
+    if schema.name.as_deref() == Some(name) && !matches!(schema.kind, TypeKind::Ref { .. }) {
+        return Some(schema);
+    }
+    let children: Vec<&SchemaType> = match &schema.kind {
+        TypeKind::Object { properties, .. } => properties.values().collect(),
+        TypeKind::Array { items } | TypeKind::Set { items, .. } => vec![items],
+        TypeKind::Map { key, value, .. } => vec![key, value],
+        TypeKind::TaggedUnion { data_fields, .. } => data_fields.values().collect(),
+        TypeKind::Variant { cases } => cases.iter().filter_map(|c| c.data.as_ref()).collect(),
+        TypeKind::Result { ok, err } => vec![ok, err],
+        TypeKind::Tuple { fields } => fields.iter().collect(),
+        _ => Vec::new(),
+    };
+    children
+        .into_iter()
+        .find_map(|child| find_named(child, name))
+}
+
+fn convert(schema: &SchemaType) -> Value {
     let mut obj = serde_json::Map::new();
 
-    // Add description if present
+    // Add title and description if present
+    if let Some(title) = &schema.title {
+        obj.insert("title".to_string(), json!(title));
+    }
     if let Some(desc) = &schema.description {
         obj.insert("description".to_string(), json!(desc));
     }
+    obj.extend(schema.constraints.json_keywords());
 
     match &schema.kind {
         TypeKind::String => {
@@ -44,22 +139,25 @@ pub fn to_anthropic_schema(schema: &SchemaType) -> Value {
         } => {
             let mut props = serde_json::Map::new();
             for (key, value) in properties {
-                props.insert(key.clone(), to_anthropic_schema(value));
+                props.insert(key.clone(), convert(value));
             }
 
             obj.insert("type".to_string(), json!("object"));
             obj.insert("properties".to_string(), Value::Object(props));
             obj.insert("required".to_string(), json!(required));
+            if schema.denies_unknown_fields() {
+                obj.insert("additionalProperties".to_string(), json!(false));
+            }
         }
 
         TypeKind::Array { items } => {
             obj.insert("type".to_string(), json!("array"));
-            obj.insert("items".to_string(), to_anthropic_schema(items));
+            obj.insert("items".to_string(), convert(items));
         }
 
         TypeKind::Set { items, .. } => {
             obj.insert("type".to_string(), json!("array"));
-            obj.insert("items".to_string(), to_anthropic_schema(items));
+            obj.insert("items".to_string(), convert(items));
             obj.insert("uniqueItems".to_string(), json!(true));
         }
 
@@ -67,20 +165,14 @@ pub fn to_anthropic_schema(schema: &SchemaType) -> Value {
             // If key is String, use additionalProperties
             if matches!(key.kind, TypeKind::String) {
                 obj.insert("type".to_string(), json!("object"));
-                obj.insert(
-                    "additionalProperties".to_string(),
-                    to_anthropic_schema(value),
-                );
+                obj.insert("additionalProperties".to_string(), convert(value));
             } else {
                 // For non-string keys, use array of tuples
-                let tuple_schema = SchemaType {
-                    kind: TypeKind::Tuple {
-                        fields: vec![(**key).clone(), (**value).clone()],
-                    },
-                    description: None,
-                };
+                let tuple_schema = SchemaType::new(TypeKind::Tuple {
+                    fields: vec![(**key).clone(), (**value).clone()],
+                });
                 obj.insert("type".to_string(), json!("array"));
-                obj.insert("items".to_string(), to_anthropic_schema(&tuple_schema));
+                obj.insert("items".to_string(), convert(&tuple_schema));
             }
         }
 
@@ -110,7 +202,7 @@ pub fn to_anthropic_schema(schema: &SchemaType) -> Value {
 
             // Add all data fields (they're all optional since they depend on tag)
             for (field_name, field_schema) in data_fields {
-                properties.insert(field_name.clone(), to_anthropic_schema(field_schema));
+                properties.insert(field_name.clone(), convert(field_schema));
             }
 
             obj.insert("type".to_string(), json!("object"));
@@ -118,53 +210,82 @@ pub fn to_anthropic_schema(schema: &SchemaType) -> Value {
             obj.insert("required".to_string(), json!([tag_field]));
         }
 
+        TypeKind::Variant { cases } if schema.tagging() == Some(Tagging::Untagged) => {
+            // No discriminator to flatten around; any payload shape may appear
+            let payloads: Vec<Value> = cases
+                .iter()
+                .map(|case| match &case.data {
+                    Some(data) => convert(data),
+                    None => json!({ "type": "null" }),
+                })
+                .collect();
+            obj.insert("anyOf".to_string(), json!(payloads));
+        }
+
         TypeKind::Variant { cases } => {
             // Similar to TaggedUnion but with proper per-case structure
             // Flatten for Anthropic compatibility
+            let tagging = schema.tagging();
+            let tag = tagging
+                .as_ref()
+                .and_then(|tagging| tagging.tag())
+                .unwrap_or("type");
             let mut properties = serde_json::Map::new();
 
             // Add discriminator field
             let tag_variants: Vec<String> = cases.iter().map(|c| c.name.clone()).collect();
             properties.insert(
-                "type".to_string(),
+                tag.to_string(),
                 json!({
                     "type": "string",
                     "enum": tag_variants,
                 }),
             );
 
-            // Collect all unique fields from all cases
-            let mut all_fields = std::collections::HashMap::new();
-            for case in cases {
-                if let Some(data) = &case.data {
-                    if let TypeKind::Object {
-                        properties: props, ..
-                    } = &data.kind
-                    {
-                        for (field_name, field_schema) in props {
-                            all_fields
-                                .entry(field_name.clone())
-                                .or_insert_with(|| field_schema.clone());
+            if let Some(Tagging::Adjacent { content, .. }) = &tagging {
+                // Payloads stay nested under the content property
+                let payloads: Vec<Value> = cases
+                    .iter()
+                    .filter_map(|case| case.data.as_ref())
+                    .map(convert)
+                    .collect();
+                if !payloads.is_empty() {
+                    properties.insert(content.clone(), json!({ "anyOf": payloads }));
+                }
+            } else {
+                // Collect all unique fields from all cases, in case and
+                // declaration order; the first case to declare a field wins
+                for case in cases {
+                    for (field_name, field_schema) in case.fields() {
+                        if !properties.contains_key(field_name) {
+                            properties.insert(field_name.to_string(), convert(field_schema));
                         }
                     }
                 }
-            }
 
-            // Add all fields as optional
-            for (field_name, field_schema) in all_fields {
-                properties.insert(field_name, to_anthropic_schema(&field_schema));
+                // Every data field has to be optional in the flat object, so
+                // spell out which fields each case requires on the discriminator
+                let requirements: Vec<String> = cases
+                    .iter()
+                    .filter(|case| !case.required().is_empty())
+                    .map(|case| format!("{:?} requires {}", case.name, case.required().join(", ")))
+                    .collect();
+                if !requirements.is_empty() {
+                    properties[tag]["description"] =
+                        json!(format!("Selects the case; {}", requirements.join("; ")));
+                }
             }
 
             obj.insert("type".to_string(), json!("object"));
             obj.insert("properties".to_string(), Value::Object(properties));
-            obj.insert("required".to_string(), json!(["type"]));
+            obj.insert("required".to_string(), json!([tag]));
         }
 
         TypeKind::Result { ok, err } => {
             // Represent as union with ok/error fields
             let mut properties = serde_json::Map::new();
-            properties.insert("ok".to_string(), to_anthropic_schema(ok));
-            properties.insert("error".to_string(), to_anthropic_schema(err));
+            properties.insert("ok".to_string(), convert(ok));
+            properties.insert("error".to_string(), convert(err));
 
             obj.insert("type".to_string(), json!("object"));
             obj.insert("properties".to_string(), Value::Object(properties));
@@ -180,7 +301,7 @@ pub fn to_anthropic_schema(schema: &SchemaType) -> Value {
                 obj.insert("type".to_string(), json!("array"));
                 obj.insert("maxItems".to_string(), json!(0));
             } else {
-                let items: Vec<Value> = fields.iter().map(to_anthropic_schema).collect();
+                let items: Vec<Value> = fields.iter().map(convert).collect();
                 obj.insert("type".to_string(), json!("array"));
                 obj.insert("prefixItems".to_string(), json!(items));
                 obj.insert("minItems".to_string(), json!(fields.len()));
@@ -195,12 +316,3 @@ pub fn to_anthropic_schema(schema: &SchemaType) -> Value {
 
     Value::Object(obj)
 }
-
-/// Helper to create a full tool schema for Anthropic
-pub fn create_tool_schema(name: &str, description: &str, input_schema: &SchemaType) -> Value {
-    json!({
-        "name": name,
-        "description": description,
-        "input_schema": to_anthropic_schema(input_schema),
-    })
-}
@@ -1,6 +1,6 @@
-use schema::Schema;
-use schema_anthropic::{create_tool_schema, to_anthropic_schema};
-use serde_json::json;
+use schema::{GenerateOptions, Schema};
+use schema_anthropic::{create_tool_schema, to_anthropic_schema, to_anthropic_schema_with_options};
+use serde_json::{Value, json};
 
 #[derive(Schema)]
 #[allow(dead_code)]
@@ -88,3 +88,131 @@ fn test_no_oneof_in_output() {
     assert!(!json_str.contains("oneOf"));
     assert!(!json_str.contains("one_of"));
 }
+
+#[derive(Schema)]
+#[allow(dead_code)]
+struct Category {
+    name: String,
+    children: Vec<Category>,
+    tags: Vec<ClickElement>,
+}
+
+/// Every `$ref` target in `value`
+fn refs(value: &Value, out: &mut Vec<String>) {
+    match value {
+        Value::Object(obj) => {
+            if let Some(target) = obj.get("$ref").and_then(Value::as_str) {
+                out.push(target.to_string());
+            }
+            obj.values().for_each(|child| refs(child, out));
+        }
+        Value::Array(items) => items.iter().for_each(|child| refs(child, out)),
+        _ => {}
+    }
+}
+
+fn assert_refs_resolve(schema: &Value) {
+    let mut targets = vec![];
+    refs(schema, &mut targets);
+    assert!(!targets.is_empty());
+    for target in targets {
+        let pointer = target.strip_prefix('#').unwrap();
+        assert!(
+            schema.pointer(pointer).is_some(),
+            "{target} does not resolve"
+        );
+    }
+}
+
+#[test]
+fn test_max_depth_to_anthropic() {
+    let schema = Category::schema();
+    let anthropic = to_anthropic_schema_with_options(&schema, &GenerateOptions::new().max_depth(2));
+
+    assert_eq!(
+        anthropic["properties"]["tags"]["items"]["$ref"],
+        "#/definitions/ClickElement"
+    );
+    assert_eq!(
+        anthropic["definitions"]["ClickElement"]["required"],
+        json!(["selector"])
+    );
+    assert_refs_resolve(&anthropic);
+}
+
+#[test]
+fn test_recursive_refs_resolve() {
+    let anthropic = to_anthropic_schema(&Category::schema());
+
+    assert_eq!(
+        anthropic["properties"]["children"]["items"]["$ref"],
+        "#/definitions/Category"
+    );
+    assert_eq!(
+        anthropic["definitions"]["Category"]["properties"]["name"]["type"],
+        "string"
+    );
+    assert_refs_resolve(&anthropic);
+}
+
+#[derive(Schema)]
+#[allow(dead_code)]
+enum Currency {
+    Usd,
+    Eur,
+    Gbp,
+    Jpy,
+    Chf,
+}
+
+#[derive(Schema)]
+#[allow(dead_code)]
+struct Price {
+    currency: Currency,
+}
+
+#[test]
+fn test_large_enum_to_anthropic() {
+    let options = GenerateOptions::new().max_enum_variants(2);
+    let anthropic = to_anthropic_schema_with_options(&Price::schema(), &options);
+
+    let currency = &anthropic["properties"]["currency"];
+    assert_eq!(currency["type"], "string");
+    assert!(currency.get("enum").is_none());
+    assert_eq!(currency["description"], "One of: Usd, Eur, ... (3 more)");
+}
+
+#[derive(Schema)]
+#[allow(dead_code)]
+enum Shape {
+    Circle { radius: f64 },
+    Rect { width: f64, height: Option<f64> },
+    Empty,
+}
+
+#[test]
+fn test_variant_requirements_on_discriminator() {
+    let anthropic = to_anthropic_schema(&Shape::schema());
+
+    assert_eq!(anthropic["required"], json!(["type"]));
+    assert_eq!(
+        anthropic["properties"]["type"]["description"],
+        "Selects the case; \"Circle\" requires radius; \"Rect\" requires width"
+    );
+    assert!(anthropic["properties"]["height"].is_object());
+}
+
+#[derive(Schema)]
+#[schema(deny_unknown_fields)]
+#[allow(dead_code)]
+struct Search {
+    query: String,
+}
+
+#[test]
+fn test_deny_unknown_fields() {
+    let schema = to_anthropic_schema(&Search::schema());
+    assert_eq!(schema["additionalProperties"], json!(false));
+    let open = to_anthropic_schema(&ClickElement::schema());
+    assert!(open.get("additionalProperties").is_none());
+}
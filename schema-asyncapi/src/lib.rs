@@ -0,0 +1,585 @@
+//! AsyncAPI 3.0 documents
+//!
+//! An [`AsyncApiBuilder`] collects [`Server`]s, [`Channel`]s with the
+//! [`Message`]s they carry, and the [`Operation`]s a service performs on
+//! them. Message payloads are written as JSON Schema by `schema-jsonschema`,
+//! with every named type defined once under `components/schemas` however
+//! many messages use it.
+
+use schema::{GenerateOptions, Schema, SchemaType};
+use schema_jsonschema::Definitions;
+use serde_json::{Map, Value, json};
+use std::fmt;
+
+/// The AsyncAPI version documents declare
+pub const ASYNCAPI_VERSION: &str = "3.0.0";
+
+/// Why a document can't be built
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AsyncApiError {
+    /// An operation names a channel that wasn't added
+    UnknownChannel { operation: String, channel: String },
+    /// An operation names a message its channel doesn't carry
+    UnknownMessage { operation: String, message: String },
+    /// Two channels carry different messages under one name
+    ConflictingMessage { message: String },
+}
+
+impl fmt::Display for AsyncApiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AsyncApiError::UnknownChannel { operation, channel } => {
+                write!(
+                    f,
+                    "operation {:?} uses channel {:?}, which isn't defined",
+                    operation, channel
+                )
+            }
+            AsyncApiError::UnknownMessage { operation, message } => {
+                write!(
+                    f,
+                    "operation {:?} uses message {:?}, which its channel doesn't carry",
+                    operation, message
+                )
+            }
+            AsyncApiError::ConflictingMessage { message } => {
+                write!(f, "channels define message {:?} differently", message)
+            }
+        }
+    }
+}
+
+impl std::error::Error for AsyncApiError {}
+
+/// A broker the service connects to
+#[derive(Debug, Clone)]
+pub struct Server {
+    pub name: String,
+    /// Host, and port if needed, such as `broker.example.com:9092`
+    pub host: String,
+    /// Such as `kafka`, `amqp`, `mqtt` or `ws`
+    pub protocol: String,
+    pub description: Option<String>,
+}
+
+impl Server {
+    pub fn new(name: &str, host: &str, protocol: &str) -> Self {
+        Server {
+            name: name.to_string(),
+            host: host.to_string(),
+            protocol: protocol.to_string(),
+            description: None,
+        }
+    }
+
+    pub fn description(mut self, description: &str) -> Self {
+        self.description = Some(description.to_string());
+        self
+    }
+}
+
+/// A message a channel carries
+#[derive(Debug, Clone)]
+pub struct Message {
+    pub name: String,
+    pub title: Option<String>,
+    pub description: Option<String>,
+    /// Overrides the document's `application/json` default
+    pub content_type: Option<String>,
+    pub payload: SchemaType,
+}
+
+impl Message {
+    /// A message whose payload is `T`, described by `T`'s doc comment
+    pub fn new<T: Schema>(name: &str) -> Self {
+        Self::from_schema(name, T::schema())
+    }
+
+    /// A message with a payload of an explicit schema
+    pub fn from_schema(name: &str, payload: SchemaType) -> Self {
+        Message {
+            name: name.to_string(),
+            title: None,
+            description: payload.docs(),
+            content_type: None,
+            payload,
+        }
+    }
+
+    pub fn title(mut self, title: &str) -> Self {
+        self.title = Some(title.to_string());
+        self
+    }
+
+    /// Replaces the description taken from the payload's doc comment
+    pub fn description(mut self, description: &str) -> Self {
+        self.description = Some(description.to_string());
+        self
+    }
+
+    pub fn content_type(mut self, content_type: &str) -> Self {
+        self.content_type = Some(content_type.to_string());
+        self
+    }
+
+    /// The Message Object, with named types in the payload added to
+    /// `definitions`
+    fn to_json(&self, definitions: &mut Definitions, options: &GenerateOptions) -> Value {
+        let mut message = json!({
+            "name": self.name,
+            "payload": definitions.add(&options.apply(&self.payload)),
+        });
+        if let Some(title) = &self.title {
+            message["title"] = json!(title);
+        }
+        if let Some(description) = &self.description {
+            message["description"] = json!(description);
+        }
+        if let Some(content_type) = &self.content_type {
+            message["contentType"] = json!(content_type);
+        }
+        message
+    }
+}
+
+/// An address messages are sent to, such as a topic or queue
+#[derive(Debug, Clone)]
+pub struct Channel {
+    /// Key of the channel in the document, which operations refer to
+    pub id: String,
+    /// Where the channel is on the server, such as `user.signedup`;
+    /// `{param}` expressions are allowed
+    pub address: String,
+    pub description: Option<String>,
+    pub messages: Vec<Message>,
+}
+
+impl Channel {
+    pub fn new(id: &str, address: &str) -> Self {
+        Channel {
+            id: id.to_string(),
+            address: address.to_string(),
+            description: None,
+            messages: vec![],
+        }
+    }
+
+    pub fn description(mut self, description: &str) -> Self {
+        self.description = Some(description.to_string());
+        self
+    }
+
+    /// Carry a message whose payload is `T`
+    pub fn register<T: Schema>(self, name: &str) -> Self {
+        self.message(Message::new::<T>(name))
+    }
+
+    /// Carry a fully configured message
+    ///
+    /// Adding a message with the same name again replaces the earlier one.
+    pub fn message(mut self, message: Message) -> Self {
+        match self.messages.iter_mut().find(|m| m.name == message.name) {
+            Some(existing) => *existing = message,
+            None => self.messages.push(message),
+        }
+        self
+    }
+}
+
+/// Whether an operation sends messages to its channel or receives them
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    Send,
+    Receive,
+}
+
+impl Action {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Action::Send => "send",
+            Action::Receive => "receive",
+        }
+    }
+}
+
+/// Something the service does on a channel
+#[derive(Debug, Clone)]
+pub struct Operation {
+    /// Key of the operation in the document
+    pub id: String,
+    pub action: Action,
+    /// Id of the channel it acts on
+    pub channel: String,
+    pub summary: Option<String>,
+    pub description: Option<String>,
+    /// Names of the channel's messages it handles; all of them when empty
+    pub messages: Vec<String>,
+}
+
+impl Operation {
+    pub fn new(id: &str, action: Action, channel: &str) -> Self {
+        Operation {
+            id: id.to_string(),
+            action,
+            channel: channel.to_string(),
+            summary: None,
+            description: None,
+            messages: vec![],
+        }
+    }
+
+    /// The service publishes to `channel`
+    pub fn send(id: &str, channel: &str) -> Self {
+        Self::new(id, Action::Send, channel)
+    }
+
+    /// The service consumes from `channel`
+    pub fn receive(id: &str, channel: &str) -> Self {
+        Self::new(id, Action::Receive, channel)
+    }
+
+    pub fn summary(mut self, summary: &str) -> Self {
+        self.summary = Some(summary.to_string());
+        self
+    }
+
+    pub fn description(mut self, description: &str) -> Self {
+        self.description = Some(description.to_string());
+        self
+    }
+
+    /// Handle only the named messages of the channel, rather than all
+    pub fn message(mut self, name: &str) -> Self {
+        self.messages.push(name.to_string());
+        self
+    }
+}
+
+/// A complete AsyncAPI document
+#[derive(Debug, Clone)]
+pub struct AsyncApiBuilder {
+    title: String,
+    version: String,
+    description: Option<String>,
+    servers: Vec<Server>,
+    channels: Vec<Channel>,
+    operations: Vec<Operation>,
+    generate: GenerateOptions,
+}
+
+impl AsyncApiBuilder {
+    /// A document for the API `title` at `version` (the API's version, not
+    /// AsyncAPI's)
+    pub fn new(title: &str, version: &str) -> Self {
+        AsyncApiBuilder {
+            title: title.to_string(),
+            version: version.to_string(),
+            description: None,
+            servers: vec![],
+            channels: vec![],
+            operations: vec![],
+            generate: GenerateOptions::default(),
+        }
+    }
+
+    pub fn description(mut self, description: &str) -> Self {
+        self.description = Some(description.to_string());
+        self
+    }
+
+    /// Adding a server with the same name again replaces the earlier one
+    pub fn server(mut self, server: Server) -> Self {
+        match self.servers.iter_mut().find(|s| s.name == server.name) {
+            Some(existing) => *existing = server,
+            None => self.servers.push(server),
+        }
+        self
+    }
+
+    /// Adding a channel with the same id again replaces the earlier one
+    pub fn channel(mut self, channel: Channel) -> Self {
+        match self.channels.iter_mut().find(|c| c.id == channel.id) {
+            Some(existing) => *existing = channel,
+            None => self.channels.push(channel),
+        }
+        self
+    }
+
+    /// Adding an operation with the same id again replaces the earlier one
+    pub fn operation(mut self, operation: Operation) -> Self {
+        match self.operations.iter_mut().find(|o| o.id == operation.id) {
+            Some(existing) => *existing = operation,
+            None => self.operations.push(operation),
+        }
+        self
+    }
+
+    /// Bound every payload schema before it is written
+    pub fn generate(mut self, options: GenerateOptions) -> Self {
+        self.generate = options;
+        self
+    }
+
+    /// The document, with messages and the named types in their payloads
+    /// under `components`
+    pub fn build(&self) -> Result<Value, AsyncApiError> {
+        let mut definitions = Definitions::new("#/components/schemas/");
+        let mut messages = Map::new();
+        let mut channels = Map::new();
+        for channel in &self.channels {
+            let mut refs = Map::new();
+            for message in &channel.messages {
+                let definition = message.to_json(&mut definitions, &self.generate);
+                match messages.get(&message.name) {
+                    Some(existing) if *existing != definition => {
+                        return Err(AsyncApiError::ConflictingMessage {
+                            message: message.name.clone(),
+                        });
+                    }
+                    Some(_) => {}
+                    None => {
+                        messages.insert(message.name.clone(), definition);
+                    }
+                }
+                refs.insert(
+                    message.name.clone(),
+                    reference(&["components", "messages", &message.name]),
+                );
+            }
+
+            let mut object = json!({ "address": channel.address, "messages": refs });
+            if let Some(description) = &channel.description {
+                object["description"] = json!(description);
+            }
+            channels.insert(channel.id.clone(), object);
+        }
+
+        let mut operations = Map::new();
+        for operation in &self.operations {
+            operations.insert(operation.id.clone(), self.operation_json(operation)?);
+        }
+
+        let mut info = json!({ "title": self.title, "version": self.version });
+        if let Some(description) = &self.description {
+            info["description"] = json!(description);
+        }
+        let mut document = json!({
+            "asyncapi": ASYNCAPI_VERSION,
+            "info": info,
+            "defaultContentType": "application/json",
+        });
+        if !self.servers.is_empty() {
+            let servers: Map<String, Value> = self
+                .servers
+                .iter()
+                .map(|server| {
+                    let mut object = json!({ "host": server.host, "protocol": server.protocol });
+                    if let Some(description) = &server.description {
+                        object["description"] = json!(description);
+                    }
+                    (server.name.clone(), object)
+                })
+                .collect();
+            document["servers"] = Value::Object(servers);
+        }
+        if !channels.is_empty() {
+            document["channels"] = Value::Object(channels);
+        }
+        if !operations.is_empty() {
+            document["operations"] = Value::Object(operations);
+        }
+        let mut components = Map::new();
+        if !messages.is_empty() {
+            components.insert("messages".to_string(), Value::Object(messages));
+        }
+        let schemas = definitions.into_definitions();
+        if !schemas.is_empty() {
+            components.insert("schemas".to_string(), json!(schemas));
+        }
+        if !components.is_empty() {
+            document["components"] = Value::Object(components);
+        }
+        Ok(document)
+    }
+
+    /// The Operation Object, pointing at its channel and the channel's
+    /// entries for its messages
+    fn operation_json(&self, operation: &Operation) -> Result<Value, AsyncApiError> {
+        let channel = self
+            .channels
+            .iter()
+            .find(|channel| channel.id == operation.channel)
+            .ok_or_else(|| AsyncApiError::UnknownChannel {
+                operation: operation.id.clone(),
+                channel: operation.channel.clone(),
+            })?;
+
+        let names: Vec<&String> = match operation.messages.is_empty() {
+            true => channel
+                .messages
+                .iter()
+                .map(|message| &message.name)
+                .collect(),
+            false => operation.messages.iter().collect(),
+        };
+        let mut messages = vec![];
+        for name in names {
+            if !channel.messages.iter().any(|message| message.name == *name) {
+                return Err(AsyncApiError::UnknownMessage {
+                    operation: operation.id.clone(),
+                    message: name.clone(),
+                });
+            }
+            messages.push(reference(&["channels", &channel.id, "messages", name]));
+        }
+
+        let mut object = json!({
+            "action": operation.action.as_str(),
+            "channel": reference(&["channels", &channel.id]),
+            "messages": messages,
+        });
+        if let Some(summary) = &operation.summary {
+            object["summary"] = json!(summary);
+        }
+        if let Some(description) = &operation.description {
+            object["description"] = json!(description);
+        }
+        Ok(object)
+    }
+}
+
+/// A Reference Object to the document location at `path`
+fn reference(path: &[&str]) -> Value {
+    let pointer: String = path
+        .iter()
+        .map(|segment| format!("/{}", segment.replace('~', "~0").replace('/', "~1")))
+        .collect();
+    json!({ "$ref": format!("#{pointer}") })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Schema)]
+    #[allow(dead_code)]
+    struct User {
+        id: String,
+        email: String,
+    }
+
+    /// A user signed up
+    #[derive(Schema)]
+    #[allow(dead_code)]
+    struct UserSignedUp {
+        user: User,
+    }
+
+    #[derive(Schema)]
+    #[allow(dead_code)]
+    struct UserRenamed {
+        user: User,
+        previous: String,
+    }
+
+    fn builder() -> AsyncApiBuilder {
+        AsyncApiBuilder::new("Accounts", "1.2.0")
+            .server(Server::new("production", "kafka.example.com:9092", "kafka"))
+            .channel(
+                Channel::new("users", "accounts/users")
+                    .register::<UserSignedUp>("UserSignedUp")
+                    .register::<UserRenamed>("UserRenamed"),
+            )
+            .operation(Operation::send("publishUsers", "users"))
+            .operation(Operation::receive("onSignup", "users").message("UserSignedUp"))
+    }
+
+    #[test]
+    fn test_document() {
+        let document = builder().build().unwrap();
+        assert_eq!(document["asyncapi"], "3.0.0");
+        assert_eq!(document["info"]["version"], "1.2.0");
+        assert_eq!(
+            document["servers"]["production"],
+            json!({ "host": "kafka.example.com:9092", "protocol": "kafka" })
+        );
+        assert_eq!(
+            document["channels"]["users"],
+            json!({
+                "address": "accounts/users",
+                "messages": {
+                    "UserSignedUp": { "$ref": "#/components/messages/UserSignedUp" },
+                    "UserRenamed": { "$ref": "#/components/messages/UserRenamed" }
+                }
+            })
+        );
+        assert_eq!(
+            document["operations"]["onSignup"],
+            json!({
+                "action": "receive",
+                "channel": { "$ref": "#/channels/users" },
+                "messages": [{ "$ref": "#/channels/users/messages/UserSignedUp" }]
+            })
+        );
+        assert_eq!(
+            document["operations"]["publishUsers"]["messages"]
+                .as_array()
+                .unwrap()
+                .len(),
+            2
+        );
+
+        let components = &document["components"];
+        assert_eq!(
+            components["messages"]["UserSignedUp"],
+            json!({
+                "name": "UserSignedUp",
+                "description": "A user signed up",
+                "payload": { "$ref": "#/components/schemas/UserSignedUp" }
+            })
+        );
+        // Both payloads share one User definition
+        assert_eq!(
+            components["schemas"]
+                .as_object()
+                .unwrap()
+                .keys()
+                .collect::<Vec<_>>(),
+            ["User", "UserRenamed", "UserSignedUp"]
+        );
+        assert_eq!(
+            components["schemas"]["UserRenamed"]["properties"]["user"],
+            json!({ "$ref": "#/components/schemas/User" })
+        );
+    }
+
+    #[test]
+    fn test_errors() {
+        let missing = builder().operation(Operation::send("audit", "audit"));
+        assert_eq!(
+            missing.build(),
+            Err(AsyncApiError::UnknownChannel {
+                operation: "audit".to_string(),
+                channel: "audit".to_string()
+            })
+        );
+
+        let unknown = builder().operation(Operation::send("rename", "users").message("Deleted"));
+        assert_eq!(
+            unknown.build(),
+            Err(AsyncApiError::UnknownMessage {
+                operation: "rename".to_string(),
+                message: "Deleted".to_string()
+            })
+        );
+
+        let conflicting =
+            builder().channel(Channel::new("audit", "audit").register::<User>("UserSignedUp"));
+        assert_eq!(
+            conflicting.build(),
+            Err(AsyncApiError::ConflictingMessage {
+                message: "UserSignedUp".to_string()
+            })
+        );
+    }
+}
@@ -0,0 +1,289 @@
+//! Incremental generation of schema artifacts
+//!
+//! Build scripts that emit WIT files or OpenAPI documents on every build
+//! touch their outputs even when nothing changed, which makes downstream
+//! tools (wit-bindgen, openapi-generator, ...) rebuild too. [`Generator`]
+//! records a fingerprint of each artifact's source schema in a manifest and
+//! leaves artifacts alone, mtime included, while that fingerprint holds.
+//!
+//! ```no_run
+//! use schema::Schema;
+//! use schema_build::Generator;
+//!
+//! #[derive(Schema)]
+//! struct User {
+//!     name: String,
+//! }
+//!
+//! let mut generator = Generator::new("target/schemas").unwrap();
+//! generator
+//!     .emit("user.json", &User::schema(), |schema| format!("{:?}", schema))
+//!     .unwrap();
+//! generator.finish().unwrap();
+//! ```
+//!
+//! Schemas can be reshaped before emission with a [`Pipeline`] loaded from a
+//! TOML config. With the `tokio` feature, the `source` module reads bundles
+//! back (`http` adds deployed services) to compare them.
+
+use schema::SchemaType;
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+pub mod pipeline;
+#[cfg(feature = "tokio")]
+pub mod source;
+
+pub use pipeline::{Pipeline, Transform};
+
+/// Manifest file written to the output directory
+pub const MANIFEST_NAME: &str = ".schema-fingerprints";
+
+/// First manifest line; a manifest from another version is discarded so a
+/// changed backend regenerates everything
+const MANIFEST_HEADER: &str = concat!("# schema-build ", env!("CARGO_PKG_VERSION"));
+
+/// What [`Generator::emit`] did with an artifact
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    /// The source fingerprint matched; the renderer wasn't run
+    Skipped,
+    /// The renderer ran but produced the existing contents, so the file
+    /// wasn't rewritten
+    Unchanged,
+    /// The file was created or rewritten
+    Written,
+}
+
+/// Writes artifacts into a directory, skipping those whose source schema
+/// hasn't changed since the last run
+#[derive(Debug)]
+pub struct Generator {
+    out_dir: PathBuf,
+    /// Fingerprint hashes from the previous run, by artifact path
+    previous: BTreeMap<String, String>,
+    /// Fingerprint hashes of artifacts emitted in this run
+    current: BTreeMap<String, String>,
+    force: bool,
+}
+
+impl Generator {
+    /// Open `out_dir` (created if missing) and read its manifest
+    pub fn new(out_dir: impl AsRef<Path>) -> io::Result<Self> {
+        let out_dir = out_dir.as_ref().to_path_buf();
+        fs::create_dir_all(&out_dir)?;
+
+        let previous = match fs::read_to_string(out_dir.join(MANIFEST_NAME)) {
+            Ok(manifest) => parse_manifest(&manifest),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => BTreeMap::new(),
+            Err(e) => return Err(e),
+        };
+
+        Ok(Generator {
+            out_dir,
+            previous,
+            current: BTreeMap::new(),
+            force: false,
+        })
+    }
+
+    /// Render every artifact regardless of the manifest, e.g. after changing
+    /// how artifacts are rendered
+    ///
+    /// Files whose contents come out the same are still left untouched.
+    pub fn force(mut self, force: bool) -> Self {
+        self.force = force;
+        self
+    }
+
+    /// Write `artifact` (a path relative to the output directory) from
+    /// `schema`, unless neither the schema nor the output changed
+    ///
+    /// `render` only runs when the schema's fingerprint differs from the last
+    /// run or the file is missing.
+    pub fn emit(
+        &mut self,
+        artifact: &str,
+        schema: &SchemaType,
+        render: impl FnOnce(&SchemaType) -> String,
+    ) -> io::Result<Outcome> {
+        let hash = fingerprint_hash(schema);
+        let path = self.out_dir.join(artifact);
+        let outcome = if !self.force && self.previous.get(artifact) == Some(&hash) && path.exists()
+        {
+            Outcome::Skipped
+        } else {
+            write_if_changed(&path, &render(schema))?
+        };
+
+        self.current.insert(artifact.to_string(), hash);
+        Ok(outcome)
+    }
+
+    /// Save the manifest for the next run
+    ///
+    /// Artifacts not emitted in this run are dropped from the manifest; their
+    /// files are left in place.
+    pub fn finish(self) -> io::Result<()> {
+        let mut manifest = format!("{}\n", MANIFEST_HEADER);
+        for (artifact, hash) in &self.current {
+            manifest.push_str(&format!("{}  {}\n", hash, artifact));
+        }
+        write_if_changed(&self.out_dir.join(MANIFEST_NAME), &manifest)?;
+        Ok(())
+    }
+}
+
+/// Write `contents` to `path` only if the file doesn't already hold exactly
+/// that, creating parent directories as needed
+pub fn write_if_changed(path: &Path, contents: &str) -> io::Result<Outcome> {
+    match fs::read(path) {
+        Ok(existing) if existing == contents.as_bytes() => return Ok(Outcome::Unchanged),
+        Ok(_) => {}
+        Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+        Err(e) => return Err(e),
+    }
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, contents)?;
+    Ok(Outcome::Written)
+}
+
+/// Short stable hash of [`SchemaType::fingerprint`] (64-bit FNV-1a, which
+/// unlike `DefaultHasher` is the same across Rust releases)
+pub fn fingerprint_hash(schema: &SchemaType) -> String {
+    let hash = schema
+        .fingerprint()
+        .bytes()
+        .fold(0xcbf29ce484222325u64, |hash, byte| {
+            (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+        });
+    format!("{:016x}", hash)
+}
+
+fn parse_manifest(manifest: &str) -> BTreeMap<String, String> {
+    if manifest.lines().next() != Some(MANIFEST_HEADER) {
+        return BTreeMap::new();
+    }
+    parse_manifest_entries(manifest)
+}
+
+/// Manifest entries regardless of which version wrote them
+fn parse_manifest_entries(manifest: &str) -> BTreeMap<String, String> {
+    manifest
+        .lines()
+        .filter(|line| !line.starts_with('#'))
+        .filter_map(|line| line.split_once("  "))
+        .map(|(hash, artifact)| (artifact.to_string(), hash.to_string()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use schema::Schema;
+    use std::cell::Cell;
+
+    #[derive(Schema)]
+    #[allow(dead_code)]
+    struct User {
+        name: String,
+    }
+
+    #[derive(Schema)]
+    #[allow(dead_code)]
+    struct UserV2 {
+        name: String,
+        email: Option<String>,
+    }
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("schema-build-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    fn run(dir: &Path, schema: &SchemaType, renders: &Cell<usize>) -> Outcome {
+        let mut generator = Generator::new(dir).unwrap();
+        let outcome = generator
+            .emit("types/user.txt", schema, |schema| {
+                renders.set(renders.get() + 1);
+                schema.fingerprint()
+            })
+            .unwrap();
+        generator.finish().unwrap();
+        outcome
+    }
+
+    #[test]
+    fn test_skips_unchanged_schemas() {
+        let dir = temp_dir("skip");
+        let renders = Cell::new(0);
+
+        assert_eq!(run(&dir, &User::schema(), &renders), Outcome::Written);
+        let modified = fs::metadata(dir.join("types/user.txt"))
+            .unwrap()
+            .modified()
+            .unwrap();
+
+        assert_eq!(run(&dir, &User::schema(), &renders), Outcome::Skipped);
+        assert_eq!(renders.get(), 1);
+        assert_eq!(
+            fs::metadata(dir.join("types/user.txt"))
+                .unwrap()
+                .modified()
+                .unwrap(),
+            modified
+        );
+
+        assert_eq!(run(&dir, &UserV2::schema(), &renders), Outcome::Written);
+        assert_eq!(renders.get(), 2);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_regenerates_missing_artifacts() {
+        let dir = temp_dir("missing");
+        let renders = Cell::new(0);
+
+        run(&dir, &User::schema(), &renders);
+        fs::remove_file(dir.join("types/user.txt")).unwrap();
+        assert_eq!(run(&dir, &User::schema(), &renders), Outcome::Written);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_force_keeps_identical_output() {
+        let dir = temp_dir("force");
+
+        let mut generator = Generator::new(&dir).unwrap();
+        generator
+            .emit("user.txt", &User::schema(), |_| "same".to_string())
+            .unwrap();
+        generator.finish().unwrap();
+
+        let mut generator = Generator::new(&dir).unwrap().force(true);
+        let outcome = generator
+            .emit("user.txt", &User::schema(), |_| "same".to_string())
+            .unwrap();
+        assert_eq!(outcome, Outcome::Unchanged);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_manifest_from_other_version_is_ignored() {
+        let manifest = "# schema-build 0.0.0\n0123456789abcdef  user.txt\n";
+        assert!(parse_manifest(manifest).is_empty());
+
+        let manifest = format!("{}\n0123456789abcdef  user.txt\n", MANIFEST_HEADER);
+        assert_eq!(parse_manifest(&manifest)["user.txt"], "0123456789abcdef");
+    }
+}
@@ -0,0 +1,473 @@
+//! Ordered schema transforms, configurable from TOML
+//!
+//! Shaping rules (renames, hidden fields, extra constraints) can live in a
+//! reviewable config file instead of code:
+//!
+//! ```toml
+//! [[transform]]
+//! kind = "hide"
+//! properties = ["password_hash", "internal_id"]
+//!
+//! [[transform]]
+//! kind = "rename"
+//! case = "camelCase"
+//!
+//! [[transform]]
+//! kind = "constraints"
+//! property = "email"
+//! format = "email"
+//! max_length = 254
+//!
+//! [[transform]]
+//! kind = "normalize"
+//! ```
+//!
+//! Transforms run in file order, so later ones see earlier ones' output
+//! (the `constraints` rule above must name the renamed property).
+
+use schema::case::{Case, ParseCaseError};
+use schema::{Constraints, SchemaType, TypeKind, VariantCase};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fmt;
+use std::io;
+use std::path::Path;
+
+/// A single step that reshapes a schema
+pub trait Transform {
+    fn apply(&self, schema: SchemaType) -> SchemaType;
+}
+
+/// Transforms applied in order
+#[derive(Default)]
+pub struct Pipeline {
+    transforms: Vec<Box<dyn Transform>>,
+}
+
+impl Pipeline {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append a transform
+    pub fn then(mut self, transform: impl Transform + 'static) -> Self {
+        self.transforms.push(Box::new(transform));
+        self
+    }
+
+    /// Build a pipeline from a TOML config (see the module docs)
+    pub fn from_toml(config: &str) -> Result<Self, ConfigError> {
+        let config: Config = toml::from_str(config)?;
+        config
+            .transform
+            .into_iter()
+            .try_fold(Pipeline::new(), |pipeline, transform| {
+                Ok(match transform {
+                    TransformConfig::Rename { case } => {
+                        pipeline.then(RenameProperties(case.parse()?))
+                    }
+                    TransformConfig::Hide { properties } => {
+                        pipeline.then(HideProperties(properties))
+                    }
+                    TransformConfig::Constraints {
+                        property,
+                        constraints,
+                    } => pipeline.then(InjectConstraints {
+                        property,
+                        constraints: constraints.into(),
+                    }),
+                    TransformConfig::Normalize => pipeline.then(Normalize),
+                })
+            })
+    }
+
+    /// Read a TOML config file
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, ConfigError> {
+        Self::from_toml(&std::fs::read_to_string(path)?)
+    }
+
+    /// Run every transform over `schema`
+    pub fn run(&self, schema: &SchemaType) -> SchemaType {
+        self.transforms
+            .iter()
+            .fold(schema.clone(), |schema, transform| transform.apply(schema))
+    }
+}
+
+/// Rename every object property to the given case
+///
+/// Enum and variant case names are left alone.
+#[derive(Debug, Clone)]
+pub struct RenameProperties(pub Case);
+
+impl Transform for RenameProperties {
+    fn apply(&self, schema: SchemaType) -> SchemaType {
+        let rename = |name: String| self.0.apply(&name);
+        map_tree(schema, &|mut schema| {
+            schema.kind = match schema.kind {
+                TypeKind::Object {
+                    properties,
+                    required,
+                } => TypeKind::Object {
+                    properties: properties
+                        .into_iter()
+                        .map(|(k, v)| (rename(k), v))
+                        .collect(),
+                    required: required.into_iter().map(rename).collect(),
+                },
+                TypeKind::TaggedUnion {
+                    tag_field,
+                    tag_variants,
+                    data_fields,
+                } => TypeKind::TaggedUnion {
+                    tag_field: rename(tag_field),
+                    tag_variants,
+                    data_fields: data_fields
+                        .into_iter()
+                        .map(|(k, v)| (rename(k), v))
+                        .collect(),
+                },
+                other => other,
+            };
+            schema.property_order = schema.property_order.into_iter().map(rename).collect();
+            schema
+        })
+    }
+}
+
+/// Remove properties with any of these names, wherever they occur
+#[derive(Debug, Clone)]
+pub struct HideProperties(pub Vec<String>);
+
+impl Transform for HideProperties {
+    fn apply(&self, schema: SchemaType) -> SchemaType {
+        let visible = |name: &String| !self.0.contains(name);
+        map_tree(schema, &|mut schema| {
+            if let TypeKind::Object {
+                properties,
+                required,
+            } = &mut schema.kind
+            {
+                properties.retain(|name, _| visible(name));
+                required.retain(visible);
+            }
+            if let TypeKind::TaggedUnion { data_fields, .. } = &mut schema.kind {
+                data_fields.retain(|name, _| visible(name));
+            }
+            schema.property_order.retain(visible);
+            schema
+        })
+    }
+}
+
+/// Set constraints on every property with the given name
+///
+/// Only the constraints set here are changed; others are kept.
+#[derive(Debug, Clone)]
+pub struct InjectConstraints {
+    pub property: String,
+    pub constraints: Constraints,
+}
+
+impl Transform for InjectConstraints {
+    fn apply(&self, schema: SchemaType) -> SchemaType {
+        let inject = |target: &mut SchemaType| {
+            let c = &self.constraints;
+            let t = &mut target.constraints;
+            t.minimum = c.minimum.or(t.minimum);
+            t.maximum = c.maximum.or(t.maximum);
+            t.min_length = c.min_length.or(t.min_length);
+            t.max_length = c.max_length.or(t.max_length);
+            t.min_items = c.min_items.or(t.min_items);
+            t.max_items = c.max_items.or(t.max_items);
+            t.unique_items |= c.unique_items;
+            t.pattern = c.pattern.clone().or(t.pattern.take());
+            t.format = c.format.clone().or(t.format.take());
+        };
+        map_tree(schema, &|mut schema| {
+            if let TypeKind::Object { properties, .. }
+            | TypeKind::TaggedUnion {
+                data_fields: properties,
+                ..
+            } = &mut schema.kind
+                && let Some(target) = properties.get_mut(&self.property)
+            {
+                inject(target);
+            }
+            schema
+        })
+    }
+}
+
+/// Canonicalize the schema (see [`SchemaType::canonicalize`])
+#[derive(Debug, Clone, Copy)]
+pub struct Normalize;
+
+impl Transform for Normalize {
+    fn apply(&self, schema: SchemaType) -> SchemaType {
+        schema.canonicalize()
+    }
+}
+
+/// Rebuild the tree bottom-up, applying `f` to every node after its children
+fn map_tree(schema: SchemaType, f: &impl Fn(SchemaType) -> SchemaType) -> SchemaType {
+    let child = |s: SchemaType| map_tree(s, f);
+    let boxed = |s: Box<SchemaType>| Box::new(child(*s));
+    let map = |m: HashMap<String, SchemaType>| {
+        m.into_iter()
+            .map(|(k, v)| (k, child(v)))
+            .collect::<HashMap<_, _>>()
+    };
+
+    let kind = match schema.kind {
+        TypeKind::Object {
+            properties,
+            required,
+        } => TypeKind::Object {
+            properties: map(properties),
+            required,
+        },
+        TypeKind::Array { items } => TypeKind::Array {
+            items: boxed(items),
+        },
+        TypeKind::Set { items, ordered } => TypeKind::Set {
+            items: boxed(items),
+            ordered,
+        },
+        TypeKind::Map {
+            key,
+            value,
+            ordered,
+        } => TypeKind::Map {
+            key: boxed(key),
+            value: boxed(value),
+            ordered,
+        },
+        TypeKind::TaggedUnion {
+            tag_field,
+            tag_variants,
+            data_fields,
+        } => TypeKind::TaggedUnion {
+            tag_field,
+            tag_variants,
+            data_fields: map(data_fields),
+        },
+        TypeKind::Variant { cases } => TypeKind::Variant {
+            cases: cases
+                .into_iter()
+                .map(|case| VariantCase {
+                    data: case.data.map(child),
+                    ..case
+                })
+                .collect(),
+        },
+        TypeKind::Result { ok, err } => TypeKind::Result {
+            ok: boxed(ok),
+            err: boxed(err),
+        },
+        TypeKind::Tuple { fields } => TypeKind::Tuple {
+            fields: fields.into_iter().map(child).collect(),
+        },
+        other => other,
+    };
+
+    f(SchemaType { kind, ..schema })
+}
+
+/// Errors from loading a pipeline config
+#[derive(Debug)]
+pub enum ConfigError {
+    Io(io::Error),
+    Toml(toml::de::Error),
+    Case(ParseCaseError),
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::Io(e) => write!(f, "failed to read pipeline config: {}", e),
+            ConfigError::Toml(e) => write!(f, "invalid pipeline config: {}", e),
+            ConfigError::Case(e) => write!(f, "invalid pipeline config: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+impl From<io::Error> for ConfigError {
+    fn from(e: io::Error) -> Self {
+        ConfigError::Io(e)
+    }
+}
+
+impl From<toml::de::Error> for ConfigError {
+    fn from(e: toml::de::Error) -> Self {
+        ConfigError::Toml(e)
+    }
+}
+
+impl From<ParseCaseError> for ConfigError {
+    fn from(e: ParseCaseError) -> Self {
+        ConfigError::Case(e)
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Config {
+    #[serde(default)]
+    transform: Vec<TransformConfig>,
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+enum TransformConfig {
+    Rename {
+        case: String,
+    },
+    Hide {
+        properties: Vec<String>,
+    },
+    Constraints {
+        property: String,
+        #[serde(flatten)]
+        constraints: ConstraintsConfig,
+    },
+    Normalize,
+}
+
+#[derive(Deserialize)]
+struct ConstraintsConfig {
+    minimum: Option<f64>,
+    maximum: Option<f64>,
+    min_length: Option<usize>,
+    max_length: Option<usize>,
+    min_items: Option<usize>,
+    max_items: Option<usize>,
+    #[serde(default)]
+    unique_items: bool,
+    pattern: Option<String>,
+    format: Option<String>,
+}
+
+impl From<ConstraintsConfig> for Constraints {
+    fn from(c: ConstraintsConfig) -> Self {
+        Constraints {
+            minimum: c.minimum,
+            maximum: c.maximum,
+            min_length: c.min_length,
+            max_length: c.max_length,
+            min_items: c.min_items,
+            max_items: c.max_items,
+            unique_items: c.unique_items,
+            pattern: c.pattern,
+            format: c.format,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use schema::Schema;
+
+    #[derive(Schema)]
+    #[allow(dead_code)]
+    struct Account {
+        user_name: String,
+        email_address: String,
+        password_hash: String,
+        profile: Profile,
+    }
+
+    #[derive(Schema)]
+    #[allow(dead_code)]
+    struct Profile {
+        display_name: Option<String>,
+        password_hash: String,
+    }
+
+    const CONFIG: &str = r#"
+        [[transform]]
+        kind = "hide"
+        properties = ["password_hash"]
+
+        [[transform]]
+        kind = "rename"
+        case = "camelCase"
+
+        [[transform]]
+        kind = "constraints"
+        property = "emailAddress"
+        format = "email"
+        max_length = 254
+
+        [[transform]]
+        kind = "normalize"
+    "#;
+
+    fn properties(schema: &SchemaType) -> &HashMap<String, SchemaType> {
+        match &schema.kind {
+            TypeKind::Object { properties, .. } => properties,
+            _ => panic!("expected object"),
+        }
+    }
+
+    #[test]
+    fn test_config_pipeline() {
+        let schema = Pipeline::from_toml(CONFIG).unwrap().run(&Account::schema());
+
+        let names: Vec<_> = schema
+            .ordered_properties()
+            .into_iter()
+            .map(|(name, _)| name)
+            .collect();
+        assert_eq!(names, vec!["userName", "emailAddress", "profile"]);
+
+        let profile = &properties(&schema)["profile"];
+        assert!(properties(profile).contains_key("displayName"));
+        assert!(!properties(profile).contains_key("passwordHash"));
+
+        let email = &properties(&schema)["emailAddress"].constraints;
+        assert_eq!(email.format.as_deref(), Some("email"));
+        assert_eq!(email.max_length, Some(254));
+
+        let TypeKind::Object { required, .. } = &schema.kind else {
+            unreachable!()
+        };
+        assert_eq!(required, &vec!["emailAddress", "profile", "userName"]);
+    }
+
+    #[test]
+    fn test_code_pipeline() {
+        struct Describe;
+        impl Transform for Describe {
+            fn apply(&self, mut schema: SchemaType) -> SchemaType {
+                schema.description = Some("Shaped".to_string());
+                schema
+            }
+        }
+
+        let schema = Pipeline::new()
+            .then(HideProperties(vec!["profile".to_string()]))
+            .then(Describe)
+            .run(&Account::schema());
+        assert_eq!(properties(&schema).len(), 3);
+        assert_eq!(schema.description.as_deref(), Some("Shaped"));
+    }
+
+    #[test]
+    fn test_config_errors() {
+        let unknown_case = "[[transform]]\nkind = \"rename\"\ncase = \"Title Case\"";
+        assert!(matches!(
+            Pipeline::from_toml(unknown_case),
+            Err(ConfigError::Case(_))
+        ));
+
+        let unknown_kind = "[[transform]]\nkind = \"shuffle\"";
+        assert!(matches!(
+            Pipeline::from_toml(unknown_kind),
+            Err(ConfigError::Toml(_))
+        ));
+    }
+}
@@ -0,0 +1,284 @@
+//! Reading generated bundles back, locally or from a deployed service
+//!
+//! A bundle is an output directory written by [`Generator`]: the artifacts
+//! plus the fingerprint manifest. Comparing two bundles' manifests tells
+//! which artifacts differ without rendering or parsing anything, e.g. the
+//! local build against what a running service serves:
+//!
+//! ```no_run
+//! # #[cfg(feature = "http")]
+//! # async fn check() -> std::io::Result<()> {
+//! use schema_build::source::{FsBundle, HttpSource, compare};
+//!
+//! let local = FsBundle::new("target/schemas");
+//! let deployed = HttpSource::new("https://api.example.com/schemas/");
+//! for (artifact, change) in compare(&local, &deployed).await? {
+//!     println!("{artifact}: {change:?}");
+//! }
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! [`Generator`]: crate::Generator
+
+use crate::{MANIFEST_NAME, parse_manifest_entries};
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Somewhere a bundle of generated artifacts can be read from
+pub trait SchemaSource {
+    /// Fingerprint hash of each artifact, keyed by artifact path
+    fn fingerprints(&self) -> impl Future<Output = io::Result<BTreeMap<String, String>>> + Send;
+
+    /// Contents of `artifact`, or `None` if the bundle doesn't have it
+    fn artifact(&self, artifact: &str) -> impl Future<Output = io::Result<Option<String>>> + Send;
+}
+
+/// How an artifact differs between two bundles
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Change {
+    /// Only in the first bundle
+    Added,
+    /// Only in the second bundle
+    Removed,
+    /// In both, with different fingerprints
+    Changed,
+}
+
+/// Artifacts that differ between `ours` and `theirs`, by artifact path
+///
+/// Manifests from another schema-build version may hash identical schemas
+/// differently, so such artifacts can show up as changed.
+pub async fn compare(
+    ours: &impl SchemaSource,
+    theirs: &impl SchemaSource,
+) -> io::Result<BTreeMap<String, Change>> {
+    let ours = ours.fingerprints().await?;
+    let theirs = theirs.fingerprints().await?;
+
+    let mut changes = BTreeMap::new();
+    for (artifact, hash) in &ours {
+        match theirs.get(artifact) {
+            None => {
+                changes.insert(artifact.clone(), Change::Added);
+            }
+            Some(other) if other != hash => {
+                changes.insert(artifact.clone(), Change::Changed);
+            }
+            Some(_) => {}
+        }
+    }
+    for artifact in theirs.keys() {
+        if !ours.contains_key(artifact) {
+            changes.insert(artifact.clone(), Change::Removed);
+        }
+    }
+    Ok(changes)
+}
+
+/// A bundle in a local directory
+#[derive(Debug, Clone)]
+pub struct FsBundle {
+    dir: PathBuf,
+}
+
+impl FsBundle {
+    pub fn new(dir: impl AsRef<Path>) -> Self {
+        FsBundle {
+            dir: dir.as_ref().to_path_buf(),
+        }
+    }
+}
+
+impl SchemaSource for FsBundle {
+    async fn fingerprints(&self) -> io::Result<BTreeMap<String, String>> {
+        let manifest = tokio::fs::read_to_string(self.dir.join(MANIFEST_NAME)).await?;
+        Ok(parse_manifest_entries(&manifest))
+    }
+
+    async fn artifact(&self, artifact: &str) -> io::Result<Option<String>> {
+        match tokio::fs::read_to_string(self.dir.join(artifact)).await {
+            Ok(contents) => Ok(Some(contents)),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+}
+
+/// A bundle served over HTTP, with the manifest and artifacts at their
+/// bundle paths relative to `base_url`
+#[cfg(feature = "http")]
+#[derive(Debug, Clone)]
+pub struct HttpSource {
+    base_url: String,
+    client: reqwest::Client,
+}
+
+#[cfg(feature = "http")]
+impl HttpSource {
+    pub fn new(base_url: impl Into<String>) -> Self {
+        Self::with_client(base_url, reqwest::Client::new())
+    }
+
+    /// Use a preconfigured client, e.g. with auth headers or timeouts
+    pub fn with_client(base_url: impl Into<String>, client: reqwest::Client) -> Self {
+        let mut base_url = base_url.into();
+        if !base_url.ends_with('/') {
+            base_url.push('/');
+        }
+        HttpSource { base_url, client }
+    }
+
+    /// Body of `path`, or `None` on 404
+    async fn get(&self, path: &str) -> io::Result<Option<String>> {
+        let response = self
+            .client
+            .get(format!("{}{}", self.base_url, path))
+            .send()
+            .await
+            .map_err(io::Error::other)?;
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        let response = response.error_for_status().map_err(io::Error::other)?;
+        response.text().await.map(Some).map_err(io::Error::other)
+    }
+}
+
+#[cfg(feature = "http")]
+impl SchemaSource for HttpSource {
+    async fn fingerprints(&self) -> io::Result<BTreeMap<String, String>> {
+        let manifest = self.get(MANIFEST_NAME).await?.ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("no {} at {}", MANIFEST_NAME, self.base_url),
+            )
+        })?;
+        Ok(parse_manifest_entries(&manifest))
+    }
+
+    async fn artifact(&self, artifact: &str) -> io::Result<Option<String>> {
+        self.get(artifact).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Generator;
+    use schema::Schema;
+    use std::fs;
+
+    #[derive(Schema)]
+    #[allow(dead_code)]
+    struct User {
+        name: String,
+    }
+
+    #[derive(Schema)]
+    #[allow(dead_code)]
+    struct UserV2 {
+        name: String,
+        email: Option<String>,
+    }
+
+    fn bundle(name: &str, artifacts: &[(&str, schema::SchemaType)]) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("schema-source-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let mut generator = Generator::new(&dir).unwrap();
+        for (artifact, schema) in artifacts {
+            generator
+                .emit(artifact, schema, |schema| schema.fingerprint())
+                .unwrap();
+        }
+        generator.finish().unwrap();
+        dir
+    }
+
+    #[tokio::test]
+    async fn test_compare_fs_bundles() {
+        let ours = bundle(
+            "ours",
+            &[("user.txt", UserV2::schema()), ("new.txt", User::schema())],
+        );
+        let theirs = bundle(
+            "theirs",
+            &[("user.txt", User::schema()), ("old.txt", User::schema())],
+        );
+
+        let changes = compare(&FsBundle::new(&ours), &FsBundle::new(&theirs))
+            .await
+            .unwrap();
+        assert_eq!(
+            changes.into_iter().collect::<Vec<_>>(),
+            [
+                ("new.txt".to_string(), Change::Added),
+                ("old.txt".to_string(), Change::Removed),
+                ("user.txt".to_string(), Change::Changed),
+            ]
+        );
+
+        let source = FsBundle::new(&ours);
+        assert_eq!(
+            source.artifact("new.txt").await.unwrap(),
+            Some(User::schema().fingerprint())
+        );
+        assert_eq!(source.artifact("missing.txt").await.unwrap(), None);
+
+        fs::remove_dir_all(&ours).unwrap();
+        fs::remove_dir_all(&theirs).unwrap();
+    }
+
+    #[cfg(feature = "http")]
+    #[tokio::test]
+    async fn test_http_source() {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let dir = bundle("http", &[("user.txt", User::schema())]);
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        // Serves the bundle directory, one request per connection
+        let root = dir.clone();
+        tokio::spawn(async move {
+            loop {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                let mut request = vec![0; 4096];
+                let n = stream.read(&mut request).await.unwrap();
+                let request = String::from_utf8_lossy(&request[..n]);
+                let path = request.split(' ').nth(1).unwrap();
+                let path = path.trim_start_matches("/schemas/");
+                let response = match fs::read_to_string(root.join(path)) {
+                    Ok(body) => format!(
+                        "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                        body.len(),
+                        body
+                    ),
+                    Err(_) => {
+                        "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"
+                            .to_string()
+                    }
+                };
+                stream.write_all(response.as_bytes()).await.unwrap();
+            }
+        });
+
+        let source = HttpSource::new(format!("http://{}/schemas", addr));
+        assert!(
+            compare(&FsBundle::new(&dir), &source)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+        assert_eq!(
+            source.artifact("user.txt").await.unwrap(),
+            Some(User::schema().fingerprint())
+        );
+        assert_eq!(source.artifact("missing.txt").await.unwrap(), None);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
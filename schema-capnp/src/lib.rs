@@ -0,0 +1,714 @@
+//! Cap'n Proto schemas from schemas
+//!
+//! The root and every named type nested in it become a top-level `struct`
+//! (or `enum`), each after the ones it uses. Arrays and sets are `List(T)`,
+//! maps a list of generated `Entry` structs, and variants a struct holding
+//! one unnamed `union`. Anonymous types that need a declaration are named
+//! after where they appear (`EventMove`).
+//!
+//! Ordinals follow declaration order, so new fields belong at the end, as
+//! Cap'n Proto itself requires. Text, lists and structs are pointers and can
+//! be null, so an optional one is a plain field; an optional number, `Bool`
+//! or enum is a named union of `unset :Void` and `value`, which keeps "absent"
+//! apart from zero.
+//!
+//! The file ID is derived from the root's name, so regenerating keeps it;
+//! set one with [`CapnpOptions::file_id`] when the file already has one.
+//! Member names are camelCase, as Cap'n Proto requires, so its JSON codec
+//! won't read serde's field names back; the binary encoding is the goal.
+
+use schema::case::Case;
+use schema::{
+    GenerateOptions, IntegerKind, NumberKind, RefPreference, Schema, SchemaType, TypeKind,
+};
+use serde_json::Value;
+use std::collections::BTreeSet;
+use std::fmt;
+
+/// Options for the generated `.capnp` file
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CapnpOptions {
+    /// `@0x...` file ID; derived from the root's name when unset
+    pub file_id: Option<u64>,
+    pub generate: GenerateOptions,
+}
+
+impl CapnpOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Keep the ID of an existing file; Cap'n Proto requires the high bit set
+    pub fn file_id(mut self, file_id: u64) -> Self {
+        self.file_id = Some(file_id);
+        self
+    }
+
+    /// Bound the schema before it is written
+    pub fn generate(mut self, generate: GenerateOptions) -> Self {
+        self.generate = generate;
+        self
+    }
+}
+
+/// Why a schema has no `.capnp` file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CapnpError {
+    /// The file ID given doesn't have its high bit set
+    InvalidFileId(u64),
+}
+
+impl fmt::Display for CapnpError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CapnpError::InvalidFileId(id) => {
+                write!(f, "file ID {:#018x} does not have its high bit set", id)
+            }
+        }
+    }
+}
+
+impl std::error::Error for CapnpError {}
+
+/// The `.capnp` file for `T` and the named types it contains
+pub fn to_capnp<T: Schema>() -> Result<String, CapnpError> {
+    schema_type_to_capnp(&T::schema(), T::type_name())
+}
+
+/// The `.capnp` file for `T` and the named types it contains, with `options`
+pub fn to_capnp_with_options<T: Schema>(options: &CapnpOptions) -> Result<String, CapnpError> {
+    schema_type_to_capnp_with_options(&T::schema(), T::type_name(), options)
+}
+
+/// The `.capnp` file for a SchemaType, named `type_name` (or the schema's own
+/// name, or `Root`)
+pub fn schema_type_to_capnp(
+    schema: &SchemaType,
+    type_name: Option<&str>,
+) -> Result<String, CapnpError> {
+    schema_type_to_capnp_with_options(schema, type_name, &CapnpOptions::default())
+}
+
+/// The `.capnp` file for a SchemaType, with `options`
+pub fn schema_type_to_capnp_with_options(
+    schema: &SchemaType,
+    type_name: Option<&str>,
+    options: &CapnpOptions,
+) -> Result<String, CapnpError> {
+    let schema = options.generate.apply(schema);
+    let mut module = Module::default();
+    let ident = module.unique(&type_ident(
+        type_name.or(schema.name.as_deref()).unwrap_or("Root"),
+    ));
+    let file_id = match options.file_id {
+        Some(id) if id & HIGH_BIT == 0 => return Err(CapnpError::InvalidFileId(id)),
+        Some(id) => id,
+        None => file_id(&ident),
+    };
+    if let Some(name) = &schema.name {
+        module.named.push(Named {
+            name: name.clone(),
+            shape: shape(&schema),
+            ident: ident.clone(),
+            first: schema.clone(),
+        });
+    }
+    module.declare(&ident, &schema);
+
+    let mut output = format!("@{:#018x};\n", file_id);
+    for declaration in &module.output {
+        output.push('\n');
+        output.push_str(declaration);
+    }
+    Ok(output)
+}
+
+/// Bit every Cap'n Proto ID has set
+const HIGH_BIT: u64 = 1 << 63;
+
+/// A file ID that stays the same for the same root: the FNV-1a hash of its
+/// name, with the high bit set
+fn file_id(root: &str) -> u64 {
+    let hash = root.bytes().fold(0xcbf2_9ce4_8422_2325_u64, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    });
+    hash | HIGH_BIT
+}
+
+/// A named type that has a declaration
+struct Named {
+    name: String,
+    /// Fingerprint without use-site annotations
+    shape: String,
+    ident: String,
+    /// The occurrence the declaration was written from
+    first: SchemaType,
+}
+
+/// A field's type
+struct Capnp {
+    ty: String,
+    /// Whether the type is stored behind a pointer, which can be null
+    pointer: bool,
+}
+
+impl Capnp {
+    fn data(ty: impl Into<String>) -> Self {
+        Capnp {
+            ty: ty.into(),
+            pointer: false,
+        }
+    }
+
+    fn pointer(ty: impl Into<String>) -> Self {
+        Capnp {
+            ty: ty.into(),
+            pointer: true,
+        }
+    }
+}
+
+/// One member of a struct, before numbering
+struct Field {
+    /// Wire name
+    name: String,
+    capnp: Capnp,
+    optional: bool,
+    default: Option<String>,
+    docs: Option<String>,
+}
+
+impl Field {
+    fn new(name: &str, capnp: Capnp) -> Self {
+        Field {
+            name: name.to_string(),
+            capnp,
+            optional: false,
+            default: None,
+            docs: None,
+        }
+    }
+}
+
+#[derive(Default)]
+struct Module {
+    named: Vec<Named>,
+    /// Every struct and enum name in use
+    taken: BTreeSet<String>,
+    /// Declarations, each after the ones it uses
+    output: Vec<String>,
+}
+
+impl Module {
+    /// `base`, or `base2`, `base3`, ... if something already has the name
+    fn unique(&mut self, base: &str) -> String {
+        let ident = (1..)
+            .map(|n| match n {
+                1 => base.to_string(),
+                n => format!("{base}{n}"),
+            })
+            .find(|ident| !self.taken.contains(ident))
+            .unwrap();
+        self.taken.insert(ident.clone());
+        ident
+    }
+
+    /// The struct or enum `ident` for `schema`; types that are neither are
+    /// wrapped in a struct with one `value` field
+    fn declare(&mut self, ident: &str, schema: &SchemaType) {
+        if declares(schema) {
+            return self.define(ident, schema);
+        }
+        let capnp = self.expression(schema, &format!("{ident}Value"));
+        let field = Field::new("value", capnp);
+        self.structure(ident, schema.docs(), vec![field], false)
+    }
+
+    /// The declaration for a kind that has one of its own
+    fn define(&mut self, ident: &str, schema: &SchemaType) {
+        match &schema.kind {
+            TypeKind::Object { required, .. } => {
+                let mut fields = vec![];
+                for (name, property) in schema.ordered_properties() {
+                    let mut field = self.field(ident, name, property);
+                    // A default stands in for an absent value
+                    field.optional =
+                        !required.iter().any(|r| r == name) && property.default.is_none();
+                    fields.push(field);
+                }
+                self.structure(ident, schema.docs(), fields, false)
+            }
+            TypeKind::Enum { variants } => {
+                let mut lines = vec![];
+                for (i, name) in member_names(variants).iter().enumerate() {
+                    lines.push(format!("  {name} @{i};"));
+                }
+                let mut declaration = format!("enum {ident} {{\n");
+                declaration.push_str(&comment(schema.docs().as_deref(), "  "));
+                declaration.push_str(&lines.join("\n"));
+                declaration.push_str("\n}\n");
+                self.output.push(declaration);
+            }
+            TypeKind::TaggedUnion {
+                tag_field,
+                tag_variants,
+                data_fields,
+            } => {
+                let tag = self.unique(&format!("{ident}{}", type_ident(tag_field)));
+                let tag_schema = SchemaType {
+                    kind: TypeKind::Enum {
+                        variants: tag_variants.clone(),
+                    },
+                    ..Default::default()
+                };
+                self.define(&tag, &tag_schema);
+
+                let mut fields = vec![Field::new(tag_field, Capnp::data(tag))];
+                let mut names: Vec<&String> = data_fields.keys().collect();
+                names.sort();
+                for name in names {
+                    let mut field = self.field(ident, name, &data_fields[name]);
+                    field.optional = true;
+                    fields.push(field);
+                }
+                self.structure(ident, schema.docs(), fields, false)
+            }
+            TypeKind::Variant { cases } => {
+                let mut fields = vec![];
+                for case in cases {
+                    let capnp = match &case.data {
+                        Some(data) => {
+                            self.reference(data, &format!("{ident}{}", type_ident(&case.name)))
+                        }
+                        None => Capnp::data("Void"),
+                    };
+                    let mut field = Field::new(&case.name, capnp);
+                    field.docs = case.description.clone();
+                    fields.push(field);
+                }
+                self.structure(ident, schema.docs(), fields, true)
+            }
+            TypeKind::Result { ok, err } => {
+                let ok = self.reference(ok, &format!("{ident}Ok"));
+                let err = self.reference(err, &format!("{ident}Error"));
+                let fields = vec![Field::new("ok", ok), Field::new("error", err)];
+                self.structure(ident, schema.docs(), fields, true)
+            }
+            TypeKind::Tuple { fields: items } => {
+                let mut fields = vec![];
+                for (i, item) in items.iter().enumerate() {
+                    let capnp = self.reference(item, &format!("{ident}Item{i}"));
+                    fields.push(Field::new(&format!("item{i}"), capnp));
+                }
+                self.structure(ident, schema.docs(), fields, false)
+            }
+            _ => unreachable!("declares() covers the kinds defined here"),
+        }
+    }
+
+    /// A nested schema: its declaration's name if it is a named type,
+    /// otherwise written in place, with anonymous declarations named after
+    /// `context`
+    fn reference(&mut self, schema: &SchemaType, context: &str) -> Capnp {
+        let Some(name) = &schema.name else {
+            return self.expression(schema, context);
+        };
+        if matches!(schema.kind, TypeKind::Ref { .. })
+            || schema.ref_preference() == Some(RefPreference::Inline)
+        {
+            return self.expression(schema, context);
+        }
+
+        let shape = shape(schema);
+        if let Some(named) = self
+            .named
+            .iter()
+            .find(|named| named.name == *name && named.shape == shape)
+        {
+            return declared(named);
+        }
+
+        // A different type may already have taken the name
+        let ident = self.unique(&type_ident(name));
+        self.named.push(Named {
+            name: name.clone(),
+            shape,
+            ident: ident.clone(),
+            first: schema.clone(),
+        });
+        self.declare(&ident, schema);
+        declared(self.named.last().expect("just pushed"))
+    }
+
+    fn expression(&mut self, schema: &SchemaType, context: &str) -> Capnp {
+        if declares(schema) {
+            let ident = self.unique(context);
+            self.define(&ident, schema);
+            return match schema.kind {
+                TypeKind::Enum { .. } => Capnp::data(ident),
+                _ => Capnp::pointer(ident),
+            };
+        }
+
+        match &schema.kind {
+            TypeKind::String => Capnp::pointer("Text"),
+            TypeKind::Integer(kind) => Capnp::data(integer(kind)),
+            TypeKind::Number(NumberKind::F32) => Capnp::data("Float32"),
+            TypeKind::Number(NumberKind::F64) => Capnp::data("Float64"),
+            TypeKind::Boolean => Capnp::data("Bool"),
+            TypeKind::Null => Capnp::data("Void"),
+            TypeKind::Array { items } | TypeKind::Set { items, .. } => {
+                let items = self.reference(items, &format!("{context}Item"));
+                Capnp::pointer(format!("List({})", items.ty))
+            }
+            // Cap'n Proto has no maps, so list the entries
+            TypeKind::Map { key, value, .. } => {
+                let entry = self.unique(&format!("{context}Entry"));
+                let key = self.reference(key, &format!("{entry}Key"));
+                let value = self.reference(value, &format!("{entry}Value"));
+                let fields = vec![Field::new("key", key), Field::new("value", value)];
+                self.structure(&entry, None, fields, false);
+                Capnp::pointer(format!("List({entry})"))
+            }
+            TypeKind::Ref { name } => match self.named.iter().find(|named| named.name == *name) {
+                Some(named) => declared(named),
+                None => Capnp::pointer(type_ident(name)),
+            },
+            _ => unreachable!("declares() covers the remaining kinds"),
+        }
+    }
+
+    /// A field of the struct `owner`
+    fn field(&mut self, owner: &str, name: &str, schema: &SchemaType) -> Field {
+        let capnp = self.reference(schema, &format!("{owner}{}", type_ident(name)));
+        let mut field = Field::new(name, capnp);
+        field.default = default(schema);
+        field.docs = self.use_site_docs(schema);
+        field
+    }
+
+    /// Write `struct ident`, numbering its members in order; with `union`,
+    /// they are the members of its unnamed union
+    fn structure(&mut self, ident: &str, docs: Option<String>, fields: Vec<Field>, union: bool) {
+        // An unnamed union needs two members
+        let union = union && fields.len() > 1;
+        let indent = if union { "    " } else { "  " };
+        let mut ordinal = 0;
+        let mut lines = vec![];
+        let names = member_names(fields.iter().map(|field| &field.name));
+        for (field, name) in fields.iter().zip(names) {
+            let docs = comment(field.docs.as_deref(), indent);
+            if field.optional && !field.capnp.pointer && field.capnp.ty != "Void" {
+                lines.push(format!(
+                    "{indent}{name} :union {{\n{indent}  unset @{} :Void;\n{indent}  value @{} :{};\n{indent}}}\n{docs}",
+                    ordinal,
+                    ordinal + 1,
+                    field.capnp.ty
+                ));
+                ordinal += 2;
+                continue;
+            }
+            let default = match &field.default {
+                Some(default) => format!(" = {default}"),
+                None => String::new(),
+            };
+            lines.push(format!(
+                "{indent}{name} @{ordinal} :{}{default};\n{docs}",
+                field.capnp.ty
+            ));
+            ordinal += 1;
+        }
+
+        let mut body: String = lines.concat();
+        if union {
+            body = format!("  union {{\n{body}  }}\n");
+        }
+        let docs = comment(docs.as_deref(), "  ");
+        match (docs.is_empty(), body.is_empty()) {
+            (true, true) => self.output.push(format!("struct {ident} {{}}\n")),
+            _ => self
+                .output
+                .push(format!("struct {ident} {{\n{docs}{body}}}\n")),
+        }
+    }
+
+    /// The docs for a field, unless its type's declaration already has them
+    fn use_site_docs(&self, schema: &SchemaType) -> Option<String> {
+        schema.docs().filter(|docs| {
+            let declared = schema.name.as_ref().and_then(|name| {
+                self.named
+                    .iter()
+                    .find(|named| named.name == *name && named.shape == shape(schema))
+            });
+            declared.is_none_or(|named| named.first.docs().as_ref() != Some(docs))
+        })
+    }
+}
+
+/// The type of a field holding a declared type: a struct unless it's an enum
+fn declared(named: &Named) -> Capnp {
+    match named.first.kind {
+        TypeKind::Enum { .. } => Capnp::data(named.ident.clone()),
+        _ => Capnp::pointer(named.ident.clone()),
+    }
+}
+
+/// Whether `schema` is written as a struct or enum of its own
+fn declares(schema: &SchemaType) -> bool {
+    matches!(
+        schema.kind,
+        TypeKind::Object { .. }
+            | TypeKind::Enum { .. }
+            | TypeKind::TaggedUnion { .. }
+            | TypeKind::Variant { .. }
+            | TypeKind::Result { .. }
+            | TypeKind::Tuple { .. }
+    )
+}
+
+/// The `= value` literal for a scalar field's default
+fn default(schema: &SchemaType) -> Option<String> {
+    match (&schema.kind, schema.default.as_ref()?) {
+        (TypeKind::Boolean, Value::Bool(b)) => Some(b.to_string()),
+        (TypeKind::Integer(_), Value::Number(n)) if !n.is_f64() => Some(n.to_string()),
+        (TypeKind::Number(_), Value::Number(n)) => Some(n.to_string()),
+        (TypeKind::String, default @ Value::String(_)) => Some(default.to_string()),
+        (TypeKind::Enum { variants }, Value::String(s)) => {
+            let i = variants.iter().position(|variant| variant == s)?;
+            Some(member_names(variants).swap_remove(i))
+        }
+        _ => None,
+    }
+}
+
+fn integer(kind: &IntegerKind) -> &'static str {
+    match kind {
+        IntegerKind::I32 => "Int32",
+        IntegerKind::I64 => "Int64",
+        IntegerKind::U8 => "UInt8",
+        IntegerKind::U32 => "UInt32",
+        IntegerKind::U64 | IntegerKind::Usize => "UInt64",
+    }
+}
+
+/// `# ` lines for `docs` at `indent`, ending in a newline; Cap'n Proto puts
+/// doc comments after what they describe
+fn comment(docs: Option<&str>, indent: &str) -> String {
+    let Some(docs) = docs else {
+        return String::new();
+    };
+    docs.lines()
+        .map(|line| match line {
+            "" => format!("{indent}#\n"),
+            line => format!("{indent}# {line}\n"),
+        })
+        .collect()
+}
+
+/// A struct or enum name: PascalCase letters and digits, starting with a
+/// capital
+fn type_ident(name: &str) -> String {
+    let ident: String = Case::Pascal
+        .apply(name)
+        .chars()
+        .filter(char::is_ascii_alphanumeric)
+        .collect();
+    match ident.starts_with(|c: char| c.is_ascii_uppercase()) {
+        true => ident,
+        false => format!("T{ident}"),
+    }
+}
+
+/// A field or enumerant name: camelCase letters and digits, starting with a
+/// lowercase letter
+fn member_name(name: &str) -> String {
+    let ident: String = Case::Camel
+        .apply(name)
+        .chars()
+        .filter(char::is_ascii_alphanumeric)
+        .collect();
+    match ident.starts_with(|c: char| c.is_ascii_lowercase()) {
+        true => ident,
+        false => format!("field{ident}"),
+    }
+}
+
+/// The member names of one struct or enum, numbered where two wire names
+/// have the same camelCase
+fn member_names<'a>(names: impl IntoIterator<Item = &'a String>) -> Vec<String> {
+    let mut taken = BTreeSet::new();
+    names
+        .into_iter()
+        .map(|name| {
+            let base = member_name(name);
+            (1..)
+                .map(|n| match n {
+                    1 => base.clone(),
+                    n => format!("{base}{n}"),
+                })
+                .find(|name| taken.insert(name.clone()))
+                .unwrap()
+        })
+        .collect()
+}
+
+/// What makes two occurrences of a named type the same declaration: its
+/// fingerprint without the annotations a field adds to it
+fn shape(schema: &SchemaType) -> String {
+    let mut bare = schema.clone();
+    bare.title = None;
+    bare.description = None;
+    bare.default = None;
+    for key in [
+        schema::DEPRECATED,
+        schema::READ_ONLY,
+        schema::WRITE_ONLY,
+        schema::SINCE,
+        schema::REF_PREFERENCE,
+    ] {
+        bare.extensions.remove(key);
+    }
+    bare.fingerprint()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_optional_scalars_take_two_ordinals() {
+        #[derive(Schema)]
+        #[allow(dead_code)]
+        enum Unit {
+            Celsius,
+            Fahrenheit,
+            // The same enumerant as Celsius in camelCase
+            #[schema(rename = "CELSIUS")]
+            LegacyCelsius,
+        }
+
+        fn default_label() -> String {
+            "raw \"feed\"".to_string()
+        }
+
+        fn default_samples() -> u32 {
+            10
+        }
+
+        /// One sensor reading
+        #[derive(Schema)]
+        #[allow(dead_code)]
+        struct Reading {
+            sensor_id: Option<String>,
+            value: Option<f32>,
+            unit: Option<Unit>,
+            #[schema(default = "default_label")]
+            label: String,
+            #[schema(default = "default_samples")]
+            samples: u32,
+            #[schema(rename = "2nd")]
+            second: Option<i64>,
+            history: Vec<f32>,
+            // The same member as sensor_id in camelCase
+            #[schema(rename = "sensorId")]
+            legacy_sensor: String,
+        }
+
+        let capnp =
+            to_capnp_with_options::<Reading>(&CapnpOptions::new().file_id(0xa1b2_c3d4_e5f6_0718));
+        assert_eq!(
+            capnp.unwrap(),
+            r#"@0xa1b2c3d4e5f60718;
+
+enum Unit {
+  celsius @0;
+  fahrenheit @1;
+  celsius2 @2;
+}
+
+struct Reading {
+  # One sensor reading
+  sensorId @0 :Text;
+  value :union {
+    unset @1 :Void;
+    value @2 :Float32;
+  }
+  unit :union {
+    unset @3 :Void;
+    value @4 :Unit;
+  }
+  label @5 :Text = "raw \"feed\"";
+  samples @6 :UInt32 = 10;
+  field2nd :union {
+    unset @7 :Void;
+    value @8 :Int64;
+  }
+  history @9 :List(Float32);
+  sensorId2 @10 :Text;
+}
+"#
+        );
+
+        // A default names the enumerant as numbered
+        let mut unit = Unit::schema();
+        unit.default = Some(Value::from("CELSIUS"));
+        assert_eq!(default(&unit).unwrap(), "celsius2");
+    }
+
+    #[test]
+    fn test_variants_become_unions() {
+        #[derive(Schema)]
+        #[allow(dead_code)]
+        enum Event {
+            Start,
+            Move { x: i64 },
+            Batch(Vec<Vec<u32>>),
+        }
+
+        let capnp = to_capnp::<Event>().unwrap();
+        assert_eq!(
+            capnp.split_once('\n').unwrap().1,
+            r#"
+struct EventMove {
+  x @0 :Int64;
+}
+
+struct Event {
+  union {
+    start @0 :Void;
+    move @1 :EventMove;
+    batch @2 :List(List(UInt32));
+  }
+}
+"#
+        );
+        // Derived from the name, so it stays put when the type changes
+        assert_eq!(
+            capnp.split_once('\n').unwrap().0,
+            format!("@{:#018x};", file_id("Event"))
+        );
+
+        // An unnamed union needs at least two members
+        #[derive(Schema)]
+        #[allow(dead_code)]
+        enum Wrapped {
+            Text(String),
+        }
+
+        let capnp = to_capnp::<Wrapped>().unwrap();
+        assert_eq!(
+            capnp.split_once('\n').unwrap().1,
+            "\nstruct Wrapped {\n  text @0 :Text;\n}\n"
+        );
+    }
+
+    #[test]
+    fn test_file_id_needs_high_bit() {
+        let options = CapnpOptions::new().file_id(0x1234);
+        assert_eq!(
+            to_capnp_with_options::<String>(&options).unwrap_err(),
+            CapnpError::InvalidFileId(0x1234)
+        );
+        assert!(file_id("Anything") & HIGH_BIT != 0);
+    }
+}
@@ -0,0 +1,371 @@
+//! ClickHouse `CREATE TABLE` statements from schemas
+//!
+//! ClickHouse's column types are richer and stricter than the generic SQL
+//! backend's, so it has its own: optional scalars are `Nullable(T)`, enums
+//! `LowCardinality(String)`, nested objects named `Tuple`s, lists of objects
+//! `Nested` columns, and maps `Map(K, V)`. Composites can't be `Nullable`, so
+//! an absent list, map or object reads back as empty. Variants, results and
+//! other values with no column type are stored as their JSON encoding in a
+//! `String`.
+//!
+//! The table uses `MergeTree` ordered by `tuple()` unless
+//! [`ClickHouseOptions::engine`] and [`ClickHouseOptions::order_by`] say
+//! otherwise; doc comments become table and column comments.
+
+use schema::case::Case;
+use schema::{GenerateOptions, IntegerKind, NumberKind, Schema, SchemaType, TypeKind};
+use serde_json::Value;
+use std::fmt;
+
+/// Options for the generated DDL
+#[derive(Debug, Clone)]
+pub struct ClickHouseOptions {
+    /// Table engine, e.g. `ReplacingMergeTree(version)`
+    pub engine: String,
+    /// Sorting key columns, in order; none sorts by `tuple()`
+    pub order_by: Vec<String>,
+    pub generate: GenerateOptions,
+}
+
+impl ClickHouseOptions {
+    pub fn new() -> Self {
+        ClickHouseOptions {
+            engine: "MergeTree".to_string(),
+            order_by: vec![],
+            generate: GenerateOptions::default(),
+        }
+    }
+
+    pub fn engine(mut self, engine: &str) -> Self {
+        self.engine = engine.to_string();
+        self
+    }
+
+    /// Sort (and, for `MergeTree`s, index) rows by these top-level columns
+    pub fn order_by(mut self, columns: &[&str]) -> Self {
+        self.order_by = columns.iter().map(|column| column.to_string()).collect();
+        self
+    }
+
+    /// Bound the schema before it is written
+    pub fn generate(mut self, generate: GenerateOptions) -> Self {
+        self.generate = generate;
+        self
+    }
+}
+
+impl Default for ClickHouseOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Why a schema has no table
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ClickHouseError {
+    /// The schema is not an object, so it has no columns
+    NotAnObject,
+    /// An `order_by` column that isn't one of the table's
+    UnknownSortColumn(String),
+    /// An `order_by` column that is `Nullable`, which sorting keys reject
+    NullableSortColumn(String),
+}
+
+impl fmt::Display for ClickHouseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ClickHouseError::NotAnObject => {
+                write!(f, "only object schemas map to ClickHouse tables")
+            }
+            ClickHouseError::UnknownSortColumn(column) => {
+                write!(f, "sorting key column {:?} is not in the table", column)
+            }
+            ClickHouseError::NullableSortColumn(column) => {
+                write!(f, "sorting key column {:?} is nullable", column)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ClickHouseError {}
+
+/// The DDL for a table of `T`, named after `T` in snake_case
+pub fn to_create_table<T: Schema>(options: &ClickHouseOptions) -> Result<String, ClickHouseError> {
+    let table = Case::Snake.apply(T::type_name().unwrap_or("root"));
+    schema_type_to_create_table(&T::schema(), &table, options)
+}
+
+/// The DDL for a table named `table` holding an object SchemaType
+pub fn schema_type_to_create_table(
+    schema: &SchemaType,
+    table: &str,
+    options: &ClickHouseOptions,
+) -> Result<String, ClickHouseError> {
+    let schema = options.generate.apply(schema);
+    let TypeKind::Object { required, .. } = &schema.kind else {
+        return Err(ClickHouseError::NotAnObject);
+    };
+
+    let mut columns = vec![];
+    for (name, property) in schema.ordered_properties() {
+        let optional = is_optional(required, name, property);
+        let ty = match &property.kind {
+            // `Nested` is only allowed as a table column
+            TypeKind::Array { items } if matches!(items.kind, TypeKind::Object { .. }) => {
+                format!("Nested({})", elements(items))
+            }
+            _ => column_type(property, optional),
+        };
+        let mut line = format!("    {} {}", identifier(name), ty);
+        if let Some(default) = property.default.as_ref().and_then(literal) {
+            line.push_str(&format!(" DEFAULT {}", default));
+        }
+        if let Some(docs) = property.docs() {
+            line.push_str(&format!(" COMMENT {}", string(&docs)));
+        }
+        columns.push((name, ty, line));
+    }
+
+    for key in &options.order_by {
+        match columns.iter().find(|(name, ..)| *name == key) {
+            None => return Err(ClickHouseError::UnknownSortColumn(key.clone())),
+            Some((_, ty, _)) if ty.starts_with("Nullable(") => {
+                return Err(ClickHouseError::NullableSortColumn(key.clone()));
+            }
+            Some(_) => {}
+        }
+    }
+    let order_by = match &options.order_by[..] {
+        [] => "tuple()".to_string(),
+        [column] => identifier(column),
+        columns => {
+            let columns: Vec<String> = columns.iter().map(|c| identifier(c)).collect();
+            format!("({})", columns.join(", "))
+        }
+    };
+
+    let lines: Vec<String> = columns.into_iter().map(|(_, _, line)| line).collect();
+    let mut ddl = format!(
+        "CREATE TABLE {}\n(\n{}\n)\nENGINE = {}\nORDER BY {}",
+        identifier(table),
+        lines.join(",\n"),
+        options.engine,
+        order_by
+    );
+    if let Some(docs) = schema.docs() {
+        ddl.push_str(&format!("\nCOMMENT {}", string(&docs)));
+    }
+    ddl.push_str(";\n");
+    Ok(ddl)
+}
+
+/// A column or element type; `optional` values are `Nullable` where
+/// ClickHouse allows it
+fn column_type(schema: &SchemaType, optional: bool) -> String {
+    let nullable = |ty: String| match optional {
+        true => format!("Nullable({})", ty),
+        false => ty,
+    };
+    match &schema.kind {
+        TypeKind::String => nullable(string_type(schema).to_string()),
+        TypeKind::Integer(kind) => nullable(integer(kind).to_string()),
+        TypeKind::Number(NumberKind::F32) => nullable("Float32".to_string()),
+        TypeKind::Number(NumberKind::F64) => nullable("Float64".to_string()),
+        TypeKind::Boolean => nullable("Bool".to_string()),
+        // `Nullable` goes inside `LowCardinality`
+        TypeKind::Enum { .. } => format!("LowCardinality({})", nullable("String".to_string())),
+        TypeKind::Object { .. } => format!("Tuple({})", elements(schema)),
+        TypeKind::Array { items } | TypeKind::Set { items, .. } => {
+            format!("Array({})", column_type(items, false))
+        }
+        TypeKind::Map { key, value, .. } => format!(
+            "Map({}, {})",
+            column_type(key, false),
+            column_type(value, false)
+        ),
+        TypeKind::Tuple { fields } if !fields.is_empty() => {
+            let fields: Vec<String> = fields.iter().map(|f| column_type(f, false)).collect();
+            format!("Tuple({})", fields.join(", "))
+        }
+        // Stored as JSON text
+        _ => nullable("String".to_string()),
+    }
+}
+
+/// `name Type` pairs for a named `Tuple` or `Nested`
+fn elements(object: &SchemaType) -> String {
+    let TypeKind::Object { required, .. } = &object.kind else {
+        return String::new();
+    };
+    let elements: Vec<String> = object
+        .ordered_properties()
+        .into_iter()
+        .map(|(name, property)| {
+            let optional = is_optional(required, name, property);
+            format!("{} {}", identifier(name), column_type(property, optional))
+        })
+        .collect();
+    elements.join(", ")
+}
+
+/// Whether a property can be absent; defaulted ones are filled in instead
+fn is_optional(required: &[String], name: &str, property: &SchemaType) -> bool {
+    !required.iter().any(|r| r == name) && property.default.is_none()
+}
+
+fn string_type(schema: &SchemaType) -> &'static str {
+    match schema.constraints.format.as_deref() {
+        Some("uuid") => "UUID",
+        Some("date") => "Date32",
+        Some("date-time") => "DateTime64(3)",
+        Some("ipv4") => "IPv4",
+        Some("ipv6") => "IPv6",
+        _ => "String",
+    }
+}
+
+fn integer(kind: &IntegerKind) -> &'static str {
+    match kind {
+        IntegerKind::I32 => "Int32",
+        IntegerKind::I64 => "Int64",
+        IntegerKind::U8 => "UInt8",
+        IntegerKind::U32 => "UInt32",
+        IntegerKind::U64 | IntegerKind::Usize => "UInt64",
+    }
+}
+
+/// A scalar default as a literal; composite defaults are left to the type's
+/// own empty value
+fn literal(value: &Value) -> Option<String> {
+    match value {
+        Value::String(s) => Some(string(s)),
+        Value::Number(n) => Some(n.to_string()),
+        Value::Bool(b) => Some(b.to_string()),
+        _ => None,
+    }
+}
+
+/// A string literal
+fn string(value: &str) -> String {
+    format!("'{}'", value.replace('\\', "\\\\").replace('\'', "\\'"))
+}
+
+/// A table, column or element name, backquoted unless it's a plain word
+fn identifier(name: &str) -> String {
+    let plain = name.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+    match plain {
+        true => name.to_string(),
+        false => format!("`{}`", name.replace('\\', "\\\\").replace('`', "\\`")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[derive(Schema)]
+    #[allow(dead_code)]
+    enum Status {
+        Pending,
+        Shipped,
+    }
+
+    #[derive(Schema)]
+    #[allow(dead_code)]
+    struct Address {
+        city: String,
+        zip: Option<String>,
+    }
+
+    #[derive(Schema)]
+    #[allow(dead_code)]
+    struct LineItem {
+        sku: String,
+        quantity: u32,
+    }
+
+    #[derive(Schema)]
+    #[allow(dead_code)]
+    enum Payment {
+        Card { last4: String },
+        Cash,
+    }
+
+    /// An order placed in the shop
+    #[derive(Schema)]
+    #[allow(dead_code)]
+    struct Purchase {
+        id: String,
+        /// When the order was placed
+        placed_at: String,
+        status: Status,
+        coupon: Option<Status>,
+        note: Option<String>,
+        #[schema(default)]
+        priority: u8,
+        ship_to: Address,
+        items: Vec<LineItem>,
+        tags: Vec<String>,
+        totals: HashMap<String, f64>,
+        payment: Payment,
+    }
+
+    #[test]
+    fn test_create_table() {
+        let options = ClickHouseOptions::new()
+            .engine("ReplacingMergeTree")
+            .order_by(&["status", "placed_at"]);
+        let mut schema = Purchase::schema();
+        if let TypeKind::Object { properties, .. } = &mut schema.kind {
+            for (name, format) in [("id", "uuid"), ("placed_at", "date-time")] {
+                properties.get_mut(name).unwrap().constraints.format = Some(format.to_string());
+            }
+        }
+        assert_eq!(
+            schema_type_to_create_table(&schema, "purchase", &options).unwrap(),
+            "CREATE TABLE purchase
+(
+    id UUID,
+    placed_at DateTime64(3) COMMENT 'When the order was placed',
+    status LowCardinality(String),
+    coupon LowCardinality(Nullable(String)),
+    note Nullable(String),
+    priority UInt8 DEFAULT 0,
+    ship_to Tuple(city String, zip Nullable(String)),
+    items Nested(sku String, quantity UInt32),
+    tags Array(String),
+    totals Map(String, Float64) COMMENT 'Unordered map/dictionary of key-value pairs',
+    payment String
+)
+ENGINE = ReplacingMergeTree
+ORDER BY (status, placed_at)
+COMMENT 'An order placed in the shop';
+"
+        );
+    }
+
+    #[test]
+    fn test_default_sorting_key() {
+        let ddl = to_create_table::<Address>(&ClickHouseOptions::new()).unwrap();
+        assert!(ddl.ends_with("ENGINE = MergeTree\nORDER BY tuple();\n"));
+    }
+
+    #[test]
+    fn test_errors() {
+        let options = ClickHouseOptions::new();
+        assert_eq!(
+            to_create_table::<Vec<String>>(&options),
+            Err(ClickHouseError::NotAnObject)
+        );
+        assert_eq!(
+            to_create_table::<Purchase>(&options.clone().order_by(&["missing"])),
+            Err(ClickHouseError::UnknownSortColumn("missing".to_string()))
+        );
+        assert_eq!(
+            to_create_table::<Purchase>(&options.order_by(&["note"])),
+            Err(ClickHouseError::NullableSortColumn("note".to_string()))
+        );
+    }
+}
@@ -0,0 +1,682 @@
+//! Conformance matrix for schema backends
+//!
+//! [`samples`] builds one schema for every way the `TypeKind`s can nest, up
+//! to a depth, and [`run`] feeds each one to every backend in [`backends`].
+//! Panics are always failures; JSON output is checked against the JSON
+//! Schema 2020-12 meta-schema and WIT output is parsed by `wit-parser`.
+//!
+//! ```no_run
+//! let report = schema_conformance_suite::run(2);
+//! println!("{}", report);
+//! assert!(report.panics().is_empty());
+//! ```
+//!
+//! New `TypeKind`s go in [`leaves`] or [`wrap`], new backends in
+//! [`backends`]; the matrix then covers them automatically.
+
+use schema::{IntegerKind, NumberKind, SchemaType, Tagging, TypeKind, VariantCase};
+use serde_json::{Value, json};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::panic::{AssertUnwindSafe, catch_unwind};
+
+/// Name of the definition every `Ref` leaf points at
+const REF_TARGET: &str = "Node";
+
+/// A generated schema and a label describing its nesting
+#[derive(Debug, Clone)]
+pub struct Sample {
+    /// e.g. `array<object<string>>`
+    pub label: String,
+    pub schema: SchemaType,
+}
+
+impl Sample {
+    /// The outermost kind, e.g. `array` for `array<object<string>>`
+    pub fn kind(&self) -> &str {
+        self.label.split('<').next().unwrap_or_default()
+    }
+}
+
+/// What a backend made of a sample
+#[derive(Debug, Clone, PartialEq)]
+pub enum Outcome {
+    /// The output was produced and, where a checker exists, is valid
+    Valid,
+    /// The backend rejected the schema with an error, which is allowed
+    Unsupported(String),
+    /// The output failed validation
+    Invalid(String),
+    /// The backend panicked
+    Panicked(String),
+}
+
+/// A backend under test
+pub struct Backend {
+    pub name: &'static str,
+    pub check: fn(&SchemaType) -> Outcome,
+}
+
+/// Every backend in the workspace
+pub fn backends() -> Vec<Backend> {
+    vec![
+        Backend {
+            name: "anthropic",
+            check: |schema| json_schema(&schema_anthropic::to_anthropic_schema(schema)),
+        },
+        Backend {
+            name: "gemini",
+            check: |schema| {
+                schema_gemini::to_gemini_schema(schema);
+                Outcome::Valid
+            },
+        },
+        Backend {
+            name: "openapi",
+            check: |schema| json_schema(&schema_openapi::schema_type_to_openapi(schema)),
+        },
+        Backend {
+            name: "k8s",
+            check: |schema| json_schema(&schema_k8s::schema_type_to_structural(schema)),
+        },
+        Backend {
+            name: "jsonschema",
+            check: |schema| json_schema(&schema_jsonschema::schema_type_to_json_schema(schema)),
+        },
+        Backend {
+            name: "jsonschema-import",
+            check: |schema| {
+                let document = schema_jsonschema::schema_type_to_json_schema(schema);
+                match schema_jsonschema::parse(&document) {
+                    Ok(parsed)
+                        if schema_jsonschema::schema_type_to_json_schema(&parsed) == document =>
+                    {
+                        Outcome::Valid
+                    }
+                    Ok(_) => Outcome::Invalid("document changed after a round trip".to_string()),
+                    Err(e) => Outcome::Unsupported(e.to_string()),
+                }
+            },
+        },
+        Backend {
+            name: "mcp",
+            check: |schema| match schema_mcp::Tool::from_schema("sample", schema.clone()).to_json()
+            {
+                Ok(tool) => json_schema(&tool["inputSchema"]),
+                Err(e) => Outcome::Unsupported(e.to_string()),
+            },
+        },
+        Backend {
+            name: "asyncapi",
+            check: |schema| {
+                let message = schema_asyncapi::Message::from_schema("sample", schema.clone());
+                match schema_asyncapi::AsyncApiBuilder::new("Sample", "1.0.0")
+                    .channel(schema_asyncapi::Channel::new("sample", "sample").message(message))
+                    .build()
+                {
+                    Ok(document) => {
+                        json_schema(&document["components"]["messages"]["sample"]["payload"])
+                    }
+                    Err(e) => Outcome::Unsupported(e.to_string()),
+                }
+            },
+        },
+        Backend {
+            name: "openai",
+            check: |schema| json_schema(&schema_openai::schema_type_to_strict(schema).schema),
+        },
+        Backend {
+            name: "prompt",
+            check: |schema| {
+                let options = schema_prompt::PromptOptions::new();
+                schema_prompt::schema_type_to_prompt(schema, &options);
+                Outcome::Valid
+            },
+        },
+        Backend {
+            name: "wit",
+            check: |schema| wit(&schema_wit::schema_type_to_wit(schema, Some("sample"))),
+        },
+        Backend {
+            name: "env",
+            check: |schema| {
+                schema_env::schema_type_to_env_vars(schema, "APP");
+                Outcome::Valid
+            },
+        },
+        Backend {
+            name: "postman",
+            check: |schema| {
+                let openapi = schema_openapi::schema_type_to_openapi(schema);
+                schema_postman::example_value(&json!({}), &openapi);
+                Outcome::Valid
+            },
+        },
+        Backend {
+            name: "typescript",
+            check: |schema| {
+                schema_typescript::schema_type_to_typescript(schema, Some("Sample"));
+                Outcome::Valid
+            },
+        },
+        Backend {
+            name: "zod",
+            check: |schema| {
+                schema_zod::schema_type_to_zod(schema, Some("Sample"));
+                Outcome::Valid
+            },
+        },
+        Backend {
+            name: "pydantic",
+            check: |schema| {
+                schema_pydantic::schema_type_to_pydantic(schema, Some("Sample"));
+                Outcome::Valid
+            },
+        },
+        Backend {
+            name: "swift",
+            check: |schema| {
+                schema_swift::schema_type_to_swift(schema, Some("Sample"));
+                Outcome::Valid
+            },
+        },
+        Backend {
+            name: "proto",
+            check: |schema| match schema_proto::schema_type_to_proto(schema, Some("Sample")) {
+                Ok(_) => Outcome::Valid,
+                Err(e) => Outcome::Unsupported(e.to_string()),
+            },
+        },
+        Backend {
+            name: "graphql",
+            check: |schema| {
+                let options =
+                    schema_graphql::GraphqlOptions::new().flavor(schema_graphql::Flavor::Both);
+                schema_graphql::schema_type_to_graphql_with_options(
+                    schema,
+                    Some("Sample"),
+                    &options,
+                );
+                Outcome::Valid
+            },
+        },
+        Backend {
+            name: "parquet",
+            check: |schema| match schema_parquet::schema_type_to_parquet(schema, Some("sample")) {
+                Ok(output) => parquet_message(&output),
+                Err(e) => Outcome::Unsupported(e.to_string()),
+            },
+        },
+        Backend {
+            name: "sql",
+            check: |schema| {
+                let options = schema_sql::SqlOptions::default();
+                match schema_sql::schema_type_to_create_table(schema, "sample", &options) {
+                    Ok(_) => Outcome::Valid,
+                    Err(e) => Outcome::Unsupported(e.to_string()),
+                }
+            },
+        },
+        Backend {
+            name: "clickhouse",
+            check: |schema| {
+                let options = schema_clickhouse::ClickHouseOptions::default();
+                match schema_clickhouse::schema_type_to_create_table(schema, "sample", &options) {
+                    Ok(_) => Outcome::Valid,
+                    Err(e) => Outcome::Unsupported(e.to_string()),
+                }
+            },
+        },
+        Backend {
+            name: "spark",
+            check: |schema| match schema_spark::schema_type_to_spark(schema) {
+                Ok(_) => Outcome::Valid,
+                Err(e) => Outcome::Unsupported(e.to_string()),
+            },
+        },
+        Backend {
+            name: "iceberg",
+            check: |schema| match schema_iceberg::schema_type_to_iceberg(schema) {
+                Ok(_) => Outcome::Valid,
+                Err(e) => Outcome::Unsupported(e.to_string()),
+            },
+        },
+        Backend {
+            name: "dbml",
+            check: |schema| {
+                let options = schema_dbml::DbmlOptions::default();
+                match schema_dbml::schema_type_to_dbml(schema, "sample", &options) {
+                    Ok(_) => Outcome::Valid,
+                    Err(e) => Outcome::Unsupported(e.to_string()),
+                }
+            },
+        },
+        Backend {
+            name: "capnp",
+            check: |schema| match schema_capnp::schema_type_to_capnp(schema, Some("Sample")) {
+                Ok(_) => Outcome::Valid,
+                Err(e) => Outcome::Invalid(e.to_string()),
+            },
+        },
+        Backend {
+            name: "flatbuffers",
+            check: |schema| {
+                schema_flatbuffers::schema_type_to_flatbuffers(schema, Some("Sample"));
+                Outcome::Valid
+            },
+        },
+        Backend {
+            name: "thrift",
+            check: |schema| match schema_thrift::schema_type_to_thrift(schema, Some("Sample")) {
+                Ok(_) => Outcome::Valid,
+                Err(e) => Outcome::Unsupported(e.to_string()),
+            },
+        },
+        Backend {
+            name: "smithy",
+            check: |schema| {
+                let options = schema_smithy::SmithyOptions::new("conformance");
+                match schema_smithy::schema_type_to_smithy(schema, Some("Sample"), &options) {
+                    Ok(_) => Outcome::Valid,
+                    Err(e) => Outcome::Unsupported(e.to_string()),
+                }
+            },
+        },
+        Backend {
+            name: "cue",
+            check: |schema| {
+                let options = schema_cue::CueOptions::new();
+                match schema_cue::schema_type_to_cue(schema, Some("Sample"), &options) {
+                    Ok(_) => Outcome::Valid,
+                    Err(e) => Outcome::Unsupported(e.to_string()),
+                }
+            },
+        },
+        Backend {
+            name: "dhall",
+            check: |schema| match schema_dhall::schema_type_to_dhall(schema, Some("Sample")) {
+                Ok(_) => Outcome::Valid,
+                Err(e) => Outcome::Unsupported(e.to_string()),
+            },
+        },
+        Backend {
+            name: "xsd",
+            check: |schema| {
+                schema_xsd::schema_type_to_xsd(schema, Some("Sample"));
+                Outcome::Valid
+            },
+        },
+        Backend {
+            name: "docs",
+            check: |schema| {
+                let options = schema_docs::DocsOptions::new();
+                schema_docs::schema_type_to_html(schema, Some("Sample"), &options);
+                Outcome::Valid
+            },
+        },
+        Backend {
+            name: "diagram",
+            check: |schema| {
+                schema_diagram::schema_type_to_mermaid(schema, Some("Sample"));
+                schema_diagram::schema_type_to_dot(schema, Some("Sample"));
+                Outcome::Valid
+            },
+        },
+        Backend {
+            name: "csv",
+            check: |schema| match schema_csv::schema_type_to_csv_columns(schema) {
+                Ok(_) => Outcome::Valid,
+                Err(e) => Outcome::Unsupported(e.to_string()),
+            },
+        },
+    ]
+}
+
+fn json_schema(output: &Value) -> Outcome {
+    match jsonschema::draft202012::meta::validate(output) {
+        Ok(()) => Outcome::Valid,
+        Err(e) => Outcome::Invalid(e.to_string()),
+    }
+}
+
+fn parquet_message(output: &str) -> Outcome {
+    match parquet::schema::parser::parse_message_type(output) {
+        Ok(_) => Outcome::Valid,
+        Err(e) => Outcome::Invalid(e.to_string()),
+    }
+}
+
+/// Parse a type definition (or a bare type, aliased as `type sample = ...`)
+/// inside a package that also defines the `Ref` target
+fn wit(output: &str) -> Outcome {
+    let is_definition = ["record ", "variant ", "enum ", "///"]
+        .iter()
+        .any(|start| output.starts_with(start));
+    let definition = if is_definition {
+        output.to_string()
+    } else {
+        format!("type sample = {};", output)
+    };
+    let source = format!(
+        "package conformance:suite;\n\ninterface types {{\n    type {} = u32;\n\n{}\n}}\n",
+        REF_TARGET.to_lowercase(),
+        definition
+    );
+
+    let mut resolve = wit_parser::Resolve::new();
+    match resolve.push_source("conformance.wit", &source) {
+        Ok(_) => Outcome::Valid,
+        Err(e) => Outcome::Invalid(format!("{:#}", e)),
+    }
+}
+
+/// Schemas with no children
+pub fn leaves() -> Vec<Sample> {
+    let leaf = |label: &str, kind: TypeKind| Sample {
+        label: label.to_string(),
+        schema: SchemaType::new(kind),
+    };
+    vec![
+        leaf("string", TypeKind::String),
+        leaf("i32", TypeKind::Integer(IntegerKind::I32)),
+        leaf("u64", TypeKind::Integer(IntegerKind::U64)),
+        leaf("f64", TypeKind::Number(NumberKind::F64)),
+        leaf("boolean", TypeKind::Boolean),
+        leaf("null", TypeKind::Null),
+        leaf(
+            "enum",
+            TypeKind::Enum {
+                variants: vec!["on".to_string(), "off".to_string()],
+            },
+        ),
+        leaf(
+            "ref",
+            TypeKind::Ref {
+                name: REF_TARGET.to_string(),
+            },
+        ),
+    ]
+}
+
+/// Every composite kind wrapped around `child`
+pub fn wrap(child: &Sample) -> Vec<Sample> {
+    let inner = || Box::new(child.schema.clone());
+    let object = |required: bool| TypeKind::Object {
+        properties: HashMap::from([("value".to_string(), child.schema.clone())]),
+        required: if required {
+            vec!["value".to_string()]
+        } else {
+            vec![]
+        },
+    };
+
+    let variant = || TypeKind::Variant {
+        cases: vec![
+            VariantCase {
+                name: "some".to_string(),
+                data: Some(child.schema.clone()),
+                ..Default::default()
+            },
+            VariantCase {
+                name: "none".to_string(),
+                ..Default::default()
+            },
+        ],
+    };
+
+    let kinds = [
+        ("array", TypeKind::Array { items: inner() }),
+        (
+            "set",
+            TypeKind::Set {
+                items: inner(),
+                ordered: false,
+            },
+        ),
+        (
+            "map",
+            TypeKind::Map {
+                key: Box::new(SchemaType::new(TypeKind::String)),
+                value: inner(),
+                ordered: true,
+            },
+        ),
+        ("object", object(true)),
+        ("optional-object", object(false)),
+        ("variant", variant()),
+        (
+            "tagged-union",
+            TypeKind::TaggedUnion {
+                tag_field: "type".to_string(),
+                tag_variants: vec!["some".to_string(), "none".to_string()],
+                data_fields: HashMap::from([("value".to_string(), child.schema.clone())]),
+            },
+        ),
+        (
+            "result",
+            TypeKind::Result {
+                ok: inner(),
+                err: Box::new(SchemaType::new(TypeKind::String)),
+            },
+        ),
+        (
+            "tuple",
+            TypeKind::Tuple {
+                fields: vec![child.schema.clone(), SchemaType::new(TypeKind::Boolean)],
+            },
+        ),
+    ];
+
+    let mut samples: Vec<Sample> = kinds
+        .into_iter()
+        .map(|(label, kind)| Sample {
+            label: format!("{}<{}>", label, child.label),
+            schema: SchemaType::new(kind),
+        })
+        .collect();
+
+    let taggings = [
+        (
+            "internal-variant",
+            Tagging::Internal {
+                tag: "kind".to_string(),
+            },
+        ),
+        (
+            "adjacent-variant",
+            Tagging::Adjacent {
+                tag: "kind".to_string(),
+                content: "value".to_string(),
+            },
+        ),
+        ("untagged-variant", Tagging::Untagged),
+    ];
+    for (label, tagging) in taggings {
+        let mut schema = SchemaType::new(variant());
+        schema.set_tagging(tagging);
+        samples.push(Sample {
+            label: format!("{}<{}>", label, child.label),
+            schema,
+        });
+    }
+    samples
+}
+
+/// Every leaf, plus every composite nested up to `depth` levels deep
+pub fn samples(depth: usize) -> Vec<Sample> {
+    let mut all = leaves();
+    let mut previous = leaves();
+    for _ in 0..depth {
+        let next: Vec<Sample> = previous.iter().flat_map(wrap).collect();
+        all.extend(next.iter().cloned());
+        previous = next;
+    }
+    all
+}
+
+/// One backend's outcome for one sample
+#[derive(Debug, Clone)]
+pub struct Check {
+    pub sample: Sample,
+    pub backend: &'static str,
+    pub outcome: Outcome,
+}
+
+/// Outcomes of every backend over every sample
+#[derive(Debug, Clone)]
+pub struct Report {
+    pub checks: Vec<Check>,
+}
+
+impl Report {
+    /// Checks where the backend panicked
+    pub fn panics(&self) -> Vec<&Check> {
+        self.matching(|outcome| matches!(outcome, Outcome::Panicked(_)))
+    }
+
+    /// Checks where the backend produced output that failed validation
+    pub fn invalid(&self) -> Vec<&Check> {
+        self.matching(|outcome| matches!(outcome, Outcome::Invalid(_)))
+    }
+
+    fn matching(&self, predicate: impl Fn(&Outcome) -> bool) -> Vec<&Check> {
+        self.checks
+            .iter()
+            .filter(|check| predicate(&check.outcome))
+            .collect()
+    }
+}
+
+/// Run every backend over [`samples`]`(depth)`
+pub fn run(depth: usize) -> Report {
+    let backends = backends();
+    let mut checks = vec![];
+    for sample in samples(depth) {
+        for backend in &backends {
+            let outcome = catch_unwind(AssertUnwindSafe(|| (backend.check)(&sample.schema)))
+                .unwrap_or_else(|panic| Outcome::Panicked(panic_message(panic)));
+            checks.push(Check {
+                sample: sample.clone(),
+                backend: backend.name,
+                outcome,
+            });
+        }
+    }
+    Report { checks }
+}
+
+fn panic_message(panic: Box<dyn std::any::Any + Send>) -> String {
+    panic
+        .downcast_ref::<&str>()
+        .map(|s| s.to_string())
+        .or_else(|| panic.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "non-string panic".to_string())
+}
+
+/// Coverage matrix: one row per outermost kind, one column per backend,
+/// each cell `valid/total` with unsupported, invalid and panicked counts
+/// when nonzero
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut backends: Vec<&str> = vec![];
+        let mut cells: BTreeMap<(&str, &str), [usize; 4]> = BTreeMap::new();
+        for check in &self.checks {
+            if !backends.contains(&check.backend) {
+                backends.push(check.backend);
+            }
+            let counts = cells
+                .entry((check.sample.kind(), check.backend))
+                .or_default();
+            let index = match check.outcome {
+                Outcome::Valid => 0,
+                Outcome::Unsupported(_) => 1,
+                Outcome::Invalid(_) => 2,
+                Outcome::Panicked(_) => 3,
+            };
+            counts[index] += 1;
+        }
+
+        write!(f, "{:<16}", "kind")?;
+        for backend in &backends {
+            write!(f, " {:>18}", backend)?;
+        }
+        writeln!(f)?;
+
+        let mut kinds: Vec<&str> = cells.keys().map(|(kind, _)| *kind).collect();
+        kinds.dedup();
+        for kind in kinds {
+            write!(f, "{:<16}", kind)?;
+            for backend in &backends {
+                let [valid, unsupported, invalid, panicked] = cells[&(kind, *backend)];
+                let total = valid + unsupported + invalid + panicked;
+                let mut cell = format!("{}/{}", valid, total);
+                for (count, tag) in [(unsupported, "u"), (invalid, "i"), (panicked, "p")] {
+                    if count > 0 {
+                        cell.push_str(&format!(" {}{}", count, tag));
+                    }
+                }
+                write!(f, " {:>18}", cell)?;
+            }
+            writeln!(f)?;
+        }
+        write!(f, "(u = unsupported, i = invalid output, p = panicked)")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_samples_cover_every_kind() {
+        let samples = samples(2);
+        let leaf_count = leaves().len();
+        let wrap_count = wrap(&leaves()[0]).len();
+        assert_eq!(
+            samples.len(),
+            leaf_count + leaf_count * wrap_count + leaf_count * wrap_count * wrap_count
+        );
+        assert!(samples.iter().any(|s| s.label == "map<variant<ref>>"));
+    }
+
+    /// Whether the WIT backend is expected to produce valid WIT: it has no
+    /// `null` type, doesn't support `TaggedUnion`, and writes nested
+    /// records, variants and enums inline instead of as named definitions
+    fn wit_expressible(sample: &Sample) -> bool {
+        let kinds: Vec<&str> = sample
+            .label
+            .split('<')
+            .map(|kind| kind.trim_end_matches('>'))
+            .collect();
+        !kinds.contains(&"null")
+            && !kinds.contains(&"tagged-union")
+            && !kinds[1..].iter().any(|kind| {
+                matches!(*kind, "object" | "optional-object" | "enum") || kind.ends_with("variant")
+            })
+    }
+
+    /// Whether a JSON Schema import is expected to reproduce the document:
+    /// an untagged variant with a unit case is written as `anyOf` with a
+    /// `null` branch, which reads back as a nullable value
+    fn import_unambiguous(sample: &Sample) -> bool {
+        !sample.label.contains("untagged-variant")
+    }
+
+    #[test]
+    fn test_conformance() {
+        let report = run(2);
+        println!("{}", report);
+        assert!(report.panics().is_empty(), "{:#?}", report.panics());
+
+        let unexpected: Vec<_> = report
+            .invalid()
+            .into_iter()
+            .filter(|check| check.backend != "wit" || wit_expressible(&check.sample))
+            .filter(|check| {
+                check.backend != "jsonschema-import" || import_unambiguous(&check.sample)
+            })
+            .map(|check| (check.backend, &check.sample.label, &check.outcome))
+            .collect();
+        assert!(unexpected.is_empty(), "{:#?}", unexpected);
+    }
+}
@@ -0,0 +1,375 @@
+use schema::{IntegerKind, Schema, SchemaType, TypeKind};
+use serde_json::{Map, Number, Value};
+use std::fmt;
+
+/// A CSV column derived from an object property
+#[derive(Debug, Clone, PartialEq)]
+pub struct CsvColumn {
+    /// Header text; the (possibly renamed) property name
+    pub header: String,
+    pub kind: ColumnKind,
+    /// Non-required columns accept empty cells, which parse to `null`
+    pub required: bool,
+    pub description: Option<String>,
+}
+
+/// How a column's cells are parsed
+#[derive(Debug, Clone, PartialEq)]
+pub enum ColumnKind {
+    String,
+    Integer(IntegerKind),
+    Number,
+    Boolean,
+    /// One of a fixed set of strings
+    Enum(Vec<String>),
+}
+
+/// Errors from mapping schemas to columns or parsing rows
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CsvError {
+    /// The schema is not an object, so it has no columns
+    NotAnObject,
+    /// A property is an array, object or other non-scalar type
+    NestedProperty { property: String },
+    /// A row has a different number of cells than there are columns
+    ColumnCount { expected: usize, found: usize },
+    /// A required cell is empty
+    MissingValue { column: String },
+    /// A cell doesn't parse as the column's type
+    InvalidValue {
+        column: String,
+        value: String,
+        expected: String,
+    },
+    /// A quoted cell is not closed
+    UnterminatedQuote,
+}
+
+impl fmt::Display for CsvError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CsvError::NotAnObject => write!(f, "only object schemas map to CSV columns"),
+            CsvError::NestedProperty { property } => {
+                write!(
+                    f,
+                    "property `{}` is not a scalar and can't be a column",
+                    property
+                )
+            }
+            CsvError::ColumnCount { expected, found } => {
+                write!(f, "expected {} cells, found {}", expected, found)
+            }
+            CsvError::MissingValue { column } => write!(f, "column `{}` is required", column),
+            CsvError::InvalidValue {
+                column,
+                value,
+                expected,
+            } => write!(
+                f,
+                "column `{}`: {:?} is not a valid {}",
+                column, value, expected
+            ),
+            CsvError::UnterminatedQuote => write!(f, "unterminated quoted cell"),
+        }
+    }
+}
+
+impl std::error::Error for CsvError {}
+
+/// Columns for a flat struct, in field declaration order
+pub fn to_csv_columns<T: Schema>() -> Result<Vec<CsvColumn>, CsvError> {
+    schema_type_to_csv_columns(&T::schema())
+}
+
+/// Columns for a flat object schema, in property declaration order
+pub fn schema_type_to_csv_columns(schema: &SchemaType) -> Result<Vec<CsvColumn>, CsvError> {
+    let TypeKind::Object { required, .. } = &schema.kind else {
+        return Err(CsvError::NotAnObject);
+    };
+
+    schema
+        .ordered_properties()
+        .into_iter()
+        .map(|(name, prop)| {
+            let kind = match &prop.kind {
+                TypeKind::String => ColumnKind::String,
+                TypeKind::Integer(kind) => ColumnKind::Integer(*kind),
+                TypeKind::Number(_) => ColumnKind::Number,
+                TypeKind::Boolean => ColumnKind::Boolean,
+                TypeKind::Enum { variants } => ColumnKind::Enum(variants.clone()),
+                _ => {
+                    return Err(CsvError::NestedProperty {
+                        property: name.to_string(),
+                    });
+                }
+            };
+            Ok(CsvColumn {
+                header: name.to_string(),
+                kind,
+                required: required.iter().any(|r| r == name),
+                description: prop.description.clone(),
+            })
+        })
+        .collect()
+}
+
+impl CsvColumn {
+    /// Parse a single cell into a JSON value
+    pub fn parse(&self, cell: &str) -> Result<Value, CsvError> {
+        if cell.is_empty() {
+            return match (&self.kind, self.required) {
+                (ColumnKind::String, true) => Ok(Value::String(String::new())),
+                (_, true) => Err(CsvError::MissingValue {
+                    column: self.header.clone(),
+                }),
+                (_, false) => Ok(Value::Null),
+            };
+        }
+
+        let invalid = |expected: &str| CsvError::InvalidValue {
+            column: self.header.clone(),
+            value: cell.to_string(),
+            expected: expected.to_string(),
+        };
+
+        match &self.kind {
+            ColumnKind::String => Ok(Value::String(cell.to_string())),
+            ColumnKind::Integer(kind) => {
+                let value: i128 = cell.trim().parse().map_err(|_| invalid(kind.rust_name()))?;
+                if !(kind.min()..=kind.max()).contains(&value) {
+                    return Err(invalid(kind.rust_name()));
+                }
+                Ok(match i64::try_from(value) {
+                    Ok(v) => Value::from(v),
+                    Err(_) => Value::from(value as u64),
+                })
+            }
+            ColumnKind::Number => cell
+                .trim()
+                .parse::<f64>()
+                .ok()
+                .and_then(Number::from_f64)
+                .map(Value::Number)
+                .ok_or_else(|| invalid("number")),
+            ColumnKind::Boolean => match cell.trim() {
+                "true" | "TRUE" | "1" => Ok(Value::Bool(true)),
+                "false" | "FALSE" | "0" => Ok(Value::Bool(false)),
+                _ => Err(invalid("boolean")),
+            },
+            ColumnKind::Enum(variants) => {
+                if variants.iter().any(|v| v == cell) {
+                    Ok(Value::String(cell.to_string()))
+                } else {
+                    Err(invalid(&format!("one of {}", variants.join(", "))))
+                }
+            }
+        }
+    }
+
+    /// Format a JSON value as a cell (unquoted)
+    pub fn format(&self, value: &Value) -> String {
+        match value {
+            Value::Null => String::new(),
+            Value::String(s) => s.clone(),
+            other => other.to_string(),
+        }
+    }
+}
+
+/// The header line for these columns
+pub fn header_row(columns: &[CsvColumn]) -> String {
+    encode_record(columns.iter().map(|c| c.header.as_str()))
+}
+
+/// Encode an object value as a CSV line, in column order
+///
+/// Missing properties become empty cells.
+pub fn encode_row(columns: &[CsvColumn], value: &Value) -> String {
+    let cells: Vec<String> = columns
+        .iter()
+        .map(|c| c.format(value.get(&c.header).unwrap_or(&Value::Null)))
+        .collect();
+    encode_record(cells.iter().map(String::as_str))
+}
+
+/// Parse a CSV line into an object value keyed by header
+pub fn parse_row(columns: &[CsvColumn], line: &str) -> Result<Value, CsvError> {
+    let cells = split_record(line)?;
+    if cells.len() != columns.len() {
+        return Err(CsvError::ColumnCount {
+            expected: columns.len(),
+            found: cells.len(),
+        });
+    }
+
+    let mut object = Map::new();
+    for (column, cell) in columns.iter().zip(&cells) {
+        let value = column.parse(cell)?;
+        if !value.is_null() {
+            object.insert(column.header.clone(), value);
+        }
+    }
+    Ok(Value::Object(object))
+}
+
+/// Join cells with commas, quoting cells that contain `,`, `"` or newlines
+fn encode_record<'a>(cells: impl Iterator<Item = &'a str>) -> String {
+    cells
+        .map(|cell| {
+            if cell.contains([',', '"', '\n', '\r']) {
+                format!("\"{}\"", cell.replace('"', "\"\""))
+            } else {
+                cell.to_string()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// Split a single CSV line, honouring quoted cells and `""` escapes
+fn split_record(line: &str) -> Result<Vec<String>, CsvError> {
+    let line = line.strip_suffix('\n').unwrap_or(line);
+    let line = line.strip_suffix('\r').unwrap_or(line);
+
+    let mut cells = Vec::new();
+    let mut cell = String::new();
+    let mut chars = line.chars().peekable();
+    let mut in_quotes = false;
+
+    while let Some(ch) = chars.next() {
+        match (ch, in_quotes) {
+            ('"', true) if chars.peek() == Some(&'"') => {
+                chars.next();
+                cell.push('"');
+            }
+            ('"', true) => in_quotes = false,
+            ('"', false) if cell.is_empty() => in_quotes = true,
+            (',', false) => cells.push(std::mem::take(&mut cell)),
+            (ch, _) => cell.push(ch),
+        }
+    }
+
+    if in_quotes {
+        return Err(CsvError::UnterminatedQuote);
+    }
+    cells.push(cell);
+    Ok(cells)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[derive(Schema)]
+    #[schema(rename_all_variants = "lowercase")]
+    #[allow(dead_code)]
+    enum Status {
+        Active,
+        Closed,
+    }
+
+    /// An exported order
+    #[derive(Schema)]
+    #[allow(dead_code)]
+    struct OrderRow {
+        #[schema(rename = "order_id")]
+        id: u64,
+        /// Customer display name
+        customer: String,
+        total: f64,
+        status: Status,
+        paid: bool,
+        note: Option<String>,
+    }
+
+    #[derive(Schema)]
+    #[allow(dead_code)]
+    struct Nested {
+        tags: Vec<String>,
+    }
+
+    #[test]
+    fn test_columns() {
+        let columns = to_csv_columns::<OrderRow>().unwrap();
+        let headers: Vec<_> = columns.iter().map(|c| c.header.as_str()).collect();
+        assert_eq!(
+            headers,
+            vec!["order_id", "customer", "total", "status", "paid", "note"]
+        );
+
+        assert_eq!(columns[0].kind, ColumnKind::Integer(IntegerKind::U64));
+        assert_eq!(
+            columns[3].kind,
+            ColumnKind::Enum(vec!["active".to_string(), "closed".to_string()])
+        );
+        assert_eq!(
+            columns[1].description.as_deref(),
+            Some("Customer display name")
+        );
+        assert!(columns[0].required);
+        assert!(!columns[5].required);
+    }
+
+    #[test]
+    fn test_nested_rejected() {
+        assert_eq!(
+            to_csv_columns::<Nested>(),
+            Err(CsvError::NestedProperty {
+                property: "tags".to_string()
+            })
+        );
+        assert_eq!(to_csv_columns::<Status>(), Err(CsvError::NotAnObject));
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let columns = to_csv_columns::<OrderRow>().unwrap();
+        assert_eq!(
+            header_row(&columns),
+            "order_id,customer,total,status,paid,note"
+        );
+
+        let value = json!({
+            "order_id": 7,
+            "customer": "Doe, \"JD\" Jane",
+            "total": 12.5,
+            "status": "active",
+            "paid": true
+        });
+        let line = encode_row(&columns, &value);
+        assert_eq!(line, "7,\"Doe, \"\"JD\"\" Jane\",12.5,active,true,");
+        assert_eq!(parse_row(&columns, &line).unwrap(), value);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let columns = to_csv_columns::<OrderRow>().unwrap();
+
+        assert_eq!(
+            parse_row(&columns, "1,a,2"),
+            Err(CsvError::ColumnCount {
+                expected: 6,
+                found: 3
+            })
+        );
+        assert_eq!(
+            parse_row(&columns, ",a,1,active,true,"),
+            Err(CsvError::MissingValue {
+                column: "order_id".to_string()
+            })
+        );
+        assert!(matches!(
+            parse_row(&columns, "-1,a,1,active,true,"),
+            Err(CsvError::InvalidValue { .. })
+        ));
+        assert!(matches!(
+            parse_row(&columns, "1,a,1,pending,true,"),
+            Err(CsvError::InvalidValue { .. })
+        ));
+        assert_eq!(
+            parse_row(&columns, "1,\"a,1,active,true,"),
+            Err(CsvError::UnterminatedQuote)
+        );
+    }
+}
@@ -0,0 +1,717 @@
+//! CUE definitions from schemas
+//!
+//! The root and every named type nested in it become a definition
+//! (`#Person: {...}`), referenced by name wherever they're used. Objects are
+//! structs with `field?:` for optional properties and `*value | type` for
+//! defaults, and stay open (`...`) unless they deny unknown fields, since
+//! CUE definitions are otherwise closed. Constraints are written as CUE
+//! expressions: `int & >=0`, `strings.MaxRunes(80)`, `=~"^[a-z]+$"`,
+//! `list.MinItems(1)`, with the `strings`, `list` and `time` imports they
+//! need.
+//!
+//! Enums are disjunctions of string literals, and variants disjunctions of
+//! the JSON each case is written as under the schema's tagging. Output is
+//! indented with tabs, as `cue fmt` does.
+
+use schema::case::Case;
+use schema::{
+    GenerateOptions, IntegerKind, NumberKind, RefPreference, Schema, SchemaType, Tagging, TypeKind,
+    VariantCase,
+};
+use serde_json::Value;
+use std::collections::BTreeSet;
+use std::fmt;
+
+/// Options for the generated `.cue` file
+#[derive(Debug, Clone, Default)]
+pub struct CueOptions {
+    /// Package clause, if any, e.g. `config`
+    pub package: Option<String>,
+    pub generate: GenerateOptions,
+}
+
+impl CueOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn package(mut self, package: &str) -> Self {
+        self.package = Some(package.to_string());
+        self
+    }
+
+    /// Bound the schema before it is written
+    pub fn generate(mut self, generate: GenerateOptions) -> Self {
+        self.generate = generate;
+        self
+    }
+}
+
+/// Why a schema has no CUE file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CueError {
+    /// The package name isn't a CUE identifier
+    InvalidPackage(String),
+}
+
+impl fmt::Display for CueError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CueError::InvalidPackage(package) => {
+                write!(f, "{:?} is not a valid CUE package name", package)
+            }
+        }
+    }
+}
+
+impl std::error::Error for CueError {}
+
+/// The `.cue` definitions for `T` and the named types it contains
+pub fn to_cue<T: Schema>(options: &CueOptions) -> Result<String, CueError> {
+    schema_type_to_cue(&T::schema(), T::type_name(), options)
+}
+
+/// The `.cue` definitions for a SchemaType, its root definition named
+/// `type_name` (or the schema's own name, or `Root`)
+pub fn schema_type_to_cue(
+    schema: &SchemaType,
+    type_name: Option<&str>,
+    options: &CueOptions,
+) -> Result<String, CueError> {
+    if let Some(package) = &options.package
+        && !is_identifier(package)
+    {
+        return Err(CueError::InvalidPackage(package.clone()));
+    }
+
+    let schema = options.generate.apply(schema);
+    let mut module = Module::default();
+    let ident = module.unique(&identifier(
+        type_name.or(schema.name.as_deref()).unwrap_or("Root"),
+    ));
+    if let Some(name) = &schema.name {
+        module.named.push(Named {
+            name: name.clone(),
+            shape: shape(&schema),
+            ident: ident.clone(),
+            first: schema.clone(),
+        });
+    }
+    module.declare(&ident, &schema);
+    // The root reads first; the types it uses follow in the order met
+    module.output.rotate_right(1);
+
+    let mut sections = vec![];
+    if let Some(package) = &options.package {
+        sections.push(format!("package {}\n", package));
+    }
+    match module.imports.len() {
+        0 => {}
+        1 => sections.push(format!("import {:?}\n", module.imports.first().unwrap())),
+        _ => {
+            let imports: String = module
+                .imports
+                .iter()
+                .map(|import| format!("\t{:?}\n", import))
+                .collect();
+            sections.push(format!("import (\n{})\n", imports));
+        }
+    }
+    sections.extend(module.output);
+    Ok(sections.join("\n"))
+}
+
+/// A named type that has a definition
+struct Named {
+    name: String,
+    /// Fingerprint without use-site annotations
+    shape: String,
+    ident: String,
+    /// The occurrence the definition was written from
+    first: SchemaType,
+}
+
+#[derive(Default)]
+struct Module {
+    named: Vec<Named>,
+    /// Every definition name in use, without the `#`
+    taken: BTreeSet<String>,
+    /// Packages the constraints call into
+    imports: BTreeSet<&'static str>,
+    /// Definitions, each after the ones it uses until the root moves first
+    output: Vec<String>,
+}
+
+impl Module {
+    /// `base`, or `base2`, `base3`, ... if something already has the name
+    fn unique(&mut self, base: &str) -> String {
+        let ident = (1..)
+            .map(|n| match n {
+                1 => base.to_string(),
+                n => format!("{base}{n}"),
+            })
+            .find(|ident| !self.taken.contains(ident))
+            .unwrap();
+        self.taken.insert(ident.clone());
+        ident
+    }
+
+    /// Write the definition `#ident`
+    fn declare(&mut self, ident: &str, schema: &SchemaType) {
+        let expression = self.definition(schema, "");
+        let mut declaration = comment(schema.docs().as_deref(), "");
+        declaration.push_str(&format!("#{ident}: {expression}\n"));
+        self.output.push(declaration);
+    }
+
+    /// A nested schema: a reference to its definition if it is a named type,
+    /// otherwise written in place
+    fn reference(&mut self, schema: &SchemaType, indent: &str) -> String {
+        let Some(name) = &schema.name else {
+            return self.definition(schema, indent);
+        };
+        if schema.ref_preference() == Some(RefPreference::Inline) {
+            return self.definition(schema, indent);
+        }
+        if let TypeKind::Ref { name } = &schema.kind {
+            return self.ref_to(name);
+        }
+
+        let shape = shape(schema);
+        if let Some(named) = self
+            .named
+            .iter()
+            .find(|named| named.name == *name && named.shape == shape)
+        {
+            return format!("#{}", named.ident);
+        }
+
+        // A different type may already have taken the name
+        let ident = self.unique(&identifier(name));
+        self.named.push(Named {
+            name: name.clone(),
+            shape,
+            ident: ident.clone(),
+            first: schema.clone(),
+        });
+        self.declare(&ident, schema);
+        format!("#{ident}")
+    }
+
+    fn ref_to(&self, name: &str) -> String {
+        let ident = self
+            .named
+            .iter()
+            .find(|named| named.name == name)
+            .map(|named| named.ident.clone())
+            .unwrap_or_else(|| identifier(name));
+        format!("#{ident}")
+    }
+
+    /// The type itself, with its constraints, as an expression starting at
+    /// `indent`
+    fn definition(&mut self, schema: &SchemaType, indent: &str) -> String {
+        let inner = format!("{indent}\t");
+        let base = match &schema.kind {
+            TypeKind::String if schema.constraints.format.as_deref() == Some("date-time") => {
+                self.imports.insert("time");
+                "time.Time".to_string()
+            }
+            TypeKind::String => "string".to_string(),
+            TypeKind::Integer(kind) => integer(kind).to_string(),
+            TypeKind::Number(NumberKind::F32) => "float32".to_string(),
+            TypeKind::Number(NumberKind::F64) => "float64".to_string(),
+            TypeKind::Boolean => "bool".to_string(),
+            TypeKind::Null => "null".to_string(),
+            TypeKind::Enum { variants } => disjunction(variants.iter().map(|v| literal(v))),
+            TypeKind::Ref { name } => self.ref_to(name),
+            TypeKind::Array { items } | TypeKind::Set { items, .. } => {
+                format!("[...{}]", self.reference(items, indent))
+            }
+            TypeKind::Map { key, value, .. } => {
+                let label = match &key.kind {
+                    TypeKind::Enum { variants } => disjunction(variants.iter().map(|v| literal(v))),
+                    // Keys are written as JSON object keys, so numbers are
+                    // their decimal strings
+                    TypeKind::Integer(_) => literal_pattern("^-?[0-9]+$"),
+                    _ => "string".to_string(),
+                };
+                format!("{{[{}]: {}}}", label, self.reference(value, indent))
+            }
+            TypeKind::Object { required, .. } => {
+                let mut fields = vec![];
+                for (name, property) in schema.ordered_properties() {
+                    let optional = !required.iter().any(|r| r == name);
+                    fields.push(self.field(name, property, optional, &inner));
+                }
+                if !schema.denies_unknown_fields() {
+                    fields.push(format!("{inner}...\n"));
+                }
+                block(fields, indent)
+            }
+            TypeKind::TaggedUnion {
+                tag_field,
+                tag_variants,
+                data_fields,
+            } => {
+                let tag = disjunction(tag_variants.iter().map(|v| literal(v)));
+                let mut fields = vec![format!("{inner}{}: {}\n", label(tag_field), tag)];
+                let mut names: Vec<&String> = data_fields.keys().collect();
+                names.sort();
+                for name in names {
+                    fields.push(self.field(name, &data_fields[name], true, &inner));
+                }
+                fields.push(format!("{inner}...\n"));
+                block(fields, indent)
+            }
+            TypeKind::Variant { cases } if cases.is_empty() => "_|_".to_string(),
+            TypeKind::Variant { cases } => {
+                let tagging = schema.tagging();
+                let cases: Vec<String> = cases
+                    .iter()
+                    .map(|case| self.case(case, tagging.as_ref(), indent))
+                    .collect();
+                disjunction(cases.into_iter())
+            }
+            TypeKind::Result { ok, err } => {
+                let ok = self.reference(ok, &inner);
+                let err = self.reference(err, &inner);
+                format!(
+                    "{} | {}",
+                    block(vec![format!("{inner}ok: {ok}\n")], indent),
+                    block(vec![format!("{inner}error: {err}\n")], indent)
+                )
+            }
+            TypeKind::Tuple { fields } => {
+                let fields: Vec<String> =
+                    fields.iter().map(|f| self.reference(f, indent)).collect();
+                format!("[{}]", fields.join(", "))
+            }
+        };
+
+        let constraints = self.constraints(schema);
+        if constraints.is_empty() {
+            return base;
+        }
+        // A disjunction binds looser than the constraints that follow it
+        let base = match base.contains(" | ") {
+            true => format!("({base})"),
+            false => base,
+        };
+        std::iter::once(base)
+            .chain(constraints)
+            .collect::<Vec<_>>()
+            .join(" & ")
+    }
+
+    /// A struct field on its own line(s) at `indent`
+    fn field(&mut self, name: &str, schema: &SchemaType, optional: bool, indent: &str) -> String {
+        let mut ty = self.reference(schema, indent);
+        let mut marker = if optional { "?" } else { "" };
+        if let Some(default) = &schema.default {
+            let ty_alone = match ty.contains(" | ") {
+                true => format!("({ty})"),
+                false => ty,
+            };
+            ty = format!("*{} | {}", cue_value(default), ty_alone);
+            // Absent fields take the default, so there always is one
+            marker = "";
+        }
+        let mut lines = comment(self.use_site_docs(schema).as_deref(), indent);
+        lines.push_str(&format!("{indent}{}{marker}: {ty}\n", label(name)));
+        lines
+    }
+
+    /// A variant case as the JSON it's written as
+    fn case(&mut self, case: &VariantCase, tagging: Option<&Tagging>, indent: &str) -> String {
+        let inner = format!("{indent}\t");
+        let name = literal(&case.name);
+        let docs = comment(case.description.as_deref(), &inner);
+        let with_docs = |mut fields: Vec<String>| {
+            fields.insert(0, docs.clone());
+            block(fields, indent)
+        };
+
+        match (tagging, &case.data) {
+            (None, None) => name,
+            (None, Some(data)) => {
+                let data = self.reference(data, &inner);
+                with_docs(vec![
+                    format!("{inner}type: {name}\n"),
+                    format!("{inner}data: {data}\n"),
+                ])
+            }
+            (Some(Tagging::Internal { tag }) | Some(Tagging::Adjacent { tag, .. }), None) => {
+                with_docs(vec![format!("{inner}{}: {name}\n", label(tag))])
+            }
+            (Some(Tagging::Internal { tag }), Some(data)) => {
+                let tag = format!("{inner}{}: {name}\n", label(tag));
+                match (&data.kind, &data.name) {
+                    // An anonymous record's fields sit beside the tag
+                    (TypeKind::Object { required, .. }, None) => {
+                        let mut fields = vec![tag];
+                        for (field, property) in data.ordered_properties() {
+                            let optional = !required.iter().any(|r| r == field);
+                            fields.push(self.field(field, property, optional, &inner));
+                        }
+                        if !data.denies_unknown_fields() {
+                            fields.push(format!("{inner}...\n"));
+                        }
+                        with_docs(fields)
+                    }
+                    _ => format!(
+                        "{} & {}",
+                        self.reference(data, indent),
+                        with_docs(vec![tag])
+                    ),
+                }
+            }
+            (Some(Tagging::Adjacent { tag, content }), Some(data)) => {
+                let data = self.reference(data, &inner);
+                with_docs(vec![
+                    format!("{inner}{}: {name}\n", label(tag)),
+                    format!("{inner}{}: {data}\n", label(content)),
+                ])
+            }
+            (Some(Tagging::Untagged), None) => "null".to_string(),
+            (Some(Tagging::Untagged), Some(data)) => self.reference(data, indent),
+        }
+    }
+
+    /// Constraint expressions to conjoin with the type
+    fn constraints(&mut self, schema: &SchemaType) -> Vec<String> {
+        let constraints = &schema.constraints;
+        let mut parts = vec![];
+        match &schema.kind {
+            TypeKind::String => {
+                if let Some(min) = constraints.min_length {
+                    self.imports.insert("strings");
+                    parts.push(format!("strings.MinRunes({min})"));
+                }
+                if let Some(max) = constraints.max_length {
+                    self.imports.insert("strings");
+                    parts.push(format!("strings.MaxRunes({max})"));
+                }
+                if let Some(pattern) = &constraints.pattern {
+                    parts.push(literal_pattern(pattern));
+                }
+            }
+            TypeKind::Integer(_) | TypeKind::Number(_) => {
+                if let Some(min) = constraints.minimum {
+                    parts.push(format!(">={}", number(min)));
+                }
+                if let Some(max) = constraints.maximum {
+                    parts.push(format!("<={}", number(max)));
+                }
+            }
+            TypeKind::Array { .. } | TypeKind::Set { .. } => {
+                if let Some(min) = constraints.min_items {
+                    parts.push(format!("list.MinItems({min})"));
+                }
+                if let Some(max) = constraints.max_items {
+                    parts.push(format!("list.MaxItems({max})"));
+                }
+                if constraints.unique_items || matches!(schema.kind, TypeKind::Set { .. }) {
+                    parts.push("list.UniqueItems()".to_string());
+                }
+                if !parts.is_empty() {
+                    self.imports.insert("list");
+                }
+            }
+            _ => {}
+        }
+        parts
+    }
+
+    /// The docs for a field, unless its type's definition already has them
+    fn use_site_docs(&self, schema: &SchemaType) -> Option<String> {
+        schema.docs().filter(|docs| {
+            let declared = schema.name.as_ref().and_then(|name| {
+                self.named
+                    .iter()
+                    .find(|named| named.name == *name && named.shape == shape(schema))
+            });
+            declared.is_none_or(|named| named.first.docs().as_ref() != Some(docs))
+        })
+    }
+}
+
+/// `{`, the lines, and a `}` at `indent`
+fn block(lines: Vec<String>, indent: &str) -> String {
+    match lines.concat() {
+        body if body.is_empty() => "{}".to_string(),
+        body => format!("{{\n{body}{indent}}}"),
+    }
+}
+
+fn disjunction(values: impl Iterator<Item = String>) -> String {
+    values.collect::<Vec<_>>().join(" | ")
+}
+
+/// The predeclared CUE type bounding each integer kind
+fn integer(kind: &IntegerKind) -> &'static str {
+    match kind {
+        IntegerKind::I32 => "int32",
+        IntegerKind::I64 => "int64",
+        IntegerKind::U8 => "uint8",
+        IntegerKind::U32 => "uint32",
+        IntegerKind::U64 | IntegerKind::Usize => "uint64",
+    }
+}
+
+/// A bound without a trailing `.0` when it's whole
+fn number(n: f64) -> String {
+    match n.fract() == 0.0 && n.abs() < 1e15 {
+        true => format!("{}", n as i64),
+        false => n.to_string(),
+    }
+}
+
+/// A JSON value as a CUE value; the two agree on everything JSON has
+fn cue_value(value: &Value) -> String {
+    value.to_string()
+}
+
+/// A CUE string literal
+fn literal(s: &str) -> String {
+    Value::from(s).to_string()
+}
+
+/// A `=~` regular expression match
+fn literal_pattern(pattern: &str) -> String {
+    format!("=~{}", literal(pattern))
+}
+
+/// A field label, quoted unless it's a plain identifier; `_` and `#` start
+/// hidden fields and definitions, so those are quoted too. A quoted label
+/// can't be referenced, so it doesn't shadow a type or package of its name
+fn label(name: &str) -> String {
+    match is_identifier(name) && !KEYWORDS.contains(&name) && !SHADOWED.contains(&name) {
+        true => name.to_string(),
+        false => literal(name),
+    }
+}
+
+/// Words that can't be used as unquoted labels
+const KEYWORDS: &[&str] = &[
+    "package", "import", "for", "in", "if", "let", "true", "false", "null",
+];
+
+/// The predeclared types and packages the definitions refer to
+const SHADOWED: &[&str] = &[
+    "string", "int32", "int64", "uint8", "uint32", "uint64", "float32", "float64", "bool",
+    "strings", "list", "time",
+];
+
+/// A definition name: letters, digits and `_`, starting with a letter
+fn identifier(name: &str) -> String {
+    let ident: String = Case::Pascal
+        .apply(name)
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric() || *c == '_')
+        .collect();
+    match is_identifier(&ident) {
+        true => ident,
+        false => format!("T{ident}"),
+    }
+}
+
+fn is_identifier(s: &str) -> bool {
+    s.starts_with(|c: char| c.is_ascii_alphabetic())
+        && s.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// `//` lines for `docs` at `indent`, ending in a newline
+fn comment(docs: Option<&str>, indent: &str) -> String {
+    let Some(docs) = docs else {
+        return String::new();
+    };
+    docs.lines()
+        .map(|line| match line {
+            "" => format!("{indent}//\n"),
+            line => format!("{indent}// {line}\n"),
+        })
+        .collect()
+}
+
+/// What makes two occurrences of a named type the same definition: its
+/// fingerprint without the annotations a field adds to it
+fn shape(schema: &SchemaType) -> String {
+    let mut bare = schema.clone();
+    bare.title = None;
+    bare.description = None;
+    bare.default = None;
+    for key in [
+        schema::DEPRECATED,
+        schema::READ_ONLY,
+        schema::WRITE_ONLY,
+        schema::SINCE,
+        schema::REF_PREFERENCE,
+    ] {
+        bare.extensions.remove(key);
+    }
+    bare.fingerprint()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn test_recursive_definitions() {
+        /// A directory tree
+        #[derive(Schema)]
+        #[allow(dead_code)]
+        struct Node {
+            #[schema(rename = "_id")]
+            id: u64,
+            #[schema(rename = "if")]
+            condition: Option<String>,
+            children: Vec<Node>,
+            parent: Option<Box<Node>>,
+            // A field named for a type would shadow it
+            string: String,
+            #[schema(min_items = 1)]
+            list: Vec<String>,
+        }
+
+        assert_eq!(
+            to_cue::<Node>(&CueOptions::new()).unwrap(),
+            r#"import "list"
+
+// A directory tree
+#Node: {
+	"_id": uint64
+	"if"?: string
+	children: [...#Node]
+	parent?: #Node
+	"string": string
+	"list": [...string] & list.MinItems(1)
+	...
+}
+"#
+        );
+    }
+
+    #[test]
+    fn test_constraints_and_closed_structs() {
+        fn timestamp() -> SchemaType {
+            let mut schema = SchemaType::new(TypeKind::String);
+            schema.constraints.format = Some("date-time".to_string());
+            schema
+        }
+
+        fn default_source() -> String {
+            "app".to_string()
+        }
+
+        #[derive(Schema)]
+        #[allow(dead_code)]
+        enum Level {
+            Info,
+            Warn,
+        }
+
+        /// One log line
+        #[derive(Schema)]
+        #[allow(dead_code)]
+        #[schema(deny_unknown_fields)]
+        struct Entry {
+            #[schema(with = "timestamp")]
+            at: String,
+            #[schema(min_length = 1, max_length = 80, pattern = "^[a-z]")]
+            message: String,
+            #[schema(minimum = 0.5, maximum = 100)]
+            weight: f32,
+            level: Level,
+            #[schema(default = "default_source")]
+            source: String,
+            counts: BTreeMap<u32, u8>,
+            seen: BTreeSet<String>,
+            span: (u32, u32),
+            outcome: Result<u32, String>,
+        }
+
+        let options = CueOptions::new().package("logs");
+        assert_eq!(
+            to_cue::<Entry>(&options).unwrap(),
+            r#"package logs
+
+import (
+	"list"
+	"strings"
+	"time"
+)
+
+// One log line
+#Entry: {
+	at: time.Time
+	message: string & strings.MinRunes(1) & strings.MaxRunes(80) & =~"^[a-z]"
+	weight: float32 & >=0.5 & <=100
+	level: #Level
+	source: *"app" | string
+	// Ordered map/dictionary of key-value pairs
+	counts: {[=~"^-?[0-9]+$"]: uint8}
+	// Ordered set of unique values
+	seen: [...string] & list.UniqueItems()
+	span: [uint32, uint32]
+	outcome: {
+		ok: uint32
+	} | {
+		error: string
+	}
+}
+
+#Level: "Info" | "Warn"
+"#
+        );
+    }
+
+    #[test]
+    fn test_variant_taggings() {
+        #[derive(Schema)]
+        #[allow(dead_code)]
+        enum Shape {
+            /// No area
+            Dot,
+            Circle {
+                radius: f64,
+            },
+            Line(f64),
+        }
+
+        let mut schema = Shape::schema();
+        let cue = |schema: &SchemaType| schema_type_to_cue(schema, None, &CueOptions::new());
+        assert_eq!(
+            cue(&schema).unwrap(),
+            "#Shape: \"Dot\" | {\n\ttype: \"Circle\"\n\tdata: {\n\t\tradius: float64\n\t\t...\n\t}\n} | {\n\ttype: \"Line\"\n\tdata: float64\n}\n"
+        );
+
+        schema.set_tagging(Tagging::Internal {
+            tag: "kind".to_string(),
+        });
+        assert_eq!(
+            cue(&schema).unwrap(),
+            "#Shape: {\n\t// No area\n\tkind: \"Dot\"\n} | {\n\tkind: \"Circle\"\n\tradius: float64\n\t...\n} | float64 & {\n\tkind: \"Line\"\n}\n"
+        );
+
+        schema.set_tagging(Tagging::Adjacent {
+            tag: "kind".to_string(),
+            content: "value".to_string(),
+        });
+        assert!(
+            cue(&schema)
+                .unwrap()
+                .contains("{\n\tkind: \"Line\"\n\tvalue: float64\n}")
+        );
+    }
+
+    #[test]
+    fn test_invalid_package() {
+        assert_eq!(
+            to_cue::<String>(&CueOptions::new().package("my-team")),
+            Err(CueError::InvalidPackage("my-team".to_string()))
+        );
+    }
+}
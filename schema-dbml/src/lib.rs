@@ -0,0 +1,533 @@
+//! DBML (dbdiagram.io) models from schemas
+//!
+//! Where [`schema_sql`] writes one table per object schema, this lays out
+//! the relations between them: every named object type reachable from the
+//! root gets a table of its own, keyed by its `id` property or a generated
+//! `id bigint [pk, increment]`. A field holding another table's type becomes
+//! a `<field>_id` foreign key, and a list of them a `<table>_id` foreign key
+//! on the other table, each with a `Ref`. Anonymous nested objects are
+//! flattened or stored as JSON as [`NestedObjects`] says, enums become DBML
+//! `Enum`s, and other composite values are `jsonb`. Column types follow the
+//! Postgres DDL.
+
+use schema::case::Case;
+use schema::{
+    GenerateOptions, IntegerKind, NumberKind, RefPreference, Schema, SchemaType, TypeKind,
+};
+use serde_json::Value;
+use std::fmt;
+
+pub use schema_sql::NestedObjects;
+
+/// Words DBML reads as keywords, which names are quoted to avoid
+const KEYWORDS: &[&str] = &[
+    "enum",
+    "indexes",
+    "note",
+    "project",
+    "ref",
+    "table",
+    "tablegroup",
+];
+
+/// Options for the generated model
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DbmlOptions {
+    /// Where the properties of anonymous nested objects go
+    pub nested: NestedObjects,
+    pub generate: GenerateOptions,
+}
+
+impl DbmlOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn nested(mut self, nested: NestedObjects) -> Self {
+        self.nested = nested;
+        self
+    }
+
+    /// Bound the schema before it is written
+    pub fn generate(mut self, generate: GenerateOptions) -> Self {
+        self.generate = generate;
+        self
+    }
+}
+
+/// Why a schema has no DBML model
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DbmlError {
+    /// The schema is not an object, so it has no table
+    NotAnObject,
+}
+
+impl fmt::Display for DbmlError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DbmlError::NotAnObject => write!(f, "only object schemas map to DBML tables"),
+        }
+    }
+}
+
+impl std::error::Error for DbmlError {}
+
+/// The model for `T` and the object types it contains, its table named
+/// after `T` in snake_case
+pub fn to_dbml<T: Schema>(options: &DbmlOptions) -> Result<String, DbmlError> {
+    let table = Case::Snake.apply(T::type_name().unwrap_or("root"));
+    schema_type_to_dbml(&T::schema(), &table, options)
+}
+
+/// The model for an object SchemaType, its table named `table`
+pub fn schema_type_to_dbml(
+    schema: &SchemaType,
+    table: &str,
+    options: &DbmlOptions,
+) -> Result<String, DbmlError> {
+    let schema = options.generate.apply(schema);
+    if !matches!(schema.kind, TypeKind::Object { .. }) {
+        return Err(DbmlError::NotAnObject);
+    }
+
+    let mut model = Model {
+        options,
+        named: vec![],
+        tables: vec![],
+        enums: vec![],
+        refs: vec![],
+    };
+    model.table(table, &schema);
+    Ok(model.render())
+}
+
+/// A named object type that has a table
+struct Named {
+    name: String,
+    /// Fingerprint without use-site annotations
+    shape: String,
+    table: usize,
+}
+
+struct Table {
+    name: String,
+    /// Type of the `id` column other tables' foreign keys hold
+    key: String,
+    columns: Vec<Column>,
+    docs: Option<String>,
+}
+
+struct Column {
+    name: String,
+    ty: String,
+    /// `pk`, `increment`, `not null`, `default: ...` and `note: ...`
+    settings: Vec<String>,
+}
+
+struct Model<'a> {
+    options: &'a DbmlOptions,
+    named: Vec<Named>,
+    tables: Vec<Table>,
+    /// `Enum` definitions, by type name
+    enums: Vec<(String, Vec<String>)>,
+    /// `Ref: a.b > c.d` lines
+    refs: Vec<String>,
+}
+
+impl Model<'_> {
+    /// Add the table `name` for an object and the tables it relates to;
+    /// returns its index
+    fn table(&mut self, name: &str, object: &SchemaType) -> usize {
+        let name = (1..)
+            .map(|n| match n {
+                1 => name.to_string(),
+                n => format!("{name}_{n}"),
+            })
+            .find(|name| self.tables.iter().all(|table| table.name != *name))
+            .unwrap();
+
+        // A scalar `id` property is the key; otherwise one is generated
+        let id = match &object.kind {
+            TypeKind::Object { properties, .. } => properties.get("id"),
+            _ => None,
+        };
+        let (key, columns) = match id.map(|id| self.scalar(id)) {
+            Some(Some(key)) => (key, vec![]),
+            Some(None) => ("bigint".to_string(), vec![]),
+            None => {
+                let id = Column {
+                    name: "id".to_string(),
+                    ty: "bigint".to_string(),
+                    settings: vec!["pk".to_string(), "increment".to_string()],
+                };
+                ("bigint".to_string(), vec![id])
+            }
+        };
+
+        let index = self.tables.len();
+        self.tables.push(Table {
+            name,
+            key,
+            columns,
+            docs: object.docs(),
+        });
+        if let Some(name) = &object.name {
+            self.named.push(Named {
+                name: name.clone(),
+                shape: shape(object),
+                table: index,
+            });
+        }
+        self.object(index, object, "", true);
+        index
+    }
+
+    /// Columns for an object's properties, prefixed with `prefix`;
+    /// `present` is whether the object itself is always there
+    fn object(&mut self, table: usize, object: &SchemaType, prefix: &str, present: bool) {
+        let TypeKind::Object { required, .. } = &object.kind else {
+            return;
+        };
+        for (name, property) in object.ordered_properties() {
+            let column = format!("{prefix}{name}");
+            let not_null = present && required.iter().any(|r| r == name);
+
+            if let Some(target) = self.related(property) {
+                let column = format!("{column}_id");
+                let from = format!("{}.{}", self.tables[table].name, column);
+                self.foreign_key(table, &column, target, not_null, property.docs());
+                self.relate(&from, target);
+                continue;
+            }
+            match &property.kind {
+                TypeKind::Array { items } | TypeKind::Set { items, .. } => {
+                    if let Some(child) = self.related(items) {
+                        // The other side holds the key back to this table
+                        let column = format!("{}_id", self.tables[table].name);
+                        let from = format!("{}.{}", self.tables[child].name, column);
+                        if !self.tables[child].columns.iter().any(|c| c.name == column) {
+                            self.foreign_key(child, &column, table, false, None);
+                        }
+                        self.relate(&from, table);
+                        continue;
+                    }
+                }
+                TypeKind::Object { .. } if self.options.nested == NestedObjects::Flatten => {
+                    self.object(table, property, &format!("{column}_"), not_null);
+                    continue;
+                }
+                _ => {}
+            }
+            self.column(table, column, not_null, property);
+        }
+    }
+
+    /// The table for `schema` if it is a named object type (or refers to
+    /// one), adding it on first use
+    fn related(&mut self, schema: &SchemaType) -> Option<usize> {
+        if let TypeKind::Ref { name } = &schema.kind {
+            return self
+                .named
+                .iter()
+                .find(|named| named.name == *name)
+                .map(|named| named.table);
+        }
+        let name = schema.name.as_ref()?;
+        if !matches!(schema.kind, TypeKind::Object { .. })
+            || schema.ref_preference() == Some(RefPreference::Inline)
+        {
+            return None;
+        }
+
+        let shape = shape(schema);
+        if let Some(named) = self
+            .named
+            .iter()
+            .find(|named| named.name == *name && named.shape == shape)
+        {
+            return Some(named.table);
+        }
+        Some(self.table(&Case::Snake.apply(name), schema))
+    }
+
+    /// A column on `table` holding the key of `target`
+    fn foreign_key(
+        &mut self,
+        table: usize,
+        column: &str,
+        target: usize,
+        not_null: bool,
+        docs: Option<String>,
+    ) {
+        let mut settings = vec![];
+        if not_null {
+            settings.push("not null".to_string());
+        }
+        if let Some(docs) = docs {
+            settings.push(format!("note: {}", string(&docs)));
+        }
+        let ty = self.tables[target].key.clone();
+        self.tables[table].columns.push(Column {
+            name: column.to_string(),
+            ty,
+            settings,
+        });
+    }
+
+    /// A many-to-one `Ref` from the column `from` to `target`'s `id`
+    fn relate(&mut self, from: &str, target: usize) {
+        let reference = format!(
+            "Ref: {} > {}.id",
+            qualified(from),
+            identifier(&self.tables[target].name)
+        );
+        if !self.refs.contains(&reference) {
+            self.refs.push(reference);
+        }
+    }
+
+    fn column(&mut self, table: usize, name: String, not_null: bool, schema: &SchemaType) {
+        let ty = match &schema.kind {
+            TypeKind::Enum { variants } => {
+                let ty = match &schema.name {
+                    Some(enum_name) => Case::Snake.apply(enum_name),
+                    None => format!("{}_{}", self.tables[table].name, name),
+                };
+                if !self.enums.iter().any(|(existing, _)| *existing == ty) {
+                    self.enums.push((ty.clone(), variants.clone()));
+                }
+                identifier(&ty)
+            }
+            // Everything else is stored as its JSON encoding
+            _ => self.scalar(schema).unwrap_or_else(|| "jsonb".to_string()),
+        };
+
+        let mut settings = vec![];
+        if name == "id" && self.tables[table].columns.iter().all(|c| c.name != "id") {
+            settings.push("pk".to_string());
+        } else if not_null {
+            settings.push("not null".to_string());
+        }
+        let default = schema.default.as_ref().and_then(|default| match default {
+            Value::String(s) => Some(string(s)),
+            Value::Number(n) => Some(n.to_string()),
+            Value::Bool(b) => Some(b.to_string()),
+            Value::Array(_) | Value::Object(_) => Some(string(&default.to_string())),
+            Value::Null => None,
+        });
+        if let Some(default) = default {
+            settings.push(format!("default: {default}"));
+        }
+        if let Some(docs) = schema.docs() {
+            settings.push(format!("note: {}", string(&docs)));
+        }
+
+        self.tables[table]
+            .columns
+            .push(Column { name, ty, settings });
+    }
+
+    /// The column type for a scalar, following the Postgres DDL
+    fn scalar(&self, schema: &SchemaType) -> Option<String> {
+        let ty = match &schema.kind {
+            TypeKind::String => match schema.constraints.max_length {
+                Some(max) => format!("varchar({max})"),
+                None => "text".to_string(),
+            },
+            TypeKind::Integer(kind) => integer(kind).to_string(),
+            TypeKind::Number(NumberKind::F32) => "real".to_string(),
+            TypeKind::Number(NumberKind::F64) => "\"double precision\"".to_string(),
+            TypeKind::Boolean => "boolean".to_string(),
+            _ => return None,
+        };
+        Some(ty)
+    }
+
+    fn render(&self) -> String {
+        let mut blocks = vec![];
+        for (name, values) in &self.enums {
+            let values: String = values
+                .iter()
+                .map(|value| format!("  {}\n", identifier(value)))
+                .collect();
+            blocks.push(format!("Enum {} {{\n{values}}}\n", identifier(name)));
+        }
+        for table in &self.tables {
+            let mut block = format!("Table {} {{\n", identifier(&table.name));
+            for column in &table.columns {
+                block.push_str(&format!("  {} {}", identifier(&column.name), column.ty));
+                if !column.settings.is_empty() {
+                    block.push_str(&format!(" [{}]", column.settings.join(", ")));
+                }
+                block.push('\n');
+            }
+            if let Some(docs) = &table.docs {
+                block.push_str(&format!("\n  Note: {}\n", string(docs)));
+            }
+            block.push_str("}\n");
+            blocks.push(block);
+        }
+        if !self.refs.is_empty() {
+            blocks.push(self.refs.iter().map(|r| format!("{r}\n")).collect());
+        }
+        blocks.join("\n")
+    }
+}
+
+/// Postgres integer types; it has no unsigned ones, and u64 needs 20 digits
+fn integer(kind: &IntegerKind) -> &'static str {
+    match kind {
+        IntegerKind::I32 => "integer",
+        IntegerKind::U8 => "smallint",
+        IntegerKind::I64 | IntegerKind::U32 => "bigint",
+        IntegerKind::U64 | IntegerKind::Usize => "numeric(20,0)",
+    }
+}
+
+/// A DBML string, triple-quoted when it spans lines
+fn string(value: &str) -> String {
+    let escaped = value.replace('\\', "\\\\").replace('\'', "\\'");
+    match value.contains('\n') {
+        true => format!("'''{escaped}'''"),
+        false => format!("'{escaped}'"),
+    }
+}
+
+/// A table, column, enum or value name, double-quoted unless it's a plain
+/// word that isn't a keyword
+fn identifier(name: &str) -> String {
+    let plain = name.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+        && !KEYWORDS.contains(&name.to_ascii_lowercase().as_str());
+    match plain {
+        true => name.to_string(),
+        false => format!("\"{}\"", name.replace('\\', "\\\\").replace('"', "\\\"")),
+    }
+}
+
+/// `table.column` with each part quoted as needed
+fn qualified(path: &str) -> String {
+    let (table, column) = path.split_once('.').unwrap_or((path, ""));
+    format!("{}.{}", identifier(table), identifier(column))
+}
+
+/// What makes two occurrences of a named type the same table: its
+/// fingerprint without the annotations a field adds to it
+fn shape(schema: &SchemaType) -> String {
+    let mut bare = schema.clone();
+    bare.title = None;
+    bare.description = None;
+    bare.default = None;
+    for key in [
+        schema::DEPRECATED,
+        schema::READ_ONLY,
+        schema::WRITE_ONLY,
+        schema::SINCE,
+        schema::REF_PREFERENCE,
+    ] {
+        bare.extensions.remove(key);
+    }
+    bare.fingerprint()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Schema)]
+    #[allow(dead_code)]
+    enum Status {
+        Open,
+        Shipped,
+    }
+
+    #[derive(Schema)]
+    #[allow(dead_code)]
+    struct Customer {
+        id: u32,
+        #[schema(max_length = 120)]
+        email: String,
+    }
+
+    #[derive(Schema)]
+    #[allow(dead_code)]
+    struct Line {
+        sku: String,
+        quantity: u32,
+    }
+
+    /// A customer's order
+    #[derive(Schema)]
+    #[allow(dead_code)]
+    struct Order {
+        status: Status,
+        customer: Customer,
+        /// Set once the order is paid
+        paid_at: Option<String>,
+        lines: Vec<Line>,
+        tags: Vec<String>,
+        #[schema(default)]
+        gift: bool,
+    }
+
+    #[test]
+    fn test_tables_and_refs() {
+        assert_eq!(
+            to_dbml::<Order>(&DbmlOptions::new()).unwrap(),
+            r#"Enum status {
+  Open
+  Shipped
+}
+
+Table order {
+  id bigint [pk, increment]
+  status status [not null]
+  customer_id bigint [not null]
+  paid_at text [note: 'Set once the order is paid']
+  tags jsonb [not null]
+  gift boolean [default: false]
+
+  Note: 'A customer\'s order'
+}
+
+Table customer {
+  id bigint [pk]
+  email varchar(120) [not null]
+}
+
+Table line {
+  id bigint [pk, increment]
+  sku text [not null]
+  quantity bigint [not null]
+  order_id bigint
+}
+
+Ref: order.customer_id > customer.id
+Ref: line.order_id > order.id
+"#
+        );
+    }
+
+    #[test]
+    fn test_recursive_and_errors() {
+        #[derive(Schema)]
+        #[allow(dead_code)]
+        struct Category {
+            name: String,
+            parent: Option<Box<Category>>,
+            children: Vec<Category>,
+        }
+
+        let dbml = to_dbml::<Category>(&DbmlOptions::new()).unwrap();
+        assert!(dbml.contains("  parent_id bigint\n"));
+        assert!(dbml.contains("  category_id bigint\n"));
+        assert!(dbml.contains(
+            "Ref: category.parent_id > category.id\nRef: category.category_id > category.id\n"
+        ));
+
+        assert_eq!(
+            to_dbml::<Vec<String>>(&DbmlOptions::new()),
+            Err(DbmlError::NotAnObject)
+        );
+    }
+}
@@ -0,0 +1,632 @@
+//! Parsing of `#[schema(...)]` attributes
+
+use crate::constraints::ConstraintAttrs;
+use crate::rename::RenameRule;
+use proc_macro2::Span;
+use syn::meta::ParseNestedMeta;
+use syn::punctuated::Punctuated;
+use syn::spanned::Spanned;
+use syn::{Attribute, Expr, ExprLit, ExprPath, Lit, LitBool, LitStr, Meta};
+
+/// Options from `#[schema(...)]` on a struct or enum
+pub struct ContainerAttrs {
+    /// `#[schema(title = "...")]`: takes precedence over doc comments
+    pub title: Option<String>,
+    /// `#[schema(description = "...")]`: takes precedence over doc comments
+    pub description: Option<String>,
+    /// `#[schema(title_from_docs = false)]` keeps multi-paragraph doc comments
+    /// whole instead of using the first paragraph as the title
+    pub title_from_docs: bool,
+    /// `#[schema(wit(name = "..."))]`: WIT type name override
+    pub wit_name: Option<String>,
+    /// `#[schema(transparent)]`: a single-field struct has its field's schema
+    ///
+    /// Flags hold the attribute's span, for errors about where they're used.
+    pub transparent: Option<Span>,
+    /// `#[schema(tag = "...")]`: internally tagged enum, like serde's `tag`;
+    /// on a struct with named fields, a property holding the struct's name
+    pub tag: Option<LitStr>,
+    /// `#[schema(content = "...")]`: with `tag`, adjacently tagged
+    pub content: Option<LitStr>,
+    /// `#[schema(untagged)]`: cases are written as bare payloads
+    pub untagged: Option<Span>,
+    /// `#[schema(bound = "T: Schema")]`: where-clause predicates replacing
+    /// the inferred `Schema` bounds; `bound = ""` adds none
+    pub bound: Option<Vec<syn::WherePredicate>>,
+    /// `#[schema(version = "2.1.0")]`: the type's own semantic version
+    pub version: Option<String>,
+    /// `#[schema(emit_json_const)]`: also generate a `JSON_SCHEMA` constant
+    pub emit_json_const: Option<Span>,
+    /// `#[schema(partial)]`: also generate `partial_schema()`
+    pub partial: Option<Span>,
+    /// `#[schema(deny_unknown_fields)]`: objects reject undeclared properties
+    pub deny_unknown_fields: bool,
+    /// Rust's `#[deprecated]` on the type
+    pub deprecated: Option<Deprecated>,
+    /// `#[schema(extension("x-key" = value))]`: vendor extensions, with
+    /// values in `serde_json::json!` syntax
+    pub extensions: Vec<(String, proc_macro2::TokenStream)>,
+    /// `#[schema(example = value)]`, repeatable: example values in
+    /// `serde_json::json!` syntax
+    pub examples: Vec<proc_macro2::TokenStream>,
+    /// `#[schema(inline)]` or `#[schema(as_ref)]`: default sharing of the
+    /// type wherever it's used, when normalizing
+    pub ref_preference: Option<RefPreference>,
+    /// `#[schema(legacy_tagged_union)]`: emit the flattened `TaggedUnion`
+    /// instead of a `Variant`
+    pub legacy_tagged_union: Option<Span>,
+    /// serde's `rename_all`: renames a struct's fields or an enum's variants
+    pub rename_all: Option<RenameRule>,
+    /// `#[schema(rename_all_variants = "snake_case")]`: renames an enum's
+    /// variants, taking precedence over `rename_all`
+    pub rename_all_variants: Option<(RenameRule, Span)>,
+    /// serde's `rename_all_fields`: renames the fields of every struct variant
+    pub rename_all_fields: Option<RenameRule>,
+    /// serde's container `rename`: the name a tagged struct writes under
+    /// its tag
+    pub rename: Option<String>,
+    /// serde's container `default`: every field may be omitted
+    pub default: bool,
+}
+
+/// Options from `#[schema(...)]` on a field or enum variant
+#[derive(Default)]
+pub struct FieldAttrs {
+    /// `#[schema(skip)]`: leave the field out of the schema
+    pub skip: bool,
+    /// `#[schema(flatten)]`: merge an object field's properties into the parent
+    pub flatten: bool,
+    /// `#[schema(default)]` or `#[schema(default = "path")]`
+    pub default: Option<FieldDefault>,
+    /// `#[schema(with = "path")]`: a `fn() -> SchemaType` used instead of the
+    /// field type's `Schema` impl
+    pub with: Option<ExprPath>,
+    /// `#[schema(rename = "...")]`: emitted property or case name
+    pub rename: Option<String>,
+    /// serde's `rename_all` on a struct variant: renames its fields
+    pub rename_all: Option<RenameRule>,
+    /// `#[schema(title = "...")]`: takes precedence over doc comments
+    pub title: Option<String>,
+    /// `#[schema(description = "...")]`: takes precedence over doc comments
+    pub description: Option<String>,
+    /// `#[schema(wit(name = "..."))]`: WIT field name override
+    pub wit_name: Option<String>,
+    /// `#[schema(read_only)]`: only present in responses
+    pub read_only: bool,
+    /// `#[schema(write_only)]`: only present in requests
+    pub write_only: bool,
+    /// `#[schema(since = "1.4.0")]`: version the field or variant was added in
+    pub since: Option<String>,
+    /// Rust's `#[deprecated]` on the field or variant
+    pub deprecated: Option<Deprecated>,
+    /// `#[schema(extension("x-key" = value))]`: vendor extensions, with
+    /// values in `serde_json::json!` syntax
+    pub extensions: Vec<(String, proc_macro2::TokenStream)>,
+    /// `#[schema(example = value)]`, repeatable: example values in
+    /// `serde_json::json!` syntax
+    pub examples: Vec<proc_macro2::TokenStream>,
+    /// `#[schema(inline)]` or `#[schema(as_ref)]`: sharing of this use of
+    /// the field's type, overriding the type's own default
+    pub ref_preference: Option<RefPreference>,
+    /// `#[schema(minimum = 0, max_length = 64, pattern = "...")]` and
+    /// validation crates' rules
+    pub constraints: ConstraintAttrs,
+}
+
+/// `#[schema(inline)]` or `#[schema(as_ref)]`
+#[derive(Clone, Copy)]
+pub enum RefPreference {
+    Inline,
+    AsRef,
+}
+
+impl RefPreference {
+    /// Parse `inline` or `as_ref`; returns false for other keys
+    fn parse(meta: &ParseNestedMeta, slot: &mut Option<Self>) -> syn::Result<bool> {
+        let preference = if meta.path.is_ident("inline") {
+            RefPreference::Inline
+        } else if meta.path.is_ident("as_ref") {
+            RefPreference::AsRef
+        } else {
+            return Ok(false);
+        };
+        if slot.is_some() {
+            return Err(meta.error("only one of `inline` and `as_ref` may be set"));
+        }
+        *slot = Some(preference);
+        Ok(true)
+    }
+
+    /// Statement recording the preference on a `schema` variable
+    pub fn statement(self) -> proc_macro2::TokenStream {
+        let variant = match self {
+            RefPreference::Inline => quote::quote!(Inline),
+            RefPreference::AsRef => quote::quote!(AsRef),
+        };
+        quote::quote! { schema.set_ref_preference(schema::RefPreference::#variant); }
+    }
+}
+
+/// A `#[deprecated]` attribute
+pub struct Deprecated {
+    /// `#[deprecated = "..."]` or `#[deprecated(note = "...")]`
+    pub note: Option<String>,
+}
+
+impl Deprecated {
+    /// The first `#[deprecated]` in `attrs`, if any
+    pub fn parse(attrs: &[Attribute]) -> syn::Result<Option<Self>> {
+        let Some(attr) = attrs.iter().find(|attr| attr.path().is_ident("deprecated")) else {
+            return Ok(None);
+        };
+        let note = match &attr.meta {
+            Meta::Path(_) => None,
+            Meta::NameValue(meta) => match &meta.value {
+                Expr::Lit(ExprLit {
+                    lit: Lit::Str(note),
+                    ..
+                }) => Some(note.value()),
+                value => return Err(syn::Error::new_spanned(value, "expected a string note")),
+            },
+            Meta::List(_) => {
+                let mut note = None;
+                attr.parse_nested_meta(|meta| {
+                    let value = string_value(&meta)?;
+                    if meta.path.is_ident("note") {
+                        note = Some(value);
+                    }
+                    Ok(())
+                })?;
+                note
+            }
+        };
+        Ok(Some(Deprecated { note }))
+    }
+
+    /// `Option<&str>` expression for the note
+    pub fn note_expr(&self) -> proc_macro2::TokenStream {
+        match &self.note {
+            Some(note) => quote::quote! { Some(#note) },
+            None => quote::quote! { None },
+        }
+    }
+}
+
+/// Where a field's default value comes from
+pub enum FieldDefault {
+    /// `Default::default()`
+    Trait,
+    /// A function returning the default, like serde's `default = "path"`
+    Path(ExprPath),
+}
+
+impl Default for ContainerAttrs {
+    fn default() -> Self {
+        ContainerAttrs {
+            title: None,
+            description: None,
+            title_from_docs: true,
+            wit_name: None,
+            transparent: None,
+            tag: None,
+            content: None,
+            untagged: None,
+            bound: None,
+            version: None,
+            emit_json_const: None,
+            partial: None,
+            deny_unknown_fields: false,
+            deprecated: None,
+            extensions: Vec::new(),
+            examples: Vec::new(),
+            ref_preference: None,
+            legacy_tagged_union: None,
+            rename_all: None,
+            rename_all_variants: None,
+            rename_all_fields: None,
+            rename: None,
+            default: false,
+        }
+    }
+}
+
+impl ContainerAttrs {
+    pub fn parse(attrs: &[Attribute]) -> syn::Result<Self> {
+        let mut parsed = ContainerAttrs {
+            deprecated: Deprecated::parse(attrs)?,
+            ..Default::default()
+        };
+        #[cfg(feature = "serde")]
+        parsed.parse_serde(attrs)?;
+
+        for_each_meta(attrs, |meta| {
+            if meta.path.is_ident("title") {
+                parsed.title = Some(string_value(&meta)?);
+                Ok(())
+            } else if meta.path.is_ident("title_from_docs") {
+                let value: LitBool = meta.value()?.parse()?;
+                parsed.title_from_docs = value.value;
+                Ok(())
+            } else if meta.path.is_ident("description") {
+                parsed.description = Some(string_value(&meta)?);
+                Ok(())
+            } else if meta.path.is_ident("wit") {
+                parse_wit(&meta, &mut parsed.wit_name)
+            } else if meta.path.is_ident("transparent") {
+                parsed.transparent = Some(meta.path.span());
+                Ok(())
+            } else if meta.path.is_ident("tag") {
+                parsed.tag = Some(meta.value()?.parse()?);
+                Ok(())
+            } else if meta.path.is_ident("content") {
+                parsed.content = Some(meta.value()?.parse()?);
+                Ok(())
+            } else if meta.path.is_ident("untagged") {
+                parsed.untagged = Some(meta.path.span());
+                Ok(())
+            } else if RefPreference::parse(&meta, &mut parsed.ref_preference)? {
+                Ok(())
+            } else if meta.path.is_ident("extension") {
+                parse_extensions(&meta, &mut parsed.extensions)
+            } else if meta.path.is_ident("example") {
+                parsed.examples.push(example_value(&meta)?);
+                Ok(())
+            } else if meta.path.is_ident("bound") {
+                let bound: LitStr = meta.value()?.parse()?;
+                let predicates = bound.parse_with(
+                    Punctuated::<syn::WherePredicate, syn::Token![,]>::parse_terminated,
+                )?;
+                parsed.bound = Some(predicates.into_iter().collect());
+                Ok(())
+            } else if meta.path.is_ident("version") {
+                parsed.version = Some(version_value(&meta)?);
+                Ok(())
+            } else if meta.path.is_ident("emit_json_const") {
+                parsed.emit_json_const = Some(meta.path.span());
+                Ok(())
+            } else if meta.path.is_ident("partial") {
+                parsed.partial = Some(meta.path.span());
+                Ok(())
+            } else if meta.path.is_ident("deny_unknown_fields") {
+                parsed.deny_unknown_fields = true;
+                Ok(())
+            } else if meta.path.is_ident("legacy_tagged_union") {
+                parsed.legacy_tagged_union = Some(meta.path.span());
+                Ok(())
+            } else if meta.path.is_ident("rename_all_variants") {
+                let rule: LitStr = meta.value()?.parse()?;
+                parsed.rename_all_variants = Some((RenameRule::parse(&rule)?, rule.span()));
+                Ok(())
+            } else {
+                Err(unknown_attribute(&meta, CONTAINER_KEYS))
+            }
+        })?;
+
+        if let (Some(content), None) = (&parsed.content, &parsed.tag) {
+            return Err(syn::Error::new_spanned(
+                content,
+                "`content` requires `tag`, as in #[schema(tag = \"t\", content = \"c\")]",
+            ));
+        }
+        if let (Some(untagged), Some(_)) = (parsed.untagged, &parsed.tag) {
+            return Err(syn::Error::new(
+                untagged,
+                "`untagged` can't be combined with `tag`",
+            ));
+        }
+        if let Some(legacy) = parsed.legacy_tagged_union
+            && (parsed.untagged.is_some() || parsed.content.is_some())
+        {
+            return Err(syn::Error::new(
+                legacy,
+                "`legacy_tagged_union` only supports internal tagging, not `untagged` or `content`",
+            ));
+        }
+
+        Ok(parsed)
+    }
+}
+
+impl FieldAttrs {
+    pub fn parse(attrs: &[Attribute]) -> syn::Result<Self> {
+        Self::parse_keys(attrs, FIELD_KEYS)
+    }
+
+    /// Options on an enum variant, which takes fewer keys than a field
+    pub fn parse_variant(attrs: &[Attribute]) -> syn::Result<Self> {
+        Self::parse_keys(attrs, VARIANT_KEYS)
+    }
+
+    fn parse_keys(attrs: &[Attribute], keys: &[&str]) -> syn::Result<Self> {
+        let mut parsed = FieldAttrs {
+            constraints: ConstraintAttrs::parse(attrs)?,
+            deprecated: Deprecated::parse(attrs)?,
+            ..Default::default()
+        };
+        #[cfg(feature = "serde")]
+        parsed.parse_serde(attrs)?;
+
+        for_each_meta(attrs, |meta| {
+            if !keys.iter().any(|key| meta.path.is_ident(key)) {
+                return Err(
+                    match FIELD_KEYS.iter().find(|key| meta.path.is_ident(key)) {
+                        Some(key) => meta.error(format!("`{}` is not supported on variants", key)),
+                        None => unknown_attribute(&meta, keys),
+                    },
+                );
+            }
+            if parsed.constraints.parse_schema_key(&meta)?
+                || RefPreference::parse(&meta, &mut parsed.ref_preference)?
+            {
+                Ok(())
+            } else if meta.path.is_ident("skip") {
+                parsed.skip = true;
+                Ok(())
+            } else if meta.path.is_ident("flatten") {
+                parsed.flatten = true;
+                Ok(())
+            } else if meta.path.is_ident("default") {
+                parsed.default = Some(if meta.input.peek(syn::Token![=]) {
+                    let path: LitStr = meta.value()?.parse()?;
+                    FieldDefault::Path(path.parse()?)
+                } else {
+                    FieldDefault::Trait
+                });
+                Ok(())
+            } else if meta.path.is_ident("with") {
+                let path: LitStr = meta.value()?.parse()?;
+                parsed.with = Some(path.parse()?);
+                Ok(())
+            } else if meta.path.is_ident("rename") {
+                parsed.rename = Some(string_value(&meta)?);
+                Ok(())
+            } else if meta.path.is_ident("title") {
+                parsed.title = Some(string_value(&meta)?);
+                Ok(())
+            } else if meta.path.is_ident("description") {
+                parsed.description = Some(string_value(&meta)?);
+                Ok(())
+            } else if meta.path.is_ident("wit") {
+                parse_wit(&meta, &mut parsed.wit_name)
+            } else if meta.path.is_ident("since") {
+                parsed.since = Some(version_value(&meta)?);
+                Ok(())
+            } else if meta.path.is_ident("extension") {
+                parse_extensions(&meta, &mut parsed.extensions)
+            } else if meta.path.is_ident("example") {
+                parsed.examples.push(example_value(&meta)?);
+                Ok(())
+            } else if meta.path.is_ident("read_only") || meta.path.is_ident("write_only") {
+                if parsed.read_only || parsed.write_only {
+                    return Err(meta.error("a field can't be both `read_only` and `write_only`"));
+                }
+                if meta.path.is_ident("read_only") {
+                    parsed.read_only = true;
+                } else {
+                    parsed.write_only = true;
+                }
+                Ok(())
+            } else {
+                Err(unknown_attribute(&meta, FIELD_KEYS))
+            }
+        })?;
+
+        Ok(parsed)
+    }
+}
+
+/// Keys accepted in `#[schema(...)]` on a struct or enum
+const CONTAINER_KEYS: &[&str] = &[
+    "title",
+    "title_from_docs",
+    "description",
+    "wit",
+    "transparent",
+    "tag",
+    "content",
+    "untagged",
+    "inline",
+    "as_ref",
+    "extension",
+    "example",
+    "bound",
+    "partial",
+    "emit_json_const",
+    "version",
+    "deny_unknown_fields",
+    "legacy_tagged_union",
+    "rename_all_variants",
+];
+
+/// Keys accepted in `#[schema(...)]` on a field
+const FIELD_KEYS: &[&str] = &[
+    "minimum",
+    "maximum",
+    "min_length",
+    "max_length",
+    "min_items",
+    "max_items",
+    "unique_items",
+    "pattern",
+    "inline",
+    "as_ref",
+    "skip",
+    "flatten",
+    "default",
+    "with",
+    "rename",
+    "title",
+    "description",
+    "wit",
+    "since",
+    "extension",
+    "example",
+    "read_only",
+    "write_only",
+];
+
+/// Keys accepted in `#[schema(...)]` on an enum variant
+const VARIANT_KEYS: &[&str] = &["skip", "rename", "description", "since", "extension"];
+
+/// Error for a key that isn't in `known`, suggesting the closest match for
+/// likely typos
+fn unknown_attribute(meta: &ParseNestedMeta, known: &[&str]) -> syn::Error {
+    let path = &meta.path;
+    let name = quote::quote!(#path).to_string().replace(' ', "");
+    let suggestion = known
+        .iter()
+        .map(|key| (edit_distance(&name, key), key))
+        .filter(|(distance, _)| *distance <= 2)
+        .min();
+    match suggestion {
+        Some((_, key)) => meta.error(format!(
+            "unknown schema attribute `{}`; did you mean `{}`?",
+            name, key
+        )),
+        None => meta.error(format!(
+            "unknown schema attribute `{}`; expected one of {}",
+            name,
+            known.join(", ")
+        )),
+    }
+}
+
+/// Levenshtein distance between `a` and `b`
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, a) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, b) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(a != *b);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+/// Run `f` on every item inside every `#[schema(...)]` attribute
+fn for_each_meta(
+    attrs: &[Attribute],
+    mut f: impl FnMut(ParseNestedMeta) -> syn::Result<()>,
+) -> syn::Result<()> {
+    for attr in attrs {
+        if attr.path().is_ident("schema") {
+            attr.parse_nested_meta(&mut f)?;
+        }
+    }
+    Ok(())
+}
+
+/// `wit(name = "...")`
+fn parse_wit(meta: &ParseNestedMeta, wit_name: &mut Option<String>) -> syn::Result<()> {
+    meta.parse_nested_meta(|wit| {
+        if wit.path.is_ident("name") {
+            *wit_name = Some(string_value(&wit)?);
+            Ok(())
+        } else {
+            Err(wit.error("unknown wit attribute"))
+        }
+    })
+}
+
+/// `extension("x-key" = value, ...)`; a value runs to the next top-level
+/// comma, so JSON objects and arrays work as in `serde_json::json!`
+fn parse_extensions(
+    meta: &ParseNestedMeta,
+    extensions: &mut Vec<(String, proc_macro2::TokenStream)>,
+) -> syn::Result<()> {
+    let content;
+    syn::parenthesized!(content in meta.input);
+    while !content.is_empty() {
+        let key: LitStr = content.parse()?;
+        if !key.value().starts_with("x-") {
+            return Err(syn::Error::new_spanned(
+                key,
+                "extension keys must start with `x-`",
+            ));
+        }
+        content.parse::<syn::Token![=]>()?;
+        let mut value = proc_macro2::TokenStream::new();
+        while !content.is_empty() && !content.peek(syn::Token![,]) {
+            value.extend([content.parse::<proc_macro2::TokenTree>()?]);
+        }
+        if value.is_empty() {
+            return Err(content.error("expected an extension value"));
+        }
+        extensions.push((key.value(), value));
+        if !content.is_empty() {
+            content.parse::<syn::Token![,]>()?;
+        }
+    }
+    Ok(())
+}
+
+/// `example = value`; like an extension value, it runs to the next top-level
+/// comma
+fn example_value(meta: &ParseNestedMeta) -> syn::Result<proc_macro2::TokenStream> {
+    let input = meta.value()?;
+    let mut value = proc_macro2::TokenStream::new();
+    while !input.is_empty() && !input.peek(syn::Token![,]) {
+        value.extend([input.parse::<proc_macro2::TokenTree>()?]);
+    }
+    if value.is_empty() {
+        return Err(input.error("expected an example value"));
+    }
+    Ok(value)
+}
+
+/// Statement recording `examples` on `target`, if there are any
+pub fn examples_statement(
+    target: proc_macro2::TokenStream,
+    examples: &[proc_macro2::TokenStream],
+) -> Option<proc_macro2::TokenStream> {
+    (!examples.is_empty()).then(|| {
+        quote::quote! {
+            #target.extensions.insert(
+                schema::EXAMPLES.to_string(),
+                schema::__private::serde_json::json!([#(#examples),*]),
+            );
+        }
+    })
+}
+
+/// Statements inserting `extensions` into `target`'s extension map
+pub fn extension_statements(
+    target: proc_macro2::TokenStream,
+    extensions: &[(String, proc_macro2::TokenStream)],
+) -> Vec<proc_macro2::TokenStream> {
+    extensions
+        .iter()
+        .map(|(key, value)| {
+            quote::quote! {
+                #target.extensions.insert(
+                    #key.to_string(),
+                    schema::__private::serde_json::json!(#value),
+                );
+            }
+        })
+        .collect()
+}
+
+/// Value of a `key = "..."` item
+fn string_value(meta: &ParseNestedMeta) -> syn::Result<String> {
+    let value: LitStr = meta.value()?.parse()?;
+    Ok(value.value())
+}
+
+/// Value of a `key = "1.2.3"` item; WIT gates need a full semver version
+fn version_value(meta: &ParseNestedMeta) -> syn::Result<String> {
+    let value: LitStr = meta.value()?.parse()?;
+    let version = value.value();
+    let core = version.split(['-', '+']).next().unwrap_or_default();
+    let parts: Vec<&str> = core.split('.').collect();
+    if parts.len() != 3 || !parts.iter().all(|p| p.parse::<u64>().is_ok()) {
+        return Err(syn::Error::new_spanned(
+            value,
+            "expected a semver version like \"1.4.0\"",
+        ));
+    }
+    Ok(version)
+}
@@ -0,0 +1,276 @@
+//! Field constraints from `#[schema(...)]` and validation crates' attributes
+//!
+//! With the `garde` feature, `#[garde(...)]` rules are read; with the
+//! `validator` feature, `#[validate(...)]` rules are. Literal-text rules
+//! (`contains`, `prefix`, `suffix`, `ascii`, `alphanumeric`) become patterns;
+//! rules without a schema equivalent (custom functions, nested validation,
+//! ...) are ignored.
+//! Explicit `#[schema(...)]` constraints take precedence.
+
+use quote::quote;
+#[cfg(any(feature = "garde", feature = "validator"))]
+use syn::Expr;
+use syn::meta::ParseNestedMeta;
+use syn::{Attribute, LitStr};
+
+/// Constraints collected for one field
+#[derive(Default)]
+pub struct ConstraintAttrs {
+    pub minimum: Option<syn::Expr>,
+    pub maximum: Option<syn::Expr>,
+    /// Bounds from validation crates' `length` rules; item counts for
+    /// collections, characters otherwise
+    pub length_min: Option<syn::Expr>,
+    pub length_max: Option<syn::Expr>,
+    pub min_length: Option<syn::Expr>,
+    pub max_length: Option<syn::Expr>,
+    pub min_items: Option<syn::Expr>,
+    pub max_items: Option<syn::Expr>,
+    pub unique_items: bool,
+    pub pattern: Option<String>,
+    pub format: Option<&'static str>,
+}
+
+impl ConstraintAttrs {
+    #[cfg_attr(
+        not(any(feature = "garde", feature = "validator")),
+        allow(unused_variables, unused_mut)
+    )]
+    pub fn parse(attrs: &[Attribute]) -> syn::Result<Self> {
+        let mut parsed = ConstraintAttrs::default();
+
+        for attr in attrs {
+            #[cfg(feature = "garde")]
+            if attr.path().is_ident("garde") {
+                attr.parse_nested_meta(|meta| parsed.parse_rule(&meta))?;
+            }
+            #[cfg(feature = "validator")]
+            if attr.path().is_ident("validate") {
+                attr.parse_nested_meta(|meta| parsed.parse_rule(&meta))?;
+            }
+        }
+
+        Ok(parsed)
+    }
+
+    /// Whether no constraint was given
+    pub fn is_empty(&self) -> bool {
+        self.overrides().is_empty()
+    }
+
+    /// `#[schema(...)]` constraint keys; returns false for other keys
+    pub fn parse_schema_key(&mut self, meta: &ParseNestedMeta) -> syn::Result<bool> {
+        let slot = if meta.path.is_ident("minimum") {
+            &mut self.minimum
+        } else if meta.path.is_ident("maximum") {
+            &mut self.maximum
+        } else if meta.path.is_ident("min_length") {
+            &mut self.min_length
+        } else if meta.path.is_ident("max_length") {
+            &mut self.max_length
+        } else if meta.path.is_ident("min_items") {
+            &mut self.min_items
+        } else if meta.path.is_ident("max_items") {
+            &mut self.max_items
+        } else if meta.path.is_ident("unique_items") {
+            self.unique_items = true;
+            return Ok(true);
+        } else if meta.path.is_ident("pattern") {
+            let pattern: LitStr = meta.value()?.parse()?;
+            self.pattern = Some(pattern.value());
+            return Ok(true);
+        } else {
+            return Ok(false);
+        };
+        *slot = Some(meta.value()?.parse()?);
+        Ok(true)
+    }
+
+    /// Statements that apply the constraints to a `schema` variable
+    pub fn overrides(&self) -> Vec<proc_macro2::TokenStream> {
+        let mut overrides = vec![];
+
+        if let Some(minimum) = &self.minimum {
+            overrides.push(quote! { schema.constraints.minimum = Some((#minimum) as f64); });
+        }
+        if let Some(maximum) = &self.maximum {
+            overrides.push(quote! { schema.constraints.maximum = Some((#maximum) as f64); });
+        }
+        if self.length_min.is_some() || self.length_max.is_some() {
+            let min = length_expr(&self.length_min);
+            let max = length_expr(&self.length_max);
+            overrides.push(quote! {
+                schema::__private::apply_length(&mut schema, #min, #max);
+            });
+        }
+        let counts = [
+            (quote!(min_length), &self.min_length),
+            (quote!(max_length), &self.max_length),
+            (quote!(min_items), &self.min_items),
+            (quote!(max_items), &self.max_items),
+        ];
+        for (slot, count) in counts {
+            if let Some(count) = count {
+                overrides.push(quote! { schema.constraints.#slot = Some((#count) as usize); });
+            }
+        }
+        if self.unique_items {
+            overrides.push(quote! { schema.constraints.unique_items = true; });
+        }
+        if let Some(pattern) = &self.pattern {
+            overrides.push(quote! { schema.constraints.pattern = Some(#pattern.to_string()); });
+        }
+        if let Some(format) = self.format {
+            overrides.push(quote! { schema.constraints.format = Some(#format.to_string()); });
+        }
+
+        overrides
+    }
+
+    /// One rule such as `length(min = 1)`, `email` or `pattern("...")`;
+    /// garde and validator share most rule names
+    #[cfg(any(feature = "garde", feature = "validator"))]
+    fn parse_rule(&mut self, meta: &ParseNestedMeta) -> syn::Result<()> {
+        let format = [
+            ("email", "email"),
+            ("url", "uri"),
+            ("ipv4", "ipv4"),
+            ("ipv6", "ipv6"),
+        ]
+        .into_iter()
+        .find(|(rule, _)| meta.path.is_ident(rule));
+
+        let text = [
+            ("contains", "", ""),
+            ("prefix", "^", ""),
+            ("suffix", "", "$"),
+        ]
+        .into_iter()
+        .find(|(rule, ..)| meta.path.is_ident(rule));
+
+        if let Some((_, format)) = format {
+            self.format = Some(format);
+            Ok(())
+        } else if let Some((_, start, end)) = text {
+            if let Some(text) = literal_argument(meta)? {
+                self.pattern = Some(format!("{start}{}{end}", regex_escape(&text)));
+            }
+            Ok(())
+        } else if meta.path.is_ident("ascii") {
+            self.pattern = Some(r"^[\x00-\x7F]*$".to_string());
+            Ok(())
+        } else if meta.path.is_ident("alphanumeric") {
+            self.pattern = Some("^[a-zA-Z0-9]*$".to_string());
+            Ok(())
+        } else if meta.path.is_ident("length") {
+            meta.parse_nested_meta(|bound| {
+                if bound.path.is_ident("min") {
+                    self.length_min = Some(bound.value()?.parse()?);
+                } else if bound.path.is_ident("max") {
+                    self.length_max = Some(bound.value()?.parse()?);
+                } else if bound.path.is_ident("equal") {
+                    let value: Expr = bound.value()?.parse()?;
+                    self.length_min = Some(value.clone());
+                    self.length_max = Some(value);
+                } else {
+                    // garde's counting modes (`chars`, `bytes`, ...) and
+                    // anything else without a schema equivalent
+                    skip(&bound)?;
+                }
+                Ok(())
+            })
+        } else if meta.path.is_ident("range") {
+            meta.parse_nested_meta(|bound| {
+                if bound.path.is_ident("min") {
+                    self.minimum = Some(bound.value()?.parse()?);
+                } else if bound.path.is_ident("max") {
+                    self.maximum = Some(bound.value()?.parse()?);
+                } else if bound.path.is_ident("equal") {
+                    let value: Expr = bound.value()?.parse()?;
+                    self.minimum = Some(value.clone());
+                    self.maximum = Some(value);
+                } else {
+                    skip(&bound)?;
+                }
+                Ok(())
+            })
+        } else if meta.path.is_ident("pattern") && meta.input.peek(syn::token::Paren) {
+            // garde's `pattern("regex")`; a path to a compiled regex can't be
+            // read at compile time
+            let content;
+            syn::parenthesized!(content in meta.input);
+            if content.peek(LitStr) {
+                let pattern: LitStr = content.parse()?;
+                self.pattern = Some(pattern.value());
+            }
+            content.parse::<proc_macro2::TokenStream>()?;
+            Ok(())
+        } else {
+            skip(meta)
+        }
+    }
+}
+
+fn length_expr(bound: &Option<syn::Expr>) -> proc_macro2::TokenStream {
+    match bound {
+        Some(bound) => quote! { Some((#bound) as usize) },
+        None => quote! { None },
+    }
+}
+
+/// The string in garde's `rule("text")`, or validator's `rule = "text"` and
+/// `rule(pattern = "text")`; `None` for paths and other non-literal values
+#[cfg(any(feature = "garde", feature = "validator"))]
+fn literal_argument(meta: &ParseNestedMeta) -> syn::Result<Option<String>> {
+    let mut text = None;
+    if meta.input.peek(syn::Token![=]) {
+        if let Expr::Lit(syn::ExprLit {
+            lit: syn::Lit::Str(lit),
+            ..
+        }) = meta.value()?.parse()?
+        {
+            text = Some(lit.value());
+        }
+    } else if meta.input.peek(syn::token::Paren) {
+        let content;
+        syn::parenthesized!(content in meta.input);
+        if content.peek(LitStr) {
+            text = Some(content.parse::<LitStr>()?.value());
+        } else if content.peek(syn::Ident) && content.peek2(syn::Token![=]) {
+            let key: syn::Ident = content.parse()?;
+            content.parse::<syn::Token![=]>()?;
+            if key == "pattern" && content.peek(LitStr) {
+                text = Some(content.parse::<LitStr>()?.value());
+            }
+        }
+        content.parse::<proc_macro2::TokenStream>()?;
+    }
+    Ok(text)
+}
+
+/// `text` with regex metacharacters escaped
+#[cfg(any(feature = "garde", feature = "validator"))]
+fn regex_escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for ch in text.chars() {
+        if "\\.+*?()|[]{}^$".contains(ch) {
+            escaped.push('\\');
+        }
+        escaped.push(ch);
+    }
+    escaped
+}
+
+/// Consume an item that has no schema equivalent: `rule`, `rule = value`
+/// or `rule(...)`
+#[cfg(any(feature = "garde", feature = "validator"))]
+fn skip(meta: &ParseNestedMeta) -> syn::Result<()> {
+    if meta.input.peek(syn::Token![=]) {
+        meta.value()?.parse::<Expr>()?;
+    } else if meta.input.peek(syn::token::Paren) {
+        let content;
+        syn::parenthesized!(content in meta.input);
+        content.parse::<proc_macro2::TokenStream>()?;
+    }
+    Ok(())
+}
@@ -0,0 +1,119 @@
+use crate::attrs::FieldAttrs;
+use quote::quote;
+use std::collections::BTreeSet;
+use syn::{
+    Data, GenericArgument, GenericParam, Generics, Ident, PathArguments, Type, WherePredicate,
+};
+
+/// `generics` with a `Schema` bound on every type parameter a field's schema
+/// depends on, added to any where clause the type already has
+///
+/// Skipped fields, fields with a `with` override and `PhantomData<T>` don't
+/// call `T::schema()`, so parameters used only there stay unbounded.
+pub fn with_schema_bounds(generics: &Generics, data: &Data) -> syn::Result<Generics> {
+    let params: BTreeSet<&Ident> = generics.type_params().map(|param| &param.ident).collect();
+    if params.is_empty() {
+        return Ok(generics.clone());
+    }
+
+    let fields: Vec<&syn::Field> = match data {
+        Data::Struct(data) => data.fields.iter().collect(),
+        Data::Enum(data) => data.variants.iter().flat_map(|v| &v.fields).collect(),
+        Data::Union(_) => vec![],
+    };
+
+    let mut used = BTreeSet::new();
+    for field in fields {
+        let attrs = FieldAttrs::parse(&field.attrs)?;
+        if !attrs.skip && attrs.with.is_none() {
+            collect_params(&field.ty, &params, &mut used);
+        }
+    }
+
+    let mut generics = generics.clone();
+    let where_clause = generics.make_where_clause();
+    for param in used {
+        where_clause
+            .predicates
+            .push(syn::parse_quote!(#param: schema::Schema));
+    }
+    Ok(generics)
+}
+
+/// `generics` with `bound` added to its where clause instead of the inferred
+/// `Schema` bounds, for `#[schema(bound = "...")]`
+pub fn with_bounds(generics: &Generics, bound: &[WherePredicate]) -> Generics {
+    let mut generics = generics.clone();
+    generics
+        .make_where_clause()
+        .predicates
+        .extend(bound.iter().cloned());
+    generics
+}
+
+/// Type parameters from `params` that `ty` mentions outside `PhantomData`
+fn collect_params<'a>(ty: &Type, params: &BTreeSet<&'a Ident>, used: &mut BTreeSet<&'a Ident>) {
+    match ty {
+        Type::Path(path) => {
+            if let Some(qself) = &path.qself {
+                collect_params(&qself.ty, params, used);
+            }
+            if path.qself.is_none()
+                && let Some(first) = path.path.segments.first()
+                && let Some(param) = params.get(&first.ident)
+            {
+                used.insert(param);
+            }
+            for segment in &path.path.segments {
+                if segment.ident == "PhantomData" {
+                    continue;
+                }
+                if let PathArguments::AngleBracketed(args) = &segment.arguments {
+                    for arg in &args.args {
+                        if let GenericArgument::Type(ty) = arg {
+                            collect_params(ty, params, used);
+                        }
+                    }
+                }
+            }
+        }
+        Type::Array(array) => collect_params(&array.elem, params, used),
+        Type::Slice(slice) => collect_params(&slice.elem, params, used),
+        Type::Reference(reference) => collect_params(&reference.elem, params, used),
+        Type::Ptr(ptr) => collect_params(&ptr.elem, params, used),
+        Type::Paren(paren) => collect_params(&paren.elem, params, used),
+        Type::Group(group) => collect_params(&group.elem, params, used),
+        Type::Tuple(tuple) => {
+            for elem in &tuple.elems {
+                collect_params(elem, params, used);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// `type_name()` body: the type's name, plus its type and const arguments
+/// for generic types (`Page_User` for `Page<User>`, `Buf_16` for `Buf<16>`)
+pub fn type_name_expr(name: &Ident, generics: &Generics) -> proc_macro2::TokenStream {
+    let args: Vec<proc_macro2::TokenStream> = generics
+        .params
+        .iter()
+        .filter_map(|param| match param {
+            GenericParam::Type(param) => {
+                let ident = &param.ident;
+                Some(quote! { std::any::type_name::<#ident>() })
+            }
+            GenericParam::Const(param) => {
+                let ident = &param.ident;
+                Some(quote! { &#ident.to_string() })
+            }
+            GenericParam::Lifetime(_) => None,
+        })
+        .collect();
+    if args.is_empty() {
+        return quote! { stringify!(#name) };
+    }
+    quote! {
+        schema::__private::generic_type_name(stringify!(#name), &[#(#args),*])
+    }
+}
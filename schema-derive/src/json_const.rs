@@ -0,0 +1,231 @@
+//! `#[schema(emit_json_const)]`: the type's JSON Schema as a string constant
+//!
+//! The schema is rendered while the macro expands, without calling any
+//! `Schema` impl, so it's limited to types described entirely by their own
+//! definition: structs with named fields of primitive, `String`, `Option`,
+//! `Vec` and reference types, and plain unit-only enums. Anything that needs
+//! another type's schema or a runtime value (nested types, `with`, `flatten`,
+//! defaults, constraints, generics, ...) is rejected rather than guessed.
+//! The output matches `schema_anthropic::to_anthropic_schema`, minus `x-*`
+//! metadata.
+
+use crate::attrs::{ContainerAttrs, FieldAttrs};
+use crate::{VariantNames, is_option_type, title_and_description};
+use proc_macro2::Span;
+use syn::spanned::Spanned;
+use syn::{Data, DeriveInput, Fields, GenericArgument, PathArguments, Type};
+
+/// The JSON Schema of `input`, serialized
+pub fn render(
+    input: &DeriveInput,
+    container_attrs: &ContainerAttrs,
+    span: Span,
+) -> syn::Result<String> {
+    if input.generics.type_params().next().is_some()
+        || input.generics.const_params().next().is_some()
+    {
+        return Err(syn::Error::new(
+            span,
+            "`emit_json_const` doesn't support generic types; their schema depends on the type arguments",
+        ));
+    }
+    if let Some(span) = container_attrs.transparent {
+        return Err(unsupported(span, "`transparent`"));
+    }
+    if let Some(span) = container_attrs
+        .tag
+        .as_ref()
+        .map(|tag| tag.span())
+        .or(container_attrs.untagged)
+        .or(container_attrs.legacy_tagged_union)
+    {
+        return Err(unsupported(span, "tagged or untagged enums"));
+    }
+    if container_attrs.deprecated.is_some() {
+        return Err(unsupported(span, "`#[deprecated]`"));
+    }
+
+    let (title, description) = title_and_description(
+        container_attrs.title.clone(),
+        container_attrs.description.clone(),
+        &input.attrs,
+        container_attrs.title_from_docs,
+    );
+    let mut members = annotations(title, description);
+
+    match &input.data {
+        Data::Struct(data) => {
+            let Fields::Named(fields) = &data.fields else {
+                return Err(unsupported(data.fields.span(), "tuple and unit structs"));
+            };
+            members.extend(object_members(fields, container_attrs)?);
+        }
+        Data::Enum(data) => {
+            let names = VariantNames::for_container(container_attrs);
+            let mut variants = vec![];
+            for variant in &data.variants {
+                let variant_attrs = FieldAttrs::parse_variant(&variant.attrs)?;
+                if variant_attrs.skip {
+                    continue;
+                }
+                if !matches!(variant.fields, Fields::Unit) {
+                    return Err(unsupported(variant.span(), "enum variants with data"));
+                }
+                variants.push(string(&names.name(variant, variant_attrs)));
+            }
+            members.push(member("type", string("string")));
+            members.push(member("enum", format!("[{}]", variants.join(","))));
+        }
+        Data::Union(_) => return Err(unsupported(span, "unions")),
+    }
+
+    Ok(format!("{{{}}}", members.join(",")))
+}
+
+/// `"type": "object"` and its properties, for named fields
+fn object_members(
+    fields: &syn::FieldsNamed,
+    container_attrs: &ContainerAttrs,
+) -> syn::Result<Vec<String>> {
+    let mut properties = vec![];
+    let mut required = vec![];
+
+    for field in &fields.named {
+        let field_attrs = FieldAttrs::parse(&field.attrs)?;
+        if field_attrs.skip {
+            continue;
+        }
+        if field_attrs.flatten {
+            return Err(unsupported(field.span(), "`flatten`"));
+        }
+        if let Some(with) = &field_attrs.with {
+            return Err(unsupported(with.span(), "`with`"));
+        }
+        if field_attrs.default.is_some() {
+            return Err(unsupported(field.span(), "defaults"));
+        }
+        if field_attrs.deprecated.is_some() {
+            return Err(unsupported(field.span(), "`#[deprecated]`"));
+        }
+        if !field_attrs.constraints.is_empty() {
+            return Err(unsupported(field.span(), "constraints"));
+        }
+
+        let name = field_attrs.rename.clone().unwrap_or_else(|| {
+            let ident = field.ident.as_ref().unwrap().to_string();
+            match container_attrs.rename_all {
+                Some(rule) => rule.apply_to_field(&ident),
+                None => ident,
+            }
+        });
+        let (title, description) = title_and_description(
+            field_attrs.title.clone(),
+            field_attrs.description.clone(),
+            &field.attrs,
+            container_attrs.title_from_docs,
+        );
+        let mut members = annotations(title, description);
+        members.extend(type_members(&field.ty).ok_or_else(|| {
+            syn::Error::new_spanned(
+                &field.ty,
+                "`emit_json_const` only supports primitive, `String`, `Option` and `Vec` fields; \
+                 other types' schemas aren't known while the derive expands",
+            )
+        })?);
+
+        if !is_option_type(&field.ty) {
+            required.push(string(&name));
+        }
+        properties.push(format!("{}:{{{}}}", string(&name), members.join(",")));
+    }
+
+    let mut members = vec![
+        member("type", string("object")),
+        member("properties", format!("{{{}}}", properties.join(","))),
+        member("required", format!("[{}]", required.join(","))),
+    ];
+    if container_attrs.deny_unknown_fields {
+        members.push(member("additionalProperties", "false".to_string()));
+    }
+    Ok(members)
+}
+
+/// Keywords describing `ty`, or `None` if its schema can't be known here
+fn type_members(ty: &Type) -> Option<Vec<String>> {
+    match ty {
+        Type::Reference(reference) => type_members(&reference.elem),
+        Type::Paren(paren) => type_members(&paren.elem),
+        Type::Slice(slice) => array(&slice.elem),
+        Type::Path(path) if path.qself.is_none() => {
+            let segment = path.path.segments.last()?;
+            let kind = match segment.ident.to_string().as_str() {
+                "String" | "str" => "string",
+                "bool" => "boolean",
+                "i8" | "i16" | "i32" | "i64" | "i128" | "isize" | "u8" | "u16" | "u32" | "u64"
+                | "u128" | "usize" => "integer",
+                "f32" | "f64" => "number",
+                "Option" | "Box" => return type_members(single_argument(segment)?),
+                "Vec" => return array(single_argument(segment)?),
+                _ => return None,
+            };
+            Some(vec![member("type", string(kind))])
+        }
+        _ => None,
+    }
+}
+
+fn array(items: &Type) -> Option<Vec<String>> {
+    Some(vec![
+        member("type", string("array")),
+        member("items", format!("{{{}}}", type_members(items)?.join(","))),
+    ])
+}
+
+/// `T` in `Segment<T>`
+fn single_argument(segment: &syn::PathSegment) -> Option<&Type> {
+    let PathArguments::AngleBracketed(args) = &segment.arguments else {
+        return None;
+    };
+    match args.args.first()? {
+        GenericArgument::Type(ty) if args.args.len() == 1 => Some(ty),
+        _ => None,
+    }
+}
+
+fn annotations(title: Option<String>, description: Option<String>) -> Vec<String> {
+    let mut members = vec![];
+    if let Some(title) = title {
+        members.push(member("title", string(&title)));
+    }
+    if let Some(description) = description {
+        members.push(member("description", string(&description)));
+    }
+    members
+}
+
+fn member(key: &str, value: String) -> String {
+    format!("{}:{value}", string(key))
+}
+
+/// `value` as a JSON string literal
+fn string(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len() + 2);
+    escaped.push('"');
+    for ch in value.chars() {
+        match ch {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            ch if ch.is_control() => escaped.push_str(&format!("\\u{:04x}", ch as u32)),
+            ch => escaped.push(ch),
+        }
+    }
+    escaped.push('"');
+    escaped
+}
+
+fn unsupported(span: Span, what: &str) -> syn::Error {
+    syn::Error::new(span, format!("`emit_json_const` doesn't support {what}"))
+}
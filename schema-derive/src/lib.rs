@@ -2,6 +2,17 @@ use proc_macro::TokenStream;
 use quote::quote;
 use syn::{Data, DeriveInput, Fields, Lit, Meta, parse_macro_input};
 
+mod attrs;
+mod constraints;
+mod generics;
+mod json_const;
+mod rename;
+#[cfg(feature = "serde")]
+mod serde_attrs;
+
+use attrs::{ContainerAttrs, FieldAttrs, FieldDefault, examples_statement, extension_statements};
+use rename::RenameRule;
+
 /// Extract documentation comments from attributes
 fn extract_docs(attrs: &[syn::Attribute]) -> Option<String> {
     let mut docs = Vec::new();
@@ -25,185 +36,688 @@ fn extract_docs(attrs: &[syn::Attribute]) -> Option<String> {
     }
 }
 
-/// Check if field has #[schema(skip)] attribute
-fn is_skipped(attrs: &[syn::Attribute]) -> bool {
-    attrs.iter().any(|attr| {
-        if attr.path().is_ident("schema")
-            && let Ok(meta) = attr.meta.require_list()
-        {
-            return meta.tokens.to_string() == "skip";
-        }
-        false
-    })
-}
-
 #[proc_macro_derive(Schema, attributes(schema))]
 pub fn derive_schema(input: TokenStream) -> TokenStream {
     let input = parse_macro_input!(input as DeriveInput);
 
     let name = &input.ident;
-    let generics = &input.generics;
+    let container_attrs = match ContainerAttrs::parse(&input.attrs) {
+        Ok(container_attrs) => container_attrs,
+        Err(err) => return err.to_compile_error().into(),
+    };
+
+    let generics = match &container_attrs.bound {
+        Some(bound) => Ok(generics::with_bounds(&input.generics, bound)),
+        None => generics::with_schema_bounds(&input.generics, &input.data),
+    };
+    let generics = match generics {
+        Ok(generics) => generics,
+        Err(err) => return err.to_compile_error().into(),
+    };
     let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let type_name = generics::type_name_expr(name, &generics);
 
     let schema_impl = match &input.data {
-        Data::Struct(data) => derive_struct(data, &input.attrs),
-        Data::Enum(data) => derive_enum(data, &input.attrs),
-        Data::Union(_) => {
-            return quote! {
-                compile_error!("Schema derive does not support unions");
+        Data::Struct(data) => derive_struct(name, data, &input.attrs, &container_attrs),
+        Data::Enum(data) => derive_enum(data, &input.attrs, &container_attrs),
+        Data::Union(data) => Err(syn::Error::new_spanned(
+            data.union_token,
+            "Schema can't be derived for unions; use a struct or enum",
+        )),
+    };
+    let schema_impl = match schema_impl {
+        Ok(schema_impl) => schema_impl,
+        Err(err) => return err.to_compile_error().into(),
+    };
+
+    let wit_name = container_attrs.wit_name.iter();
+    let version = container_attrs.version.as_ref().map(|version| {
+        quote! {
+            fn schema_version() -> Option<&'static str> {
+                Some(#version)
             }
+        }
+    });
+    let record_version = container_attrs.version.as_ref().map(|version| {
+        quote! {
+            schema.extensions.insert(
+                schema::SCHEMA_VERSION.to_string(),
+                schema::__private::serde_json::Value::from(#version),
+            );
+        }
+    });
+    let ref_preference = container_attrs.ref_preference.map(|p| p.statement());
+    let extensions = extension_statements(quote!(schema), &container_attrs.extensions);
+    let examples = examples_statement(quote!(schema), &container_attrs.examples);
+    let deprecate = container_attrs.deprecated.as_ref().map(|deprecated| {
+        let note = deprecated.note_expr();
+        quote! { schema.deprecate(#note); }
+    });
+    // A transparent wrapper keeps its field's name, as if it weren't there
+    let set_name = container_attrs.transparent.is_none().then(|| {
+        quote! { schema.name = Some(type_name.to_string()); }
+    });
+
+    let partial = match (&input.data, container_attrs.partial) {
+        (_, None) => None,
+        (
+            Data::Struct(syn::DataStruct {
+                fields: Fields::Named(_),
+                ..
+            }),
+            Some(_),
+        ) if container_attrs.transparent.is_none() => Some(quote! {
+            impl #impl_generics #name #ty_generics #where_clause {
+                /// This type's schema with every field optional, for partial
+                /// updates
+                pub fn partial_schema() -> schema::SchemaType {
+                    <Self as schema::Schema>::schema().partial()
+                }
+            }
+        }),
+        (_, Some(span)) => {
+            return syn::Error::new(
+                span,
+                "`partial` is only supported on non-transparent structs with named fields",
+            )
+            .to_compile_error()
             .into();
         }
     };
 
+    let json_const = match container_attrs
+        .emit_json_const
+        .map(|span| json_const::render(&input, &container_attrs, span))
+    {
+        Some(Ok(json)) => Some(quote! {
+            impl #impl_generics #name #ty_generics #where_clause {
+                /// This type's JSON Schema, rendered when the derive expanded
+                pub const JSON_SCHEMA: &'static str = #json;
+            }
+        }),
+        Some(Err(err)) => return err.to_compile_error().into(),
+        None => None,
+    };
+
     let expanded = quote! {
+        #partial
+        #json_const
+
+        #[allow(deprecated)]
         impl #impl_generics schema::Schema for #name #ty_generics #where_clause {
             fn schema() -> schema::SchemaType {
-                #schema_impl
+                let type_name = #type_name;
+                schema::__private::recursion_guard(
+                    std::any::type_name::<Self>(),
+                    type_name,
+                    || {
+                        let mut schema = #schema_impl;
+                        #set_name
+                        #deprecate
+                        #ref_preference
+                        #record_version
+                        #(#extensions)*
+                        #examples
+                        #(schema.extensions.insert(
+                            "x-wit-name".to_string(),
+                            schema::__private::serde_json::Value::from(#wit_name),
+                        );)*
+                        schema
+                    },
+                )
             }
 
             fn type_name() -> Option<&'static str> {
-                Some(stringify!(#name))
+                Some(#type_name)
             }
+
+            #version
         }
     };
 
     TokenStream::from(expanded)
 }
 
-fn description_expr(attrs: &[syn::Attribute]) -> proc_macro2::TokenStream {
-    match extract_docs(attrs) {
-        Some(desc) => quote! { Some(#desc.to_string()) },
+/// Description from a `description` attribute, falling back to doc comments
+fn description(explicit: Option<String>, attrs: &[syn::Attribute]) -> Option<String> {
+    explicit.or_else(|| extract_docs(attrs))
+}
+
+/// Title and description from attributes, falling back to doc comments
+///
+/// With `title_from_docs`, a multi-paragraph doc comment contributes its
+/// first paragraph as the title and the rest as the description. An explicit
+/// description replaces the doc comment entirely.
+fn title_and_description(
+    explicit_title: Option<String>,
+    explicit_description: Option<String>,
+    attrs: &[syn::Attribute],
+    title_from_docs: bool,
+) -> (Option<String>, Option<String>) {
+    if explicit_description.is_some() {
+        return (explicit_title, explicit_description);
+    }
+
+    let (doc_title, doc_description) = match extract_docs(attrs) {
+        Some(docs) if title_from_docs => match docs.split_once("\n\n") {
+            Some((title, rest)) => (
+                Some(title.lines().collect::<Vec<_>>().join(" ")),
+                Some(rest.trim().to_string()),
+            ),
+            None => (None, Some(docs)),
+        },
+        docs => (None, docs),
+    };
+    (explicit_title.or(doc_title), doc_description)
+}
+
+fn string_option_expr(value: Option<String>) -> proc_macro2::TokenStream {
+    match value {
+        Some(value) => quote! { Some(#value.to_string()) },
         None => quote! { None },
     }
 }
 
-fn schema_with_description(
-    field_type: &syn::Type,
-    field_attrs: &[syn::Attribute],
+/// The field's schema before overrides: its type's `Schema` impl, or the
+/// `with` function
+fn base_schema(field: &syn::Field, field_attrs: &FieldAttrs) -> proc_macro2::TokenStream {
+    let field_type = &field.ty;
+    match &field_attrs.with {
+        Some(path) => quote! { #path() },
+        None => quote! { <#field_type as schema::Schema>::schema() },
+    }
+}
+
+/// Schema expression for a field, with its attribute overrides applied
+fn field_schema(
+    field: &syn::Field,
+    field_attrs: &FieldAttrs,
+    container_attrs: &ContainerAttrs,
 ) -> proc_macro2::TokenStream {
-    match extract_docs(field_attrs) {
-        Some(desc) => quote! {
+    let field_type = &field.ty;
+    let base = base_schema(field, field_attrs);
+    let mut overrides = vec![];
+
+    let (title, description) = title_and_description(
+        field_attrs.title.clone(),
+        field_attrs.description.clone(),
+        &field.attrs,
+        container_attrs.title_from_docs,
+    );
+    if let Some(title) = title {
+        overrides.push(quote! { schema.title = Some(#title.to_string()); });
+    }
+    if let Some(desc) = description {
+        overrides.push(quote! { schema.description = Some(#desc.to_string()); });
+    }
+    if let Some(deprecated) = &field_attrs.deprecated {
+        let note = deprecated.note_expr();
+        overrides.push(quote! { schema.deprecate(#note); });
+    }
+    if let Some(default) = &field_attrs.default {
+        let value = match default {
+            FieldDefault::Trait => quote! { <#field_type as Default>::default() },
+            FieldDefault::Path(path) => quote! { #path() },
+        };
+        overrides.push(quote! {
+            schema.default = schema::__private::serde_json::to_value(#value).ok();
+        });
+    }
+    overrides.extend(field_attrs.constraints.overrides());
+    overrides.extend(field_attrs.ref_preference.map(|p| p.statement()));
+    overrides.extend(extension_statements(
+        quote!(schema),
+        &field_attrs.extensions,
+    ));
+    overrides.extend(examples_statement(quote!(schema), &field_attrs.examples));
+    for (set, key) in [
+        (field_attrs.read_only, quote!(schema::READ_ONLY)),
+        (field_attrs.write_only, quote!(schema::WRITE_ONLY)),
+    ] {
+        if set {
+            overrides.push(quote! {
+                schema.extensions.insert(
+                    #key.to_string(),
+                    schema::__private::serde_json::Value::Bool(true),
+                );
+            });
+        }
+    }
+    if let Some(since) = &field_attrs.since {
+        overrides.push(quote! {
+            schema.extensions.insert(
+                schema::SINCE.to_string(),
+                schema::__private::serde_json::Value::from(#since),
+            );
+        });
+    }
+    if let Some(wit_name) = &field_attrs.wit_name {
+        overrides.push(quote! {
+            schema.extensions.insert(
+                "x-wit-field-name".to_string(),
+                schema::__private::serde_json::Value::from(#wit_name),
+            );
+        });
+    }
+
+    if overrides.is_empty() {
+        base
+    } else {
+        quote! {
             {
-                let mut schema = <#field_type as schema::Schema>::schema();
-                schema.description = Some(#desc.to_string());
+                let mut schema = #base;
+                #(#overrides)*
                 schema
             }
-        },
-        None => quote! { <#field_type as schema::Schema>::schema() },
+        }
     }
 }
 
-fn derive_struct(data: &syn::DataStruct, attrs: &[syn::Attribute]) -> proc_macro2::TokenStream {
-    let description_expr = description_expr(attrs);
+/// Object schema expression for named fields, keeping declaration order
+fn named_fields_object(
+    fields: &syn::FieldsNamed,
+    container_attrs: &ContainerAttrs,
+    rename_all: Option<RenameRule>,
+) -> syn::Result<proc_macro2::TokenStream> {
+    let mut statements = vec![];
 
-    match &data.fields {
-        Fields::Named(fields) => {
-            let mut properties = vec![];
-            let mut required = vec![];
+    for field in &fields.named {
+        let field_attrs = FieldAttrs::parse(&field.attrs)?;
+
+        // Skip fields with #[schema(skip)] attribute
+        if field_attrs.skip {
+            continue;
+        }
+
+        let field_type = &field.ty;
+
+        // Option<T> and defaulted fields, or every field of a defaulted
+        // struct, may be omitted; everything else is required
+        let is_optional =
+            is_option_type(field_type) || field_attrs.default.is_some() || container_attrs.default;
 
-            for field in &fields.named {
-                // Skip fields with #[schema(skip)] attribute
-                if is_skipped(&field.attrs) {
-                    continue;
+        if field_attrs.flatten {
+            // Merge an object's properties into the parent, like serde's flatten;
+            // a flattened Option<T> contributes no required properties
+            let merge_required = if is_optional {
+                quote! {}
+            } else {
+                quote! { required.extend(flattened_required); }
+            };
+            let flattened = base_schema(field, &field_attrs);
+            statements.push(quote! {
+                let flattened = #flattened;
+                if let schema::TypeKind::Object {
+                    properties: flattened_properties,
+                    required: flattened_required,
+                } = flattened.kind
+                {
+                    property_order.extend(flattened.property_order);
+                    properties.extend(flattened_properties);
+                    #merge_required
                 }
+            });
+            continue;
+        }
 
-                let field_name = field.ident.as_ref().unwrap();
-                let field_name_str = field_name.to_string();
-                let field_type = &field.ty;
+        let field_name_str = field_attrs.rename.clone().unwrap_or_else(|| {
+            let ident = field.ident.as_ref().unwrap().to_string();
+            match rename_all {
+                Some(rule) => rule.apply_to_field(&ident),
+                None => ident,
+            }
+        });
+
+        // Get base schema and apply field overrides
+        let schema_expr = field_schema(field, &field_attrs, container_attrs);
+
+        statements.push(quote! {
+            properties.insert(
+                #field_name_str.to_string(),
+                #schema_expr
+            );
+            property_order.push(#field_name_str.to_string());
+        });
+
+        if !is_optional {
+            statements.push(quote! {
+                required.push(#field_name_str.to_string());
+            });
+        }
+    }
 
-                // Check if field is Option<T> - if not, it's required
-                let is_optional = is_option_type(field_type);
+    Ok(quote! {
+        {
+            let mut properties = std::collections::HashMap::new();
+            let mut required = Vec::new();
+            let mut property_order = Vec::new();
+            #(#statements)*
+            schema::SchemaType {
+                kind: schema::TypeKind::Object {
+                    properties,
+                    required,
+                },
+                property_order,
+                ..Default::default()
+            }
+        }
+    })
+}
 
-                // Get base schema and add description
-                let schema_expr = schema_with_description(field_type, &field.attrs);
+fn derive_struct(
+    name: &syn::Ident,
+    data: &syn::DataStruct,
+    attrs: &[syn::Attribute],
+    container_attrs: &ContainerAttrs,
+) -> syn::Result<proc_macro2::TokenStream> {
+    let (title, description) = title_and_description(
+        container_attrs.title.clone(),
+        container_attrs.description.clone(),
+        attrs,
+        container_attrs.title_from_docs,
+    );
+
+    if let Some(span) = container_attrs
+        .untagged
+        .or(container_attrs.legacy_tagged_union)
+    {
+        return Err(syn::Error::new(
+            span,
+            "`untagged` and `legacy_tagged_union` only apply to enums",
+        ));
+    }
+    if let Some(tag) = &container_attrs.tag
+        && !matches!(data.fields, Fields::Named(_))
+    {
+        return Err(syn::Error::new(
+            tag.span(),
+            "`tag` only applies to enums and structs with named fields",
+        ));
+    }
+    if let Some((_, span)) = container_attrs.rename_all_variants {
+        return Err(syn::Error::new(
+            span,
+            "`rename_all_variants` only applies to enums",
+        ));
+    }
 
-                properties.push(quote! {
-                    properties.insert(
-                        #field_name_str.to_string(),
-                        #schema_expr
-                    );
-                });
+    if container_attrs.transparent.is_some() {
+        return transparent_struct(data, container_attrs, title, description);
+    }
+
+    let title_expr = string_option_expr(title.clone());
+    let description_expr = string_option_expr(description.clone());
+    let closed = deny_unknown_fields(container_attrs);
 
-                if !is_optional {
-                    required.push(quote! {
-                        required.push(#field_name_str.to_string());
-                    });
+    match &data.fields {
+        Fields::Named(fields) => {
+            let object = named_fields_object(fields, container_attrs, container_attrs.rename_all)?;
+            // Like serde, a tagged struct writes its name under the tag first
+            let tag = container_attrs.tag.as_ref().map(|tag| {
+                let tag_value = container_attrs
+                    .rename
+                    .clone()
+                    .unwrap_or_else(|| name.to_string());
+                quote! {
+                    if let schema::TypeKind::Object { properties, required } = &mut schema.kind {
+                        properties.insert(
+                            #tag.to_string(),
+                            schema::SchemaType::new(schema::TypeKind::Enum {
+                                variants: vec![#tag_value.to_string()],
+                            }),
+                        );
+                        required.insert(0, #tag.to_string());
+                        schema.property_order.insert(0, #tag.to_string());
+                    }
                 }
-            }
+            });
 
-            quote! {
+            Ok(quote! {
                 {
-                    let mut properties = std::collections::HashMap::new();
-                    let mut required = Vec::new();
-                    #(#properties)*
-                    #(#required)*
-                    schema::SchemaType {
-                        kind: schema::TypeKind::Object {
-                            properties,
-                            required,
-                        },
-                        description: #description_expr,
-                    }
+                    let mut schema = #object;
+                    #tag
+                    schema.title = #title_expr;
+                    schema.description = #description_expr;
+                    #closed
+                    schema
                 }
-            }
+            })
         }
-        Fields::Unnamed(_) => {
-            quote! {
-                compile_error!("Schema derive does not support tuple structs");
+        Fields::Unnamed(fields) => tuple_struct(fields, container_attrs, title, description),
+        Fields::Unit => Ok(quote! {
+            {
+                #[allow(unused_mut)]
+                let mut schema = schema::SchemaType {
+                    kind: schema::TypeKind::Object {
+                        properties: std::collections::HashMap::new(),
+                        required: Vec::new(),
+                    },
+                    title: #title_expr,
+                    description: #description_expr,
+                    ..Default::default()
+                };
+                #closed
+                schema
             }
+        }),
+    }
+}
+
+/// Statement closing a `schema` object, with `deny_unknown_fields`; on an
+/// enum this applies to each struct variant's fields
+fn deny_unknown_fields(container_attrs: &ContainerAttrs) -> Option<proc_macro2::TokenStream> {
+    container_attrs
+        .deny_unknown_fields
+        .then(|| quote! { schema.set_deny_unknown_fields(true); })
+}
+
+/// A newtype's field schema, or a tuple of the fields' schemas, with the
+/// struct's docs taking precedence
+fn tuple_struct(
+    fields: &syn::FieldsUnnamed,
+    container_attrs: &ContainerAttrs,
+    title: Option<String>,
+    description: Option<String>,
+) -> syn::Result<proc_macro2::TokenStream> {
+    let mut field_schemas = vec![];
+    for field in &fields.unnamed {
+        let field_attrs = FieldAttrs::parse(&field.attrs)?;
+        if !field_attrs.skip {
+            field_schemas.push(field_schema(field, &field_attrs, container_attrs));
         }
-        Fields::Unit => quote! {
-            schema::SchemaType {
-                kind: schema::TypeKind::Object {
-                    properties: std::collections::HashMap::new(),
-                    required: Vec::new(),
-                },
-                description: #description_expr,
-            }
+    }
+
+    let schema_expr = match <[_; 1]>::try_from(field_schemas) {
+        Ok([field_schema]) => field_schema,
+        Err(field_schemas) => quote! {
+            schema::SchemaType::new(schema::TypeKind::Tuple {
+                fields: vec![#(#field_schemas),*],
+            })
         },
+    };
+    let title = title.map(|title| quote! { schema.title = Some(#title.to_string()); });
+    let description =
+        description.map(|desc| quote! { schema.description = Some(#desc.to_string()); });
+
+    Ok(quote! {
+        {
+            let mut schema = #schema_expr;
+            #title
+            #description
+            schema
+        }
+    })
+}
+
+/// The single field's schema, with the struct's docs taking precedence
+fn transparent_struct(
+    data: &syn::DataStruct,
+    container_attrs: &ContainerAttrs,
+    title: Option<String>,
+    description: Option<String>,
+) -> syn::Result<proc_macro2::TokenStream> {
+    let mut fields = vec![];
+    for field in &data.fields {
+        let field_attrs = FieldAttrs::parse(&field.attrs)?;
+        if !field_attrs.skip {
+            fields.push((field, field_attrs));
+        }
     }
+    let [(field, field_attrs)] = <[_; 1]>::try_from(fields).map_err(|_| {
+        syn::Error::new_spanned(
+            &data.fields,
+            "#[schema(transparent)] requires exactly one non-skipped field",
+        )
+    })?;
+
+    let schema_expr = field_schema(field, &field_attrs, container_attrs);
+    let title = title.map(|title| quote! { schema.title = Some(#title.to_string()); });
+    let description =
+        description.map(|desc| quote! { schema.description = Some(#desc.to_string()); });
+
+    Ok(quote! {
+        {
+            let mut schema = #schema_expr;
+            #title
+            #description
+            schema
+        }
+    })
 }
 
-fn derive_enum(data: &syn::DataEnum, attrs: &[syn::Attribute]) -> proc_macro2::TokenStream {
-    let type_description = description_expr(attrs);
+fn derive_enum(
+    data: &syn::DataEnum,
+    attrs: &[syn::Attribute],
+    container_attrs: &ContainerAttrs,
+) -> syn::Result<proc_macro2::TokenStream> {
+    if let Some(span) = container_attrs.transparent {
+        return Err(syn::Error::new(
+            span,
+            "`transparent` only applies to single-field structs",
+        ));
+    }
+
+    let (title, type_description) = title_and_description(
+        container_attrs.title.clone(),
+        container_attrs.description.clone(),
+        attrs,
+        container_attrs.title_from_docs,
+    );
+    let title_expr = string_option_expr(title);
+    let type_description = string_option_expr(type_description);
+
+    // Skipped variants are never written, so they aren't cases
+    let mut variants = vec![];
+    for variant in &data.variants {
+        let variant_attrs = FieldAttrs::parse_variant(&variant.attrs)?;
+        if !variant_attrs.skip {
+            variants.push((variant, variant_attrs));
+        }
+    }
 
-    // Check if this is a simple enum (all variants are unit) or tagged union
-    let all_unit = data
-        .variants
+    // Check if this is a simple enum (all variants are unit) or tagged union;
+    // tagged unit variants are objects on the wire, so they need cases
+    let all_unit = variants
         .iter()
-        .all(|v| matches!(v.fields, Fields::Unit));
+        .all(|(v, _)| matches!(v.fields, Fields::Unit));
+    let variant_names = VariantNames::for_container(container_attrs);
 
-    if all_unit {
+    if all_unit
+        && container_attrs.tag.is_none()
+        && container_attrs.untagged.is_none()
+        && container_attrs.legacy_tagged_union.is_none()
+    {
         // Simple enum - generate Enum schema
-        let variants: Vec<_> = data
-            .variants
-            .iter()
-            .map(|v| {
-                let variant_name = v.ident.to_string().to_lowercase();
-                quote! { variants.push(#variant_name.to_string()); }
-            })
-            .collect();
+        let mut names = vec![];
+        let mut since = vec![];
+        let mut deprecated = vec![];
+        let mut discriminants = vec![];
+        for (v, variant_attrs) in variants {
+            let ident = &v.ident;
+            let variant_since = variant_attrs.since.clone();
+            let variant_deprecated = variant_attrs.deprecated.is_some();
+            let variant_name = variant_names.name(v, variant_attrs);
+            if let Some(version) = variant_since {
+                since.push(quote! { since.insert(#variant_name.to_string(), #version.into()); });
+            }
+            if variant_deprecated {
+                deprecated.push(variant_name.clone());
+            }
+            discriminants.push(quote! {
+                discriminants.insert(#variant_name.to_string(), (Self::#ident as i64).into());
+            });
+            names.push(quote! { variants.push(#variant_name.to_string()); });
+        }
 
-        quote! {
+        // Plain enums have no per-case metadata, so versions go in one map
+        let enum_since = (!since.is_empty()).then(|| {
+            quote! {
+                let mut since = schema::__private::serde_json::Map::new();
+                #(#since)*
+                schema.extensions.insert(
+                    schema::ENUM_SINCE.to_string(),
+                    schema::__private::serde_json::Value::Object(since),
+                );
+            }
+        });
+        // The cast also covers implicit and const-expression discriminants
+        let explicit = data.variants.iter().any(|v| v.discriminant.is_some());
+        let enum_discriminants = explicit.then(|| {
+            quote! {
+                let mut discriminants = schema::__private::serde_json::Map::new();
+                #(#discriminants)*
+                schema.extensions.insert(
+                    schema::ENUM_DISCRIMINANTS.to_string(),
+                    schema::__private::serde_json::Value::Object(discriminants),
+                );
+            }
+        });
+        let enum_deprecated = (!deprecated.is_empty()).then(|| {
+            quote! {
+                schema.extensions.insert(
+                    schema::ENUM_DEPRECATED.to_string(),
+                    schema::__private::serde_json::json!([#(#deprecated),*]),
+                );
+            }
+        });
+
+        Ok(quote! {
             {
                 let mut variants = Vec::new();
-                #(#variants)*
-                schema::SchemaType {
+                #(#names)*
+                let mut schema = schema::SchemaType {
                     kind: schema::TypeKind::Enum {
                         variants,
                     },
+                    title: #title_expr,
                     description: #type_description,
-                }
+                    ..Default::default()
+                };
+                #enum_since
+                #enum_deprecated
+                #enum_discriminants
+                schema
             }
-        }
+        })
     } else {
         // Proper variant type that preserves per-case structure
         let mut variant_cases = vec![];
 
-        for variant in &data.variants {
-            let variant_name = variant.ident.to_string().to_lowercase();
-            let variant_docs = description_expr(&variant.attrs);
+        for (variant, variant_attrs) in variants {
+            let variant_docs = string_option_expr(description(
+                variant_attrs.description.clone(),
+                &variant.attrs,
+            ));
+            let since = variant_attrs.since.clone().into_iter();
+            let deprecate = variant_attrs.deprecated.as_ref().map(|deprecated| {
+                let note = deprecated.note_expr();
+                quote! { case.deprecate(#note); }
+            });
+            let extensions = extension_statements(quote!(case), &variant_attrs.extensions);
+            let rename_fields = variant_attrs
+                .rename_all
+                .or(container_attrs.rename_all_fields);
+            let variant_name = variant_names.name(variant, variant_attrs);
 
             let data_expr = match &variant.fields {
                 Fields::Unit => {
@@ -212,87 +726,130 @@ fn derive_enum(data: &syn::DataEnum, attrs: &[syn::Attribute]) -> proc_macro2::T
                 }
                 Fields::Named(fields) => {
                     // Build a record type from the named fields
-                    let mut properties = vec![];
-                    let mut required = vec![];
-
-                    for field in &fields.named {
-                        if is_skipped(&field.attrs) {
-                            continue;
-                        }
-
-                        let field_name = field.ident.as_ref().unwrap();
-                        let field_name_str = field_name.to_string();
-                        let field_type = &field.ty;
-                        let is_optional = is_option_type(field_type);
-                        let schema_expr = schema_with_description(field_type, &field.attrs);
-
-                        properties.push(quote! {
-                            properties.insert(
-                                #field_name_str.to_string(),
-                                #schema_expr
-                            );
-                        });
-
-                        if !is_optional {
-                            required.push(quote! {
-                                required.push(#field_name_str.to_string());
-                            });
-                        }
-                    }
-
-                    quote! {
-                        Some(schema::SchemaType {
-                            kind: schema::TypeKind::Object {
-                                properties: {
-                                    let mut properties = std::collections::HashMap::new();
-                                    #(#properties)*
-                                    properties
-                                },
-                                required: {
-                                    let mut required = Vec::new();
-                                    #(#required)*
-                                    required
-                                },
-                            },
-                            description: None,
-                        })
+                    let object = named_fields_object(fields, container_attrs, rename_fields)?;
+                    match deny_unknown_fields(container_attrs) {
+                        Some(closed) => quote! {
+                            Some({
+                                let mut schema = #object;
+                                #closed
+                                schema
+                            })
+                        },
+                        None => quote! { Some(#object) },
                     }
                 }
                 Fields::Unnamed(fields) if fields.unnamed.len() == 1 => {
-                    // Single unnamed field - use it directly
-                    let field_type = &fields.unnamed[0].ty;
-                    let schema_expr = schema_with_description(field_type, &fields.unnamed[0].attrs);
-                    quote! { Some(#schema_expr) }
+                    // Single unnamed field - use it directly, or no data when
+                    // it is skipped
+                    let field = &fields.unnamed[0];
+                    let field_attrs = FieldAttrs::parse(&field.attrs)?;
+                    if field_attrs.skip {
+                        quote! { None }
+                    } else {
+                        let schema_expr = field_schema(field, &field_attrs, container_attrs);
+                        quote! { Some(#schema_expr) }
+                    }
                 }
-                Fields::Unnamed(_) => {
-                    return quote! {
-                        compile_error!("Schema derive does not support enum variants with multiple unnamed fields");
-                    };
+                Fields::Unnamed(fields) => {
+                    // Several unnamed fields - a tuple payload of those kept
+                    let mut field_schemas = vec![];
+                    for field in &fields.unnamed {
+                        let field_attrs = FieldAttrs::parse(&field.attrs)?;
+                        if !field_attrs.skip {
+                            field_schemas.push(field_schema(field, &field_attrs, container_attrs));
+                        }
+                    }
+                    quote! {
+                        Some(schema::SchemaType::new(schema::TypeKind::Tuple {
+                            fields: vec![#(#field_schemas),*],
+                        }))
+                    }
                 }
             };
 
             variant_cases.push(quote! {
-                cases.push(schema::VariantCase {
+                let mut case = schema::VariantCase {
                     name: #variant_name.to_string(),
                     data: #data_expr,
                     description: #variant_docs,
-                });
+                    ..Default::default()
+                };
+                #(case.extensions.insert(
+                    schema::SINCE.to_string(),
+                    schema::__private::serde_json::Value::from(#since),
+                );)*
+                #deprecate
+                #(#extensions)*
+                cases.push(case);
             });
         }
 
-        quote! {
+        let tagging = match (&container_attrs.tag, &container_attrs.content) {
+            (Some(tag), Some(content)) => Some(quote! {
+                schema::Tagging::Adjacent {
+                    tag: #tag.to_string(),
+                    content: #content.to_string(),
+                }
+            }),
+            (Some(tag), None) => {
+                Some(quote! { schema::Tagging::Internal { tag: #tag.to_string() } })
+            }
+            (None, _) if container_attrs.untagged.is_some() => {
+                Some(quote! { schema::Tagging::Untagged })
+            }
+            (None, _) => None,
+        }
+        .map(|tagging| quote! { schema.set_tagging(#tagging); });
+        let legacy = container_attrs
+            .legacy_tagged_union
+            .map(|_| quote! { let schema = schema.into_tagged_union(); });
+
+        Ok(quote! {
             {
                 let mut cases = Vec::new();
                 #(#variant_cases)*
-                schema::SchemaType {
+                let mut schema = schema::SchemaType {
                     kind: schema::TypeKind::Variant {
                         cases,
                     },
+                    title: #title_expr,
                     description: #type_description,
-                }
+                    ..Default::default()
+                };
+                #tagging
+                #legacy
+                schema
             }
+        })
+    }
+}
+
+/// How an enum's variants are named when not renamed individually
+struct VariantNames {
+    rename_all: Option<RenameRule>,
+}
+
+impl VariantNames {
+    fn for_container(container_attrs: &ContainerAttrs) -> Self {
+        VariantNames {
+            rename_all: container_attrs
+                .rename_all_variants
+                .map(|(rule, _)| rule)
+                .or(container_attrs.rename_all),
         }
     }
+
+    /// Emitted name of an enum variant: the `rename` override, the
+    /// `rename_all` rule, or the identifier as written, like serde
+    fn name(&self, variant: &syn::Variant, variant_attrs: FieldAttrs) -> String {
+        let ident = variant.ident.to_string();
+        variant_attrs
+            .rename
+            .unwrap_or_else(|| match self.rename_all {
+                Some(rule) => rule.apply_to_variant(&ident),
+                None => ident,
+            })
+    }
 }
 
 fn is_option_type(ty: &syn::Type) -> bool {
@@ -0,0 +1,115 @@
+//! `#[serde(...)]` attributes, read with the `serde` feature
+//!
+//! Only attributes that change the accepted shape are mirrored: renames,
+//! `skip`, `flatten`, `default`, `deny_unknown_fields`, the enum
+//! representations and a struct's `tag`. Everything
+//! else (`alias`, `with`, `bound`, ...) is ignored, and explicit
+//! `#[schema(...)]` attributes take precedence.
+
+use crate::attrs::{ContainerAttrs, FieldAttrs, FieldDefault};
+use crate::rename::RenameRule;
+use syn::meta::ParseNestedMeta;
+use syn::spanned::Spanned;
+use syn::{Attribute, LitStr};
+
+impl ContainerAttrs {
+    /// Container options from `#[serde(...)]`
+    pub fn parse_serde(&mut self, attrs: &[Attribute]) -> syn::Result<()> {
+        for_each_serde_meta(attrs, |meta| {
+            if meta.path.is_ident("rename_all") {
+                self.rename_all = Some(rename_rule(&meta)?);
+            } else if meta.path.is_ident("rename_all_fields") {
+                self.rename_all_fields = Some(rename_rule(&meta)?);
+            } else if meta.path.is_ident("tag") {
+                self.tag = Some(meta.value()?.parse()?);
+            } else if meta.path.is_ident("content") {
+                self.content = Some(meta.value()?.parse()?);
+            } else if meta.path.is_ident("untagged") {
+                self.untagged = Some(meta.path.span());
+            } else if meta.path.is_ident("transparent") {
+                self.transparent = Some(meta.path.span());
+            } else if meta.path.is_ident("deny_unknown_fields") {
+                self.deny_unknown_fields = true;
+            } else if meta.path.is_ident("rename") {
+                self.rename = Some(serialized_name(&meta)?.value());
+            } else if meta.path.is_ident("default") {
+                // With or without a function, every field may be left out
+                skip_value(&meta)?;
+                self.default = true;
+            } else {
+                skip_value(&meta)?;
+            }
+            Ok(())
+        })
+    }
+}
+
+impl FieldAttrs {
+    /// Field or variant options from `#[serde(...)]`
+    pub fn parse_serde(&mut self, attrs: &[Attribute]) -> syn::Result<()> {
+        for_each_serde_meta(attrs, |meta| {
+            if meta.path.is_ident("rename") {
+                self.rename = Some(serialized_name(&meta)?.value());
+            } else if meta.path.is_ident("rename_all") {
+                self.rename_all = Some(rename_rule(&meta)?);
+            } else if meta.path.is_ident("skip") || meta.path.is_ident("skip_serializing") {
+                self.skip = true;
+            } else if meta.path.is_ident("flatten") {
+                self.flatten = true;
+            } else if meta.path.is_ident("default") {
+                self.default = Some(if meta.input.peek(syn::Token![=]) {
+                    let path: LitStr = meta.value()?.parse()?;
+                    FieldDefault::Path(path.parse()?)
+                } else {
+                    FieldDefault::Trait
+                });
+            } else {
+                skip_value(&meta)?;
+            }
+            Ok(())
+        })
+    }
+}
+
+/// Run `f` on every item inside every `#[serde(...)]` attribute
+fn for_each_serde_meta(
+    attrs: &[Attribute],
+    mut f: impl FnMut(ParseNestedMeta) -> syn::Result<()>,
+) -> syn::Result<()> {
+    for attr in attrs {
+        if attr.path().is_ident("serde") {
+            attr.parse_nested_meta(&mut f)?;
+        }
+    }
+    Ok(())
+}
+
+/// `key = "..."`, or the `serialize` half of `key(serialize = "...")`
+fn serialized_name(meta: &ParseNestedMeta) -> syn::Result<LitStr> {
+    if meta.input.peek(syn::Token![=]) {
+        return meta.value()?.parse();
+    }
+    let mut serialize = None;
+    meta.parse_nested_meta(|half| {
+        let value: LitStr = half.value()?.parse()?;
+        if half.path.is_ident("serialize") {
+            serialize = Some(value);
+        }
+        Ok(())
+    })?;
+    serialize.ok_or_else(|| meta.error("expected `serialize = \"...\"`"))
+}
+
+fn rename_rule(meta: &ParseNestedMeta) -> syn::Result<RenameRule> {
+    RenameRule::parse(&serialized_name(meta)?)
+}
+
+/// Consume an ignored item's `= value` or `(...)`
+fn skip_value(meta: &ParseNestedMeta) -> syn::Result<()> {
+    if meta.input.peek(syn::Token![=]) {
+        meta.value()?.parse::<syn::Expr>()?;
+    } else if meta.input.peek(syn::token::Paren) {
+        meta.parse_nested_meta(|nested| skip_value(&nested))?;
+    }
+    Ok(())
+}
@@ -0,0 +1,9 @@
+use schema::Schema;
+
+#[derive(Schema)]
+struct Page {
+    #[schema(since = "next")]
+    cursor: Option<String>,
+}
+
+fn main() {}
@@ -0,0 +1,14 @@
+use schema::Schema;
+
+#[derive(Schema)]
+struct Point {
+    x: f64,
+}
+
+#[derive(Schema)]
+#[schema(emit_json_const)]
+struct Path {
+    points: Vec<Point>,
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+use schema::Schema;
+
+#[derive(Schema)]
+struct User {
+    #[schema(renmae = "userName")]
+    name: String,
+}
+
+fn main() {}
@@ -0,0 +1,10 @@
+use schema::Schema;
+
+#[derive(Schema)]
+#[schema(partial)]
+enum Mode {
+    Fast,
+    Slow,
+}
+
+fn main() {}
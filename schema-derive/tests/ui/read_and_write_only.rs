@@ -0,0 +1,9 @@
+use schema::Schema;
+
+#[derive(Schema)]
+struct Secret {
+    #[schema(read_only, write_only)]
+    value: String,
+}
+
+fn main() {}
@@ -0,0 +1,7 @@
+use schema::Schema;
+
+#[derive(Schema)]
+#[schema(tag = "kind")]
+struct Circle(f64);
+
+fn main() {}
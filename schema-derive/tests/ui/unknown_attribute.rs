@@ -0,0 +1,9 @@
+use schema::Schema;
+
+#[derive(Schema)]
+#[schema(colour = "red")]
+struct Shirt {
+    size: u8,
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+use schema::Schema;
+
+#[derive(Schema)]
+#[schema(tag = "kind", untagged)]
+enum Shape {
+    Circle { radius: f64 },
+}
+
+fn main() {}
@@ -0,0 +1,12 @@
+use schema::Schema;
+
+#[derive(Schema)]
+enum Payment {
+    Card {
+        number: String,
+    },
+    #[schema(read_only)]
+    Cash,
+}
+
+fn main() {}
@@ -0,0 +1,553 @@
+//! Dhall types from schemas
+//!
+//! The output is one Dhall expression: a `let` binding per named type,
+//! dependencies first, and a record of them all (`in { Person, Role }`), so
+//! configuration can import `./types.dhall` and annotate with `types.Person`.
+//! Objects are record types with `Optional` fields for optional properties,
+//! enums and variants are union types, and maps are `List { mapKey : Text,
+//! mapValue : V }` as the Prelude's `Map` is, which `dhall-to-json` writes as
+//! an object. Tuples are `{ _1 : A, _2 : B }` records.
+//!
+//! Unsigned integers are `Natural`s, signed ones `Integer`s and floats
+//! `Double`s. Dhall types can't refer to themselves, so recursive schemas are
+//! an error. Only type-level doc comments carry over, as `--` comments on
+//! their bindings.
+
+use schema::case::Case;
+use schema::{GenerateOptions, IntegerKind, RefPreference, Schema, SchemaType, TypeKind};
+use std::collections::BTreeSet;
+use std::fmt;
+
+/// Why a schema has no Dhall type
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DhallError {
+    /// The named type contains itself
+    Recursive(String),
+}
+
+impl fmt::Display for DhallError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DhallError::Recursive(name) => {
+                write!(f, "{} is recursive, which Dhall types can't be", name)
+            }
+        }
+    }
+}
+
+impl std::error::Error for DhallError {}
+
+/// The Dhall types for `T` and the named types it contains
+pub fn to_dhall<T: Schema>() -> Result<String, DhallError> {
+    schema_type_to_dhall(&T::schema(), T::type_name())
+}
+
+/// The Dhall types for `T`, bounded by `options`
+pub fn to_dhall_with_options<T: Schema>(options: &GenerateOptions) -> Result<String, DhallError> {
+    schema_type_to_dhall_with_options(&T::schema(), T::type_name(), options)
+}
+
+/// Convert a SchemaType to Dhall, bounded by `options`
+pub fn schema_type_to_dhall_with_options(
+    schema: &SchemaType,
+    type_name: Option<&str>,
+    options: &GenerateOptions,
+) -> Result<String, DhallError> {
+    schema_type_to_dhall(&options.apply(schema), type_name)
+}
+
+/// The Dhall types for a SchemaType, its root binding named `type_name` (or
+/// the schema's own name, or `Root`)
+pub fn schema_type_to_dhall(
+    schema: &SchemaType,
+    type_name: Option<&str>,
+) -> Result<String, DhallError> {
+    let mut module = Module::default();
+    let root = module.unique(&identifier(
+        type_name.or(schema.name.as_deref()).unwrap_or("Root"),
+    ));
+    if let Some(name) = &schema.name {
+        module.named.push(Named {
+            name: name.clone(),
+            shape: shape(schema),
+            ident: root.clone(),
+        });
+    }
+    module.bind(&root, schema)?;
+
+    let mut output: String = module
+        .bindings
+        .iter()
+        .map(|binding| format!("{}\n", binding))
+        .collect();
+    let names: Vec<&str> = module.order.iter().map(String::as_str).collect();
+    output.push_str(&format!("in  {{ {} }}\n", names.join(", ")));
+    Ok(output)
+}
+
+/// A named type that has a binding
+struct Named {
+    name: String,
+    /// Fingerprint without use-site annotations
+    shape: String,
+    ident: String,
+}
+
+#[derive(Default)]
+struct Module {
+    named: Vec<Named>,
+    /// Every binding name in use
+    taken: BTreeSet<String>,
+    /// Names whose binding is being written, which a `Ref` can't point to
+    writing: Vec<String>,
+    /// `let` bindings, each after the ones it uses
+    bindings: Vec<String>,
+    /// Binding names in the order written
+    order: Vec<String>,
+}
+
+impl Module {
+    /// `base`, or `base2`, `base3`, ... if something already has the name
+    fn unique(&mut self, base: &str) -> String {
+        let ident = (1..)
+            .map(|n| match n {
+                1 => base.to_string(),
+                n => format!("{base}{n}"),
+            })
+            .find(|ident| !self.taken.contains(ident))
+            .unwrap();
+        self.taken.insert(ident.clone());
+        ident
+    }
+
+    /// Write `let ident = ...`
+    fn bind(&mut self, ident: &str, schema: &SchemaType) -> Result<(), DhallError> {
+        if let Some(name) = &schema.name {
+            self.writing.push(name.clone());
+        }
+        let expression = self.expression(schema)?;
+        if schema.name.is_some() {
+            self.writing.pop();
+        }
+
+        let mut binding = comment(schema.docs().as_deref());
+        match expression.contains('\n') {
+            true => binding.push_str(&format!("let {ident} =\n{}\n", indent(&expression, 6))),
+            false => binding.push_str(&format!("let {ident} = {expression}\n")),
+        }
+        self.bindings.push(binding);
+        self.order.push(ident.to_string());
+        Ok(())
+    }
+
+    /// A nested schema: its binding's name if it is a named type, otherwise
+    /// written in place
+    fn reference(&mut self, schema: &SchemaType) -> Result<String, DhallError> {
+        let Some(name) = &schema.name else {
+            return self.expression(schema);
+        };
+        if matches!(schema.kind, TypeKind::Ref { .. })
+            || schema.ref_preference() == Some(RefPreference::Inline)
+        {
+            return self.expression(schema);
+        }
+
+        let shape = shape(schema);
+        if let Some(named) = self
+            .named
+            .iter()
+            .find(|named| named.name == *name && named.shape == shape)
+        {
+            return Ok(named.ident.clone());
+        }
+
+        // A different type may already have taken the name
+        let ident = self.unique(&identifier(name));
+        self.named.push(Named {
+            name: name.clone(),
+            shape,
+            ident: ident.clone(),
+        });
+        self.bind(&ident, schema)?;
+        Ok(ident)
+    }
+
+    /// The type written out, possibly over several lines
+    fn expression(&mut self, schema: &SchemaType) -> Result<String, DhallError> {
+        Ok(match &schema.kind {
+            TypeKind::String => "Text".to_string(),
+            TypeKind::Integer(kind) => integer(kind).to_string(),
+            TypeKind::Number(_) => "Double".to_string(),
+            TypeKind::Boolean => "Bool".to_string(),
+            // Nothing to hold; `dhall-to-json` writes an empty record as `{}`
+            TypeKind::Null => "{}".to_string(),
+            TypeKind::Enum { variants } => {
+                union(variants.iter().map(|v| (label(v), None)).collect())
+            }
+            TypeKind::Ref { name } => {
+                if self.writing.contains(name) {
+                    return Err(DhallError::Recursive(name.clone()));
+                }
+                self.named
+                    .iter()
+                    .find(|named| named.name == *name)
+                    .map(|named| named.ident.clone())
+                    .unwrap_or_else(|| identifier(name))
+            }
+            TypeKind::Array { items } | TypeKind::Set { items, .. } => {
+                let items = self.reference(items)?;
+                apply("List", &items)
+            }
+            TypeKind::Map { value, .. } => {
+                // `dhall-to-json` only writes `Text`-keyed entries as an
+                // object, and JSON keys are text whatever the Rust key type
+                let value = self.reference(value)?;
+                let entry = record(vec![
+                    ("mapKey".to_string(), "Text".to_string()),
+                    ("mapValue".to_string(), value),
+                ]);
+                apply("List", &entry)
+            }
+            TypeKind::Object { required, .. } => {
+                let mut fields = vec![];
+                for (name, property) in schema.ordered_properties() {
+                    let ty = self.reference(property)?;
+                    let optional =
+                        !required.iter().any(|r| r == name) && property.default.is_none();
+                    let ty = match optional {
+                        true => apply("Optional", &ty),
+                        false => ty,
+                    };
+                    fields.push((label(name), ty));
+                }
+                record(fields)
+            }
+            TypeKind::TaggedUnion {
+                tag_field,
+                tag_variants,
+                data_fields,
+            } => {
+                let alternatives: Vec<String> = tag_variants.iter().map(|v| label(v)).collect();
+                let mut fields = vec![(
+                    label(tag_field),
+                    format!("< {} >", alternatives.join(" | ")),
+                )];
+                let mut names: Vec<&String> = data_fields.keys().collect();
+                names.sort();
+                for name in names {
+                    let ty = self.reference(&data_fields[name])?;
+                    fields.push((label(name), apply("Optional", &ty)));
+                }
+                record(fields)
+            }
+            TypeKind::Variant { cases } => {
+                let mut alternatives = vec![];
+                for case in cases {
+                    let data = case.data.as_ref().map(|d| self.reference(d)).transpose()?;
+                    alternatives.push((label(&case.name), data));
+                }
+                union(alternatives)
+            }
+            TypeKind::Result { ok, err } => union(vec![
+                ("Ok".to_string(), Some(self.reference(ok)?)),
+                ("Err".to_string(), Some(self.reference(err)?)),
+            ]),
+            TypeKind::Tuple { fields } => {
+                let mut items = vec![];
+                for (i, field) in fields.iter().enumerate() {
+                    items.push((format!("_{}", i + 1), self.reference(field)?));
+                }
+                record(items)
+            }
+        })
+    }
+}
+
+/// `{ a : A, b : B }`, or one field per line in `dhall format`'s layout
+/// when that's too long
+fn record(fields: Vec<(String, String)>) -> String {
+    if fields.is_empty() {
+        return "{}".to_string();
+    }
+    let single: Vec<String> = fields.iter().map(|(n, ty)| format!("{n} : {ty}")).collect();
+    let single = format!("{{ {} }}", single.join(", "));
+    if fits(&single) {
+        return single;
+    }
+    let mut lines = vec![];
+    for (i, (name, ty)) in fields.iter().enumerate() {
+        let lead = if i == 0 { "{" } else { "," };
+        match ty.contains('\n') {
+            true => lines.push(format!("{lead} {name} :\n{}", indent(ty, 4))),
+            false => lines.push(format!("{lead} {name} : {ty}")),
+        }
+    }
+    lines.push("}".to_string());
+    lines.join("\n")
+}
+
+/// `< A | B : T >`, one alternative per line when that's too long and some
+/// hold data
+fn union(alternatives: Vec<(String, Option<String>)>) -> String {
+    if alternatives.is_empty() {
+        return "<>".to_string();
+    }
+    let single: Vec<String> = alternatives
+        .iter()
+        .map(|(name, data)| match data {
+            Some(ty) => format!("{name} : {ty}"),
+            None => name.clone(),
+        })
+        .collect();
+    let single = format!("< {} >", single.join(" | "));
+    if fits(&single) || alternatives.iter().all(|(_, data)| data.is_none()) {
+        return single;
+    }
+    let mut lines = vec![];
+    for (i, (name, data)) in alternatives.iter().enumerate() {
+        let lead = if i == 0 { "<" } else { "|" };
+        match data {
+            None => lines.push(format!("{lead} {name}")),
+            Some(ty) if ty.contains('\n') => {
+                lines.push(format!("{lead} {name} :\n{}", indent(ty, 4)))
+            }
+            Some(ty) => lines.push(format!("{lead} {name} : {ty}")),
+        }
+    }
+    lines.push(">".to_string());
+    lines.join("\n")
+}
+
+/// Whether an expression can stay on one line
+fn fits(expression: &str) -> bool {
+    !expression.contains('\n') && expression.len() <= 60
+}
+
+/// `function argument`, with a multi-line argument beneath the function
+fn apply(function: &str, argument: &str) -> String {
+    match argument.contains('\n') {
+        true => format!("{function}\n{}", indent(argument, 2)),
+        false if argument.contains(' ') && !argument.starts_with(['{', '<']) => {
+            format!("{function} ({argument})")
+        }
+        false => format!("{function} {argument}"),
+    }
+}
+
+/// Every line of `text` indented by `width` spaces
+fn indent(text: &str, width: usize) -> String {
+    let pad = " ".repeat(width);
+    text.lines()
+        .map(|line| format!("{pad}{line}"))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn integer(kind: &IntegerKind) -> &'static str {
+    match kind {
+        IntegerKind::I32 | IntegerKind::I64 => "Integer",
+        IntegerKind::U8 | IntegerKind::U32 | IntegerKind::U64 | IntegerKind::Usize => "Natural",
+    }
+}
+
+/// Keywords and builtins, which labels must quote
+const RESERVED: &[&str] = &[
+    "if",
+    "then",
+    "else",
+    "let",
+    "in",
+    "as",
+    "using",
+    "merge",
+    "missing",
+    "Infinity",
+    "NaN",
+    "Some",
+    "toMap",
+    "assert",
+    "forall",
+    "with",
+    "showConstructor",
+    "Type",
+    "Kind",
+    "Sort",
+    "Bool",
+    "True",
+    "False",
+    "Natural",
+    "Integer",
+    "Double",
+    "Text",
+    "List",
+    "Optional",
+    "None",
+    "Date",
+    "Time",
+    "TimeZone",
+    "Bytes",
+];
+
+/// A record field or union alternative, backquoted unless it's a plain
+/// identifier
+fn label(name: &str) -> String {
+    let plain = name.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '/'))
+        && !RESERVED.contains(&name);
+    match plain {
+        true => name.to_string(),
+        false => format!("`{}`", name.replace('`', "")),
+    }
+}
+
+/// A binding name: a PascalCase identifier that isn't a builtin
+fn identifier(name: &str) -> String {
+    let ident: String = Case::Pascal
+        .apply(name)
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric() || *c == '_')
+        .collect();
+    match ident.starts_with(|c: char| c.is_ascii_alphabetic()) && !RESERVED.contains(&&*ident) {
+        true => ident,
+        false => format!("T{ident}"),
+    }
+}
+
+/// `--` lines for `docs`, ending in a newline
+fn comment(docs: Option<&str>) -> String {
+    let Some(docs) = docs else {
+        return String::new();
+    };
+    docs.lines()
+        .map(|line| match line {
+            "" => "--\n".to_string(),
+            line => format!("-- {line}\n"),
+        })
+        .collect()
+}
+
+/// What makes two occurrences of a named type the same binding: its
+/// fingerprint without the annotations a field adds to it
+fn shape(schema: &SchemaType) -> String {
+    let mut bare = schema.clone();
+    bare.title = None;
+    bare.description = None;
+    bare.default = None;
+    for key in [
+        schema::DEPRECATED,
+        schema::READ_ONLY,
+        schema::WRITE_ONLY,
+        schema::SINCE,
+        schema::REF_PREFERENCE,
+    ] {
+        bare.extensions.remove(key);
+    }
+    bare.fingerprint()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn test_labels_and_layout() {
+        #[derive(Schema)]
+        #[allow(dead_code)]
+        enum Never {}
+
+        #[derive(Schema)]
+        #[allow(dead_code)]
+        enum Body {
+            #[schema(rename = "None")]
+            Empty,
+            Inline(String),
+            Parts(Vec<(String, u32)>),
+        }
+
+        /// A named type that isn't the builtin
+        #[derive(Schema)]
+        #[allow(dead_code)]
+        struct Text {
+            #[schema(rename = "in")]
+            within: Option<String>,
+            #[schema(rename = "content-type")]
+            content_type: String,
+            #[schema(rename = "2x")]
+            double: bool,
+        }
+
+        /// A message with its attachments
+        #[derive(Schema)]
+        #[allow(dead_code)]
+        struct Message {
+            offset: i64,
+            body: Body,
+            headers: BTreeMap<u32, Text>,
+            outcome: Result<u8, Never>,
+        }
+
+        assert_eq!(
+            to_dhall::<Message>().unwrap(),
+            "let Body =
+      < `None`
+      | Inline : Text
+      | Parts : List { _1 : Text, _2 : Natural }
+      >
+
+-- A named type that isn't the builtin
+let TText = { `in` : Optional Text, content-type : Text, `2x` : Bool }
+
+let Never = <>
+
+-- A message with its attachments
+let Message =
+      { offset : Integer
+      , body : Body
+      , headers : List { mapKey : Text, mapValue : TText }
+      , outcome : < Ok : Natural | Err : Never >
+      }
+
+in  { Body, TText, Never, Message }
+"
+        );
+    }
+
+    #[test]
+    fn test_recursive_is_an_error() {
+        #[derive(Schema)]
+        #[allow(dead_code)]
+        struct Node {
+            children: Vec<Node>,
+        }
+
+        assert_eq!(
+            to_dhall::<Node>(),
+            Err(DhallError::Recursive("Node".to_string()))
+        );
+
+        // Through another type, from either end
+        #[derive(Schema)]
+        #[allow(dead_code)]
+        struct Folder {
+            files: Vec<File>,
+        }
+
+        #[derive(Schema)]
+        #[allow(dead_code)]
+        struct File {
+            parent: Option<Box<Folder>>,
+        }
+
+        assert_eq!(
+            to_dhall::<Folder>(),
+            Err(DhallError::Recursive("Folder".to_string()))
+        );
+        assert_eq!(
+            to_dhall::<File>(),
+            Err(DhallError::Recursive("File".to_string()))
+        );
+    }
+}
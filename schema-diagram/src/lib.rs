@@ -0,0 +1,444 @@
+//! Mermaid class diagrams and Graphviz DOT graphs from schemas
+//!
+//! Every type [`schema_docs`] documents is a node, and so is each anonymous
+//! object or variant nested in one (a variant's struct payload, say), named
+//! after where it appears (`Line_Item`). Objects list their fields, enums
+//! their values and variants their cases. Each field, case or item that
+//! uses another node is an edge labelled with its name, carrying how many
+//! of the target it holds: `1`, `0..1` for optional fields, `*` through
+//! arrays, sets and maps.
+
+use schema::Schema;
+use schema::case::Case;
+use schema_docs::{Docs, TypeExpr};
+
+/// A Mermaid `classDiagram` of `T` and the named types it contains
+pub fn to_mermaid<T: Schema>() -> String {
+    docs_to_mermaid(&schema_docs::to_docs::<T>())
+}
+
+/// A Mermaid `classDiagram` of a SchemaType, its root named `type_name`
+pub fn schema_type_to_mermaid(schema: &schema::SchemaType, type_name: Option<&str>) -> String {
+    docs_to_mermaid(&schema_docs::schema_type_to_docs(schema, type_name))
+}
+
+/// A Mermaid `classDiagram` of every type in `docs`
+pub fn docs_to_mermaid(docs: &Docs) -> String {
+    let graph = Graph::new(docs);
+    let mut output = String::from("classDiagram\n");
+    for node in &graph.nodes {
+        let name = mermaid_id(&node.id);
+        let mut lines = vec![];
+        if let Some(stereotype) = node.stereotype {
+            lines.push(format!("<<{stereotype}>>"));
+        }
+        for member in &node.members {
+            lines.push(match &member.ty {
+                Some(ty) => format!("+{} {}", mermaid_text(&ty.mermaid), member.name),
+                None => member.name.clone(),
+            });
+        }
+        match lines.is_empty() {
+            true => output.push_str(&format!("    class {name}\n")),
+            false => {
+                output.push_str(&format!("    class {name} {{\n"));
+                for line in lines {
+                    output.push_str(&format!("        {}\n", mermaid_text(&line)));
+                }
+                output.push_str("    }\n");
+            }
+        }
+    }
+    for edge in &graph.edges {
+        output.push_str(&format!(
+            "    {} --> \"{}\" {} : {}\n",
+            mermaid_id(&edge.from),
+            edge.multiplicity,
+            mermaid_id(&edge.to),
+            mermaid_text(&edge.label)
+        ));
+    }
+    output
+}
+
+/// A Graphviz `digraph` of `T` and the named types it contains
+pub fn to_dot<T: Schema>() -> String {
+    docs_to_dot(&schema_docs::to_docs::<T>())
+}
+
+/// A Graphviz `digraph` of a SchemaType, its root named `type_name`
+pub fn schema_type_to_dot(schema: &schema::SchemaType, type_name: Option<&str>) -> String {
+    docs_to_dot(&schema_docs::schema_type_to_docs(schema, type_name))
+}
+
+/// A Graphviz `digraph` of every type in `docs`, one record node per type
+pub fn docs_to_dot(docs: &Docs) -> String {
+    let graph = Graph::new(docs);
+    let mut output = String::from("digraph schema {\n    node [shape=record];\n");
+    for node in &graph.nodes {
+        let mut title = record_text(&node.id);
+        if let Some(stereotype) = node.stereotype {
+            title = format!("{}\\n{title}", record_text(&format!("«{stereotype}»")));
+        }
+        let members: String = node
+            .members
+            .iter()
+            .map(|member| {
+                let line = match &member.ty {
+                    Some(ty) => format!("{}: {}", member.name, ty.dot),
+                    None => member.name.clone(),
+                };
+                format!("{}\\l", record_text(&line))
+            })
+            .collect();
+        let label = match members.is_empty() {
+            true => format!("{{{title}}}"),
+            false => format!("{{{title}|{members}}}"),
+        };
+        // Record text is already escaped, `\l` line ends included
+        output.push_str(&format!("    {} [label=\"{label}\"];\n", quote(&node.id)));
+    }
+    for edge in &graph.edges {
+        output.push_str(&format!(
+            "    {} -> {} [label={}, headlabel={}];\n",
+            quote(&edge.from),
+            quote(&edge.to),
+            quote(&edge.label),
+            quote(edge.multiplicity)
+        ));
+    }
+    output.push_str("}\n");
+    output
+}
+
+/// Nodes and edges for one diagram
+struct Graph {
+    nodes: Vec<Node>,
+    edges: Vec<Edge>,
+}
+
+struct Node {
+    id: String,
+    /// `enumeration`, `variant`, and so on; `None` for objects
+    stereotype: Option<&'static str>,
+    members: Vec<NodeMember>,
+}
+
+/// A field (with a type), enum value or case
+struct NodeMember {
+    name: String,
+    ty: Option<Label>,
+}
+
+struct Edge {
+    from: String,
+    to: String,
+    label: String,
+    multiplicity: &'static str,
+}
+
+/// A type as written in each format: Mermaid spells generics `Set~T~`
+#[derive(Default)]
+struct Label {
+    mermaid: String,
+    dot: String,
+}
+
+impl Graph {
+    fn new(docs: &Docs) -> Self {
+        let mut graph = Graph {
+            nodes: vec![],
+            edges: vec![],
+        };
+        for entry in &docs.types {
+            graph.node(&entry.id, &entry.expr);
+        }
+        graph
+    }
+
+    /// Add the node `id` for `expr`, and the nodes nested in it
+    fn node(&mut self, id: &str, expr: &TypeExpr) {
+        let index = self.nodes.len();
+        self.nodes.push(Node {
+            id: id.to_string(),
+            stereotype: None,
+            members: vec![],
+        });
+
+        let (stereotype, members) = match expr {
+            TypeExpr::Object(fields) => {
+                let mut members = vec![];
+                for field in fields {
+                    let context = format!("{id}_{}", Case::Pascal.apply(&field.name));
+                    let multiplicity = match field.required {
+                        true => "1",
+                        false => "0..1",
+                    };
+                    let mut ty = self.label(id, &field.name, &field.expr, &context, multiplicity);
+                    if !field.required {
+                        ty.mermaid.push('?');
+                        ty.dot.push('?');
+                    }
+                    members.push(NodeMember {
+                        name: field.name.clone(),
+                        ty: Some(ty),
+                    });
+                }
+                (None, members)
+            }
+            TypeExpr::Enum(values) => {
+                let members = values
+                    .iter()
+                    .map(|value| NodeMember {
+                        name: value.clone(),
+                        ty: None,
+                    })
+                    .collect();
+                (Some("enumeration"), members)
+            }
+            TypeExpr::Variant(cases) => {
+                let mut members = vec![];
+                for case in cases {
+                    let ty = case.data.as_ref().map(|data| {
+                        let context = format!("{id}_{}", Case::Pascal.apply(&case.name));
+                        self.label(id, &case.name, data, &context, "1")
+                    });
+                    members.push(NodeMember {
+                        name: case.name.clone(),
+                        ty,
+                    });
+                }
+                (Some("variant"), members)
+            }
+            expr => {
+                let ty = self.label(id, "value", expr, &format!("{id}_Value"), "1");
+                let member = NodeMember {
+                    name: "value".to_string(),
+                    ty: Some(ty),
+                };
+                (Some("alias"), vec![member])
+            }
+        };
+        self.nodes[index].stereotype = stereotype;
+        self.nodes[index].members = members;
+    }
+
+    /// How a member of `from` named `name` writes `expr`, adding an edge to
+    /// each node it uses; anonymous objects and variants become nodes named
+    /// `context`
+    fn label(
+        &mut self,
+        from: &str,
+        name: &str,
+        expr: &TypeExpr,
+        context: &str,
+        multiplicity: &'static str,
+    ) -> Label {
+        let edge = |graph: &mut Self, to: &str| {
+            graph.edges.push(Edge {
+                from: from.to_string(),
+                to: to.to_string(),
+                label: name.to_string(),
+                multiplicity,
+            })
+        };
+        let generic = |name: &str, params: &[&Label]| Label {
+            mermaid: format!(
+                "{name}~{}~",
+                params
+                    .iter()
+                    .map(|param| param.mermaid.as_str())
+                    .collect::<Vec<_>>()
+                    .join(",")
+            ),
+            dot: format!(
+                "{name}<{}>",
+                params
+                    .iter()
+                    .map(|param| param.dot.as_str())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+        };
+
+        match expr {
+            TypeExpr::Primitive(primitive) => {
+                // `integer (u8)` reads as `u8`
+                let scalar = match primitive.split_once(" (") {
+                    Some((_, kind)) => kind.trim_end_matches(')'),
+                    None => primitive,
+                };
+                Label {
+                    mermaid: scalar.to_string(),
+                    dot: scalar.to_string(),
+                }
+            }
+            TypeExpr::Named(id) => {
+                edge(self, id);
+                Label {
+                    mermaid: id.clone(),
+                    dot: id.clone(),
+                }
+            }
+            TypeExpr::Object(fields) if fields.is_empty() => Label {
+                mermaid: "object".to_string(),
+                dot: "object".to_string(),
+            },
+            TypeExpr::Enum(_) | TypeExpr::Object(_) | TypeExpr::Variant(_) => {
+                let id = self.unique(context);
+                self.node(&id, expr);
+                edge(self, &id);
+                Label {
+                    mermaid: id.clone(),
+                    dot: id,
+                }
+            }
+            TypeExpr::Array(items) => {
+                let items = self.label(from, name, items, &format!("{context}Item"), "*");
+                Label {
+                    mermaid: format!("{}[]", items.mermaid),
+                    dot: format!("{}[]", items.dot),
+                }
+            }
+            TypeExpr::Set(items) => {
+                let items = self.label(from, name, items, &format!("{context}Item"), "*");
+                generic("Set", &[&items])
+            }
+            TypeExpr::Map(key, value) => {
+                let key = self.label(from, name, key, &format!("{context}Key"), "*");
+                let value = self.label(from, name, value, &format!("{context}Value"), "*");
+                generic("Map", &[&key, &value])
+            }
+            TypeExpr::Result(ok, err) => {
+                let ok = self.label(from, name, ok, &format!("{context}Ok"), multiplicity);
+                let err = self.label(from, name, err, &format!("{context}Error"), multiplicity);
+                generic("Result", &[&ok, &err])
+            }
+            TypeExpr::Tuple(items) => {
+                let items: Vec<Label> = items
+                    .iter()
+                    .enumerate()
+                    .map(|(i, item)| {
+                        self.label(from, name, item, &format!("{context}{i}"), multiplicity)
+                    })
+                    .collect();
+                generic("Tuple", &items.iter().collect::<Vec<_>>())
+            }
+        }
+    }
+
+    /// `base`, or `base2`, `base3`, ... if a node already has the id
+    fn unique(&self, base: &str) -> String {
+        (1..)
+            .map(|n| match n {
+                1 => base.to_string(),
+                n => format!("{base}{n}"),
+            })
+            .find(|id| self.nodes.iter().all(|node| node.id != *id))
+            .unwrap()
+    }
+}
+
+/// A Mermaid class name: letters, digits and `_`
+fn mermaid_id(id: &str) -> String {
+    id.chars()
+        .map(|c| match c.is_ascii_alphanumeric() {
+            true => c,
+            false => '_',
+        })
+        .collect()
+}
+
+/// Text inside a Mermaid class body or edge label, without the braces and
+/// line breaks that would end it
+fn mermaid_text(text: &str) -> String {
+    text.replace(['{', '}', '\n'], " ")
+}
+
+/// Text inside a quoted DOT record label, with record syntax and quotes
+/// escaped
+fn record_text(text: &str) -> String {
+    let mut escaped = String::new();
+    for c in text.chars() {
+        if "{}|<>\\\"".contains(c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// A DOT quoted string
+fn quote(text: &str) -> String {
+    format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Schema)]
+    #[allow(dead_code)]
+    enum Status {
+        Open,
+        Closed,
+    }
+
+    #[derive(Schema)]
+    #[allow(dead_code)]
+    enum Line {
+        Item { sku: String, quantity: u32 },
+        Note(String),
+    }
+
+    #[derive(Schema)]
+    #[allow(dead_code)]
+    struct Order {
+        status: Status,
+        lines: Vec<Line>,
+        parent: Option<Box<Order>>,
+    }
+
+    #[test]
+    fn test_mermaid_class_diagram() {
+        assert_eq!(
+            to_mermaid::<Order>(),
+            r#"classDiagram
+    class Order {
+        +Status status
+        +Line[] lines
+        +Order? parent
+    }
+    class Status {
+        <<enumeration>>
+        Open
+        Closed
+    }
+    class Line {
+        <<variant>>
+        +Line_Item Item
+        +string Note
+    }
+    class Line_Item {
+        +string sku
+        +u32 quantity
+    }
+    Order --> "1" Status : status
+    Order --> "*" Line : lines
+    Order --> "0..1" Order : parent
+    Line --> "1" Line_Item : Item
+"#
+        );
+    }
+
+    #[test]
+    fn test_dot_records() {
+        let dot = to_dot::<Order>();
+        assert!(dot.starts_with("digraph schema {\n    node [shape=record];\n"));
+        assert!(dot.contains(
+            r#"    "Order" [label="{Order|status: Status\llines: Line[]\lparent: Order?\l}"];"#
+        ));
+        assert!(dot.contains(r#"    "Status" [label="{«enumeration»\nStatus|Open\lClosed\l}"];"#));
+        assert!(dot.contains(r#"    "Order" -> "Line" [label="lines", headlabel="*"];"#));
+    }
+}
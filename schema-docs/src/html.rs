@@ -0,0 +1,463 @@
+//! Static HTML reference pages
+//!
+//! One self-contained page: a sidebar listing every type, a section per
+//! type anchored at `#type-<id>` with links to the types it uses and the
+//! types that use it, and anonymous nested objects and variants in
+//! collapsible `<details>`. A search box filters types by name, field, case
+//! and value names. No assets are loaded, so the page can be hosted as-is.
+
+use crate::{CaseDoc, Docs, FieldDoc, TypeDoc, TypeExpr};
+use serde_json::{Map, Value};
+
+const STYLE: &str = "body { margin: 0; display: flex; font: 15px/1.5 system-ui, sans-serif; color: #1f2328; }
+nav { position: sticky; top: 0; height: 100vh; overflow-y: auto; box-sizing: border-box; width: 16rem; flex-shrink: 0; padding: 1rem; border-right: 1px solid #d0d7de; background: #f6f8fa; }
+nav input { width: 100%; box-sizing: border-box; padding: 0.3rem; }
+nav ul { list-style: none; padding: 0; }
+main { flex: 1; min-width: 0; padding: 1rem 2rem; }
+section { border-bottom: 1px solid #d0d7de; padding-bottom: 1rem; }
+a { color: #0969da; text-decoration: none; }
+code { font-size: 90%; }
+table { border-collapse: collapse; width: 100%; }
+th, td { border: 1px solid #d0d7de; padding: 0.3rem 0.6rem; text-align: left; vertical-align: top; }
+td > p:first-child { margin-top: 0; }
+td > p:last-child { margin-bottom: 0; }
+details { margin-top: 0.3rem; }
+.kind, .badge { font-size: 75%; font-weight: normal; color: #59636e; }
+.badge { border: 1px solid currentColor; border-radius: 1rem; padding: 0 0.4rem; }
+.deprecated { text-decoration: line-through; }
+.constraint { display: inline-block; margin-right: 0.4rem; color: #59636e; }
+";
+
+const SCRIPT: &str = r#"const search = document.getElementById("search");
+search.addEventListener("input", () => {
+  const query = search.value.trim().toLowerCase();
+  for (const element of document.querySelectorAll("[data-search]")) {
+    element.hidden = query !== "" && !element.dataset.search.includes(query);
+  }
+});
+"#;
+
+/// A self-contained reference page
+#[derive(Debug, Clone, Default)]
+pub struct HtmlPage {
+    title: Option<String>,
+}
+
+impl HtmlPage {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Page title; defaults to the first root type's name
+    pub fn title(mut self, title: &str) -> Self {
+        self.title = Some(title.to_string());
+        self
+    }
+
+    /// Render the page for every type in `docs`
+    pub fn render(&self, docs: &Docs) -> String {
+        let title = self
+            .title
+            .as_deref()
+            .or_else(|| {
+                let root = docs.types.iter().find(|entry| entry.root)?;
+                Some(root.name.as_str())
+            })
+            .unwrap_or("Reference");
+        let title = escape_html(title);
+
+        let mut nav = String::new();
+        let mut sections = String::new();
+        for entry in &docs.types {
+            let terms = escape_html(&search_terms(entry));
+            nav.push_str(&format!(
+                "<li data-search=\"{terms}\"><a href=\"#{}\">{}</a></li>\n",
+                anchor(&entry.id),
+                escape_html(&entry.id)
+            ));
+            sections.push_str(&section(docs, entry, &terms));
+        }
+
+        format!(
+            r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+<meta charset="utf-8">
+<meta name="viewport" content="width=device-width, initial-scale=1">
+<title>{title}</title>
+<style>
+{STYLE}</style>
+</head>
+<body>
+<nav>
+<input type="search" id="search" placeholder="Search types and fields" aria-label="Search types and fields">
+<ul>
+{nav}</ul>
+</nav>
+<main>
+<h1>{title}</h1>
+{sections}</main>
+<script>
+{SCRIPT}</script>
+</body>
+</html>
+"#
+        )
+    }
+}
+
+/// The section documenting `entry`
+fn section(docs: &Docs, entry: &TypeDoc, terms: &str) -> String {
+    let id = anchor(&entry.id);
+    let mut html = format!("<section id=\"{id}\" data-search=\"{terms}\">\n");
+    let class = match entry.deprecated {
+        true => " class=\"deprecated\"",
+        false => "",
+    };
+    html.push_str(&format!(
+        "<h2><a href=\"#{id}\"{class}>{}</a> <span class=\"kind\">{}</span>",
+        escape_html(&entry.id),
+        kind(&entry.expr)
+    ));
+    if entry.deprecated {
+        html.push_str(" <span class=\"badge\">deprecated</span>");
+    }
+    html.push_str("</h2>\n");
+    html.push_str(&paragraphs(entry.description.as_deref()));
+    let constraints = constraints(&entry.constraints);
+    if !constraints.is_empty() {
+        html.push_str(&format!("<p>{constraints}</p>\n"));
+    }
+
+    match &entry.expr {
+        TypeExpr::Object(fields) if !fields.is_empty() => {
+            html.push_str(&fields_table(docs, fields, &id))
+        }
+        TypeExpr::Enum(values) => {
+            html.push_str("<ul>\n");
+            for value in values {
+                html.push_str(&format!("<li>{}</li>\n", literal(value)));
+            }
+            html.push_str("</ul>\n");
+        }
+        TypeExpr::Variant(cases) if !cases.is_empty() => {
+            html.push_str(&cases_table(docs, cases, &id))
+        }
+        expr => html.push_str(&format!("<p>{}</p>\n", expression(docs, expr, &id))),
+    }
+
+    let users: Vec<String> = docs
+        .types
+        .iter()
+        .filter(|user| uses(&user.expr, &entry.id))
+        .map(|user| link(&user.id))
+        .collect();
+    if !users.is_empty() {
+        html.push_str(&format!("<p>Used by {}</p>\n", users.join(", ")));
+    }
+    html.push_str("</section>\n");
+    html
+}
+
+/// One row per field, anchored at `<prefix>.<field>`
+fn fields_table(docs: &Docs, fields: &[FieldDoc], prefix: &str) -> String {
+    let mut html = String::from(
+        "<table>\n<thead><tr><th>Field</th><th>Type</th><th>Description</th></tr></thead>\n<tbody>\n",
+    );
+    for field in fields {
+        let id = format!("{prefix}.{}", anchor_part(&field.name));
+        let class = match field.deprecated {
+            true => " class=\"deprecated\"",
+            false => "",
+        };
+        let mut name = format!(
+            "<a href=\"#{id}\"{class}><code>{}</code></a>",
+            escape_html(&field.name)
+        );
+        let flags = [
+            (field.required, "required"),
+            (field.deprecated, "deprecated"),
+            (field.read_only, "read-only"),
+            (field.write_only, "write-only"),
+        ];
+        for (set, flag) in flags {
+            if set {
+                name.push_str(&format!(" <span class=\"badge\">{flag}</span>"));
+            }
+        }
+
+        let mut description = paragraphs(field.description.as_deref());
+        let mut details = constraints(&field.constraints);
+        if let Some(default) = &field.default {
+            details.insert_str(
+                0,
+                &format!(
+                    "<span class=\"constraint\">default: <code>{}</code></span>",
+                    escape_html(&default.to_string())
+                ),
+            );
+        }
+        if !details.is_empty() {
+            description.push_str(&format!("<p>{details}</p>"));
+        }
+
+        html.push_str(&format!(
+            "<tr id=\"{id}\"><td>{name}</td><td>{}</td><td>{description}</td></tr>\n",
+            expression(docs, &field.expr, &id)
+        ));
+    }
+    html.push_str("</tbody>\n</table>\n");
+    html
+}
+
+/// One row per case, anchored at `<prefix>.<case>`
+fn cases_table(docs: &Docs, cases: &[CaseDoc], prefix: &str) -> String {
+    let mut html = String::from(
+        "<table>\n<thead><tr><th>Case</th><th>Data</th><th>Description</th></tr></thead>\n<tbody>\n",
+    );
+    for case in cases {
+        let id = format!("{prefix}.{}", anchor_part(&case.name));
+        let class = match case.deprecated {
+            true => " class=\"deprecated\"",
+            false => "",
+        };
+        let mut name = format!(
+            "<a href=\"#{id}\"{class}><code>{}</code></a>",
+            escape_html(&case.name)
+        );
+        if case.deprecated {
+            name.push_str(" <span class=\"badge\">deprecated</span>");
+        }
+        let data = match &case.data {
+            Some(data) => expression(docs, data, &id),
+            None => "none".to_string(),
+        };
+        html.push_str(&format!(
+            "<tr id=\"{id}\"><td>{name}</td><td>{data}</td><td>{}</td></tr>\n",
+            paragraphs(case.description.as_deref())
+        ));
+    }
+    html.push_str("</tbody>\n</table>\n");
+    html
+}
+
+/// `expr` inline, with named types linked and anonymous objects and
+/// variants collapsible; rows inside are anchored under `prefix`
+fn expression(docs: &Docs, expr: &TypeExpr, prefix: &str) -> String {
+    match expr {
+        TypeExpr::Primitive(name) => format!("<code>{}</code>", escape_html(name)),
+        TypeExpr::Named(id) => match docs.get(id) {
+            Some(_) => link(id),
+            None => format!("<code>{}</code>", escape_html(id)),
+        },
+        TypeExpr::Object(fields) if fields.is_empty() => "<code>object</code>".to_string(),
+        TypeExpr::Object(fields) => format!(
+            "<details open><summary>object</summary>\n{}</details>",
+            fields_table(docs, fields, prefix)
+        ),
+        TypeExpr::Enum(values) => {
+            let values: Vec<String> = values.iter().map(|value| literal(value)).collect();
+            format!("one of {}", values.join(", "))
+        }
+        TypeExpr::Variant(cases) if cases.is_empty() => "<code>never</code>".to_string(),
+        TypeExpr::Variant(cases) => format!(
+            "<details open><summary>one of {} cases</summary>\n{}</details>",
+            cases.len(),
+            cases_table(docs, cases, prefix)
+        ),
+        TypeExpr::Result(ok, err) => format!(
+            "result of {} or error {}",
+            expression(docs, ok, &format!("{prefix}.ok")),
+            expression(docs, err, &format!("{prefix}.error"))
+        ),
+        TypeExpr::Tuple(items) => {
+            let items: Vec<String> = items
+                .iter()
+                .enumerate()
+                .map(|(i, item)| expression(docs, item, &format!("{prefix}.{i}")))
+                .collect();
+            format!("tuple ({})", items.join(", "))
+        }
+        TypeExpr::Array(items) => format!("array of {}", expression(docs, items, prefix)),
+        TypeExpr::Set(items) => format!("set of {}", expression(docs, items, prefix)),
+        TypeExpr::Map(key, value) => format!(
+            "map from {} to {}",
+            expression(docs, key, &format!("{prefix}.key")),
+            expression(docs, value, &format!("{prefix}.value"))
+        ),
+    }
+}
+
+/// What a section heading calls the type
+fn kind(expr: &TypeExpr) -> &'static str {
+    match expr {
+        TypeExpr::Primitive(_) | TypeExpr::Named(_) => "alias",
+        TypeExpr::Object(_) => "object",
+        TypeExpr::Enum(_) => "enum",
+        TypeExpr::Variant(_) => "variant",
+        TypeExpr::Result(..) => "result",
+        TypeExpr::Tuple(_) => "tuple",
+        TypeExpr::Array(_) => "array",
+        TypeExpr::Set(_) => "set",
+        TypeExpr::Map(..) => "map",
+    }
+}
+
+/// Whether `expr` refers to the entry `id` anywhere outside other entries
+fn uses(expr: &TypeExpr, id: &str) -> bool {
+    match expr {
+        TypeExpr::Primitive(_) => false,
+        TypeExpr::Named(named) => named == id,
+        TypeExpr::Object(fields) => fields.iter().any(|field| uses(&field.expr, id)),
+        TypeExpr::Enum(_) => false,
+        TypeExpr::Variant(cases) => cases
+            .iter()
+            .any(|case| case.data.as_ref().is_some_and(|data| uses(data, id))),
+        TypeExpr::Tuple(items) => items.iter().any(|item| uses(item, id)),
+        TypeExpr::Array(items) | TypeExpr::Set(items) => uses(items, id),
+        TypeExpr::Result(first, second) | TypeExpr::Map(first, second) => {
+            uses(first, id) || uses(second, id)
+        }
+    }
+}
+
+/// Lowercase names the search box matches `entry` by: its own, and those
+/// of its fields, cases and values
+fn search_terms(entry: &TypeDoc) -> String {
+    fn collect(expr: &TypeExpr, terms: &mut Vec<String>) {
+        match expr {
+            TypeExpr::Primitive(_) | TypeExpr::Named(_) => {}
+            TypeExpr::Object(fields) => {
+                for field in fields {
+                    terms.push(field.name.clone());
+                    collect(&field.expr, terms);
+                }
+            }
+            TypeExpr::Enum(values) => terms.extend(values.iter().cloned()),
+            TypeExpr::Variant(cases) => {
+                for case in cases {
+                    terms.push(case.name.clone());
+                    if let Some(data) = &case.data {
+                        collect(data, terms);
+                    }
+                }
+            }
+            TypeExpr::Tuple(items) => items.iter().for_each(|item| collect(item, terms)),
+            TypeExpr::Array(items) | TypeExpr::Set(items) => collect(items, terms),
+            TypeExpr::Result(first, second) | TypeExpr::Map(first, second) => {
+                collect(first, terms);
+                collect(second, terms);
+            }
+        }
+    }
+
+    let mut terms = vec![entry.id.clone()];
+    collect(&entry.expr, &mut terms);
+    terms.join(" ").to_lowercase()
+}
+
+/// Keywords as `name: value` labels
+fn constraints(keywords: &Map<String, Value>) -> String {
+    keywords
+        .iter()
+        .map(|(keyword, value)| {
+            format!(
+                "<span class=\"constraint\">{}: <code>{}</code></span>",
+                escape_html(keyword),
+                escape_html(&value.to_string())
+            )
+        })
+        .collect()
+}
+
+/// Docs as paragraphs, split at blank lines
+fn paragraphs(docs: Option<&str>) -> String {
+    let Some(docs) = docs else {
+        return String::new();
+    };
+    docs.split("\n\n")
+        .filter(|paragraph| !paragraph.trim().is_empty())
+        .map(|paragraph| format!("<p>{}</p>\n", escape_html(paragraph.trim())))
+        .collect()
+}
+
+fn link(id: &str) -> String {
+    format!(
+        "<a href=\"#{}\"><code>{}</code></a>",
+        anchor(id),
+        escape_html(id)
+    )
+}
+
+/// A string value as it appears on the wire
+fn literal(value: &str) -> String {
+    format!(
+        "<code>{}</code>",
+        escape_html(&Value::from(value).to_string())
+    )
+}
+
+/// The element id of an entry's section
+fn anchor(id: &str) -> String {
+    format!("type-{}", anchor_part(id))
+}
+
+/// A name usable in an element id and URL fragment
+fn anchor_part(name: &str) -> String {
+    name.chars()
+        .map(|c| match c.is_ascii_alphanumeric() || "-_.".contains(c) {
+            true => c,
+            false => '_',
+        })
+        .collect()
+}
+
+fn escape_html(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{DocsOptions, to_html};
+    use schema::Schema;
+
+    #[test]
+    fn test_page_links_types() {
+        /// Where to ship <things>
+        #[derive(Schema)]
+        #[allow(dead_code)]
+        struct Address {
+            city: String,
+        }
+
+        #[derive(Schema)]
+        #[allow(dead_code)]
+        struct Order {
+            shipping: Address,
+            lines: Vec<Line>,
+        }
+
+        #[derive(Schema)]
+        #[allow(dead_code)]
+        enum Line {
+            Item { sku: String, quantity: u32 },
+            Note(String),
+        }
+
+        let html = to_html::<Order>(&DocsOptions::new());
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.contains("<title>Order</title>"));
+        assert!(html.contains("<section id=\"type-Address\" data-search=\"address city\">"));
+        assert!(html.contains("<p>Where to ship &lt;things&gt;</p>"));
+        assert!(html.contains("<tr id=\"type-Order.shipping\"><td><a href=\"#type-Order.shipping\"><code>shipping</code></a> <span class=\"badge\">required</span></td><td><a href=\"#type-Address\"><code>Address</code></a></td>"));
+        assert!(html.contains("array of <a href=\"#type-Line\"><code>Line</code></a>"));
+        assert!(html.contains("<p>Used by <a href=\"#type-Order\"><code>Order</code></a></p>"));
+        // Anonymous payloads are nested and collapsible
+        assert!(html.contains("<tr id=\"type-Line.Item\"><td><a href=\"#type-Line.Item\"><code>Item</code></a></td><td><details open><summary>object</summary>"));
+        assert!(html.contains("<tr id=\"type-Line.Item.quantity\">"));
+        assert!(html.contains("id=\"search\""));
+    }
+}
@@ -0,0 +1,438 @@
+//! Reference documentation for schemas
+//!
+//! [`Docs`] collects the named types reachable from one or more schemas into
+//! a flat list of [`TypeDoc`]s, each describing its fields, values or cases
+//! with [`TypeExpr`]s that point at other entries by id. Anonymous objects
+//! and variants stay nested where they appear. Renderers such as
+//! [`html::HtmlPage`] only walk this model, so every output links types the
+//! same way.
+
+use schema::{
+    GenerateOptions, NumberKind, RefPreference, Schema, SchemaType, TypeKind, VariantCase,
+};
+use serde_json::{Map, Value};
+
+pub mod html;
+
+/// Documentation for `T` and the named types it contains
+pub fn to_docs<T: Schema>() -> Docs {
+    let mut docs = Docs::new();
+    docs.add::<T>();
+    docs
+}
+
+/// Documentation for a SchemaType, its root named `type_name` (or the
+/// schema's own name, or `Root`)
+pub fn schema_type_to_docs(schema: &SchemaType, type_name: Option<&str>) -> Docs {
+    let mut docs = Docs::new();
+    docs.add_schema(schema, type_name);
+    docs
+}
+
+/// A standalone HTML reference page for `T`
+pub fn to_html<T: Schema>(options: &DocsOptions) -> String {
+    schema_type_to_html(&T::schema(), T::type_name(), options)
+}
+
+/// A standalone HTML reference page for a SchemaType
+pub fn schema_type_to_html(
+    schema: &SchemaType,
+    type_name: Option<&str>,
+    options: &DocsOptions,
+) -> String {
+    let schema = options.generate.apply(schema);
+    let docs = schema_type_to_docs(&schema, type_name);
+    let mut page = html::HtmlPage::new();
+    if let Some(title) = &options.title {
+        page = page.title(title);
+    }
+    page.render(&docs)
+}
+
+/// Options for [`to_html`]
+#[derive(Debug, Clone, Default)]
+pub struct DocsOptions {
+    /// Page title; defaults to the root type's name
+    pub title: Option<String>,
+    pub generate: GenerateOptions,
+}
+
+impl DocsOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn title(mut self, title: &str) -> Self {
+        self.title = Some(title.to_string());
+        self
+    }
+
+    /// Bound the schema before it is written
+    pub fn generate(mut self, generate: GenerateOptions) -> Self {
+        self.generate = generate;
+        self
+    }
+}
+
+/// The documented types, in the order they were first reached
+#[derive(Debug, Clone, Default)]
+pub struct Docs {
+    pub types: Vec<TypeDoc>,
+    named: Vec<Named>,
+}
+
+/// One documented type
+#[derive(Debug, Clone, PartialEq)]
+pub struct TypeDoc {
+    /// Unique among the types, usable as an anchor; a name another type
+    /// already took is numbered (`Item2`)
+    pub id: String,
+    pub name: String,
+    pub description: Option<String>,
+    pub deprecated: bool,
+    /// Whether the type was added as a root rather than reached from one
+    pub root: bool,
+    pub expr: TypeExpr,
+    /// JSON Schema keywords constraining the type, such as `minLength`
+    pub constraints: Map<String, Value>,
+}
+
+/// The shape of a type, with named types referenced by id
+#[derive(Debug, Clone, PartialEq)]
+pub enum TypeExpr {
+    /// A scalar: `string`, `integer (u8)`, `number (f64)`, `boolean`,
+    /// `null`, or a referenced name no entry has
+    Primitive(String),
+    /// The [`TypeDoc`] with this id
+    Named(String),
+    Object(Vec<FieldDoc>),
+    Enum(Vec<String>),
+    Variant(Vec<CaseDoc>),
+    Result(Box<TypeExpr>, Box<TypeExpr>),
+    Tuple(Vec<TypeExpr>),
+    Array(Box<TypeExpr>),
+    Set(Box<TypeExpr>),
+    Map(Box<TypeExpr>, Box<TypeExpr>),
+}
+
+/// A property of an object
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldDoc {
+    /// Wire name
+    pub name: String,
+    pub expr: TypeExpr,
+    pub required: bool,
+    /// Docs for this use, left out when they repeat the named type's own
+    pub description: Option<String>,
+    pub default: Option<Value>,
+    pub deprecated: bool,
+    pub read_only: bool,
+    pub write_only: bool,
+    /// Keywords on this use of an unnamed type
+    pub constraints: Map<String, Value>,
+}
+
+/// A case of a variant
+#[derive(Debug, Clone, PartialEq)]
+pub struct CaseDoc {
+    pub name: String,
+    /// `None` for unit cases
+    pub data: Option<TypeExpr>,
+    pub description: Option<String>,
+    pub deprecated: bool,
+}
+
+/// A named type that has an entry
+#[derive(Debug, Clone)]
+struct Named {
+    name: String,
+    /// Fingerprint without use-site annotations
+    shape: String,
+    id: String,
+    /// The occurrence the entry was written from
+    first: SchemaType,
+}
+
+impl Docs {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add `T` and the named types it contains; returns its id
+    pub fn add<T: Schema>(&mut self) -> String {
+        self.add_schema(&T::schema(), T::type_name())
+    }
+
+    /// Add a schema as a root, named `type_name` (or the schema's own name,
+    /// or `Root`); returns its id
+    ///
+    /// Named types already reached from an earlier root are shared rather
+    /// than documented twice.
+    pub fn add_schema(&mut self, schema: &SchemaType, type_name: Option<&str>) -> String {
+        let name = type_name.or(schema.name.as_deref()).unwrap_or("Root");
+        let existing = self
+            .named
+            .iter()
+            .find(|named| named.name == name && named.shape == shape(schema));
+        let id = match existing {
+            Some(named) => named.id.clone(),
+            None => self.define(name, schema),
+        };
+        if let Some(entry) = self.types.iter_mut().find(|entry| entry.id == id) {
+            entry.root = true;
+        }
+        id
+    }
+
+    /// The entry with `id`
+    pub fn get(&self, id: &str) -> Option<&TypeDoc> {
+        self.types.iter().find(|entry| entry.id == id)
+    }
+
+    /// Register `schema` under `name` and write its entry
+    fn define(&mut self, name: &str, schema: &SchemaType) -> String {
+        let id = (1..)
+            .map(|n| match n {
+                1 => name.to_string(),
+                n => format!("{name}{n}"),
+            })
+            .find(|id| self.get(id).is_none())
+            .unwrap();
+        self.named.push(Named {
+            name: name.to_string(),
+            shape: shape(schema),
+            id: id.clone(),
+            first: schema.clone(),
+        });
+
+        // Reserve the entry before recursing, so self-references see it
+        let index = self.types.len();
+        self.types.push(TypeDoc {
+            id: id.clone(),
+            name: name.to_string(),
+            description: schema.docs(),
+            deprecated: schema.deprecated(),
+            root: false,
+            expr: TypeExpr::Primitive(String::new()),
+            constraints: schema.constraints.json_keywords(),
+        });
+        self.types[index].expr = self.expression(schema);
+        id
+    }
+
+    /// A nested schema: its entry if it is a named type, otherwise in place
+    fn reference(&mut self, schema: &SchemaType) -> TypeExpr {
+        let Some(name) = &schema.name else {
+            return self.expression(schema);
+        };
+        if is_inline(schema) {
+            return self.expression(schema);
+        }
+
+        let shape = shape(schema);
+        let existing = self
+            .named
+            .iter()
+            .find(|named| named.name == *name && named.shape == shape);
+        match existing {
+            Some(named) => TypeExpr::Named(named.id.clone()),
+            None => TypeExpr::Named(self.define(name, schema)),
+        }
+    }
+
+    /// `schema` described in place, with nested named types by id
+    fn expression(&mut self, schema: &SchemaType) -> TypeExpr {
+        match &schema.kind {
+            TypeKind::String => TypeExpr::Primitive("string".to_string()),
+            TypeKind::Integer(kind) => {
+                TypeExpr::Primitive(format!("integer ({})", kind.rust_name()))
+            }
+            TypeKind::Number(NumberKind::F32) => TypeExpr::Primitive("number (f32)".to_string()),
+            TypeKind::Number(NumberKind::F64) => TypeExpr::Primitive("number (f64)".to_string()),
+            TypeKind::Boolean => TypeExpr::Primitive("boolean".to_string()),
+            TypeKind::Null => TypeExpr::Primitive("null".to_string()),
+            TypeKind::Ref { name } => match self.named.iter().find(|named| named.name == *name) {
+                Some(named) => TypeExpr::Named(named.id.clone()),
+                None => TypeExpr::Primitive(name.clone()),
+            },
+            TypeKind::Object { required, .. } => {
+                let mut fields = vec![];
+                for (name, property) in schema.ordered_properties() {
+                    let required = required.iter().any(|r| r == name);
+                    fields.push(self.field(name, property, required));
+                }
+                TypeExpr::Object(fields)
+            }
+            TypeKind::Enum { variants } => TypeExpr::Enum(variants.clone()),
+            TypeKind::TaggedUnion {
+                tag_field,
+                tag_variants,
+                data_fields,
+            } => {
+                let mut fields = vec![FieldDoc {
+                    name: tag_field.clone(),
+                    expr: TypeExpr::Enum(tag_variants.clone()),
+                    required: true,
+                    description: None,
+                    default: None,
+                    deprecated: false,
+                    read_only: false,
+                    write_only: false,
+                    constraints: Map::new(),
+                }];
+                let mut names: Vec<&String> = data_fields.keys().collect();
+                names.sort();
+                for name in names {
+                    fields.push(self.field(name, &data_fields[name], false));
+                }
+                TypeExpr::Object(fields)
+            }
+            TypeKind::Variant { cases } => {
+                let cases = cases.iter().map(|case| self.case(case)).collect();
+                TypeExpr::Variant(cases)
+            }
+            TypeKind::Result { ok, err } => {
+                TypeExpr::Result(Box::new(self.reference(ok)), Box::new(self.reference(err)))
+            }
+            TypeKind::Tuple { fields } => {
+                TypeExpr::Tuple(fields.iter().map(|item| self.reference(item)).collect())
+            }
+            TypeKind::Array { items } => TypeExpr::Array(Box::new(self.reference(items))),
+            TypeKind::Set { items, .. } => TypeExpr::Set(Box::new(self.reference(items))),
+            TypeKind::Map { key, value, .. } => TypeExpr::Map(
+                Box::new(self.reference(key)),
+                Box::new(self.reference(value)),
+            ),
+        }
+    }
+
+    fn field(&mut self, name: &str, schema: &SchemaType, required: bool) -> FieldDoc {
+        let expr = self.reference(schema);
+        let constraints = match &expr {
+            TypeExpr::Named(_) => Map::new(),
+            _ => schema.constraints.json_keywords(),
+        };
+        FieldDoc {
+            name: name.to_string(),
+            description: self.use_site_docs(schema),
+            expr,
+            required,
+            default: schema.default.clone(),
+            deprecated: schema.deprecated(),
+            read_only: schema.read_only(),
+            write_only: schema.write_only(),
+            constraints,
+        }
+    }
+
+    fn case(&mut self, case: &VariantCase) -> CaseDoc {
+        CaseDoc {
+            name: case.name.clone(),
+            data: case.data.as_ref().map(|data| self.reference(data)),
+            description: case.description.clone(),
+            deprecated: case.deprecated(),
+        }
+    }
+
+    /// The docs for a use of `schema`, unless its entry already has them
+    fn use_site_docs(&self, schema: &SchemaType) -> Option<String> {
+        schema.docs().filter(|docs| {
+            let declared = schema.name.as_ref().and_then(|name| {
+                self.named
+                    .iter()
+                    .find(|named| named.name == *name && named.shape == shape(schema))
+            });
+            declared.is_none_or(|named| named.first.docs().as_ref() != Some(docs))
+        })
+    }
+}
+
+/// Whether a named `schema` is described in place rather than as an entry
+fn is_inline(schema: &SchemaType) -> bool {
+    matches!(schema.kind, TypeKind::Ref { .. })
+        || schema.ref_preference() == Some(RefPreference::Inline)
+}
+
+/// What makes two occurrences of a named type the same entry: its
+/// fingerprint without the annotations a field adds to it
+fn shape(schema: &SchemaType) -> String {
+    let mut bare = schema.clone();
+    bare.title = None;
+    bare.description = None;
+    bare.default = None;
+    for key in [
+        schema::DEPRECATED,
+        schema::READ_ONLY,
+        schema::WRITE_ONLY,
+        schema::SINCE,
+        schema::REF_PREFERENCE,
+    ] {
+        bare.extensions.remove(key);
+    }
+    bare.fingerprint()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Schema)]
+    #[allow(dead_code)]
+    enum Status {
+        Active,
+        Suspended,
+    }
+
+    /// An account holder
+    #[derive(Schema)]
+    #[allow(dead_code)]
+    struct Account {
+        /// Login name
+        #[schema(min_length = 3)]
+        handle: String,
+        status: Status,
+        manager: Option<Box<Account>>,
+        settings: Settings,
+    }
+
+    #[derive(Schema)]
+    #[allow(dead_code)]
+    struct Settings {
+        theme: Option<String>,
+    }
+
+    #[test]
+    fn test_named_types_become_entries() {
+        let docs = to_docs::<Account>();
+        let ids: Vec<&str> = docs.types.iter().map(|entry| entry.id.as_str()).collect();
+        assert_eq!(ids, ["Account", "Status", "Settings"]);
+
+        let account = docs.get("Account").unwrap();
+        assert!(account.root);
+        assert_eq!(account.description.as_deref(), Some("An account holder"));
+        let TypeExpr::Object(fields) = &account.expr else {
+            panic!("expected an object, got {:?}", account.expr);
+        };
+        assert_eq!(fields[0].name, "handle");
+        assert_eq!(fields[0].description.as_deref(), Some("Login name"));
+        assert_eq!(fields[0].constraints["minLength"], 3);
+        assert_eq!(fields[1].expr, TypeExpr::Named("Status".to_string()));
+        assert_eq!(fields[2].expr, TypeExpr::Named("Account".to_string()));
+        assert!(!fields[2].required);
+
+        assert_eq!(
+            docs.get("Status").unwrap().expr,
+            TypeExpr::Enum(vec!["Active".to_string(), "Suspended".to_string()])
+        );
+        assert!(!docs.get("Settings").unwrap().root);
+    }
+
+    #[test]
+    fn test_roots_share_entries() {
+        let mut docs = Docs::new();
+        docs.add::<Account>();
+        assert_eq!(docs.add::<Settings>(), "Settings");
+        assert_eq!(docs.types.len(), 3);
+        assert!(docs.get("Settings").unwrap().root);
+    }
+}
@@ -0,0 +1,397 @@
+use schema::{Schema, SchemaType, TypeKind};
+use serde_json::{Map, Value, json};
+use std::fmt;
+
+/// Separator between nesting levels, e.g. `APP_SERVER__PORT`
+pub const NESTING_SEPARATOR: &str = "__";
+
+/// A single environment variable derived from a config schema
+#[derive(Debug, Clone, PartialEq)]
+pub struct EnvVar {
+    /// Full variable name, e.g. `APP_SERVER__PORT`
+    pub name: String,
+    /// Path of property names from the root object to this value
+    pub path: Vec<String>,
+    /// Human-readable type hint, e.g. `integer` or `one of: debug, info`
+    pub type_hint: String,
+    /// Whether the variable must be set
+    pub required: bool,
+    pub description: Option<String>,
+}
+
+/// Errors produced while reading configuration from environment variables
+#[derive(Debug, Clone, PartialEq)]
+pub enum EnvError {
+    /// A required variable was not set
+    Missing { var: String },
+    /// A variable was set but could not be coerced to the schema type
+    Invalid {
+        var: String,
+        value: String,
+        expected: String,
+    },
+}
+
+impl fmt::Display for EnvError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EnvError::Missing { var } => {
+                write!(f, "missing required environment variable {}", var)
+            }
+            EnvError::Invalid {
+                var,
+                value,
+                expected,
+            } => write!(
+                f,
+                "invalid value {:?} for environment variable {}: expected {}",
+                value, var, expected
+            ),
+        }
+    }
+}
+
+impl std::error::Error for EnvError {}
+
+/// List the environment variables for a config type
+pub fn to_env_vars<T: Schema>(prefix: &str) -> Vec<EnvVar> {
+    schema_type_to_env_vars(&T::schema(), prefix)
+}
+
+/// List the environment variables for a SchemaType
+///
+/// Nested objects are walked recursively and joined with `__`; every other
+/// kind becomes a single variable. Non-object roots map to the bare prefix.
+pub fn schema_type_to_env_vars(schema: &SchemaType, prefix: &str) -> Vec<EnvVar> {
+    let mut vars = Vec::new();
+    collect_vars(schema, prefix, &mut Vec::new(), true, &mut vars);
+    vars
+}
+
+fn collect_vars(
+    schema: &SchemaType,
+    prefix: &str,
+    path: &mut Vec<String>,
+    required: bool,
+    vars: &mut Vec<EnvVar>,
+) {
+    if let TypeKind::Object {
+        properties,
+        required: required_fields,
+    } = &schema.kind
+        && !properties.is_empty()
+    {
+        // Sort fields for deterministic output
+        let mut fields: Vec<_> = properties.iter().collect();
+        fields.sort_by_key(|(name, _)| *name);
+
+        for (field_name, field_schema) in fields {
+            path.push(field_name.clone());
+            let field_required = required && required_fields.contains(field_name);
+            collect_vars(field_schema, prefix, path, field_required, vars);
+            path.pop();
+        }
+        return;
+    }
+
+    vars.push(EnvVar {
+        name: var_name(prefix, path),
+        path: path.clone(),
+        type_hint: type_hint(schema),
+        required,
+        description: schema.docs(),
+    });
+}
+
+/// Build a variable name from a prefix and a property path
+pub fn var_name(prefix: &str, path: &[String]) -> String {
+    let nested = path
+        .iter()
+        .map(|segment| segment.to_uppercase())
+        .collect::<Vec<_>>()
+        .join(NESTING_SEPARATOR);
+
+    match (prefix.is_empty(), nested.is_empty()) {
+        (true, _) => nested,
+        (false, true) => prefix.to_uppercase(),
+        (false, false) => format!("{}_{}", prefix.to_uppercase(), nested),
+    }
+}
+
+fn type_hint(schema: &SchemaType) -> String {
+    match &schema.kind {
+        TypeKind::String => "string".to_string(),
+        TypeKind::Integer(_) => "integer".to_string(),
+        TypeKind::Number(_) => "number".to_string(),
+        TypeKind::Boolean => "boolean".to_string(),
+        TypeKind::Null => "null".to_string(),
+        TypeKind::Enum { variants } => format!("one of: {}", variants.join(", ")),
+        TypeKind::Array { items } | TypeKind::Set { items, .. } => {
+            format!("comma-separated list of {}", type_hint(items))
+        }
+        _ => "JSON".to_string(),
+    }
+}
+
+/// Render dotenv-style documentation for a config type
+pub fn to_env_docs<T: Schema>(prefix: &str) -> String {
+    render_env_docs(&to_env_vars::<T>(prefix))
+}
+
+/// Render dotenv-style documentation for a list of variables
+pub fn render_env_docs(vars: &[EnvVar]) -> String {
+    let mut output = String::new();
+
+    for var in vars {
+        if let Some(desc) = &var.description {
+            for line in desc.lines() {
+                output.push_str(&format!("# {}\n", line));
+            }
+        }
+
+        let requirement = if var.required { "required" } else { "optional" };
+        output.push_str(&format!("# ({}, {})\n", var.type_hint, requirement));
+        output.push_str(&format!("{}=\n", var.name));
+    }
+
+    output
+}
+
+/// Read a config type from the process environment
+///
+/// Returns a JSON value shaped like the schema, ready for `serde_json::from_value`.
+pub fn from_env<T: Schema>(prefix: &str) -> Result<Value, EnvError> {
+    from_vars::<T, _>(prefix, std::env::vars())
+}
+
+/// Read a config type from an explicit set of variables
+pub fn from_vars<T, I>(prefix: &str, vars: I) -> Result<Value, EnvError>
+where
+    T: Schema,
+    I: IntoIterator<Item = (String, String)>,
+{
+    schema_type_from_vars(&T::schema(), prefix, vars)
+}
+
+/// Read a SchemaType-shaped value from an explicit set of variables
+///
+/// Optional variables that are unset are omitted from the result.
+pub fn schema_type_from_vars<I>(
+    schema: &SchemaType,
+    prefix: &str,
+    vars: I,
+) -> Result<Value, EnvError>
+where
+    I: IntoIterator<Item = (String, String)>,
+{
+    let vars: std::collections::HashMap<String, String> = vars.into_iter().collect();
+    let mut root = Value::Object(Map::new());
+
+    for var in schema_type_to_env_vars(schema, prefix) {
+        let raw = match vars.get(&var.name) {
+            Some(raw) => raw,
+            None if var.required => return Err(EnvError::Missing { var: var.name }),
+            None => continue,
+        };
+
+        let leaf = leaf_schema(schema, &var.path);
+        let value = coerce(leaf, raw).ok_or_else(|| EnvError::Invalid {
+            var: var.name.clone(),
+            value: raw.clone(),
+            expected: var.type_hint.clone(),
+        })?;
+
+        insert_at(&mut root, &var.path, value);
+    }
+
+    Ok(root)
+}
+
+fn leaf_schema<'a>(schema: &'a SchemaType, path: &[String]) -> &'a SchemaType {
+    path.iter()
+        .fold(schema, |current, segment| match &current.kind {
+            TypeKind::Object { properties, .. } => &properties[segment],
+            _ => current,
+        })
+}
+
+fn insert_at(root: &mut Value, path: &[String], value: Value) {
+    let Some((last, parents)) = path.split_last() else {
+        *root = value;
+        return;
+    };
+
+    let mut current = root;
+    for segment in parents {
+        current = current
+            .as_object_mut()
+            .expect("intermediate env path is always an object")
+            .entry(segment.clone())
+            .or_insert_with(|| Value::Object(Map::new()));
+    }
+
+    if let Some(obj) = current.as_object_mut() {
+        obj.insert(last.clone(), value);
+    }
+}
+
+/// Coerce a raw string into a JSON value guided by the schema
+pub fn coerce(schema: &SchemaType, raw: &str) -> Option<Value> {
+    let trimmed = raw.trim();
+
+    match &schema.kind {
+        TypeKind::String => Some(json!(raw)),
+        TypeKind::Integer(_) => trimmed
+            .parse::<i64>()
+            .map(|v| json!(v))
+            .or_else(|_| trimmed.parse::<u64>().map(|v| json!(v)))
+            .ok(),
+        TypeKind::Number(_) => trimmed.parse::<f64>().ok().map(|v| json!(v)),
+        TypeKind::Boolean => match trimmed.to_lowercase().as_str() {
+            "true" | "1" | "yes" | "on" => Some(json!(true)),
+            "false" | "0" | "no" | "off" => Some(json!(false)),
+            _ => None,
+        },
+        TypeKind::Enum { variants } => variants
+            .iter()
+            .find(|v| v.eq_ignore_ascii_case(trimmed))
+            .map(|v| json!(v)),
+        TypeKind::Array { items } | TypeKind::Set { items, .. } => {
+            if trimmed.starts_with('[') {
+                return serde_json::from_str(trimmed).ok();
+            }
+            if trimmed.is_empty() {
+                return Some(json!([]));
+            }
+            trimmed
+                .split(',')
+                .map(|item| coerce(items, item))
+                .collect::<Option<Vec<_>>>()
+                .map(Value::Array)
+        }
+        // Complex kinds are accepted as inline JSON
+        _ => serde_json::from_str(trimmed).ok(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Schema)]
+    #[allow(dead_code)]
+    enum LogLevel {
+        Debug,
+        Info,
+        Warn,
+    }
+
+    #[derive(Schema)]
+    #[allow(dead_code)]
+    struct Server {
+        /// Port to listen on
+        port: u32,
+        host: Option<String>,
+    }
+
+    #[derive(Schema)]
+    #[allow(dead_code)]
+    struct Config {
+        server: Server,
+        log_level: LogLevel,
+        features: Vec<String>,
+        verbose: Option<bool>,
+    }
+
+    fn vars(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_var_names() {
+        let names: Vec<String> = to_env_vars::<Config>("app")
+            .into_iter()
+            .map(|v| v.name)
+            .collect();
+
+        assert_eq!(
+            names,
+            vec![
+                "APP_FEATURES",
+                "APP_LOG_LEVEL",
+                "APP_SERVER__HOST",
+                "APP_SERVER__PORT",
+                "APP_VERBOSE",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_requiredness() {
+        let vars = to_env_vars::<Config>("APP");
+        let port = vars.iter().find(|v| v.name == "APP_SERVER__PORT").unwrap();
+        let host = vars.iter().find(|v| v.name == "APP_SERVER__HOST").unwrap();
+
+        assert!(port.required);
+        assert!(!host.required);
+        assert_eq!(port.description.as_deref(), Some("Port to listen on"));
+    }
+
+    #[test]
+    fn test_docs() {
+        let docs = to_env_docs::<Server>("APP");
+        assert!(docs.contains("# Port to listen on\n# (integer, required)\nAPP_PORT=\n"));
+        assert!(docs.contains("# (string, optional)\nAPP_HOST=\n"));
+    }
+
+    #[test]
+    fn test_from_vars() {
+        let value = from_vars::<Config, _>(
+            "APP",
+            vars(&[
+                ("APP_SERVER__PORT", "8080"),
+                ("APP_LOG_LEVEL", "INFO"),
+                ("APP_FEATURES", "a,b"),
+                ("APP_VERBOSE", "yes"),
+            ]),
+        )
+        .unwrap();
+
+        assert_eq!(
+            value,
+            json!({
+                "server": { "port": 8080 },
+                "log_level": "Info",
+                "features": ["a", "b"],
+                "verbose": true
+            })
+        );
+    }
+
+    #[test]
+    fn test_missing_required() {
+        let err = from_vars::<Config, _>("APP", vars(&[("APP_LOG_LEVEL", "info")])).unwrap_err();
+        assert_eq!(
+            err,
+            EnvError::Missing {
+                var: "APP_FEATURES".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn test_invalid_value() {
+        let err = from_vars::<Server, _>("APP", vars(&[("APP_PORT", "eighty")])).unwrap_err();
+        assert!(matches!(err, EnvError::Invalid { ref var, .. } if var == "APP_PORT"));
+        assert!(err.to_string().contains("expected integer"));
+    }
+
+    #[test]
+    fn test_coerce_complex_as_json() {
+        let schema = <std::collections::HashMap<String, u32>>::schema();
+        assert_eq!(coerce(&schema, r#"{"a": 1}"#), Some(json!({"a": 1})));
+    }
+}
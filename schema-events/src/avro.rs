@@ -0,0 +1,248 @@
+//! Avro schemas for event envelopes
+//!
+//! Objects become records, with optional properties as `["null", T]`
+//! unions defaulting to `null`. Named types are defined where first met and
+//! referenced by name after that, which is also how recursive types refer
+//! back to themselves. Avro has no constraints, so bounds, patterns and the
+//! envelope's version limits are left to the JSON Schema.
+
+use schema::{IntegerKind, NumberKind, SchemaType, TypeKind};
+use serde_json::{Value, json};
+use std::collections::BTreeSet;
+
+/// Writes Avro types, defining each named type once
+#[derive(Default)]
+pub(crate) struct Module {
+    /// Schema names already defined, with how each is referenced: its Avro
+    /// name, or for a variant the names of its union's records
+    named: Vec<(String, Value)>,
+    /// Every Avro name in use
+    taken: BTreeSet<String>,
+}
+
+impl Module {
+    /// `base`, or `base2`, `base3`, ... if something already has the name
+    pub(crate) fn unique(&mut self, base: &str) -> String {
+        let name = (1..)
+            .map(|n| match n {
+                1 => base.to_string(),
+                n => format!("{base}{n}"),
+            })
+            .find(|name| !self.taken.contains(name))
+            .unwrap();
+        self.taken.insert(name.clone());
+        name
+    }
+
+    /// The Avro type for `schema`; `hint` names it if it needs a name and
+    /// has none of its own
+    pub(crate) fn avro(&mut self, schema: &SchemaType, hint: &str) -> Value {
+        if let TypeKind::Ref { name } = &schema.kind {
+            return self
+                .defined(name)
+                .unwrap_or_else(|| json!(identifier(name)));
+        }
+        let Some(name) = &schema.name else {
+            return self.define(schema, hint);
+        };
+        if let Some(avro) = self.defined(name) {
+            return avro;
+        }
+        self.define(schema, &identifier(name))
+    }
+
+    fn defined(&self, name: &str) -> Option<Value> {
+        self.named
+            .iter()
+            .find(|(schema, _)| schema == name)
+            .map(|(_, avro)| avro.clone())
+    }
+
+    /// Reserve the Avro name for a named type before writing it, so
+    /// recursive references find it
+    fn name(&mut self, schema: &SchemaType, hint: &str) -> String {
+        let name = self.unique(hint);
+        if let Some(schema_name) = &schema.name {
+            self.named.push((schema_name.clone(), json!(name)));
+        }
+        name
+    }
+
+    fn define(&mut self, schema: &SchemaType, hint: &str) -> Value {
+        let mut avro = match &schema.kind {
+            TypeKind::String => json!("string"),
+            TypeKind::Integer(kind) => json!(integer(kind)),
+            TypeKind::Number(NumberKind::F32) => json!("float"),
+            TypeKind::Number(NumberKind::F64) => json!("double"),
+            TypeKind::Boolean => json!("boolean"),
+            TypeKind::Null => json!("null"),
+            TypeKind::Enum { variants } if variants.iter().all(|v| is_identifier(v)) => {
+                json!({ "type": "enum", "name": self.name(schema, hint), "symbols": variants })
+            }
+            // Symbols must be identifiers, so anything else stays text
+            TypeKind::Enum { .. } => json!("string"),
+            TypeKind::Array { items } | TypeKind::Set { items, .. } => {
+                json!({ "type": "array", "items": self.avro(items, &format!("{hint}Item")) })
+            }
+            TypeKind::Map { key, value, .. }
+                if matches!(key.kind, TypeKind::String | TypeKind::Enum { .. }) =>
+            {
+                json!({ "type": "map", "values": self.avro(value, &format!("{hint}Value")) })
+            }
+            // Other keys are written as [key, value] pairs
+            TypeKind::Map { key, value, .. } => {
+                let name = self.unique(&format!("{hint}Entry"));
+                let fields = vec![
+                    field("key", self.avro(key, &format!("{name}Key")), false),
+                    field("value", self.avro(value, &format!("{name}Value")), false),
+                ];
+                json!({ "type": "array", "items": record(name, fields) })
+            }
+            TypeKind::Object { .. } => {
+                let name = self.name(schema, hint);
+                let fields = self.fields(schema, &name);
+                record(name, fields)
+            }
+            TypeKind::TaggedUnion {
+                tag_field,
+                data_fields,
+                ..
+            } => {
+                let name = self.name(schema, hint);
+                let mut names: Vec<&String> = data_fields.keys().collect();
+                names.sort();
+                let mut fields = vec![field(tag_field, json!("string"), false)];
+                for data in names {
+                    let avro = self.avro(&data_fields[data], &format!("{name}{}", pascal(data)));
+                    fields.push(field(data, avro, true));
+                }
+                record(name, fields)
+            }
+            TypeKind::Variant { cases } => {
+                // A union has no name of its own, so a reference to it names
+                // its records, which recursive cases can then point to
+                let base = schema.name.as_deref().map_or(hint.to_string(), identifier);
+                let names: Vec<String> = cases
+                    .iter()
+                    .map(|case| self.unique(&format!("{base}{}", pascal(&case.name))))
+                    .collect();
+                if let Some(schema_name) = &schema.name {
+                    self.named.push((schema_name.clone(), json!(names)));
+                }
+                let mut members = vec![];
+                for (case, case_name) in cases.iter().zip(names) {
+                    let fields = match &case.data {
+                        None => vec![],
+                        Some(
+                            data @ SchemaType {
+                                kind: TypeKind::Object { .. },
+                                name: None,
+                                ..
+                            },
+                        ) => self.fields(data, &case_name),
+                        Some(data) => {
+                            let avro = self.avro(data, &format!("{case_name}Value"));
+                            vec![field("value", avro, false)]
+                        }
+                    };
+                    members.push(record(case_name, fields));
+                }
+                json!(members)
+            }
+            TypeKind::Result { ok, err } => {
+                let ok_name = self.unique(&format!("{hint}Ok"));
+                let err_name = self.unique(&format!("{hint}Err"));
+                let ok = self.avro(ok, &format!("{ok_name}Value"));
+                let err = self.avro(err, &format!("{err_name}Value"));
+                json!([
+                    record(ok_name, vec![field("ok", ok, false)]),
+                    record(err_name, vec![field("error", err, false)]),
+                ])
+            }
+            TypeKind::Tuple { fields } => {
+                let name = self.name(schema, hint);
+                let fields = fields
+                    .iter()
+                    .enumerate()
+                    .map(|(i, item)| {
+                        let avro = self.avro(item, &format!("{name}Item{i}"));
+                        field(&format!("_{i}"), avro, false)
+                    })
+                    .collect();
+                record(name, fields)
+            }
+            TypeKind::Ref { .. } => unreachable!("references are resolved by avro()"),
+        };
+        if let (Some(docs), Value::Object(obj)) = (schema.docs(), &mut avro) {
+            obj.insert("doc".to_string(), json!(docs));
+        }
+        avro
+    }
+
+    /// The record fields for an object's properties
+    fn fields(&mut self, schema: &SchemaType, owner: &str) -> Vec<Value> {
+        let TypeKind::Object { required, .. } = &schema.kind else {
+            return vec![];
+        };
+        schema
+            .ordered_properties()
+            .into_iter()
+            .map(|(name, property)| {
+                let avro = self.avro(property, &format!("{owner}{}", pascal(name)));
+                let mut field = field(name, avro, !required.iter().any(|r| r == name));
+                if let Some(docs) = property.docs() {
+                    field["doc"] = json!(docs);
+                }
+                field
+            })
+            .collect()
+    }
+}
+
+fn record(name: String, fields: Vec<Value>) -> Value {
+    json!({ "type": "record", "name": name, "fields": fields })
+}
+
+/// A record field; an optional one may be `null` and defaults to it
+fn field(name: &str, avro: Value, optional: bool) -> Value {
+    match optional {
+        true => {
+            let members = match avro {
+                Value::Array(members) => std::iter::once(json!("null")).chain(members).collect(),
+                avro => vec![json!("null"), avro],
+            };
+            json!({ "name": name, "type": members, "default": null })
+        }
+        false => json!({ "name": name, "type": avro }),
+    }
+}
+
+fn integer(kind: &IntegerKind) -> &'static str {
+    match kind {
+        IntegerKind::I32 | IntegerKind::U8 => "int",
+        // u32 doesn't fit Avro's signed 32-bit int
+        IntegerKind::I64 | IntegerKind::U32 | IntegerKind::U64 | IntegerKind::Usize => "long",
+    }
+}
+
+/// A PascalCase part of a generated name, `user.created` -> `UserCreated`
+fn pascal(name: &str) -> String {
+    identifier(&schema::case::Case::Pascal.apply(name))
+}
+
+/// A name Avro accepts: letters, digits and `_`, not starting with a digit
+fn identifier(name: &str) -> String {
+    let ident: String = name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    match is_identifier(&ident) {
+        true => ident,
+        false => format!("_{ident}"),
+    }
+}
+
+fn is_identifier(name: &str) -> bool {
+    name.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
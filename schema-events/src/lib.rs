@@ -0,0 +1,550 @@
+use schema::{IntegerKind, Schema, SchemaType, Tagging, TypeKind, VariantCase};
+use schema_asyncapi::{Channel, Message};
+use serde_json::{Value, json};
+use std::collections::HashMap;
+use std::fmt;
+
+mod avro;
+
+/// A registered event type
+#[derive(Debug, Clone, PartialEq)]
+pub struct EventType {
+    /// Wire name stored in the envelope's `type` field, e.g. `user.created`
+    pub name: String,
+    /// Payload schema version stored in the envelope's `version` field
+    pub version: u32,
+    pub payload: SchemaType,
+}
+
+/// Registry of event types that share the standard envelope
+///
+/// Every event is wrapped as `{type, version, timestamp, payload}`.
+#[derive(Debug, Clone, Default)]
+pub struct EventRegistry {
+    events: Vec<EventType>,
+}
+
+/// A single decoded event envelope
+#[derive(Debug, Clone, PartialEq)]
+pub struct Envelope {
+    pub event_type: String,
+    pub version: u32,
+    /// RFC 3339 timestamp, kept as the raw string
+    pub timestamp: String,
+    pub payload: Value,
+}
+
+/// Errors produced while decoding JSON Lines event streams
+#[derive(Debug, Clone, PartialEq)]
+pub enum EventError {
+    /// The line is not valid JSON
+    Json(String),
+    /// A required envelope field is missing or has the wrong JSON type
+    MalformedEnvelope { field: &'static str },
+    /// The `type` field names an event that was never registered
+    UnknownEvent { event_type: String },
+    /// The event is registered, but under a different version
+    VersionMismatch {
+        event_type: String,
+        expected: u32,
+        found: u32,
+    },
+}
+
+impl fmt::Display for EventError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EventError::Json(err) => write!(f, "invalid JSON: {}", err),
+            EventError::MalformedEnvelope { field } => {
+                write!(f, "malformed envelope: missing or invalid `{}`", field)
+            }
+            EventError::UnknownEvent { event_type } => {
+                write!(f, "unknown event type {:?}", event_type)
+            }
+            EventError::VersionMismatch {
+                event_type,
+                expected,
+                found,
+            } => write!(
+                f,
+                "event {:?} has version {}, expected {}",
+                event_type, found, expected
+            ),
+        }
+    }
+}
+
+impl std::error::Error for EventError {}
+
+impl EventRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register an event type under a wire name and payload version
+    ///
+    /// Registering the same name again replaces the previous registration.
+    pub fn register<T: Schema>(self, name: &str, version: u32) -> Self {
+        self.register_schema(name, version, T::schema())
+    }
+
+    /// Register an event type from an explicit payload schema
+    pub fn register_schema(mut self, name: &str, version: u32, payload: SchemaType) -> Self {
+        let event = EventType {
+            name: name.to_string(),
+            version,
+            payload,
+        };
+
+        match self.events.iter_mut().find(|e| e.name == name) {
+            Some(existing) => *existing = event,
+            None => self.events.push(event),
+        }
+        self
+    }
+
+    /// Registered events in registration order
+    pub fn events(&self) -> &[EventType] {
+        &self.events
+    }
+
+    pub fn get(&self, name: &str) -> Option<&EventType> {
+        self.events.iter().find(|e| e.name == name)
+    }
+
+    /// The envelope schema: a Variant tagged internally by `type`, with one
+    /// case per registered event
+    ///
+    /// Each case holds the rest of the envelope, its `version` limited to
+    /// the registered one, so the schema accepts exactly the lines
+    /// [`encode_line`](Self::encode_line) writes and any backend (OpenAPI,
+    /// WIT, Anthropic, ...) can render the event stream contract.
+    pub fn envelope_schema(&self) -> SchemaType {
+        let cases = self.events.iter().map(envelope_case).collect();
+        let mut schema = SchemaType {
+            kind: TypeKind::Variant { cases },
+            description: Some("Event envelope".to_string()),
+            name: Some("EventEnvelope".to_string()),
+            ..Default::default()
+        };
+        schema.set_tagging(Tagging::Internal {
+            tag: "type".to_string(),
+        });
+        schema
+    }
+
+    /// The envelope as a JSON Schema (draft 2020-12) document
+    pub fn json_schema(&self) -> Value {
+        schema_jsonschema::schema_type_to_json_schema(&self.envelope_schema())
+    }
+
+    /// The envelope as an Avro schema: a record whose `payload` is a union
+    /// of the registered payloads
+    ///
+    /// Avro can't limit `type` to the registered names or `version` to the
+    /// registered versions; [`json_schema`](Self::json_schema) does.
+    pub fn avro_schema(&self) -> Value {
+        let mut module = avro::Module::default();
+        let name = module.unique("EventEnvelope");
+        let mut payloads = vec![];
+        for event in &self.events {
+            match module.avro(&event.payload, &format!("{name}Payload")) {
+                Value::Array(members) => payloads.extend(members),
+                payload => payloads.push(payload),
+            }
+        }
+        json!({
+            "type": "record",
+            "name": name,
+            "doc": "Event envelope",
+            "fields": [
+                { "name": "type", "type": "string", "doc": "Event type discriminator" },
+                { "name": "version", "type": "long", "doc": "Payload schema version" },
+                { "name": "timestamp", "type": "string", "doc": "RFC 3339 timestamp" },
+                { "name": "payload", "type": payloads },
+            ]
+        })
+    }
+
+    /// An AsyncAPI channel carrying one message per registered event, each
+    /// payload the envelope for that event
+    pub fn channel(&self, id: &str, address: &str) -> Channel {
+        self.events
+            .iter()
+            .fold(Channel::new(id, address), |channel, event| {
+                let mut payload = SchemaType {
+                    kind: TypeKind::Variant {
+                        cases: vec![envelope_case(event)],
+                    },
+                    name: Some(format!(
+                        "{}Envelope",
+                        event.payload.name.as_deref().unwrap_or("Event")
+                    )),
+                    ..Default::default()
+                };
+                payload.set_tagging(Tagging::Internal {
+                    tag: "type".to_string(),
+                });
+                let mut message = Message::from_schema(&event.name, payload);
+                message.description = event.payload.docs();
+                channel.message(message)
+            })
+    }
+
+    /// Encode a payload as a single JSON Lines record
+    ///
+    /// The version is taken from the registration; unknown event types are
+    /// rejected so producers can't emit events consumers don't know about.
+    pub fn encode_line(
+        &self,
+        event_type: &str,
+        timestamp: &str,
+        payload: Value,
+    ) -> Result<String, EventError> {
+        let event = self
+            .get(event_type)
+            .ok_or_else(|| EventError::UnknownEvent {
+                event_type: event_type.to_string(),
+            })?;
+
+        let envelope = json!({
+            "type": event.name,
+            "version": event.version,
+            "timestamp": timestamp,
+            "payload": payload,
+        });
+        Ok(envelope.to_string())
+    }
+
+    /// Decode and check a single JSON Lines record
+    pub fn decode_line(&self, line: &str) -> Result<Envelope, EventError> {
+        let value: Value =
+            serde_json::from_str(line).map_err(|err| EventError::Json(err.to_string()))?;
+
+        let event_type = value["type"]
+            .as_str()
+            .ok_or(EventError::MalformedEnvelope { field: "type" })?;
+        let version = value["version"]
+            .as_u64()
+            .and_then(|v| u32::try_from(v).ok())
+            .ok_or(EventError::MalformedEnvelope { field: "version" })?;
+        let timestamp = value["timestamp"]
+            .as_str()
+            .ok_or(EventError::MalformedEnvelope { field: "timestamp" })?;
+        let payload = value
+            .get("payload")
+            .ok_or(EventError::MalformedEnvelope { field: "payload" })?;
+
+        let event = self
+            .get(event_type)
+            .ok_or_else(|| EventError::UnknownEvent {
+                event_type: event_type.to_string(),
+            })?;
+        if event.version != version {
+            return Err(EventError::VersionMismatch {
+                event_type: event_type.to_string(),
+                expected: event.version,
+                found: version,
+            });
+        }
+
+        Ok(Envelope {
+            event_type: event_type.to_string(),
+            version,
+            timestamp: timestamp.to_string(),
+            payload: payload.clone(),
+        })
+    }
+
+    /// Decode a JSON Lines stream, skipping blank lines
+    pub fn decode_lines(&self, input: &str) -> Result<Vec<Envelope>, EventError> {
+        input
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| self.decode_line(line))
+            .collect()
+    }
+}
+
+/// The variant case for one event: the envelope's fields besides `type`
+fn envelope_case(event: &EventType) -> VariantCase {
+    let mut version = SchemaType {
+        kind: TypeKind::Integer(IntegerKind::U32),
+        description: Some("Payload schema version".to_string()),
+        ..Default::default()
+    };
+    version.constraints.minimum = Some(event.version.into());
+    version.constraints.maximum = Some(event.version.into());
+
+    let mut timestamp = SchemaType {
+        kind: TypeKind::String,
+        description: Some("RFC 3339 timestamp".to_string()),
+        ..Default::default()
+    };
+    timestamp.constraints.format = Some("date-time".to_string());
+
+    let mut properties = HashMap::new();
+    properties.insert("version".to_string(), version);
+    properties.insert("timestamp".to_string(), timestamp);
+    properties.insert("payload".to_string(), event.payload.clone());
+
+    VariantCase {
+        name: event.name.clone(),
+        data: Some(SchemaType::new(TypeKind::Object {
+            properties,
+            required: vec![
+                "version".to_string(),
+                "timestamp".to_string(),
+                "payload".to_string(),
+            ],
+        })),
+        description: event.payload.docs(),
+        ..Default::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use schema::validate::validate;
+
+    /// A user signed up
+    #[derive(Schema)]
+    #[allow(dead_code)]
+    struct UserCreated {
+        id: String,
+    }
+
+    #[derive(Schema)]
+    #[allow(dead_code)]
+    struct UserDeleted {
+        id: String,
+        reason: Option<String>,
+    }
+
+    fn registry() -> EventRegistry {
+        EventRegistry::new()
+            .register::<UserCreated>("user.created", 1)
+            .register::<UserDeleted>("user.deleted", 2)
+    }
+
+    #[test]
+    fn test_envelope_schema() {
+        let schema = registry().envelope_schema();
+        assert_eq!(
+            schema.tagging(),
+            Some(Tagging::Internal {
+                tag: "type".to_string()
+            })
+        );
+        let TypeKind::Variant { cases } = &schema.kind else {
+            panic!("Expected Variant envelope");
+        };
+        assert_eq!(cases.len(), 2);
+        assert_eq!(cases[0].name, "user.created");
+        assert_eq!(cases[0].description, Some("A user signed up".to_string()));
+        assert_eq!(cases[0].required(), ["version", "timestamp", "payload"]);
+        let fields = cases[1].fields();
+        let (_, payload) = fields.iter().find(|(name, _)| *name == "payload").unwrap();
+        assert_eq!(payload.name, Some("UserDeleted".to_string()));
+    }
+
+    #[test]
+    fn test_encoded_lines_validate() {
+        let registry = registry();
+        let envelope = registry.envelope_schema();
+        let line = registry
+            .encode_line(
+                "user.deleted",
+                "2024-01-01T00:00:00Z",
+                json!({"id": "u1", "reason": "spam"}),
+            )
+            .unwrap();
+        let line: Value = serde_json::from_str(&line).unwrap();
+        assert_eq!(validate(&envelope, &line), Ok(()));
+
+        // Only the registered version, and a payload of the event's type
+        let mut stale = line.clone();
+        stale["version"] = json!(1);
+        assert!(validate(&envelope, &stale).is_err());
+        let mut swapped = line.clone();
+        swapped["payload"] = json!({"id": 7});
+        assert!(validate(&envelope, &swapped).is_err());
+        let mut unknown = line;
+        unknown["type"] = json!("user.renamed");
+        assert!(validate(&envelope, &unknown).is_err());
+    }
+
+    #[test]
+    fn test_json_schema() {
+        let document = registry().json_schema();
+        let created = &document["oneOf"][0];
+        assert_eq!(created["properties"]["type"]["const"], "user.created");
+        assert_eq!(
+            created["properties"]["version"],
+            json!({
+                "type": "integer",
+                "minimum": 1,
+                "maximum": 1,
+                "description": "Payload schema version"
+            })
+        );
+        assert_eq!(
+            created["properties"]["payload"],
+            json!({ "$ref": "#/$defs/UserCreated" })
+        );
+        assert_eq!(document["$defs"]["UserDeleted"]["type"], "object");
+    }
+
+    #[test]
+    fn test_avro_schema() {
+        #[derive(Schema)]
+        #[allow(dead_code)]
+        enum Change {
+            Rename { to: String },
+            Close,
+        }
+
+        let registry = registry().register::<Change>("user.changed", 1);
+        assert_eq!(
+            registry.avro_schema()["fields"][3],
+            json!({
+                "name": "payload",
+                "type": [
+                    {
+                        "type": "record",
+                        "name": "UserCreated",
+                        "doc": "A user signed up",
+                        "fields": [{ "name": "id", "type": "string" }]
+                    },
+                    {
+                        "type": "record",
+                        "name": "UserDeleted",
+                        "fields": [
+                            { "name": "id", "type": "string" },
+                            { "name": "reason", "type": ["null", "string"], "default": null }
+                        ]
+                    },
+                    {
+                        "type": "record",
+                        "name": "ChangeRename",
+                        "fields": [{ "name": "to", "type": "string" }]
+                    },
+                    { "type": "record", "name": "ChangeClose", "fields": [] }
+                ]
+            })
+        );
+
+        // A recursive payload refers back to its record by name
+        #[derive(Schema)]
+        #[allow(dead_code)]
+        struct Folder {
+            children: Vec<Folder>,
+        }
+
+        let registry = EventRegistry::new().register::<Folder>("folder.created", 1);
+        assert_eq!(
+            registry.avro_schema()["fields"][3]["type"],
+            json!([{
+                "type": "record",
+                "name": "Folder",
+                "fields": [{ "name": "children", "type": { "type": "array", "items": "Folder" } }]
+            }])
+        );
+    }
+
+    #[test]
+    fn test_asyncapi_channel() {
+        let channel = registry().channel("users", "users.events");
+        assert_eq!(channel.messages.len(), 2);
+        let created = &channel.messages[0];
+        assert_eq!(created.name, "user.created");
+        assert_eq!(created.description, Some("A user signed up".to_string()));
+        assert_eq!(
+            created.payload.tagging(),
+            Some(Tagging::Internal {
+                tag: "type".to_string()
+            })
+        );
+
+        let document = schema_asyncapi::AsyncApiBuilder::new("Users", "1.0.0")
+            .channel(channel)
+            .build()
+            .unwrap();
+        let envelope = &document["components"]["schemas"]["UserCreatedEnvelope"];
+        assert_eq!(
+            envelope["oneOf"][0]["properties"]["type"]["const"],
+            "user.created"
+        );
+    }
+
+    #[test]
+    fn test_register_replaces() {
+        let registry = registry().register::<UserCreated>("user.created", 3);
+        assert_eq!(registry.events().len(), 2);
+        assert_eq!(registry.get("user.created").unwrap().version, 3);
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let registry = registry();
+        let line = registry
+            .encode_line("user.created", "2024-01-01T00:00:00Z", json!({"id": "u1"}))
+            .unwrap();
+
+        let envelope = registry.decode_line(&line).unwrap();
+        assert_eq!(envelope.event_type, "user.created");
+        assert_eq!(envelope.version, 1);
+        assert_eq!(envelope.payload, json!({"id": "u1"}));
+    }
+
+    #[test]
+    fn test_decode_errors() {
+        let registry = registry();
+
+        assert!(matches!(
+            registry.decode_line("not json"),
+            Err(EventError::Json(_))
+        ));
+        assert_eq!(
+            registry.decode_line(r#"{"type": "user.created"}"#),
+            Err(EventError::MalformedEnvelope { field: "version" })
+        );
+        assert_eq!(
+            registry.decode_line(
+                r#"{"type": "user.renamed", "version": 1, "timestamp": "t", "payload": {}}"#
+            ),
+            Err(EventError::UnknownEvent {
+                event_type: "user.renamed".to_string()
+            })
+        );
+        assert_eq!(
+            registry.decode_line(
+                r#"{"type": "user.deleted", "version": 1, "timestamp": "t", "payload": {}}"#
+            ),
+            Err(EventError::VersionMismatch {
+                event_type: "user.deleted".to_string(),
+                expected: 2,
+                found: 1
+            })
+        );
+    }
+
+    #[test]
+    fn test_decode_lines_skips_blank() {
+        let registry = registry();
+        let stream = format!(
+            "{}\n\n{}\n",
+            registry
+                .encode_line("user.created", "t1", json!({"id": "a"}))
+                .unwrap(),
+            registry
+                .encode_line("user.deleted", "t2", json!({"id": "a"}))
+                .unwrap()
+        );
+
+        let events = registry.decode_lines(&stream).unwrap();
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[1].version, 2);
+    }
+}
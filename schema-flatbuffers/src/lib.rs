@@ -0,0 +1,825 @@
+//! FlatBuffers schemas from schemas
+//!
+//! The root and every named type nested in it become a top-level `table`
+//! (or `enum`), each after the ones it uses; tuples of scalars are `struct`s.
+//! Arrays and sets are vectors, maps vectors of generated `Entry` tables
+//! keyed on `key`, and variants a table holding one `union`, whose members
+//! are a table per case. Anonymous types that need a declaration are named
+//! after where they appear (`EventMove`), as are the wrapper tables for
+//! positions FlatBuffers can't nest directly, such as a vector of vectors.
+//!
+//! Required strings, vectors and tables are `(required)`; optional scalars
+//! and enums default to `null` so absence stays apart from zero. Fields are
+//! numbered by declaration order, so new ones belong at the end.
+
+use schema::case::Case;
+use schema::{
+    GenerateOptions, IntegerKind, NumberKind, RefPreference, Schema, SchemaType, TypeKind,
+};
+use serde_json::Value;
+use std::collections::BTreeSet;
+
+/// Options for the generated `.fbs` file
+#[derive(Debug, Clone)]
+pub struct FlatbuffersOptions {
+    /// `namespace` declaration, e.g. `Game.Net`
+    pub namespace: Option<String>,
+    /// Whether to declare the root as the `root_type`
+    pub root_type: bool,
+    pub generate: GenerateOptions,
+}
+
+impl Default for FlatbuffersOptions {
+    fn default() -> Self {
+        FlatbuffersOptions {
+            namespace: None,
+            root_type: true,
+            generate: GenerateOptions::default(),
+        }
+    }
+}
+
+impl FlatbuffersOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn namespace(mut self, namespace: &str) -> Self {
+        self.namespace = Some(namespace.to_string());
+        self
+    }
+
+    /// Whether to end with `root_type`, on by default
+    pub fn root_type(mut self, root_type: bool) -> Self {
+        self.root_type = root_type;
+        self
+    }
+
+    /// Bound the schema before it is written
+    pub fn generate(mut self, generate: GenerateOptions) -> Self {
+        self.generate = generate;
+        self
+    }
+}
+
+/// The `.fbs` file for `T` and the named types it contains
+pub fn to_flatbuffers<T: Schema>() -> String {
+    schema_type_to_flatbuffers(&T::schema(), T::type_name())
+}
+
+/// The `.fbs` file for `T` and the named types it contains, with `options`
+pub fn to_flatbuffers_with_options<T: Schema>(options: &FlatbuffersOptions) -> String {
+    schema_type_to_flatbuffers_with_options(&T::schema(), T::type_name(), options)
+}
+
+/// The `.fbs` file for a SchemaType, named `type_name` (or the schema's own
+/// name, or `Root`)
+pub fn schema_type_to_flatbuffers(schema: &SchemaType, type_name: Option<&str>) -> String {
+    schema_type_to_flatbuffers_with_options(schema, type_name, &FlatbuffersOptions::default())
+}
+
+/// The `.fbs` file for a SchemaType, with `options`
+pub fn schema_type_to_flatbuffers_with_options(
+    schema: &SchemaType,
+    type_name: Option<&str>,
+    options: &FlatbuffersOptions,
+) -> String {
+    let schema = options.generate.apply(schema);
+    let mut module = Module::default();
+    let ident = module.unique(&identifier(
+        type_name.or(schema.name.as_deref()).unwrap_or("Root"),
+    ));
+    if let Some(name) = &schema.name {
+        module.named.push(Named {
+            name: name.clone(),
+            shape: shape(&schema),
+            ident: ident.clone(),
+            first: schema.clone(),
+        });
+    }
+    // Only a table can be the root
+    match is_table(&schema) {
+        true => module.declare(&ident, &schema),
+        false => module.wrap(&ident, &schema),
+    };
+
+    let mut output = String::new();
+    if let Some(namespace) = &options.namespace {
+        output.push_str(&format!("namespace {};\n", namespace));
+    }
+    for declaration in &module.output {
+        if !output.is_empty() {
+            output.push('\n');
+        }
+        output.push_str(declaration);
+    }
+    if options.root_type {
+        output.push_str(&format!("\nroot_type {};\n", ident));
+    }
+    output
+}
+
+/// A named type that has a declaration
+struct Named {
+    name: String,
+    /// Fingerprint without use-site annotations
+    shape: String,
+    ident: String,
+    /// The occurrence the declaration was written from
+    first: SchemaType,
+}
+
+/// What a field's type is, as far as FlatBuffers' rules go
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Storage {
+    /// Numbers, bools and enums: stored inline, can default to `null`
+    Scalar,
+    /// Fixed-size structs: stored inline, can't be absent
+    Struct,
+    Vector,
+    /// Strings and tables
+    Reference,
+    /// Can't be required, and only a table holds one
+    Union,
+}
+
+/// A field's type
+struct Fbs {
+    ty: String,
+    storage: Storage,
+}
+
+impl Fbs {
+    fn new(ty: impl Into<String>, storage: Storage) -> Self {
+        Fbs {
+            ty: ty.into(),
+            storage,
+        }
+    }
+}
+
+/// One field of a table or struct
+struct Field {
+    /// Wire name
+    name: String,
+    fbs: Fbs,
+    required: bool,
+    default: Option<String>,
+    key: bool,
+    docs: Option<String>,
+}
+
+impl Field {
+    fn new(name: &str, fbs: Fbs) -> Self {
+        Field {
+            name: name.to_string(),
+            fbs,
+            required: true,
+            default: None,
+            key: false,
+            docs: None,
+        }
+    }
+}
+
+#[derive(Default)]
+struct Module {
+    named: Vec<Named>,
+    /// Every type name in use
+    taken: BTreeSet<String>,
+    /// Declarations, each after the ones it uses
+    output: Vec<String>,
+    /// The empty table `null` values are written as
+    empty: Option<String>,
+}
+
+impl Module {
+    /// `base`, or `base2`, `base3`, ... if something already has the name
+    fn unique(&mut self, base: &str) -> String {
+        let ident = (1..)
+            .map(|n| match n {
+                1 => base.to_string(),
+                n => format!("{base}{n}"),
+            })
+            .find(|ident| !self.taken.contains(ident))
+            .unwrap();
+        self.taken.insert(ident.clone());
+        ident
+    }
+
+    /// The declaration `ident` for `schema`; types with none of their own
+    /// are wrapped in a table with one `value` field
+    fn declare(&mut self, ident: &str, schema: &SchemaType) -> Fbs {
+        match declares(schema) {
+            true => self.define(ident, schema),
+            false => self.wrap(ident, schema),
+        }
+    }
+
+    /// A table `ident` with one `value` field holding `schema`
+    fn wrap(&mut self, ident: &str, schema: &SchemaType) -> Fbs {
+        let fbs = self.expression(schema, &format!("{ident}Value"));
+        let field = Field::new("value", fbs);
+        self.table(ident, "table", schema.docs(), vec![field]);
+        Fbs::new(ident, Storage::Reference)
+    }
+
+    /// The declaration for a kind that has one of its own
+    fn define(&mut self, ident: &str, schema: &SchemaType) -> Fbs {
+        match &schema.kind {
+            TypeKind::Object { required, .. } => {
+                let mut fields = vec![];
+                for (name, property) in schema.ordered_properties() {
+                    let mut field = self.field(ident, name, property);
+                    field.required = required.iter().any(|r| r == name);
+                    fields.push(field);
+                }
+                self.table(ident, "table", schema.docs(), fields);
+                Fbs::new(ident, Storage::Reference)
+            }
+            TypeKind::Enum { variants } => {
+                let names = numbered(variants.iter().map(|v| identifier(v)));
+                let base = match variants.len() {
+                    0..=256 => "ubyte",
+                    _ => "ushort",
+                };
+                let mut declaration = comment(schema.docs().as_deref(), "");
+                declaration.push_str(&format!(
+                    "enum {ident} : {base} {{\n{}\n}}\n",
+                    names
+                        .iter()
+                        .map(|name| format!("  {name}"))
+                        .collect::<Vec<_>>()
+                        .join(",\n")
+                ));
+                self.output.push(declaration);
+                Fbs::new(ident, Storage::Scalar)
+            }
+            TypeKind::TaggedUnion {
+                tag_field,
+                tag_variants,
+                data_fields,
+            } => {
+                let tag = self.unique(&format!("{ident}{}", pascal(tag_field)));
+                let tag_schema = SchemaType {
+                    kind: TypeKind::Enum {
+                        variants: tag_variants.clone(),
+                    },
+                    ..Default::default()
+                };
+                let tag = self.define(&tag, &tag_schema);
+
+                let mut fields = vec![Field::new(tag_field, tag)];
+                let mut names: Vec<&String> = data_fields.keys().collect();
+                names.sort();
+                for name in names {
+                    let mut field = self.field(ident, name, &data_fields[name]);
+                    field.required = false;
+                    fields.push(field);
+                }
+                self.table(ident, "table", schema.docs(), fields);
+                Fbs::new(ident, Storage::Reference)
+            }
+            TypeKind::Variant { cases } => {
+                let mut members = vec![];
+                for case in cases {
+                    let context = format!("{ident}{}", pascal(&case.name));
+                    let member = match &case.data {
+                        // An anonymous object is the case's own table
+                        Some(data) if data.name.is_none() && is_table(data) => {
+                            let member = self.unique(&context);
+                            self.define(&member, data);
+                            member
+                        }
+                        Some(data) => {
+                            let member = self.unique(&context);
+                            let fbs = self.reference(data, &format!("{member}Value"));
+                            self.table(&member, "table", None, vec![Field::new("value", fbs)]);
+                            member
+                        }
+                        None => {
+                            let member = self.unique(&context);
+                            self.table(&member, "table", None, vec![]);
+                            member
+                        }
+                    };
+                    members.push((member, case.description.clone()));
+                }
+                self.union(ident, schema.docs(), members)
+            }
+            TypeKind::Result { ok, err } => {
+                let mut members = vec![];
+                for (suffix, data) in [("Ok", ok), ("Error", err)] {
+                    let member = self.unique(&format!("{ident}{suffix}"));
+                    let fbs = self.reference(data, &format!("{member}Value"));
+                    self.table(&member, "table", None, vec![Field::new("value", fbs)]);
+                    members.push((member, None));
+                }
+                self.union(ident, schema.docs(), members)
+            }
+            TypeKind::Tuple { fields: items } => {
+                let mut fields = vec![];
+                for (i, item) in items.iter().enumerate() {
+                    let fbs = self.reference(item, &format!("{ident}Item{i}"));
+                    fields.push(Field::new(&format!("item{i}"), fbs));
+                }
+                // Fixed-size when every item is; structs can't be empty
+                let inline = !fields.is_empty()
+                    && fields
+                        .iter()
+                        .all(|f| matches!(f.fbs.storage, Storage::Scalar | Storage::Struct));
+                match inline {
+                    true => {
+                        self.table(ident, "struct", schema.docs(), fields);
+                        Fbs::new(ident, Storage::Struct)
+                    }
+                    false => {
+                        self.table(ident, "table", schema.docs(), fields);
+                        Fbs::new(ident, Storage::Reference)
+                    }
+                }
+            }
+            _ => unreachable!("declares() covers the kinds defined here"),
+        }
+    }
+
+    /// A nested schema: its declaration's name if it is a named type,
+    /// otherwise written in place, with anonymous declarations named after
+    /// `context`
+    fn reference(&mut self, schema: &SchemaType, context: &str) -> Fbs {
+        let Some(name) = &schema.name else {
+            return self.expression(schema, context);
+        };
+        if matches!(schema.kind, TypeKind::Ref { .. })
+            || schema.ref_preference() == Some(RefPreference::Inline)
+        {
+            return self.expression(schema, context);
+        }
+
+        let shape = shape(schema);
+        if let Some(named) = self
+            .named
+            .iter()
+            .find(|named| named.name == *name && named.shape == shape)
+        {
+            return declared(named);
+        }
+
+        // A different type may already have taken the name
+        let ident = self.unique(&identifier(name));
+        self.named.push(Named {
+            name: name.clone(),
+            shape,
+            ident: ident.clone(),
+            first: schema.clone(),
+        });
+        self.declare(&ident, schema)
+    }
+
+    fn expression(&mut self, schema: &SchemaType, context: &str) -> Fbs {
+        if declares(schema) {
+            let ident = self.unique(context);
+            return self.define(&ident, schema);
+        }
+
+        match &schema.kind {
+            TypeKind::String => Fbs::new("string", Storage::Reference),
+            TypeKind::Integer(kind) => Fbs::new(integer(kind), Storage::Scalar),
+            TypeKind::Number(NumberKind::F32) => Fbs::new("float", Storage::Scalar),
+            TypeKind::Number(NumberKind::F64) => Fbs::new("double", Storage::Scalar),
+            TypeKind::Boolean => Fbs::new("bool", Storage::Scalar),
+            TypeKind::Null => {
+                let empty = match &self.empty {
+                    Some(empty) => empty.clone(),
+                    None => {
+                        let empty = self.unique("Empty");
+                        self.table(&empty, "table", None, vec![]);
+                        self.empty = Some(empty.clone());
+                        empty
+                    }
+                };
+                Fbs::new(empty, Storage::Reference)
+            }
+            TypeKind::Array { items } | TypeKind::Set { items, .. } => {
+                let context = format!("{context}Item");
+                let items = self.reference(items, &context);
+                let items = self.element(items, &context);
+                Fbs::new(format!("[{}]", items.ty), Storage::Vector)
+            }
+            TypeKind::Map { key, value, .. } => {
+                let entry = self.unique(&format!("{context}Entry"));
+                let mut key = Field::new("key", self.reference(key, &format!("{entry}Key")));
+                // Sorted lookup by key works on strings and scalars
+                key.key = key.fbs.ty == "string" || key.fbs.storage == Storage::Scalar;
+                let value = self.reference(value, &format!("{entry}Value"));
+                self.table(&entry, "table", None, vec![key, Field::new("value", value)]);
+                Fbs::new(format!("[{entry}]"), Storage::Vector)
+            }
+            TypeKind::Ref { name } => match self.named.iter().find(|named| named.name == *name) {
+                Some(named) => declared(named),
+                None => Fbs::new(identifier(name), Storage::Reference),
+            },
+            _ => unreachable!("declares() covers the remaining kinds"),
+        }
+    }
+
+    /// A vector's element type, wrapping vectors in a table named `context`
+    /// since FlatBuffers has no vectors of vectors
+    fn element(&mut self, fbs: Fbs, context: &str) -> Fbs {
+        if fbs.storage != Storage::Vector {
+            return fbs;
+        }
+        let ident = self.unique(context);
+        self.table(&ident, "table", None, vec![Field::new("values", fbs)]);
+        Fbs::new(ident, Storage::Reference)
+    }
+
+    /// A field of the table `owner`
+    fn field(&mut self, owner: &str, name: &str, schema: &SchemaType) -> Field {
+        let fbs = self.reference(schema, &format!("{owner}{}", pascal(name)));
+        let mut field = Field::new(name, fbs);
+        field.default = default(schema);
+        field.docs = self.use_site_docs(schema);
+        field
+    }
+
+    /// A `union ident_Kind` of `members` and the table `ident` holding it
+    fn union(
+        &mut self,
+        ident: &str,
+        docs: Option<String>,
+        members: Vec<(String, Option<String>)>,
+    ) -> Fbs {
+        let union = self.unique(&format!("{ident}Kind"));
+        let lines: Vec<String> = members
+            .iter()
+            .map(|(member, docs)| format!("{}  {member}", comment(docs.as_deref(), "  ")))
+            .collect();
+        self.output
+            .push(format!("union {union} {{\n{}\n}}\n", lines.join(",\n")));
+        let field = Field::new("kind", Fbs::new(union, Storage::Union));
+        self.table(ident, "table", docs, vec![field]);
+        Fbs::new(ident, Storage::Reference)
+    }
+
+    /// Write `table ident` or `struct ident`
+    fn table(&mut self, ident: &str, keyword: &str, docs: Option<String>, fields: Vec<Field>) {
+        let mut lines = vec![];
+        let names = numbered(fields.iter().map(|field| field_name(&field.name)));
+        for (field, name) in fields.iter().zip(names) {
+            let mut attributes = vec![];
+            let mut default = String::new();
+            match field.fbs.storage {
+                Storage::Scalar if keyword == "table" => {
+                    if let Some(value) = &field.default {
+                        default = format!(" = {value}");
+                    } else if !field.required {
+                        default = " = null".to_string();
+                    }
+                }
+                // Struct fields are always present and take no attributes
+                Storage::Vector | Storage::Reference | Storage::Struct
+                    if keyword == "table" && field.required && !field.key =>
+                {
+                    attributes.push("required");
+                }
+                _ => {}
+            }
+            if field.key {
+                attributes.push("key");
+            }
+            let attributes = match attributes.len() {
+                0 => String::new(),
+                _ => format!(" ({})", attributes.join(", ")),
+            };
+            lines.push(format!(
+                "{}  {name}:{}{default}{attributes};",
+                comment(field.docs.as_deref(), "  "),
+                field.fbs.ty
+            ));
+        }
+
+        let mut declaration = comment(docs.as_deref(), "");
+        match lines.is_empty() {
+            true => declaration.push_str(&format!("{keyword} {ident} {{}}\n")),
+            false => {
+                declaration.push_str(&format!("{keyword} {ident} {{\n{}\n}}\n", lines.join("\n")))
+            }
+        }
+        self.output.push(declaration);
+    }
+
+    /// The docs for a field, unless its type's declaration already has them
+    fn use_site_docs(&self, schema: &SchemaType) -> Option<String> {
+        schema.docs().filter(|docs| {
+            let declared = schema.name.as_ref().and_then(|name| {
+                self.named
+                    .iter()
+                    .find(|named| named.name == *name && named.shape == shape(schema))
+            });
+            declared.is_none_or(|named| named.first.docs().as_ref() != Some(docs))
+        })
+    }
+}
+
+/// The type of a field holding a declared type
+fn declared(named: &Named) -> Fbs {
+    let storage = match &named.first.kind {
+        TypeKind::Enum { .. } => Storage::Scalar,
+        TypeKind::Tuple { fields } if !fields.is_empty() && fields.iter().all(inline) => {
+            Storage::Struct
+        }
+        _ => Storage::Reference,
+    };
+    Fbs::new(named.ident.clone(), storage)
+}
+
+/// Whether a tuple item keeps its tuple a fixed-size struct
+fn inline(schema: &SchemaType) -> bool {
+    match &schema.kind {
+        TypeKind::Integer(_) | TypeKind::Number(_) | TypeKind::Boolean | TypeKind::Enum { .. } => {
+            true
+        }
+        TypeKind::Tuple { fields } => !fields.is_empty() && fields.iter().all(inline),
+        _ => false,
+    }
+}
+
+/// Whether `schema` is written as a declaration of its own
+fn declares(schema: &SchemaType) -> bool {
+    matches!(
+        schema.kind,
+        TypeKind::Object { .. }
+            | TypeKind::Enum { .. }
+            | TypeKind::TaggedUnion { .. }
+            | TypeKind::Variant { .. }
+            | TypeKind::Result { .. }
+            | TypeKind::Tuple { .. }
+    )
+}
+
+/// Whether `schema` is declared as a table
+fn is_table(schema: &SchemaType) -> bool {
+    match &schema.kind {
+        TypeKind::Tuple { .. } => !inline(schema),
+        TypeKind::Enum { .. } => false,
+        _ => declares(schema),
+    }
+}
+
+/// The `= value` literal for a scalar field's default
+fn default(schema: &SchemaType) -> Option<String> {
+    match (&schema.kind, schema.default.as_ref()?) {
+        (TypeKind::Boolean, Value::Bool(b)) => Some(b.to_string()),
+        (TypeKind::Integer(_), Value::Number(n)) if !n.is_f64() => Some(n.to_string()),
+        (TypeKind::Number(_), Value::Number(n)) => Some(n.to_string()),
+        (TypeKind::Enum { variants }, Value::String(s)) => {
+            let i = variants.iter().position(|variant| variant == s)?;
+            Some(numbered(variants.iter().map(|v| identifier(v))).swap_remove(i))
+        }
+        _ => None,
+    }
+}
+
+fn integer(kind: &IntegerKind) -> &'static str {
+    match kind {
+        IntegerKind::I32 => "int",
+        IntegerKind::I64 => "long",
+        IntegerKind::U8 => "ubyte",
+        IntegerKind::U32 => "uint",
+        IntegerKind::U64 | IntegerKind::Usize => "ulong",
+    }
+}
+
+/// `/// ` lines for `docs` at `indent`, ending in a newline
+fn comment(docs: Option<&str>, indent: &str) -> String {
+    let Some(docs) = docs else {
+        return String::new();
+    };
+    docs.lines()
+        .map(|line| match line {
+            "" => format!("{indent}///\n"),
+            line => format!("{indent}/// {line}\n"),
+        })
+        .collect()
+}
+
+/// A type name for part of another's, `per_role` -> `PerRole`
+fn pascal(name: &str) -> String {
+    identifier(&Case::Pascal.apply(name))
+}
+
+/// A name usable as a FlatBuffers identifier
+fn identifier(name: &str) -> String {
+    let ident: String = name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    match ident.starts_with(|c: char| c.is_ascii_digit()) {
+        true => format!("_{ident}"),
+        false => ident,
+    }
+}
+
+/// A field name in FlatBuffers' snake_case
+fn field_name(name: &str) -> String {
+    let ident = identifier(&Case::Snake.apply(name));
+    match ident.starts_with('_') {
+        true => format!("field{ident}"),
+        false => ident,
+    }
+}
+
+/// The names of one declaration's fields or enumerants, numbered where two
+/// wire names come out the same
+fn numbered(names: impl IntoIterator<Item = String>) -> Vec<String> {
+    let mut taken = BTreeSet::new();
+    names
+        .into_iter()
+        .map(|base| {
+            (1..)
+                .map(|n| match n {
+                    1 => base.clone(),
+                    n => format!("{base}{n}"),
+                })
+                .find(|name| taken.insert(name.clone()))
+                .unwrap()
+        })
+        .collect()
+}
+
+/// What makes two occurrences of a named type the same declaration: its
+/// fingerprint without the annotations a field adds to it
+fn shape(schema: &SchemaType) -> String {
+    let mut bare = schema.clone();
+    bare.title = None;
+    bare.description = None;
+    bare.default = None;
+    for key in [
+        schema::DEPRECATED,
+        schema::READ_ONLY,
+        schema::WRITE_ONLY,
+        schema::SINCE,
+        schema::REF_PREFERENCE,
+    ] {
+        bare.extensions.remove(key);
+    }
+    bare.fingerprint()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_structs_keys_and_nulls() {
+        /// One frame of a replay
+        #[derive(Schema)]
+        #[allow(dead_code)]
+        struct Frame {
+            position: (f32, f32),
+            bounds: ((i32, i32), (i32, i32)),
+            label: (String, u8),
+            heat: Option<f32>,
+            note: Option<String>,
+            players: HashMap<String, u8>,
+            grid: Vec<Vec<u8>>,
+            // The same field name as heat in snake_case
+            #[schema(rename = "HEAT")]
+            legacy_heat: f32,
+        }
+
+        let options = FlatbuffersOptions::new().namespace("Game.Net");
+        assert_eq!(
+            to_flatbuffers_with_options::<Frame>(&options),
+            r#"namespace Game.Net;
+
+struct FramePosition {
+  item0:float;
+  item1:float;
+}
+
+struct FrameBoundsItem0 {
+  item0:int;
+  item1:int;
+}
+
+struct FrameBoundsItem1 {
+  item0:int;
+  item1:int;
+}
+
+struct FrameBounds {
+  item0:FrameBoundsItem0;
+  item1:FrameBoundsItem1;
+}
+
+table FrameLabel {
+  item0:string (required);
+  item1:ubyte;
+}
+
+table FramePlayersEntry {
+  key:string (key);
+  value:ubyte;
+}
+
+table FrameGridItem {
+  values:[ubyte] (required);
+}
+
+/// One frame of a replay
+table Frame {
+  position:FramePosition (required);
+  bounds:FrameBounds (required);
+  label:FrameLabel (required);
+  heat:float = null;
+  note:string;
+  /// Unordered map/dictionary of key-value pairs
+  players:[FramePlayersEntry] (required);
+  grid:[FrameGridItem] (required);
+  heat2:float;
+}
+
+root_type Frame;
+"#
+        );
+    }
+
+    #[test]
+    fn test_roots_and_wide_enums() {
+        // Only a table can be the root
+        assert_eq!(
+            to_flatbuffers::<Vec<String>>(),
+            "table Root {\n  value:[string] (required);\n}\n\nroot_type Root;\n"
+        );
+
+        let wide = SchemaType {
+            kind: TypeKind::Enum {
+                variants: (0..300).map(|i| format!("v{i}")).collect(),
+            },
+            ..Default::default()
+        };
+        let fbs = schema_type_to_flatbuffers(&wide, Some("Wide"));
+        assert!(
+            fbs.starts_with("enum WideValue : ushort {\n  v0,\n"),
+            "{fbs}"
+        );
+        assert!(
+            fbs.ends_with("  v299\n}\n\ntable Wide {\n  value:WideValue;\n}\n\nroot_type Wide;\n"),
+            "{fbs}"
+        );
+
+        // Enumerants that only differ in punctuation are numbered
+        let status = SchemaType {
+            kind: TypeKind::Enum {
+                variants: vec!["in-progress".into(), "in_progress".into()],
+            },
+            default: Some(Value::from("in_progress")),
+            ..Default::default()
+        };
+        let fbs = schema_type_to_flatbuffers(&status, Some("Status"));
+        assert!(
+            fbs.starts_with("enum StatusValue : ubyte {\n  in_progress,\n  in_progress2\n}\n"),
+            "{fbs}"
+        );
+        assert_eq!(default(&status).unwrap(), "in_progress2");
+    }
+
+    #[test]
+    fn test_variants_become_unions() {
+        #[derive(Schema)]
+        #[allow(dead_code)]
+        enum Event {
+            Start,
+            Move { x: i64 },
+            Batch(Vec<u32>),
+        }
+
+        let options = FlatbuffersOptions::new().root_type(false);
+        assert_eq!(
+            to_flatbuffers_with_options::<Event>(&options),
+            r#"table EventStart {}
+
+table EventMove {
+  x:long;
+}
+
+table EventBatch {
+  value:[uint] (required);
+}
+
+union EventKind {
+  EventStart,
+  EventMove,
+  EventBatch
+}
+
+table Event {
+  kind:EventKind;
+}
+"#
+        );
+    }
+}
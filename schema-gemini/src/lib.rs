@@ -0,0 +1,296 @@
+use schema::{GenerateOptions, IntegerKind, NumberKind, SchemaType, Tagging, TypeKind};
+use serde_json::{Value, json};
+
+/// Formats Gemini accepts on `STRING` schemas; others are dropped
+const STRING_FORMATS: &[&str] = &["enum", "date-time"];
+
+/// Convert a SchemaType to a Gemini function-calling schema
+///
+/// Key differences from standard JSON Schema:
+/// - Uppercase type names (`STRING`, `OBJECT`, ...); see [`lowercase_types`]
+/// - No `$ref`: everything is inlined, and recursive references become a
+///   placeholder `OBJECT`
+/// - No `additionalProperties`, so maps are arrays of `{key, value}` entries
+/// - Tagged enums flatten into one object around the discriminator, as for
+///   Anthropic
+/// - Object properties keep their declaration order via `propertyOrdering`
+pub fn to_gemini_schema(schema: &SchemaType) -> Value {
+    let mut obj = serde_json::Map::new();
+
+    if let Some(title) = &schema.title {
+        obj.insert("title".to_string(), json!(title));
+    }
+    if let Some(desc) = &schema.description {
+        obj.insert("description".to_string(), json!(desc));
+    }
+    for (keyword, value) in schema.constraints.json_keywords() {
+        match keyword.as_str() {
+            "uniqueItems" => {}
+            "format" if !STRING_FORMATS.contains(&value.as_str().unwrap_or_default()) => {}
+            _ => {
+                obj.insert(keyword, value);
+            }
+        }
+    }
+    if let Some(default) = &schema.default {
+        obj.insert("default".to_string(), default.clone());
+    }
+
+    match &schema.kind {
+        TypeKind::String => {
+            obj.insert("type".to_string(), json!("STRING"));
+        }
+
+        TypeKind::Integer(kind) => {
+            obj.insert("type".to_string(), json!("INTEGER"));
+            let format = match kind {
+                IntegerKind::I32 | IntegerKind::U8 => "int32",
+                IntegerKind::I64 | IntegerKind::U32 | IntegerKind::U64 | IntegerKind::Usize => {
+                    "int64"
+                }
+            };
+            obj.insert("format".to_string(), json!(format));
+        }
+
+        TypeKind::Number(kind) => {
+            obj.insert("type".to_string(), json!("NUMBER"));
+            let format = match kind {
+                NumberKind::F32 => "float",
+                NumberKind::F64 => "double",
+            };
+            obj.insert("format".to_string(), json!(format));
+        }
+
+        TypeKind::Boolean => {
+            obj.insert("type".to_string(), json!("BOOLEAN"));
+        }
+
+        TypeKind::Null => {
+            obj.insert("type".to_string(), json!("NULL"));
+        }
+
+        TypeKind::Object { required, .. } => {
+            let ordered = schema.ordered_properties();
+            let props: serde_json::Map<String, Value> = ordered
+                .iter()
+                .map(|(name, property)| (name.to_string(), to_gemini_schema(property)))
+                .collect();
+
+            obj.insert("type".to_string(), json!("OBJECT"));
+            obj.insert("properties".to_string(), Value::Object(props));
+            obj.insert("required".to_string(), json!(required));
+            obj.insert(
+                "propertyOrdering".to_string(),
+                json!(ordered.iter().map(|(name, _)| name).collect::<Vec<_>>()),
+            );
+        }
+
+        TypeKind::Array { items } | TypeKind::Set { items, .. } => {
+            obj.insert("type".to_string(), json!("ARRAY"));
+            obj.insert("items".to_string(), to_gemini_schema(items));
+        }
+
+        TypeKind::Map { key, value, .. } => {
+            // Objects can't have open-ended properties, so list the entries
+            obj.insert("type".to_string(), json!("ARRAY"));
+            obj.insert(
+                "items".to_string(),
+                json!({
+                    "type": "OBJECT",
+                    "properties": {
+                        "key": to_gemini_schema(key),
+                        "value": to_gemini_schema(value)
+                    },
+                    "required": ["key", "value"],
+                    "propertyOrdering": ["key", "value"]
+                }),
+            );
+        }
+
+        TypeKind::Enum { variants } => {
+            obj.insert("type".to_string(), json!("STRING"));
+            obj.insert("format".to_string(), json!("enum"));
+            obj.insert("enum".to_string(), json!(variants));
+        }
+
+        TypeKind::TaggedUnion {
+            tag_field,
+            tag_variants,
+            data_fields,
+        } => {
+            let mut properties = serde_json::Map::new();
+            properties.insert(
+                tag_field.clone(),
+                json!({
+                    "type": "STRING",
+                    "format": "enum",
+                    "enum": tag_variants,
+                }),
+            );
+            for (field_name, field_schema) in data_fields {
+                properties.insert(field_name.clone(), to_gemini_schema(field_schema));
+            }
+
+            obj.insert("type".to_string(), json!("OBJECT"));
+            obj.insert("properties".to_string(), Value::Object(properties));
+            obj.insert("required".to_string(), json!([tag_field]));
+        }
+
+        TypeKind::Variant { cases } if schema.tagging() == Some(Tagging::Untagged) => {
+            let payloads: Vec<Value> = cases
+                .iter()
+                .map(|case| match &case.data {
+                    Some(data) => to_gemini_schema(data),
+                    None => json!({ "type": "NULL" }),
+                })
+                .collect();
+            obj.insert("anyOf".to_string(), json!(payloads));
+        }
+
+        TypeKind::Variant { cases } => {
+            // One flat object: the discriminator plus every case's fields,
+            // all optional since each depends on the case
+            let tagging = schema.tagging();
+            let tag = tagging
+                .as_ref()
+                .and_then(|tagging| tagging.tag())
+                .unwrap_or("type");
+            let mut properties = serde_json::Map::new();
+            let mut ordering = vec![tag.to_string()];
+
+            let tag_variants: Vec<String> = cases.iter().map(|c| c.name.clone()).collect();
+            properties.insert(
+                tag.to_string(),
+                json!({
+                    "type": "STRING",
+                    "format": "enum",
+                    "enum": tag_variants,
+                }),
+            );
+
+            if let Some(Tagging::Adjacent { content, .. }) = &tagging {
+                let payloads: Vec<Value> = cases
+                    .iter()
+                    .filter_map(|case| case.data.as_ref())
+                    .map(to_gemini_schema)
+                    .collect();
+                if !payloads.is_empty() {
+                    properties.insert(content.clone(), json!({ "anyOf": payloads }));
+                    ordering.push(content.clone());
+                }
+            } else {
+                for case in cases {
+                    for (field_name, field_schema) in case.fields() {
+                        if !properties.contains_key(field_name) {
+                            properties
+                                .insert(field_name.to_string(), to_gemini_schema(field_schema));
+                            ordering.push(field_name.to_string());
+                        }
+                    }
+                }
+
+                let requirements: Vec<String> = cases
+                    .iter()
+                    .filter(|case| !case.required().is_empty())
+                    .map(|case| format!("{:?} requires {}", case.name, case.required().join(", ")))
+                    .collect();
+                if !requirements.is_empty() {
+                    properties[tag]["description"] =
+                        json!(format!("Selects the case; {}", requirements.join("; ")));
+                }
+            }
+
+            obj.insert("type".to_string(), json!("OBJECT"));
+            obj.insert("properties".to_string(), Value::Object(properties));
+            obj.insert("required".to_string(), json!([tag]));
+            obj.insert("propertyOrdering".to_string(), json!(ordering));
+        }
+
+        TypeKind::Result { ok, err } => {
+            obj.insert("type".to_string(), json!("OBJECT"));
+            obj.insert(
+                "properties".to_string(),
+                json!({
+                    "ok": to_gemini_schema(ok),
+                    "error": to_gemini_schema(err),
+                }),
+            );
+            obj.insert("propertyOrdering".to_string(), json!(["ok", "error"]));
+            obj.insert(
+                "description".to_string(),
+                json!("Result type - exactly one of ok or error will be present"),
+            );
+        }
+
+        TypeKind::Tuple { fields } => {
+            // No positional items, so any field's type may appear anywhere;
+            // only the length is exact
+            obj.insert("type".to_string(), json!("ARRAY"));
+            let mut items: Vec<Value> = vec![];
+            for field in fields.iter().map(to_gemini_schema) {
+                if !items.contains(&field) {
+                    items.push(field);
+                }
+            }
+            match items.len() {
+                0 => {}
+                1 => {
+                    obj.insert("items".to_string(), items.remove(0));
+                }
+                _ => {
+                    obj.insert("items".to_string(), json!({ "anyOf": items }));
+                }
+            }
+            obj.insert("minItems".to_string(), json!(fields.len()));
+            obj.insert("maxItems".to_string(), json!(fields.len()));
+        }
+
+        TypeKind::Ref { name } => {
+            // The enclosing type is being described already; Gemini can't
+            // point back at it
+            return json!({
+                "type": "OBJECT",
+                "description": format!("Another {name}, nested recursively"),
+            });
+        }
+    }
+
+    Value::Object(obj)
+}
+
+/// Convert a SchemaType to a Gemini function-calling schema, bounded by `options`
+pub fn to_gemini_schema_with_options(schema: &SchemaType, options: &GenerateOptions) -> Value {
+    to_gemini_schema(&options.apply(schema))
+}
+
+/// A function declaration for Gemini's `tools[].functionDeclarations`
+pub fn to_gemini_function(name: &str, description: &str, parameters: &SchemaType) -> Value {
+    json!({
+        "name": name,
+        "description": description,
+        "parameters": to_gemini_schema(parameters),
+    })
+}
+
+/// Rewrite `type` values to lowercase, for API versions that take JSON
+/// Schema type names
+pub fn lowercase_types(schema: &mut Value) {
+    match schema {
+        Value::Object(obj) => {
+            for (key, value) in obj.iter_mut() {
+                match value {
+                    Value::String(ty) if key == "type" => *ty = ty.to_lowercase(),
+                    // Values, not schemas
+                    _ if key == "default" || key == "enum" => {}
+                    // Property names are user data, not schemas
+                    Value::Object(properties) if key == "properties" => {
+                        properties.values_mut().for_each(lowercase_types)
+                    }
+                    _ => lowercase_types(value),
+                }
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(lowercase_types),
+        _ => {}
+    }
+}
@@ -0,0 +1,111 @@
+use schema::Schema;
+use schema_gemini::{lowercase_types, to_gemini_function, to_gemini_schema};
+use serde_json::json;
+use std::collections::HashMap;
+
+#[derive(Schema)]
+#[allow(dead_code)]
+struct GetWeather {
+    /// City to look up
+    city: String,
+    days: Option<u8>,
+    unit: Unit,
+}
+
+#[derive(Schema)]
+#[allow(dead_code)]
+#[schema(rename_all_variants = "lowercase")]
+enum Unit {
+    Celsius,
+    Fahrenheit,
+}
+
+#[derive(Schema)]
+#[allow(dead_code)]
+#[schema(tag = "action")]
+enum Command {
+    Stop,
+    Move { x: f64, y: f64 },
+}
+
+#[derive(Schema)]
+#[allow(dead_code)]
+struct Category {
+    name: String,
+    children: Vec<Category>,
+}
+
+#[test]
+fn test_struct_to_gemini() {
+    let gemini = to_gemini_schema(&GetWeather::schema());
+
+    let expected = json!({
+        "type": "OBJECT",
+        "properties": {
+            "city": { "type": "STRING", "description": "City to look up" },
+            "days": { "type": "INTEGER", "format": "int32" },
+            "unit": { "type": "STRING", "format": "enum", "enum": ["celsius", "fahrenheit"] }
+        },
+        "required": ["city", "unit"],
+        "propertyOrdering": ["city", "days", "unit"]
+    });
+
+    assert_eq!(gemini, expected);
+}
+
+#[test]
+fn test_tagged_enum_flattens() {
+    let gemini = to_gemini_schema(&Command::schema());
+
+    assert_eq!(gemini["type"], "OBJECT");
+    assert_eq!(
+        gemini["properties"]["action"]["enum"],
+        json!(["Stop", "Move"])
+    );
+    assert_eq!(gemini["properties"]["x"]["type"], "NUMBER");
+    assert_eq!(gemini["required"], json!(["action"]));
+    assert_eq!(gemini["propertyOrdering"], json!(["action", "x", "y"]));
+}
+
+#[test]
+fn test_maps_and_recursion_avoid_refs() {
+    let map = HashMap::<String, i64>::schema();
+    let gemini = to_gemini_schema(&map);
+    assert_eq!(gemini["type"], "ARRAY");
+    assert_eq!(gemini["items"]["required"], json!(["key", "value"]));
+    assert_eq!(gemini["items"]["properties"]["value"]["format"], "int64");
+
+    let gemini = to_gemini_schema(&Category::schema());
+    let nested = &gemini["properties"]["children"]["items"];
+    assert_eq!(nested["type"], "OBJECT");
+    assert!(nested.get("$ref").is_none());
+}
+
+#[test]
+fn test_function_declaration() {
+    let function = to_gemini_function("get_weather", "Look up a forecast", &GetWeather::schema());
+
+    assert_eq!(function["name"], "get_weather");
+    assert_eq!(function["description"], "Look up a forecast");
+    assert_eq!(function["parameters"]["type"], "OBJECT");
+}
+
+#[test]
+fn test_lowercase_types() {
+    #[derive(Schema)]
+    #[allow(dead_code)]
+    struct Filter {
+        #[schema(rename = "type")]
+        kind: Unit,
+    }
+
+    let mut gemini = to_gemini_schema(&Filter::schema());
+    lowercase_types(&mut gemini);
+
+    assert_eq!(gemini["type"], "object");
+    assert_eq!(gemini["properties"]["type"]["type"], "string");
+    assert_eq!(
+        gemini["properties"]["type"]["enum"],
+        json!(["celsius", "fahrenheit"])
+    );
+}
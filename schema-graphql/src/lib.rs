@@ -0,0 +1,707 @@
+//! GraphQL SDL from schemas
+//!
+//! The root and every named type nested in it become an object `type`,
+//! `enum` or `union`, each after the ones it uses. Required fields are
+//! non-null (`!`), list items always are, and docs become SDL descriptions.
+//!
+//! Objects can also be written as `input` types (see [`Flavor`]). Inputs
+//! can't be unions, so variants and `Result`s become `@oneOf` inputs there,
+//! with one nullable field per case.
+//!
+//! GraphQL has no maps, tuples or 64-bit integers: maps are lists of
+//! `key`/`value` entries, tuples are objects with `item0`, `item1`, ...
+//! fields, and integers wider than `Int`'s 32 bits use a `BigInt` scalar.
+//! Anonymous types that need a declaration are named after where they
+//! appear (`MemberScoresEntry`).
+
+use schema::case::Case;
+use schema::{GenerateOptions, IntegerKind, RefPreference, Schema, SchemaType, TypeKind};
+use std::collections::BTreeSet;
+
+/// Which kinds of object type to write
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Flavor {
+    /// `type`s, for query results
+    #[default]
+    Output,
+    /// `input`s, for arguments, suffixed `Input`
+    Input,
+    /// Both, so one Rust type can be returned and accepted
+    Both,
+}
+
+/// Options for the generated SDL
+#[derive(Debug, Clone, Copy, Default)]
+pub struct GraphqlOptions {
+    pub flavor: Flavor,
+    pub generate: GenerateOptions,
+}
+
+impl GraphqlOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn flavor(mut self, flavor: Flavor) -> Self {
+        self.flavor = flavor;
+        self
+    }
+
+    /// Bound the schema before it is written
+    pub fn generate(mut self, generate: GenerateOptions) -> Self {
+        self.generate = generate;
+        self
+    }
+}
+
+/// The SDL for `T` and the named types it contains
+pub fn to_graphql<T: Schema>() -> String {
+    schema_type_to_graphql(&T::schema(), T::type_name())
+}
+
+/// The SDL for `T` and the named types it contains, with `options`
+pub fn to_graphql_with_options<T: Schema>(options: &GraphqlOptions) -> String {
+    schema_type_to_graphql_with_options(&T::schema(), T::type_name(), options)
+}
+
+/// The SDL for a SchemaType, named `type_name` (or the schema's own name, or
+/// `Root`)
+pub fn schema_type_to_graphql(schema: &SchemaType, type_name: Option<&str>) -> String {
+    schema_type_to_graphql_with_options(schema, type_name, &GraphqlOptions::default())
+}
+
+/// The SDL for a SchemaType, with `options`
+pub fn schema_type_to_graphql_with_options(
+    schema: &SchemaType,
+    type_name: Option<&str>,
+    options: &GraphqlOptions,
+) -> String {
+    let schema = options.generate.apply(schema);
+    let mut module = Module::default();
+    let ident = module.unique(&name_token(
+        type_name.or(schema.name.as_deref()).unwrap_or("Root"),
+    ));
+    module.named.push(Named {
+        name: schema.name.clone().unwrap_or_else(|| ident.clone()),
+        shape: shape(&schema),
+        ident: ident.clone(),
+        input: None,
+        first: schema.clone(),
+    });
+
+    let inputs: &[bool] = match options.flavor {
+        Flavor::Output => &[false],
+        Flavor::Input => &[true],
+        Flavor::Both => &[false, true],
+    };
+    for &input in inputs {
+        let declared = module.flavored(0, input);
+        module.declared.insert(declared.clone());
+        match declares(&schema) {
+            true => module.declare(&declared, &ident, &schema, input),
+            // Only object types can be the root of an operation's data
+            false => {
+                let field = module.field(&ident, "value", true, &schema, input);
+                module.object(&declared, schema.docs(), &[field], input, "");
+            }
+        }
+    }
+
+    let mut sections: Vec<String> = module
+        .scalars
+        .iter()
+        .map(|(scalar, docs)| format!("{}scalar {}", description(Some(docs), ""), scalar))
+        .collect();
+    sections.extend(module.output);
+    let mut output = sections.join("\n\n");
+    output.push('\n');
+    output
+}
+
+/// A named type that has a declaration
+struct Named {
+    name: String,
+    /// Fingerprint without use-site annotations
+    shape: String,
+    ident: String,
+    /// The name of its `input` flavor, once one is needed
+    input: Option<String>,
+    /// The occurrence the declaration was written from
+    first: SchemaType,
+}
+
+#[derive(Default)]
+struct Module {
+    named: Vec<Named>,
+    /// Every type name in use
+    taken: BTreeSet<String>,
+    /// Type names already written
+    declared: BTreeSet<String>,
+    /// Declarations, each after the ones it uses
+    output: Vec<String>,
+    /// Custom scalars used, with their descriptions
+    scalars: BTreeSet<(&'static str, &'static str)>,
+}
+
+impl Module {
+    /// `base`, or `base2`, `base3`, ... if something already has the name
+    fn unique(&mut self, base: &str) -> String {
+        let ident = (1..)
+            .map(|n| match n {
+                1 => base.to_string(),
+                n => format!("{base}{n}"),
+            })
+            .find(|ident| !self.taken.contains(ident))
+            .unwrap();
+        self.taken.insert(ident.clone());
+        ident
+    }
+
+    /// The name of a named type in the flavor asked for; enums have only one
+    fn flavored(&mut self, index: usize, input: bool) -> String {
+        if !input || matches!(self.named[index].first.kind, TypeKind::Enum { .. }) {
+            return self.named[index].ident.clone();
+        }
+        if let Some(ident) = &self.named[index].input {
+            return ident.clone();
+        }
+        let ident = self.unique(&format!("{}Input", self.named[index].ident));
+        self.named[index].input = Some(ident.clone());
+        ident
+    }
+
+    /// Write the declaration `ident` of `schema`; `base` is its output
+    /// name, which anonymous types inside it are named after
+    fn declare(&mut self, ident: &str, base: &str, schema: &SchemaType, input: bool) {
+        let docs = schema.docs();
+        match &schema.kind {
+            TypeKind::Object { required, .. } => {
+                let fields: Vec<String> = schema
+                    .ordered_properties()
+                    .into_iter()
+                    .map(|(name, property)| {
+                        let required = required.iter().any(|r| r == name);
+                        self.field(base, name, required, property, input)
+                    })
+                    .collect();
+                self.object(ident, docs, &fields, input, "");
+            }
+            TypeKind::Enum { variants } => {
+                let values: Vec<String> = variants
+                    .iter()
+                    .map(|variant| format!("  {}", enum_value(variant)))
+                    .collect();
+                self.output.push(format!(
+                    "{}enum {} {{\n{}\n}}",
+                    description(docs.as_deref(), ""),
+                    ident,
+                    values.join("\n")
+                ));
+            }
+            TypeKind::TaggedUnion {
+                tag_field,
+                tag_variants,
+                data_fields,
+            } => {
+                let tag = SchemaType {
+                    kind: TypeKind::Enum {
+                        variants: tag_variants.clone(),
+                    },
+                    ..Default::default()
+                };
+                let mut fields = vec![self.field(base, tag_field, true, &tag, input)];
+                let mut names: Vec<&String> = data_fields.keys().collect();
+                names.sort();
+                for name in names {
+                    fields.push(self.field(base, name, false, &data_fields[name], input));
+                }
+                self.object(ident, docs, &fields, input, "");
+            }
+            TypeKind::Variant { cases } if input => {
+                let fields: Vec<String> = cases
+                    .iter()
+                    .map(|case| {
+                        let ty = match &case.data {
+                            Some(data) => {
+                                self.reference(data, &format!("{base}{}", pascal(&case.name)), true)
+                            }
+                            None => "Boolean".to_string(),
+                        };
+                        format!(
+                            "{}  {}: {}",
+                            description(case.description.as_deref(), "  "),
+                            name_token(&case.name),
+                            ty
+                        )
+                    })
+                    .collect();
+                self.object(ident, docs, &fields, true, " @oneOf");
+            }
+            TypeKind::Variant { cases } => {
+                let tag = schema.tagging();
+                let tag = tag
+                    .as_ref()
+                    .and_then(|tagging| tagging.tag())
+                    .unwrap_or("type");
+                let mut members: Vec<String> = vec![];
+                for case in cases {
+                    let context = format!("{base}{}", pascal(&case.name));
+                    let case_docs = case.description.clone();
+                    let member = match &case.data {
+                        // A named object that no other case uses stands for the case
+                        Some(data) if matches!(data.kind, TypeKind::Object { .. }) => {
+                            let member = self.reference(data, &context, false);
+                            match members.contains(&member) {
+                                true => self.wrapper(&context, case_docs, data),
+                                false => member,
+                            }
+                        }
+                        Some(data) => self.wrapper(&context, case_docs, data),
+                        None => {
+                            let ident = self.unique(&context);
+                            let field = format!(
+                                "{}  {}: String!",
+                                description(Some(&format!("Always \"{}\"", case.name)), "  "),
+                                name_token(tag)
+                            );
+                            self.object(&ident, case_docs, &[field], false, "");
+                            ident
+                        }
+                    };
+                    members.push(member);
+                }
+                self.union(ident, docs, &members);
+            }
+            TypeKind::Result { ok, err } if input => {
+                let fields = vec![
+                    format!("  ok: {}", self.reference(ok, &format!("{base}Ok"), true)),
+                    format!(
+                        "  error: {}",
+                        self.reference(err, &format!("{base}Error"), true)
+                    ),
+                ];
+                self.object(ident, docs, &fields, true, " @oneOf");
+            }
+            TypeKind::Result { ok, err } => {
+                let mut members = vec![];
+                for (name, suffix, data) in [("ok", "Ok", ok), ("error", "Error", err)] {
+                    let member = self.unique(&format!("{base}{suffix}"));
+                    let field = self.field(&member, name, true, data, false);
+                    self.object(&member, None, &[field], false, "");
+                    members.push(member);
+                }
+                self.union(ident, docs, &members);
+            }
+            TypeKind::Tuple { fields: items } => {
+                let fields: Vec<String> = items
+                    .iter()
+                    .enumerate()
+                    .map(|(i, item)| self.field(base, &format!("item{i}"), true, item, input))
+                    .collect();
+                self.object(ident, docs, &fields, input, "");
+            }
+            _ => unreachable!("declares() covers the kinds declared here"),
+        }
+    }
+
+    /// An object type for a case whose payload isn't an object of its own:
+    /// the payload sits in its `value` field
+    fn wrapper(&mut self, context: &str, docs: Option<String>, data: &SchemaType) -> String {
+        let ident = self.unique(context);
+        let field = self.field(&ident, "value", true, data, false);
+        self.object(&ident, docs, &[field], false, "");
+        ident
+    }
+
+    /// A nested schema's type: its declaration's name if it is a named
+    /// type, otherwise written in place, with anonymous declarations named
+    /// after `context`
+    fn reference(&mut self, schema: &SchemaType, context: &str, input: bool) -> String {
+        let Some(name) = &schema.name else {
+            return self.expression(schema, context, input);
+        };
+        if matches!(schema.kind, TypeKind::Ref { .. })
+            || schema.ref_preference() == Some(RefPreference::Inline)
+            || !declares(schema)
+        {
+            return self.expression(schema, context, input);
+        }
+
+        let shape = shape(schema);
+        let index = match self
+            .named
+            .iter()
+            .position(|named| named.name == *name && named.shape == shape)
+        {
+            Some(index) => index,
+            // A different type may already have taken the name
+            None => {
+                let ident = self.unique(&name_token(name));
+                self.named.push(Named {
+                    name: name.clone(),
+                    shape,
+                    ident,
+                    input: None,
+                    first: schema.clone(),
+                });
+                self.named.len() - 1
+            }
+        };
+        let ident = self.flavored(index, input);
+        if self.declared.insert(ident.clone()) {
+            let base = self.named[index].ident.clone();
+            self.declare(&ident, &base, schema, input);
+        }
+        ident
+    }
+
+    /// The type of `schema` in place, declaring it under `context` if it
+    /// needs a declaration
+    fn expression(&mut self, schema: &SchemaType, context: &str, input: bool) -> String {
+        if declares(schema) {
+            let ident = match input && !matches!(schema.kind, TypeKind::Enum { .. }) {
+                true => self.unique(&format!("{context}Input")),
+                false => self.unique(context),
+            };
+            self.declared.insert(ident.clone());
+            self.declare(&ident, context, schema, input);
+            return ident;
+        }
+
+        match &schema.kind {
+            TypeKind::String => "String".to_string(),
+            TypeKind::Integer(IntegerKind::I32 | IntegerKind::U8) => "Int".to_string(),
+            TypeKind::Integer(_) => {
+                self.scalars
+                    .insert(("BigInt", "An integer outside the 32-bit range of `Int`"));
+                "BigInt".to_string()
+            }
+            TypeKind::Number(_) => "Float".to_string(),
+            TypeKind::Boolean => "Boolean".to_string(),
+            TypeKind::Null => {
+                self.scalars.insert(("Void", "Always null"));
+                "Void".to_string()
+            }
+            TypeKind::Array { items } | TypeKind::Set { items, .. } => {
+                format!(
+                    "[{}!]",
+                    self.reference(items, &format!("{context}Item"), input)
+                )
+            }
+            TypeKind::Map { key, value, .. } => {
+                let entry = match input {
+                    true => self.unique(&format!("{context}EntryInput")),
+                    false => self.unique(&format!("{context}Entry")),
+                };
+                let base = format!("{context}Entry");
+                let fields = vec![
+                    self.field(&base, "key", true, key, input),
+                    self.field(&base, "value", true, value, input),
+                ];
+                self.object(&entry, None, &fields, input, "");
+                format!("[{}!]", entry)
+            }
+            TypeKind::Ref { name } => match self.named.iter().position(|named| named.name == *name)
+            {
+                Some(index) => self.flavored(index, input),
+                None => name_token(name),
+            },
+            _ => unreachable!("declares() covers the remaining kinds"),
+        }
+    }
+
+    /// `name: Type` inside the object `owner`, non-null if `required`
+    fn field(
+        &mut self,
+        owner: &str,
+        name: &str,
+        required: bool,
+        schema: &SchemaType,
+        input: bool,
+    ) -> String {
+        let ty = self.reference(schema, &format!("{owner}{}", pascal(name)), input);
+        let non_null = if required { "!" } else { "" };
+        // Inputs can only deprecate fields a caller may leave out
+        let deprecated = if schema.deprecated() && !(input && required) {
+            " @deprecated"
+        } else {
+            ""
+        };
+        format!(
+            "{}  {}: {}{}{}",
+            description(self.use_site_docs(schema).as_deref(), "  "),
+            name_token(name),
+            ty,
+            non_null,
+            deprecated
+        )
+    }
+
+    fn object(
+        &mut self,
+        ident: &str,
+        docs: Option<String>,
+        fields: &[String],
+        input: bool,
+        directives: &str,
+    ) {
+        let keyword = if input { "input" } else { "type" };
+        let mut declaration = format!(
+            "{}{} {}{}",
+            description(docs.as_deref(), ""),
+            keyword,
+            ident,
+            directives
+        );
+        if !fields.is_empty() {
+            declaration.push_str(&format!(" {{\n{}\n}}", fields.join("\n")));
+        }
+        self.output.push(declaration);
+    }
+
+    fn union(&mut self, ident: &str, docs: Option<String>, members: &[String]) {
+        let mut declaration = format!("{}union {}", description(docs.as_deref(), ""), ident);
+        if !members.is_empty() {
+            declaration.push_str(&format!(" = {}", members.join(" | ")));
+        }
+        self.output.push(declaration);
+    }
+
+    /// The docs for a field, unless its type's declaration already has them
+    fn use_site_docs(&self, schema: &SchemaType) -> Option<String> {
+        schema.docs().filter(|docs| {
+            let declared = schema.name.as_ref().and_then(|name| {
+                self.named
+                    .iter()
+                    .find(|named| named.name == *name && named.shape == shape(schema))
+            });
+            declared.is_none_or(|named| named.first.docs().as_ref() != Some(docs))
+        })
+    }
+}
+
+/// Whether `schema` is written as a declaration of its own
+fn declares(schema: &SchemaType) -> bool {
+    matches!(
+        schema.kind,
+        TypeKind::Object { .. }
+            | TypeKind::Enum { .. }
+            | TypeKind::TaggedUnion { .. }
+            | TypeKind::Variant { .. }
+            | TypeKind::Result { .. }
+            | TypeKind::Tuple { .. }
+    )
+}
+
+/// A block string description at `indent`, ending in a newline
+fn description(docs: Option<&str>, indent: &str) -> String {
+    let Some(docs) = docs else {
+        return String::new();
+    };
+    let docs = docs.replace("\"\"\"", "\\\"\"\"");
+    // A closing quote would run into the delimiter, so that goes on its own line too
+    if !docs.contains('\n') && !docs.ends_with('"') {
+        return format!("{indent}\"\"\"{docs}\"\"\"\n");
+    }
+    let mut block = format!("{indent}\"\"\"\n");
+    for line in docs.lines() {
+        match line {
+            "" => block.push('\n'),
+            line => block.push_str(&format!("{indent}{line}\n")),
+        }
+    }
+    block.push_str(&format!("{indent}\"\"\"\n"));
+    block
+}
+
+/// A type name for part of another's, `per_role` -> `PerRole`
+fn pascal(name: &str) -> String {
+    name_token(&Case::Pascal.apply(name))
+}
+
+/// A GraphQL name: letters, digits and underscores, not starting with a digit
+fn name_token(name: &str) -> String {
+    let token: String = name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    match token.is_empty() || token.starts_with(|c: char| c.is_ascii_digit()) {
+        true => format!("_{token}"),
+        false => token,
+    }
+}
+
+/// An enum value: a name that isn't `true`, `false` or `null`
+fn enum_value(value: &str) -> String {
+    let token = name_token(value);
+    match token.as_str() {
+        "true" | "false" | "null" => format!("{token}_"),
+        _ => token,
+    }
+}
+
+/// What makes two occurrences of a named type the same declaration: its
+/// fingerprint without the annotations a field adds to it
+fn shape(schema: &SchemaType) -> String {
+    let mut bare = schema.clone();
+    bare.title = None;
+    bare.description = None;
+    bare.default = None;
+    for key in [
+        schema::DEPRECATED,
+        schema::READ_ONLY,
+        schema::WRITE_ONLY,
+        schema::SINCE,
+        schema::REF_PREFERENCE,
+    ] {
+        bare.extensions.remove(key);
+    }
+    bare.fingerprint()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_names_and_descriptions() {
+        #[derive(Schema)]
+        #[allow(dead_code)]
+        enum Answer {
+            #[schema(rename = "true")]
+            Yes,
+            #[schema(rename = "null")]
+            Unknown,
+            #[schema(rename = "2fa")]
+            SecondFactor,
+        }
+
+        /// Quotes `"""` survive, and so does a closing "quote"
+        #[derive(Schema)]
+        #[allow(dead_code)]
+        struct Survey {
+            #[schema(rename = "content-type")]
+            content_type: String,
+            answer: Answer,
+            responses: u64,
+            weights: HashMap<String, f32>,
+        }
+
+        assert_eq!(
+            to_graphql::<Survey>(),
+            r#""""An integer outside the 32-bit range of `Int`"""
+scalar BigInt
+
+enum Answer {
+  true_
+  null_
+  _2fa
+}
+
+type SurveyWeightsEntry {
+  key: String!
+  value: Float!
+}
+
+"""
+Quotes `\"""` survive, and so does a closing "quote"
+"""
+type Survey {
+  content_type: String!
+  answer: Answer!
+  responses: BigInt!
+  """Unordered map/dictionary of key-value pairs"""
+  weights: [SurveyWeightsEntry!]!
+}
+"#
+        );
+    }
+
+    #[test]
+    fn test_input_flavor() {
+        #[derive(Schema)]
+        #[allow(dead_code)]
+        enum Plan {
+            Free,
+            Paid,
+        }
+
+        #[derive(Schema)]
+        #[allow(dead_code, deprecated)]
+        struct Signup {
+            #[deprecated]
+            handle: String,
+            #[deprecated]
+            referrer: Option<String>,
+            plan: Plan,
+            limits: HashMap<String, i32>,
+        }
+
+        let sdl = to_graphql_with_options::<Signup>(&GraphqlOptions::new().flavor(Flavor::Both));
+        assert!(sdl.contains("type Signup {\n  handle: String! @deprecated\n"));
+        // A required input can't be deprecated, since callers must send it
+        assert!(sdl.contains("input SignupInput {\n  handle: String!\n"));
+        assert!(sdl.contains("  referrer: String @deprecated\n  plan: Plan!\n"));
+        assert!(sdl.contains("input SignupLimitsEntryInput {\n  key: String!\n"));
+        assert!(sdl.contains("  limits: [SignupLimitsEntryInput!]!\n"));
+        // Enums have no input flavor
+        assert_eq!(sdl.matches("enum Plan").count(), 1);
+        assert!(!sdl.contains("PlanInput"));
+    }
+
+    #[test]
+    fn test_variants() {
+        #[derive(Schema)]
+        #[allow(dead_code)]
+        struct Point {
+            x: f64,
+        }
+
+        #[derive(Schema)]
+        #[allow(dead_code)]
+        #[schema(tag = "kind")]
+        enum Event {
+            Start,
+            Move { x: i32 },
+            Jump(Point),
+            // Point already stands for Jump, so Land needs a wrapper
+            Land(Point),
+            Rename(String),
+        }
+
+        assert_eq!(
+            to_graphql::<Event>(),
+            r#"type EventStart {
+  """
+  Always "Start"
+  """
+  kind: String!
+}
+
+type EventMove {
+  x: Int!
+}
+
+type Point {
+  x: Float!
+}
+
+type EventLand {
+  value: Point!
+}
+
+type EventRename {
+  value: String!
+}
+
+union Event = EventStart | EventMove | Point | EventLand | EventRename
+"#
+        );
+
+        let inputs = to_graphql_with_options::<Event>(&GraphqlOptions::new().flavor(Flavor::Input));
+        assert!(inputs.ends_with(
+            "input EventInput @oneOf {\n  Start: Boolean\n  Move: EventMoveInput\n  Jump: PointInput\n  Land: PointInput\n  Rename: String\n}\n"
+        ));
+    }
+}
@@ -0,0 +1,47 @@
+//! Delta Lake schema strings
+//!
+//! A Delta table's `metaData.schemaString` is a Spark `StructType` in its JSON
+//! form, so this is schema-spark's output serialized, with the same nullable
+//! flags, `comment` metadata and type mapping.
+
+use crate::IcebergError;
+use schema::{Schema, SchemaType};
+use schema_spark::SparkError;
+
+/// The Delta Lake `schemaString` for `T`
+pub fn to_delta_schema<T: Schema>() -> Result<String, IcebergError> {
+    schema_type_to_delta(&T::schema())
+}
+
+/// The Delta Lake `schemaString` for an object SchemaType
+pub fn schema_type_to_delta(schema: &SchemaType) -> Result<String, IcebergError> {
+    match schema_spark::schema_type_to_spark(schema) {
+        Ok(struct_type) => Ok(struct_type.to_string()),
+        Err(SparkError::NotAnObject) => Err(IcebergError::NotAnObject),
+        Err(SparkError::InvalidDecimal { column }) => Err(IcebergError::InvalidDecimal { column }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_schema_string() {
+        #[derive(Schema)]
+        #[allow(dead_code)]
+        struct Reading {
+            sensor: String,
+            value: Option<f64>,
+        }
+
+        assert_eq!(
+            to_delta_schema::<Reading>().unwrap(),
+            r#"{"fields":[{"metadata":{},"name":"sensor","nullable":false,"type":"string"},{"metadata":{},"name":"value","nullable":true,"type":"double"}],"type":"struct"}"#
+        );
+        assert_eq!(
+            to_delta_schema::<Vec<String>>(),
+            Err(IcebergError::NotAnObject)
+        );
+    }
+}
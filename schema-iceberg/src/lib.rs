@@ -0,0 +1,355 @@
+//! Apache Iceberg table schemas from schemas
+//!
+//! An object schema becomes an Iceberg schema in the JSON form table metadata
+//! stores and the REST catalog's `CreateTableRequest` takes. Every field,
+//! list element and map key and value gets an ID, numbered the way Iceberg's
+//! own `AssignFreshIds` does: a struct's fields first, then whatever is
+//! nested inside each. Properties that can be absent are optional; defaulted
+//! ones are always written, so they're `required`. Doc comments become
+//! field `doc`s.
+//!
+//! Type mapping:
+//! - nested objects are `struct`s, arrays and sets `list`s, and maps `map`s
+//! - unsigned integers get the next wider type, so `u64` is `decimal(20,0)`
+//! - string formats `date-time`, `date`, `time` and `uuid` become
+//!   `timestamptz`, `date`, `time` and `uuid`
+//! - fields marked with the [`DECIMAL`] extension become `decimal(p,s)`
+//! - enums are `string`s, and results `struct`s with optional `ok` and
+//!   `error` fields
+//!
+//! Iceberg has no unions, tuples or recursion, so variants, tuples and
+//! recursive references are `string` fields holding their JSON encoding.
+//!
+//! With the `delta` feature, [`to_delta_schema`] gives the `schemaString` of
+//! a Delta Lake table instead.
+
+#[cfg(feature = "delta")]
+mod delta;
+
+#[cfg(feature = "delta")]
+pub use delta::{schema_type_to_delta, to_delta_schema};
+
+use schema::{GenerateOptions, IntegerKind, NumberKind, Schema, SchemaType, TypeKind};
+use serde_json::{Value, json};
+use std::fmt;
+
+/// Extension marking a number or string field as a fixed-point decimal:
+/// `#[schema(extension("x-decimal" = { "precision": 18, "scale": 2 }))]`
+pub const DECIMAL: &str = "x-decimal";
+
+/// The widest decimal Iceberg holds
+const MAX_PRECISION: u64 = 38;
+
+/// Why a schema has no table schema
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IcebergError {
+    /// The schema is not an object, so it has no fields
+    NotAnObject,
+    /// A [`DECIMAL`] extension without a precision and scale the table accepts
+    InvalidDecimal { column: String },
+}
+
+impl fmt::Display for IcebergError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IcebergError::NotAnObject => {
+                write!(f, "only object schemas map to table schemas")
+            }
+            IcebergError::InvalidDecimal { column } => write!(
+                f,
+                "column {:?} needs a decimal precision between 1 and {} and a scale between 0 and the precision",
+                column, MAX_PRECISION
+            ),
+        }
+    }
+}
+
+impl std::error::Error for IcebergError {}
+
+/// The Iceberg schema JSON for `T`
+pub fn to_iceberg_schema<T: Schema>() -> Result<Value, IcebergError> {
+    schema_type_to_iceberg(&T::schema())
+}
+
+/// The Iceberg schema JSON for `T`, bounded by `options`
+pub fn to_iceberg_schema_with_options<T: Schema>(
+    options: &GenerateOptions,
+) -> Result<Value, IcebergError> {
+    schema_type_to_iceberg_with_options(&T::schema(), options)
+}
+
+/// The Iceberg schema JSON for a SchemaType, bounded by `options`
+pub fn schema_type_to_iceberg_with_options(
+    schema: &SchemaType,
+    options: &GenerateOptions,
+) -> Result<Value, IcebergError> {
+    schema_type_to_iceberg(&options.apply(schema))
+}
+
+/// The Iceberg schema JSON for an object SchemaType, as schema 0 of a new
+/// table
+pub fn schema_type_to_iceberg(schema: &SchemaType) -> Result<Value, IcebergError> {
+    let TypeKind::Object { required, .. } = &schema.kind else {
+        return Err(IcebergError::NotAnObject);
+    };
+    let mut next_id = 0;
+    let fields = properties(schema, required);
+    let Value::Object(mut root) = struct_type(&fields, &mut next_id)? else {
+        unreachable!("struct types are objects");
+    };
+    root.insert("schema-id".to_string(), json!(0));
+    Ok(Value::Object(root))
+}
+
+/// A struct field to be: its name, schema and whether it is required
+type Field<'a> = (&'a str, &'a SchemaType, bool);
+
+/// An object's properties as struct fields
+fn properties<'a>(object: &'a SchemaType, required: &[String]) -> Vec<Field<'a>> {
+    object
+        .ordered_properties()
+        .into_iter()
+        .map(|(name, property)| {
+            // Defaulted properties can be left out of input, but the writer
+            // always fills them in
+            let required = required.iter().any(|r| r == name) || property.default.is_some();
+            (name, property, required)
+        })
+        .collect()
+}
+
+/// A `struct`, numbering its fields before anything nested in them
+fn struct_type(fields: &[Field], next_id: &mut u32) -> Result<Value, IcebergError> {
+    let ids: Vec<u32> = fields.iter().map(|_| fresh(next_id)).collect();
+    let mut out = vec![];
+    for ((name, schema, required), id) in fields.iter().zip(ids) {
+        let mut field = json!({
+            "id": id,
+            "name": name,
+            "required": *required && !is_null(schema),
+            "type": data_type(name, schema, next_id)?,
+        });
+        if let Some(docs) = schema.docs() {
+            field["doc"] = Value::String(docs);
+        }
+        out.push(field);
+    }
+    Ok(json!({ "type": "struct", "fields": out }))
+}
+
+/// The type of a value in `column`
+fn data_type(column: &str, schema: &SchemaType, next_id: &mut u32) -> Result<Value, IcebergError> {
+    if let Some(decimal) = schema.extensions.get(DECIMAL) {
+        let precision = decimal.get("precision").and_then(|p| p.as_u64());
+        let scale = decimal.get("scale").and_then(|s| s.as_u64());
+        return match (precision, scale) {
+            (Some(precision @ 1..=MAX_PRECISION), Some(scale)) if scale <= precision => {
+                Ok(json!(format!("decimal({precision},{scale})")))
+            }
+            _ => Err(IcebergError::InvalidDecimal {
+                column: column.to_string(),
+            }),
+        };
+    }
+
+    let ty = match &schema.kind {
+        TypeKind::String => match schema.constraints.format.as_deref() {
+            Some("date-time") => "timestamptz",
+            Some("date") => "date",
+            Some("time") => "time",
+            Some("uuid") => "uuid",
+            _ => "string",
+        },
+        TypeKind::Integer(kind) => match kind {
+            IntegerKind::I32 | IntegerKind::U8 => "int",
+            IntegerKind::I64 | IntegerKind::U32 => "long",
+            IntegerKind::U64 | IntegerKind::Usize => "decimal(20,0)",
+        },
+        TypeKind::Number(NumberKind::F32) => "float",
+        TypeKind::Number(NumberKind::F64) => "double",
+        TypeKind::Boolean => "boolean",
+        TypeKind::Enum { .. } => "string",
+        TypeKind::Object { required, .. } => {
+            return struct_type(&properties(schema, required), next_id);
+        }
+        TypeKind::Array { items } | TypeKind::Set { items, .. } => {
+            let element_id = fresh(next_id);
+            return Ok(json!({
+                "type": "list",
+                "element-id": element_id,
+                "element": data_type(column, items, next_id)?,
+                "element-required": !is_null(items),
+            }));
+        }
+        TypeKind::Map { key, value, .. } => {
+            let key_id = fresh(next_id);
+            let value_id = fresh(next_id);
+            return Ok(json!({
+                "type": "map",
+                "key-id": key_id,
+                "key": data_type(column, key, next_id)?,
+                "value-id": value_id,
+                "value": data_type(column, value, next_id)?,
+                "value-required": !is_null(value),
+            }));
+        }
+        TypeKind::Result { ok, err } => {
+            return struct_type(&[("ok", ok, false), ("error", err, false)], next_id);
+        }
+        TypeKind::TaggedUnion {
+            tag_field,
+            data_fields,
+            ..
+        } => {
+            let tag = SchemaType::new(TypeKind::String);
+            let mut fields: Vec<Field> = vec![(tag_field, &tag, true)];
+            let mut names: Vec<&String> = data_fields.keys().collect();
+            names.sort();
+            for name in names {
+                fields.push((name, &data_fields[name], false));
+            }
+            return struct_type(&fields, next_id);
+        }
+        // Always null, so always optional
+        TypeKind::Null => "string",
+        // Kept as JSON text
+        TypeKind::Tuple { .. } | TypeKind::Variant { .. } | TypeKind::Ref { .. } => "string",
+    };
+    Ok(json!(ty))
+}
+
+/// The next unused field ID
+fn fresh(next_id: &mut u32) -> u32 {
+    *next_id += 1;
+    *next_id
+}
+
+/// Whether a value is always null
+fn is_null(schema: &SchemaType) -> bool {
+    matches!(schema.kind, TypeKind::Null)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[derive(Schema)]
+    #[allow(dead_code)]
+    struct Address {
+        city: String,
+        zip: Option<String>,
+    }
+
+    /// A placed order
+    #[derive(Schema)]
+    #[allow(dead_code)]
+    struct Purchase {
+        id: u64,
+        /// When it was placed
+        at: String,
+        #[schema(default)]
+        priority: u8,
+        #[schema(extension("x-decimal" = { "precision": 12, "scale": 2 }))]
+        total: f64,
+        ship_to: Option<Address>,
+        lines: Vec<Address>,
+        counts: HashMap<String, i32>,
+        result: Result<bool, String>,
+    }
+
+    #[test]
+    fn test_schema() {
+        let mut schema = Purchase::schema();
+        if let TypeKind::Object { properties, .. } = &mut schema.kind {
+            properties.get_mut("at").unwrap().constraints.format = Some("date-time".to_string());
+        }
+        let address = |city: u32, zip: u32| {
+            json!({
+                "type": "struct",
+                "fields": [
+                    { "id": city, "name": "city", "required": true, "type": "string" },
+                    { "id": zip, "name": "zip", "required": false, "type": "string" },
+                ],
+            })
+        };
+        assert_eq!(
+            schema_type_to_iceberg(&schema).unwrap(),
+            json!({
+                "type": "struct",
+                "schema-id": 0,
+                "fields": [
+                    { "id": 1, "name": "id", "required": true, "type": "decimal(20,0)" },
+                    {
+                        "id": 2,
+                        "name": "at",
+                        "required": true,
+                        "type": "timestamptz",
+                        "doc": "When it was placed",
+                    },
+                    { "id": 3, "name": "priority", "required": true, "type": "int" },
+                    { "id": 4, "name": "total", "required": true, "type": "decimal(12,2)" },
+                    { "id": 5, "name": "ship_to", "required": false, "type": address(9, 10) },
+                    {
+                        "id": 6,
+                        "name": "lines",
+                        "required": true,
+                        "type": {
+                            "type": "list",
+                            "element-id": 11,
+                            "element": address(12, 13),
+                            "element-required": true,
+                        },
+                    },
+                    {
+                        "id": 7,
+                        "name": "counts",
+                        "required": true,
+                        "type": {
+                            "type": "map",
+                            "key-id": 14,
+                            "key": "string",
+                            "value-id": 15,
+                            "value": "int",
+                            "value-required": true,
+                        },
+                        "doc": "Unordered map/dictionary of key-value pairs",
+                    },
+                    {
+                        "id": 8,
+                        "name": "result",
+                        "required": true,
+                        "type": {
+                            "type": "struct",
+                            "fields": [
+                                { "id": 16, "name": "ok", "required": false, "type": "boolean" },
+                                { "id": 17, "name": "error", "required": false, "type": "string" },
+                            ],
+                        },
+                    },
+                ],
+            })
+        );
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(
+            to_iceberg_schema::<Vec<String>>(),
+            Err(IcebergError::NotAnObject)
+        );
+
+        #[derive(Schema)]
+        #[allow(dead_code)]
+        struct Price {
+            #[schema(extension("x-decimal" = { "precision": 2, "scale": 4 }))]
+            amount: f64,
+        }
+        assert_eq!(
+            to_iceberg_schema::<Price>(),
+            Err(IcebergError::InvalidDecimal {
+                column: "amount".to_string()
+            })
+        );
+    }
+}
@@ -0,0 +1,788 @@
+//! Standard JSON Schema (draft 2020-12) documents
+//!
+//! Every named type nested inside the root (derived structs and enums,
+//! `schema_alias!` types, ...) becomes an entry in `$defs`, referenced with
+//! `$ref` wherever it's used. `Ref`s left by recursive types resolve to the
+//! same definitions, or to `#` when they point back at the root.
+//!
+//! [`parse`] reads documents the other way, from draft-07 or 2020-12 JSON
+//! Schema into a SchemaType.
+
+use schema::{
+    GenerateOptions, IntegerKind, RefPreference, Schema, SchemaType, Tagging, TypeKind, VariantCase,
+};
+use serde_json::{Map, Value, json};
+use std::collections::BTreeMap;
+
+mod parse;
+
+pub use parse::{ParseError, parse};
+
+/// The dialect every generated document declares in `$schema`
+pub const DIALECT: &str = "https://json-schema.org/draft/2020-12/schema";
+
+/// Convert a Schema to a JSON Schema document
+pub fn to_json_schema<T: Schema>() -> Value {
+    schema_type_to_json_schema(&T::schema())
+}
+
+/// Convert a Schema to a JSON Schema document, bounded by `options`
+pub fn to_json_schema_with_options<T: Schema>(options: &GenerateOptions) -> Value {
+    schema_type_to_json_schema_with_options(&T::schema(), options)
+}
+
+/// Convert a SchemaType to a JSON Schema document, bounded by `options`
+pub fn schema_type_to_json_schema_with_options(
+    schema: &SchemaType,
+    options: &GenerateOptions,
+) -> Value {
+    schema_type_to_json_schema(&options.apply(schema))
+}
+
+/// Convert a SchemaType to a JSON Schema document
+pub fn schema_type_to_json_schema(schema: &SchemaType) -> Value {
+    let mut registry = Registry::new("#/$defs/");
+    if let Some(name) = &schema.name {
+        registry.entries.push(Entry {
+            name: name.clone(),
+            pointer: "#".to_string(),
+            shape: shape(schema),
+            first: schema.clone(),
+        });
+    }
+
+    let root = registry.convert_inline(schema);
+    let mut document = Map::new();
+    document.insert("$schema".to_string(), json!(DIALECT));
+    if let Value::Object(root) = root {
+        document.extend(root);
+    }
+    if !registry.definitions.is_empty() {
+        document.insert("$defs".to_string(), json!(registry.definitions));
+    }
+    Value::Object(document)
+}
+
+/// Named types shared by several schemas, each defined once at a common
+/// location such as `#/components/schemas/`
+///
+/// Documents that carry many schemas (API descriptions with a components
+/// section) add each one here, and write [`Definitions::definitions`] out
+/// where `base` points.
+pub struct Definitions {
+    registry: Registry,
+}
+
+impl Definitions {
+    /// Definitions referenced as `<base><name>`
+    pub fn new(base: &str) -> Self {
+        Definitions {
+            registry: Registry::new(base),
+        }
+    }
+
+    /// `schema` with every named type in it, itself included, as a `$ref`
+    /// to its definition
+    pub fn add(&mut self, schema: &SchemaType) -> Value {
+        self.registry.convert(schema)
+    }
+
+    /// The definitions so far, by name; a name another type already took
+    /// is numbered (`Item2`)
+    pub fn definitions(&self) -> &BTreeMap<String, Value> {
+        &self.registry.definitions
+    }
+
+    pub fn into_definitions(self) -> BTreeMap<String, Value> {
+        self.registry.definitions
+    }
+}
+
+/// A named type given a definition (or the root itself)
+struct Entry {
+    name: String,
+    /// Where `$ref`s to it point
+    pointer: String,
+    /// Fingerprint without use-site annotations
+    shape: String,
+    /// The occurrence the definition was written from
+    first: SchemaType,
+}
+
+struct Registry {
+    /// What definition names are appended to, to point at them
+    base: String,
+    entries: Vec<Entry>,
+    definitions: BTreeMap<String, Value>,
+}
+
+impl Registry {
+    fn new(base: &str) -> Self {
+        Registry {
+            base: base.to_string(),
+            entries: vec![],
+            definitions: BTreeMap::new(),
+        }
+    }
+
+    /// A nested schema: a `$ref` if it is a named type, otherwise in place
+    fn convert(&mut self, schema: &SchemaType) -> Value {
+        let Some(name) = &schema.name else {
+            return self.convert_inline(schema);
+        };
+        if matches!(schema.kind, TypeKind::Ref { .. })
+            || schema.ref_preference() == Some(RefPreference::Inline)
+        {
+            return self.convert_inline(schema);
+        }
+
+        let shape = shape(schema);
+        let existing = self
+            .entries
+            .iter()
+            .find(|entry| entry.name == *name && entry.shape == shape);
+        let (pointer, first) = match existing {
+            Some(entry) => (entry.pointer.clone(), entry.first.clone()),
+            None => (self.define(name, shape, schema), schema.clone()),
+        };
+
+        let mut result = json!({ "$ref": pointer });
+        // The definition carries the first occurrence's annotations; later
+        // uses only add where theirs differ
+        annotate(&mut result, schema, Some(&first));
+        result
+    }
+
+    /// Register `schema` under `name` (or a numbered variant of it, if a
+    /// different type already took the name) and write its definition
+    fn define(&mut self, name: &str, shape: String, schema: &SchemaType) -> String {
+        let key = (1..)
+            .map(|n| match n {
+                1 => name.to_string(),
+                n => format!("{name}{n}"),
+            })
+            .find(|key| !self.definitions.contains_key(key))
+            .unwrap();
+        let target = self.pointer(&key);
+        self.entries.push(Entry {
+            name: name.to_string(),
+            pointer: target.clone(),
+            shape,
+            first: schema.clone(),
+        });
+        // Reserve the key before recursing, so self-references see it
+        self.definitions.insert(key.clone(), Value::Null);
+        let definition = self.convert_inline(schema);
+        self.definitions.insert(key, definition);
+        target
+    }
+
+    /// The `$ref` target for a `Ref` by name
+    fn resolve(&self, name: &str) -> String {
+        self.entries
+            .iter()
+            .find(|entry| entry.name == name)
+            .map(|entry| entry.pointer.clone())
+            .unwrap_or_else(|| self.pointer(name))
+    }
+
+    /// `<base><name>`, with the name escaped as a JSON Pointer segment
+    fn pointer(&self, name: &str) -> String {
+        format!(
+            "{}{}",
+            self.base,
+            name.replace('~', "~0").replace('/', "~1")
+        )
+    }
+
+    /// `schema` written out in place, with nested named types as `$ref`s
+    fn convert_inline(&mut self, schema: &SchemaType) -> Value {
+        let mut result = match &schema.kind {
+            TypeKind::String => json!({ "type": "string" }),
+            TypeKind::Number(_) => json!({ "type": "number" }),
+            TypeKind::Integer(kind) => integer(kind),
+            TypeKind::Boolean => json!({ "type": "boolean" }),
+            TypeKind::Null => json!({ "type": "null" }),
+            TypeKind::Array { items } => json!({
+                "type": "array",
+                "items": self.convert(items)
+            }),
+            TypeKind::Set { items, .. } => json!({
+                "type": "array",
+                "items": self.convert(items),
+                "uniqueItems": true
+            }),
+            TypeKind::Map { key, value, .. } => {
+                if matches!(key.kind, TypeKind::String | TypeKind::Enum { .. }) {
+                    let mut obj = json!({
+                        "type": "object",
+                        "additionalProperties": self.convert(value)
+                    });
+                    let names = self.convert(key);
+                    if names != json!({ "type": "string" }) {
+                        obj["propertyNames"] = names;
+                    }
+                    obj
+                } else {
+                    // Non-string keys are encoded as an array of [key, value] pairs
+                    json!({
+                        "type": "array",
+                        "items": {
+                            "type": "array",
+                            "prefixItems": [self.convert(key), self.convert(value)],
+                            "items": false,
+                            "minItems": 2
+                        }
+                    })
+                }
+            }
+            TypeKind::Object {
+                properties,
+                required,
+            } => {
+                let props: Map<String, Value> = ordered_properties(schema, properties)
+                    .into_iter()
+                    .map(|(name, property)| (name.clone(), self.convert(property)))
+                    .collect();
+
+                let mut obj = json!({
+                    "type": "object",
+                    "properties": props
+                });
+                if !required.is_empty() {
+                    obj["required"] = json!(required);
+                }
+                if schema.denies_unknown_fields() {
+                    obj["additionalProperties"] = json!(false);
+                }
+                obj
+            }
+            TypeKind::Enum { variants } => json!({
+                "type": "string",
+                "enum": variants
+            }),
+            TypeKind::TaggedUnion {
+                tag_field,
+                tag_variants,
+                data_fields,
+            } => {
+                let schemas: Vec<Value> = tag_variants
+                    .iter()
+                    .map(|variant| {
+                        let mut props: Map<String, Value> = data_fields
+                            .iter()
+                            .map(|(name, field)| (name.clone(), self.convert(field)))
+                            .collect();
+                        props.insert(tag_field.clone(), json!({ "const": variant }));
+                        json!({
+                            "type": "object",
+                            "properties": props,
+                            "required": [tag_field]
+                        })
+                    })
+                    .collect();
+                json!({ "oneOf": schemas })
+            }
+            TypeKind::Variant { cases } => match schema.tagging() {
+                // Cases are told apart by shape alone, so several may match
+                Some(Tagging::Untagged) => json!({
+                    "anyOf": cases
+                        .iter()
+                        .map(|case| self.tagged_case(case, &Tagging::Untagged))
+                        .collect::<Vec<_>>()
+                }),
+                Some(tagging) => json!({
+                    "oneOf": cases
+                        .iter()
+                        .map(|case| self.tagged_case(case, &tagging))
+                        .collect::<Vec<_>>()
+                }),
+                None => json!({
+                    "oneOf": cases
+                        .iter()
+                        .map(|case| self.default_case(case))
+                        .collect::<Vec<_>>()
+                }),
+            },
+            TypeKind::Result { ok, err } => json!({
+                "oneOf": [
+                    {
+                        "type": "object",
+                        "properties": { "ok": self.convert(ok) },
+                        "required": ["ok"],
+                        "additionalProperties": false
+                    },
+                    {
+                        "type": "object",
+                        "properties": { "error": self.convert(err) },
+                        "required": ["error"],
+                        "additionalProperties": false
+                    }
+                ]
+            }),
+            TypeKind::Tuple { fields } => {
+                if fields.is_empty() {
+                    json!({ "type": "array", "maxItems": 0 })
+                } else {
+                    let items: Vec<Value> = fields.iter().map(|f| self.convert(f)).collect();
+                    json!({
+                        "type": "array",
+                        "prefixItems": items,
+                        "items": false,
+                        "minItems": fields.len()
+                    })
+                }
+            }
+            TypeKind::Ref { name } => json!({ "$ref": self.resolve(name) }),
+        };
+
+        annotate(&mut result, schema, None);
+        if let Value::Object(obj) = &mut result {
+            obj.extend(schema.constraints.json_keywords());
+            for key in [
+                schema::SINCE,
+                schema::SCHEMA_VERSION,
+                schema::ENUM_SINCE,
+                schema::ENUM_DEPRECATED,
+                schema::ENUM_DISCRIMINANTS,
+            ] {
+                if let Some(value) = schema.extensions.get(key) {
+                    obj.insert(key.to_string(), value.clone());
+                }
+            }
+            for (key, value) in schema.vendor_extensions() {
+                obj.insert(key.to_string(), value.clone());
+            }
+        }
+        result
+    }
+
+    /// A case of an untagged-by-default Variant: the name as a string for
+    /// unit cases, `{"type": name, "data": payload}` otherwise
+    fn default_case(&mut self, case: &VariantCase) -> Value {
+        let obj = match &case.data {
+            None => json!({ "const": case.name }),
+            Some(data) => json!({
+                "type": "object",
+                "properties": {
+                    "type": { "const": case.name },
+                    "data": self.convert(data)
+                },
+                "required": ["type", "data"]
+            }),
+        };
+        case_annotations(obj, case)
+    }
+
+    /// A case of a Variant with an explicit [`Tagging`]
+    fn tagged_case(&mut self, case: &VariantCase, tagging: &Tagging) -> Value {
+        let obj = match (tagging, &case.data) {
+            (Tagging::Untagged, None) => json!({ "type": "null" }),
+            (Tagging::Untagged, Some(data)) => self.convert(data),
+            (Tagging::Internal { tag } | Tagging::Adjacent { tag, .. }, None) => {
+                tag_object(tag, case)
+            }
+            (Tagging::Adjacent { tag, content }, Some(data)) => {
+                let mut obj = tag_object(tag, case);
+                obj["properties"][content] = self.convert(data);
+                obj["required"] = json!([tag, content]);
+                obj
+            }
+            (Tagging::Internal { tag }, Some(data)) => {
+                let mut obj = tag_object(tag, case);
+                let payload = self.convert(data);
+                match payload["properties"].as_object() {
+                    // Record payloads share the object with the tag
+                    Some(properties) => {
+                        for (name, property) in properties {
+                            obj["properties"][name] = property.clone();
+                        }
+                        let required = payload["required"].as_array().into_iter().flatten();
+                        obj["required"] = std::iter::once(json!(tag))
+                            .chain(required.cloned())
+                            .collect();
+                        if let Some(closed) = payload.get("additionalProperties") {
+                            obj["additionalProperties"] = closed.clone();
+                        }
+                        obj
+                    }
+                    None => json!({ "allOf": [payload, obj] }),
+                }
+            }
+        };
+        case_annotations(obj, case)
+    }
+}
+
+/// Properties in declaration order, followed by any it doesn't list
+fn ordered_properties<'a>(
+    schema: &SchemaType,
+    properties: &'a std::collections::HashMap<String, SchemaType>,
+) -> Vec<(&'a String, &'a SchemaType)> {
+    let mut ordered: Vec<_> = properties.iter().collect();
+    ordered.sort_by_key(|(name, _)| {
+        let position = schema.property_order.iter().position(|n| n == *name);
+        (position.unwrap_or(usize::MAX), (*name).clone())
+    });
+    ordered
+}
+
+/// An object whose `tag` property holds the case name
+fn tag_object(tag: &str, case: &VariantCase) -> Value {
+    json!({
+        "type": "object",
+        "properties": {
+            tag: { "const": case.name }
+        },
+        "required": [tag]
+    })
+}
+
+fn case_annotations(mut obj: Value, case: &VariantCase) -> Value {
+    if let Some(desc) = &case.description {
+        obj["description"] = json!(desc);
+    }
+    if let Some(since) = case.since() {
+        obj[schema::SINCE] = json!(since);
+    }
+    if case.deprecated() {
+        obj["deprecated"] = json!(true);
+    }
+    for (key, value) in case.vendor_extensions() {
+        obj[key] = value.clone();
+    }
+    obj
+}
+
+/// Title, description, default and the deprecated/read-only/write-only
+/// flags, skipping any that `defined` already carries
+fn annotate(result: &mut Value, schema: &SchemaType, defined: Option<&SchemaType>) {
+    let differs = |get: fn(&SchemaType) -> Value| defined.is_none_or(|d| get(d) != get(schema));
+
+    if let Some(title) = &schema.title
+        && differs(|s| json!(s.title))
+    {
+        result["title"] = json!(title);
+    }
+    if let Some(desc) = &schema.description
+        && differs(|s| json!(s.description))
+    {
+        result["description"] = json!(desc);
+    }
+    if let Some(default) = &schema.default
+        && differs(|s| json!(s.default))
+    {
+        result["default"] = default.clone();
+    }
+    if schema.deprecated() && differs(|s| json!(s.deprecated())) {
+        result["deprecated"] = json!(true);
+    }
+    if schema.read_only() && differs(|s| json!(s.read_only())) {
+        result["readOnly"] = json!(true);
+    }
+    if schema.write_only() && differs(|s| json!(s.write_only())) {
+        result["writeOnly"] = json!(true);
+    }
+}
+
+/// An integer schema bounded to the range of `kind`, since JSON Schema has
+/// no formats for fixed-width integers
+fn integer(kind: &IntegerKind) -> Value {
+    json!({
+        "type": "integer",
+        "minimum": kind.min() as i64,
+        "maximum": kind.max() as u64
+    })
+}
+
+/// What makes two occurrences of a named type the same definition: its
+/// fingerprint without the annotations a field adds to it
+fn shape(schema: &SchemaType) -> String {
+    let mut bare = schema.clone();
+    bare.title = None;
+    bare.description = None;
+    bare.default = None;
+    for key in [
+        schema::DEPRECATED,
+        schema::READ_ONLY,
+        schema::WRITE_ONLY,
+        schema::SINCE,
+        schema::REF_PREFERENCE,
+    ] {
+        bare.extensions.remove(key);
+    }
+    bare.fingerprint()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_document_header() {
+        #[derive(Schema)]
+        #[allow(dead_code)]
+        struct Person {
+            name: String,
+            age: Option<u32>,
+        }
+
+        let schema = to_json_schema::<Person>();
+        assert_eq!(schema["$schema"], DIALECT);
+        assert_eq!(schema["type"], "object");
+        assert_eq!(schema["properties"]["age"]["type"], "integer");
+        assert_eq!(schema["required"], json!(["name"]));
+        assert!(schema.get("$defs").is_none());
+    }
+
+    #[test]
+    fn test_named_types_become_defs() {
+        /// A postal address
+        #[derive(Schema)]
+        #[allow(dead_code)]
+        struct Address {
+            street: String,
+        }
+
+        #[derive(Schema)]
+        #[allow(dead_code)]
+        struct Person {
+            home: Address,
+            /// Where they work
+            work: Option<Address>,
+        }
+
+        let schema = to_json_schema::<Person>();
+        assert_eq!(
+            schema["properties"]["home"],
+            json!({ "$ref": "#/$defs/Address" })
+        );
+        assert_eq!(
+            schema["properties"]["work"],
+            json!({ "$ref": "#/$defs/Address", "description": "Where they work" })
+        );
+        assert_eq!(schema["$defs"]["Address"]["type"], "object");
+        assert_eq!(
+            schema["$defs"]["Address"]["description"],
+            "A postal address"
+        );
+        assert_eq!(schema["$defs"].as_object().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_recursive_refs() {
+        #[derive(Schema)]
+        #[allow(dead_code)]
+        struct Comment {
+            text: String,
+            replies: Vec<Comment>,
+        }
+
+        #[derive(Schema)]
+        #[allow(dead_code)]
+        struct Thread {
+            first: Comment,
+        }
+
+        let schema = to_json_schema::<Comment>();
+        assert_eq!(schema["properties"]["replies"]["items"]["$ref"], "#");
+
+        let schema = to_json_schema::<Thread>();
+        assert_eq!(schema["properties"]["first"]["$ref"], "#/$defs/Comment");
+        assert_eq!(
+            schema["$defs"]["Comment"]["properties"]["replies"]["items"]["$ref"],
+            "#/$defs/Comment"
+        );
+    }
+
+    #[test]
+    fn test_shared_definitions() {
+        #[derive(Schema)]
+        #[allow(dead_code)]
+        struct User {
+            name: String,
+        }
+
+        #[derive(Schema)]
+        #[allow(dead_code)]
+        struct Created {
+            user: User,
+        }
+
+        #[derive(Schema)]
+        #[allow(dead_code)]
+        struct Renamed {
+            user: User,
+            previous: String,
+        }
+
+        let mut definitions = Definitions::new("#/components/schemas/");
+        assert_eq!(
+            definitions.add(&Created::schema()),
+            json!({ "$ref": "#/components/schemas/Created" })
+        );
+        definitions.add(&Renamed::schema());
+        assert_eq!(
+            definitions.add(&Vec::<User>::schema())["items"],
+            json!({ "$ref": "#/components/schemas/User" })
+        );
+
+        let definitions = definitions.into_definitions();
+        assert_eq!(
+            definitions.keys().collect::<Vec<_>>(),
+            ["Created", "Renamed", "User"]
+        );
+        assert_eq!(
+            definitions["Renamed"]["properties"]["user"],
+            json!({ "$ref": "#/components/schemas/User" })
+        );
+    }
+
+    #[test]
+    fn test_maps() {
+        #[derive(Schema, PartialEq, Eq, Hash)]
+        #[allow(dead_code)]
+        enum Color {
+            Red,
+            Green,
+        }
+
+        #[derive(Schema)]
+        #[allow(dead_code)]
+        struct Palette {
+            names: HashMap<String, u32>,
+            weights: HashMap<Color, f64>,
+            points: HashMap<(i32, i32), String>,
+        }
+
+        let schema = to_json_schema::<Palette>();
+        let props = &schema["properties"];
+        assert_eq!(props["names"]["additionalProperties"]["type"], "integer");
+        assert!(props["names"].get("propertyNames").is_none());
+        assert_eq!(props["weights"]["propertyNames"]["$ref"], "#/$defs/Color");
+        assert_eq!(schema["$defs"]["Color"]["enum"], json!(["Red", "Green"]));
+        assert_eq!(props["points"]["type"], "array");
+        assert_eq!(props["points"]["items"]["prefixItems"][1]["type"], "string");
+        assert_eq!(props["points"]["items"]["items"], false);
+    }
+
+    #[test]
+    fn test_collections_and_results() {
+        let set = SchemaType::new(TypeKind::Set {
+            items: Box::new(String::schema()),
+            ordered: false,
+        });
+        assert_eq!(schema_type_to_json_schema(&set)["uniqueItems"], true);
+
+        let tuple = <(String, u8)>::schema();
+        let tuple = schema_type_to_json_schema(&tuple);
+        assert_eq!(tuple["prefixItems"].as_array().unwrap().len(), 2);
+        assert_eq!(tuple["items"], false);
+        assert_eq!(tuple["minItems"], 2);
+
+        let result = SchemaType::new(TypeKind::Result {
+            ok: Box::new(u32::schema()),
+            err: Box::new(String::schema()),
+        });
+        let result = schema_type_to_json_schema(&result);
+        assert_eq!(result["oneOf"][0]["required"], json!(["ok"]));
+        assert_eq!(result["oneOf"][1]["properties"]["error"]["type"], "string");
+    }
+
+    #[test]
+    fn test_variants() {
+        #[derive(Schema)]
+        #[allow(dead_code)]
+        enum Shape {
+            Empty,
+            Circle { radius: f64 },
+        }
+
+        #[derive(Schema)]
+        #[allow(dead_code)]
+        #[schema(tag = "kind")]
+        enum Event {
+            Start,
+            Move { x: i32 },
+        }
+
+        #[derive(Schema)]
+        #[allow(dead_code)]
+        #[schema(untagged)]
+        enum Value {
+            Number(f64),
+            Text(String),
+        }
+
+        let shape = to_json_schema::<Shape>();
+        assert_eq!(shape["oneOf"][0], json!({ "const": "Empty" }));
+        assert_eq!(
+            shape["oneOf"][1]["properties"]["type"],
+            json!({ "const": "Circle" })
+        );
+        assert_eq!(
+            shape["oneOf"][1]["properties"]["data"]["properties"]["radius"]["type"],
+            "number"
+        );
+
+        let event = to_json_schema::<Event>();
+        assert_eq!(event["oneOf"][1]["properties"]["kind"]["const"], "Move");
+        assert_eq!(event["oneOf"][1]["required"], json!(["kind", "x"]));
+
+        let value = to_json_schema::<Value>();
+        assert_eq!(value["anyOf"][0]["type"], "number");
+        assert_eq!(value["anyOf"][1]["type"], "string");
+    }
+
+    #[test]
+    fn test_annotations_and_constraints() {
+        #[derive(Schema)]
+        #[allow(dead_code)]
+        #[schema(deny_unknown_fields)]
+        struct Account {
+            #[schema(read_only)]
+            id: String,
+            #[schema(min_length = 3, max_length = 20)]
+            handle: String,
+            #[schema(extension("x-order" = 1))]
+            #[deprecated = "use handle"]
+            nick: Option<String>,
+        }
+
+        let schema = to_json_schema::<Account>();
+        let props = &schema["properties"];
+        assert_eq!(schema["additionalProperties"], false);
+        assert_eq!(props["id"]["readOnly"], true);
+        assert_eq!(props["handle"]["minLength"], 3);
+        assert_eq!(props["handle"]["maxLength"], 20);
+        assert_eq!(props["nick"]["deprecated"], true);
+        assert_eq!(props["nick"]["x-order"], 1);
+    }
+
+    #[test]
+    fn test_integer_widths() {
+        #[derive(Schema)]
+        #[allow(dead_code)]
+        struct Counter {
+            step: u8,
+            total: i64,
+            #[schema(minimum = 1)]
+            batch: u8,
+        }
+
+        let props = &to_json_schema::<Counter>()["properties"];
+        assert_eq!(
+            props["step"],
+            json!({ "type": "integer", "minimum": 0, "maximum": 255 })
+        );
+        assert_eq!(
+            props["total"],
+            json!({ "type": "integer", "minimum": i64::MIN, "maximum": i64::MAX })
+        );
+        assert_eq!(
+            props["batch"],
+            json!({ "type": "integer", "minimum": 1, "maximum": 255 })
+        );
+    }
+}
@@ -0,0 +1,919 @@
+//! Reading JSON Schema documents back into SchemaTypes
+//!
+//! Draft-07 and 2020-12 documents are both understood. `$ref`s into the
+//! document (`$defs`, `definitions`, or any other `#/...` pointer) are
+//! resolved to the target, named after its last pointer segment; a `$ref`
+//! back into a definition still being read becomes a `Ref`. `oneOf`/`anyOf`
+//! become Variants, recognising the shapes [`schema_type_to_json_schema`]
+//! writes for each [`Tagging`] and for Results, and a union with `null` is
+//! the other branch, as optional values are (so an untagged Variant with a
+//! unit case reads back as its other case made nullable).
+//!
+//! [`schema_type_to_json_schema`]: crate::schema_type_to_json_schema
+
+use schema::{IntegerKind, NumberKind, SchemaType, Tagging, TypeKind, VariantCase};
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+use std::fmt;
+
+/// Why a JSON Schema document has no SchemaType
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseError {
+    /// A `$ref` that doesn't point into the document
+    UnresolvedRef(String),
+    /// A value where a schema was expected that isn't one, at a JSON Pointer
+    Invalid { path: String, reason: String },
+    /// A valid schema SchemaType has no representation for
+    Unsupported { path: String, reason: String },
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::UnresolvedRef(reference) => {
+                write!(f, "$ref {:?} does not point into the document", reference)
+            }
+            ParseError::Invalid { path, reason } => {
+                write!(f, "invalid schema at {:?}: {}", path, reason)
+            }
+            ParseError::Unsupported { path, reason } => {
+                write!(f, "unsupported schema at {:?}: {}", path, reason)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// Read a JSON Schema document into a SchemaType
+pub fn parse(value: &Value) -> Result<SchemaType, ParseError> {
+    let mut parser = Parser {
+        document: value,
+        root_name: value
+            .get("title")
+            .and_then(Value::as_str)
+            .unwrap_or("Root")
+            .to_string(),
+        definitions: HashMap::new(),
+        parsing: vec![],
+        root_referenced: false,
+    };
+    let mut schema = parser.schema(value, "")?;
+    // `#` refs name the root so they have something to point at
+    if parser.root_referenced {
+        schema.name = Some(parser.root_name);
+    }
+    Ok(schema)
+}
+
+struct Parser<'a> {
+    document: &'a Value,
+    /// What `Ref`s back to the root are called: its title, or `Root`
+    root_name: String,
+    /// Definitions read so far, by pointer
+    definitions: HashMap<String, SchemaType>,
+    /// Definitions being read, whose uses become `Ref`s
+    parsing: Vec<String>,
+    root_referenced: bool,
+}
+
+impl Parser<'_> {
+    /// The schema at `path`, with its annotations and constraints
+    fn schema(&mut self, value: &Value, path: &str) -> Result<SchemaType, ParseError> {
+        let object = match value {
+            Value::Object(object) => object,
+            Value::Bool(true) => return Err(unsupported(path, "accepts any value")),
+            Value::Bool(false) => return Err(unsupported(path, "accepts no value")),
+            _ => return Err(invalid(path, "not an object or boolean")),
+        };
+        let mut schema = self.kind(object, path)?;
+        annotate(&mut schema, object, path)?;
+        Ok(schema)
+    }
+
+    fn kind(&mut self, object: &Map<String, Value>, path: &str) -> Result<SchemaType, ParseError> {
+        if let Some(reference) = object.get("$ref") {
+            let reference = reference
+                .as_str()
+                .ok_or_else(|| invalid(path, "$ref is not a string"))?;
+            return self.reference(reference);
+        }
+        if let Some(all) = object.get("allOf") {
+            return self.all_of(array(all, &format!("{path}/allOf"))?, path);
+        }
+        for keyword in ["oneOf", "anyOf"] {
+            if let Some(branches) = object.get(keyword) {
+                let path = format!("{path}/{keyword}");
+                return self.union(array(branches, &path)?, keyword == "anyOf", &path);
+            }
+        }
+        if let Some(value) = object.get("const") {
+            return constant(value, path);
+        }
+        if let Some(values) = object.get("enum") {
+            return enumeration(array(values, &format!("{path}/enum"))?, path);
+        }
+
+        let ty = match object.get("type") {
+            Some(Value::String(ty)) => ty.as_str(),
+            // `["string", "null"]` is an optional string
+            Some(Value::Array(types)) => {
+                let types: Vec<&str> = types
+                    .iter()
+                    .filter_map(Value::as_str)
+                    .filter(|ty| *ty != "null")
+                    .collect();
+                match types[..] {
+                    [] => "null",
+                    [ty] => ty,
+                    _ => return Err(unsupported(path, "several types")),
+                }
+            }
+            Some(_) => return Err(invalid(path, "type is not a string or array")),
+            None if object.contains_key("properties")
+                || object.contains_key("additionalProperties") =>
+            {
+                "object"
+            }
+            None if object.contains_key("items") || object.contains_key("prefixItems") => "array",
+            None => return Err(unsupported(path, "no type, so any value")),
+        };
+
+        let kind = match ty {
+            "string" => TypeKind::String,
+            "integer" => TypeKind::Integer(match object.get("format").and_then(Value::as_str) {
+                Some("int32") => IntegerKind::I32,
+                Some("uint8") => IntegerKind::U8,
+                Some("uint32") => IntegerKind::U32,
+                Some("uint64") => IntegerKind::U64,
+                _ => width(object).unwrap_or(IntegerKind::I64),
+            }),
+            "number" => TypeKind::Number(match object.get("format").and_then(Value::as_str) {
+                Some("float") => NumberKind::F32,
+                _ => NumberKind::F64,
+            }),
+            "boolean" => TypeKind::Boolean,
+            "null" => TypeKind::Null,
+            "array" => return self.array(object, path),
+            "object" => return self.object(object, path),
+            ty => return Err(invalid(path, &format!("unknown type {ty:?}"))),
+        };
+        Ok(SchemaType::new(kind))
+    }
+
+    /// The definition a `$ref` points at, or a `Ref` if it is being read
+    fn reference(&mut self, reference: &str) -> Result<SchemaType, ParseError> {
+        let unresolved = || ParseError::UnresolvedRef(reference.to_string());
+        let pointer = reference.strip_prefix('#').ok_or_else(unresolved)?;
+        if pointer.is_empty() {
+            self.root_referenced = true;
+            return Ok(SchemaType::new(TypeKind::Ref {
+                name: self.root_name.clone(),
+            }));
+        }
+        let target = self.document.pointer(pointer).ok_or_else(unresolved)?;
+        let name = pointer
+            .rsplit('/')
+            .next()
+            .unwrap_or_default()
+            .replace("~1", "/")
+            .replace("~0", "~");
+
+        if self.parsing.iter().any(|p| p == pointer) {
+            return Ok(SchemaType::new(TypeKind::Ref { name }));
+        }
+        if let Some(definition) = self.definitions.get(pointer) {
+            return Ok(definition.clone());
+        }
+        self.parsing.push(pointer.to_string());
+        let definition = self.schema(target, pointer);
+        self.parsing.pop();
+        let mut definition = definition?;
+        if !matches!(definition.kind, TypeKind::Ref { .. }) {
+            definition.name = Some(name);
+        }
+        self.definitions
+            .insert(pointer.to_string(), definition.clone());
+        Ok(definition)
+    }
+
+    /// A single `allOf` branch, or objects merged into one
+    fn all_of(&mut self, branches: &[Value], path: &str) -> Result<SchemaType, ParseError> {
+        if let [branch] = branches {
+            return self.schema(branch, &format!("{path}/allOf/0"));
+        }
+        let mut properties = HashMap::new();
+        let mut required = vec![];
+        let mut property_order = vec![];
+        for (i, branch) in branches.iter().enumerate() {
+            let branch = self.schema(branch, &format!("{path}/allOf/{i}"))?;
+            let TypeKind::Object {
+                properties: more,
+                required: more_required,
+            } = branch.kind
+            else {
+                return Err(unsupported(
+                    path,
+                    "allOf of schemas that aren't all objects",
+                ));
+            };
+            property_order.extend(branch.property_order);
+            properties.extend(more);
+            for name in more_required {
+                if !required.contains(&name) {
+                    required.push(name);
+                }
+            }
+        }
+        let mut schema = SchemaType::new(TypeKind::Object {
+            properties,
+            required,
+        });
+        schema.property_order = property_order;
+        Ok(schema)
+    }
+
+    /// A `oneOf`/`anyOf`: the other branch beside `null`, a Result, or a
+    /// Variant tagged as its branches show
+    fn union(
+        &mut self,
+        branches: &[Value],
+        any: bool,
+        path: &str,
+    ) -> Result<SchemaType, ParseError> {
+        let others: Vec<(usize, &Value)> = branches
+            .iter()
+            .enumerate()
+            .filter(|(_, branch)| !is_null(branch))
+            .collect();
+        if let [(i, branch)] = others[..]
+            && branches.len() <= 2
+        {
+            return self.schema(branch, &format!("{path}/{i}"));
+        }
+
+        let objects: Option<Vec<&Map<String, Value>>> = branches
+            .iter()
+            .map(|branch| tag_object(branch).get("properties")?.as_object())
+            .collect();
+        if let Some(objects) = &objects
+            && let [ok, err] = objects[..]
+            && ok.keys().eq(["ok"])
+            && err.keys().eq(["error"])
+        {
+            return Ok(SchemaType::new(TypeKind::Result {
+                ok: Box::new(self.schema(&ok["ok"], &format!("{path}/0/properties/ok"))?),
+                err: Box::new(self.schema(&err["error"], &format!("{path}/1/properties/error"))?),
+            }));
+        }
+
+        if !any {
+            if let Some(schema) = self.default_variant(branches, path)? {
+                return Ok(schema);
+            }
+            if let Some(objects) = &objects
+                && let Some(tag) = tag_property(branches, objects)
+            {
+                return self.tagged_variant(branches, objects, tag, path);
+            }
+        }
+
+        // Untagged: each case is told apart by its shape
+        let mut cases = vec![];
+        for (i, branch) in branches.iter().enumerate() {
+            let data = match is_null(branch) {
+                true => None,
+                false => Some(self.schema(branch, &format!("{path}/{i}"))?),
+            };
+            let name = branch
+                .get("title")
+                .and_then(Value::as_str)
+                .map(str::to_string)
+                .or_else(|| data.as_ref().and_then(|data| data.name.clone()))
+                .unwrap_or_else(|| match &data {
+                    Some(_) => format!("Case{}", i + 1),
+                    None => "Null".to_string(),
+                });
+            cases.push(case(name, data, branch));
+        }
+        let mut schema = SchemaType::new(TypeKind::Variant { cases });
+        schema.set_tagging(Tagging::Untagged);
+        Ok(schema)
+    }
+
+    /// The shape written without a [`Tagging`]: a string per unit case and
+    /// `{"type": name, "data": payload}` otherwise
+    fn default_variant(
+        &mut self,
+        branches: &[Value],
+        path: &str,
+    ) -> Result<Option<SchemaType>, ParseError> {
+        let mut cases = vec![];
+        for (i, branch) in branches.iter().enumerate() {
+            if let Some(name) = branch.get("const").and_then(Value::as_str) {
+                cases.push(case(name.to_string(), None, branch));
+                continue;
+            }
+            let Some(properties) = branch.get("properties").and_then(Value::as_object) else {
+                return Ok(None);
+            };
+            let name = properties.get("type").and_then(|t| t["const"].as_str());
+            match (name, properties.get("data")) {
+                (Some(name), Some(data)) if properties.len() == 2 => {
+                    let data = self.schema(data, &format!("{path}/{i}/properties/data"))?;
+                    cases.push(case(name.to_string(), Some(data), branch));
+                }
+                _ => return Ok(None),
+            }
+        }
+        Ok(Some(SchemaType::new(TypeKind::Variant { cases })))
+    }
+
+    /// Internally or adjacently tagged cases, each an object whose `tag`
+    /// property holds its name
+    fn tagged_variant(
+        &mut self,
+        branches: &[Value],
+        objects: &[&Map<String, Value>],
+        tag: &str,
+        path: &str,
+    ) -> Result<SchemaType, ParseError> {
+        // Adjacent when every case holds at most one other property, the
+        // same required one
+        let others: Vec<(&Value, &String)> = branches
+            .iter()
+            .zip(objects)
+            .flat_map(|(branch, properties)| properties.keys().map(move |name| (branch, name)))
+            .filter(|(_, name)| *name != tag)
+            .collect();
+        let content = match others.split_first() {
+            Some(((_, first), rest))
+                if rest.iter().all(|(_, other)| other == first)
+                    && objects.iter().all(|properties| properties.len() <= 2)
+                    && others.iter().all(|(branch, name)| requires(branch, name)) =>
+            {
+                Some(first.as_str())
+            }
+            _ => None,
+        };
+
+        let mut cases = vec![];
+        for (i, (branch, properties)) in branches.iter().zip(objects).enumerate() {
+            let name = properties[tag]["const"].as_str().unwrap_or_default();
+            let data = match content {
+                Some(content) => match properties.get(content) {
+                    Some(data) => {
+                        Some(self.schema(data, &format!("{path}/{i}/properties/{content}"))?)
+                    }
+                    None => None,
+                },
+                // A payload that isn't a record sits beside the tag object
+                None if branch.get("allOf").is_some() => {
+                    let payload = &branch["allOf"][0];
+                    Some(self.schema(payload, &format!("{path}/{i}/allOf/0"))?)
+                }
+                None if properties.len() == 1 => None,
+                None => {
+                    // The case's own fields sit beside the tag
+                    let mut payload = branch.as_object().cloned().unwrap_or_default();
+                    payload.retain(|key, _| {
+                        ["type", "properties", "required", "additionalProperties"]
+                            .contains(&key.as_str())
+                    });
+                    if let Some(Value::Object(fields)) = payload.get_mut("properties") {
+                        fields.remove(tag);
+                    }
+                    if let Some(Value::Array(required)) = payload.get_mut("required") {
+                        required.retain(|name| name != tag);
+                    }
+                    let payload = Value::Object(payload);
+                    Some(self.schema(&payload, &format!("{path}/{i}"))?)
+                }
+            };
+            cases.push(case(name.to_string(), data, branch));
+        }
+
+        let mut schema = SchemaType::new(TypeKind::Variant { cases });
+        schema.set_tagging(match content {
+            Some(content) => Tagging::Adjacent {
+                tag: tag.to_string(),
+                content: content.to_string(),
+            },
+            None => Tagging::Internal {
+                tag: tag.to_string(),
+            },
+        });
+        Ok(schema)
+    }
+
+    /// Tuples from `prefixItems` (or draft-07 `items` arrays), sets from
+    /// `uniqueItems`, and arrays
+    fn array(&mut self, object: &Map<String, Value>, path: &str) -> Result<SchemaType, ParseError> {
+        let prefix = match (object.get("prefixItems"), object.get("items")) {
+            (Some(prefix), _) => Some((prefix, "prefixItems")),
+            (None, Some(items @ Value::Array(_))) => Some((items, "items")),
+            _ => None,
+        };
+        if let Some((prefix, keyword)) = prefix {
+            let fields = array(prefix, &format!("{path}/{keyword}"))?
+                .iter()
+                .enumerate()
+                .map(|(i, item)| self.schema(item, &format!("{path}/{keyword}/{i}")))
+                .collect::<Result<_, _>>()?;
+            return Ok(SchemaType::new(TypeKind::Tuple { fields }));
+        }
+
+        let Some(items) = object.get("items") else {
+            if object.get("maxItems").and_then(Value::as_u64) == Some(0) {
+                return Ok(SchemaType::new(TypeKind::Tuple { fields: vec![] }));
+            }
+            return Err(unsupported(path, "array items may be anything"));
+        };
+        let items = Box::new(self.schema(items, &format!("{path}/items"))?);
+        let kind = match object.get("uniqueItems") {
+            Some(Value::Bool(true)) => TypeKind::Set {
+                items,
+                ordered: false,
+            },
+            _ => TypeKind::Array { items },
+        };
+        Ok(SchemaType::new(kind))
+    }
+
+    /// Objects with `properties`, or maps from `additionalProperties` (and
+    /// `propertyNames`) alone
+    fn object(
+        &mut self,
+        object: &Map<String, Value>,
+        path: &str,
+    ) -> Result<SchemaType, ParseError> {
+        let properties = match object.get("properties") {
+            Some(Value::Object(properties)) => Some(properties),
+            Some(_) => return Err(invalid(path, "properties is not an object")),
+            None => None,
+        };
+        let additional = object
+            .get("additionalProperties")
+            .filter(|value| !matches!(value, Value::Bool(_)));
+
+        if let (None, Some(value)) = (properties, additional) {
+            let key = match object.get("propertyNames") {
+                Some(names) => self.schema(names, &format!("{path}/propertyNames"))?,
+                None => SchemaType::new(TypeKind::String),
+            };
+            let value = self.schema(value, &format!("{path}/additionalProperties"))?;
+            return Ok(SchemaType::new(TypeKind::Map {
+                key: Box::new(key),
+                value: Box::new(value),
+                ordered: false,
+            }));
+        }
+
+        let mut fields = HashMap::new();
+        let mut property_order = vec![];
+        for (name, property) in properties.into_iter().flatten() {
+            let property = self.schema(property, &format!("{path}/properties/{name}"))?;
+            fields.insert(name.clone(), property);
+            property_order.push(name.clone());
+        }
+        let required = match object.get("required") {
+            Some(required) => array(required, &format!("{path}/required"))?
+                .iter()
+                .filter_map(Value::as_str)
+                .map(str::to_string)
+                .collect(),
+            None => vec![],
+        };
+        let mut schema = SchemaType::new(TypeKind::Object {
+            properties: fields,
+            required,
+        });
+        schema.property_order = property_order;
+        if object.get("additionalProperties") == Some(&Value::Bool(false)) {
+            schema.set_deny_unknown_fields(true);
+        }
+        Ok(schema)
+    }
+}
+
+/// A `const`: a one-value Enum for strings, the value's type otherwise
+fn constant(value: &Value, path: &str) -> Result<SchemaType, ParseError> {
+    let kind = match value {
+        Value::String(s) => TypeKind::Enum {
+            variants: vec![s.clone()],
+        },
+        other => json_type(other).ok_or_else(|| unsupported(path, "non-scalar const"))?,
+    };
+    Ok(SchemaType::new(kind))
+}
+
+/// An `enum` of strings (and possibly `null`), or of one other type
+fn enumeration(values: &[Value], path: &str) -> Result<SchemaType, ParseError> {
+    let values: Vec<&Value> = values.iter().filter(|value| !value.is_null()).collect();
+    if let Some(variants) = values
+        .iter()
+        .map(|value| value.as_str().map(str::to_string))
+        .collect::<Option<Vec<_>>>()
+    {
+        return Ok(SchemaType::new(TypeKind::Enum { variants }));
+    }
+    let kinds: Vec<Option<TypeKind>> = values.iter().map(|value| json_type(value)).collect();
+    match &kinds[..] {
+        [Some(first), rest @ ..] if rest.iter().all(|kind| kind.as_ref() == Some(first)) => {
+            Ok(SchemaType::new(first.clone()))
+        }
+        _ => Err(unsupported(path, "enum of mixed or composite values")),
+    }
+}
+
+/// The kind of a scalar JSON value
+fn json_type(value: &Value) -> Option<TypeKind> {
+    match value {
+        Value::Bool(_) => Some(TypeKind::Boolean),
+        Value::Number(n) if n.is_f64() => Some(TypeKind::Number(NumberKind::F64)),
+        Value::Number(_) => Some(TypeKind::Integer(IntegerKind::I64)),
+        Value::Null => Some(TypeKind::Null),
+        _ => None,
+    }
+}
+
+/// The property every branch requires and holds a different constant in,
+/// preferring the one listed first
+fn tag_property<'a>(branches: &[Value], objects: &[&'a Map<String, Value>]) -> Option<&'a str> {
+    let first = objects.first()?;
+    first.keys().map(String::as_str).find(|tag| {
+        branches.iter().zip(objects).all(|(branch, properties)| {
+            properties.get(*tag).is_some_and(|p| p["const"].is_string())
+                && requires(tag_object(branch), tag)
+        })
+    })
+}
+
+/// The part of an internally tagged case holding the tag: the case itself,
+/// or the second half of `{"allOf": [payload, tag object]}`
+fn tag_object(branch: &Value) -> &Value {
+    match branch.get("allOf").and_then(Value::as_array) {
+        Some(all) if all.len() == 2 => &all[1],
+        _ => branch,
+    }
+}
+
+/// Whether an object schema lists `name` as required
+fn requires(object: &Value, name: &str) -> bool {
+    object["required"]
+        .as_array()
+        .is_some_and(|required| required.iter().any(|r| r == name))
+}
+
+/// A case carrying the branch's description, deprecation and extensions
+fn case(name: String, data: Option<SchemaType>, branch: &Value) -> VariantCase {
+    let mut case = VariantCase {
+        name,
+        data,
+        description: branch
+            .get("description")
+            .and_then(Value::as_str)
+            .map(str::to_string),
+        extensions: Default::default(),
+    };
+    if branch.get("deprecated") == Some(&Value::Bool(true)) {
+        case.deprecate(None);
+    }
+    for (key, value) in branch.as_object().into_iter().flatten() {
+        if key.starts_with("x-") {
+            case.extensions.insert(key.clone(), value.clone());
+        }
+    }
+    // The payload has the annotations when the branch is the payload itself
+    if let Some(data) = &case.data
+        && data.description == case.description
+    {
+        case.description = None;
+    }
+    case
+}
+
+/// Title, description, default, flags, `x-*` extensions and constraints
+fn annotate(
+    schema: &mut SchemaType,
+    object: &Map<String, Value>,
+    path: &str,
+) -> Result<(), ParseError> {
+    if let Some(title) = object.get("title").and_then(Value::as_str) {
+        schema.title = Some(title.to_string());
+    }
+    if let Some(description) = object.get("description").and_then(Value::as_str) {
+        schema.description = Some(description.to_string());
+    }
+    if let Some(default) = object.get("default") {
+        schema.default = Some(default.clone());
+    }
+    let flags = [
+        ("deprecated", schema::DEPRECATED),
+        ("readOnly", schema::READ_ONLY),
+        ("writeOnly", schema::WRITE_ONLY),
+    ];
+    for (keyword, extension) in flags {
+        if object.get(keyword) == Some(&Value::Bool(true)) {
+            schema
+                .extensions
+                .insert(extension.to_string(), Value::Bool(true));
+        }
+    }
+    for (key, value) in object {
+        if key.starts_with("x-") {
+            schema.extensions.insert(key.clone(), value.clone());
+        }
+    }
+
+    let number = |keyword: &str| object.get(keyword).and_then(Value::as_f64);
+    let count = |keyword: &str| -> Result<Option<usize>, ParseError> {
+        match object.get(keyword) {
+            None => Ok(None),
+            Some(value) => match value.as_u64() {
+                Some(count) => Ok(Some(count as usize)),
+                None => Err(invalid(path, &format!("{keyword} is not a count"))),
+            },
+        }
+    };
+    let constraints = &mut schema.constraints;
+    match &schema.kind {
+        TypeKind::Integer(_) | TypeKind::Number(_) => {
+            // Exclusive bounds are only exact for integers
+            let integer = matches!(schema.kind, TypeKind::Integer(_));
+            let exclusive = |keyword: &str, step: f64| {
+                number(keyword)
+                    .filter(|_| integer)
+                    .map(|bound| bound + step)
+            };
+            // A bound at the integer kind's own limit is its width, not a
+            // constraint
+            let limit = |keyword: &str, limit: fn(&IntegerKind) -> i128| match &schema.kind {
+                TypeKind::Integer(kind) => object.get(keyword).and_then(whole) == Some(limit(kind)),
+                _ => false,
+            };
+            if !limit("minimum", IntegerKind::min)
+                && let Some(minimum) =
+                    number("minimum").or_else(|| exclusive("exclusiveMinimum", 1.0))
+            {
+                constraints.minimum = Some(minimum);
+            }
+            if !limit("maximum", IntegerKind::max)
+                && let Some(maximum) =
+                    number("maximum").or_else(|| exclusive("exclusiveMaximum", -1.0))
+            {
+                constraints.maximum = Some(maximum);
+            }
+        }
+        TypeKind::String => {
+            constraints.min_length = count("minLength")?.or(constraints.min_length);
+            constraints.max_length = count("maxLength")?.or(constraints.max_length);
+            if let Some(pattern) = object.get("pattern").and_then(Value::as_str) {
+                constraints.pattern = Some(pattern.to_string());
+            }
+            if let Some(format) = object.get("format").and_then(Value::as_str) {
+                constraints.format = Some(format.to_string());
+            }
+        }
+        // A tuple's item counts are its length
+        TypeKind::Array { .. } | TypeKind::Set { .. } => {
+            constraints.min_items = count("minItems")?.or(constraints.min_items);
+            constraints.max_items = count("maxItems")?.or(constraints.max_items);
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+/// Whether a branch only allows `null`
+/// The fixed-width integer kind whose whole range `minimum`..`maximum` spans
+fn width(object: &Map<String, Value>) -> Option<IntegerKind> {
+    let bound = |keyword: &str| object.get(keyword).and_then(whole);
+    let (minimum, maximum) = (bound("minimum")?, bound("maximum")?);
+    [
+        IntegerKind::U8,
+        IntegerKind::I32,
+        IntegerKind::U32,
+        IntegerKind::I64,
+        IntegerKind::U64,
+    ]
+    .into_iter()
+    .find(|kind| kind.min() == minimum && kind.max() == maximum)
+}
+
+/// A JSON integer, exactly
+fn whole(value: &Value) -> Option<i128> {
+    (value.as_i64().map(i128::from)).or_else(|| value.as_u64().map(i128::from))
+}
+
+fn is_null(branch: &Value) -> bool {
+    branch.get("type").and_then(Value::as_str) == Some("null")
+}
+
+fn array<'a>(value: &'a Value, path: &str) -> Result<&'a [Value], ParseError> {
+    value
+        .as_array()
+        .map(Vec::as_slice)
+        .ok_or_else(|| invalid(path, "not an array"))
+}
+
+fn invalid(path: &str, reason: &str) -> ParseError {
+    ParseError::Invalid {
+        path: path.to_string(),
+        reason: reason.to_string(),
+    }
+}
+
+fn unsupported(path: &str, reason: &str) -> ParseError {
+    ParseError::Unsupported {
+        path: path.to_string(),
+        reason: reason.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{schema_type_to_json_schema, to_json_schema};
+    use schema::Schema;
+    use serde_json::json;
+    use std::collections::{BTreeMap, BTreeSet};
+
+    /// Reading a generated document back and writing it again changes
+    /// nothing
+    fn assert_round_trip<T: Schema>() {
+        let document = to_json_schema::<T>();
+        let parsed = parse(&document).unwrap();
+        assert_eq!(schema_type_to_json_schema(&parsed), document);
+    }
+
+    #[test]
+    fn test_round_trips() {
+        #[derive(Schema)]
+        #[allow(dead_code)]
+        enum Color {
+            Red,
+            Green,
+        }
+
+        #[derive(Schema)]
+        #[allow(dead_code)]
+        enum Shape {
+            Empty,
+            Circle { radius: f64 },
+        }
+
+        #[derive(Schema)]
+        #[allow(dead_code)]
+        #[schema(tag = "kind")]
+        enum Event {
+            Start,
+            Move { x: i32, y: Option<i32> },
+        }
+
+        #[derive(Schema)]
+        #[allow(dead_code)]
+        #[schema(tag = "t", content = "c")]
+        enum Message {
+            Ping,
+            Text(String),
+        }
+
+        #[derive(Schema)]
+        #[allow(dead_code)]
+        #[schema(untagged)]
+        enum Value {
+            Number(f64),
+            Text(String),
+        }
+
+        /// A node in a tree
+        #[derive(Schema)]
+        #[allow(dead_code)]
+        #[schema(deny_unknown_fields)]
+        struct Node {
+            #[schema(read_only)]
+            id: String,
+            #[schema(min_length = 3, max_length = 20, pattern = "^[a-z]+$")]
+            handle: String,
+            #[schema(extension("x-order" = 1))]
+            #[deprecated = "use handle"]
+            nick: Option<String>,
+            color: Color,
+            weights: BTreeMap<Color, f64>,
+            points: BTreeMap<u32, String>,
+            tags: BTreeSet<String>,
+            pair: (String, u8),
+            shape: Shape,
+            events: Vec<Event>,
+            message: Message,
+            value: Value,
+            result: Result<u32, String>,
+            children: Vec<Node>,
+        }
+
+        assert_round_trip::<Node>();
+    }
+
+    #[test]
+    fn test_integer_widths_from_bounds() {
+        let parsed = parse(&json!({ "type": "integer", "minimum": 0, "maximum": 255 })).unwrap();
+        assert_eq!(parsed.kind, TypeKind::Integer(IntegerKind::U8));
+        assert_eq!(parsed.constraints.minimum, None);
+
+        let parsed = parse(&json!({ "type": "integer", "minimum": 1, "maximum": 255 })).unwrap();
+        assert_eq!(parsed.kind, TypeKind::Integer(IntegerKind::I64));
+        assert_eq!(parsed.constraints.minimum, Some(1.0));
+        assert_eq!(parsed.constraints.maximum, Some(255.0));
+    }
+
+    #[test]
+    fn test_draft_07_document() {
+        let document = json!({
+            "$schema": "http://json-schema.org/draft-07/schema#",
+            "title": "Order",
+            "type": "object",
+            "properties": {
+                "id": { "type": "integer", "format": "int32", "exclusiveMinimum": 0 },
+                "email": { "type": ["string", "null"], "format": "email" },
+                "status": { "$ref": "#/definitions/Status", "description": "Where it is" },
+                "lines": { "type": "array", "items": { "$ref": "#/definitions/Line" } },
+                "parent": { "oneOf": [{ "$ref": "#" }, { "type": "null" }] }
+            },
+            "required": ["id", "status"],
+            "definitions": {
+                "Status": { "type": "string", "enum": ["open", "paid", null] },
+                "Line": {
+                    "type": "object",
+                    "properties": { "sku": { "type": "string" } },
+                    "additionalProperties": { "type": "number" }
+                }
+            }
+        });
+
+        let schema = parse(&document).unwrap();
+        assert_eq!(schema.name.as_deref(), Some("Order"));
+        let TypeKind::Object {
+            properties,
+            required,
+        } = &schema.kind
+        else {
+            panic!("expected an object, got {:?}", schema.kind);
+        };
+        assert_eq!(required, &["id", "status"]);
+
+        let id = &properties["id"];
+        assert_eq!(id.kind, TypeKind::Integer(IntegerKind::I32));
+        assert_eq!(id.constraints.minimum, Some(1.0));
+        let email = &properties["email"];
+        assert_eq!(email.kind, TypeKind::String);
+        assert_eq!(email.constraints.format.as_deref(), Some("email"));
+
+        let status = &properties["status"];
+        assert_eq!(status.name.as_deref(), Some("Status"));
+        assert_eq!(status.description.as_deref(), Some("Where it is"));
+        assert_eq!(
+            status.kind,
+            TypeKind::Enum {
+                variants: vec!["open".to_string(), "paid".to_string()]
+            }
+        );
+
+        let TypeKind::Array { items } = &properties["lines"].kind else {
+            panic!("expected an array");
+        };
+        assert_eq!(items.name.as_deref(), Some("Line"));
+        assert!(matches!(items.kind, TypeKind::Object { .. }));
+        assert_eq!(
+            properties["parent"].kind,
+            TypeKind::Ref {
+                name: "Order".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(
+            parse(&json!({ "$ref": "other.json#/$defs/Thing" })),
+            Err(ParseError::UnresolvedRef(
+                "other.json#/$defs/Thing".to_string()
+            ))
+        );
+        assert_eq!(
+            parse(&json!({ "$ref": "#/$defs/Missing" })),
+            Err(ParseError::UnresolvedRef("#/$defs/Missing".to_string()))
+        );
+        assert_eq!(
+            parse(&json!({ "type": "object", "properties": { "anything": {} } })),
+            Err(ParseError::Unsupported {
+                path: "/properties/anything".to_string(),
+                reason: "no type, so any value".to_string()
+            })
+        );
+        assert!(matches!(
+            parse(&json!({ "type": 3 })),
+            Err(ParseError::Invalid { .. })
+        ));
+    }
+}
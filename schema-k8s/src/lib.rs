@@ -0,0 +1,667 @@
+//! Kubernetes CustomResourceDefinition schemas
+//!
+//! The API server only accepts a CRD's `openAPIV3Schema` if it is
+//! *structural*: every node has a `type` (or says why it doesn't), no
+//! `$ref`, `additionalProperties` never beside `properties`, and
+//! `allOf`/`anyOf`/`oneOf` hold nothing but value checks on fields the
+//! surrounding schema already declares. The schemas written here keep to
+//! those rules:
+//!
+//! - A Variant becomes one object holding every case's fields, with a
+//!   `oneOf` whose branches only pin the tag to one case name and list that
+//!   case's required fields. Untagged variants check with `anyOf` instead,
+//!   since their cases can overlap.
+//! - Where a value's possible shapes share no type, the node is
+//!   `x-kubernetes-int-or-string` for integers mixed with strings and
+//!   `x-kubernetes-preserve-unknown-fields` otherwise, leaving the check to
+//!   the operator. Recursive references are the same, since they can't be
+//!   spelled out.
+//! - Sets of scalars are `x-kubernetes-list-type: set`; Kubernetes rejects
+//!   `uniqueItems`.
+//! - Unit values are a `nullable` object that must be empty, as schemas have
+//!   no null type.
+//!
+//! Objects that deny unknown fields are left open: Kubernetes doesn't allow
+//! `additionalProperties: false`, and prunes undeclared fields anyway.
+
+use schema::{GenerateOptions, IntegerKind, NumberKind, Schema, SchemaType, Tagging, TypeKind};
+use serde_json::{Map, Value, json};
+use std::collections::BTreeSet;
+
+/// Accepts any value below the node, which then isn't pruned
+pub const PRESERVE_UNKNOWN_FIELDS: &str = "x-kubernetes-preserve-unknown-fields";
+
+/// Accepts an integer or a string
+pub const INT_OR_STRING: &str = "x-kubernetes-int-or-string";
+
+/// How an array's items are merged by server-side apply
+pub const LIST_TYPE: &str = "x-kubernetes-list-type";
+
+/// The `openAPIV3Schema` of a custom resource whose `spec` is `Spec`
+pub fn to_crd_schema<Spec: Schema>() -> Value {
+    schema_types_to_crd_schema(&Spec::schema(), None)
+}
+
+/// The `openAPIV3Schema` of a custom resource whose `spec` is `Spec` and
+/// whose `status` subresource is `Status`
+pub fn to_crd_schema_with_status<Spec: Schema, Status: Schema>() -> Value {
+    schema_types_to_crd_schema(&Spec::schema(), Some(&Status::schema()))
+}
+
+/// The `openAPIV3Schema` of a custom resource with a required `spec` and an
+/// optional `status`
+///
+/// `apiVersion`, `kind` and `metadata` are left to the API server, which
+/// declares them for every resource.
+pub fn schema_types_to_crd_schema(spec: &SchemaType, status: Option<&SchemaType>) -> Value {
+    let mut properties = Map::new();
+    properties.insert("spec".to_string(), schema_type_to_structural(spec));
+    if let Some(status) = status {
+        properties.insert("status".to_string(), schema_type_to_structural(status));
+    }
+    json!({
+        "type": "object",
+        "properties": properties,
+        "required": ["spec"]
+    })
+}
+
+/// A structural schema for `T`, for use anywhere in an `openAPIV3Schema`
+pub fn to_structural_schema<T: Schema>() -> Value {
+    schema_type_to_structural(&T::schema())
+}
+
+/// A structural schema for `T`, bounded by `options`
+pub fn to_structural_schema_with_options<T: Schema>(options: &GenerateOptions) -> Value {
+    schema_type_to_structural_with_options(&T::schema(), options)
+}
+
+/// A structural schema for a SchemaType, bounded by `options`
+pub fn schema_type_to_structural_with_options(
+    schema: &SchemaType,
+    options: &GenerateOptions,
+) -> Value {
+    schema_type_to_structural(&options.apply(schema))
+}
+
+/// A structural schema for a SchemaType
+pub fn schema_type_to_structural(schema: &SchemaType) -> Value {
+    let mut result = match &schema.kind {
+        TypeKind::String => json!({ "type": "string" }),
+        TypeKind::Integer(kind) => {
+            let format = match kind {
+                IntegerKind::I32 | IntegerKind::U8 => "int32",
+                IntegerKind::I64 | IntegerKind::U32 | IntegerKind::U64 | IntegerKind::Usize => {
+                    "int64"
+                }
+            };
+            json!({ "type": "integer", "format": format })
+        }
+        TypeKind::Number(kind) => {
+            let format = match kind {
+                NumberKind::F32 => "float",
+                NumberKind::F64 => "double",
+            };
+            json!({ "type": "number", "format": format })
+        }
+        TypeKind::Boolean => json!({ "type": "boolean" }),
+        TypeKind::Null => null(),
+        TypeKind::Array { items } => json!({
+            "type": "array",
+            "items": schema_type_to_structural(items)
+        }),
+        TypeKind::Set { items, .. } => set(schema_type_to_structural(items)),
+        TypeKind::Map { key, value, .. } => match key.kind {
+            TypeKind::String | TypeKind::Enum { .. } => json!({
+                "type": "object",
+                "additionalProperties": schema_type_to_structural(value)
+            }),
+            // Other keys are a list of [key, value] pairs
+            _ => json!({
+                "type": "array",
+                "items": tuple(&[
+                    schema_type_to_structural(key),
+                    schema_type_to_structural(value),
+                ]),
+            }),
+        },
+        TypeKind::Object { required, .. } => {
+            let properties: Map<String, Value> = schema
+                .ordered_properties()
+                .into_iter()
+                .map(|(name, property)| (name.to_string(), schema_type_to_structural(property)))
+                .collect();
+            let mut object = json!({ "type": "object", "properties": properties });
+            if !required.is_empty() {
+                object["required"] = json!(required);
+            }
+            object
+        }
+        TypeKind::Enum { variants } => json!({ "type": "string", "enum": variants }),
+        TypeKind::TaggedUnion {
+            tag_field,
+            tag_variants,
+            data_fields,
+        } => {
+            let mut properties: Map<String, Value> = data_fields
+                .iter()
+                .map(|(name, field)| (name.clone(), schema_type_to_structural(field)))
+                .collect();
+            properties.insert(
+                tag_field.clone(),
+                json!({ "type": "string", "enum": tag_variants }),
+            );
+            json!({
+                "type": "object",
+                "properties": properties,
+                "required": [tag_field]
+            })
+        }
+        TypeKind::Variant { cases } => {
+            let tagging = schema.tagging();
+            let shapes: Vec<Value> = cases
+                .iter()
+                .map(|case| {
+                    let data = case.data.as_ref().map(schema_type_to_structural);
+                    case_shape(&case.name, data, tagging.as_ref())
+                })
+                .collect();
+            let mut merged = merge(&shapes);
+            let branches: Vec<Map<String, Value>> =
+                shapes.iter().map(|shape| checks(shape, &merged)).collect();
+            match tagging {
+                // Checks need declared properties to refer to, and an empty
+                // branch would match anything
+                _ if merged["type"] != "object" || merged.get("properties").is_none() => {}
+                _ if branches.iter().any(Map::is_empty) => {}
+                Some(Tagging::Untagged) => merged["anyOf"] = json!(branches),
+                // The tag keeps the branches apart, so exactly one matches
+                _ => merged["oneOf"] = json!(branches),
+            }
+            merged
+        }
+        TypeKind::Result { ok, err } => json!({
+            "type": "object",
+            "properties": {
+                "ok": schema_type_to_structural(ok),
+                "error": schema_type_to_structural(err)
+            },
+            "oneOf": [{ "required": ["ok"] }, { "required": ["error"] }]
+        }),
+        TypeKind::Tuple { fields } => tuple(
+            &fields
+                .iter()
+                .map(schema_type_to_structural)
+                .collect::<Vec<_>>(),
+        ),
+        TypeKind::Ref { name } => json!({
+            PRESERVE_UNKNOWN_FIELDS: true,
+            "description": format!("Another {name}, nested recursively")
+        }),
+    };
+
+    if let Some(title) = &schema.title {
+        result["title"] = json!(title);
+    }
+    if let Some(description) = &schema.description {
+        result["description"] = json!(description);
+    }
+    if let Some(default) = &schema.default {
+        result["default"] = default.clone();
+    }
+    if let Value::Object(object) = &mut result {
+        for (keyword, value) in schema.constraints.json_keywords() {
+            match keyword.as_str() {
+                "uniqueItems" => {}
+                _ => {
+                    object.insert(keyword, value);
+                }
+            }
+        }
+        // Other extensions would be dropped by the API server
+        for (key, value) in schema.vendor_extensions() {
+            if key.starts_with("x-kubernetes-") {
+                object.insert(key.to_string(), value.clone());
+            }
+        }
+    }
+    if schema.constraints.unique_items && result["type"] == "array" && is_scalar(&result["items"]) {
+        result[LIST_TYPE] = json!("set");
+    }
+
+    result
+}
+
+/// What a unit value is: `null`, or an empty object
+fn null() -> Value {
+    json!({ "type": "object", "nullable": true, "maxProperties": 0 })
+}
+
+/// Any value at all
+fn any() -> Value {
+    json!({ PRESERVE_UNKNOWN_FIELDS: true })
+}
+
+/// An array of unique `items`, which Kubernetes can only check for scalars
+fn set(items: Value) -> Value {
+    let mut set = json!({ "type": "array" });
+    if is_scalar(&items) {
+        set[LIST_TYPE] = json!("set");
+    }
+    set["items"] = items;
+    set
+}
+
+/// A fixed-length array, with items any of the fields' shapes
+fn tuple(fields: &[Value]) -> Value {
+    json!({
+        "type": "array",
+        "items": merge(fields),
+        "minItems": fields.len(),
+        "maxItems": fields.len()
+    })
+}
+
+fn is_scalar(schema: &Value) -> bool {
+    schema[INT_OR_STRING] == true
+        || matches!(
+            schema["type"].as_str(),
+            Some("string" | "integer" | "number" | "boolean")
+        )
+}
+
+/// How one Variant case is written under `tagging`
+fn case_shape(name: &str, data: Option<Value>, tagging: Option<&Tagging>) -> Value {
+    let tag = |tag: &str| {
+        json!({
+            "type": "object",
+            "properties": { tag: { "type": "string", "enum": [name] } },
+            "required": [tag]
+        })
+    };
+    match (tagging, data) {
+        (None, None) => json!({ "type": "string", "enum": [name] }),
+        (None, Some(data)) => {
+            let mut shape = tag("type");
+            shape["properties"]["data"] = data;
+            shape["required"] = json!(["type", "data"]);
+            shape
+        }
+        (Some(Tagging::Untagged), None) => null(),
+        (Some(Tagging::Untagged), Some(data)) => data,
+        (Some(Tagging::Internal { tag: name } | Tagging::Adjacent { tag: name, .. }), None) => {
+            tag(name)
+        }
+        (Some(Tagging::Adjacent { tag: name, content }), Some(data)) => {
+            let mut shape = tag(name);
+            shape["properties"][content] = data;
+            shape["required"] = json!([name, content]);
+            shape
+        }
+        (Some(Tagging::Internal { tag: name }), Some(data)) => {
+            let mut shape = tag(name);
+            match data["properties"].as_object() {
+                // Record payloads share the object with the tag
+                Some(properties) => {
+                    for (field, property) in properties {
+                        if field != name {
+                            shape["properties"][field] = property.clone();
+                        }
+                    }
+                    let required = data["required"].as_array().into_iter().flatten();
+                    shape["required"] = std::iter::once(json!(name))
+                        .chain(required.filter(|field| *field != name).cloned())
+                        .collect();
+                }
+                None => shape[PRESERVE_UNKNOWN_FIELDS] = json!(true),
+            }
+            shape
+        }
+    }
+}
+
+/// One schema that every value of `schemas` matches, as exact as
+/// structural schemas allow: objects keep every property, scalar enums
+/// combine, and keywords only some of them have are dropped
+fn merge(schemas: &[Value]) -> Value {
+    let mut distinct: Vec<Value> = vec![];
+    let mut nullable = false;
+    for schema in schemas {
+        if *schema == null() {
+            nullable = true;
+            continue;
+        }
+        let mut schema = schema.clone();
+        if let Some(object) = schema.as_object_mut() {
+            nullable |= object.remove("nullable") == Some(json!(true));
+        }
+        if !distinct.contains(&schema) {
+            distinct.push(schema);
+        }
+    }
+
+    let mut merged = match distinct.len() {
+        0 if nullable => return null(),
+        0 => return any(),
+        1 => distinct.remove(0),
+        _ => combine(&distinct),
+    };
+    if nullable {
+        merged["nullable"] = json!(true);
+    }
+    merged
+}
+
+/// [`merge`] for several distinct, non-null schemas
+fn combine(schemas: &[Value]) -> Value {
+    let types: BTreeSet<&str> = schemas
+        .iter()
+        .map(|schema| schema["type"].as_str().unwrap_or_default())
+        .collect();
+    let ty = match types.into_iter().collect::<Vec<_>>()[..] {
+        [""] => return any(),
+        [ty] => ty,
+        ["integer", "string"] => return json!({ INT_OR_STRING: true }),
+        _ => return any(),
+    };
+
+    const COMBINED: &[&str] = &[
+        "properties",
+        "required",
+        "additionalProperties",
+        "items",
+        "enum",
+    ];
+    let mut merged = Map::new();
+    for (keyword, value) in schemas[0].as_object().into_iter().flatten() {
+        if !COMBINED.contains(&keyword.as_str())
+            && schemas
+                .iter()
+                .all(|schema| schema.get(keyword) == Some(value))
+        {
+            merged.insert(keyword.clone(), value.clone());
+        }
+    }
+
+    let all = |keyword: &str| -> Option<Vec<&Value>> {
+        schemas.iter().map(|schema| schema.get(keyword)).collect()
+    };
+    let any_have = |keyword: &str| schemas.iter().any(|schema| schema.get(keyword).is_some());
+    match ty {
+        "object" if any_have("additionalProperties") => match all("additionalProperties") {
+            Some(values) => {
+                let values: Vec<Value> = values.into_iter().cloned().collect();
+                merged.insert("additionalProperties".to_string(), merge(&values));
+            }
+            // Maps mixed with records
+            None => return json!({ "type": "object", PRESERVE_UNKNOWN_FIELDS: true }),
+        },
+        "object" => {
+            let mut names: Vec<&String> = vec![];
+            for schema in schemas {
+                for name in schema["properties"]
+                    .as_object()
+                    .into_iter()
+                    .flat_map(Map::keys)
+                {
+                    if !names.contains(&name) {
+                        names.push(name);
+                    }
+                }
+            }
+            let properties: Map<String, Value> = names
+                .into_iter()
+                .map(|name| {
+                    let shapes: Vec<Value> = schemas
+                        .iter()
+                        .filter_map(|schema| schema["properties"].get(name).cloned())
+                        .collect();
+                    (name.clone(), merge(&shapes))
+                })
+                .collect();
+            if !properties.is_empty() {
+                merged.insert("properties".to_string(), Value::Object(properties));
+            }
+            // Only what every shape requires
+            let required: Vec<&Value> = schemas[0]["required"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter(|name| {
+                    schemas.iter().all(|schema| {
+                        schema["required"]
+                            .as_array()
+                            .is_some_and(|required| required.contains(name))
+                    })
+                })
+                .collect();
+            if !required.is_empty() {
+                merged.insert("required".to_string(), json!(required));
+            }
+        }
+        "array" => {
+            let items: Vec<Value> = schemas
+                .iter()
+                .filter_map(|schema| schema.get("items").cloned())
+                .collect();
+            merged.insert("items".to_string(), merge(&items));
+        }
+        _ => {
+            if let Some(enums) = all("enum") {
+                let mut values: Vec<&Value> = vec![];
+                for value in enums
+                    .into_iter()
+                    .flat_map(|e| e.as_array().into_iter().flatten())
+                {
+                    if !values.contains(&value) {
+                        values.push(value);
+                    }
+                }
+                merged.insert("enum".to_string(), json!(values));
+            }
+        }
+    }
+    Value::Object(merged)
+}
+
+/// The value checks `shape` adds to `merged`, as a `oneOf` branch: only
+/// `enum` and `required`, on properties `merged` declares
+fn checks(shape: &Value, merged: &Value) -> Map<String, Value> {
+    let mut branch = Map::new();
+    for keyword in ["enum", "required"] {
+        if let Some(value) = shape.get(keyword)
+            && merged.get(keyword) != Some(value)
+        {
+            branch.insert(keyword.to_string(), value.clone());
+        }
+    }
+    let mut properties = Map::new();
+    for (name, property) in shape["properties"].as_object().into_iter().flatten() {
+        if let Some(declared) = merged["properties"].get(name) {
+            let nested = checks(property, declared);
+            if !nested.is_empty() {
+                properties.insert(name.clone(), Value::Object(nested));
+            }
+        }
+    }
+    if !properties.is_empty() {
+        branch.insert("properties".to_string(), Value::Object(properties));
+    }
+    branch
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Junctors may only hold these, on properties the schema declares
+    const VALUE_CHECKS: &[&str] = &["enum", "required", "properties"];
+
+    /// Panics where `schema` breaks a structural-schema rule
+    fn assert_structural(schema: &Value) {
+        let object = schema.as_object().unwrap();
+        assert!(
+            object.contains_key("type")
+                || object.contains_key(PRESERVE_UNKNOWN_FIELDS)
+                || object.contains_key(INT_OR_STRING),
+            "untyped node {schema}"
+        );
+        assert!(!object.contains_key("$ref"));
+        assert!(!object.contains_key("uniqueItems"));
+        assert!(
+            !(object.contains_key("properties") && object.contains_key("additionalProperties"))
+        );
+        if schema["type"] == "array" {
+            assert!(object.contains_key("items"), "array without items {schema}");
+        }
+        for junctor in ["allOf", "anyOf", "oneOf"] {
+            for branch in schema[junctor].as_array().into_iter().flatten() {
+                assert_checks(branch, schema);
+            }
+        }
+        for property in schema["properties"].as_object().into_iter().flatten() {
+            assert_structural(property.1);
+        }
+        for keyword in ["items", "additionalProperties"] {
+            if let Some(nested) = schema.get(keyword) {
+                assert_structural(nested);
+            }
+        }
+    }
+
+    fn assert_checks(branch: &Value, declared: &Value) {
+        for (keyword, value) in branch.as_object().unwrap() {
+            assert!(
+                VALUE_CHECKS.contains(&keyword.as_str()),
+                "{keyword} in a junctor"
+            );
+            if keyword == "properties" {
+                for (name, nested) in value.as_object().unwrap() {
+                    let declared = &declared["properties"][name];
+                    assert!(!declared.is_null(), "undeclared {name} in a junctor");
+                    assert_checks(nested, declared);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_fields_are_structural() {
+        #[derive(Schema)]
+        #[allow(dead_code)]
+        struct Node {
+            name: String,
+            children: Vec<Node>,
+        }
+
+        /// A replica set
+        #[derive(Schema)]
+        #[allow(dead_code)]
+        struct Spec {
+            replicas: Option<u32>,
+            zones: std::collections::BTreeSet<String>,
+            labels: std::collections::HashMap<String, String>,
+            ports: std::collections::HashMap<u32, String>,
+            tree: Node,
+            pause: (),
+        }
+
+        let schema = to_structural_schema::<Spec>();
+        assert_structural(&schema);
+        assert_eq!(
+            schema["required"],
+            json!(["zones", "labels", "ports", "tree", "pause"])
+        );
+        assert_eq!(
+            schema["properties"]["replicas"],
+            json!({ "type": "integer", "format": "int64" })
+        );
+        assert_eq!(schema["properties"]["zones"][LIST_TYPE], "set");
+        assert_eq!(
+            schema["properties"]["labels"]["additionalProperties"],
+            json!({ "type": "string" })
+        );
+        assert_eq!(
+            schema["properties"]["ports"]["items"]["items"],
+            json!({ INT_OR_STRING: true })
+        );
+        assert_eq!(
+            schema["properties"]["tree"]["properties"]["children"]["items"]
+                [PRESERVE_UNKNOWN_FIELDS],
+            true
+        );
+    }
+
+    #[test]
+    fn test_variant_merges_into_one_object() {
+        #[derive(Schema)]
+        #[allow(dead_code)]
+        #[schema(tag = "kind")]
+        enum Backup {
+            Disabled,
+            Daily {
+                hour: u8,
+            },
+            Bucket {
+                bucket: String,
+                prefix: Option<String>,
+            },
+        }
+
+        let schema = to_structural_schema::<Backup>();
+        assert_structural(&schema);
+        assert_eq!(
+            schema,
+            json!({
+                "type": "object",
+                "properties": {
+                    "kind": { "type": "string", "enum": ["Disabled", "Daily", "Bucket"] },
+                    "hour": { "type": "integer", "format": "int32" },
+                    "bucket": { "type": "string" },
+                    "prefix": { "type": "string" }
+                },
+                "required": ["kind"],
+                "oneOf": [
+                    { "properties": { "kind": { "enum": ["Disabled"] } } },
+                    { "required": ["kind", "hour"], "properties": { "kind": { "enum": ["Daily"] } } },
+                    { "required": ["kind", "bucket"], "properties": { "kind": { "enum": ["Bucket"] } } }
+                ]
+            })
+        );
+    }
+
+    #[test]
+    fn test_crd_schema_with_status() {
+        #[derive(Schema)]
+        #[allow(dead_code)]
+        enum Phase {
+            Pending,
+            Ready,
+        }
+
+        #[derive(Schema)]
+        #[allow(dead_code)]
+        struct Spec {
+            image: String,
+        }
+
+        #[derive(Schema)]
+        #[allow(dead_code)]
+        #[schema(untagged)]
+        enum Status {
+            Unknown,
+            Phase { phase: Phase },
+            Message(String),
+        }
+
+        let schema = to_crd_schema_with_status::<Spec, Status>();
+        assert_structural(&schema);
+        assert_eq!(schema["required"], json!(["spec"]));
+        assert_eq!(schema["properties"]["spec"]["required"], json!(["image"]));
+        // Objects mixed with strings can't be typed; null is still allowed
+        assert_eq!(
+            schema["properties"]["status"],
+            json!({ PRESERVE_UNKNOWN_FIELDS: true, "nullable": true })
+        );
+    }
+}
@@ -0,0 +1,244 @@
+//! Model Context Protocol tool definitions
+//!
+//! A [`Tool`] pairs a name with the schema of its arguments (and optionally
+//! its structured result); [`ToolsList`] collects a server's tools into the
+//! `tools/list` result. Schemas are written as JSON Schema draft 2020-12 by
+//! `schema-jsonschema`.
+
+use schema::{Schema, SchemaType, TypeKind};
+use serde_json::{Value, json};
+use std::fmt;
+
+/// Why a tool can't be listed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum McpError {
+    /// MCP requires `inputSchema` to describe an object
+    InputNotAnObject { tool: String },
+    /// MCP requires `outputSchema` to describe an object
+    OutputNotAnObject { tool: String },
+}
+
+impl fmt::Display for McpError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            McpError::InputNotAnObject { tool } => {
+                write!(f, "tool {:?} takes arguments that aren't an object", tool)
+            }
+            McpError::OutputNotAnObject { tool } => {
+                write!(f, "tool {:?} returns a result that isn't an object", tool)
+            }
+        }
+    }
+}
+
+impl std::error::Error for McpError {}
+
+/// One tool a server offers
+#[derive(Debug, Clone)]
+pub struct Tool {
+    pub name: String,
+    /// Human-readable display name
+    pub title: Option<String>,
+    pub description: Option<String>,
+    /// Schema of the tool's arguments
+    pub input: SchemaType,
+    /// Schema of the tool's `structuredContent`, if it returns any
+    pub output: Option<SchemaType>,
+}
+
+impl Tool {
+    /// A tool taking `T` as its arguments, described by `T`'s doc comment
+    pub fn new<T: Schema>(name: &str) -> Self {
+        Self::from_schema(name, T::schema())
+    }
+
+    /// A tool taking arguments of an explicit schema
+    pub fn from_schema(name: &str, input: SchemaType) -> Self {
+        Tool {
+            name: name.to_string(),
+            title: None,
+            description: input.docs(),
+            input,
+            output: None,
+        }
+    }
+
+    pub fn title(mut self, title: &str) -> Self {
+        self.title = Some(title.to_string());
+        self
+    }
+
+    /// Replaces the description taken from the arguments' doc comment
+    pub fn description(mut self, description: &str) -> Self {
+        self.description = Some(description.to_string());
+        self
+    }
+
+    /// Declare the tool's structured result as `T`
+    pub fn output<T: Schema>(self) -> Self {
+        self.output_schema(T::schema())
+    }
+
+    /// Declare the tool's structured result from an explicit schema
+    pub fn output_schema(mut self, output: SchemaType) -> Self {
+        self.output = Some(output);
+        self
+    }
+
+    /// The tool definition as it appears in `tools/list`
+    pub fn to_json(&self) -> Result<Value, McpError> {
+        if !matches!(self.input.kind, TypeKind::Object { .. }) {
+            return Err(McpError::InputNotAnObject {
+                tool: self.name.clone(),
+            });
+        }
+
+        let mut tool = json!({
+            "name": self.name,
+            "inputSchema": schema_jsonschema::schema_type_to_json_schema(&self.input),
+        });
+        if let Some(title) = &self.title {
+            tool["title"] = json!(title);
+        }
+        if let Some(description) = &self.description {
+            tool["description"] = json!(description);
+        }
+        if let Some(output) = &self.output {
+            if !matches!(output.kind, TypeKind::Object { .. }) {
+                return Err(McpError::OutputNotAnObject {
+                    tool: self.name.clone(),
+                });
+            }
+            tool["outputSchema"] = schema_jsonschema::schema_type_to_json_schema(output);
+        }
+        Ok(tool)
+    }
+}
+
+/// Helper to create a single MCP tool definition
+pub fn to_mcp_tool<T: Schema>(name: &str, description: &str) -> Result<Value, McpError> {
+    Tool::new::<T>(name).description(description).to_json()
+}
+
+/// The tools a server lists, in registration order
+#[derive(Debug, Clone, Default)]
+pub struct ToolsList {
+    tools: Vec<Tool>,
+}
+
+impl ToolsList {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a tool taking `T` as its arguments
+    pub fn register<T: Schema>(self, name: &str, description: &str) -> Self {
+        self.tool(Tool::new::<T>(name).description(description))
+    }
+
+    /// Add a fully configured tool
+    ///
+    /// Adding a tool with the same name again replaces the earlier one.
+    pub fn tool(mut self, tool: Tool) -> Self {
+        match self.tools.iter_mut().find(|t| t.name == tool.name) {
+            Some(existing) => *existing = tool,
+            None => self.tools.push(tool),
+        }
+        self
+    }
+
+    pub fn tools(&self) -> &[Tool] {
+        &self.tools
+    }
+
+    /// The `result` of a `tools/list` request: `{"tools": [...]}`
+    pub fn result(&self) -> Result<Value, McpError> {
+        let tools = self
+            .tools
+            .iter()
+            .map(Tool::to_json)
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(json!({ "tools": tools }))
+    }
+
+    /// The full JSON-RPC response to the `tools/list` request with `id`
+    pub fn response(&self, id: Value) -> Result<Value, McpError> {
+        Ok(json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "result": self.result()?,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Search the index
+    #[derive(Schema)]
+    #[allow(dead_code)]
+    struct Search {
+        query: String,
+        limit: Option<u32>,
+    }
+
+    #[derive(Schema)]
+    #[allow(dead_code)]
+    struct Hits {
+        paths: Vec<String>,
+    }
+
+    #[test]
+    fn test_tool_definition() {
+        let tool = Tool::new::<Search>("search")
+            .title("Search")
+            .output::<Hits>()
+            .to_json()
+            .unwrap();
+
+        assert_eq!(tool["name"], "search");
+        assert_eq!(tool["title"], "Search");
+        assert_eq!(tool["description"], "Search the index");
+        assert_eq!(tool["inputSchema"]["type"], "object");
+        assert_eq!(tool["inputSchema"]["required"], json!(["query"]));
+        assert_eq!(tool["outputSchema"]["properties"]["paths"]["type"], "array");
+    }
+
+    #[test]
+    fn test_tools_list_response() {
+        let list = ToolsList::new()
+            .register::<Search>("search", "Find files")
+            .register::<Hits>("open", "Open files")
+            .register::<Search>("search", "Find files by content");
+
+        let response = list.response(json!(1)).unwrap();
+        assert_eq!(response["jsonrpc"], "2.0");
+        assert_eq!(response["id"], 1);
+        let tools = response["result"]["tools"].as_array().unwrap();
+        assert_eq!(tools.len(), 2);
+        assert_eq!(tools[0]["description"], "Find files by content");
+        assert_eq!(tools[1]["name"], "open");
+        assert!(tools[0].get("outputSchema").is_none());
+    }
+
+    #[test]
+    fn test_non_object_schemas_are_rejected() {
+        let err = to_mcp_tool::<Vec<String>>("list", "List things").unwrap_err();
+        assert_eq!(
+            err,
+            McpError::InputNotAnObject {
+                tool: "list".to_string()
+            }
+        );
+
+        let err = Tool::new::<Search>("search")
+            .output::<String>()
+            .to_json()
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "tool \"search\" returns a result that isn't an object"
+        );
+    }
+}
@@ -0,0 +1,784 @@
+//! Schemas for OpenAI's strict Structured Outputs
+//!
+//! Strict mode accepts a subset of JSON Schema: every object is closed with
+//! `additionalProperties: false` and lists all of its properties in
+//! `required`, only a handful of keywords are understood, and the root must
+//! be an object. The schema is generated by `schema-jsonschema` and then
+//! rewritten to fit; each change that alters what the model writes is
+//! reported as a [`Rewrite`], so callers know which values need converting
+//! back before they deserialize.
+
+use schema::{GenerateOptions, Schema, SchemaType};
+use serde_json::{Map, Value, json};
+use std::fmt;
+
+/// Keywords strict mode understands; anything else is removed
+const SUPPORTED: &[&str] = &[
+    "type",
+    "description",
+    "properties",
+    "required",
+    "additionalProperties",
+    "items",
+    "anyOf",
+    "enum",
+    "$ref",
+    "$defs",
+    "pattern",
+    "format",
+    "minimum",
+    "maximum",
+    "exclusiveMinimum",
+    "exclusiveMaximum",
+    "multipleOf",
+    "minItems",
+    "maxItems",
+];
+
+/// Annotations that don't constrain values, dropped without a report
+const ANNOTATIONS: &[&str] = &["title", "default", "deprecated", "readOnly", "writeOnly"];
+
+/// String formats strict mode understands
+const FORMATS: &[&str] = &[
+    "date-time",
+    "time",
+    "date",
+    "duration",
+    "email",
+    "hostname",
+    "ipv4",
+    "ipv6",
+    "uuid",
+];
+
+/// Property a non-object root is wrapped in
+pub const ROOT_PROPERTY: &str = "value";
+
+/// A strict-mode schema and the rewrites it took to get there
+#[derive(Debug, Clone, PartialEq)]
+pub struct StrictSchema {
+    pub schema: Value,
+    pub rewrites: Vec<Rewrite>,
+}
+
+/// A construct that strict mode can't express as-is, and what it became
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rewrite {
+    /// JSON Pointer to the rewritten schema within [`StrictSchema::schema`]
+    pub path: String,
+    pub kind: RewriteKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RewriteKind {
+    /// An optional property is required and accepts `null` instead
+    NullableOptional,
+    /// A map is an array of `{"key", "value"}` entries
+    MapAsEntries,
+    /// A tuple is an object with properties `"0"`, `"1"`, ...
+    TupleAsObject,
+    /// The parts of an `allOf` were merged into a single object
+    MergedAllOf,
+    /// An `allOf` part that isn't a plain object, such as the string,
+    /// union or recursive payload of an internally tagged newtype case, was
+    /// left out of the merged object
+    DroppedAllOfPart,
+    /// An unsupported keyword was removed
+    DroppedKeyword(String),
+    /// The non-object root sits under the [`ROOT_PROPERTY`] property
+    WrappedRoot,
+}
+
+impl fmt::Display for Rewrite {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let path = if self.path.is_empty() {
+            "/"
+        } else {
+            &self.path
+        };
+        write!(f, "{}: ", path)?;
+        match &self.kind {
+            RewriteKind::NullableOptional => write!(f, "optional property made nullable"),
+            RewriteKind::MapAsEntries => write!(f, "map written as an array of entries"),
+            RewriteKind::TupleAsObject => write!(f, "tuple written as an object"),
+            RewriteKind::MergedAllOf => write!(f, "allOf merged into one object"),
+            RewriteKind::DroppedAllOfPart => {
+                write!(f, "allOf part that isn't a plain object removed")
+            }
+            RewriteKind::DroppedKeyword(keyword) => {
+                write!(f, "unsupported keyword `{}` removed", keyword)
+            }
+            RewriteKind::WrappedRoot => {
+                write!(f, "root wrapped in an object under `{}`", ROOT_PROPERTY)
+            }
+        }
+    }
+}
+
+impl StrictSchema {
+    /// A Chat Completions `response_format` requesting this schema
+    pub fn response_format(&self, name: &str) -> Value {
+        json!({
+            "type": "json_schema",
+            "json_schema": {
+                "name": name,
+                "strict": true,
+                "schema": self.schema,
+            }
+        })
+    }
+
+    /// A Chat Completions function tool taking this schema as parameters
+    pub fn function_tool(&self, name: &str, description: &str) -> Value {
+        json!({
+            "type": "function",
+            "function": {
+                "name": name,
+                "description": description,
+                "parameters": self.schema,
+                "strict": true,
+            }
+        })
+    }
+}
+
+/// Convert a Schema to a strict Structured Outputs schema
+pub fn to_strict_schema<T: Schema>() -> StrictSchema {
+    schema_type_to_strict(&T::schema())
+}
+
+/// Convert a Schema to a strict Structured Outputs schema, bounded by `options`
+pub fn to_strict_schema_with_options<T: Schema>(options: &GenerateOptions) -> StrictSchema {
+    schema_type_to_strict(&options.apply(&T::schema()))
+}
+
+/// Convert a SchemaType to a strict Structured Outputs schema
+pub fn schema_type_to_strict(schema: &SchemaType) -> StrictSchema {
+    let Value::Object(mut document) = schema_jsonschema::schema_type_to_json_schema(schema) else {
+        unreachable!("JSON Schema documents are objects")
+    };
+    document.remove("$schema");
+    let definitions = match document.remove("$defs") {
+        Some(Value::Object(definitions)) => definitions,
+        _ => Map::new(),
+    };
+
+    let mut rewriter = Rewriter {
+        definitions: definitions.clone(),
+        rewrites: vec![],
+    };
+    let mut root = Value::Object(document);
+    rewriter.rewrite(&mut root, "");
+    let mut root_rewrites = std::mem::take(&mut rewriter.rewrites);
+
+    let mut strict_definitions = Map::new();
+    for (name, mut definition) in definitions {
+        rewriter.rewrite(&mut definition, &format!("/$defs/{}", escape(&name)));
+        strict_definitions.insert(name, definition);
+    }
+
+    if !is_object(&root) {
+        let recursive = [&root]
+            .into_iter()
+            .chain(strict_definitions.values())
+            .any(|schema| refers_to(schema, "#"));
+        let inner = if recursive {
+            // `#` is about to mean the wrapper, so the root moves to a
+            // definition of its own
+            let name = (1..)
+                .map(|n| match (n, &schema.name) {
+                    (1, Some(name)) => name.clone(),
+                    (1, None) => "Root".to_string(),
+                    (n, name) => format!("{}{n}", name.as_deref().unwrap_or("Root")),
+                })
+                .find(|name| !strict_definitions.contains_key(name))
+                .unwrap();
+            let target = format!("#/$defs/{}", escape(&name));
+            redirect(&mut root, "#", &target);
+            for definition in strict_definitions.values_mut() {
+                redirect(definition, "#", &target);
+            }
+            prefix(&mut root_rewrites, &format!("/$defs/{}", escape(&name)));
+            strict_definitions.insert(name, root);
+            json!({ "$ref": target })
+        } else {
+            prefix(&mut root_rewrites, &format!("/properties/{ROOT_PROPERTY}"));
+            root
+        };
+        root = json!({
+            "type": "object",
+            "properties": { ROOT_PROPERTY: inner },
+            "required": [ROOT_PROPERTY],
+            "additionalProperties": false
+        });
+        root_rewrites.insert(
+            0,
+            Rewrite {
+                path: String::new(),
+                kind: RewriteKind::WrappedRoot,
+            },
+        );
+    }
+
+    if !strict_definitions.is_empty() {
+        root["$defs"] = Value::Object(strict_definitions);
+    }
+    root_rewrites.extend(rewriter.rewrites);
+    StrictSchema {
+        schema: root,
+        rewrites: root_rewrites,
+    }
+}
+
+struct Rewriter {
+    /// Definitions as generated, for resolving `allOf` parts
+    definitions: Map<String, Value>,
+    rewrites: Vec<Rewrite>,
+}
+
+impl Rewriter {
+    fn report(&mut self, path: &str, kind: RewriteKind) {
+        self.rewrites.push(Rewrite {
+            path: path.to_string(),
+            kind,
+        });
+    }
+
+    /// Rewrite `schema` (at `path` in the output) and everything in it
+    fn rewrite(&mut self, schema: &mut Value, path: &str) {
+        let Value::Object(obj) = schema else {
+            return;
+        };
+
+        // Title and description share the one annotation strict mode keeps
+        if let Some(Value::String(title)) = obj.remove("title") {
+            let description = match obj.get("description").and_then(Value::as_str) {
+                Some(description) => format!("{title}\n\n{description}"),
+                None => title,
+            };
+            obj.insert("description".to_string(), json!(description));
+        }
+        if let Some(cases) = obj.remove("oneOf") {
+            obj.insert("anyOf".to_string(), cases);
+        }
+        if let Some(value) = obj.remove("const") {
+            if !obj.contains_key("type")
+                && let Some(ty) = json_type(&value)
+            {
+                obj.insert("type".to_string(), json!(ty));
+            }
+            obj.insert("enum".to_string(), json!([value]));
+        }
+        if let Some(Value::Array(parts)) = obj.remove("allOf") {
+            self.report(path, RewriteKind::MergedAllOf);
+            self.merge_all_of(obj, parts, path);
+        }
+        // Tuples: positional items, or no items at all for `()`
+        if obj.contains_key("prefixItems")
+            || (obj.get("type").and_then(Value::as_str) == Some("array")
+                && !obj.contains_key("items"))
+        {
+            obj.remove("items");
+            let fields = match obj.remove("prefixItems") {
+                Some(Value::Array(fields)) => fields,
+                _ => vec![],
+            };
+            obj.remove("minItems");
+            obj.remove("maxItems");
+            obj.insert("type".to_string(), json!("object"));
+            let names: Vec<String> = (0..fields.len()).map(|i| i.to_string()).collect();
+            obj.insert(
+                "properties".to_string(),
+                Value::Object(names.iter().cloned().zip(fields).collect()),
+            );
+            obj.insert("required".to_string(), json!(names));
+            self.report(path, RewriteKind::TupleAsObject);
+        }
+        if let Some(value) = obj.remove("additionalProperties") {
+            if value.is_object() {
+                let key = obj
+                    .remove("propertyNames")
+                    .unwrap_or_else(|| json!({ "type": "string" }));
+                obj.remove("properties");
+                obj.remove("required");
+                obj.insert("type".to_string(), json!("array"));
+                obj.insert(
+                    "items".to_string(),
+                    json!({
+                        "type": "object",
+                        "properties": { "key": key, "value": value },
+                        "required": ["key", "value"],
+                        "additionalProperties": false
+                    }),
+                );
+                self.report(path, RewriteKind::MapAsEntries);
+            } else {
+                obj.insert("additionalProperties".to_string(), value);
+            }
+        }
+
+        // Children, now that this node's shape is settled
+        if let Some(Value::Object(properties)) = obj.get_mut("properties") {
+            for (name, property) in properties.iter_mut() {
+                self.rewrite(property, &format!("{path}/properties/{}", escape(name)));
+            }
+        }
+        if let Some(items) = obj.get_mut("items") {
+            self.rewrite(items, &format!("{path}/items"));
+        }
+        if let Some(Value::Array(cases)) = obj.get_mut("anyOf") {
+            for (index, case) in cases.iter_mut().enumerate() {
+                self.rewrite(case, &format!("{path}/anyOf/{index}"));
+            }
+        }
+
+        if obj.get("type").and_then(Value::as_str) == Some("object") {
+            self.close_object(obj, path);
+        }
+        if let Some(format) = obj.get("format").and_then(Value::as_str)
+            && !FORMATS.contains(&format)
+        {
+            obj.remove("format");
+            self.report(path, RewriteKind::DroppedKeyword("format".to_string()));
+        }
+        let unsupported: Vec<String> = obj
+            .keys()
+            .filter(|key| !SUPPORTED.contains(&key.as_str()))
+            .cloned()
+            .collect();
+        for key in unsupported {
+            obj.remove(&key);
+            if !ANNOTATIONS.contains(&key.as_str()) && !key.starts_with("x-") {
+                self.report(path, RewriteKind::DroppedKeyword(key));
+            }
+        }
+    }
+
+    /// Require every property, letting the optional ones be `null`, and
+    /// refuse undeclared ones
+    fn close_object(&mut self, obj: &mut Map<String, Value>, path: &str) {
+        let required: Vec<String> = obj
+            .get("required")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+            .filter_map(|name| name.as_str().map(str::to_string))
+            .collect();
+        let properties = obj
+            .entry("properties")
+            .or_insert_with(|| json!({}))
+            .as_object_mut()
+            .expect("properties is an object");
+        let mut names = vec![];
+        for (name, property) in properties.iter_mut() {
+            if !required.contains(name) {
+                make_nullable(property);
+                self.report(
+                    &format!("{path}/properties/{}", escape(name)),
+                    RewriteKind::NullableOptional,
+                );
+            }
+            names.push(name.clone());
+        }
+        obj.insert("required".to_string(), json!(names));
+        obj.insert("additionalProperties".to_string(), json!(false));
+    }
+
+    /// Fold `allOf` parts (object payloads or `$ref`s to them) into `obj`,
+    /// reporting any part that has no properties to fold
+    fn merge_all_of(&mut self, obj: &mut Map<String, Value>, parts: Vec<Value>, path: &str) {
+        for part in parts {
+            let part = match part.get("$ref").and_then(Value::as_str) {
+                Some(target) => target
+                    .strip_prefix("#/$defs/")
+                    .and_then(|name| self.definitions.get(&unescape(name)))
+                    .cloned()
+                    .unwrap_or(Value::Null),
+                None => part,
+            };
+            let Some(part) = part.as_object().filter(|part| is_plain_object(part)) else {
+                self.report(path, RewriteKind::DroppedAllOfPart);
+                continue;
+            };
+            obj.insert("type".to_string(), json!("object"));
+            if let Some(Value::Object(properties)) = part.get("properties") {
+                let merged = obj
+                    .entry("properties")
+                    .or_insert_with(|| json!({}))
+                    .as_object_mut()
+                    .expect("properties is an object");
+                for (name, property) in properties {
+                    merged.insert(name.clone(), property.clone());
+                }
+            }
+            if let Some(Value::Array(required)) = part.get("required") {
+                let merged = obj
+                    .entry("required")
+                    .or_insert_with(|| json!([]))
+                    .as_array_mut()
+                    .expect("required is an array");
+                for name in required {
+                    if !merged.contains(name) {
+                        merged.push(name.clone());
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Let `schema` also accept `null`
+fn make_nullable(schema: &mut Value) {
+    let Value::Object(obj) = schema else {
+        return;
+    };
+    let simple = !obj.contains_key("$ref") && !obj.contains_key("anyOf");
+    match obj.get("type").cloned() {
+        Some(Value::String(ty)) if simple && ty == "null" => {}
+        Some(Value::String(ty)) if simple => {
+            obj.insert("type".to_string(), json!([ty, "null"]));
+            if let Some(Value::Array(values)) = obj.get_mut("enum") {
+                values.push(Value::Null);
+            }
+        }
+        _ => {
+            let description = obj.remove("description");
+            let inner = std::mem::take(obj);
+            obj.insert(
+                "anyOf".to_string(),
+                json!([Value::Object(inner), { "type": "null" }]),
+            );
+            if let Some(description) = description {
+                obj.insert("description".to_string(), description);
+            }
+        }
+    }
+}
+
+/// An object schema made of properties alone, so merging them loses nothing
+fn is_plain_object(schema: &Map<String, Value>) -> bool {
+    schema.get("type").and_then(Value::as_str) == Some("object")
+        && ["anyOf", "oneOf", "allOf", "$ref"]
+            .iter()
+            .all(|keyword| !schema.contains_key(*keyword))
+        && !schema
+            .get("additionalProperties")
+            .is_some_and(Value::is_object)
+}
+
+fn is_object(schema: &Value) -> bool {
+    schema.get("type").and_then(Value::as_str) == Some("object")
+        && schema.get("anyOf").is_none()
+        && schema.get("$ref").is_none()
+}
+
+/// Whether any `$ref` inside `schema` points at `target`
+fn refers_to(schema: &Value, target: &str) -> bool {
+    match schema {
+        Value::Object(obj) => obj
+            .iter()
+            .any(|(key, value)| (key == "$ref" && value == target) || refers_to(value, target)),
+        Value::Array(items) => items.iter().any(|item| refers_to(item, target)),
+        _ => false,
+    }
+}
+
+/// Point every `$ref` to `from` at `to` instead
+fn redirect(schema: &mut Value, from: &str, to: &str) {
+    match schema {
+        Value::Object(obj) => {
+            for (key, value) in obj.iter_mut() {
+                if key == "$ref" && value == from {
+                    *value = json!(to);
+                } else {
+                    redirect(value, from, to);
+                }
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(|item| redirect(item, from, to)),
+        _ => {}
+    }
+}
+
+fn prefix(rewrites: &mut [Rewrite], prefix: &str) {
+    for rewrite in rewrites {
+        rewrite.path = format!("{prefix}{}", rewrite.path);
+    }
+}
+
+fn json_type(value: &Value) -> Option<&'static str> {
+    match value {
+        Value::String(_) => Some("string"),
+        Value::Bool(_) => Some("boolean"),
+        Value::Number(n) if n.is_i64() || n.is_u64() => Some("integer"),
+        Value::Number(_) => Some("number"),
+        Value::Null => Some("null"),
+        _ => None,
+    }
+}
+
+fn escape(segment: &str) -> String {
+    segment.replace('~', "~0").replace('/', "~1")
+}
+
+fn unescape(segment: &str) -> String {
+    segment.replace("~1", "/").replace("~0", "~")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn kinds(strict: &StrictSchema) -> Vec<(&str, &RewriteKind)> {
+        strict
+            .rewrites
+            .iter()
+            .map(|rewrite| (rewrite.path.as_str(), &rewrite.kind))
+            .collect()
+    }
+
+    #[test]
+    fn test_optional_fields_become_nullable() {
+        /// A user profile
+        #[derive(Schema)]
+        #[allow(dead_code)]
+        struct Profile {
+            name: String,
+            age: Option<u32>,
+            #[schema(min_length = 1)]
+            bio: String,
+        }
+
+        let strict = to_strict_schema::<Profile>();
+        assert_eq!(
+            strict.schema,
+            json!({
+                "type": "object",
+                "description": "A user profile",
+                "properties": {
+                    "name": { "type": "string" },
+                    "age": { "type": ["integer", "null"], "minimum": 0, "maximum": 4294967295u64 },
+                    "bio": { "type": "string" }
+                },
+                "required": ["age", "bio", "name"],
+                "additionalProperties": false
+            })
+        );
+        assert_eq!(
+            kinds(&strict),
+            [
+                (
+                    "/properties/bio",
+                    &RewriteKind::DroppedKeyword("minLength".to_string())
+                ),
+                ("/properties/age", &RewriteKind::NullableOptional),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_nested_types_stay_in_defs() {
+        #[derive(Schema)]
+        #[allow(dead_code)]
+        struct Address {
+            city: String,
+            zip: Option<String>,
+        }
+
+        #[derive(Schema)]
+        #[allow(dead_code)]
+        struct Order {
+            shipping: Address,
+            billing: Option<Address>,
+        }
+
+        let strict = to_strict_schema::<Order>();
+        let schema = &strict.schema;
+        assert_eq!(schema["properties"]["shipping"]["$ref"], "#/$defs/Address");
+        assert_eq!(
+            schema["properties"]["billing"],
+            json!({ "anyOf": [{ "$ref": "#/$defs/Address" }, { "type": "null" }] })
+        );
+        assert_eq!(schema["$defs"]["Address"]["additionalProperties"], false);
+        assert_eq!(
+            schema["$defs"]["Address"]["properties"]["zip"]["type"],
+            json!(["string", "null"])
+        );
+        assert!(
+            strict
+                .rewrites
+                .iter()
+                .any(|rewrite| rewrite.path == "/$defs/Address/properties/zip")
+        );
+    }
+
+    #[test]
+    fn test_maps_and_tuples() {
+        #[derive(Schema)]
+        #[allow(dead_code)]
+        struct Stats {
+            counts: HashMap<String, u32>,
+            point: (f64, f64),
+        }
+
+        let strict = to_strict_schema::<Stats>();
+        let props = &strict.schema["properties"];
+        assert_eq!(props["counts"]["type"], "array");
+        assert_eq!(
+            props["counts"]["items"]["required"],
+            json!(["key", "value"])
+        );
+        assert_eq!(
+            props["counts"]["items"]["properties"]["value"]["type"],
+            "integer"
+        );
+        assert_eq!(props["point"]["type"], "object");
+        assert_eq!(props["point"]["required"], json!(["0", "1"]));
+        assert_eq!(props["point"]["additionalProperties"], false);
+        assert_eq!(
+            kinds(&strict),
+            [
+                ("/properties/counts", &RewriteKind::MapAsEntries),
+                ("/properties/point", &RewriteKind::TupleAsObject),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_variants_use_any_of() {
+        #[derive(Schema)]
+        #[allow(dead_code)]
+        #[schema(tag = "kind")]
+        enum Action {
+            Stop,
+            Move { x: i32, y: Option<i32> },
+        }
+
+        #[derive(Schema)]
+        #[allow(dead_code)]
+        struct Plan {
+            action: Action,
+        }
+
+        let strict = to_strict_schema::<Plan>();
+        let action = &strict.schema["$defs"]["Action"];
+        assert!(action.get("oneOf").is_none());
+        assert_eq!(
+            action["anyOf"][0],
+            json!({
+                "type": "object",
+                "properties": { "kind": { "type": "string", "enum": ["Stop"] } },
+                "required": ["kind"],
+                "additionalProperties": false
+            })
+        );
+        assert_eq!(action["anyOf"][1]["required"], json!(["kind", "x", "y"]));
+        assert_eq!(
+            action["anyOf"][1]["properties"]["y"]["type"],
+            json!(["integer", "null"])
+        );
+    }
+
+    #[test]
+    fn test_internally_tagged_newtype_payloads() {
+        #[derive(Schema)]
+        #[allow(dead_code)]
+        struct Point {
+            x: f64,
+        }
+
+        #[derive(Schema)]
+        #[allow(dead_code)]
+        #[schema(tag = "kind")]
+        enum Step {
+            Jump(Point),
+            Note(String),
+            Repeat(Box<Step>),
+        }
+
+        let strict = to_strict_schema::<Step>();
+        let cases = &strict.schema["properties"]["value"]["anyOf"];
+        assert_eq!(cases[0]["required"], json!(["kind", "x"]));
+        assert_eq!(cases[1]["required"], json!(["kind"]));
+        assert_eq!(
+            kinds(&strict),
+            [
+                ("", &RewriteKind::WrappedRoot),
+                ("/properties/value/anyOf/0", &RewriteKind::MergedAllOf),
+                ("/properties/value/anyOf/1", &RewriteKind::MergedAllOf),
+                ("/properties/value/anyOf/1", &RewriteKind::DroppedAllOfPart),
+                ("/properties/value/anyOf/2", &RewriteKind::MergedAllOf),
+                ("/properties/value/anyOf/2", &RewriteKind::DroppedAllOfPart),
+            ]
+        );
+        assert_eq!(
+            strict.rewrites[3].to_string(),
+            "/properties/value/anyOf/1: allOf part that isn't a plain object removed"
+        );
+    }
+
+    #[test]
+    fn test_non_object_root_is_wrapped() {
+        #[derive(Schema)]
+        #[allow(dead_code)]
+        enum Mood {
+            Happy,
+            Sad,
+        }
+
+        let strict = to_strict_schema::<Mood>();
+        assert_eq!(
+            strict.schema,
+            json!({
+                "type": "object",
+                "properties": {
+                    "value": { "type": "string", "enum": ["Happy", "Sad"] }
+                },
+                "required": ["value"],
+                "additionalProperties": false
+            })
+        );
+        assert_eq!(kinds(&strict), [("", &RewriteKind::WrappedRoot)]);
+        assert_eq!(
+            strict.rewrites[0].to_string(),
+            "/: root wrapped in an object under `value`"
+        );
+    }
+
+    #[test]
+    fn test_recursive_root_moves_to_defs() {
+        #[derive(Schema)]
+        #[allow(dead_code)]
+        enum Tree {
+            Leaf,
+            Node(Vec<Tree>),
+        }
+
+        let strict = to_strict_schema::<Tree>();
+        let schema = &strict.schema;
+        assert_eq!(schema["properties"]["value"]["$ref"], "#/$defs/Tree");
+        let node = &schema["$defs"]["Tree"]["anyOf"][1];
+        assert_eq!(node["properties"]["data"]["items"]["$ref"], "#/$defs/Tree");
+    }
+
+    #[test]
+    fn test_request_wrappers() {
+        #[derive(Schema)]
+        #[allow(dead_code)]
+        struct Answer {
+            text: String,
+        }
+
+        let strict = to_strict_schema::<Answer>();
+        let format = strict.response_format("answer");
+        assert_eq!(format["type"], "json_schema");
+        assert_eq!(format["json_schema"]["strict"], true);
+        assert_eq!(format["json_schema"]["schema"], strict.schema);
+
+        let tool = strict.function_tool("answer", "Give the answer");
+        assert_eq!(tool["function"]["name"], "answer");
+        assert_eq!(tool["function"]["strict"], true);
+        assert_eq!(tool["function"]["parameters"], strict.schema);
+    }
+}
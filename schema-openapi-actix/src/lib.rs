@@ -0,0 +1,257 @@
+//! OpenAPI paths from actix-web services
+//!
+//! An [`ApiService`] collects handlers, each added with
+//! [`ApiService::api_route`] alongside the [`Operation`] that documents it.
+//! The handler is routed by that operation's method and path, so the
+//! document lists exactly the routes the app serves:
+//!
+//! ```
+//! # use schema::Schema;
+//! # use schema_openapi::{OpenApiBuilder, Operation};
+//! # use schema_openapi_actix::ApiService;
+//! # #[derive(Schema)]
+//! # struct User { name: String }
+//! # #[derive(Schema)]
+//! # struct NewUser { name: String }
+//! # async fn get_user() -> &'static str { "ada" }
+//! # async fn create_user() -> &'static str { "created" }
+//! let api = ApiService::new()
+//!     .api_route(
+//!         Operation::get("/users/{id}").response::<User>("200", "The user"),
+//!         get_user,
+//!     )
+//!     .api_route(
+//!         Operation::post("/users")
+//!             .request::<NewUser>()
+//!             .response::<User>("201", "Created"),
+//!         create_user,
+//!     );
+//! let document = api.openapi(OpenApiBuilder::new("Users", "1.0.0")).build();
+//! // Cloned into `HttpServer::new`'s factory, which runs once per worker
+//! let app = actix_web::App::new().configure(|cfg| api.configure(cfg));
+//! ```
+//!
+//! Path segments with a pattern, such as `{id:\d+}` or `{tail:.*}`, are
+//! documented as the plain parameter `{id}` or `{tail}`.
+
+use actix_web::dev::Handler;
+use actix_web::http::Method as HttpMethod;
+use actix_web::web::{self, ServiceConfig};
+use actix_web::{FromRequest, Responder};
+use schema_openapi::{Method, OpenApiBuilder, Operation};
+use std::sync::Arc;
+
+/// Registers one route on an app
+type Register = Arc<dyn Fn(&mut ServiceConfig) + Send + Sync>;
+
+/// Documented actix-web routes, applied to an app with
+/// [`configure`](ApiService::configure)
+///
+/// Cloning is cheap, so one service can be moved into `HttpServer::new`'s
+/// factory and configure every worker's app.
+#[derive(Clone, Default)]
+pub struct ApiService {
+    routes: Vec<Register>,
+    operations: Vec<Operation>,
+}
+
+impl ApiService {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Route `operation`'s method and path to `handler`
+    pub fn api_route<F, Args>(mut self, operation: Operation, handler: F) -> Self
+    where
+        F: Handler<Args> + Send + Sync,
+        Args: FromRequest + 'static,
+        F::Output: Responder + 'static,
+    {
+        let path = operation.path.clone();
+        let method = http_method(operation.method);
+        self.routes.push(Arc::new(move |cfg: &mut ServiceConfig| {
+            cfg.route(&path, web::method(method.clone()).to(handler.clone()));
+        }));
+        self.operations.push(operation);
+        self
+    }
+
+    /// Route `path` without documenting it, for health checks and the like
+    pub fn route<F, Args>(mut self, path: &str, method: Method, handler: F) -> Self
+    where
+        F: Handler<Args> + Send + Sync,
+        Args: FromRequest + 'static,
+        F::Output: Responder + 'static,
+    {
+        let path = path.to_string();
+        let method = http_method(method);
+        self.routes.push(Arc::new(move |cfg: &mut ServiceConfig| {
+            cfg.route(&path, web::method(method.clone()).to(handler.clone()));
+        }));
+        self
+    }
+
+    /// Serve `api` in a scope under `prefix`, documenting its routes there
+    pub fn scope(mut self, prefix: &str, api: ApiService) -> Self {
+        let scope_prefix = prefix.to_string();
+        let routes = api.routes;
+        self.routes.push(Arc::new(move |cfg: &mut ServiceConfig| {
+            let routes = routes.clone();
+            cfg.service(web::scope(&scope_prefix).configure(move |cfg| {
+                for register in &routes {
+                    register(cfg);
+                }
+            }));
+        }));
+        self.operations
+            .extend(api.operations.into_iter().map(|mut operation| {
+                operation.path = format!("{}{}", prefix, operation.path);
+                operation
+            }));
+        self
+    }
+
+    /// Add every route to an app, as `App::configure(|cfg| api.configure(cfg))`
+    pub fn configure(&self, cfg: &mut ServiceConfig) {
+        for register in &self.routes {
+            register(cfg);
+        }
+    }
+
+    /// The documented routes, in the order they were added
+    pub fn operations(&self) -> &[Operation] {
+        &self.operations
+    }
+
+    /// `builder` with an operation for every documented route
+    pub fn openapi(&self, builder: OpenApiBuilder) -> OpenApiBuilder {
+        self.operations.iter().fold(builder, |builder, operation| {
+            let mut operation = operation.clone();
+            operation.path = openapi_path(&operation.path);
+            builder.operation(operation)
+        })
+    }
+}
+
+/// The actix-web method for an operation's method
+fn http_method(method: Method) -> HttpMethod {
+    match method {
+        Method::Get => HttpMethod::GET,
+        Method::Put => HttpMethod::PUT,
+        Method::Post => HttpMethod::POST,
+        Method::Delete => HttpMethod::DELETE,
+        Method::Options => HttpMethod::OPTIONS,
+        Method::Head => HttpMethod::HEAD,
+        Method::Patch => HttpMethod::PATCH,
+        Method::Trace => HttpMethod::TRACE,
+    }
+}
+
+/// An actix-web path as an OpenAPI path template, dropping each segment's
+/// `:pattern`
+fn openapi_path(path: &str) -> String {
+    let mut out = String::with_capacity(path.len());
+    let mut rest = path;
+    while let Some(open) = rest.find('{') {
+        out.push_str(&rest[..=open]);
+        rest = &rest[open + 1..];
+        let close = closing_brace(rest);
+        let segment = &rest[..close];
+        out.push_str(segment.split_once(':').map_or(segment, |(name, _)| name));
+        rest = &rest[close..];
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Where the `{...}` segment `rest` starts inside ends, allowing for braces
+/// in its pattern such as `{id:\d{4}}`
+fn closing_brace(rest: &str) -> usize {
+    let mut depth = 0;
+    for (i, c) in rest.char_indices() {
+        match c {
+            '{' => depth += 1,
+            '}' if depth == 0 => return i,
+            '}' => depth -= 1,
+            _ => {}
+        }
+    }
+    rest.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use schema::Schema;
+    use schema_openapi::Response;
+    use serde_json::json;
+
+    #[derive(Schema)]
+    #[allow(dead_code)]
+    struct Order {
+        id: u32,
+    }
+
+    async fn get_order() -> &'static str {
+        "order"
+    }
+
+    async fn cancel_order() -> &'static str {
+        "cancelled"
+    }
+
+    #[test]
+    fn test_paths_from_routes() {
+        let orders = ApiService::new()
+            .api_route(
+                Operation::get(r"/{id:\d+}").response::<Order>("200", "The order"),
+                get_order,
+            )
+            .api_route(
+                Operation::delete(r"/{id:\d+}").respond(Response::empty("204", "Cancelled")),
+                cancel_order,
+            );
+        let api =
+            ApiService::new()
+                .scope("/orders", orders)
+                .route("/health", Method::Get, get_order);
+        let _app = actix_web::App::new().configure(|cfg| api.configure(cfg));
+
+        let document = api
+            .openapi(OpenApiBuilder::new("Orders", "1.0.0"))
+            .build()
+            .unwrap();
+        assert_eq!(
+            document["paths"],
+            json!({
+                "/orders/{id}": {
+                    "get": {
+                        "responses": {
+                            "200": {
+                                "description": "The order",
+                                "content": {
+                                    "application/json": {
+                                        "schema": { "$ref": "#/components/schemas/Order" },
+                                    },
+                                },
+                            },
+                        },
+                    },
+                    "delete": {
+                        "responses": { "204": { "description": "Cancelled" } },
+                    },
+                },
+            })
+        );
+    }
+
+    #[test]
+    fn test_openapi_path() {
+        assert_eq!(openapi_path("/files/{tail:.*}"), "/files/{tail}");
+        assert_eq!(
+            openapi_path(r"/years/{year:\d{4}}/{slug}"),
+            "/years/{year}/{slug}"
+        );
+        assert_eq!(openapi_path("/plain"), "/plain");
+    }
+}
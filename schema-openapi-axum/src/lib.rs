@@ -0,0 +1,262 @@
+//! OpenAPI paths from axum routers
+//!
+//! An [`ApiRouter`] wraps an axum [`Router`]. Each handler added with
+//! [`ApiRouter::api_route`] is routed by its [`Operation`]'s method and path,
+//! so the document the router produces lists exactly the routes it serves:
+//!
+//! ```
+//! # use schema::Schema;
+//! # use schema_openapi::{OpenApiBuilder, Operation};
+//! # use schema_openapi_axum::ApiRouter;
+//! # #[derive(Schema)]
+//! # struct User { name: String }
+//! # #[derive(Schema)]
+//! # struct NewUser { name: String }
+//! # async fn get_user() {}
+//! # async fn create_user() {}
+//! let api = ApiRouter::<()>::new()
+//!     .api_route(
+//!         Operation::get("/users/{id}").response::<User>("200", "The user"),
+//!         get_user,
+//!     )
+//!     .api_route(
+//!         Operation::post("/users")
+//!             .request::<NewUser>()
+//!             .response::<User>("201", "Created"),
+//!         create_user,
+//!     );
+//! let document = api.openapi(OpenApiBuilder::new("Users", "1.0.0")).build();
+//! let router: axum::Router = api.into_router();
+//! ```
+//!
+//! axum 0.8 writes path parameters as `{id}`, as OpenAPI does; a wildcard
+//! `{*rest}` is documented as the parameter `{rest}`.
+
+use axum::Router;
+use axum::handler::Handler;
+use axum::routing::{MethodFilter, MethodRouter, on};
+use schema_openapi::{Method, OpenApiBuilder, Operation};
+
+/// An axum router that records an [`Operation`] for each documented route
+pub struct ApiRouter<S = ()> {
+    router: Router<S>,
+    operations: Vec<Operation>,
+}
+
+impl<S> Default for ApiRouter<S>
+where
+    S: Clone + Send + Sync + 'static,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<S> ApiRouter<S>
+where
+    S: Clone + Send + Sync + 'static,
+{
+    pub fn new() -> Self {
+        ApiRouter {
+            router: Router::new(),
+            operations: vec![],
+        }
+    }
+
+    /// Route `operation`'s method and path to `handler`
+    ///
+    /// Several operations can share a path with different methods; the same
+    /// method and path twice panics, as axum's `route` does.
+    pub fn api_route<H, T>(mut self, operation: Operation, handler: H) -> Self
+    where
+        H: Handler<T, S>,
+        T: 'static,
+    {
+        self.router = self.router.route(
+            &operation.path,
+            on(method_filter(operation.method), handler),
+        );
+        self.operations.push(operation);
+        self
+    }
+
+    /// Route `path` without documenting it, for health checks and the like
+    pub fn route(mut self, path: &str, method_router: MethodRouter<S>) -> Self {
+        self.router = self.router.route(path, method_router);
+        self
+    }
+
+    /// Serve `api` under `prefix`, documenting its routes there
+    pub fn nest(mut self, prefix: &str, api: ApiRouter<S>) -> Self {
+        self.router = self.router.nest(prefix, api.router);
+        self.operations
+            .extend(api.operations.into_iter().map(|mut operation| {
+                operation.path = nested_path(prefix, &operation.path);
+                operation
+            }));
+        self
+    }
+
+    /// Serve `api`'s routes alongside these
+    pub fn merge(mut self, api: ApiRouter<S>) -> Self {
+        self.router = self.router.merge(api.router);
+        self.operations.extend(api.operations);
+        self
+    }
+
+    /// Provide the handlers' state, as axum's `with_state`
+    pub fn with_state<S2>(self, state: S) -> ApiRouter<S2> {
+        ApiRouter {
+            router: self.router.with_state(state),
+            operations: self.operations,
+        }
+    }
+
+    /// The documented routes, in the order they were added
+    pub fn operations(&self) -> &[Operation] {
+        &self.operations
+    }
+
+    /// `builder` with an operation for every documented route
+    pub fn openapi(&self, builder: OpenApiBuilder) -> OpenApiBuilder {
+        self.operations.iter().fold(builder, |builder, operation| {
+            let mut operation = operation.clone();
+            operation.path = openapi_path(&operation.path);
+            builder.operation(operation)
+        })
+    }
+
+    /// The axum router, to serve or to add layers to
+    pub fn into_router(self) -> Router<S> {
+        self.router
+    }
+}
+
+/// The axum filter for an operation's method
+fn method_filter(method: Method) -> MethodFilter {
+    match method {
+        Method::Get => MethodFilter::GET,
+        Method::Put => MethodFilter::PUT,
+        Method::Post => MethodFilter::POST,
+        Method::Delete => MethodFilter::DELETE,
+        Method::Options => MethodFilter::OPTIONS,
+        Method::Head => MethodFilter::HEAD,
+        Method::Patch => MethodFilter::PATCH,
+        Method::Trace => MethodFilter::TRACE,
+    }
+}
+
+/// `path` nested under `prefix`, joined the way axum joins them: a nested
+/// `/` is the prefix itself
+fn nested_path(prefix: &str, path: &str) -> String {
+    if prefix.ends_with('/') {
+        format!("{}{}", prefix, path.trim_start_matches('/'))
+    } else if path == "/" {
+        prefix.to_string()
+    } else {
+        format!("{}{}", prefix, path)
+    }
+}
+
+/// An axum path as an OpenAPI path template: `{*rest}` becomes `{rest}`
+fn openapi_path(path: &str) -> String {
+    path.replace("{*", "{")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use schema::Schema;
+    use serde_json::json;
+
+    #[derive(Schema)]
+    #[allow(dead_code)]
+    struct User {
+        name: String,
+    }
+
+    async fn get_user() -> &'static str {
+        "ada"
+    }
+
+    async fn create_user() -> &'static str {
+        "created"
+    }
+
+    async fn health() {}
+
+    #[test]
+    fn test_paths_from_routes() {
+        let users = ApiRouter::new()
+            .api_route(
+                Operation::get("/{id}").response::<User>("200", "The user"),
+                get_user,
+            )
+            .api_route(
+                Operation::post("/")
+                    .request::<User>()
+                    .response::<User>("201", "Created"),
+                create_user,
+            );
+        let api = ApiRouter::new()
+            .nest("/users", users)
+            .route("/health", axum::routing::get(health));
+
+        let document = api
+            .openapi(OpenApiBuilder::new("Users", "1.0.0"))
+            .build()
+            .unwrap();
+        let user = json!({ "$ref": "#/components/schemas/User" });
+        assert_eq!(
+            document["paths"],
+            json!({
+                "/users/{id}": {
+                    "get": {
+                        "responses": {
+                            "200": {
+                                "description": "The user",
+                                "content": { "application/json": { "schema": user } },
+                            },
+                        },
+                    },
+                },
+                "/users": {
+                    "post": {
+                        "requestBody": {
+                            "required": true,
+                            "content": { "application/json": { "schema": user } },
+                        },
+                        "responses": {
+                            "201": {
+                                "description": "Created",
+                                "content": { "application/json": { "schema": user } },
+                            },
+                        },
+                    },
+                },
+            })
+        );
+        let _router: Router = api.into_router();
+    }
+
+    #[test]
+    fn test_shared_path_and_wildcard() {
+        let api = ApiRouter::<()>::new()
+            .api_route(
+                Operation::get("/files/{*path}").response::<String>("200", "The file"),
+                get_user,
+            )
+            .api_route(
+                Operation::delete("/files/{*path}")
+                    .respond(schema_openapi::Response::empty("204", "Deleted")),
+                create_user,
+            );
+        assert_eq!(api.operations().len(), 2);
+        let document = api
+            .openapi(OpenApiBuilder::new("Files", "1.0.0"))
+            .build()
+            .unwrap();
+        let item = &document["paths"]["/files/{path}"];
+        assert!(item["get"].is_object() && item["delete"].is_object());
+    }
+}
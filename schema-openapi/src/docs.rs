@@ -0,0 +1,121 @@
+//! Interactive HTML documentation pages for OpenAPI documents
+//!
+//! The document is embedded in the page, so serving it needs no extra
+//! endpoint. The UI's script and stylesheet aren't bundled: the page loads
+//! them from the `asset_base` it is given, such as the route a service
+//! serves its copy of `swagger-ui-dist` or `redoc`'s `bundles` from, so it
+//! works without network access. A CDN URL works too, for services that
+//! can reach one.
+
+use serde_json::Value;
+
+/// Which documentation UI to render
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DocsUi {
+    SwaggerUi,
+    Redoc,
+}
+
+/// A documentation page with the document embedded
+#[derive(Debug, Clone)]
+pub struct DocsPage {
+    ui: DocsUi,
+    title: Option<String>,
+    asset_base: String,
+}
+
+impl DocsPage {
+    /// A page loading the UI's files from `asset_base`: the directory
+    /// holding `swagger-ui-bundle.js` and `swagger-ui.css`, or
+    /// `redoc.standalone.js`
+    pub fn new(ui: DocsUi, asset_base: &str) -> Self {
+        DocsPage {
+            ui,
+            title: None,
+            asset_base: asset_base.trim_end_matches('/').to_string(),
+        }
+    }
+
+    /// Page title; defaults to the document's `info.title`
+    pub fn title(mut self, title: &str) -> Self {
+        self.title = Some(title.to_string());
+        self
+    }
+
+    /// Render the page with `document` embedded
+    pub fn render(&self, document: &Value) -> String {
+        let title = self
+            .title
+            .as_deref()
+            .or_else(|| document["info"]["title"].as_str())
+            .unwrap_or("API documentation");
+        let title = escape_html(title);
+        let base = &self.asset_base;
+        let spec = embed_json(document);
+
+        match self.ui {
+            DocsUi::SwaggerUi => format!(
+                r##"<!DOCTYPE html>
+<html lang="en">
+<head>
+<meta charset="utf-8">
+<meta name="viewport" content="width=device-width, initial-scale=1">
+<title>{title}</title>
+<link rel="stylesheet" href="{base}/swagger-ui.css">
+</head>
+<body>
+<div id="swagger-ui"></div>
+<script src="{base}/swagger-ui-bundle.js"></script>
+<script>
+window.ui = SwaggerUIBundle({{ spec: {spec}, dom_id: "#swagger-ui" }});
+</script>
+</body>
+</html>
+"##
+            ),
+            DocsUi::Redoc => format!(
+                r##"<!DOCTYPE html>
+<html lang="en">
+<head>
+<meta charset="utf-8">
+<meta name="viewport" content="width=device-width, initial-scale=1">
+<title>{title}</title>
+</head>
+<body>
+<div id="redoc"></div>
+<script src="{base}/redoc.standalone.js"></script>
+<script>
+Redoc.init({spec}, {{}}, document.getElementById("redoc"));
+</script>
+</body>
+</html>
+"##
+            ),
+        }
+    }
+}
+
+/// Swagger UI page for `document`, with its files under `asset_base`
+pub fn swagger_ui_html(document: &Value, asset_base: &str) -> String {
+    DocsPage::new(DocsUi::SwaggerUi, asset_base).render(document)
+}
+
+/// ReDoc page for `document`, with its files under `asset_base`
+pub fn redoc_html(document: &Value, asset_base: &str) -> String {
+    DocsPage::new(DocsUi::Redoc, asset_base).render(document)
+}
+
+/// Serialize JSON for inclusion in a `<script>` element
+///
+/// `<` only occurs inside JSON strings, where `<` is an equivalent
+/// escape; this keeps `</script>` in descriptions from closing the element.
+fn embed_json(value: &Value) -> String {
+    value.to_string().replace('<', "\\u003c")
+}
+
+fn escape_html(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
@@ -0,0 +1,903 @@
+//! Complete OpenAPI documents
+//!
+//! An [`OpenApiBuilder`] collects the API's [`Server`]s, [`Tag`]s and
+//! [`Operation`]s, whose request bodies and [`Response`]s are typed by
+//! schemas. Every named type they use is defined once under
+//! `components/schemas` and referenced from the operations.
+
+use crate::{
+    ComponentsError, OpenApiVersion, OutputOrder, Parameter, ParameterError, ParameterLocation,
+    Registry, SecurityRequirement, SecurityScheme, named_schema,
+};
+use schema::{GenerateOptions, Schema, SchemaType};
+use serde_json::{Map, Value, json};
+use std::fmt;
+
+/// Why a document can't be built
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OpenApiError {
+    /// Two different types share a component name
+    Components(ComponentsError),
+    /// An operation declares no responses, which OpenAPI requires
+    NoResponses { operation: String },
+    /// Two operations share an `operationId`, which must be unique
+    DuplicateOperationId { operation_id: String },
+    /// A security requirement names a scheme the document doesn't define
+    UnknownSecurityScheme { name: String },
+}
+
+impl fmt::Display for OpenApiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OpenApiError::Components(e) => e.fmt(f),
+            OpenApiError::NoResponses { operation } => {
+                write!(f, "operation {} declares no responses", operation)
+            }
+            OpenApiError::DuplicateOperationId { operation_id } => {
+                write!(f, "operationId {:?} is used more than once", operation_id)
+            }
+            OpenApiError::UnknownSecurityScheme { name } => {
+                write!(f, "security scheme {:?} is required but not defined", name)
+            }
+        }
+    }
+}
+
+impl std::error::Error for OpenApiError {}
+
+impl From<ComponentsError> for OpenApiError {
+    fn from(e: ComponentsError) -> Self {
+        OpenApiError::Components(e)
+    }
+}
+
+/// A server the API is served from
+#[derive(Debug, Clone)]
+pub struct Server {
+    /// Such as `https://api.example.com/v1`; `{variable}`s are not expanded
+    pub url: String,
+    pub description: Option<String>,
+}
+
+impl Server {
+    pub fn new(url: &str) -> Self {
+        Server {
+            url: url.to_string(),
+            description: None,
+        }
+    }
+
+    pub fn description(mut self, description: &str) -> Self {
+        self.description = Some(description.to_string());
+        self
+    }
+}
+
+/// A group operations are listed under
+#[derive(Debug, Clone)]
+pub struct Tag {
+    pub name: String,
+    pub description: Option<String>,
+}
+
+impl Tag {
+    pub fn new(name: &str) -> Self {
+        Tag {
+            name: name.to_string(),
+            description: None,
+        }
+    }
+
+    pub fn description(mut self, description: &str) -> Self {
+        self.description = Some(description.to_string());
+        self
+    }
+}
+
+/// The HTTP method of an operation
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Method {
+    Get,
+    Put,
+    Post,
+    Delete,
+    Options,
+    Head,
+    Patch,
+    Trace,
+}
+
+impl Method {
+    /// The key of the operation in its Path Item
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Method::Get => "get",
+            Method::Put => "put",
+            Method::Post => "post",
+            Method::Delete => "delete",
+            Method::Options => "options",
+            Method::Head => "head",
+            Method::Patch => "patch",
+            Method::Trace => "trace",
+        }
+    }
+}
+
+/// The body an operation accepts
+#[derive(Debug, Clone)]
+pub struct RequestBody {
+    pub description: Option<String>,
+    /// Whether the body must be sent
+    pub required: bool,
+    pub content_type: String,
+    pub schema: SchemaType,
+}
+
+impl RequestBody {
+    /// A required `application/json` body of `T`
+    pub fn new<T: Schema>() -> Self {
+        Self::from_schema(named_schema::<T>())
+    }
+
+    /// A required `application/json` body of an explicit schema
+    pub fn from_schema(schema: SchemaType) -> Self {
+        RequestBody {
+            description: None,
+            required: true,
+            content_type: "application/json".to_string(),
+            schema,
+        }
+    }
+
+    pub fn description(mut self, description: &str) -> Self {
+        self.description = Some(description.to_string());
+        self
+    }
+
+    /// Let callers leave the body out
+    pub fn optional(mut self) -> Self {
+        self.required = false;
+        self
+    }
+
+    pub fn content_type(mut self, content_type: &str) -> Self {
+        self.content_type = content_type.to_string();
+        self
+    }
+
+    fn to_json(&self, registry: &mut Registry, options: &GenerateOptions) -> Value {
+        let schema = registry.convert_root(&options.apply(&self.schema));
+        let mut body = json!({
+            "required": self.required,
+            "content": content(&self.content_type, schema),
+        });
+        if let Some(description) = &self.description {
+            body["description"] = json!(description);
+        }
+        body
+    }
+}
+
+/// One response an operation can give
+#[derive(Debug, Clone)]
+pub struct Response {
+    /// Status code such as `200`, a range such as `4XX`, or `default`
+    pub status: String,
+    pub description: String,
+    pub content_type: String,
+    /// The body's schema; `None` for responses without one
+    pub schema: Option<SchemaType>,
+}
+
+impl Response {
+    /// A response with an `application/json` body of `T`
+    pub fn new<T: Schema>(status: &str, description: &str) -> Self {
+        Self::from_schema(status, description, named_schema::<T>())
+    }
+
+    /// A response with an `application/json` body of an explicit schema
+    pub fn from_schema(status: &str, description: &str, schema: SchemaType) -> Self {
+        Response {
+            schema: Some(schema),
+            ..Self::empty(status, description)
+        }
+    }
+
+    /// A response without a body, such as `204`
+    pub fn empty(status: &str, description: &str) -> Self {
+        Response {
+            status: status.to_string(),
+            description: description.to_string(),
+            content_type: "application/json".to_string(),
+            schema: None,
+        }
+    }
+
+    pub fn content_type(mut self, content_type: &str) -> Self {
+        self.content_type = content_type.to_string();
+        self
+    }
+
+    fn to_json(&self, registry: &mut Registry, options: &GenerateOptions) -> Value {
+        let mut response = json!({ "description": self.description });
+        if let Some(schema) = &self.schema {
+            response["content"] = content(
+                &self.content_type,
+                registry.convert_root(&options.apply(schema)),
+            );
+        }
+        response
+    }
+}
+
+/// Something the API does at a path
+#[derive(Debug, Clone)]
+pub struct Operation {
+    pub method: Method,
+    /// Such as `/users/{id}`
+    pub path: String,
+    pub operation_id: Option<String>,
+    pub summary: Option<String>,
+    pub description: Option<String>,
+    /// Names of the tags it is listed under
+    pub tags: Vec<String>,
+    pub parameters: Vec<Parameter>,
+    pub request_body: Option<RequestBody>,
+    pub responses: Vec<Response>,
+    pub deprecated: bool,
+    /// Alternative requirements, any one of which is enough; `None` uses
+    /// the document's
+    pub security: Option<Vec<SecurityRequirement>>,
+}
+
+impl Operation {
+    pub fn new(method: Method, path: &str) -> Self {
+        Operation {
+            method,
+            path: path.to_string(),
+            operation_id: None,
+            summary: None,
+            description: None,
+            tags: vec![],
+            parameters: vec![],
+            request_body: None,
+            responses: vec![],
+            deprecated: false,
+            security: None,
+        }
+    }
+
+    pub fn get(path: &str) -> Self {
+        Self::new(Method::Get, path)
+    }
+
+    pub fn put(path: &str) -> Self {
+        Self::new(Method::Put, path)
+    }
+
+    pub fn post(path: &str) -> Self {
+        Self::new(Method::Post, path)
+    }
+
+    pub fn delete(path: &str) -> Self {
+        Self::new(Method::Delete, path)
+    }
+
+    pub fn patch(path: &str) -> Self {
+        Self::new(Method::Patch, path)
+    }
+
+    pub fn operation_id(mut self, operation_id: &str) -> Self {
+        self.operation_id = Some(operation_id.to_string());
+        self
+    }
+
+    pub fn summary(mut self, summary: &str) -> Self {
+        self.summary = Some(summary.to_string());
+        self
+    }
+
+    pub fn description(mut self, description: &str) -> Self {
+        self.description = Some(description.to_string());
+        self
+    }
+
+    /// List the operation under `tag` too
+    pub fn tag(mut self, tag: &str) -> Self {
+        self.tags.push(tag.to_string());
+        self
+    }
+
+    /// Take each property of `T` as a parameter in `location`, such as the
+    /// fields of a typed query struct
+    pub fn parameters<T: Schema>(
+        mut self,
+        location: ParameterLocation,
+    ) -> Result<Self, ParameterError> {
+        for parameter in Parameter::from_object(&named_schema::<T>(), location)? {
+            self = self.parameter(parameter);
+        }
+        Ok(self)
+    }
+
+    /// Take one parameter
+    ///
+    /// Adding a parameter with the same name and location again replaces the
+    /// earlier one.
+    pub fn parameter(mut self, parameter: Parameter) -> Self {
+        match self
+            .parameters
+            .iter_mut()
+            .find(|p| p.name == parameter.name && p.location == parameter.location)
+        {
+            Some(existing) => *existing = parameter,
+            None => self.parameters.push(parameter),
+        }
+        self
+    }
+
+    /// Accept a required JSON body of `T`
+    pub fn request<T: Schema>(self) -> Self {
+        self.request_body(RequestBody::new::<T>())
+    }
+
+    /// Accept a fully configured body
+    pub fn request_body(mut self, body: RequestBody) -> Self {
+        self.request_body = Some(body);
+        self
+    }
+
+    /// Respond with a JSON body of `T` under `status`
+    pub fn response<T: Schema>(self, status: &str, description: &str) -> Self {
+        self.respond(Response::new::<T>(status, description))
+    }
+
+    /// Give a fully configured response
+    ///
+    /// Adding a response with the same status again replaces the earlier one.
+    pub fn respond(mut self, response: Response) -> Self {
+        match self
+            .responses
+            .iter_mut()
+            .find(|r| r.status == response.status)
+        {
+            Some(existing) => *existing = response,
+            None => self.responses.push(response),
+        }
+        self
+    }
+
+    pub fn deprecated(mut self) -> Self {
+        self.deprecated = true;
+        self
+    }
+
+    /// Accept requests that satisfy `requirement`, in place of the
+    /// document's requirements; each call adds an alternative
+    pub fn security(mut self, requirement: SecurityRequirement) -> Self {
+        self.security.get_or_insert_with(Vec::new).push(requirement);
+        self
+    }
+
+    /// Accept requests without credentials, whatever the document requires
+    pub fn unauthenticated(mut self) -> Self {
+        self.security = Some(vec![]);
+        self
+    }
+
+    /// `GET /users/{id}`, for errors
+    fn label(&self) -> String {
+        format!("{} {}", self.method.as_str().to_uppercase(), self.path)
+    }
+
+    /// The Operation Object, with named types added to `registry`
+    fn to_json(
+        &self,
+        registry: &mut Registry,
+        options: &GenerateOptions,
+    ) -> Result<Value, OpenApiError> {
+        if self.responses.is_empty() {
+            return Err(OpenApiError::NoResponses {
+                operation: self.label(),
+            });
+        }
+
+        let mut object = Map::new();
+        if !self.tags.is_empty() {
+            object.insert("tags".to_string(), json!(self.tags));
+        }
+        if let Some(summary) = &self.summary {
+            object.insert("summary".to_string(), json!(summary));
+        }
+        if let Some(description) = &self.description {
+            object.insert("description".to_string(), json!(description));
+        }
+        if let Some(operation_id) = &self.operation_id {
+            object.insert("operationId".to_string(), json!(operation_id));
+        }
+        if !self.parameters.is_empty() {
+            let parameters: Vec<Value> = self
+                .parameters
+                .iter()
+                .map(|parameter| parameter.to_json(registry, options))
+                .collect();
+            object.insert("parameters".to_string(), json!(parameters));
+        }
+        if let Some(body) = &self.request_body {
+            object.insert("requestBody".to_string(), body.to_json(registry, options));
+        }
+        let responses: Map<String, Value> = self
+            .responses
+            .iter()
+            .map(|response| (response.status.clone(), response.to_json(registry, options)))
+            .collect();
+        object.insert("responses".to_string(), Value::Object(responses));
+        if self.deprecated {
+            object.insert("deprecated".to_string(), json!(true));
+        }
+        if let Some(security) = &self.security {
+            object.insert("security".to_string(), security_json(security));
+        }
+        Ok(Value::Object(object))
+    }
+}
+
+/// A complete OpenAPI document
+#[derive(Debug, Clone)]
+pub struct OpenApiBuilder {
+    title: String,
+    version: String,
+    description: Option<String>,
+    servers: Vec<Server>,
+    tags: Vec<Tag>,
+    operations: Vec<Operation>,
+    security_schemes: Vec<(String, SecurityScheme)>,
+    security: Vec<SecurityRequirement>,
+    openapi_version: OpenApiVersion,
+    strict: bool,
+    order: OutputOrder,
+    generate: GenerateOptions,
+}
+
+impl OpenApiBuilder {
+    /// A document for the API `title` at `version` (the API's version, not
+    /// OpenAPI's)
+    pub fn new(title: &str, version: &str) -> Self {
+        OpenApiBuilder {
+            title: title.to_string(),
+            version: version.to_string(),
+            description: None,
+            servers: vec![],
+            tags: vec![],
+            operations: vec![],
+            security_schemes: vec![],
+            security: vec![],
+            openapi_version: OpenApiVersion::default(),
+            strict: false,
+            order: OutputOrder::default(),
+            generate: GenerateOptions::default(),
+        }
+    }
+
+    pub fn description(mut self, description: &str) -> Self {
+        self.description = Some(description.to_string());
+        self
+    }
+
+    /// Servers are listed in the order they're added
+    pub fn server(mut self, server: Server) -> Self {
+        self.servers.push(server);
+        self
+    }
+
+    /// Adding a tag with the same name again replaces the earlier one
+    pub fn tag(mut self, tag: Tag) -> Self {
+        match self.tags.iter_mut().find(|t| t.name == tag.name) {
+            Some(existing) => *existing = tag,
+            None => self.tags.push(tag),
+        }
+        self
+    }
+
+    /// Adding an operation with the same method and path again replaces the
+    /// earlier one
+    pub fn operation(mut self, operation: Operation) -> Self {
+        match self
+            .operations
+            .iter_mut()
+            .find(|o| o.method == operation.method && o.path == operation.path)
+        {
+            Some(existing) => *existing = operation,
+            None => self.operations.push(operation),
+        }
+        self
+    }
+
+    /// Define a scheme under `components/securitySchemes`; adding one with
+    /// the same name again replaces the earlier one
+    pub fn security_scheme(mut self, name: &str, scheme: SecurityScheme) -> Self {
+        match self.security_schemes.iter_mut().find(|(n, _)| n == name) {
+            Some(existing) => existing.1 = scheme,
+            None => self.security_schemes.push((name.to_string(), scheme)),
+        }
+        self
+    }
+
+    /// Require `requirement` of every operation that doesn't set its own;
+    /// each call adds an alternative
+    pub fn security(mut self, requirement: SecurityRequirement) -> Self {
+        self.security.push(requirement);
+        self
+    }
+
+    /// The OpenAPI release the document and its schemas are written for
+    pub fn openapi_version(mut self, version: OpenApiVersion) -> Self {
+        self.openapi_version = version;
+        self
+    }
+
+    /// Close every object schema to undeclared properties; see
+    /// [`OpenApiOptions::strict`](crate::OpenApiOptions::strict)
+    pub fn strict(mut self) -> Self {
+        self.strict = true;
+        self
+    }
+
+    /// The order properties, `required` lists and cases are written in; see
+    /// [`OutputOrder`]
+    pub fn order(mut self, order: OutputOrder) -> Self {
+        self.order = order;
+        self
+    }
+
+    /// Bound every request and response schema before it is written
+    pub fn generate(mut self, options: GenerateOptions) -> Self {
+        self.generate = options;
+        self
+    }
+
+    /// The document, with the named types of every body under
+    /// `components/schemas`
+    pub fn build(&self) -> Result<Value, OpenApiError> {
+        self.check_security()?;
+        let mut registry = Registry::collecting(self.openapi_version)
+            .strict(self.strict)
+            .order(self.order);
+        let mut paths = Map::new();
+        let mut operation_ids = vec![];
+        for operation in &self.operations {
+            if let Some(operation_id) = &operation.operation_id {
+                if operation_ids.contains(&operation_id) {
+                    return Err(OpenApiError::DuplicateOperationId {
+                        operation_id: operation_id.clone(),
+                    });
+                }
+                operation_ids.push(operation_id);
+            }
+            let object = operation.to_json(&mut registry, &self.generate)?;
+            let item = paths
+                .entry(operation.path.clone())
+                .or_insert_with(|| json!({}));
+            item[operation.method.as_str()] = object;
+        }
+
+        let mut info = json!({ "title": self.title, "version": self.version });
+        if let Some(description) = &self.description {
+            info["description"] = json!(description);
+        }
+        let mut document = json!({
+            "openapi": self.openapi_version.as_str(),
+            "info": info,
+        });
+        if !self.servers.is_empty() {
+            let servers: Vec<Value> = self
+                .servers
+                .iter()
+                .map(|server| {
+                    let mut object = json!({ "url": server.url });
+                    if let Some(description) = &server.description {
+                        object["description"] = json!(description);
+                    }
+                    object
+                })
+                .collect();
+            document["servers"] = json!(servers);
+        }
+        if !self.tags.is_empty() {
+            let tags: Vec<Value> = self
+                .tags
+                .iter()
+                .map(|tag| {
+                    let mut object = json!({ "name": tag.name });
+                    if let Some(description) = &tag.description {
+                        object["description"] = json!(description);
+                    }
+                    object
+                })
+                .collect();
+            document["tags"] = json!(tags);
+        }
+        document["paths"] = Value::Object(paths);
+        let schemas = registry.into_components()?;
+        let mut components = Map::new();
+        if !schemas.is_empty() {
+            components.insert("schemas".to_string(), json!(schemas));
+        }
+        if !self.security_schemes.is_empty() {
+            let schemes: Map<String, Value> = self
+                .security_schemes
+                .iter()
+                .map(|(name, scheme)| (name.clone(), scheme.to_json()))
+                .collect();
+            components.insert("securitySchemes".to_string(), Value::Object(schemes));
+        }
+        if !components.is_empty() {
+            document["components"] = Value::Object(components);
+        }
+        if !self.security.is_empty() {
+            document["security"] = security_json(&self.security);
+        }
+        Ok(document)
+    }
+
+    /// Fails on the first requirement naming a scheme that isn't defined
+    fn check_security(&self) -> Result<(), OpenApiError> {
+        let requirements = self.security.iter().chain(
+            self.operations
+                .iter()
+                .flat_map(|operation| operation.security.iter().flatten()),
+        );
+        for requirement in requirements {
+            for (name, _) in &requirement.schemes {
+                if !self.security_schemes.iter().any(|(n, _)| n == name) {
+                    return Err(OpenApiError::UnknownSecurityScheme { name: name.clone() });
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A `security` list of alternative requirements
+fn security_json(requirements: &[SecurityRequirement]) -> Value {
+    let requirements: Vec<Value> = requirements.iter().map(|r| r.to_json()).collect();
+    json!(requirements)
+}
+
+/// A `content` map with one media type
+fn content(content_type: &str, schema: Value) -> Value {
+    json!({ content_type: { "schema": schema } })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Schema)]
+    #[allow(dead_code)]
+    struct User {
+        id: String,
+        email: String,
+    }
+
+    /// Fields of a new user
+    #[derive(Schema)]
+    #[allow(dead_code)]
+    struct CreateUser {
+        email: String,
+    }
+
+    #[derive(Schema)]
+    #[allow(dead_code)]
+    struct Problem {
+        title: String,
+    }
+
+    #[test]
+    fn test_document() {
+        let document = OpenApiBuilder::new("Users", "1.2.0")
+            .description("Manages accounts")
+            .server(Server::new("https://api.example.com").description("Production"))
+            .tag(Tag::new("users").description("Account management"))
+            .operation(
+                Operation::post("/users")
+                    .operation_id("createUser")
+                    .tag("users")
+                    .request::<CreateUser>()
+                    .response::<User>("201", "Created")
+                    .response::<Problem>("4XX", "Rejected"),
+            )
+            .operation(
+                Operation::get("/users/{id}")
+                    .operation_id("getUser")
+                    .response::<User>("200", "The user"),
+            )
+            .operation(
+                Operation::delete("/users/{id}")
+                    .respond(Response::empty("204", "Deleted"))
+                    .deprecated(),
+            )
+            .build()
+            .unwrap();
+
+        let user = json!({ "$ref": "#/components/schemas/User" });
+        assert_eq!(
+            document,
+            json!({
+                "openapi": "3.1.0",
+                "info": { "title": "Users", "version": "1.2.0", "description": "Manages accounts" },
+                "servers": [{ "url": "https://api.example.com", "description": "Production" }],
+                "tags": [{ "name": "users", "description": "Account management" }],
+                "paths": {
+                    "/users": {
+                        "post": {
+                            "tags": ["users"],
+                            "operationId": "createUser",
+                            "requestBody": {
+                                "required": true,
+                                "content": { "application/json": {
+                                    "schema": { "$ref": "#/components/schemas/CreateUser" }
+                                } },
+                            },
+                            "responses": {
+                                "201": {
+                                    "description": "Created",
+                                    "content": { "application/json": { "schema": user } },
+                                },
+                                "4XX": {
+                                    "description": "Rejected",
+                                    "content": { "application/json": {
+                                        "schema": { "$ref": "#/components/schemas/Problem" }
+                                    } },
+                                },
+                            },
+                        },
+                    },
+                    "/users/{id}": {
+                        "get": {
+                            "operationId": "getUser",
+                            "responses": {
+                                "200": {
+                                    "description": "The user",
+                                    "content": { "application/json": { "schema": user } },
+                                },
+                            },
+                        },
+                        "delete": {
+                            "responses": { "204": { "description": "Deleted" } },
+                            "deprecated": true,
+                        },
+                    },
+                },
+                "components": { "schemas": {
+                    "CreateUser": crate::to_openapi_schema::<CreateUser>(),
+                    "Problem": crate::to_openapi_schema::<Problem>(),
+                    "User": crate::to_openapi_schema::<User>(),
+                } },
+            })
+        );
+    }
+
+    #[test]
+    fn test_type_name_only_impls() {
+        // A hand-written impl that only names itself through `type_name`
+        struct Sku;
+        impl Schema for Sku {
+            fn schema() -> SchemaType {
+                SchemaType::new(schema::TypeKind::String)
+            }
+
+            fn type_name() -> Option<&'static str> {
+                Some("Sku")
+            }
+        }
+
+        let sku = json!({ "$ref": "#/components/schemas/Sku" });
+        let document = OpenApiBuilder::new("Catalog", "1.0.0")
+            .operation(
+                Operation::get("/skus/latest")
+                    .parameter(Parameter::new::<Sku>("after", ParameterLocation::Query))
+                    .response::<Sku>("200", "The newest SKU"),
+            )
+            .build()
+            .unwrap();
+        let operation = &document["paths"]["/skus/latest"]["get"];
+        assert_eq!(
+            operation["responses"]["200"]["content"]["application/json"]["schema"],
+            sku
+        );
+        assert_eq!(operation["parameters"][0]["schema"], sku);
+        assert_eq!(
+            document["components"]["schemas"]["Sku"],
+            json!({ "type": "string", "title": "Sku" })
+        );
+    }
+
+    #[test]
+    fn test_errors() {
+        let builder = OpenApiBuilder::new("Users", "1.0.0");
+        assert_eq!(
+            builder.clone().operation(Operation::get("/users")).build(),
+            Err(OpenApiError::NoResponses {
+                operation: "GET /users".to_string()
+            })
+        );
+        assert_eq!(
+            builder
+                .operation(
+                    Operation::get("/users")
+                        .operation_id("users")
+                        .response::<User>("200", "OK"),
+                )
+                .operation(
+                    Operation::post("/users")
+                        .operation_id("users")
+                        .response::<User>("201", "OK"),
+                )
+                .build(),
+            Err(OpenApiError::DuplicateOperationId {
+                operation_id: "users".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn test_security() {
+        use crate::{OAuthFlow, SecurityRequirement, SecurityScheme};
+
+        let builder = OpenApiBuilder::new("Users", "1.0.0")
+            .security_scheme("bearer", SecurityScheme::bearer().bearer_format("JWT"))
+            .security_scheme(
+                "oauth",
+                SecurityScheme::oauth2([OAuthFlow::client_credentials(
+                    "https://auth.example.com/token",
+                )
+                .scope("read:users", "Read accounts")]),
+            )
+            .security(SecurityRequirement::new("bearer"))
+            .operation(Operation::get("/users").response::<User>("200", "OK"))
+            .operation(
+                Operation::delete("/users/{id}")
+                    .security(SecurityRequirement::new("oauth").scope("read:users"))
+                    .security(SecurityRequirement::new("bearer"))
+                    .response::<User>("200", "OK"),
+            )
+            .operation(
+                Operation::get("/health")
+                    .unauthenticated()
+                    .response::<String>("200", "OK"),
+            );
+        let document = builder.build().unwrap();
+        assert_eq!(document["security"], json!([{ "bearer": [] }]));
+        assert_eq!(
+            document["components"]["securitySchemes"]["bearer"],
+            json!({ "type": "http", "scheme": "bearer", "bearerFormat": "JWT" })
+        );
+        assert_eq!(
+            document["components"]["securitySchemes"]["oauth"]["flows"]["clientCredentials"]["scopes"],
+            json!({ "read:users": "Read accounts" })
+        );
+        // Operations without their own requirements inherit the document's
+        assert!(document["paths"]["/users"]["get"].get("security").is_none());
+        assert_eq!(
+            document["paths"]["/users/{id}"]["delete"]["security"],
+            json!([{ "oauth": ["read:users"] }, { "bearer": [] }])
+        );
+        assert_eq!(document["paths"]["/health"]["get"]["security"], json!([]));
+        #[cfg(feature = "validate")]
+        assert_eq!(crate::validate_document(&document), Ok(()));
+
+        assert_eq!(
+            builder
+                .operation(
+                    Operation::get("/admin")
+                        .security(SecurityRequirement::new("basic"))
+                        .response::<User>("200", "OK"),
+                )
+                .build(),
+            Err(OpenApiError::UnknownSecurityScheme {
+                name: "basic".to_string()
+            })
+        );
+    }
+}
@@ -1,215 +1,947 @@
-use schema::{Schema, SchemaType, TypeKind};
-use serde_json::{Value, json};
-use std::collections::HashMap;
+use schema::{
+    GenerateOptions, IntegerKind, NumberKind, RefPreference, Schema, SchemaType, Tagging, TypeKind,
+    VariantCase,
+};
+use serde_json::{Map, Value, json};
+use std::collections::BTreeMap;
+use std::fmt;
 
-/// Convert a Schema to OpenAPI 3.0 schema format
+pub mod docs;
+mod document;
+mod parameters;
+mod responses;
+mod security;
+#[cfg(feature = "utoipa")]
+mod utoipa_interop;
+#[cfg(feature = "validate")]
+mod validate;
+#[cfg(feature = "yaml")]
+mod yaml;
+
+pub use document::{
+    Method, OpenApiBuilder, OpenApiError, Operation, RequestBody, Response, Server, Tag,
+};
+pub use parameters::{
+    Parameter, ParameterError, ParameterLocation, schema_type_to_openapi_parameters,
+    to_openapi_parameters,
+};
+pub use responses::{PROBLEM_JSON, Page, Problem};
+pub use security::{
+    ApiKeyLocation, OAuthFlow, OAuthFlowKind, SecurityRequirement, SecurityScheme,
+    SecuritySchemeKind,
+};
+#[cfg(feature = "utoipa")]
+pub use utoipa_interop::{
+    UtoipaError, from_utoipa, schema_type_to_utoipa, to_utoipa_schema, utoipa_to_schema_type,
+};
+#[cfg(feature = "validate")]
+pub use validate::{ValidationError, validate_document};
+#[cfg(feature = "yaml")]
+pub use yaml::{to_openapi_yaml, to_yaml};
+
+/// Which OpenAPI release schemas are written for
+///
+/// 3.1 schemas are JSON Schema 2020-12. 3.0 schemas are an older dialect:
+/// null is `nullable: true`, single values are one-element `enum`s rather
+/// than `const`, tuples have one `items` schema for every position, and
+/// keywords beside a `$ref` are ignored, so annotated references are wrapped
+/// in `allOf`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OpenApiVersion {
+    V3_0,
+    #[default]
+    V3_1,
+}
+
+impl OpenApiVersion {
+    /// The `openapi` field of a document for this version
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            OpenApiVersion::V3_0 => "3.0.3",
+            OpenApiVersion::V3_1 => "3.1.0",
+        }
+    }
+}
+
+/// The order properties, `required` lists and `oneOf` cases are written in
+///
+/// Either way the output is the same from build to build. `required` lists
+/// and cases follow the order; properties are inserted in it too, though
+/// object keys are only kept in insertion order where something else in
+/// the build turns on serde_json's `preserve_order`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OutputOrder {
+    /// Alphabetically by property and case name, so reordering fields in
+    /// Rust doesn't show up in spec diffs
+    #[default]
+    Sorted,
+    /// As the fields and variants are declared
+    Declaration,
+}
+
+/// How schemas are written, beyond the defaults of [`to_openapi_schema`]
+#[derive(Debug, Clone, Default)]
+pub struct OpenApiOptions {
+    pub version: OpenApiVersion,
+    /// Close every object to properties it doesn't declare, as if each type
+    /// had `#[schema(deny_unknown_fields)]`
+    pub strict: bool,
+    /// The order properties and cases are written in
+    pub order: OutputOrder,
+    pub generate: GenerateOptions,
+}
+
+impl OpenApiOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn version(mut self, version: OpenApiVersion) -> Self {
+        self.version = version;
+        self
+    }
+
+    /// Write `additionalProperties: false` on every object, which several
+    /// client generators and API scanners require
+    pub fn strict(mut self) -> Self {
+        self.strict = true;
+        self
+    }
+
+    pub fn order(mut self, order: OutputOrder) -> Self {
+        self.order = order;
+        self
+    }
+
+    /// Bound the schema before it is written
+    pub fn generate(mut self, generate: GenerateOptions) -> Self {
+        self.generate = generate;
+        self
+    }
+}
+
+/// `T`'s schema, named after [`Schema::type_name`] if the schema itself
+/// carries no name, as with hand-written impls
+pub(crate) fn named_schema<T: Schema>() -> SchemaType {
+    let mut schema = T::schema();
+    if schema.name.is_none() {
+        schema.name = T::type_name().map(str::to_string);
+    }
+    schema
+}
+
+/// Convert a Schema to OpenAPI 3.1 schema format
 pub fn to_openapi_schema<T: Schema>() -> Value {
-    schema_type_to_openapi(&T::schema())
+    schema_type_to_openapi(&named_schema::<T>())
+}
+
+/// Convert a Schema to the schema format of OpenAPI `version`
+pub fn to_openapi_schema_for<T: Schema>(version: OpenApiVersion) -> Value {
+    schema_type_to_openapi_for(&named_schema::<T>(), version)
+}
+
+/// Convert a Schema to OpenAPI 3.1 schema format, bounded by `options`
+pub fn to_openapi_schema_with_options<T: Schema>(options: &GenerateOptions) -> Value {
+    schema_type_to_openapi_with_options(&named_schema::<T>(), options)
+}
+
+/// Convert a SchemaType to OpenAPI 3.1 schema format, bounded by `options`
+pub fn schema_type_to_openapi_with_options(
+    schema: &SchemaType,
+    options: &GenerateOptions,
+) -> Value {
+    schema_type_to_openapi(&options.apply(schema))
 }
 
-/// Convert a SchemaType to OpenAPI 3.0 schema format
+/// Convert a SchemaType to OpenAPI 3.1 schema format
 pub fn schema_type_to_openapi(schema: &SchemaType) -> Value {
-    let mut result = match &schema.kind {
-        TypeKind::String => json!({ "type": "string" }),
-        TypeKind::Number(_) => json!({ "type": "number" }),
-        TypeKind::Integer(_) => json!({ "type": "integer" }),
-        TypeKind::Boolean => json!({ "type": "boolean" }),
-        TypeKind::Null => json!({ "type": "null" }),
-        TypeKind::Array { items } => {
-            json!({
-                "type": "array",
-                "items": schema_type_to_openapi(items)
-            })
+    schema_type_to_openapi_for(schema, OpenApiVersion::default())
+}
+
+/// Convert a SchemaType to the schema format of OpenAPI `version`
+pub fn schema_type_to_openapi_for(schema: &SchemaType, version: OpenApiVersion) -> Value {
+    Registry::inline(version).convert(schema)
+}
+
+/// Convert a Schema to OpenAPI schema format as `options` say
+pub fn to_openapi_schema_with<T: Schema>(options: &OpenApiOptions) -> Value {
+    schema_type_to_openapi_with(&named_schema::<T>(), options)
+}
+
+/// Convert a SchemaType to OpenAPI schema format as `options` say
+pub fn schema_type_to_openapi_with(schema: &SchemaType, options: &OpenApiOptions) -> Value {
+    Registry::inline(options.version)
+        .strict(options.strict)
+        .order(options.order)
+        .convert(&options.generate.apply(schema))
+}
+
+/// Convert a Schema to a `$ref` into `components/schemas`, where every named
+/// type it uses is defined once
+pub fn to_openapi_components<T: Schema>() -> Result<Components, ComponentsError> {
+    schema_type_to_openapi_components(&named_schema::<T>())
+}
+
+/// Convert a Schema to a `$ref` into `components/schemas`, bounded by
+/// `options`
+pub fn to_openapi_components_with_options<T: Schema>(
+    options: &GenerateOptions,
+) -> Result<Components, ComponentsError> {
+    schema_type_to_openapi_components(&options.apply(&named_schema::<T>()))
+}
+
+/// Convert a SchemaType to a `$ref` into `components/schemas` (or, when the
+/// root is unnamed, a schema in place) plus the components it points at
+pub fn schema_type_to_openapi_components(
+    schema: &SchemaType,
+) -> Result<Components, ComponentsError> {
+    schema_type_to_openapi_components_for(schema, OpenApiVersion::default())
+}
+
+/// Convert a SchemaType to a `$ref` into `components/schemas` plus the
+/// components it points at, in the schema format of OpenAPI `version`
+pub fn schema_type_to_openapi_components_for(
+    schema: &SchemaType,
+    version: OpenApiVersion,
+) -> Result<Components, ComponentsError> {
+    let mut registry = Registry::collecting(version);
+    let root = registry.convert_root(schema);
+    Ok(Components {
+        root,
+        schemas: registry.into_components()?,
+    })
+}
+
+/// Convert a SchemaType to a `$ref` into `components/schemas` plus the
+/// components it points at, as `options` say
+pub fn schema_type_to_openapi_components_with(
+    schema: &SchemaType,
+    options: &OpenApiOptions,
+) -> Result<Components, ComponentsError> {
+    let mut registry = Registry::collecting(options.version)
+        .strict(options.strict)
+        .order(options.order);
+    let root = registry.convert_root(&options.generate.apply(schema));
+    Ok(Components {
+        root,
+        schemas: registry.into_components()?,
+    })
+}
+
+/// A schema split into a root and the component schemas its `$ref`s point at
+#[derive(Debug, Clone, PartialEq)]
+pub struct Components {
+    /// The schema itself; a `$ref` when it is a named type
+    pub root: Value,
+    /// `components/schemas`, by type name
+    pub schemas: BTreeMap<String, Value>,
+}
+
+/// Why a schema can't be split into components
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ComponentsError {
+    /// Two structurally different types share a name, e.g. `User` from two
+    /// modules, so one component can't stand for both
+    NameCollision(String),
+}
+
+impl fmt::Display for ComponentsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ComponentsError::NameCollision(name) => {
+                write!(f, "different types are both named {:?}", name)
+            }
         }
-        TypeKind::Set { items, .. } => {
-            json!({
-                "type": "array",
-                "items": schema_type_to_openapi(items),
-                "uniqueItems": true
-            })
+    }
+}
+
+impl std::error::Error for ComponentsError {}
+
+/// The schema a component was written from
+struct Definition {
+    /// Fingerprint of the type, to tell two types of the same name apart
+    shape: String,
+    /// The annotations the component carries, which uses of it don't repeat
+    schema: SchemaType,
+    /// Whether the annotations are the type's own, from a direct use,
+    /// rather than none at all
+    direct: bool,
+}
+
+/// Writes schemas, either entirely in place or with named types collected
+/// into components
+pub(crate) struct Registry {
+    /// Component schemas by name; `None` writes every schema in place
+    components: Option<BTreeMap<String, Value>>,
+    /// How each component was written
+    defined: BTreeMap<String, Definition>,
+    /// The first name found on two different types
+    collision: Option<String>,
+    version: OpenApiVersion,
+    /// Whether every object is closed to undeclared properties
+    strict: bool,
+    order: OutputOrder,
+}
+
+impl Registry {
+    pub(crate) fn inline(version: OpenApiVersion) -> Self {
+        Registry {
+            components: None,
+            defined: BTreeMap::new(),
+            collision: None,
+            version,
+            strict: false,
+            order: OutputOrder::default(),
+        }
+    }
+
+    /// Close every object written to undeclared properties
+    pub(crate) fn strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
+    pub(crate) fn order(mut self, order: OutputOrder) -> Self {
+        self.order = order;
+        self
+    }
+
+    /// A registry that turns named types into components
+    pub(crate) fn collecting(version: OpenApiVersion) -> Self {
+        Registry {
+            components: Some(BTreeMap::new()),
+            ..Self::inline(version)
+        }
+    }
+
+    /// The components every converted schema points into
+    pub(crate) fn into_components(self) -> Result<BTreeMap<String, Value>, ComponentsError> {
+        match self.collision {
+            Some(name) => Err(ComponentsError::NameCollision(name)),
+            None => Ok(self.components.unwrap_or_default()),
+        }
+    }
+
+    /// A schema: a `$ref` if it is a named type being collected, otherwise in
+    /// place
+    pub(crate) fn convert(&mut self, schema: &SchemaType) -> Value {
+        self.convert_use(schema, false)
+    }
+
+    /// A schema used directly, as a document root or a body rather than as
+    /// a field, so its annotations are its type's own and go on its
+    /// component
+    pub(crate) fn convert_root(&mut self, schema: &SchemaType) -> Value {
+        self.convert_use(schema, true)
+    }
+
+    fn convert_use(&mut self, schema: &SchemaType, direct: bool) -> Value {
+        let Some(name) = &schema.name else {
+            return self.convert_inline(schema);
+        };
+        if self.components.is_none()
+            || matches!(schema.kind, TypeKind::Ref { .. })
+            || schema.ref_preference() == Some(RefPreference::Inline)
+        {
+            return self.convert_inline(schema);
+        }
+
+        // A field's description, default, readOnly and the like are about
+        // the field, so the component is written without them unless the
+        // type is used directly, and each use adds its own beside the `$ref`
+        let bare = bare(schema);
+        let shape = bare.fingerprint();
+        let defined = match self.defined.get(name) {
+            Some(defined) if defined.shape != shape => {
+                self.collision.get_or_insert_with(|| name.clone());
+                defined.schema.clone()
+            }
+            Some(defined) if direct && !defined.direct => {
+                self.define(name, &without_use_site(schema), shape, true)
+            }
+            Some(defined) => defined.schema.clone(),
+            None if direct => self.define(name, &without_use_site(schema), shape, true),
+            None => self.define(name, &bare, shape, false),
+        };
+
+        let mut result = json!({ "$ref": reference(name) });
+        annotate(&mut result, schema, &defined, self.version);
+        self.wrap_ref(result)
+    }
+
+    /// Write the component `name` from `schema`, replacing any earlier one
+    fn define(
+        &mut self,
+        name: &str,
+        schema: &SchemaType,
+        shape: String,
+        direct: bool,
+    ) -> SchemaType {
+        // Claim the name before recursing, so self-references see it
+        self.defined.insert(
+            name.to_string(),
+            Definition {
+                shape,
+                schema: schema.clone(),
+                direct,
+            },
+        );
+        let definition = self.convert_inline(schema);
+        if let Some(components) = &mut self.components {
+            components.insert(name.to_string(), definition);
         }
-        TypeKind::Map { key, value, .. } => {
-            // If key is String, use additionalProperties (more idiomatic)
-            if matches!(key.kind, TypeKind::String) {
+        schema.clone()
+    }
+
+    /// `schema` written out in place
+    fn convert_inline(&mut self, schema: &SchemaType) -> Value {
+        let mut result = match &schema.kind {
+            TypeKind::String => json!({ "type": "string" }),
+            TypeKind::Number(NumberKind::F32) => json!({ "type": "number", "format": "float" }),
+            TypeKind::Number(NumberKind::F64) => json!({ "type": "number", "format": "double" }),
+            TypeKind::Integer(kind) => integer(kind),
+            TypeKind::Boolean => json!({ "type": "boolean" }),
+            TypeKind::Null => self.null(),
+            TypeKind::Array { items } => {
                 json!({
-                    "type": "object",
-                    "additionalProperties": schema_type_to_openapi(value)
+                    "type": "array",
+                    "items": self.convert(items)
                 })
-            } else {
-                // For non-string keys, fall back to array of tuples
+            }
+            TypeKind::Set { items, .. } => {
                 json!({
                     "type": "array",
-                    "items": {
-                        "type": "array",
-                        "prefixItems": [
-                            schema_type_to_openapi(key),
-                            schema_type_to_openapi(value)
-                        ],
-                        "minItems": 2,
-                        "maxItems": 2
-                    }
+                    "items": self.convert(items),
+                    "uniqueItems": true
                 })
             }
-        }
-        TypeKind::Object {
-            properties,
-            required,
-        } => {
-            let props: HashMap<String, Value> = properties
-                .iter()
-                .map(|(k, v)| (k.clone(), schema_type_to_openapi(v)))
-                .collect();
-
-            let mut obj = json!({
-                "type": "object",
-                "properties": props
-            });
-
-            if !required.is_empty() {
-                obj["required"] = json!(required);
+            TypeKind::Map { key, value, .. } => {
+                // If key is String, use additionalProperties (more idiomatic)
+                if matches!(key.kind, TypeKind::String) {
+                    json!({
+                        "type": "object",
+                        "additionalProperties": self.convert(value)
+                    })
+                } else {
+                    // For non-string keys, fall back to array of tuples
+                    let pair = vec![self.convert(key), self.convert(value)];
+                    json!({
+                        "type": "array",
+                        "items": self.tuple(pair)
+                    })
+                }
             }
-
-            obj
-        }
-        TypeKind::Enum { variants } => {
-            json!({
-                "type": "string",
-                "enum": variants
-            })
-        }
-        TypeKind::TaggedUnion {
-            tag_field,
-            tag_variants,
-            data_fields,
-        } => {
-            // Legacy: For OpenAPI, represent as oneOf with discriminator
-            let mut schemas = Vec::new();
-
-            for variant in tag_variants {
-                let mut props: HashMap<String, Value> = data_fields
-                    .iter()
-                    .map(|(k, v)| (k.clone(), schema_type_to_openapi(v)))
+            TypeKind::Object { required, .. } => {
+                // In a fixed order, so the first use of a named type is the
+                // same from run to run
+                let mut properties = schema.ordered_properties();
+                if self.order == OutputOrder::Sorted {
+                    properties.sort_by_key(|(name, _)| *name);
+                }
+                let props: Map<String, Value> = properties
+                    .into_iter()
+                    .map(|(k, v)| (k.to_string(), self.convert(v)))
                     .collect();
 
-                // Add tag field
-                props.insert(
-                    tag_field.clone(),
-                    json!({
-                        "type": "string",
-                        "enum": [variant]
-                    }),
-                );
-
-                schemas.push(json!({
+                let mut obj = json!({
                     "type": "object",
-                    "properties": props,
-                    "required": [tag_field]
-                }));
+                    "properties": props
+                });
+
+                if !required.is_empty() {
+                    obj["required"] = self.required(required.iter().cloned());
+                }
+                if schema.denies_unknown_fields() {
+                    obj["additionalProperties"] = json!(false);
+                }
+
+                obj
+            }
+            TypeKind::Enum { variants } => {
+                json!({
+                    "type": "string",
+                    "enum": variants
+                })
             }
+            TypeKind::TaggedUnion {
+                tag_field,
+                tag_variants,
+                data_fields,
+            } => {
+                // Legacy: For OpenAPI, represent as oneOf with discriminator
+                let mut schemas = Vec::new();
+                let mut names: Vec<&String> = data_fields.keys().collect();
+                names.sort();
+                let mut variants: Vec<&String> = tag_variants.iter().collect();
+                if self.order == OutputOrder::Sorted {
+                    variants.sort();
+                }
 
-            json!({
-                "oneOf": schemas,
-                "discriminator": {
-                    "propertyName": tag_field
+                for variant in variants {
+                    let mut props: Map<String, Value> = names
+                        .iter()
+                        .map(|&k| (k.clone(), self.convert(&data_fields[k])))
+                        .collect();
+
+                    // Add tag field
+                    props.insert(
+                        tag_field.clone(),
+                        json!({
+                            "type": "string",
+                            "enum": [variant]
+                        }),
+                    );
+
+                    schemas.push(json!({
+                        "type": "object",
+                        "properties": props,
+                        "required": [tag_field]
+                    }));
                 }
-            })
-        }
-        TypeKind::Variant { cases } => {
-            // Proper variant type - OpenAPI oneOf without forced discriminator
-            let schemas: Vec<Value> = cases
-                .iter()
-                .map(|case| {
-                    match &case.data {
-                        None => {
-                            // Unit variant - represent as const string
-                            json!({
-                                "type": "string",
-                                "const": case.name
-                            })
+
+                json!({
+                    "oneOf": schemas,
+                    "discriminator": {
+                        "propertyName": tag_field
+                    }
+                })
+            }
+            TypeKind::Variant { cases } if schema.tagging().is_some() => {
+                let tagging = schema.tagging().unwrap();
+                let schemas: Vec<Value> = self
+                    .ordered_cases(cases)
+                    .into_iter()
+                    .map(|case| self.tagged_case(case, &tagging))
+                    .collect();
+                match tagging.tag() {
+                    Some(tag) => json!({
+                        "oneOf": schemas,
+                        "discriminator": {
+                            "propertyName": tag
                         }
-                        Some(data) => {
-                            // Variant with data - wrap in object with tag
-                            let data_schema = schema_type_to_openapi(data);
-                            let mut obj = json!({
-                                "type": "object",
-                                "properties": {
-                                    "type": {
-                                        "type": "string",
-                                        "const": case.name
+                    }),
+                    // Cases are told apart by shape alone, so several may match
+                    None => json!({ "anyOf": schemas }),
+                }
+            }
+            TypeKind::Variant { cases } => {
+                // Proper variant type - OpenAPI oneOf without forced discriminator
+                let schemas: Vec<Value> = self
+                    .ordered_cases(cases)
+                    .into_iter()
+                    .map(|case| {
+                        match &case.data {
+                            None => {
+                                // Unit variant - represent as const string
+                                let mut obj = self.constant(&case.name);
+                                if let Some(since) = case.since() {
+                                    obj[schema::SINCE] = json!(since);
+                                }
+                                if case.deprecated() {
+                                    obj["deprecated"] = json!(true);
+                                }
+                                for (key, value) in case.vendor_extensions() {
+                                    obj[key] = value.clone();
+                                }
+                                obj
+                            }
+                            Some(data) => {
+                                // Variant with data - wrap in object with tag
+                                let data_schema = self.convert(data);
+                                let mut obj = json!({
+                                    "type": "object",
+                                    "properties": {
+                                        "type": self.constant(&case.name),
+                                        "data": data_schema
                                     },
-                                    "data": data_schema
-                                },
-                                "required": ["type", "data"]
-                            });
+                                    "required": self.required(["type".into(), "data".into()])
+                                });
 
-                            if let Some(desc) = &case.description {
-                                obj["description"] = json!(desc);
+                                if let Some(desc) = &case.description {
+                                    obj["description"] = json!(desc);
+                                }
+                                if let Some(since) = case.since() {
+                                    obj[schema::SINCE] = json!(since);
+                                }
+                                if case.deprecated() {
+                                    obj["deprecated"] = json!(true);
+                                }
+                                for (key, value) in case.vendor_extensions() {
+                                    obj[key] = value.clone();
+                                }
+                                obj
                             }
-                            obj
                         }
-                    }
+                    })
+                    .collect();
+
+                json!({ "oneOf": schemas })
+            }
+            TypeKind::Result { ok, err } => {
+                // Result type - OpenAPI oneOf with ok/error variants
+                json!({
+                    "oneOf": [
+                        {
+                            "type": "object",
+                            "properties": {
+                                "ok": self.convert(ok)
+                            },
+                            "required": ["ok"]
+                        },
+                        {
+                            "type": "object",
+                            "properties": {
+                                "error": self.convert(err)
+                            },
+                            "required": ["error"]
+                        }
+                    ]
                 })
-                .collect();
+            }
+            TypeKind::Tuple { fields } => {
+                // Tuple - OpenAPI array with fixed items
+                if fields.is_empty() {
+                    json!({ "type": "array", "maxItems": 0 })
+                } else {
+                    let items: Vec<Value> =
+                        fields.iter().map(|field| self.convert(field)).collect();
+                    self.tuple(items)
+                }
+            }
+            TypeKind::Ref { name } => {
+                json!({ "$ref": reference(name) })
+            }
+        };
 
-            json!({ "oneOf": schemas })
+        // A doc comment's title, or else the type's name, so generated
+        // clients and docs label the schema
+        if let Some(title) = schema.title.as_ref().or(schema.name.as_ref()) {
+            result["title"] = json!(title);
         }
-        TypeKind::Result { ok, err } => {
-            // Result type - OpenAPI oneOf with ok/error variants
-            json!({
-                "oneOf": [
-                    {
-                        "type": "object",
-                        "properties": {
-                            "ok": schema_type_to_openapi(ok)
-                        },
-                        "required": ["ok"]
-                    },
-                    {
-                        "type": "object",
-                        "properties": {
-                            "error": schema_type_to_openapi(err)
-                        },
-                        "required": ["error"]
-                    }
-                ]
-            })
+        if let Some(desc) = &schema.description {
+            result["description"] = json!(desc);
         }
-        TypeKind::Tuple { fields } => {
-            // Tuple - OpenAPI array with fixed items
-            if fields.is_empty() {
-                json!({ "type": "array", "maxItems": 0 })
-            } else {
-                let items: Vec<Value> = fields.iter().map(schema_type_to_openapi).collect();
-                json!({
+        if schema.deprecated() {
+            result["deprecated"] = json!(true);
+        }
+        if schema.read_only() {
+            result["readOnly"] = json!(true);
+        }
+        if schema.write_only() {
+            result["writeOnly"] = json!(true);
+        }
+        if let Some(default) = &schema.default {
+            result["default"] = default.clone();
+        }
+        set_examples(&mut result, schema.examples(), self.version);
+        if let Value::Object(obj) = &mut result {
+            obj.extend(schema.constraints.json_keywords());
+            for key in [
+                schema::SINCE,
+                schema::SCHEMA_VERSION,
+                schema::ENUM_SINCE,
+                schema::ENUM_DEPRECATED,
+                schema::ENUM_DISCRIMINANTS,
+            ] {
+                if let Some(value) = schema.extensions.get(key) {
+                    obj.insert(key.to_string(), value.clone());
+                }
+            }
+            for (key, value) in schema.vendor_extensions() {
+                obj.insert(key.to_string(), value.clone());
+            }
+        }
+        if self.strict {
+            close(&mut result);
+        }
+
+        self.wrap_ref(result)
+    }
+
+    /// A `required` list, sorted if the output is
+    fn required(&self, names: impl IntoIterator<Item = String>) -> Value {
+        let mut names: Vec<String> = names.into_iter().collect();
+        if self.order == OutputOrder::Sorted {
+            names.sort();
+        }
+        json!(names)
+    }
+
+    /// A Variant's cases in the order they're written
+    fn ordered_cases<'a>(&self, cases: &'a [VariantCase]) -> Vec<&'a VariantCase> {
+        let mut cases: Vec<&VariantCase> = cases.iter().collect();
+        if self.order == OutputOrder::Sorted {
+            cases.sort_by(|a, b| a.name.cmp(&b.name));
+        }
+        cases
+    }
+
+    /// A schema only `null` matches
+    fn null(&self) -> Value {
+        match self.version {
+            OpenApiVersion::V3_0 => json!({ "nullable": true, "enum": [null] }),
+            OpenApiVersion::V3_1 => json!({ "type": "null" }),
+        }
+    }
+
+    /// A string schema only `value` matches
+    fn constant(&self, value: &str) -> Value {
+        match self.version {
+            OpenApiVersion::V3_0 => json!({ "type": "string", "enum": [value] }),
+            OpenApiVersion::V3_1 => json!({ "type": "string", "const": value }),
+        }
+    }
+
+    /// A fixed-length array of `items`, one per position
+    ///
+    /// 3.0 has no per-position schemas, so each position accepts any of them.
+    fn tuple(&self, items: Vec<Value>) -> Value {
+        let len = items.len();
+        let items = match self.version {
+            OpenApiVersion::V3_1 => {
+                return json!({
                     "type": "array",
                     "prefixItems": items,
-                    "minItems": fields.len(),
-                    "maxItems": fields.len()
-                })
+                    "minItems": len,
+                    "maxItems": len
+                });
+            }
+            OpenApiVersion::V3_0 => {
+                let mut distinct: Vec<Value> = vec![];
+                for item in items {
+                    if !distinct.contains(&item) {
+                        distinct.push(item);
+                    }
+                }
+                match distinct.len() {
+                    1 => distinct.remove(0),
+                    _ => json!({ "anyOf": distinct }),
+                }
             }
+        };
+        json!({
+            "type": "array",
+            "items": items,
+            "minItems": len,
+            "maxItems": len
+        })
+    }
+
+    /// A `$ref` with annotations beside it, as an `allOf` for 3.0, which
+    /// ignores them otherwise
+    fn wrap_ref(&self, mut result: Value) -> Value {
+        if self.version == OpenApiVersion::V3_1 {
+            return result;
         }
-        TypeKind::Ref { name } => {
-            json!({
-                "$ref": format!("#/components/schemas/{}", name)
-            })
+        match result.as_object_mut() {
+            Some(obj) if obj.len() > 1 && obj.contains_key("$ref") => {
+                let target = obj.remove("$ref").unwrap();
+                obj.insert("allOf".to_string(), json!([{ "$ref": target }]));
+                result
+            }
+            _ => result,
+        }
+    }
+
+    /// One case of a Variant with an explicit [`Tagging`]: an object carrying
+    /// the case name in the tag property, or the bare payload when untagged
+    fn tagged_case(&mut self, case: &VariantCase, tagging: &Tagging) -> Value {
+        let mut obj = match (tagging, &case.data) {
+            (Tagging::Untagged, None) => self.null(),
+            (Tagging::Untagged, Some(data)) => self.convert(data),
+            (Tagging::Internal { tag } | Tagging::Adjacent { tag, .. }, None) => {
+                tag_object(tag, self.constant(&case.name))
+            }
+            (Tagging::Adjacent { tag, content }, Some(data)) => {
+                let mut obj = tag_object(tag, self.constant(&case.name));
+                obj["properties"][content] = self.convert(data);
+                obj["required"] = self.required([tag.clone(), content.clone()]);
+                obj
+            }
+            (Tagging::Internal { tag }, Some(data)) => {
+                let mut obj = tag_object(tag, self.constant(&case.name));
+                // A closed component can't share an `allOf` with the tag, so
+                // strict output merges the payload's properties in place
+                let payload = match self.strict {
+                    true => self.convert_inline(data),
+                    false => self.convert(data),
+                };
+                match payload["properties"].as_object() {
+                    // Record payloads share the object with the tag
+                    Some(properties) => {
+                        for (name, property) in properties {
+                            obj["properties"][name] = property.clone();
+                        }
+                        let required = payload["required"].as_array().into_iter().flatten();
+                        obj["required"] = self.required(
+                            std::iter::once(tag.clone())
+                                .chain(required.filter_map(|r| r.as_str().map(str::to_string))),
+                        );
+                        if let Some(closed) = payload.get("additionalProperties") {
+                            obj["additionalProperties"] = closed.clone();
+                        }
+                        obj
+                    }
+                    None => json!({ "allOf": [payload, obj] }),
+                }
+            }
+        };
+
+        if let Some(desc) = &case.description {
+            obj["description"] = json!(desc);
+        }
+        if let Some(since) = case.since() {
+            obj[schema::SINCE] = json!(since);
+        }
+        if case.deprecated() {
+            obj["deprecated"] = json!(true);
+        }
+        for (key, value) in case.vendor_extensions() {
+            obj[key] = value.clone();
         }
+        obj
+    }
+}
+
+/// An integer schema whose `format` tells clients which native type holds it
+///
+/// `int32` and `int64` are signed, so unsigned kinds add `minimum: 0`, and a
+/// `maximum` where the format is wider than the kind.
+fn integer(kind: &IntegerKind) -> Value {
+    let format = match kind {
+        IntegerKind::I32 | IntegerKind::U8 => "int32",
+        IntegerKind::I64 | IntegerKind::U32 | IntegerKind::U64 | IntegerKind::Usize => "int64",
     };
+    let mut obj = json!({ "type": "integer", "format": format });
+    if !kind.is_signed() {
+        obj["minimum"] = json!(0);
+    }
+    if matches!(kind, IntegerKind::U8 | IntegerKind::U32) {
+        obj["maximum"] = json!(kind.max() as u64);
+    }
+    obj
+}
+
+/// An object whose `tag` property holds the case name, as `constant`
+fn tag_object(tag: &str, constant: Value) -> Value {
+    json!({
+        "type": "object",
+        "properties": {
+            tag: constant
+        },
+        "required": [tag]
+    })
+}
 
-    // Add description if present
-    if let Some(desc) = &schema.description {
+/// Close an object schema to undeclared properties, along with the objects
+/// written in place as its `oneOf`/`anyOf` alternatives; maps, which already
+/// have `additionalProperties`, are left as they are
+fn close(schema: &mut Value) {
+    if schema.get("properties").is_some() && schema.get("additionalProperties").is_none() {
+        schema["additionalProperties"] = json!(false);
+    }
+    for keyword in ["oneOf", "anyOf"] {
+        if let Some(Value::Array(alternatives)) = schema.get_mut(keyword) {
+            alternatives.iter_mut().for_each(close);
+        }
+    }
+}
+
+/// A `$ref` target in `components/schemas`, escaped as a JSON Pointer
+/// segment
+fn reference(name: &str) -> String {
+    format!(
+        "#/components/schemas/{}",
+        name.replace('~', "~0").replace('/', "~1")
+    )
+}
+
+/// The annotations of a use of a named type: a title or description its
+/// component lacks, and every keyword that is only ever written beside a
+/// `$ref`
+fn annotate(
+    result: &mut Value,
+    schema: &SchemaType,
+    defined: &SchemaType,
+    version: OpenApiVersion,
+) {
+    if let Some(title) = &schema.title
+        && defined.title != schema.title
+    {
+        result["title"] = json!(title);
+    }
+    if let Some(desc) = &schema.description
+        && defined.description != schema.description
+    {
         result["description"] = json!(desc);
     }
+    if let Some(default) = &schema.default {
+        result["default"] = default.clone();
+    }
+    if schema.deprecated() {
+        result["deprecated"] = json!(true);
+    }
+    if schema.read_only() {
+        result["readOnly"] = json!(true);
+    }
+    if schema.write_only() {
+        result["writeOnly"] = json!(true);
+    }
+    if let Some(since) = schema.extensions.get(schema::SINCE) {
+        result[schema::SINCE] = since.clone();
+    }
+    set_examples(result, schema.examples(), version);
+}
+
+/// `examples` as 3.1's `examples` array, or as 3.0's single `example`,
+/// which only has room for the first
+fn set_examples(result: &mut Value, examples: &[Value], version: OpenApiVersion) {
+    let Some(first) = examples.first() else {
+        return;
+    };
+    match version {
+        OpenApiVersion::V3_0 => result["example"] = first.clone(),
+        OpenApiVersion::V3_1 => result["examples"] = json!(examples),
+    }
+}
+
+/// A named type without the keywords that belong to each use of it rather
+/// than to its component: a default, deprecation, readOnly/writeOnly,
+/// since and examples
+fn without_use_site(schema: &SchemaType) -> SchemaType {
+    let mut bare = schema.clone();
+    bare.default = None;
+    for key in [
+        schema::DEPRECATED,
+        schema::READ_ONLY,
+        schema::WRITE_ONLY,
+        schema::SINCE,
+        schema::EXAMPLES,
+        schema::REF_PREFERENCE,
+    ] {
+        bare.extensions.remove(key);
+    }
+    bare
+}
 
-    result
+/// A named type without any of the annotations a field adds to it; two
+/// occurrences are the same component when these match
+fn bare(schema: &SchemaType) -> SchemaType {
+    let mut bare = without_use_site(schema);
+    bare.title = None;
+    bare.description = None;
+    bare
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::collections::HashMap;
 
     #[test]
     fn test_basic_types() {
         #[derive(Schema)]
+        #[allow(dead_code)]
         struct Person {
             name: String,
             age: u32,
@@ -219,13 +951,14 @@ mod tests {
         assert_eq!(openapi["type"], "object");
         assert_eq!(openapi["properties"]["name"]["type"], "string");
         assert_eq!(openapi["properties"]["age"]["type"], "integer");
-        assert_eq!(openapi["required"][0], "name");
-        assert_eq!(openapi["required"][1], "age");
+        assert_eq!(openapi["required"][0], "age");
+        assert_eq!(openapi["required"][1], "name");
     }
 
     #[test]
     fn test_optional_fields() {
         #[derive(Schema)]
+        #[allow(dead_code)]
         struct User {
             id: String,
             email: Option<String>,
@@ -241,12 +974,14 @@ mod tests {
     #[test]
     fn test_nested_objects() {
         #[derive(Schema)]
+        #[allow(dead_code)]
         struct Address {
             street: String,
             city: String,
         }
 
         #[derive(Schema)]
+        #[allow(dead_code)]
         struct Person {
             name: String,
             address: Address,
@@ -268,6 +1003,7 @@ mod tests {
     #[test]
     fn test_arrays() {
         #[derive(Schema)]
+        #[allow(dead_code)]
         struct Team {
             name: String,
             members: Vec<String>,
@@ -282,6 +1018,7 @@ mod tests {
     #[test]
     fn test_simple_enum() {
         #[derive(Schema)]
+        #[allow(dead_code)]
         enum Status {
             Active,
             Inactive,
@@ -292,14 +1029,15 @@ mod tests {
         assert_eq!(openapi["type"], "string");
         let variants = openapi["enum"].as_array().unwrap();
         assert_eq!(variants.len(), 3);
-        assert!(variants.iter().any(|v| v == "active"));
-        assert!(variants.iter().any(|v| v == "inactive"));
-        assert!(variants.iter().any(|v| v == "pending"));
+        assert!(variants.iter().any(|v| v == "Active"));
+        assert!(variants.iter().any(|v| v == "Inactive"));
+        assert!(variants.iter().any(|v| v == "Pending"));
     }
 
     #[test]
     fn test_variant() {
         #[derive(Schema)]
+        #[allow(dead_code)]
         enum Message {
             Text {
                 content: String,
@@ -329,6 +1067,7 @@ mod tests {
     #[test]
     fn test_descriptions() {
         #[derive(Schema)]
+        #[allow(dead_code)]
         /// A user account
         struct User {
             /// Unique identifier
@@ -350,31 +1089,136 @@ mod tests {
     }
 
     #[test]
-    fn test_number_types() {
+    fn test_title_from_type_name() {
         #[derive(Schema)]
-        struct Metrics {
-            count: u32,
-            ratio: f32,
-            precise: f64,
+        #[allow(dead_code)]
+        struct Invoice {
+            lines: Vec<Line>,
         }
 
-        let openapi = to_openapi_schema::<Metrics>();
-        assert_eq!(openapi["properties"]["count"]["type"], "integer");
-        assert_eq!(openapi["properties"]["ratio"]["type"], "number");
-        assert_eq!(openapi["properties"]["precise"]["type"], "number");
-    }
-
-    #[test]
-    fn test_boolean() {
         #[derive(Schema)]
-        struct Settings {
-            enabled: bool,
-            verified: bool,
+        #[allow(dead_code)]
+        struct Line {
+            sku: String,
         }
 
-        let openapi = to_openapi_schema::<Settings>();
-        assert_eq!(openapi["properties"]["enabled"]["type"], "boolean");
-        assert_eq!(openapi["properties"]["verified"]["type"], "boolean");
+        let openapi = to_openapi_schema::<Invoice>();
+        assert_eq!(openapi["title"], "Invoice");
+        assert_eq!(openapi["properties"]["lines"]["items"]["title"], "Line");
+        assert!(openapi["properties"]["lines"].get("title").is_none());
+
+        // A hand-written impl that only names itself through `type_name`
+        struct Sku;
+        impl Schema for Sku {
+            fn schema() -> SchemaType {
+                SchemaType::new(TypeKind::String)
+            }
+
+            fn type_name() -> Option<&'static str> {
+                Some("Sku")
+            }
+        }
+
+        let components = to_openapi_components::<Sku>().unwrap();
+        assert_eq!(
+            components.root,
+            json!({ "$ref": "#/components/schemas/Sku" })
+        );
+        assert_eq!(
+            components.schemas["Sku"],
+            json!({ "type": "string", "title": "Sku" })
+        );
+    }
+
+    #[test]
+    fn test_title_from_doc_paragraphs() {
+        /// Order
+        ///
+        /// A customer's order, from checkout to delivery.
+        #[derive(Schema)]
+        #[allow(dead_code)]
+        struct Order {
+            id: String,
+        }
+
+        let openapi = to_openapi_schema::<Order>();
+        assert_eq!(openapi["title"], "Order");
+        assert_eq!(
+            openapi["description"],
+            "A customer's order, from checkout to delivery."
+        );
+    }
+
+    #[test]
+    fn test_number_types() {
+        #[derive(Schema)]
+        #[allow(dead_code)]
+        struct Metrics {
+            count: u32,
+            ratio: f32,
+            precise: f64,
+        }
+
+        let openapi = to_openapi_schema::<Metrics>();
+        assert_eq!(openapi["properties"]["count"]["type"], "integer");
+        assert_eq!(openapi["properties"]["ratio"]["type"], "number");
+        assert_eq!(openapi["properties"]["precise"]["type"], "number");
+        assert_eq!(openapi["properties"]["ratio"]["format"], "float");
+        assert_eq!(openapi["properties"]["precise"]["format"], "double");
+    }
+
+    #[test]
+    fn test_integer_formats() {
+        #[derive(Schema)]
+        #[allow(dead_code)]
+        struct Counters {
+            small: i32,
+            large: i64,
+            byte: u8,
+            count: u32,
+            total: u64,
+            #[schema(minimum = 1)]
+            page: u32,
+        }
+
+        let openapi = to_openapi_schema::<Counters>();
+        let properties = &openapi["properties"];
+        assert_eq!(
+            properties["small"],
+            json!({ "type": "integer", "format": "int32" })
+        );
+        assert_eq!(
+            properties["large"],
+            json!({ "type": "integer", "format": "int64" })
+        );
+        assert_eq!(
+            properties["byte"],
+            json!({ "type": "integer", "format": "int32", "minimum": 0, "maximum": 255 })
+        );
+        assert_eq!(
+            properties["count"],
+            json!({ "type": "integer", "format": "int64", "minimum": 0, "maximum": 4294967295u64 })
+        );
+        assert_eq!(
+            properties["total"],
+            json!({ "type": "integer", "format": "int64", "minimum": 0 })
+        );
+        // Declared bounds win over the kind's
+        assert_eq!(properties["page"]["minimum"], json!(1));
+    }
+
+    #[test]
+    fn test_boolean() {
+        #[derive(Schema)]
+        #[allow(dead_code)]
+        struct Settings {
+            enabled: bool,
+            verified: bool,
+        }
+
+        let openapi = to_openapi_schema::<Settings>();
+        assert_eq!(openapi["properties"]["enabled"]["type"], "boolean");
+        assert_eq!(openapi["properties"]["verified"]["type"], "boolean");
     }
 
     #[test]
@@ -408,4 +1252,706 @@ mod tests {
         assert_eq!(items[0]["type"], "integer");
         assert_eq!(items[1]["type"], "string");
     }
+
+    #[test]
+    fn test_recursive_type_with_options() {
+        use schema::Fallback;
+
+        #[derive(Schema)]
+        #[allow(dead_code)]
+        struct Comment {
+            body: String,
+            replies: Vec<Comment>,
+        }
+
+        let openapi = to_openapi_schema::<Comment>();
+        assert!(openapi["properties"]["replies"]["items"]["$ref"].is_string());
+
+        let options = GenerateOptions::new().on_cycle(Fallback::AnyObject);
+        let openapi = to_openapi_schema_with_options::<Comment>(&options);
+        assert_eq!(openapi["properties"]["replies"]["items"]["type"], "object");
+    }
+
+    #[test]
+    fn test_docs_pages_embed_document() {
+        let document = json!({
+            "openapi": "3.0.3",
+            "info": { "title": "Pets & Co", "version": "1.0.0" },
+            "paths": {},
+            "components": { "schemas": { "Note": { "description": "</script><b>" } } }
+        });
+
+        let swagger = docs::swagger_ui_html(&document, "/static/swagger-ui");
+        assert!(swagger.contains("<title>Pets &amp; Co</title>"));
+        assert!(swagger.contains(r#"<script src="/static/swagger-ui/swagger-ui-bundle.js">"#));
+        // Nothing is fetched from elsewhere
+        assert!(!swagger.contains("https://"));
+        assert!(swagger.contains("\\u003c/script>"));
+        assert_eq!(swagger.matches("</script>").count(), 2);
+
+        let redoc = docs::DocsPage::new(docs::DocsUi::Redoc, "/static/redoc/")
+            .title("Pets")
+            .render(&document);
+        assert!(redoc.contains("<title>Pets</title>"));
+        assert!(redoc.contains(r#"<script src="/static/redoc/redoc.standalone.js">"#));
+        assert!(redoc.contains("Redoc.init({"));
+    }
+
+    #[test]
+    fn test_constraint_keywords() {
+        #[derive(Schema)]
+        #[allow(dead_code)]
+        struct Query {
+            #[schema(min_length = 1, pattern = "^[a-z]+$")]
+            term: String,
+            #[schema(minimum = 1, maximum = 50)]
+            limit: Option<u32>,
+            #[schema(max_items = 5)]
+            tags: Vec<String>,
+        }
+
+        let openapi = to_openapi_schema::<Query>();
+        assert_eq!(openapi["properties"]["term"]["minLength"], 1);
+        assert_eq!(openapi["properties"]["term"]["pattern"], "^[a-z]+$");
+        assert_eq!(openapi["properties"]["limit"]["minimum"], 1);
+        assert_eq!(openapi["properties"]["limit"]["maximum"], 50);
+        assert_eq!(openapi["properties"]["tags"]["maxItems"], 5);
+    }
+
+    #[test]
+    fn test_since_extension() {
+        #[derive(Schema)]
+        #[allow(dead_code)]
+        struct Profile {
+            name: String,
+            #[schema(since = "1.4.0")]
+            avatar: Option<String>,
+        }
+
+        #[derive(Schema)]
+        #[allow(dead_code)]
+        enum Event {
+            Created {
+                id: u64,
+            },
+            #[schema(since = "2.1.0")]
+            Archived,
+        }
+
+        let openapi = to_openapi_schema::<Profile>();
+        assert_eq!(openapi["properties"]["avatar"]["x-since"], "1.4.0");
+        assert!(openapi["properties"]["name"].get("x-since").is_none());
+
+        let openapi = to_openapi_schema::<Event>();
+        assert_eq!(openapi["oneOf"][0]["x-since"], "2.1.0");
+        assert!(openapi["oneOf"][1].get("x-since").is_none());
+    }
+
+    #[test]
+    fn test_tagged_variants() {
+        #[derive(Schema)]
+        #[schema(tag = "kind")]
+        #[allow(dead_code)]
+        enum Shape {
+            Circle { radius: f64 },
+            Label(String),
+            Empty,
+        }
+
+        #[derive(Schema)]
+        #[schema(tag = "t", content = "c")]
+        #[allow(dead_code)]
+        enum Message {
+            Text(String),
+            Ping,
+        }
+
+        let openapi = to_openapi_schema::<Shape>();
+        assert_eq!(openapi["discriminator"]["propertyName"], "kind");
+        let circle = &openapi["oneOf"][0];
+        assert_eq!(circle["properties"]["kind"]["const"], "Circle");
+        assert_eq!(circle["properties"]["radius"]["type"], "number");
+        assert_eq!(circle["required"], json!(["kind", "radius"]));
+        // Non-record payloads can't share an object with the tag
+        assert_eq!(openapi["oneOf"][1]["required"], json!(["kind"]));
+        assert_eq!(openapi["oneOf"][2]["allOf"][0]["type"], "string");
+
+        let openapi = to_openapi_schema::<Message>();
+        assert_eq!(openapi["oneOf"][0]["required"], json!(["t"]));
+        let text = &openapi["oneOf"][1];
+        assert_eq!(text["properties"]["t"]["const"], "Text");
+        assert_eq!(text["properties"]["c"]["type"], "string");
+        assert_eq!(text["required"], json!(["c", "t"]));
+    }
+
+    #[test]
+    fn test_untagged_variant() {
+        #[derive(Schema)]
+        #[schema(untagged)]
+        #[allow(dead_code)]
+        enum Id {
+            Number(u64),
+            Named { name: String },
+            Unknown,
+        }
+
+        let openapi = to_openapi_schema::<Id>();
+        assert!(openapi.get("oneOf").is_none());
+        assert!(openapi.get("discriminator").is_none());
+        assert_eq!(openapi["anyOf"][0]["properties"]["name"]["type"], "string");
+        assert_eq!(openapi["anyOf"][1]["type"], "integer");
+        assert_eq!(openapi["anyOf"][2]["type"], "null");
+    }
+
+    #[test]
+    fn test_deny_unknown_fields() {
+        #[derive(Schema)]
+        #[schema(deny_unknown_fields)]
+        #[allow(dead_code)]
+        struct Credentials {
+            user: String,
+        }
+
+        #[derive(Schema)]
+        #[schema(tag = "kind", deny_unknown_fields)]
+        #[allow(dead_code)]
+        enum Auth {
+            Password { secret: String },
+            Anonymous,
+        }
+
+        let openapi = to_openapi_schema::<Credentials>();
+        assert_eq!(openapi["additionalProperties"], json!(false));
+
+        // The tag shares the closed object with the case's fields
+        let openapi = to_openapi_schema::<Auth>();
+        assert_eq!(openapi["oneOf"][1]["additionalProperties"], json!(false));
+        assert_eq!(
+            openapi["oneOf"][1]["properties"]["kind"]["const"],
+            "Password"
+        );
+    }
+
+    #[test]
+    fn test_strict() {
+        #[derive(Schema)]
+        #[allow(dead_code)]
+        struct Card {
+            number: String,
+        }
+
+        #[derive(Schema)]
+        #[schema(tag = "method")]
+        #[allow(dead_code)]
+        enum Payment {
+            Card(Card),
+            Cash { change: u32 },
+        }
+
+        #[derive(Schema)]
+        #[allow(dead_code)]
+        struct Checkout {
+            payment: Payment,
+            metadata: HashMap<String, String>,
+            result: Result<Card, String>,
+        }
+
+        let options = OpenApiOptions::new().strict();
+        let openapi = to_openapi_schema_with::<Checkout>(&options);
+        assert_eq!(openapi["additionalProperties"], json!(false));
+        // Maps keep their value schema
+        assert_eq!(
+            openapi["properties"]["metadata"]["additionalProperties"],
+            json!({ "type": "string" })
+        );
+        for case in openapi["properties"]["payment"]["oneOf"]
+            .as_array()
+            .unwrap()
+        {
+            assert_eq!(case["additionalProperties"], json!(false));
+        }
+        for branch in openapi["properties"]["result"]["oneOf"].as_array().unwrap() {
+            assert_eq!(branch["additionalProperties"], json!(false));
+        }
+        assert!(
+            to_openapi_schema::<Checkout>()
+                .get("additionalProperties")
+                .is_none()
+        );
+
+        // A named payload is merged with the tag rather than referenced, as
+        // the closed component would reject the tag
+        let components =
+            schema_type_to_openapi_components_with(&Checkout::schema(), &options).unwrap();
+        let card = &components.schemas["Payment"]["oneOf"][0];
+        assert_eq!(card["properties"]["number"], json!({ "type": "string" }));
+        assert_eq!(card["required"], json!(["method", "number"]));
+        assert_eq!(card["additionalProperties"], json!(false));
+        assert_eq!(
+            components.schemas["Checkout"]["additionalProperties"],
+            json!(false)
+        );
+    }
+
+    #[derive(Schema)]
+    #[allow(dead_code)]
+    enum Shape {
+        Square(f64),
+        Circle(f64),
+    }
+
+    #[derive(Schema)]
+    #[allow(dead_code)]
+    struct Drawing {
+        title: String,
+        shape: Shape,
+        author: String,
+    }
+
+    #[test]
+    fn test_sorted_output_order() {
+        let sorted = to_openapi_schema::<Drawing>();
+        assert_eq!(sorted["required"], json!(["author", "shape", "title"]));
+        let keys: Vec<&String> = sorted["properties"].as_object().unwrap().keys().collect();
+        assert_eq!(keys, ["author", "shape", "title"]);
+        let cases = &sorted["properties"]["shape"]["oneOf"];
+        assert_eq!(cases[0]["properties"]["type"]["const"], "Circle");
+        assert_eq!(cases[1]["properties"]["type"]["const"], "Square");
+
+        // The default, and stable from build to build
+        assert_eq!(
+            serde_json::to_string(&sorted).unwrap(),
+            serde_json::to_string(&to_openapi_schema_with::<Drawing>(
+                &OpenApiOptions::new().order(OutputOrder::Sorted)
+            ))
+            .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_declaration_output_order() {
+        let declared = to_openapi_schema_with::<Drawing>(
+            &OpenApiOptions::new().order(OutputOrder::Declaration),
+        );
+        assert_eq!(declared["required"], json!(["title", "shape", "author"]));
+        let cases = &declared["properties"]["shape"]["oneOf"];
+        assert_eq!(cases[0]["properties"]["type"]["const"], "Square");
+        assert_eq!(cases[1]["properties"]["type"]["const"], "Circle");
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn test_deprecated() {
+        #[derive(Schema)]
+        #[allow(dead_code)]
+        struct Job {
+            #[deprecated(note = "use `cron`")]
+            interval: Option<u32>,
+            cron: Option<String>,
+        }
+
+        #[derive(Schema)]
+        #[allow(dead_code)]
+        enum Level {
+            Low,
+            #[deprecated]
+            Medium,
+            High,
+        }
+
+        let openapi = to_openapi_schema::<Job>();
+        assert_eq!(openapi["properties"]["interval"]["deprecated"], json!(true));
+        assert_eq!(
+            openapi["properties"]["interval"]["description"],
+            "Deprecated: use `cron`"
+        );
+        assert!(openapi["properties"]["cron"].get("deprecated").is_none());
+
+        let openapi = to_openapi_schema::<Level>();
+        assert_eq!(openapi[schema::ENUM_DEPRECATED], json!(["Medium"]));
+    }
+
+    #[test]
+    fn test_schema_version() {
+        #[derive(Schema)]
+        #[schema(version = "1.3.0")]
+        #[allow(dead_code)]
+        struct Quote {
+            amount: f64,
+        }
+
+        let openapi = to_openapi_schema::<Quote>();
+        assert_eq!(openapi[schema::SCHEMA_VERSION], "1.3.0");
+    }
+
+    #[test]
+    fn test_enum_discriminants() {
+        #[derive(Schema)]
+        #[allow(dead_code)]
+        enum Status {
+            Active = 1,
+            Suspended = 2,
+        }
+
+        let openapi = to_openapi_schema::<Status>();
+        assert_eq!(openapi["enum"], json!(["Active", "Suspended"]));
+        assert_eq!(
+            openapi[schema::ENUM_DISCRIMINANTS],
+            json!({ "Active": 1, "Suspended": 2 })
+        );
+    }
+
+    #[test]
+    fn test_read_write_only() {
+        #[derive(Schema)]
+        #[allow(dead_code)]
+        struct Token {
+            #[schema(read_only)]
+            issued_at: u64,
+            #[schema(write_only)]
+            password: String,
+        }
+
+        let openapi = to_openapi_schema::<Token>();
+        assert_eq!(openapi["properties"]["issued_at"]["readOnly"], json!(true));
+        assert_eq!(openapi["properties"]["password"]["writeOnly"], json!(true));
+        assert!(openapi["properties"]["password"].get("readOnly").is_none());
+    }
+
+    #[test]
+    fn test_defaults_and_examples() {
+        #[derive(Schema)]
+        #[schema(example = { "login": "ada", "retries": 3 })]
+        #[allow(dead_code)]
+        struct Account {
+            #[schema(example = "ada", example = "grace")]
+            login: String,
+            #[schema(default, example = 3)]
+            retries: u32,
+        }
+
+        let openapi = to_openapi_schema::<Account>();
+        assert_eq!(
+            openapi["examples"],
+            json!([{ "login": "ada", "retries": 3 }])
+        );
+        assert_eq!(
+            openapi["properties"]["login"]["examples"],
+            json!(["ada", "grace"])
+        );
+        assert_eq!(openapi["properties"]["retries"]["default"], json!(0));
+        assert_eq!(openapi["properties"]["retries"]["examples"], json!([3]));
+        assert!(openapi.get(schema::EXAMPLES).is_none());
+
+        // 3.0 has a single `example`
+        let openapi = to_openapi_schema_for::<Account>(OpenApiVersion::V3_0);
+        assert_eq!(openapi["properties"]["login"]["example"], json!("ada"));
+        assert!(openapi["properties"]["login"].get("examples").is_none());
+    }
+
+    #[test]
+    fn test_vendor_extensions() {
+        #[derive(Schema)]
+        #[schema(extension("x-internal" = true), wit(name = "box"))]
+        #[allow(dead_code)]
+        struct Parcel {
+            #[schema(extension("x-order" = 2), read_only)]
+            weight: u32,
+        }
+
+        let openapi = to_openapi_schema::<Parcel>();
+        assert_eq!(openapi["x-internal"], json!(true));
+        assert_eq!(openapi["properties"]["weight"]["x-order"], json!(2));
+        // Interpreted keys and backend hints don't leak through
+        assert!(openapi.get("x-wit-name").is_none());
+        assert!(openapi["properties"]["weight"].get("x-read-only").is_none());
+    }
+
+    #[test]
+    fn test_components() {
+        #[derive(Schema)]
+        #[allow(dead_code)]
+        struct Address {
+            city: String,
+        }
+
+        #[derive(Schema)]
+        #[allow(dead_code)]
+        struct Comment {
+            body: String,
+            replies: Vec<Comment>,
+        }
+
+        #[derive(Schema)]
+        #[allow(dead_code)]
+        struct Order {
+            shipping: Option<Address>,
+            /// Where the invoice goes
+            billing: Address,
+            notes: Vec<Comment>,
+        }
+
+        let components = to_openapi_components::<Order>().unwrap();
+        assert_eq!(
+            components.root,
+            json!({ "$ref": "#/components/schemas/Order" })
+        );
+        assert_eq!(
+            components.schemas.keys().collect::<Vec<_>>(),
+            ["Address", "Comment", "Order"]
+        );
+        let order = &components.schemas["Order"];
+        assert_eq!(
+            order["properties"]["billing"],
+            json!({
+                "$ref": "#/components/schemas/Address",
+                "description": "Where the invoice goes"
+            })
+        );
+        assert_eq!(
+            order["properties"]["shipping"],
+            json!({ "$ref": "#/components/schemas/Address" })
+        );
+        assert_eq!(
+            components.schemas["Comment"]["properties"]["replies"]["items"],
+            json!({ "$ref": "#/components/schemas/Comment" })
+        );
+        assert_eq!(
+            components.schemas["Address"]["properties"]["city"],
+            json!({ "type": "string" })
+        );
+    }
+
+    #[test]
+    fn test_component_field_annotations() {
+        /// A postal address
+        #[derive(Schema)]
+        #[allow(dead_code)]
+        struct Address {
+            city: String,
+        }
+
+        #[derive(Schema)]
+        #[allow(dead_code)]
+        struct Order {
+            /// Set by the server
+            #[schema(read_only)]
+            #[deprecated]
+            created_from: Address,
+            shipping: Address,
+        }
+
+        // The first use doesn't decide what the others inherit
+        let components = to_openapi_components::<Order>().unwrap();
+        let address = &components.schemas["Address"];
+        for keyword in ["description", "readOnly", "deprecated"] {
+            assert!(address.get(keyword).is_none(), "{keyword}");
+        }
+        let order = &components.schemas["Order"];
+        assert_eq!(
+            order["properties"]["created_from"],
+            json!({
+                "$ref": "#/components/schemas/Address",
+                "description": "Set by the server",
+                "readOnly": true,
+                "deprecated": true,
+            })
+        );
+        assert_eq!(
+            order["properties"]["shipping"],
+            json!({
+                "$ref": "#/components/schemas/Address",
+                "description": "A postal address",
+            })
+        );
+
+        // Used directly, the type's own annotations go on its component
+        let components = to_openapi_components::<Address>().unwrap();
+        assert_eq!(
+            components.schemas["Address"]["description"],
+            "A postal address"
+        );
+    }
+
+    #[test]
+    fn test_component_use_site_keywords() {
+        #[derive(Schema)]
+        #[schema(example = { "cents": 100 })]
+        #[allow(dead_code)]
+        struct Money {
+            cents: i64,
+        }
+
+        #[derive(Schema)]
+        #[allow(dead_code)]
+        struct Invoice {
+            #[schema(read_only, default = "zero", example = { "cents": 250 })]
+            total: Money,
+            #[schema(write_only)]
+            tip: Money,
+            discount: Money,
+        }
+
+        fn zero() -> serde_json::Value {
+            json!({ "cents": 0 })
+        }
+
+        let components = to_openapi_components::<Invoice>().unwrap();
+        let money = &components.schemas["Money"];
+        for keyword in ["readOnly", "writeOnly", "default", "examples"] {
+            assert!(money.get(keyword).is_none(), "{keyword}");
+        }
+        let invoice = &components.schemas["Invoice"];
+        let reference = "#/components/schemas/Money";
+        assert_eq!(
+            invoice["properties"]["total"],
+            json!({
+                "$ref": reference,
+                "readOnly": true,
+                "default": { "cents": 0 },
+                "examples": [{ "cents": 250 }],
+            })
+        );
+        assert_eq!(
+            invoice["properties"]["tip"],
+            json!({ "$ref": reference, "writeOnly": true, "examples": [{ "cents": 100 }] })
+        );
+        // A later plain use isn't read-only just because an earlier one was
+        assert_eq!(
+            invoice["properties"]["discount"],
+            json!({ "$ref": reference, "examples": [{ "cents": 100 }] })
+        );
+    }
+
+    #[test]
+    fn test_component_name_collision() {
+        mod v1 {
+            #[derive(schema::Schema)]
+            #[allow(dead_code)]
+            pub struct User {
+                pub name: String,
+            }
+        }
+        mod v2 {
+            #[derive(schema::Schema)]
+            #[allow(dead_code)]
+            pub struct User {
+                pub id: u64,
+            }
+        }
+
+        #[derive(Schema)]
+        #[allow(dead_code)]
+        struct Migration {
+            from: v1::User,
+            to: v2::User,
+        }
+
+        assert_eq!(
+            to_openapi_components::<Migration>(),
+            Err(ComponentsError::NameCollision("User".to_string()))
+        );
+        // Unnamed roots stay in place
+        let components = to_openapi_components::<Vec<v1::User>>().unwrap();
+        assert_eq!(
+            components.root,
+            json!({ "type": "array", "items": { "$ref": "#/components/schemas/User" } })
+        );
+    }
+
+    #[test]
+    fn test_openapi_versions() {
+        #[derive(Schema)]
+        #[allow(dead_code)]
+        enum Shape {
+            Point,
+            Circle { radius: f64 },
+        }
+
+        #[derive(Schema)]
+        #[allow(dead_code)]
+        struct Address {
+            city: String,
+        }
+
+        #[derive(Schema)]
+        #[allow(dead_code)]
+        struct Drawing {
+            shape: Shape,
+            origin: (i32, i32),
+            label: (i32, String),
+            home: Address,
+            /// Where it was drawn
+            at: Address,
+        }
+
+        let v3_1 = schema_type_to_openapi_components_for(&Drawing::schema(), OpenApiVersion::V3_1)
+            .unwrap()
+            .schemas;
+        let drawing = &v3_1["Drawing"]["properties"];
+        assert_eq!(
+            drawing["label"]["prefixItems"][1],
+            json!({ "type": "string" })
+        );
+        assert_eq!(
+            drawing["at"],
+            json!({ "$ref": "#/components/schemas/Address", "description": "Where it was drawn" })
+        );
+        assert_eq!(v3_1["Shape"]["oneOf"][1]["const"], "Point");
+
+        let v3_0 = schema_type_to_openapi_components_for(&Drawing::schema(), OpenApiVersion::V3_0)
+            .unwrap()
+            .schemas;
+        let drawing = &v3_0["Drawing"]["properties"];
+        assert_eq!(
+            drawing["origin"],
+            json!({
+                "type": "array",
+                "items": { "type": "integer", "format": "int32" },
+                "minItems": 2,
+                "maxItems": 2
+            })
+        );
+        assert_eq!(
+            drawing["label"]["items"],
+            json!({ "anyOf": [{ "type": "integer", "format": "int32" }, { "type": "string" }] })
+        );
+        assert_eq!(
+            drawing["at"],
+            json!({
+                "allOf": [{ "$ref": "#/components/schemas/Address" }],
+                "description": "Where it was drawn"
+            })
+        );
+        assert_eq!(
+            v3_0["Shape"]["oneOf"][1],
+            json!({ "type": "string", "enum": ["Point"] })
+        );
+        assert_eq!(
+            v3_0["Shape"]["oneOf"][0]["properties"]["type"],
+            json!({ "type": "string", "enum": ["Circle"] })
+        );
+        // Neither 3.1-only keyword appears anywhere
+        let text = serde_json::to_string(&v3_0).unwrap();
+        assert!(!text.contains("prefixItems") && !text.contains("\"const\""));
+    }
+
+    #[test]
+    fn test_openapi_3_0_null() {
+        #[derive(Schema)]
+        #[schema(untagged)]
+        #[allow(dead_code)]
+        enum Limit {
+            Count(u32),
+            Unlimited,
+        }
+
+        let openapi = to_openapi_schema_for::<Limit>(OpenApiVersion::V3_0);
+        assert_eq!(
+            openapi["anyOf"][1],
+            json!({ "nullable": true, "enum": [null] })
+        );
+        let openapi = to_openapi_schema_for::<Limit>(OpenApiVersion::V3_1);
+        assert_eq!(openapi["anyOf"][1], json!({ "type": "null" }));
+    }
 }
@@ -0,0 +1,272 @@
+//! Parameter Objects from object schemas
+//!
+//! Each property of a typed query, path, header or cookie struct becomes one
+//! parameter, required when the property is. Arrays and objects are given
+//! an explicit `style` and `explode`: repeated keys (`?tag=a&tag=b`) and
+//! `deepObject` brackets (`?filter[status]=open`) in queries, and
+//! comma-separated values in paths and headers.
+
+use crate::{OpenApiVersion, Registry, named_schema};
+use schema::{GenerateOptions, Schema, SchemaType, TypeKind};
+use serde_json::{Value, json};
+use std::fmt;
+
+/// Where a parameter is sent
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParameterLocation {
+    Query,
+    Path,
+    Header,
+    Cookie,
+}
+
+impl ParameterLocation {
+    /// The parameter's `in` field
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ParameterLocation::Query => "query",
+            ParameterLocation::Path => "path",
+            ParameterLocation::Header => "header",
+            ParameterLocation::Cookie => "cookie",
+        }
+    }
+}
+
+/// Why a schema has no parameters
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParameterError {
+    /// The schema is not an object, so it has no properties to send
+    NotAnObject,
+}
+
+impl fmt::Display for ParameterError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParameterError::NotAnObject => {
+                write!(f, "only object schemas split into parameters")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ParameterError {}
+
+/// One parameter of an operation
+#[derive(Debug, Clone)]
+pub struct Parameter {
+    pub name: String,
+    pub location: ParameterLocation,
+    /// Always true in the path, where every parameter is
+    pub required: bool,
+    pub description: Option<String>,
+    pub deprecated: bool,
+    pub schema: SchemaType,
+}
+
+impl Parameter {
+    /// An optional parameter of `T`; path parameters are always required
+    pub fn new<T: Schema>(name: &str, location: ParameterLocation) -> Self {
+        Self::from_schema(name, location, named_schema::<T>())
+    }
+
+    /// An optional parameter of an explicit schema, carrying the schema's
+    /// description and deprecation
+    pub fn from_schema(name: &str, location: ParameterLocation, mut schema: SchemaType) -> Self {
+        let description = schema.description.take();
+        Parameter {
+            name: name.to_string(),
+            location,
+            required: location == ParameterLocation::Path,
+            description,
+            deprecated: schema.deprecated(),
+            schema,
+        }
+    }
+
+    /// One parameter per property of an object schema
+    pub fn from_object(
+        schema: &SchemaType,
+        location: ParameterLocation,
+    ) -> Result<Vec<Parameter>, ParameterError> {
+        let TypeKind::Object { required, .. } = &schema.kind else {
+            return Err(ParameterError::NotAnObject);
+        };
+        Ok(schema
+            .ordered_properties()
+            .into_iter()
+            .map(|(name, property)| {
+                let mut parameter = Parameter::from_schema(name, location, property.clone());
+                parameter.required |= required.iter().any(|r| r == name);
+                parameter
+            })
+            .collect())
+    }
+
+    pub fn required(mut self) -> Self {
+        self.required = true;
+        self
+    }
+
+    pub fn description(mut self, description: &str) -> Self {
+        self.description = Some(description.to_string());
+        self
+    }
+
+    /// How an array or object value is serialized, where the default
+    /// wouldn't be obvious
+    fn style(&self) -> Option<(&'static str, bool)> {
+        let structured = match &self.schema.kind {
+            TypeKind::Array { .. } | TypeKind::Set { .. } | TypeKind::Tuple { .. } => false,
+            TypeKind::Object { .. } | TypeKind::Map { .. } => true,
+            _ => return None,
+        };
+        Some(match self.location {
+            ParameterLocation::Query if structured => ("deepObject", true),
+            ParameterLocation::Query | ParameterLocation::Cookie => ("form", true),
+            ParameterLocation::Path | ParameterLocation::Header => ("simple", false),
+        })
+    }
+
+    /// The Parameter Object, with named types added to `registry`
+    pub(crate) fn to_json(&self, registry: &mut Registry, options: &GenerateOptions) -> Value {
+        let mut parameter = json!({
+            "name": self.name,
+            "in": self.location.as_str(),
+            "required": self.required,
+        });
+        if let Some(description) = &self.description {
+            parameter["description"] = json!(description);
+        }
+        if self.deprecated {
+            parameter["deprecated"] = json!(true);
+        }
+        if let Some((style, explode)) = self.style() {
+            parameter["style"] = json!(style);
+            parameter["explode"] = json!(explode);
+        }
+        parameter["schema"] = registry.convert(&options.apply(&self.schema));
+        parameter
+    }
+}
+
+/// The Parameter Objects for each property of `T`, sent in `location`
+pub fn to_openapi_parameters<T: Schema>(
+    location: ParameterLocation,
+) -> Result<Vec<Value>, ParameterError> {
+    schema_type_to_openapi_parameters(&named_schema::<T>(), location)
+}
+
+/// The Parameter Objects for each property of an object SchemaType, sent in
+/// `location`, with every type in place
+pub fn schema_type_to_openapi_parameters(
+    schema: &SchemaType,
+    location: ParameterLocation,
+) -> Result<Vec<Value>, ParameterError> {
+    let mut registry = Registry::inline(OpenApiVersion::default());
+    let options = GenerateOptions::default();
+    Ok(Parameter::from_object(schema, location)?
+        .iter()
+        .map(|parameter| parameter.to_json(&mut registry, &options))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{OpenApiBuilder, Operation};
+    use std::collections::HashMap;
+
+    #[derive(Schema)]
+    #[allow(dead_code)]
+    struct ListUsers {
+        /// Page size
+        limit: Option<u32>,
+        role: Vec<String>,
+        filter: Option<HashMap<String, String>>,
+    }
+
+    #[test]
+    fn test_query_parameters() {
+        let parameters = to_openapi_parameters::<ListUsers>(ParameterLocation::Query).unwrap();
+        assert_eq!(
+            parameters,
+            [
+                json!({
+                    "name": "limit",
+                    "in": "query",
+                    "required": false,
+                    "description": "Page size",
+                    "schema": {
+                        "type": "integer",
+                        "format": "int64",
+                        "minimum": 0,
+                        "maximum": u32::MAX,
+                    },
+                }),
+                json!({
+                    "name": "role",
+                    "in": "query",
+                    "required": true,
+                    "style": "form",
+                    "explode": true,
+                    "schema": { "type": "array", "items": { "type": "string" } },
+                }),
+                json!({
+                    "name": "filter",
+                    "in": "query",
+                    "required": false,
+                    "description": "Unordered map/dictionary of key-value pairs",
+                    "style": "deepObject",
+                    "explode": true,
+                    "schema": {
+                        "type": "object",
+                        "additionalProperties": { "type": "string" },
+                    },
+                }),
+            ]
+        );
+        assert_eq!(
+            to_openapi_parameters::<String>(ParameterLocation::Query),
+            Err(ParameterError::NotAnObject)
+        );
+    }
+
+    #[test]
+    fn test_operation_parameters() {
+        #[derive(Schema)]
+        #[allow(dead_code)]
+        struct UserPath {
+            id: Option<u64>,
+        }
+
+        let operation = Operation::get("/users/{id}")
+            .parameters::<UserPath>(ParameterLocation::Path)
+            .unwrap()
+            .parameter(Parameter::new::<String>(
+                "X-Request-Id",
+                ParameterLocation::Header,
+            ))
+            .response::<String>("200", "The user");
+        let document = OpenApiBuilder::new("Users", "1.0.0")
+            .operation(operation)
+            .build()
+            .unwrap();
+        assert_eq!(
+            document["paths"]["/users/{id}"]["get"]["parameters"],
+            json!([
+                {
+                    "name": "id",
+                    "in": "path",
+                    "required": true,
+                    "schema": { "type": "integer", "format": "int64", "minimum": 0 },
+                },
+                {
+                    "name": "X-Request-Id",
+                    "in": "header",
+                    "required": false,
+                    "schema": { "type": "string" },
+                },
+            ])
+        );
+    }
+}
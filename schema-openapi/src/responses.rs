@@ -0,0 +1,133 @@
+//! Common response shapes
+//!
+//! [`Page<T>`] is a cursor-paginated list and [`Problem`] an RFC 7807
+//! `application/problem+json` error, so every service documents (and, with
+//! serde, sends) them the same way. Each `Page<T>` is its own component,
+//! named like `Page_User`.
+
+use crate::{Operation, Response};
+use schema::{Schema, SchemaType, TypeKind};
+use serde::{Deserialize, Serialize};
+
+/// The media type of [`Problem`] bodies
+pub const PROBLEM_JSON: &str = "application/problem+json";
+
+/// One page of a cursor-paginated list
+#[derive(Debug, Clone, PartialEq, Schema, Serialize, Deserialize)]
+pub struct Page<T> {
+    /// The items on this page
+    pub items: Vec<T>,
+    /// Cursor to request the next page with; absent on the last page
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub next_cursor: Option<String>,
+}
+
+/// An RFC 7807 problem detail
+///
+/// Problem types can add their own members beside these.
+#[derive(Debug, Clone, Default, PartialEq, Schema, Serialize, Deserialize)]
+pub struct Problem {
+    /// URI identifying the problem type; `about:blank` when absent
+    #[schema(rename = "type", with = "uri_reference")]
+    #[serde(rename = "type", default, skip_serializing_if = "Option::is_none")]
+    pub problem_type: Option<String>,
+    /// Short summary of the problem type
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    /// The HTTP status code of this occurrence
+    #[schema(minimum = 100, maximum = 599)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status: Option<u32>,
+    /// Explanation specific to this occurrence
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+    /// URI identifying this occurrence
+    #[schema(with = "uri_reference")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub instance: Option<String>,
+}
+
+fn uri_reference() -> SchemaType {
+    let mut schema = SchemaType::new(TypeKind::String);
+    schema.constraints.format = Some("uri-reference".to_string());
+    schema
+}
+
+impl Response {
+    /// A [`Problem`] under `status`, as `application/problem+json`
+    pub fn problem(status: &str, description: &str) -> Self {
+        Response::new::<Problem>(status, description).content_type(PROBLEM_JSON)
+    }
+}
+
+impl Operation {
+    /// Respond with a [`Page`] of `T` under `status`
+    pub fn paginated<T: Schema>(self, status: &str, description: &str) -> Self {
+        self.response::<Page<T>>(status, description)
+    }
+
+    /// Respond with a [`Problem`] under `status`
+    pub fn problem(self, status: &str, description: &str) -> Self {
+        self.respond(Response::problem(status, description))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::OpenApiBuilder;
+    use serde_json::json;
+
+    #[derive(Schema)]
+    #[allow(dead_code)]
+    struct User {
+        name: String,
+    }
+
+    #[test]
+    fn test_page_and_problem_components() {
+        let document = OpenApiBuilder::new("Users", "1.0.0")
+            .operation(
+                Operation::get("/users")
+                    .paginated::<User>("200", "A page of users")
+                    .problem("400", "Bad cursor"),
+            )
+            .build()
+            .unwrap();
+        let responses = &document["paths"]["/users"]["get"]["responses"];
+        assert_eq!(
+            responses["200"]["content"]["application/json"]["schema"],
+            json!({ "$ref": "#/components/schemas/Page_User" })
+        );
+        assert_eq!(
+            responses["400"]["content"][PROBLEM_JSON]["schema"],
+            json!({ "$ref": "#/components/schemas/Problem" })
+        );
+
+        let schemas = &document["components"]["schemas"];
+        assert_eq!(schemas["Page_User"]["required"], json!(["items"]));
+        assert_eq!(
+            schemas["Page_User"]["properties"]["items"]["items"],
+            json!({ "$ref": "#/components/schemas/User" })
+        );
+        let problem = &schemas["Problem"];
+        assert!(problem.get("required").is_none());
+        assert_eq!(problem["properties"]["type"]["format"], "uri-reference");
+        assert_eq!(problem["properties"]["status"]["maximum"], 599);
+    }
+
+    #[test]
+    fn test_problem_serializes_per_rfc() {
+        let problem = Problem {
+            problem_type: Some("https://example.com/probs/out-of-credit".to_string()),
+            status: Some(403),
+            ..Problem::default()
+        };
+        assert_eq!(
+            serde_json::to_value(&problem).unwrap(),
+            json!({ "type": "https://example.com/probs/out-of-credit", "status": 403 })
+        );
+        let page: Page<u32> = serde_json::from_value(json!({ "items": [1, 2] })).unwrap();
+        assert_eq!(page.next_cursor, None);
+    }
+}
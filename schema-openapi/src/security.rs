@@ -0,0 +1,395 @@
+//! Security Scheme Objects and the requirements that use them
+//!
+//! A [`SecurityScheme`] is defined once under `components/securitySchemes`
+//! by name; a [`SecurityRequirement`] names the schemes a request must
+//! satisfy, with the OAuth2 scopes it needs. Requirements set on the
+//! document apply to every operation that doesn't set its own.
+
+use serde_json::{Map, Value, json};
+
+/// Where an API key is sent
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ApiKeyLocation {
+    Query,
+    Header,
+    Cookie,
+}
+
+impl ApiKeyLocation {
+    /// The scheme's `in` field
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ApiKeyLocation::Query => "query",
+            ApiKeyLocation::Header => "header",
+            ApiKeyLocation::Cookie => "cookie",
+        }
+    }
+}
+
+/// How a client authenticates
+#[derive(Debug, Clone, PartialEq)]
+pub enum SecuritySchemeKind {
+    /// A key in the query string, a header or a cookie
+    ApiKey {
+        name: String,
+        location: ApiKeyLocation,
+    },
+    /// An `Authorization` header of an HTTP scheme, such as `basic` or
+    /// `bearer`
+    Http {
+        scheme: String,
+        /// A hint to how bearer tokens are formatted, such as `JWT`
+        bearer_format: Option<String>,
+    },
+    /// OAuth2, through any of its flows
+    OAuth2 { flows: Vec<OAuthFlow> },
+    /// OpenID Connect, discovered from `url`
+    OpenIdConnect { url: String },
+}
+
+/// A way of authenticating, defined once and required by name
+#[derive(Debug, Clone, PartialEq)]
+pub struct SecurityScheme {
+    pub kind: SecuritySchemeKind,
+    pub description: Option<String>,
+}
+
+impl SecurityScheme {
+    pub fn new(kind: SecuritySchemeKind) -> Self {
+        SecurityScheme {
+            kind,
+            description: None,
+        }
+    }
+
+    /// A key named `name`, such as `X-API-Key`
+    pub fn api_key(name: &str, location: ApiKeyLocation) -> Self {
+        Self::new(SecuritySchemeKind::ApiKey {
+            name: name.to_string(),
+            location,
+        })
+    }
+
+    /// `Authorization: <scheme> ...`, for a scheme in the IANA registry
+    pub fn http(scheme: &str) -> Self {
+        Self::new(SecuritySchemeKind::Http {
+            scheme: scheme.to_string(),
+            bearer_format: None,
+        })
+    }
+
+    /// `Authorization: Basic ...`
+    pub fn basic() -> Self {
+        Self::http("basic")
+    }
+
+    /// `Authorization: Bearer ...`
+    pub fn bearer() -> Self {
+        Self::http("bearer")
+    }
+
+    /// OAuth2 with `flows`; a later flow of the same kind replaces an
+    /// earlier one
+    pub fn oauth2(flows: impl IntoIterator<Item = OAuthFlow>) -> Self {
+        let mut kept: Vec<OAuthFlow> = vec![];
+        for flow in flows {
+            match kept.iter_mut().find(|f| f.kind.key() == flow.kind.key()) {
+                Some(existing) => *existing = flow,
+                None => kept.push(flow),
+            }
+        }
+        Self::new(SecuritySchemeKind::OAuth2 { flows: kept })
+    }
+
+    pub fn open_id_connect(url: &str) -> Self {
+        Self::new(SecuritySchemeKind::OpenIdConnect {
+            url: url.to_string(),
+        })
+    }
+
+    /// How bearer tokens are formatted; only HTTP schemes have one
+    pub fn bearer_format(mut self, format: &str) -> Self {
+        if let SecuritySchemeKind::Http { bearer_format, .. } = &mut self.kind {
+            *bearer_format = Some(format.to_string());
+        }
+        self
+    }
+
+    pub fn description(mut self, description: &str) -> Self {
+        self.description = Some(description.to_string());
+        self
+    }
+
+    /// The Security Scheme Object
+    pub(crate) fn to_json(&self) -> Value {
+        let mut object = match &self.kind {
+            SecuritySchemeKind::ApiKey { name, location } => json!({
+                "type": "apiKey",
+                "name": name,
+                "in": location.as_str(),
+            }),
+            SecuritySchemeKind::Http {
+                scheme,
+                bearer_format,
+            } => {
+                let mut object = json!({ "type": "http", "scheme": scheme });
+                if let Some(format) = bearer_format {
+                    object["bearerFormat"] = json!(format);
+                }
+                object
+            }
+            SecuritySchemeKind::OAuth2 { flows } => {
+                let flows: Map<String, Value> = flows
+                    .iter()
+                    .map(|flow| (flow.kind.key().to_string(), flow.to_json()))
+                    .collect();
+                json!({ "type": "oauth2", "flows": flows })
+            }
+            SecuritySchemeKind::OpenIdConnect { url } => json!({
+                "type": "openIdConnect",
+                "openIdConnectUrl": url,
+            }),
+        };
+        if let Some(description) = &self.description {
+            object["description"] = json!(description);
+        }
+        object
+    }
+}
+
+/// Which OAuth2 flow, with the URLs it needs
+#[derive(Debug, Clone, PartialEq)]
+pub enum OAuthFlowKind {
+    Implicit {
+        authorization_url: String,
+    },
+    Password {
+        token_url: String,
+    },
+    ClientCredentials {
+        token_url: String,
+    },
+    AuthorizationCode {
+        authorization_url: String,
+        token_url: String,
+    },
+}
+
+impl OAuthFlowKind {
+    /// The flow's key in the OAuth Flows Object
+    fn key(&self) -> &'static str {
+        match self {
+            OAuthFlowKind::Implicit { .. } => "implicit",
+            OAuthFlowKind::Password { .. } => "password",
+            OAuthFlowKind::ClientCredentials { .. } => "clientCredentials",
+            OAuthFlowKind::AuthorizationCode { .. } => "authorizationCode",
+        }
+    }
+}
+
+/// One OAuth2 flow and the scopes it grants
+#[derive(Debug, Clone, PartialEq)]
+pub struct OAuthFlow {
+    pub kind: OAuthFlowKind,
+    pub refresh_url: Option<String>,
+    /// Scope names and what they allow, in the order they're added
+    pub scopes: Vec<(String, String)>,
+}
+
+impl OAuthFlow {
+    pub fn new(kind: OAuthFlowKind) -> Self {
+        OAuthFlow {
+            kind,
+            refresh_url: None,
+            scopes: vec![],
+        }
+    }
+
+    pub fn implicit(authorization_url: &str) -> Self {
+        Self::new(OAuthFlowKind::Implicit {
+            authorization_url: authorization_url.to_string(),
+        })
+    }
+
+    pub fn password(token_url: &str) -> Self {
+        Self::new(OAuthFlowKind::Password {
+            token_url: token_url.to_string(),
+        })
+    }
+
+    pub fn client_credentials(token_url: &str) -> Self {
+        Self::new(OAuthFlowKind::ClientCredentials {
+            token_url: token_url.to_string(),
+        })
+    }
+
+    pub fn authorization_code(authorization_url: &str, token_url: &str) -> Self {
+        Self::new(OAuthFlowKind::AuthorizationCode {
+            authorization_url: authorization_url.to_string(),
+            token_url: token_url.to_string(),
+        })
+    }
+
+    pub fn refresh_url(mut self, url: &str) -> Self {
+        self.refresh_url = Some(url.to_string());
+        self
+    }
+
+    /// Adding a scope with the same name again replaces its description
+    pub fn scope(mut self, name: &str, description: &str) -> Self {
+        match self.scopes.iter_mut().find(|(n, _)| n == name) {
+            Some(existing) => existing.1 = description.to_string(),
+            None => self
+                .scopes
+                .push((name.to_string(), description.to_string())),
+        }
+        self
+    }
+
+    /// The OAuth Flow Object
+    fn to_json(&self) -> Value {
+        let mut object = match &self.kind {
+            OAuthFlowKind::Implicit { authorization_url } => {
+                json!({ "authorizationUrl": authorization_url })
+            }
+            OAuthFlowKind::Password { token_url }
+            | OAuthFlowKind::ClientCredentials { token_url } => {
+                json!({ "tokenUrl": token_url })
+            }
+            OAuthFlowKind::AuthorizationCode {
+                authorization_url,
+                token_url,
+            } => json!({ "authorizationUrl": authorization_url, "tokenUrl": token_url }),
+        };
+        if let Some(url) = &self.refresh_url {
+            object["refreshUrl"] = json!(url);
+        }
+        // Required even when empty
+        let scopes: Map<String, Value> = self
+            .scopes
+            .iter()
+            .map(|(name, description)| (name.clone(), json!(description)))
+            .collect();
+        object["scopes"] = Value::Object(scopes);
+        object
+    }
+}
+
+/// Schemes a request must satisfy together, by name, each with the scopes
+/// it needs
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SecurityRequirement {
+    pub schemes: Vec<(String, Vec<String>)>,
+}
+
+impl SecurityRequirement {
+    /// Requires the scheme named `name`
+    pub fn new(name: &str) -> Self {
+        SecurityRequirement::default().and(name)
+    }
+
+    /// Also requires the scheme named `name`
+    pub fn and(mut self, name: &str) -> Self {
+        self.schemes.push((name.to_string(), vec![]));
+        self
+    }
+
+    /// Requires `scope` of the scheme named last
+    pub fn scope(mut self, scope: &str) -> Self {
+        if let Some((_, scopes)) = self.schemes.last_mut() {
+            scopes.push(scope.to_string());
+        }
+        self
+    }
+
+    /// The Security Requirement Object
+    pub(crate) fn to_json(&self) -> Value {
+        let object: Map<String, Value> = self
+            .schemes
+            .iter()
+            .map(|(name, scopes)| (name.clone(), json!(scopes)))
+            .collect();
+        Value::Object(object)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_schemes() {
+        assert_eq!(
+            SecurityScheme::api_key("X-API-Key", ApiKeyLocation::Header).to_json(),
+            json!({ "type": "apiKey", "name": "X-API-Key", "in": "header" })
+        );
+        assert_eq!(
+            SecurityScheme::bearer()
+                .bearer_format("JWT")
+                .description("A token from /login")
+                .to_json(),
+            json!({
+                "type": "http",
+                "scheme": "bearer",
+                "bearerFormat": "JWT",
+                "description": "A token from /login",
+            })
+        );
+        // Only HTTP schemes have a bearer format
+        assert_eq!(
+            SecurityScheme::open_id_connect(
+                "https://id.example.com/.well-known/openid-configuration"
+            )
+            .bearer_format("JWT")
+            .to_json(),
+            json!({
+                "type": "openIdConnect",
+                "openIdConnectUrl": "https://id.example.com/.well-known/openid-configuration",
+            })
+        );
+
+        let oauth = SecurityScheme::oauth2([
+            OAuthFlow::authorization_code(
+                "https://auth.example.com/authorize",
+                "https://auth.example.com/token",
+            )
+            .refresh_url("https://auth.example.com/refresh")
+            .scope("read:users", "Read accounts")
+            .scope("write:users", "Change accounts"),
+            OAuthFlow::client_credentials("https://auth.example.com/old-token"),
+            OAuthFlow::client_credentials("https://auth.example.com/token"),
+        ]);
+        assert_eq!(
+            oauth.to_json(),
+            json!({
+                "type": "oauth2",
+                "flows": {
+                    "authorizationCode": {
+                        "authorizationUrl": "https://auth.example.com/authorize",
+                        "tokenUrl": "https://auth.example.com/token",
+                        "refreshUrl": "https://auth.example.com/refresh",
+                        "scopes": {
+                            "read:users": "Read accounts",
+                            "write:users": "Change accounts",
+                        },
+                    },
+                    "clientCredentials": {
+                        "tokenUrl": "https://auth.example.com/token",
+                        "scopes": {},
+                    },
+                },
+            })
+        );
+    }
+
+    #[test]
+    fn test_requirement() {
+        assert_eq!(
+            SecurityRequirement::new("oauth")
+                .scope("read:users")
+                .and("apiKey")
+                .to_json(),
+            json!({ "oauth": ["read:users"], "apiKey": [] })
+        );
+    }
+}
@@ -0,0 +1,159 @@
+//! Conversions to and from utoipa's schema objects
+//!
+//! A SchemaType becomes the OpenAPI 3.1 schema [`to_openapi_schema`] writes,
+//! read into utoipa's [`RefOr<Schema>`] so it can sit among a utoipa
+//! document's own schemas. The other way, a utoipa schema is serialized and
+//! read back as JSON Schema, with `$ref`s resolved against the components
+//! given alongside it, so types already deriving `ToSchema` can feed the
+//! other backends.
+//!
+//! [`to_openapi_schema`]: crate::to_openapi_schema
+
+use schema::{Schema, SchemaType};
+use schema_jsonschema::ParseError;
+use serde_json::json;
+use std::fmt;
+use utoipa::ToSchema;
+use utoipa::openapi::schema::Schema as UtoipaSchema;
+use utoipa::openapi::{Components, RefOr};
+
+/// Why a schema can't cross between this crate and utoipa
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UtoipaError {
+    /// OpenAPI JSON that utoipa's schema objects have no form for
+    Unrepresentable(String),
+    /// A utoipa schema that doesn't read back into a SchemaType
+    Parse(ParseError),
+}
+
+impl fmt::Display for UtoipaError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            UtoipaError::Unrepresentable(reason) => {
+                write!(f, "utoipa can't hold the schema: {}", reason)
+            }
+            UtoipaError::Parse(e) => e.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for UtoipaError {}
+
+impl From<ParseError> for UtoipaError {
+    fn from(e: ParseError) -> Self {
+        UtoipaError::Parse(e)
+    }
+}
+
+/// The utoipa schema object for `T`
+pub fn to_utoipa_schema<T: Schema>() -> Result<RefOr<UtoipaSchema>, UtoipaError> {
+    schema_type_to_utoipa(&crate::named_schema::<T>())
+}
+
+/// The utoipa schema object for a SchemaType, with every type in place
+pub fn schema_type_to_utoipa(schema: &SchemaType) -> Result<RefOr<UtoipaSchema>, UtoipaError> {
+    let openapi = crate::schema_type_to_openapi(schema);
+    serde_json::from_value(openapi).map_err(|e| UtoipaError::Unrepresentable(e.to_string()))
+}
+
+/// The SchemaType of a type deriving utoipa's `ToSchema`, named by its
+/// `ToSchema::name`
+pub fn from_utoipa<T: ToSchema>() -> Result<SchemaType, UtoipaError> {
+    let mut schemas = vec![];
+    T::schemas(&mut schemas);
+    let mut components = Components::new();
+    components.schemas.extend(schemas);
+    let mut schema = utoipa_to_schema_type(&T::schema(), &components)?;
+    schema.name.get_or_insert_with(|| T::name().into_owned());
+    Ok(schema)
+}
+
+/// The SchemaType of a utoipa schema whose `#/components/schemas/...`
+/// references point into `components`
+pub fn utoipa_to_schema_type(
+    schema: &RefOr<UtoipaSchema>,
+    components: &Components,
+) -> Result<SchemaType, UtoipaError> {
+    let unrepresentable = |e: serde_json::Error| UtoipaError::Unrepresentable(e.to_string());
+    let mut document = serde_json::to_value(schema).map_err(unrepresentable)?;
+    // The references resolve within the document, so the components ride
+    // along beside the root
+    document["components"] = json!({
+        "schemas": serde_json::to_value(&components.schemas).map_err(unrepresentable)?,
+    });
+    Ok(schema_jsonschema::parse(&document)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use schema::TypeKind;
+
+    #[derive(Schema)]
+    #[allow(dead_code)]
+    struct Pet {
+        /// What the pet answers to
+        name: String,
+        age: Option<u32>,
+        tags: Vec<String>,
+    }
+
+    #[test]
+    fn test_to_utoipa() {
+        let RefOr::T(UtoipaSchema::Object(object)) = to_utoipa_schema::<Pet>().unwrap() else {
+            panic!("expected an object schema");
+        };
+        assert_eq!(object.required, ["name", "tags"]);
+        let Some(RefOr::T(UtoipaSchema::Object(name))) = object.properties.get("name") else {
+            panic!("expected a name property");
+        };
+        assert_eq!(name.description.as_deref(), Some("What the pet answers to"));
+        assert!(matches!(
+            object.properties.get("tags"),
+            Some(RefOr::T(UtoipaSchema::Array(_)))
+        ));
+    }
+
+    #[test]
+    fn test_from_utoipa() {
+        #[derive(utoipa::ToSchema)]
+        #[allow(dead_code)]
+        struct Owner {
+            name: String,
+        }
+
+        #[derive(utoipa::ToSchema)]
+        #[allow(dead_code)]
+        struct Kennel {
+            owner: Owner,
+            capacity: i32,
+            nicknames: Option<Vec<String>>,
+        }
+
+        let schema = from_utoipa::<Kennel>().unwrap();
+        assert_eq!(schema.name.as_deref(), Some("Kennel"));
+        let TypeKind::Object {
+            properties,
+            required,
+        } = &schema.kind
+        else {
+            panic!("expected an object, got {:?}", schema.kind);
+        };
+        assert_eq!(required, &["owner", "capacity"]);
+        assert_eq!(properties["owner"].name.as_deref(), Some("Owner"));
+        assert!(matches!(properties["owner"].kind, TypeKind::Object { .. }));
+        assert!(matches!(
+            properties["capacity"].kind,
+            TypeKind::Integer(schema::IntegerKind::I32)
+        ));
+        assert!(matches!(
+            properties["nicknames"].kind,
+            TypeKind::Array { .. }
+        ));
+
+        // Our own output survives the trip through utoipa
+        let pet = to_utoipa_schema::<Pet>().unwrap();
+        let back = utoipa_to_schema_type(&pet, &Components::new()).unwrap();
+        assert!(matches!(back.kind, TypeKind::Object { .. }));
+    }
+}
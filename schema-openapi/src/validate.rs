@@ -0,0 +1,127 @@
+//! Checking documents against the OpenAPI meta-schemas
+//!
+//! The official OpenAPI Initiative schemas (3.0's of 2021-09-28 and 3.1's of
+//! 2022-10-07, Apache-2.0, attributed in the crate's `NOTICE`) are embedded,
+//! and a document is checked against the one its `openapi` field names.
+//! 3.0's validates Schema Objects too, so 3.1-only keywords such as `const`
+//! or `type: "null"` in a 3.0 document are caught; 3.1's leaves Schema
+//! Objects to JSON Schema.
+
+use serde_json::Value;
+use std::fmt;
+use std::sync::OnceLock;
+
+const OPENAPI_3_0: &str = include_str!("../schemas/openapi-3.0.json");
+const OPENAPI_3_1: &str = include_str!("../schemas/openapi-3.1.json");
+
+/// Where a document breaks its meta-schema, and how
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValidationError {
+    /// JSON Pointer to the offending value, such as `/paths/~1users/get`
+    pub path: String,
+    pub message: String,
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}: {}", self.path, self.message)
+    }
+}
+
+impl std::error::Error for ValidationError {}
+
+/// Check `document` against the meta-schema of the OpenAPI version it
+/// declares, returning every violation
+pub fn validate_document(document: &Value) -> Result<(), Vec<ValidationError>> {
+    let validator = match document.get("openapi").and_then(Value::as_str) {
+        Some(version) if version.starts_with("3.0.") => validator(OPENAPI_3_0, &V3_0),
+        Some(version) if version.starts_with("3.1.") => validator(OPENAPI_3_1, &V3_1),
+        _ => {
+            return Err(vec![ValidationError {
+                path: "/openapi".to_string(),
+                message: "expected an OpenAPI version of 3.0.x or 3.1.x".to_string(),
+            }]);
+        }
+    };
+    let errors: Vec<ValidationError> = validator
+        .iter_errors(document)
+        .map(|error| ValidationError {
+            path: error.instance_path().as_str().to_string(),
+            message: error.to_string(),
+        })
+        .collect();
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+static V3_0: OnceLock<jsonschema::Validator> = OnceLock::new();
+static V3_1: OnceLock<jsonschema::Validator> = OnceLock::new();
+
+/// The compiled validator for an embedded meta-schema, built on first use
+fn validator(
+    source: &str,
+    cell: &'static OnceLock<jsonschema::Validator>,
+) -> &'static jsonschema::Validator {
+    cell.get_or_init(|| {
+        let schema: Value = serde_json::from_str(source).expect("embedded meta-schema is JSON");
+        jsonschema::validator_for(&schema).expect("embedded meta-schema compiles")
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{OpenApiBuilder, OpenApiVersion, Operation, ParameterLocation};
+    use schema::Schema;
+    use serde_json::json;
+
+    #[derive(Schema)]
+    #[allow(dead_code)]
+    enum Shape {
+        Circle { radius: f64 },
+        Point,
+    }
+
+    #[derive(Schema)]
+    #[allow(dead_code)]
+    struct Query {
+        limit: Option<u32>,
+        note: Option<()>,
+    }
+
+    fn document(version: OpenApiVersion) -> Value {
+        OpenApiBuilder::new("Shapes", "1.0.0")
+            .openapi_version(version)
+            .operation(
+                Operation::get("/shapes")
+                    .parameters::<Query>(ParameterLocation::Query)
+                    .unwrap()
+                    .paginated::<Shape>("200", "Shapes")
+                    .problem("400", "Bad query"),
+            )
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_generated_documents_validate() {
+        assert_eq!(validate_document(&document(OpenApiVersion::V3_1)), Ok(()));
+        assert_eq!(validate_document(&document(OpenApiVersion::V3_0)), Ok(()));
+    }
+
+    #[test]
+    fn test_dialect_mixup() {
+        // A 3.1 document relabelled as 3.0 keeps its `const`s
+        let mut mixed = document(OpenApiVersion::V3_1);
+        mixed["openapi"] = json!("3.0.3");
+        let errors = validate_document(&mixed).unwrap_err();
+        let paths: Vec<&str> = errors.iter().map(|e| e.path.as_str()).collect();
+        assert_eq!(paths, ["/components/schemas/Shape"]);
+
+        let errors = validate_document(&json!({ "info": {} })).unwrap_err();
+        assert_eq!(errors[0].path, "/openapi");
+    }
+}
@@ -0,0 +1,127 @@
+//! YAML output, for the tooling and reviews that expect OpenAPI files in
+//! YAML rather than JSON
+//!
+//! A document's top-level sections are written in the order the
+//! specification lists them (`openapi`, `info`, `servers`, ... ,
+//! `components`) so the file reads top-down; everything below keeps the
+//! JSON value's order.
+
+use crate::{OpenApiBuilder, OpenApiError};
+use schema::Schema;
+use serde_json::Value;
+use serde_yaml_ng::Mapping;
+
+/// Top-level fields of an OpenAPI Object, in specification order
+const DOCUMENT_ORDER: &[&str] = &[
+    "openapi",
+    "info",
+    "jsonSchemaDialect",
+    "servers",
+    "tags",
+    "paths",
+    "webhooks",
+    "components",
+    "security",
+    "externalDocs",
+];
+
+/// The OpenAPI schema of `T` as YAML
+pub fn to_openapi_yaml<T: Schema>() -> String {
+    to_yaml(&crate::to_openapi_schema::<T>())
+}
+
+/// A JSON document or schema as YAML
+pub fn to_yaml(value: &Value) -> String {
+    let yaml = match value {
+        Value::Object(object) => {
+            let mut mapping = Mapping::new();
+            let known = DOCUMENT_ORDER
+                .iter()
+                .filter_map(|key| object.get_key_value(*key));
+            let rest = object
+                .iter()
+                .filter(|(key, _)| !DOCUMENT_ORDER.contains(&key.as_str()));
+            for (key, value) in known.chain(rest) {
+                mapping.insert(key.as_str().into(), to_yaml_value(value));
+            }
+            serde_yaml_ng::Value::Mapping(mapping)
+        }
+        other => to_yaml_value(other),
+    };
+    // Every JSON value has a YAML form, and string keys can't fail
+    serde_yaml_ng::to_string(&yaml).expect("JSON values serialize to YAML")
+}
+
+fn to_yaml_value(value: &Value) -> serde_yaml_ng::Value {
+    serde_yaml_ng::to_value(value).expect("JSON values serialize to YAML")
+}
+
+impl OpenApiBuilder {
+    /// The document as YAML
+    pub fn build_yaml(&self) -> Result<String, OpenApiError> {
+        self.build().map(|document| to_yaml(&document))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Operation;
+
+    #[derive(Schema)]
+    #[allow(dead_code)]
+    struct Pet {
+        name: String,
+    }
+
+    #[test]
+    fn test_schema_yaml() {
+        assert_eq!(
+            to_openapi_yaml::<Pet>(),
+            "properties:
+  name:
+    type: string
+required:
+- name
+title: Pet
+type: object
+"
+        );
+    }
+
+    #[test]
+    fn test_document_yaml() {
+        let yaml = OpenApiBuilder::new("Pets", "1.0.0")
+            .operation(Operation::get("/pets").response::<Pet>("200", "A pet"))
+            .build_yaml()
+            .unwrap();
+        assert_eq!(
+            yaml,
+            "openapi: 3.1.0
+info:
+  title: Pets
+  version: 1.0.0
+paths:
+  /pets:
+    get:
+      responses:
+        '200':
+          content:
+            application/json:
+              schema:
+                $ref: '#/components/schemas/Pet'
+          description: A pet
+components:
+  schemas:
+    Pet:
+      properties:
+        name:
+          type: string
+      required:
+      - name
+      title: Pet
+      type: object
+"
+        );
+    }
+}
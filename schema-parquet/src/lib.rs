@@ -0,0 +1,407 @@
+//! Parquet message types from schemas
+//!
+//! An object schema becomes a Parquet `message` in the text syntax that
+//! parquet-mr's `MessageTypeParser` and parquet-rs's `parse_message_type`
+//! read. Required properties are `required` columns and the rest
+//! `optional`. Nested objects become groups, and arrays and maps use the
+//! standard three-level `LIST` and `MAP` layouts.
+//!
+//! Logical type annotations:
+//! - strings are `STRING`, and enums are `ENUM`
+//! - unsigned integers are `INTEGER(bits,false)`
+//! - string formats `date-time`, `date`, `time` and `uuid` become
+//!   `TIMESTAMP(MICROS,true)`, `DATE`, `TIME(MICROS,false)` and `UUID`
+//! - fields marked with the [`DECIMAL`] extension become `DECIMAL(p,s)`
+//!
+//! Parquet has no unions, so variants are groups with an `ENUM` tag column
+//! (unless untagged) and one optional column per case that carries data.
+//! It has no recursion either, so recursive references are `JSON` columns.
+
+use schema::{GenerateOptions, IntegerKind, NumberKind, Schema, SchemaType, TypeKind};
+use std::fmt;
+
+/// Extension marking a number or string field as a fixed-point decimal:
+/// `#[schema(extension("x-decimal" = { "precision": 18, "scale": 2 }))]`
+pub const DECIMAL: &str = "x-decimal";
+
+/// Why a schema has no Parquet message type
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParquetError {
+    /// The schema is not an object, so it has no columns
+    NotAnObject,
+    /// A [`DECIMAL`] extension without a usable precision and scale
+    InvalidDecimal { column: String },
+}
+
+impl fmt::Display for ParquetError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParquetError::NotAnObject => {
+                write!(f, "only object schemas map to Parquet message types")
+            }
+            ParquetError::InvalidDecimal { column } => write!(
+                f,
+                "column {:?} needs a decimal precision of at least 1 and a scale between 0 and the precision",
+                column
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ParquetError {}
+
+/// The Parquet message type for `T`
+pub fn to_parquet_schema<T: Schema>() -> Result<String, ParquetError> {
+    schema_type_to_parquet(&T::schema(), T::type_name())
+}
+
+/// The Parquet message type for `T`, bounded by `options`
+pub fn to_parquet_schema_with_options<T: Schema>(
+    options: &GenerateOptions,
+) -> Result<String, ParquetError> {
+    schema_type_to_parquet_with_options(&T::schema(), T::type_name(), options)
+}
+
+/// The Parquet message type for a SchemaType, bounded by `options`
+pub fn schema_type_to_parquet_with_options(
+    schema: &SchemaType,
+    type_name: Option<&str>,
+    options: &GenerateOptions,
+) -> Result<String, ParquetError> {
+    schema_type_to_parquet(&options.apply(schema), type_name)
+}
+
+/// The Parquet message type for an object SchemaType, named `type_name`
+/// (or the schema's own name, or `schema`)
+pub fn schema_type_to_parquet(
+    schema: &SchemaType,
+    type_name: Option<&str>,
+) -> Result<String, ParquetError> {
+    if !matches!(schema.kind, TypeKind::Object { .. }) {
+        return Err(ParquetError::NotAnObject);
+    }
+    let name = identifier(type_name.or(schema.name.as_deref()).unwrap_or("schema"));
+    let mut lines = vec![];
+    fields(&mut lines, 1, schema)?;
+    Ok(format!("message {} {{\n{}\n}}\n", name, lines.join("\n")))
+}
+
+#[derive(Clone, Copy)]
+enum Repetition {
+    Required,
+    Optional,
+}
+
+impl Repetition {
+    fn keyword(self) -> &'static str {
+        match self {
+            Repetition::Required => "required",
+            Repetition::Optional => "optional",
+        }
+    }
+}
+
+/// The columns for an object's properties
+fn fields(lines: &mut Vec<String>, depth: usize, object: &SchemaType) -> Result<(), ParquetError> {
+    let TypeKind::Object { required, .. } = &object.kind else {
+        return Ok(());
+    };
+    for (name, property) in object.ordered_properties() {
+        let repetition = match required.iter().any(|r| r == name) {
+            true => Repetition::Required,
+            false => Repetition::Optional,
+        };
+        column(lines, depth, repetition, name, property)?;
+    }
+    Ok(())
+}
+
+/// One column (primitive or group) named `name`
+fn column(
+    lines: &mut Vec<String>,
+    depth: usize,
+    repetition: Repetition,
+    name: &str,
+    schema: &SchemaType,
+) -> Result<(), ParquetError> {
+    let indent = "  ".repeat(depth);
+    let rep = repetition.keyword();
+    let name = identifier(name);
+    let mut primitive = |physical: &str, logical: Option<String>| {
+        let logical = logical.map(|l| format!(" ({l})")).unwrap_or_default();
+        lines.push(format!("{indent}{rep} {physical} {name}{logical};"));
+    };
+
+    if let Some(decimal) = schema.extensions.get(DECIMAL) {
+        let precision = decimal.get("precision").and_then(|p| p.as_u64());
+        let scale = decimal.get("scale").and_then(|s| s.as_u64());
+        let (Some(precision), Some(scale)) = (precision, scale) else {
+            return Err(ParquetError::InvalidDecimal { column: name });
+        };
+        if precision == 0 || scale > precision {
+            return Err(ParquetError::InvalidDecimal { column: name });
+        }
+        let physical = match precision {
+            1..=9 => "int32".to_string(),
+            10..=18 => "int64".to_string(),
+            _ => format!("fixed_len_byte_array({})", decimal_bytes(precision)),
+        };
+        primitive(&physical, Some(format!("DECIMAL({precision},{scale})")));
+        return Ok(());
+    }
+
+    match &schema.kind {
+        TypeKind::String => match schema.constraints.format.as_deref() {
+            Some("date-time") => primitive("int64", Some("TIMESTAMP(MICROS,true)".into())),
+            Some("date") => primitive("int32", Some("DATE".into())),
+            Some("time") => primitive("int64", Some("TIME(MICROS,false)".into())),
+            Some("uuid") => primitive("fixed_len_byte_array(16)", Some("UUID".into())),
+            _ => primitive("binary", Some("STRING".into())),
+        },
+        TypeKind::Integer(kind) => match kind {
+            IntegerKind::I32 => primitive("int32", None),
+            IntegerKind::I64 => primitive("int64", None),
+            IntegerKind::U8 => primitive("int32", Some("INTEGER(8,false)".into())),
+            IntegerKind::U32 => primitive("int32", Some("INTEGER(32,false)".into())),
+            IntegerKind::U64 | IntegerKind::Usize => {
+                primitive("int64", Some("INTEGER(64,false)".into()))
+            }
+        },
+        TypeKind::Number(NumberKind::F32) => primitive("float", None),
+        TypeKind::Number(NumberKind::F64) => primitive("double", None),
+        TypeKind::Boolean => primitive("boolean", None),
+        // Always null, whatever the repetition says
+        TypeKind::Null => {
+            lines.push(format!("{indent}optional int32 {name} (UNKNOWN);"));
+        }
+        TypeKind::Enum { .. } => primitive("binary", Some("ENUM".into())),
+        // Parquet can't nest a group inside itself; keep the value as JSON
+        TypeKind::Ref { .. } => primitive("binary", Some("JSON".into())),
+        TypeKind::Object { .. } => {
+            lines.push(format!("{indent}{rep} group {name} {{"));
+            fields(lines, depth + 1, schema)?;
+            lines.push(format!("{indent}}}"));
+        }
+        TypeKind::Array { items } | TypeKind::Set { items, .. } => {
+            lines.push(format!("{indent}{rep} group {name} (LIST) {{"));
+            lines.push(format!("{indent}  repeated group list {{"));
+            column(lines, depth + 2, Repetition::Required, "element", items)?;
+            lines.push(format!("{indent}  }}"));
+            lines.push(format!("{indent}}}"));
+        }
+        TypeKind::Map { key, value, .. } => {
+            lines.push(format!("{indent}{rep} group {name} (MAP) {{"));
+            lines.push(format!("{indent}  repeated group key_value {{"));
+            column(lines, depth + 2, Repetition::Required, "key", key)?;
+            column(lines, depth + 2, Repetition::Required, "value", value)?;
+            lines.push(format!("{indent}  }}"));
+            lines.push(format!("{indent}}}"));
+        }
+        TypeKind::Tuple { fields: items } => {
+            lines.push(format!("{indent}{rep} group {name} {{"));
+            for (i, item) in items.iter().enumerate() {
+                column(
+                    lines,
+                    depth + 1,
+                    Repetition::Required,
+                    &format!("item_{i}"),
+                    item,
+                )?;
+            }
+            lines.push(format!("{indent}}}"));
+        }
+        TypeKind::Result { ok, err } => {
+            lines.push(format!("{indent}{rep} group {name} {{"));
+            column(lines, depth + 1, Repetition::Optional, "ok", ok)?;
+            column(lines, depth + 1, Repetition::Optional, "error", err)?;
+            lines.push(format!("{indent}}}"));
+        }
+        TypeKind::TaggedUnion {
+            tag_field,
+            data_fields,
+            ..
+        } => {
+            lines.push(format!("{indent}{rep} group {name} {{"));
+            lines.push(format!(
+                "{indent}  required binary {} (ENUM);",
+                identifier(tag_field)
+            ));
+            let mut names: Vec<&String> = data_fields.keys().collect();
+            names.sort();
+            for field in names {
+                column(
+                    lines,
+                    depth + 1,
+                    Repetition::Optional,
+                    field,
+                    &data_fields[field],
+                )?;
+            }
+            lines.push(format!("{indent}}}"));
+        }
+        TypeKind::Variant { cases } => {
+            lines.push(format!("{indent}{rep} group {name} {{"));
+            let tagging = schema.tagging();
+            if tagging != Some(schema::Tagging::Untagged) {
+                let tag = tagging.as_ref().and_then(|t| t.tag()).unwrap_or("type");
+                lines.push(format!(
+                    "{indent}  required binary {} (ENUM);",
+                    identifier(tag)
+                ));
+            }
+            for case in cases {
+                if let Some(data) = &case.data {
+                    column(lines, depth + 1, Repetition::Optional, &case.name, data)?;
+                }
+            }
+            lines.push(format!("{indent}}}"));
+        }
+    }
+    Ok(())
+}
+
+/// The fewest bytes whose signed range holds every `precision`-digit value
+fn decimal_bytes(precision: u64) -> u64 {
+    let digits = |bytes: u64| ((8 * bytes - 1) as f64 * 2f64.log10()).floor() as u64;
+    (1..).find(|&bytes| digits(bytes) >= precision).unwrap()
+}
+
+/// A name the text syntax can hold: no whitespace or punctuation it parses
+fn identifier(name: &str) -> String {
+    name.chars()
+        .map(|c| match c {
+            c if c.is_whitespace() => '_',
+            '(' | ')' | '{' | '}' | ';' | ',' | '=' => '_',
+            c => c,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_message_type() {
+        #[derive(Schema)]
+        #[allow(dead_code)]
+        enum Status {
+            Active,
+            Closed,
+        }
+
+        #[derive(Schema)]
+        #[allow(dead_code)]
+        struct Address {
+            city: String,
+        }
+
+        #[derive(Schema)]
+        #[allow(dead_code)]
+        struct Order {
+            id: u64,
+            status: Status,
+            #[schema(extension("x-decimal" = { "precision": 12, "scale": 2 }))]
+            total: f64,
+            note: Option<String>,
+            address: Address,
+            tags: Vec<String>,
+            counts: HashMap<String, u32>,
+        }
+
+        assert_eq!(
+            to_parquet_schema::<Order>().unwrap(),
+            "message Order {
+  required int64 id (INTEGER(64,false));
+  required binary status (ENUM);
+  required int64 total (DECIMAL(12,2));
+  optional binary note (STRING);
+  required group address {
+    required binary city (STRING);
+  }
+  required group tags (LIST) {
+    repeated group list {
+      required binary element (STRING);
+    }
+  }
+  required group counts (MAP) {
+    repeated group key_value {
+      required binary key (STRING);
+      required int32 value (INTEGER(32,false));
+    }
+  }
+}
+"
+        );
+    }
+
+    #[test]
+    fn test_formats_and_variants() {
+        let mut created = SchemaType::new(TypeKind::String);
+        created.constraints.format = Some("date-time".to_string());
+        let mut wide = SchemaType::new(TypeKind::String);
+        wide.extensions.insert(
+            DECIMAL.to_string(),
+            serde_json::json!({ "precision": 38, "scale": 10 }),
+        );
+
+        #[derive(Schema)]
+        #[allow(dead_code)]
+        enum Payment {
+            Cash,
+            Card { last4: String },
+        }
+
+        let mut event = SchemaType::new(TypeKind::Object {
+            properties: [
+                ("created".to_string(), created),
+                ("amount".to_string(), wide),
+                ("payment".to_string(), Payment::schema()),
+            ]
+            .into(),
+            required: vec!["created".to_string(), "payment".to_string()],
+        });
+        event.property_order = vec![
+            "created".to_string(),
+            "amount".to_string(),
+            "payment".to_string(),
+        ];
+
+        assert_eq!(
+            schema_type_to_parquet(&event, Some("event")).unwrap(),
+            "message event {
+  required int64 created (TIMESTAMP(MICROS,true));
+  optional fixed_len_byte_array(16) amount (DECIMAL(38,10));
+  required group payment {
+    required binary type (ENUM);
+    optional group Card {
+      required binary last4 (STRING);
+    }
+  }
+}
+"
+        );
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(
+            to_parquet_schema::<Vec<String>>(),
+            Err(ParquetError::NotAnObject)
+        );
+
+        #[derive(Schema)]
+        #[allow(dead_code)]
+        struct Price {
+            #[schema(extension("x-decimal" = { "precision": 2, "scale": 4 }))]
+            amount: f64,
+        }
+        assert_eq!(
+            to_parquet_schema::<Price>(),
+            Err(ParquetError::InvalidDecimal {
+                column: "amount".to_string()
+            })
+        );
+    }
+}
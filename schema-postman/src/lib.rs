@@ -0,0 +1,380 @@
+use serde_json::{Map, Value, json};
+
+/// Postman Collection Format v2.1 schema URL (also importable by Insomnia)
+pub const COLLECTION_SCHEMA: &str =
+    "https://schema.getpostman.com/json/collection/v2.1.0/collection.json";
+
+/// HTTP methods in the order they appear within a path
+const METHODS: [&str; 8] = [
+    "get", "put", "post", "delete", "options", "head", "patch", "trace",
+];
+
+/// How deep to follow `$ref`s when building example bodies
+const MAX_EXAMPLE_DEPTH: usize = 8;
+
+/// Convert an OpenAPI 3.x document into a Postman v2.1 collection
+///
+/// Operations are grouped into folders by their first tag; untagged
+/// operations sit at the top level. Request bodies get an example generated
+/// from their JSON schema, and the first server URL becomes the `baseUrl`
+/// collection variable.
+pub fn openapi_to_postman(document: &Value) -> Value {
+    let mut folders: Vec<(String, Vec<Value>)> = Vec::new();
+    let mut top_level = Vec::new();
+
+    if let Some(paths) = document["paths"].as_object() {
+        for (path, item) in paths {
+            for method in METHODS {
+                let Some(operation) = item.get(method) else {
+                    continue;
+                };
+                let request = request_item(document, path, method, item, operation);
+
+                match operation["tags"][0].as_str() {
+                    Some(tag) => match folders.iter_mut().find(|(name, _)| name == tag) {
+                        Some((_, items)) => items.push(request),
+                        None => folders.push((tag.to_string(), vec![request])),
+                    },
+                    None => top_level.push(request),
+                }
+            }
+        }
+    }
+
+    let mut items: Vec<Value> = folders
+        .into_iter()
+        .map(|(name, item)| json!({ "name": name, "item": item }))
+        .collect();
+    items.extend(top_level);
+
+    let mut info = Map::new();
+    info.insert(
+        "name".to_string(),
+        document["info"]["title"].as_str().unwrap_or("API").into(),
+    );
+    if let Some(description) = document["info"]["description"].as_str() {
+        info.insert("description".to_string(), description.into());
+    }
+    info.insert("schema".to_string(), COLLECTION_SCHEMA.into());
+
+    json!({
+        "info": info,
+        "item": items,
+        "variable": [{
+            "key": "baseUrl",
+            "value": document["servers"][0]["url"].as_str().unwrap_or(""),
+        }],
+    })
+}
+
+fn request_item(
+    document: &Value,
+    path: &str,
+    method: &str,
+    path_item: &Value,
+    operation: &Value,
+) -> Value {
+    let name = operation["summary"]
+        .as_str()
+        .or_else(|| operation["operationId"].as_str())
+        .map(str::to_string)
+        .unwrap_or_else(|| format!("{} {}", method.to_uppercase(), path));
+
+    // Path-level parameters apply to every operation under the path
+    let parameters: Vec<Value> = path_item["parameters"]
+        .as_array()
+        .into_iter()
+        .chain(operation["parameters"].as_array())
+        .flatten()
+        .map(|p| resolve(document, p).clone())
+        .collect();
+
+    let mut request = Map::new();
+    request.insert("method".to_string(), method.to_uppercase().into());
+    request.insert("header".to_string(), json!([]));
+    request.insert("url".to_string(), url(document, path, &parameters));
+    if let Some(description) = operation["description"].as_str() {
+        request.insert("description".to_string(), description.into());
+    }
+
+    let json_body = &resolve(document, &operation["requestBody"])["content"]["application/json"];
+    if !json_body.is_null() {
+        let example = match json_body.get("example") {
+            Some(example) => example.clone(),
+            None => example_value(document, &json_body["schema"]),
+        };
+        request.insert(
+            "header".to_string(),
+            json!([{ "key": "Content-Type", "value": "application/json" }]),
+        );
+        request.insert(
+            "body".to_string(),
+            json!({
+                "mode": "raw",
+                "raw": serde_json::to_string_pretty(&example).unwrap_or_default(),
+                "options": { "raw": { "language": "json" } },
+            }),
+        );
+    }
+
+    json!({ "name": name, "request": request })
+}
+
+fn url(document: &Value, path: &str, parameters: &[Value]) -> Value {
+    // OpenAPI `{id}` templates become Postman `:id` path variables
+    let segments: Vec<String> = path
+        .split('/')
+        .filter(|s| !s.is_empty())
+        .map(
+            |s| match s.strip_prefix('{').and_then(|s| s.strip_suffix('}')) {
+                Some(var) => format!(":{}", var),
+                None => s.to_string(),
+            },
+        )
+        .collect();
+
+    let parameter_entry = |p: &Value| {
+        let value = match p.get("example") {
+            Some(example) => example.clone(),
+            None => example_value(document, &p["schema"]),
+        };
+        json!({
+            "key": p["name"],
+            "value": match value {
+                Value::String(s) => s,
+                other => other.to_string(),
+            },
+        })
+    };
+    let located = |location: &str| -> Vec<Value> {
+        parameters
+            .iter()
+            .filter(|p| p["in"] == location)
+            .map(parameter_entry)
+            .collect()
+    };
+
+    let query = located("query");
+    let mut raw = format!("{{{{baseUrl}}}}/{}", segments.join("/"));
+    if !query.is_empty() {
+        let pairs: Vec<String> = query
+            .iter()
+            .map(|q| {
+                format!(
+                    "{}={}",
+                    q["key"].as_str().unwrap_or(""),
+                    q["value"].as_str().unwrap_or("")
+                )
+            })
+            .collect();
+        raw = format!("{}?{}", raw, pairs.join("&"));
+    }
+
+    let mut url = Map::new();
+    url.insert("raw".to_string(), raw.into());
+    url.insert("host".to_string(), json!(["{{baseUrl}}"]));
+    url.insert("path".to_string(), json!(segments));
+    if !query.is_empty() {
+        url.insert("query".to_string(), json!(query));
+    }
+    let variables = located("path");
+    if !variables.is_empty() {
+        url.insert("variable".to_string(), json!(variables));
+    }
+    Value::Object(url)
+}
+
+/// Follow a local `$ref` such as `#/components/schemas/User`
+fn resolve<'a>(document: &'a Value, value: &'a Value) -> &'a Value {
+    match value["$ref"].as_str().and_then(|r| r.strip_prefix('#')) {
+        Some(pointer) => document.pointer(pointer).unwrap_or(&Value::Null),
+        None => value,
+    }
+}
+
+/// Build a plausible example value for a JSON schema
+///
+/// Explicit `example`, `default`, `const` and `enum` values win; otherwise a
+/// placeholder is derived from the type and format. `$ref`s are resolved
+/// against `document`.
+pub fn example_value(document: &Value, schema: &Value) -> Value {
+    example_at(document, schema, 0)
+}
+
+fn example_at(document: &Value, schema: &Value, depth: usize) -> Value {
+    if depth > MAX_EXAMPLE_DEPTH {
+        return Value::Null;
+    }
+    let schema = resolve(document, schema);
+
+    for key in ["example", "default", "const"] {
+        if let Some(value) = schema.get(key) {
+            return value.clone();
+        }
+    }
+    if let Some(first) = schema["enum"].get(0) {
+        return first.clone();
+    }
+    for key in ["oneOf", "anyOf"] {
+        if let Some(first) = schema[key].get(0) {
+            return example_at(document, first, depth + 1);
+        }
+    }
+    if let Some(parts) = schema["allOf"].as_array() {
+        let mut merged = Map::new();
+        for part in parts {
+            if let Value::Object(obj) = example_at(document, part, depth + 1) {
+                merged.extend(obj);
+            }
+        }
+        return Value::Object(merged);
+    }
+
+    // 3.1 allows `type: ["string", "null"]`; use the first non-null type
+    let ty = match &schema["type"] {
+        Value::Array(types) => types.iter().find(|t| *t != "null").and_then(Value::as_str),
+        other => other.as_str(),
+    };
+
+    match ty {
+        Some("string") => json!(match schema["format"].as_str() {
+            Some("date-time") => "2024-01-01T00:00:00Z",
+            Some("date") => "2024-01-01",
+            Some("uuid") => "00000000-0000-0000-0000-000000000000",
+            Some("email") => "user@example.com",
+            Some("uri") => "https://example.com",
+            _ => "string",
+        }),
+        Some("integer") => json!(schema["minimum"].as_i64().unwrap_or(0)),
+        Some("number") => json!(schema["minimum"].as_f64().unwrap_or(0.0)),
+        Some("boolean") => json!(true),
+        Some("null") => Value::Null,
+        Some("array") => match schema.get("items") {
+            Some(items) => json!([example_at(document, items, depth + 1)]),
+            None => json!([]),
+        },
+        _ => match schema["properties"].as_object() {
+            Some(properties) => Value::Object(
+                properties
+                    .iter()
+                    .map(|(name, prop)| (name.clone(), example_at(document, prop, depth + 1)))
+                    .collect(),
+            ),
+            None => json!({}),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use schema::Schema;
+    use schema_openapi::to_openapi_schema;
+
+    #[derive(Schema)]
+    #[allow(dead_code)]
+    struct CreatePet {
+        name: String,
+        age: u8,
+        tags: Vec<String>,
+        nickname: Option<String>,
+    }
+
+    fn document() -> Value {
+        json!({
+            "openapi": "3.0.3",
+            "info": { "title": "Pet Store", "version": "1.0.0" },
+            "servers": [{ "url": "https://api.example.com" }],
+            "paths": {
+                "/pets": {
+                    "post": {
+                        "tags": ["pets"],
+                        "summary": "Create a pet",
+                        "requestBody": {
+                            "content": {
+                                "application/json": {
+                                    "schema": { "$ref": "#/components/schemas/CreatePet" }
+                                }
+                            }
+                        }
+                    },
+                    "get": {
+                        "tags": ["pets"],
+                        "operationId": "listPets",
+                        "parameters": [
+                            { "name": "limit", "in": "query", "schema": { "type": "integer", "minimum": 1 } }
+                        ]
+                    }
+                },
+                "/pets/{petId}": {
+                    "parameters": [
+                        { "name": "petId", "in": "path", "required": true, "schema": { "type": "string" } }
+                    ],
+                    "delete": {}
+                }
+            },
+            "components": {
+                "schemas": { "CreatePet": to_openapi_schema::<CreatePet>() }
+            }
+        })
+    }
+
+    #[test]
+    fn test_collection_structure() {
+        let collection = openapi_to_postman(&document());
+
+        assert_eq!(collection["info"]["name"], "Pet Store");
+        assert_eq!(collection["info"]["schema"], COLLECTION_SCHEMA);
+        assert_eq!(
+            collection["variable"][0]["value"],
+            "https://api.example.com"
+        );
+
+        let items = collection["item"].as_array().unwrap();
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[0]["name"], "pets");
+        assert_eq!(items[0]["item"][0]["name"], "listPets");
+        assert_eq!(items[0]["item"][1]["name"], "Create a pet");
+        assert_eq!(items[1]["name"], "DELETE /pets/{petId}");
+    }
+
+    #[test]
+    fn test_urls_and_parameters() {
+        let collection = openapi_to_postman(&document());
+
+        let list = &collection["item"][0]["item"][0]["request"]["url"];
+        assert_eq!(list["raw"], "{{baseUrl}}/pets?limit=1");
+        assert_eq!(list["query"][0]["key"], "limit");
+
+        let delete = &collection["item"][1]["request"]["url"];
+        assert_eq!(delete["path"], json!(["pets", ":petId"]));
+        assert_eq!(delete["variable"][0]["key"], "petId");
+    }
+
+    #[test]
+    fn test_example_body_from_schema() {
+        let collection = openapi_to_postman(&document());
+        let request = &collection["item"][0]["item"][1]["request"];
+
+        assert_eq!(request["method"], "POST");
+        assert_eq!(request["header"][0]["value"], "application/json");
+
+        let body: Value = serde_json::from_str(request["body"]["raw"].as_str().unwrap()).unwrap();
+        assert_eq!(body["name"], "string");
+        assert_eq!(body["age"], 0);
+        assert_eq!(body["tags"], json!(["string"]));
+    }
+
+    #[test]
+    fn test_recursive_ref_terminates() {
+        let document = json!({
+            "components": { "schemas": { "Node": {
+                "type": "object",
+                "properties": { "next": { "$ref": "#/components/schemas/Node" } }
+            } } }
+        });
+
+        let example = example_value(&document, &json!({ "$ref": "#/components/schemas/Node" }));
+        assert!(example["next"]["next"].is_object());
+    }
+}
@@ -0,0 +1,598 @@
+//! Schemas as prose for LLM prompts
+//!
+//! Prompts that ask for JSON without tool use only need the model to know
+//! the shape, and a JSON Schema spends most of its tokens on syntax. This
+//! renders a schema as an indented outline instead:
+//!
+//! ```text
+//! Respond with an object containing:
+//! - name (string): Display name
+//! - age (integer, optional, at least 0)
+//! - role ("admin" | "member")
+//! - address (object):
+//!   - street (string)
+//! ```
+//!
+//! Scalars, enums and collections of them are written inline in a
+//! TypeScript-like notation; objects, variants and anything holding them get
+//! a nested list. Variant cases are spelled out in the JSON each serde
+//! tagging produces. [`Verbosity`] trades detail for length.
+
+use schema::{Constraints, GenerateOptions, Schema, SchemaType, Tagging, TypeKind, VariantCase};
+use std::collections::BTreeSet;
+
+/// How much of the schema beyond its shape to include
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Verbosity {
+    /// Names, types and optionality only
+    Terse,
+    /// Also the first paragraph of each description, and constraints
+    #[default]
+    Normal,
+    /// Also full descriptions, defaults, and deprecated, read-only and
+    /// write-only markers
+    Detailed,
+}
+
+/// Options for the rendered description
+#[derive(Debug, Clone)]
+pub struct PromptOptions {
+    /// Words the description opens with, before the root type
+    pub lead: String,
+    pub verbosity: Verbosity,
+    pub generate: GenerateOptions,
+}
+
+impl PromptOptions {
+    pub fn new() -> Self {
+        PromptOptions {
+            lead: "Respond with".to_string(),
+            verbosity: Verbosity::default(),
+            generate: GenerateOptions::default(),
+        }
+    }
+
+    /// Open with `lead` instead of "Respond with", e.g. "Return"
+    pub fn lead(mut self, lead: &str) -> Self {
+        self.lead = lead.to_string();
+        self
+    }
+
+    pub fn verbosity(mut self, verbosity: Verbosity) -> Self {
+        self.verbosity = verbosity;
+        self
+    }
+
+    /// Bound the schema before it is written
+    pub fn generate(mut self, generate: GenerateOptions) -> Self {
+        self.generate = generate;
+        self
+    }
+}
+
+impl Default for PromptOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A prose description of `T`
+pub fn to_prompt<T: Schema>(options: &PromptOptions) -> String {
+    schema_type_to_prompt(&T::schema(), options)
+}
+
+/// A prose description of a SchemaType
+pub fn schema_type_to_prompt(schema: &SchemaType, options: &PromptOptions) -> String {
+    let schema = options.generate.apply(schema);
+    let mut referenced = BTreeSet::new();
+    collect_refs(&schema, &mut referenced);
+    let writer = Writer {
+        verbosity: options.verbosity,
+        referenced,
+    };
+
+    let rendered = writer.render(&schema);
+    let mut output = format!("{} {}", options.lead, with_article(&rendered.summary));
+    if !rendered.lines.is_empty() {
+        output.push_str(match &schema.kind {
+            TypeKind::Object { .. } | TypeKind::TaggedUnion { .. } => " containing:",
+            TypeKind::Array { .. } | TypeKind::Set { .. } => ", each containing:",
+            _ => ":",
+        });
+    } else if let Some(notes) = writer.notes(&schema) {
+        // A single-line root still gets its constraints and description
+        output.push_str(&format!(" ({})", notes));
+    }
+    for line in rendered.lines {
+        output.push('\n');
+        output.push_str(&line);
+    }
+    output
+}
+
+/// A type as a short phrase, plus the nested list describing its parts
+struct Rendered {
+    summary: String,
+    /// `- ...` lines, indented relative to the summary's own line
+    lines: Vec<String>,
+}
+
+impl Rendered {
+    fn inline(summary: String) -> Self {
+        Rendered {
+            summary,
+            lines: vec![],
+        }
+    }
+}
+
+struct Writer {
+    verbosity: Verbosity,
+    /// Names `Ref`s point at, whose declarations are labelled so the
+    /// references can be followed
+    referenced: BTreeSet<String>,
+}
+
+impl Writer {
+    fn render(&self, schema: &SchemaType) -> Rendered {
+        let mut rendered = match &schema.kind {
+            TypeKind::String => Rendered::inline("string".to_string()),
+            TypeKind::Integer(_) => Rendered::inline("integer".to_string()),
+            TypeKind::Number(_) => Rendered::inline("number".to_string()),
+            TypeKind::Boolean => Rendered::inline("boolean".to_string()),
+            TypeKind::Null => Rendered::inline("null".to_string()),
+            TypeKind::Enum { variants } => Rendered::inline(quoted_union(variants)),
+            TypeKind::Ref { name } => Rendered::inline(name.clone()),
+            TypeKind::Array { items } => self.list("list of", items),
+            TypeKind::Set { items, .. } => self.list("list of unique", items),
+            TypeKind::Map { key, value, .. } => {
+                let key = self.render(key);
+                let value = self.render(value);
+                Rendered {
+                    summary: format!("object mapping {} keys to {}", key.summary, value.summary),
+                    lines: value.lines,
+                }
+            }
+            TypeKind::Object { required, .. } => Rendered {
+                summary: "object".to_string(),
+                lines: schema
+                    .ordered_properties()
+                    .into_iter()
+                    .flat_map(|(name, field)| {
+                        self.field(name, field, !required.iter().any(|r| r == name))
+                    })
+                    .collect(),
+            },
+            TypeKind::TaggedUnion {
+                tag_field,
+                tag_variants,
+                data_fields,
+            } => {
+                let mut fields: Vec<_> = data_fields.iter().collect();
+                fields.sort_by_key(|(name, _)| *name);
+                let mut lines = vec![format!("- {} ({})", tag_field, quoted_union(tag_variants))];
+                for (name, field) in fields {
+                    lines.extend(self.field(name, field, true));
+                }
+                Rendered {
+                    summary: "object".to_string(),
+                    lines,
+                }
+            }
+            TypeKind::Variant { cases } => {
+                let tagging = schema.tagging();
+                Rendered {
+                    summary: "one of".to_string(),
+                    lines: cases
+                        .iter()
+                        .flat_map(|case| self.case(case, tagging.as_ref()))
+                        .collect(),
+                }
+            }
+            TypeKind::Result { ok, err } => Rendered {
+                summary: "one of".to_string(),
+                lines: [("ok", ok), ("error", err)]
+                    .into_iter()
+                    .flat_map(|(key, data)| {
+                        let data = self.render(data);
+                        item(format!("{{\"{}\": {}}}", key, data.summary), data.lines)
+                    })
+                    .collect(),
+            },
+            TypeKind::Tuple { fields } => {
+                let fields: Vec<Rendered> = fields.iter().map(|f| self.render(f)).collect();
+                if fields.iter().all(|field| field.lines.is_empty()) {
+                    let summaries: Vec<_> = fields.into_iter().map(|f| f.summary).collect();
+                    Rendered::inline(format!("[{}]", summaries.join(", ")))
+                } else {
+                    Rendered {
+                        summary: format!("list of exactly {} items, in order", fields.len()),
+                        lines: fields
+                            .into_iter()
+                            .enumerate()
+                            .flat_map(|(i, field)| {
+                                item(format!("item {} ({})", i + 1, field.summary), field.lines)
+                            })
+                            .collect(),
+                    }
+                }
+            }
+        };
+
+        if let Some(name) = &schema.name
+            && self.referenced.contains(name)
+            && !matches!(schema.kind, TypeKind::Ref { .. })
+        {
+            rendered.summary = format!("{} {}", name, rendered.summary);
+        }
+        rendered
+    }
+
+    fn list(&self, prefix: &str, items: &SchemaType) -> Rendered {
+        let items = self.render(items);
+        Rendered {
+            summary: format!("{} {}", prefix, items.summary),
+            lines: items.lines,
+        }
+    }
+
+    /// `- name (type, notes): description`, followed by the type's parts
+    fn field(&self, name: &str, field: &SchemaType, optional: bool) -> Vec<String> {
+        let rendered = self.render(field);
+        let mut details = vec![rendered.summary];
+        if optional {
+            details.push("optional".to_string());
+        }
+        details.extend(self.constraint_notes(field));
+
+        let mut line = format!("{} ({})", name, details.join(", "));
+        if let Some(docs) = self.docs(field.docs()) {
+            line.push_str(&format!(": {}", docs));
+        }
+        if !rendered.lines.is_empty() && !line.ends_with(':') {
+            line.push(':');
+        }
+        item(line, rendered.lines)
+    }
+
+    /// A case as the JSON it's written as
+    fn case(&self, case: &VariantCase, tagging: Option<&Tagging>) -> Vec<String> {
+        let data = case.data.as_ref().map(|data| self.render(data));
+        let is_object = case
+            .data
+            .as_ref()
+            .is_some_and(|data| matches!(data.kind, TypeKind::Object { .. }));
+        let name = &case.name;
+
+        let (mut line, lines) = match (tagging, data) {
+            (None, None) => (format!("\"{}\"", name), vec![]),
+            (None, Some(data)) => (
+                format!("{{\"type\": \"{}\", \"data\": {}}}", name, data.summary),
+                data.lines,
+            ),
+            (Some(Tagging::Internal { tag }), None) => {
+                (format!("{{\"{}\": \"{}\"}}", tag, name), vec![])
+            }
+            (Some(Tagging::Internal { tag }), Some(data)) if is_object => (
+                format!("object with \"{}\": \"{}\", and", tag, name),
+                data.lines,
+            ),
+            (Some(Tagging::Internal { tag }), Some(data)) => (
+                format!("{} with \"{}\": \"{}\"", data.summary, tag, name),
+                data.lines,
+            ),
+            (Some(Tagging::Adjacent { tag, .. }), None) => {
+                (format!("{{\"{}\": \"{}\"}}", tag, name), vec![])
+            }
+            (Some(Tagging::Adjacent { tag, content }), Some(data)) => (
+                format!(
+                    "{{\"{}\": \"{}\", \"{}\": {}}}",
+                    tag, name, content, data.summary
+                ),
+                data.lines,
+            ),
+            (Some(Tagging::Untagged), None) => ("null".to_string(), vec![]),
+            (Some(Tagging::Untagged), Some(data)) => (data.summary, data.lines),
+        };
+
+        let mut notes = vec![];
+        if case.deprecated() && self.verbosity == Verbosity::Detailed {
+            notes.push("deprecated".to_string());
+        }
+        if !notes.is_empty() {
+            line.push_str(&format!(" ({})", notes.join(", ")));
+        }
+        if let Some(docs) = self.docs(case.description.clone()) {
+            line.push_str(&format!(": {}", docs));
+        }
+        if !lines.is_empty() && !line.ends_with(':') {
+            line.push(':');
+        }
+        item(line, lines)
+    }
+
+    /// Constraints and annotations of the root, when it's a single line
+    fn notes(&self, schema: &SchemaType) -> Option<String> {
+        let mut notes = self.constraint_notes(schema);
+        notes.extend(self.docs(schema.docs()));
+        (!notes.is_empty()).then(|| notes.join(", "))
+    }
+
+    /// Bounds, lengths, patterns and formats, then (when detailed) the
+    /// default and access markers
+    fn constraint_notes(&self, schema: &SchemaType) -> Vec<String> {
+        if self.verbosity == Verbosity::Terse {
+            return vec![];
+        }
+
+        let mut notes = bounds_notes(&schema.constraints);
+        if self.verbosity == Verbosity::Detailed {
+            if let Some(default) = &schema.default {
+                notes.push(format!("default {}", default));
+            }
+            if schema.deprecated() {
+                notes.push("deprecated".to_string());
+            }
+            if schema.read_only() {
+                notes.push("read-only".to_string());
+            }
+            if schema.write_only() {
+                notes.push("write-only".to_string());
+            }
+        }
+        notes
+    }
+
+    /// Docs on one line: the first paragraph, or everything when detailed
+    fn docs(&self, docs: Option<String>) -> Option<String> {
+        let docs = docs?;
+        let docs = match self.verbosity {
+            Verbosity::Terse => return None,
+            Verbosity::Normal => docs.split("\n\n").next().unwrap_or_default().to_string(),
+            Verbosity::Detailed => docs,
+        };
+        Some(docs.split_whitespace().collect::<Vec<_>>().join(" "))
+    }
+}
+
+/// `- line` followed by `lines` indented beneath it
+fn item(line: String, lines: Vec<String>) -> Vec<String> {
+    std::iter::once(format!("- {}", line))
+        .chain(lines.into_iter().map(|l| format!("  {}", l)))
+        .collect()
+}
+
+fn quoted_union(values: &[String]) -> String {
+    values
+        .iter()
+        .map(|value| format!("\"{}\"", value))
+        .collect::<Vec<_>>()
+        .join(" | ")
+}
+
+/// Constraints as short phrases, e.g. `1 to 5 items`
+fn bounds_notes(constraints: &Constraints) -> Vec<String> {
+    let mut notes = vec![];
+    if let Some(note) = range(constraints.minimum, constraints.maximum, "") {
+        notes.push(note);
+    }
+    let length = |n: usize| n as f64;
+    if let Some(note) = range(
+        constraints.min_length.map(length),
+        constraints.max_length.map(length),
+        " characters",
+    ) {
+        notes.push(note);
+    }
+    if let Some(note) = range(
+        constraints.min_items.map(length),
+        constraints.max_items.map(length),
+        " items",
+    ) {
+        notes.push(note);
+    }
+    if constraints.unique_items {
+        notes.push("unique".to_string());
+    }
+    if let Some(pattern) = &constraints.pattern {
+        notes.push(format!("matching `{}`", pattern));
+    }
+    if let Some(format) = &constraints.format {
+        notes.push(format!("format {}", format));
+    }
+    notes
+}
+
+fn range(min: Option<f64>, max: Option<f64>, unit: &str) -> Option<String> {
+    Some(match (min, max) {
+        (Some(min), Some(max)) if min == max => format!("exactly {}{}", min, unit),
+        (Some(min), Some(max)) => format!("{} to {}{}", min, max, unit),
+        (Some(min), None) => format!("at least {}{}", min, unit),
+        (None, Some(max)) => format!("at most {}{}", max, unit),
+        (None, None) => return None,
+    })
+}
+
+/// "an object", "a list of string"; phrases that aren't nouns stay as they are
+fn with_article(summary: &str) -> String {
+    let Some(first) = summary.chars().next() else {
+        return summary.to_string();
+    };
+    if !first.is_alphabetic() || summary.starts_with("one of") || summary == "null" {
+        return summary.to_string();
+    }
+    let article = if "aeiouAEIOU".contains(first) {
+        "an"
+    } else {
+        "a"
+    };
+    format!("{} {}", article, summary)
+}
+
+fn collect_refs(schema: &SchemaType, names: &mut BTreeSet<String>) {
+    match &schema.kind {
+        TypeKind::Ref { name } => {
+            names.insert(name.clone());
+        }
+        TypeKind::Object { properties, .. } => {
+            properties.values().for_each(|p| collect_refs(p, names));
+        }
+        TypeKind::TaggedUnion { data_fields, .. } => {
+            data_fields.values().for_each(|f| collect_refs(f, names));
+        }
+        TypeKind::Array { items } | TypeKind::Set { items, .. } => collect_refs(items, names),
+        TypeKind::Map { key, value, .. } => {
+            collect_refs(key, names);
+            collect_refs(value, names);
+        }
+        TypeKind::Variant { cases } => cases
+            .iter()
+            .filter_map(|case| case.data.as_ref())
+            .for_each(|data| collect_refs(data, names)),
+        TypeKind::Result { ok, err } => {
+            collect_refs(ok, names);
+            collect_refs(err, names);
+        }
+        TypeKind::Tuple { fields } => fields.iter().for_each(|f| collect_refs(f, names)),
+        TypeKind::String
+        | TypeKind::Integer(_)
+        | TypeKind::Number(_)
+        | TypeKind::Boolean
+        | TypeKind::Null
+        | TypeKind::Enum { .. } => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_object_outline() {
+        #[derive(Schema)]
+        #[allow(dead_code)]
+        struct Address {
+            street: String,
+            /// Postal code
+            ///
+            /// Five digits in the US.
+            #[schema(pattern = "^[0-9]{5}$")]
+            zip: Option<String>,
+        }
+
+        #[derive(Schema)]
+        #[allow(dead_code)]
+        enum Role {
+            Admin,
+            Member,
+        }
+
+        #[derive(Schema)]
+        #[allow(dead_code)]
+        struct Person {
+            /// Display name
+            name: String,
+            #[schema(minimum = 0)]
+            age: Option<u32>,
+            role: Role,
+            #[schema(min_items = 1, max_items = 5)]
+            tags: Vec<String>,
+            addresses: Vec<Address>,
+        }
+
+        assert_eq!(
+            to_prompt::<Person>(&PromptOptions::new()),
+            "Respond with an object containing:\n\
+             - name (string): Display name\n\
+             - age (integer, optional, at least 0)\n\
+             - role (\"Admin\" | \"Member\")\n\
+             - tags (list of string, 1 to 5 items)\n\
+             - addresses (list of object):\n  \
+               - street (string)\n  \
+               - zip (string, optional, matching `^[0-9]{5}$`): Postal code"
+        );
+
+        assert_eq!(
+            to_prompt::<Person>(
+                &PromptOptions::new()
+                    .verbosity(Verbosity::Terse)
+                    .lead("Return")
+            ),
+            "Return an object containing:\n\
+             - name (string)\n\
+             - age (integer, optional)\n\
+             - role (\"Admin\" | \"Member\")\n\
+             - tags (list of string)\n\
+             - addresses (list of object):\n  \
+               - street (string)\n  \
+               - zip (string, optional)"
+        );
+    }
+
+    #[test]
+    fn test_variant_taggings() {
+        #[derive(Schema)]
+        #[allow(dead_code)]
+        enum Shape {
+            /// No area
+            Dot,
+            Circle {
+                radius: f64,
+            },
+            Line(f64),
+        }
+
+        let mut schema = Shape::schema();
+        let prompt = |schema: &SchemaType| schema_type_to_prompt(schema, &PromptOptions::new());
+        assert_eq!(
+            prompt(&schema),
+            "Respond with one of:\n\
+             - \"Dot\": No area\n\
+             - {\"type\": \"Circle\", \"data\": object}:\n  \
+               - radius (number)\n\
+             - {\"type\": \"Line\", \"data\": number}"
+        );
+
+        schema.set_tagging(Tagging::Internal {
+            tag: "kind".to_string(),
+        });
+        assert_eq!(
+            prompt(&schema),
+            "Respond with one of:\n\
+             - {\"kind\": \"Dot\"}: No area\n\
+             - object with \"kind\": \"Circle\", and:\n  \
+               - radius (number)\n\
+             - number with \"kind\": \"Line\""
+        );
+
+        schema.set_tagging(Tagging::Adjacent {
+            tag: "kind".to_string(),
+            content: "value".to_string(),
+        });
+        assert!(prompt(&schema).contains("- {\"kind\": \"Line\", \"value\": number}"));
+    }
+
+    #[test]
+    fn test_detailed_and_recursive() {
+        /// A directory tree
+        #[derive(Schema)]
+        #[allow(dead_code)]
+        struct Node {
+            /// File name
+            ///
+            /// Unique among siblings.
+            name: String,
+            #[schema(default)]
+            size: u64,
+            children: Vec<Node>,
+        }
+
+        assert_eq!(
+            to_prompt::<Node>(&PromptOptions::new().verbosity(Verbosity::Detailed)),
+            "Respond with a Node object containing:\n\
+             - name (string): File name Unique among siblings.\n\
+             - size (integer, optional, default 0)\n\
+             - children (list of Node)"
+        );
+    }
+}
@@ -0,0 +1,862 @@
+//! Protobuf 3 definitions from schemas
+//!
+//! The root and every named type nested in it become a top-level `message`
+//! (or `enum`), each after the ones it uses. Non-required fields are
+//! `optional`, arrays and sets `repeated`, maps `map<K, V>`, and variants a
+//! message holding one `oneof`. Anonymous types that need a declaration are
+//! named after where they appear (`EventMove`), as are the wrapper messages
+//! for positions protobuf can't nest directly, such as a list of lists.
+//!
+//! Field numbers follow declaration order, skipping protobuf's reserved
+//! range. Adding a field anywhere but the end renumbers those after it, so
+//! pin numbers that are already on the wire with
+//! `#[schema(extension("x-proto-field" = 3))]` on the field or variant;
+//! automatic numbers skip the pinned ones.
+//!
+//! The binary encoding is the goal: protobuf's JSON mapping of these
+//! messages differs from serde's for variants, tuples and non-string map
+//! keys. Field names keep their wire names, with `json_name` where
+//! protobuf's default camelCase would differ.
+
+use schema::case::Case;
+use schema::{
+    GenerateOptions, IntegerKind, NumberKind, RefPreference, Schema, SchemaType, TypeKind,
+};
+use serde_json::Value;
+use std::collections::BTreeSet;
+use std::fmt;
+
+/// Extension pinning a field's or variant case's number
+pub const FIELD_NUMBER: &str = "x-proto-field";
+
+/// Largest field number protobuf accepts
+const MAX_FIELD_NUMBER: u64 = 536_870_911;
+
+/// Field numbers protobuf reserves for its own implementation
+const RESERVED: std::ops::RangeInclusive<u64> = 19_000..=19_999;
+
+/// Options for the generated `.proto` file
+#[derive(Debug, Clone, Default)]
+pub struct ProtoOptions {
+    /// `package` declaration, e.g. `acme.billing.v1`
+    pub package: Option<String>,
+    pub generate: GenerateOptions,
+}
+
+impl ProtoOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn package(mut self, package: &str) -> Self {
+        self.package = Some(package.to_string());
+        self
+    }
+
+    /// Bound the schema before it is written
+    pub fn generate(mut self, generate: GenerateOptions) -> Self {
+        self.generate = generate;
+        self
+    }
+}
+
+/// Why a schema has no `.proto` definition
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProtoError {
+    /// A pinned number is zero, too large, or in the reserved range
+    InvalidFieldNumber {
+        message: String,
+        field: String,
+        number: u64,
+    },
+    /// Two fields of one message are pinned to the same number
+    DuplicateFieldNumber { message: String, number: u64 },
+}
+
+impl fmt::Display for ProtoError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProtoError::InvalidFieldNumber {
+                message,
+                field,
+                number,
+            } => write!(
+                f,
+                "{}.{} is pinned to {}, which is not a valid field number",
+                message, field, number
+            ),
+            ProtoError::DuplicateFieldNumber { message, number } => {
+                write!(f, "{} has two fields numbered {}", message, number)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ProtoError {}
+
+/// The `.proto` file for `T` and the named types it contains
+pub fn to_proto<T: Schema>() -> Result<String, ProtoError> {
+    schema_type_to_proto(&T::schema(), T::type_name())
+}
+
+/// The `.proto` file for `T` and the named types it contains, with `options`
+pub fn to_proto_with_options<T: Schema>(options: &ProtoOptions) -> Result<String, ProtoError> {
+    schema_type_to_proto_with_options(&T::schema(), T::type_name(), options)
+}
+
+/// The `.proto` file for a SchemaType, named `type_name` (or the schema's own
+/// name, or `Root`)
+pub fn schema_type_to_proto(
+    schema: &SchemaType,
+    type_name: Option<&str>,
+) -> Result<String, ProtoError> {
+    schema_type_to_proto_with_options(schema, type_name, &ProtoOptions::default())
+}
+
+/// The `.proto` file for a SchemaType, with `options`
+pub fn schema_type_to_proto_with_options(
+    schema: &SchemaType,
+    type_name: Option<&str>,
+    options: &ProtoOptions,
+) -> Result<String, ProtoError> {
+    let schema = options.generate.apply(schema);
+    let mut module = Module::default();
+    let ident = module.unique(&identifier(
+        type_name.or(schema.name.as_deref()).unwrap_or("Root"),
+    ));
+    if let Some(name) = &schema.name {
+        module.named.push(Named {
+            name: name.clone(),
+            shape: shape(&schema),
+            ident: ident.clone(),
+            first: schema.clone(),
+        });
+    }
+    module.declare(&ident, &schema)?;
+
+    let mut output = "syntax = \"proto3\";\n".to_string();
+    if let Some(package) = &options.package {
+        output.push_str(&format!("\npackage {};\n", package));
+    }
+    if !module.imports.is_empty() {
+        output.push('\n');
+        for import in &module.imports {
+            output.push_str(&format!("import \"{}\";\n", import));
+        }
+    }
+    for declaration in &module.output {
+        output.push('\n');
+        output.push_str(declaration);
+    }
+    Ok(output)
+}
+
+/// A named type that has a declaration
+struct Named {
+    name: String,
+    /// Fingerprint without use-site annotations
+    shape: String,
+    ident: String,
+    /// The occurrence the declaration was written from
+    first: SchemaType,
+}
+
+/// How a type sits in a field
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Label {
+    Singular,
+    Repeated,
+    /// `ty` is the whole `map<K, V>`
+    Map,
+}
+
+/// A field's type
+struct Proto {
+    ty: String,
+    label: Label,
+}
+
+impl Proto {
+    fn singular(ty: impl Into<String>) -> Self {
+        Proto {
+            ty: ty.into(),
+            label: Label::Singular,
+        }
+    }
+}
+
+/// One field of a message, before numbering
+struct Field {
+    /// Wire name
+    name: String,
+    proto: Proto,
+    optional: bool,
+    /// Number pinned with [`FIELD_NUMBER`]
+    pinned: Option<u64>,
+    docs: Option<String>,
+    deprecated: bool,
+}
+
+impl Field {
+    fn new(name: &str, proto: Proto) -> Self {
+        Field {
+            name: name.to_string(),
+            proto,
+            optional: false,
+            pinned: None,
+            docs: None,
+            deprecated: false,
+        }
+    }
+}
+
+#[derive(Default)]
+struct Module {
+    named: Vec<Named>,
+    /// Every message and enum name in use
+    taken: BTreeSet<String>,
+    /// Declarations, each after the ones it uses
+    output: Vec<String>,
+    imports: BTreeSet<&'static str>,
+}
+
+impl Module {
+    /// `base`, or `base2`, `base3`, ... if something already has the name
+    fn unique(&mut self, base: &str) -> String {
+        let ident = (1..)
+            .map(|n| match n {
+                1 => base.to_string(),
+                n => format!("{base}{n}"),
+            })
+            .find(|ident| !self.taken.contains(ident))
+            .unwrap();
+        self.taken.insert(ident.clone());
+        ident
+    }
+
+    /// The message or enum `ident` for `schema`; types that are neither are
+    /// wrapped in a message with one `value` field
+    fn declare(&mut self, ident: &str, schema: &SchemaType) -> Result<(), ProtoError> {
+        if declares(schema) {
+            return self.define(ident, schema);
+        }
+        let proto = self.expression(schema, &format!("{ident}Value"))?;
+        let field = Field::new("value", proto);
+        self.message(ident, schema.docs(), vec![field], None)
+    }
+
+    /// The declaration for a kind that has one of its own
+    fn define(&mut self, ident: &str, schema: &SchemaType) -> Result<(), ProtoError> {
+        match &schema.kind {
+            TypeKind::Object { required, .. } => {
+                let mut fields = vec![];
+                for (name, property) in schema.ordered_properties() {
+                    let mut field = self.field(ident, name, property)?;
+                    field.optional = !required.iter().any(|r| r == name);
+                    fields.push(field);
+                }
+                self.message(ident, schema.docs(), fields, None)
+            }
+            TypeKind::Enum { variants } => {
+                let prefix = Case::ScreamingSnake.apply(ident);
+                let mut lines = vec![format!("  {prefix}_UNSPECIFIED = 0;")];
+                for (i, variant) in variants.iter().enumerate() {
+                    lines.push(format!(
+                        "  {}_{} = {};",
+                        prefix,
+                        identifier(&Case::ScreamingSnake.apply(variant)),
+                        i + 1
+                    ));
+                }
+                let mut declaration = comment(schema.docs().as_deref(), "");
+                declaration.push_str(&format!("enum {ident} {{\n{}\n}}\n", lines.join("\n")));
+                self.output.push(declaration);
+                Ok(())
+            }
+            TypeKind::TaggedUnion {
+                tag_field,
+                tag_variants,
+                data_fields,
+            } => {
+                let tag = self.unique(&format!("{ident}{}", pascal(tag_field)));
+                let tag_schema = SchemaType {
+                    kind: TypeKind::Enum {
+                        variants: tag_variants.clone(),
+                    },
+                    ..Default::default()
+                };
+                self.define(&tag, &tag_schema)?;
+
+                let mut fields = vec![Field::new(tag_field, Proto::singular(tag))];
+                let mut names: Vec<&String> = data_fields.keys().collect();
+                names.sort();
+                for name in names {
+                    let mut field = self.field(ident, name, &data_fields[name])?;
+                    field.optional = true;
+                    fields.push(field);
+                }
+                self.message(ident, schema.docs(), fields, None)
+            }
+            TypeKind::Variant { cases } => {
+                let mut fields = vec![];
+                for case in cases {
+                    let proto = match &case.data {
+                        Some(data) => {
+                            let context = format!("{ident}{}", pascal(&case.name));
+                            let proto = self.reference(data, &context)?;
+                            self.singular(proto, &context)?
+                        }
+                        None => self.empty(),
+                    };
+                    let mut field = Field::new(&case.name, proto);
+                    field.pinned = case.extensions.get(FIELD_NUMBER).and_then(Value::as_u64);
+                    field.docs = case.description.clone();
+                    field.deprecated = case.deprecated();
+                    fields.push(field);
+                }
+                let oneof = if fields.iter().any(|f| field_name(&f.name) == "kind") {
+                    "kind_"
+                } else {
+                    "kind"
+                };
+                self.message(ident, schema.docs(), fields, Some(oneof))
+            }
+            TypeKind::Result { ok, err } => {
+                let mut fields = vec![];
+                for (name, suffix, data) in [("ok", "Ok", ok), ("error", "Error", err)] {
+                    let context = format!("{ident}{suffix}");
+                    let proto = self.reference(data, &context)?;
+                    let proto = self.singular(proto, &context)?;
+                    fields.push(Field::new(name, proto));
+                }
+                self.message(ident, schema.docs(), fields, Some("result"))
+            }
+            TypeKind::Tuple { fields: items } => {
+                let mut fields = vec![];
+                for (i, item) in items.iter().enumerate() {
+                    let name = format!("item_{i}");
+                    let proto = self.reference(item, &format!("{ident}Item{i}"))?;
+                    fields.push(Field::new(&name, proto));
+                }
+                self.message(ident, schema.docs(), fields, None)
+            }
+            _ => unreachable!("declares() covers the kinds defined here"),
+        }
+    }
+
+    /// A nested schema: its declaration's name if it is a named type,
+    /// otherwise written in place, with anonymous declarations named after
+    /// `context`
+    fn reference(&mut self, schema: &SchemaType, context: &str) -> Result<Proto, ProtoError> {
+        let Some(name) = &schema.name else {
+            return self.expression(schema, context);
+        };
+        if matches!(schema.kind, TypeKind::Ref { .. })
+            || schema.ref_preference() == Some(RefPreference::Inline)
+        {
+            return self.expression(schema, context);
+        }
+
+        let shape = shape(schema);
+        if let Some(named) = self
+            .named
+            .iter()
+            .find(|named| named.name == *name && named.shape == shape)
+        {
+            return Ok(Proto::singular(named.ident.clone()));
+        }
+
+        // A different type may already have taken the name
+        let ident = self.unique(&identifier(name));
+        self.named.push(Named {
+            name: name.clone(),
+            shape,
+            ident: ident.clone(),
+            first: schema.clone(),
+        });
+        self.declare(&ident, schema)?;
+        Ok(Proto::singular(ident))
+    }
+
+    fn expression(&mut self, schema: &SchemaType, context: &str) -> Result<Proto, ProtoError> {
+        if declares(schema) {
+            let ident = self.unique(context);
+            self.define(&ident, schema)?;
+            return Ok(Proto::singular(ident));
+        }
+
+        Ok(match &schema.kind {
+            TypeKind::String => Proto::singular("string"),
+            TypeKind::Integer(kind) => Proto::singular(integer(kind)),
+            TypeKind::Number(NumberKind::F32) => Proto::singular("float"),
+            TypeKind::Number(NumberKind::F64) => Proto::singular("double"),
+            TypeKind::Boolean => Proto::singular("bool"),
+            TypeKind::Null => self.empty(),
+            TypeKind::Array { items } | TypeKind::Set { items, .. } => {
+                let context = format!("{context}Item");
+                let items = self.reference(items, &context)?;
+                Proto {
+                    ty: self.singular(items, &context)?.ty,
+                    label: Label::Repeated,
+                }
+            }
+            TypeKind::Map { key, value, .. } => {
+                let key_type = match &key.kind {
+                    TypeKind::String | TypeKind::Enum { .. } => Some("string".to_string()),
+                    TypeKind::Integer(kind) => Some(integer(kind).to_string()),
+                    TypeKind::Boolean => Some("bool".to_string()),
+                    _ => None,
+                };
+                let value_context = format!("{context}Value");
+                let value = self.reference(value, &value_context)?;
+                let value = self.singular(value, &value_context)?.ty;
+                match key_type {
+                    Some(key) => Proto {
+                        ty: format!("map<{}, {}>", key, value),
+                        label: Label::Map,
+                    },
+                    // Other keys can't key a map, so list the entries
+                    None => {
+                        let entry = self.unique(&format!("{context}Entry"));
+                        let key = self.reference(key, &format!("{entry}Key"))?;
+                        let fields = vec![
+                            Field::new("key", key),
+                            Field::new("value", Proto::singular(value)),
+                        ];
+                        self.message(&entry, None, fields, None)?;
+                        Proto {
+                            ty: entry,
+                            label: Label::Repeated,
+                        }
+                    }
+                }
+            }
+            TypeKind::Ref { name } => Proto::singular(
+                self.named
+                    .iter()
+                    .find(|named| named.name == *name)
+                    .map(|named| named.ident.clone())
+                    .unwrap_or_else(|| identifier(name)),
+            ),
+            _ => unreachable!("declares() covers the remaining kinds"),
+        })
+    }
+
+    /// A field of the message `owner`, pinned if its schema says so
+    fn field(&mut self, owner: &str, name: &str, schema: &SchemaType) -> Result<Field, ProtoError> {
+        let proto = self.reference(schema, &format!("{owner}{}", pascal(name)))?;
+        let mut field = Field::new(name, proto);
+        field.pinned = schema.extensions.get(FIELD_NUMBER).and_then(Value::as_u64);
+        field.docs = self.use_site_docs(schema);
+        field.deprecated = schema.deprecated();
+        Ok(field)
+    }
+
+    /// The type name for `proto`, wrapping repeated fields and maps in a
+    /// message named `context` where protobuf needs a single value
+    fn singular(&mut self, proto: Proto, context: &str) -> Result<Proto, ProtoError> {
+        if proto.label == Label::Singular {
+            return Ok(proto);
+        }
+        let ident = self.unique(context);
+        let name = match proto.label {
+            Label::Map => "entries",
+            _ => "values",
+        };
+        self.message(&ident, None, vec![Field::new(name, proto)], None)?;
+        Ok(Proto::singular(ident))
+    }
+
+    fn empty(&mut self) -> Proto {
+        self.imports.insert("google/protobuf/empty.proto");
+        Proto::singular("google.protobuf.Empty")
+    }
+
+    /// Write `message ident`, numbering its fields; with `oneof`, all of
+    /// them are its members
+    fn message(
+        &mut self,
+        ident: &str,
+        docs: Option<String>,
+        fields: Vec<Field>,
+        oneof: Option<&str>,
+    ) -> Result<(), ProtoError> {
+        let numbers = numbers(ident, &fields)?;
+        let indent = if oneof.is_some() { "    " } else { "  " };
+        let mut lines = vec![];
+        let names = field_names(&fields);
+        for ((field, number), name) in fields.iter().zip(numbers).zip(names) {
+            let label = match field.proto.label {
+                Label::Repeated => "repeated ",
+                Label::Singular if field.optional && oneof.is_none() => "optional ",
+                _ => "",
+            };
+            let mut options = vec![];
+            if json_name(&name) != field.name {
+                options.push(format!(
+                    "json_name = {}",
+                    serde_json::to_string(&field.name).expect("strings serialize")
+                ));
+            }
+            if field.deprecated {
+                options.push("deprecated = true".to_string());
+            }
+            let options = match options.len() {
+                0 => String::new(),
+                _ => format!(" [{}]", options.join(", ")),
+            };
+            lines.push(format!(
+                "{}{indent}{label}{} {name} = {number}{options};",
+                comment(field.docs.as_deref(), indent),
+                field.proto.ty
+            ));
+        }
+
+        let mut body = lines.join("\n");
+        if let Some(oneof) = oneof {
+            body = format!("  oneof {oneof} {{\n{body}\n  }}");
+        }
+        let mut declaration = comment(docs.as_deref(), "");
+        match body.is_empty() {
+            true => declaration.push_str(&format!("message {ident} {{}}\n")),
+            false => declaration.push_str(&format!("message {ident} {{\n{body}\n}}\n")),
+        }
+        self.output.push(declaration);
+        Ok(())
+    }
+
+    /// The docs for a field, unless its type's declaration already has them
+    fn use_site_docs(&self, schema: &SchemaType) -> Option<String> {
+        schema.docs().filter(|docs| {
+            let declared = schema.name.as_ref().and_then(|name| {
+                self.named
+                    .iter()
+                    .find(|named| named.name == *name && named.shape == shape(schema))
+            });
+            declared.is_none_or(|named| named.first.docs().as_ref() != Some(docs))
+        })
+    }
+}
+
+/// Whether `schema` is written as a message or enum of its own
+fn declares(schema: &SchemaType) -> bool {
+    matches!(
+        schema.kind,
+        TypeKind::Object { .. }
+            | TypeKind::Enum { .. }
+            | TypeKind::TaggedUnion { .. }
+            | TypeKind::Variant { .. }
+            | TypeKind::Result { .. }
+            | TypeKind::Tuple { .. }
+    )
+}
+
+/// Field numbers in order: pinned ones as given, the rest counting up from 1
+/// around them and the reserved range
+fn numbers(message: &str, fields: &[Field]) -> Result<Vec<u64>, ProtoError> {
+    let mut pinned = BTreeSet::new();
+    for field in fields {
+        let Some(number) = field.pinned else {
+            continue;
+        };
+        if number == 0 || number > MAX_FIELD_NUMBER || RESERVED.contains(&number) {
+            return Err(ProtoError::InvalidFieldNumber {
+                message: message.to_string(),
+                field: field.name.clone(),
+                number,
+            });
+        }
+        if !pinned.insert(number) {
+            return Err(ProtoError::DuplicateFieldNumber {
+                message: message.to_string(),
+                number,
+            });
+        }
+    }
+
+    let mut next = 1;
+    Ok(fields
+        .iter()
+        .map(|field| {
+            field.pinned.unwrap_or_else(|| {
+                while pinned.contains(&next) || RESERVED.contains(&next) {
+                    next += 1;
+                }
+                next += 1;
+                next - 1
+            })
+        })
+        .collect())
+}
+
+fn integer(kind: &IntegerKind) -> &'static str {
+    match kind {
+        IntegerKind::I32 => "int32",
+        IntegerKind::I64 => "int64",
+        IntegerKind::U8 | IntegerKind::U32 => "uint32",
+        IntegerKind::U64 | IntegerKind::Usize => "uint64",
+    }
+}
+
+/// `// ` lines for `docs` at `indent`, ending in a newline
+fn comment(docs: Option<&str>, indent: &str) -> String {
+    let Some(docs) = docs else {
+        return String::new();
+    };
+    docs.lines()
+        .map(|line| match line {
+            "" => format!("{indent}//\n"),
+            line => format!("{indent}// {line}\n"),
+        })
+        .collect()
+}
+
+/// The JSON name protobuf derives from a field name: underscores dropped,
+/// capitalizing the letter after each
+fn json_name(name: &str) -> String {
+    let mut json = String::new();
+    let mut capitalize = false;
+    for c in name.chars() {
+        if c == '_' {
+            capitalize = true;
+        } else if capitalize {
+            json.extend(c.to_uppercase());
+            capitalize = false;
+        } else {
+            json.push(c);
+        }
+    }
+    json
+}
+
+/// A message name for part of another's, `per_role` -> `PerRole`
+fn pascal(name: &str) -> String {
+    identifier(&Case::Pascal.apply(name))
+}
+
+/// A type name usable as a protobuf identifier
+fn identifier(name: &str) -> String {
+    let ident: String = name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    match ident.starts_with(|c: char| c.is_ascii_digit()) {
+        true => format!("_{ident}"),
+        false => ident,
+    }
+}
+
+/// A field name in protobuf's snake_case
+fn field_name(name: &str) -> String {
+    let ident = identifier(&Case::Snake.apply(name));
+    match ident.starts_with('_') {
+        true => format!("field{ident}"),
+        false => ident,
+    }
+}
+
+/// The names of one message's fields, numbered where two wire names have
+/// the same snake_case
+fn field_names(fields: &[Field]) -> Vec<String> {
+    let mut taken = BTreeSet::new();
+    fields
+        .iter()
+        .map(|field| {
+            let base = field_name(&field.name);
+            (1..)
+                .map(|n| match n {
+                    1 => base.clone(),
+                    n => format!("{base}{n}"),
+                })
+                .find(|name| taken.insert(name.clone()))
+                .unwrap()
+        })
+        .collect()
+}
+
+/// What makes two occurrences of a named type the same declaration: its
+/// fingerprint without the annotations a field adds to it
+fn shape(schema: &SchemaType) -> String {
+    let mut bare = schema.clone();
+    bare.title = None;
+    bare.description = None;
+    bare.default = None;
+    for key in [
+        schema::DEPRECATED,
+        schema::READ_ONLY,
+        schema::WRITE_ONLY,
+        schema::SINCE,
+        schema::REF_PREFERENCE,
+        FIELD_NUMBER,
+    ] {
+        bare.extensions.remove(key);
+    }
+    bare.fingerprint()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_numbers_skip_pins() {
+        #[derive(Schema)]
+        #[allow(dead_code)]
+        enum Status {
+            Active,
+            Closed,
+        }
+
+        #[derive(Schema)]
+        #[allow(dead_code)]
+        struct Account {
+            account_id: u64,
+            #[schema(extension("x-proto-field" = 1))]
+            email: String,
+            status: Status,
+            #[schema(extension("x-proto-field" = 3))]
+            closed_at: Option<i64>,
+            labels: HashMap<String, String>,
+            // The same field name as account_id in snake_case
+            #[schema(rename = "accountId")]
+            legacy_id: u64,
+        }
+
+        let proto = to_proto_with_options::<Account>(&ProtoOptions::new().package("acme.v1"));
+        assert_eq!(
+            proto.unwrap(),
+            r#"syntax = "proto3";
+
+package acme.v1;
+
+enum Status {
+  STATUS_UNSPECIFIED = 0;
+  STATUS_ACTIVE = 1;
+  STATUS_CLOSED = 2;
+}
+
+message Account {
+  uint64 account_id = 2 [json_name = "account_id"];
+  string email = 1;
+  Status status = 4;
+  optional int64 closed_at = 3 [json_name = "closed_at"];
+  // Unordered map/dictionary of key-value pairs
+  map<string, string> labels = 5;
+  uint64 account_id2 = 6 [json_name = "accountId"];
+}
+"#
+        );
+    }
+
+    #[test]
+    fn test_numbers_skip_reserved_range() {
+        let fields: Vec<Field> = (0..19_001)
+            .map(|i| Field::new(&format!("f{i}"), Proto::singular("bool")))
+            .collect();
+        let numbers = numbers("Wide", &fields).unwrap();
+        assert_eq!(numbers[18_997], 18_998);
+        assert_eq!(numbers[18_998], 18_999);
+        assert_eq!(numbers[18_999], 20_000);
+        assert_eq!(numbers[19_000], 20_001);
+    }
+
+    #[test]
+    fn test_oneof_pins_and_name() {
+        #[derive(Schema)]
+        #[allow(dead_code)]
+        enum Filter {
+            All,
+            #[schema(extension("x-proto-field" = 1))]
+            Kind(String),
+            Owner(String),
+        }
+
+        assert_eq!(
+            to_proto::<Filter>().unwrap(),
+            r#"syntax = "proto3";
+
+import "google/protobuf/empty.proto";
+
+message Filter {
+  oneof kind_ {
+    google.protobuf.Empty all = 2 [json_name = "All"];
+    string kind = 1 [json_name = "Kind"];
+    string owner = 3 [json_name = "Owner"];
+  }
+}
+"#
+        );
+    }
+
+    #[test]
+    fn test_variants_become_oneof() {
+        #[derive(Schema)]
+        #[allow(dead_code)]
+        #[schema(tag = "kind")]
+        enum Event {
+            Start,
+            Move { x: i64 },
+            Batch(Vec<Vec<u32>>),
+        }
+
+        assert_eq!(
+            to_proto::<Event>().unwrap(),
+            r#"syntax = "proto3";
+
+import "google/protobuf/empty.proto";
+
+message EventMove {
+  int64 x = 1;
+}
+
+message EventBatchItem {
+  repeated uint32 values = 1;
+}
+
+message EventBatch {
+  repeated EventBatchItem values = 1;
+}
+
+message Event {
+  oneof kind {
+    google.protobuf.Empty start = 1 [json_name = "Start"];
+    EventMove move = 2 [json_name = "Move"];
+    EventBatch batch = 3 [json_name = "Batch"];
+  }
+}
+"#
+        );
+    }
+
+    #[test]
+    fn test_invalid_pins_are_errors() {
+        #[derive(Schema)]
+        #[allow(dead_code)]
+        struct Clash {
+            #[schema(extension("x-proto-field" = 1))]
+            a: String,
+            #[schema(extension("x-proto-field" = 1))]
+            b: String,
+        }
+
+        #[derive(Schema)]
+        #[allow(dead_code)]
+        struct Reserved {
+            #[schema(extension("x-proto-field" = 19000))]
+            a: String,
+        }
+
+        assert_eq!(
+            to_proto::<Clash>().unwrap_err(),
+            ProtoError::DuplicateFieldNumber {
+                message: "Clash".to_string(),
+                number: 1
+            }
+        );
+        assert_eq!(
+            to_proto::<Reserved>().unwrap_err().to_string(),
+            "Reserved.a is pinned to 19000, which is not a valid field number"
+        );
+    }
+}
@@ -0,0 +1,984 @@
+//! Pydantic v2 models from schemas
+//!
+//! The root and every named type nested in it become a Python declaration,
+//! written after the ones it uses: objects are `BaseModel` classes, anything
+//! else a type alias. Field docs become `Field(description=...)`, constraints
+//! and integer kinds become `Field` bounds, and variants follow their serde
+//! tagging, with one model per case and a `Literal` discriminator where every
+//! case is a model.
+//!
+//! Anonymous objects that need a class, such as struct-variant payloads and
+//! `Result` arms, are named after where they appear (`EventMove`). Recursive
+//! references are quoted forward references, and the models containing one
+//! are rebuilt once the whole module is defined.
+
+use schema::case::Case;
+use schema::{
+    Constraints, GenerateOptions, IntegerKind, RefPreference, Schema, SchemaType, Tagging,
+    TypeKind, VariantCase,
+};
+use serde_json::Value;
+use std::collections::BTreeSet;
+
+/// Python keywords, which can't name a field
+const KEYWORDS: &[&str] = &[
+    "False", "None", "True", "and", "as", "assert", "async", "await", "break", "class", "continue",
+    "def", "del", "elif", "else", "except", "finally", "for", "from", "global", "if", "import",
+    "in", "is", "lambda", "nonlocal", "not", "or", "pass", "raise", "return", "try", "while",
+    "with", "yield",
+];
+
+/// Builtins the generated annotations use, which a field of the same name
+/// would shadow inside the class body
+const BUILTINS: &[&str] = &[
+    "bool", "dict", "float", "int", "list", "set", "str", "tuple",
+];
+
+/// The Python module for `T` and the named types it contains
+pub fn to_pydantic<T: Schema>() -> String {
+    schema_type_to_pydantic(&T::schema(), T::type_name())
+}
+
+/// The Python module for `T` and the named types it contains, bounded by `options`
+pub fn to_pydantic_with_options<T: Schema>(options: &GenerateOptions) -> String {
+    schema_type_to_pydantic_with_options(&T::schema(), T::type_name(), options)
+}
+
+/// The Python module for a SchemaType, bounded by `options`
+pub fn schema_type_to_pydantic_with_options(
+    schema: &SchemaType,
+    type_name: Option<&str>,
+    options: &GenerateOptions,
+) -> String {
+    schema_type_to_pydantic(&options.apply(schema), type_name)
+}
+
+/// The Python module for a SchemaType, named `type_name` (or the schema's
+/// own name, or `Root`)
+pub fn schema_type_to_pydantic(schema: &SchemaType, type_name: Option<&str>) -> String {
+    let mut module = Module::default();
+    let ident = module.unique(&identifier(
+        type_name.or(schema.name.as_deref()).unwrap_or("Root"),
+    ));
+    if let Some(name) = &schema.name {
+        module.named.push(Named {
+            name: name.clone(),
+            shape: shape(schema),
+            ident: ident.clone(),
+            first: schema.clone(),
+        });
+    }
+    module.declare(&ident, schema);
+    module.render()
+}
+
+/// A named type that has a declaration
+struct Named {
+    name: String,
+    /// Fingerprint without use-site annotations
+    shape: String,
+    ident: String,
+    /// The occurrence the declaration was written from
+    first: SchemaType,
+}
+
+/// A Python type expression
+struct Py {
+    code: String,
+    /// `Field` arguments constraining it, for the field (or `Annotated`)
+    /// holding it
+    checks: Vec<String>,
+    /// Whether it names a `BaseModel` class, usable as a discriminated union
+    /// member or a base class
+    model: bool,
+}
+
+impl Py {
+    fn other(code: impl Into<String>) -> Self {
+        Py {
+            code: code.into(),
+            checks: vec![],
+            model: false,
+        }
+    }
+
+    fn model(ident: &str) -> Self {
+        Py {
+            code: ident.to_string(),
+            checks: vec![],
+            model: true,
+        }
+    }
+}
+
+#[derive(Default)]
+struct Module {
+    named: Vec<Named>,
+    /// Every class and alias name in use
+    taken: BTreeSet<String>,
+    /// Declarations, each after the ones it uses
+    output: Vec<String>,
+    typing: BTreeSet<&'static str>,
+    pydantic: BTreeSet<&'static str>,
+    /// Models holding forward references, rebuilt at the end
+    rebuild: Vec<String>,
+    /// Forward references written so far
+    forward_refs: usize,
+}
+
+impl Module {
+    fn render(&self) -> String {
+        let mut imports = vec![];
+        if !self.typing.is_empty() {
+            imports.push(format!(
+                "from typing import {}",
+                self.typing.iter().copied().collect::<Vec<_>>().join(", ")
+            ));
+        }
+        if !self.pydantic.is_empty() {
+            imports.push(format!(
+                "from pydantic import {}",
+                self.pydantic.iter().copied().collect::<Vec<_>>().join(", ")
+            ));
+        }
+
+        let mut sections = vec![];
+        if !imports.is_empty() {
+            sections.push(imports.join("\n\n"));
+        }
+        sections.extend(self.output.iter().cloned());
+        if !self.rebuild.is_empty() {
+            sections.push(
+                self.rebuild
+                    .iter()
+                    .map(|ident| format!("{ident}.model_rebuild()"))
+                    .collect::<Vec<_>>()
+                    .join("\n"),
+            );
+        }
+
+        let mut output = sections.join("\n\n\n");
+        output.push('\n');
+        output
+    }
+
+    /// `base`, or `base2`, `base3`, ... if something already has the name
+    fn unique(&mut self, base: &str) -> String {
+        let ident = (1..)
+            .map(|n| match n {
+                1 => base.to_string(),
+                n => format!("{base}{n}"),
+            })
+            .find(|ident| !self.taken.contains(ident))
+            .unwrap();
+        self.taken.insert(ident.clone());
+        ident
+    }
+
+    fn declare(&mut self, ident: &str, schema: &SchemaType) {
+        if let TypeKind::Object { .. } = &schema.kind {
+            self.object_model(ident, "BaseModel", vec![], schema, schema.docs());
+            return;
+        }
+
+        let py = self.expression(schema, ident);
+        let mut declaration = format!("{ident} = {}", self.annotated(py));
+        if let Some(docs) = schema.docs() {
+            declaration.push('\n');
+            declaration.push_str(&docstring(&docs, ""));
+        }
+        self.output.push(declaration);
+    }
+
+    /// A nested schema: its declaration's name if it is a named type,
+    /// otherwise written in place, with anonymous classes named after `context`
+    fn reference(&mut self, schema: &SchemaType, context: &str) -> Py {
+        let Some(name) = &schema.name else {
+            return self.expression(schema, context);
+        };
+        if matches!(schema.kind, TypeKind::Ref { .. })
+            || schema.ref_preference() == Some(RefPreference::Inline)
+        {
+            return self.expression(schema, context);
+        }
+
+        let shape = shape(schema);
+        if let Some(named) = self
+            .named
+            .iter()
+            .find(|named| named.name == *name && named.shape == shape)
+        {
+            return Py {
+                code: named.ident.clone(),
+                checks: vec![],
+                model: matches!(named.first.kind, TypeKind::Object { .. }),
+            };
+        }
+
+        // A different type may already have taken the name
+        let ident = self.unique(&identifier(name));
+        self.named.push(Named {
+            name: name.clone(),
+            shape,
+            ident: ident.clone(),
+            first: schema.clone(),
+        });
+        self.declare(&ident, schema);
+        Py {
+            code: ident,
+            checks: vec![],
+            model: matches!(schema.kind, TypeKind::Object { .. }),
+        }
+    }
+
+    /// The Python type for `schema`, with its constraints as `Field` arguments
+    fn expression(&mut self, schema: &SchemaType, context: &str) -> Py {
+        let constraints = &schema.constraints;
+        match &schema.kind {
+            TypeKind::String => Py {
+                code: "str".to_string(),
+                checks: string_checks(constraints),
+                model: false,
+            },
+            TypeKind::Integer(kind) => Py {
+                code: "int".to_string(),
+                checks: number_checks(constraints, integer_bounds(kind)),
+                model: false,
+            },
+            TypeKind::Number(_) => Py {
+                code: "float".to_string(),
+                checks: number_checks(constraints, (None, None)),
+                model: false,
+            },
+            TypeKind::Boolean => Py::other("bool"),
+            TypeKind::Null => Py::other("None"),
+            TypeKind::Array { items } => {
+                let items = self.reference(items, &format!("{context}Item"));
+                Py {
+                    code: format!("list[{}]", self.annotated(items)),
+                    checks: array_checks(constraints),
+                    model: false,
+                }
+            }
+            TypeKind::Set { items: item, .. } => {
+                // Only hashable items fit a Python set
+                let hashable = matches!(
+                    item.kind,
+                    TypeKind::String
+                        | TypeKind::Integer(_)
+                        | TypeKind::Number(_)
+                        | TypeKind::Boolean
+                        | TypeKind::Enum { .. }
+                );
+                let items = self.reference(item, &format!("{context}Item"));
+                let collection = if hashable { "set" } else { "list" };
+                Py {
+                    code: format!("{collection}[{}]", self.annotated(items)),
+                    checks: array_checks(constraints),
+                    model: false,
+                }
+            }
+            TypeKind::Map { key, value, .. } => {
+                let value = self.reference(value, &format!("{context}Value"));
+                let value = self.annotated(value);
+                Py::other(match &key.kind {
+                    TypeKind::String => format!("dict[str, {}]", value),
+                    TypeKind::Enum { .. } => {
+                        let key = self.reference(key, &format!("{context}Key"));
+                        format!("dict[{}, {}]", key.code, value)
+                    }
+                    // Non-string keys are encoded as a list of [key, value] pairs
+                    _ => {
+                        let key = self.reference(key, &format!("{context}Key"));
+                        format!("list[tuple[{}, {}]]", self.annotated(key), value)
+                    }
+                })
+            }
+            TypeKind::Object { .. } => {
+                let ident = self.unique(context);
+                self.object_model(&ident, "BaseModel", vec![], schema, schema.docs());
+                Py::model(&ident)
+            }
+            TypeKind::Enum { variants } => match variants.len() {
+                0 => self.never(),
+                _ => {
+                    self.typing.insert("Literal");
+                    let variants: Vec<String> = variants.iter().map(|v| literal(v)).collect();
+                    Py::other(format!("Literal[{}]", variants.join(", ")))
+                }
+            },
+            TypeKind::TaggedUnion {
+                tag_field,
+                tag_variants,
+                data_fields,
+            } => {
+                let mut names: Vec<&String> = data_fields.keys().collect();
+                names.sort();
+                let members = tag_variants
+                    .iter()
+                    .map(|variant| {
+                        let ident = self.unique(&format!("{context}{}", pascal(variant)));
+                        let forward_refs = self.forward_refs;
+                        let mut fields = vec![self.tag_field(tag_field, variant)];
+                        for name in &names {
+                            fields.push(self.field(&ident, name, true, &data_fields[*name]));
+                        }
+                        let rebuild = self.forward_refs > forward_refs;
+                        self.model(&ident, "BaseModel", None, None, fields, rebuild);
+                        Py::model(&ident)
+                    })
+                    .collect();
+                self.union(members, Some(tag_field))
+            }
+            TypeKind::Variant { cases } => {
+                let tagging = schema.tagging();
+                let members = cases
+                    .iter()
+                    .map(|case| self.case(case, tagging.as_ref(), context))
+                    .collect();
+                let discriminator = match &tagging {
+                    None => Some("type"),
+                    Some(tagging) => tagging.tag(),
+                };
+                self.union(members, discriminator)
+            }
+            TypeKind::Result { ok, err } => {
+                let members = [("ok", "Ok", ok), ("error", "Error", err)]
+                    .into_iter()
+                    .map(|(field, suffix, data)| {
+                        let ident = self.unique(&format!("{context}{suffix}"));
+                        let forward_refs = self.forward_refs;
+                        let fields = vec![self.field(&ident, field, false, data)];
+                        let rebuild = self.forward_refs > forward_refs;
+                        self.model(&ident, "BaseModel", None, None, fields, rebuild);
+                        Py::model(&ident)
+                    })
+                    .collect();
+                // No discriminator: the arm is told apart by its one field
+                self.union(members, None)
+            }
+            TypeKind::Tuple { fields } => {
+                let fields: Vec<String> = fields
+                    .iter()
+                    .enumerate()
+                    .map(|(i, field)| {
+                        let field = self.reference(field, &format!("{context}{i}"));
+                        self.annotated(field)
+                    })
+                    .collect();
+                Py::other(match fields.len() {
+                    0 => "tuple[()]".to_string(),
+                    _ => format!("tuple[{}]", fields.join(", ")),
+                })
+            }
+            TypeKind::Ref { name } => {
+                let ident = self
+                    .named
+                    .iter()
+                    .find(|named| named.name == *name)
+                    .map(|named| named.ident.clone())
+                    .unwrap_or_else(|| identifier(name));
+                self.forward_refs += 1;
+                Py::other(literal(&ident))
+            }
+        }
+    }
+
+    /// A `BaseModel` class with `leading` fields before the object's own
+    fn object_model(
+        &mut self,
+        ident: &str,
+        base: &str,
+        leading: Vec<String>,
+        object: &SchemaType,
+        docs: Option<String>,
+    ) {
+        let forward_refs = self.forward_refs;
+        let mut fields = leading;
+        if let TypeKind::Object { required, .. } = &object.kind {
+            for (name, property) in object.ordered_properties() {
+                let optional = !required.iter().any(|r| r == name);
+                fields.push(self.field(ident, name, optional, property));
+            }
+        }
+        let config = object.denies_unknown_fields().then_some("forbid");
+        let rebuild = self.forward_refs > forward_refs;
+        self.model(ident, base, docs, config, fields, rebuild);
+    }
+
+    fn model(
+        &mut self,
+        ident: &str,
+        base: &str,
+        docs: Option<String>,
+        extra: Option<&str>,
+        fields: Vec<String>,
+        rebuild: bool,
+    ) {
+        if base == "BaseModel" {
+            self.pydantic.insert("BaseModel");
+        }
+
+        let mut body = vec![];
+        if let Some(docs) = docs {
+            body.push(docstring(&docs, "    "));
+        }
+        if let Some(extra) = extra {
+            self.pydantic.insert("ConfigDict");
+            body.push(format!(
+                "    model_config = ConfigDict(extra={})",
+                literal(extra)
+            ));
+        }
+        if !fields.is_empty() {
+            body.push(
+                fields
+                    .iter()
+                    .map(|field| format!("    {field}"))
+                    .collect::<Vec<_>>()
+                    .join("\n"),
+            );
+        }
+        if body.is_empty() {
+            body.push("    pass".to_string());
+        }
+
+        self.output
+            .push(format!("class {ident}({base}):\n{}", body.join("\n\n")));
+        if rebuild {
+            self.rebuild.push(ident.to_string());
+        }
+    }
+
+    /// `name: type = ...` inside a class belonging to `owner`
+    fn field(&mut self, owner: &str, name: &str, optional: bool, schema: &SchemaType) -> String {
+        let py = self.reference(schema, &format!("{owner}{}", pascal(name)));
+        let mut annotation = py.code;
+        let mut args = vec![];
+        match &schema.default {
+            // serde fills in the default, so validation does too
+            Some(default) => args.push(format!("default={}", python_value(default))),
+            None if optional => {
+                self.typing.insert("Optional");
+                annotation = format!("Optional[{annotation}]");
+                args.push("default=None".to_string());
+            }
+            None => {}
+        }
+
+        let ident = field_name(name);
+        if ident != name {
+            args.push(format!("alias={}", literal(name)));
+        }
+        if let Some(docs) = self.use_site_docs(schema) {
+            args.push(format!("description={}", literal(&docs)));
+        }
+        args.extend(py.checks);
+        if schema.deprecated() {
+            args.push("deprecated=True".to_string());
+        }
+
+        match args.as_slice() {
+            [] => format!("{ident}: {annotation}"),
+            [default] if default.starts_with("default=") => {
+                format!("{ident}: {annotation} = {}", &default["default=".len()..])
+            }
+            _ => {
+                self.pydantic.insert("Field");
+                format!("{ident}: {annotation} = Field({})", args.join(", "))
+            }
+        }
+    }
+
+    /// The discriminator field of a case model, defaulting to the case
+    fn tag_field(&mut self, tag: &str, name: &str) -> String {
+        self.typing.insert("Literal");
+        let ident = field_name(tag);
+        if ident == tag {
+            format!("{ident}: Literal[{0}] = {0}", literal(name))
+        } else {
+            self.pydantic.insert("Field");
+            format!(
+                "{ident}: Literal[{0}] = Field({0}, alias={1})",
+                literal(name),
+                literal(tag)
+            )
+        }
+    }
+
+    /// The docs for a field, unless its type's declaration already has them
+    fn use_site_docs(&self, schema: &SchemaType) -> Option<String> {
+        schema.docs().filter(|docs| {
+            let declared = schema.name.as_ref().and_then(|name| {
+                self.named
+                    .iter()
+                    .find(|named| named.name == *name && named.shape == shape(schema))
+            });
+            declared.is_none_or(|named| named.first.docs().as_ref() != Some(docs))
+        })
+    }
+
+    /// One member of a Variant's union
+    fn case(&mut self, case: &VariantCase, tagging: Option<&Tagging>, context: &str) -> Py {
+        let base = format!("{context}{}", pascal(&case.name));
+        let docs = case.description.clone();
+        match (tagging, &case.data) {
+            (None, None) => {
+                self.typing.insert("Literal");
+                Py::other(format!("Literal[{}]", literal(&case.name)))
+            }
+            (None, Some(data)) => {
+                let ident = self.unique(&base);
+                let forward_refs = self.forward_refs;
+                let fields = vec![
+                    self.tag_field("type", &case.name),
+                    self.field(&ident, "data", false, data),
+                ];
+                let rebuild = self.forward_refs > forward_refs;
+                self.model(&ident, "BaseModel", docs, None, fields, rebuild);
+                Py::model(&ident)
+            }
+            (Some(Tagging::Untagged), None) => Py::other("None"),
+            (Some(Tagging::Untagged), Some(data)) => self.reference(data, &base),
+            (Some(Tagging::Internal { tag } | Tagging::Adjacent { tag, .. }), None) => {
+                let ident = self.unique(&base);
+                let fields = vec![self.tag_field(tag, &case.name)];
+                self.model(&ident, "BaseModel", docs, None, fields, false);
+                Py::model(&ident)
+            }
+            (Some(Tagging::Adjacent { tag, content }), Some(data)) => {
+                let ident = self.unique(&base);
+                let forward_refs = self.forward_refs;
+                let fields = vec![
+                    self.tag_field(tag, &case.name),
+                    self.field(&ident, content, false, data),
+                ];
+                let rebuild = self.forward_refs > forward_refs;
+                self.model(&ident, "BaseModel", docs, None, fields, rebuild);
+                Py::model(&ident)
+            }
+            (Some(Tagging::Internal { tag }), Some(data)) => match &data.kind {
+                // Anonymous struct payloads share the object with the tag
+                TypeKind::Object { .. } if data.name.is_none() => {
+                    let ident = self.unique(&base);
+                    let tag = self.tag_field(tag, &case.name);
+                    self.object_model(&ident, "BaseModel", vec![tag], data, docs);
+                    Py::model(&ident)
+                }
+                _ => {
+                    let payload = self.reference(data, &format!("{base}Data"));
+                    let ident = self.unique(&base);
+                    let tag = self.tag_field(tag, &case.name);
+                    if payload.model {
+                        self.model(&ident, &payload.code, docs, None, vec![tag], false);
+                    } else {
+                        // The payload's entries sit beside the tag, untyped
+                        self.model(&ident, "BaseModel", docs, Some("allow"), vec![tag], false);
+                    }
+                    Py::model(&ident)
+                }
+            },
+        }
+    }
+
+    /// A discriminated union when every member is a model carrying
+    /// `discriminator`, a plain `Union` otherwise
+    fn union(&mut self, members: Vec<Py>, discriminator: Option<&str>) -> Py {
+        match (members.len(), discriminator) {
+            (0, _) => self.never(),
+            (1, _) => members.into_iter().next().unwrap(),
+            (_, Some(tag)) if members.iter().all(|member| member.model) => {
+                self.typing.insert("Annotated");
+                self.typing.insert("Union");
+                self.pydantic.insert("Field");
+                let members: Vec<String> = members.into_iter().map(|m| m.code).collect();
+                Py::other(format!(
+                    "Annotated[Union[{}], Field(discriminator={})]",
+                    members.join(", "),
+                    literal(tag)
+                ))
+            }
+            _ => {
+                self.typing.insert("Union");
+                let members: Vec<String> = members
+                    .into_iter()
+                    .map(|member| self.annotated(member))
+                    .collect();
+                Py::other(format!("Union[{}]", members.join(", ")))
+            }
+        }
+    }
+
+    /// A type no value has
+    fn never(&mut self) -> Py {
+        self.typing.insert("Never");
+        Py::other("Never")
+    }
+
+    /// The type with its checks attached, for positions without a `Field`
+    fn annotated(&mut self, py: Py) -> String {
+        if py.checks.is_empty() {
+            return py.code;
+        }
+        self.typing.insert("Annotated");
+        self.pydantic.insert("Field");
+        format!("Annotated[{}, Field({})]", py.code, py.checks.join(", "))
+    }
+}
+
+fn string_checks(constraints: &Constraints) -> Vec<String> {
+    let mut checks = vec![];
+    if let Some(min) = constraints.min_length {
+        checks.push(format!("min_length={}", min));
+    }
+    if let Some(max) = constraints.max_length {
+        checks.push(format!("max_length={}", max));
+    }
+    if let Some(pattern) = &constraints.pattern {
+        checks.push(format!("pattern={}", literal(pattern)));
+    }
+    checks
+}
+
+/// The integer kind's range where it says more than "a 64-bit integer":
+/// unsigned kinds are non-negative and narrower kinds have a maximum, but
+/// Python's unbounded `int` isn't held to 64-bit limits
+fn integer_bounds(kind: &IntegerKind) -> (Option<f64>, Option<f64>) {
+    let min = (kind.min() > i64::MIN as i128).then_some(kind.min() as f64);
+    let max = (kind.max() < i64::MAX as i128).then_some(kind.max() as f64);
+    (min, max)
+}
+
+/// `ge`/`le` for the tighter of the kind's bounds and the constraints
+fn number_checks(
+    constraints: &Constraints,
+    (kind_min, kind_max): (Option<f64>, Option<f64>),
+) -> Vec<String> {
+    let min = match (kind_min, constraints.minimum) {
+        (Some(a), Some(b)) => Some(a.max(b)),
+        (a, b) => a.or(b),
+    };
+    let max = match (kind_max, constraints.maximum) {
+        (Some(a), Some(b)) => Some(a.min(b)),
+        (a, b) => a.or(b),
+    };
+    let mut checks = vec![];
+    if let Some(min) = min {
+        checks.push(format!("ge={}", number(min)));
+    }
+    if let Some(max) = max {
+        checks.push(format!("le={}", number(max)));
+    }
+    checks
+}
+
+fn array_checks(constraints: &Constraints) -> Vec<String> {
+    let mut checks = vec![];
+    if let Some(min) = constraints.min_items {
+        checks.push(format!("min_length={}", min));
+    }
+    if let Some(max) = constraints.max_items {
+        checks.push(format!("max_length={}", max));
+    }
+    checks
+}
+
+/// Whole numbers print without a fraction
+fn number(n: f64) -> String {
+    if n.fract() == 0.0 {
+        format!("{}", n as i64)
+    } else {
+        n.to_string()
+    }
+}
+
+/// A Python string literal
+fn literal(value: &str) -> String {
+    serde_json::to_string(value).expect("strings serialize")
+}
+
+/// A JSON value as a Python literal
+fn python_value(value: &Value) -> String {
+    match value {
+        Value::Null => "None".to_string(),
+        Value::Bool(true) => "True".to_string(),
+        Value::Bool(false) => "False".to_string(),
+        Value::Number(n) => n.to_string(),
+        Value::String(s) => literal(s),
+        Value::Array(items) => format!(
+            "[{}]",
+            items
+                .iter()
+                .map(python_value)
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+        Value::Object(entries) => format!(
+            "{{{}}}",
+            entries
+                .iter()
+                .map(|(key, value)| format!("{}: {}", literal(key), python_value(value)))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+    }
+}
+
+/// A docstring at `indent`
+fn docstring(docs: &str, indent: &str) -> String {
+    let mut docs = docs.replace('\\', "\\\\").replace("\"\"\"", "\\\"\\\"\\\"");
+    if docs.ends_with('"') {
+        docs.pop();
+        docs.push_str("\\\"");
+    }
+    if !docs.contains('\n') {
+        return format!("{indent}\"\"\"{docs}\"\"\"");
+    }
+    let mut lines = vec![format!(
+        "{indent}\"\"\"{}",
+        docs.lines().next().unwrap_or("")
+    )];
+    for line in docs.lines().skip(1) {
+        match line {
+            "" => lines.push(String::new()),
+            line => lines.push(format!("{indent}{line}")),
+        }
+    }
+    lines.push(format!("{indent}\"\"\""));
+    lines.join("\n")
+}
+
+/// A class name for part of another's, `per_role` -> `PerRole`
+fn pascal(name: &str) -> String {
+    identifier(&Case::Pascal.apply(name))
+}
+
+/// A type name usable as a Python identifier
+fn identifier(name: &str) -> String {
+    name.chars()
+        .map(|c| if c.is_alphanumeric() { c } else { '_' })
+        .collect()
+}
+
+/// A field name usable as a Python attribute; pydantic keeps names with a
+/// leading underscore private, so those are prefixed too, and keywords and
+/// builtins get a trailing underscore
+fn field_name(name: &str) -> String {
+    let mut ident: String = name
+        .chars()
+        .map(|c| if c.is_alphanumeric() { c } else { '_' })
+        .collect();
+    if ident.is_empty() || ident.starts_with(|c: char| c.is_ascii_digit() || c == '_') {
+        ident = format!("field_{}", ident.trim_start_matches('_'));
+    }
+    if KEYWORDS.contains(&ident.as_str()) || BUILTINS.contains(&ident.as_str()) {
+        ident.push('_');
+    }
+    ident
+}
+
+/// What makes two occurrences of a named type the same declaration: its
+/// fingerprint without the annotations a field adds to it
+fn shape(schema: &SchemaType) -> String {
+    let mut bare = schema.clone();
+    bare.title = None;
+    bare.description = None;
+    bare.default = None;
+    for key in [
+        schema::DEPRECATED,
+        schema::READ_ONLY,
+        schema::WRITE_ONLY,
+        schema::SINCE,
+        schema::REF_PREFERENCE,
+    ] {
+        bare.extensions.remove(key);
+    }
+    bare.fingerprint()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_model_with_constraints() {
+        /// A new account
+        #[derive(Schema)]
+        #[allow(dead_code)]
+        #[schema(deny_unknown_fields)]
+        struct Signup {
+            /// Login name
+            #[schema(min_length = 3, max_length = 20, pattern = "^[a-z]+$")]
+            handle: String,
+            age: Option<u8>,
+            #[schema(minimum = 1)]
+            seats: u32,
+            #[schema(default)]
+            newsletter: bool,
+            #[schema(rename = "class")]
+            level: i64,
+        }
+
+        assert_eq!(
+            to_pydantic::<Signup>(),
+            r#"from typing import Optional
+
+from pydantic import BaseModel, ConfigDict, Field
+
+
+class Signup(BaseModel):
+    """A new account"""
+
+    model_config = ConfigDict(extra="forbid")
+
+    handle: str = Field(description="Login name", min_length=3, max_length=20, pattern="^[a-z]+$")
+    age: Optional[int] = Field(default=None, ge=0, le=255)
+    seats: int = Field(ge=1, le=4294967295)
+    newsletter: bool = False
+    class_: int = Field(alias="class")
+"#
+        );
+    }
+
+    #[test]
+    fn test_internally_tagged_union_uses_field_discriminator() {
+        #[derive(Schema)]
+        #[allow(dead_code)]
+        struct Label {
+            text: String,
+        }
+
+        #[derive(Schema)]
+        #[allow(dead_code)]
+        #[schema(tag = "op")]
+        enum Command {
+            Ping,
+            Resize { width: u32 },
+            Rename(Label),
+        }
+
+        assert_eq!(
+            to_pydantic::<Command>(),
+            r#"from typing import Annotated, Literal, Union
+
+from pydantic import BaseModel, Field
+
+
+class CommandPing(BaseModel):
+    op: Literal["Ping"] = "Ping"
+
+
+class CommandResize(BaseModel):
+    op: Literal["Resize"] = "Resize"
+    width: int = Field(ge=0, le=4294967295)
+
+
+class Label(BaseModel):
+    text: str
+
+
+class CommandRename(Label):
+    op: Literal["Rename"] = "Rename"
+
+
+Command = Annotated[Union[CommandPing, CommandResize, CommandRename], Field(discriminator="op")]
+"#
+        );
+    }
+
+    #[test]
+    fn test_adjacent_content_is_a_model_beside_the_literal_tag() {
+        #[derive(Schema)]
+        #[allow(dead_code)]
+        #[schema(tag = "t", content = "c")]
+        enum Reply {
+            Ok,
+            Error { code: i32 },
+        }
+
+        assert_eq!(
+            to_pydantic::<Reply>(),
+            r#"from typing import Annotated, Literal, Union
+
+from pydantic import BaseModel, Field
+
+
+class ReplyOk(BaseModel):
+    t: Literal["Ok"] = "Ok"
+
+
+class ReplyErrorC(BaseModel):
+    code: int = Field(ge=-2147483648, le=2147483647)
+
+
+class ReplyError(BaseModel):
+    t: Literal["Error"] = "Error"
+    c: ReplyErrorC
+
+
+Reply = Annotated[Union[ReplyOk, ReplyError], Field(discriminator="t")]
+"#
+        );
+    }
+
+    #[test]
+    fn test_forward_refs_are_quoted() {
+        #[derive(Schema)]
+        #[allow(dead_code)]
+        struct Team {
+            lead: Option<Box<Person>>,
+            name: String,
+        }
+
+        #[derive(Schema)]
+        #[allow(dead_code)]
+        struct Person {
+            team: Option<Team>,
+            name: String,
+        }
+
+        assert_eq!(
+            to_pydantic::<Person>(),
+            r#"from typing import Optional
+
+from pydantic import BaseModel
+
+
+class Team(BaseModel):
+    lead: Optional["Person"] = None
+    name: str
+
+
+class Person(BaseModel):
+    team: Optional[Team] = None
+    name: str
+
+
+Team.model_rebuild()
+Person.model_rebuild()
+"#
+        );
+    }
+
+    #[test]
+    fn test_recursive_models_are_rebuilt() {
+        #[derive(Schema)]
+        #[allow(dead_code)]
+        struct Node {
+            children: Vec<Node>,
+        }
+
+        assert_eq!(
+            to_pydantic::<Node>(),
+            r#"from pydantic import BaseModel
+
+
+class Node(BaseModel):
+    children: list["Node"]
+
+
+Node.model_rebuild()
+"#
+        );
+    }
+}
@@ -0,0 +1,843 @@
+//! Smithy IDL (version 2) models from schemas
+//!
+//! The root and every named type nested in it become a shape in one
+//! namespace, each after the ones it uses: objects are `structure`s, string
+//! enums `enum`s, variants and results `union`s, and arrays, sets and maps
+//! `list` and `map` shapes, which Smithy always names. Named newtypes around
+//! a scalar become a simple shape of their own (`string UserId`) carrying
+//! their constraints. Anonymous shapes are named after where they appear
+//! (`MemberTags`).
+//!
+//! Members are camelCase with `@jsonName` holding the wire name where it
+//! differs, and `@required` unless optional or defaulted. Doc comments,
+//! `@default`, `@deprecated`, `@length`, `@range`, `@pattern` and
+//! `@uniqueItems` carry over, and `date-time` strings are `Timestamp`s.
+//!
+//! Unions serialize the way the chosen Smithy protocol says (`{"move": {...}}`
+//! for the JSON protocols), not as the schema's serde tagging; tuples are
+//! structures with an `itemN` member per item, and maps with non-string keys
+//! lists of `key`/`value` entries.
+
+use schema::case::Case;
+use schema::{
+    GenerateOptions, IntegerKind, NumberKind, RefPreference, Schema, SchemaType, TypeKind,
+};
+use serde_json::Value;
+use std::collections::BTreeSet;
+use std::fmt;
+
+/// Prelude shapes, which a shape in the namespace mustn't shadow
+const PRELUDE: &[&str] = &[
+    "BigDecimal",
+    "BigInteger",
+    "Blob",
+    "Boolean",
+    "Byte",
+    "Document",
+    "Double",
+    "Float",
+    "Integer",
+    "Long",
+    "PrimitiveBoolean",
+    "PrimitiveByte",
+    "PrimitiveDouble",
+    "PrimitiveFloat",
+    "PrimitiveInteger",
+    "PrimitiveLong",
+    "PrimitiveShort",
+    "Short",
+    "String",
+    "Timestamp",
+    "Unit",
+];
+
+/// Options for the generated `.smithy` file
+#[derive(Debug, Clone)]
+pub struct SmithyOptions {
+    /// Namespace every shape is declared in, e.g. `example.accounts`
+    pub namespace: String,
+    pub generate: GenerateOptions,
+}
+
+impl SmithyOptions {
+    pub fn new(namespace: &str) -> Self {
+        SmithyOptions {
+            namespace: namespace.to_string(),
+            generate: GenerateOptions::default(),
+        }
+    }
+
+    /// Bound the schema before it is written
+    pub fn generate(mut self, generate: GenerateOptions) -> Self {
+        self.generate = generate;
+        self
+    }
+}
+
+/// Why a schema has no Smithy model
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SmithyError {
+    /// The namespace isn't dot-separated identifiers
+    InvalidNamespace(String),
+}
+
+impl fmt::Display for SmithyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SmithyError::InvalidNamespace(namespace) => {
+                write!(f, "{:?} is not a valid Smithy namespace", namespace)
+            }
+        }
+    }
+}
+
+impl std::error::Error for SmithyError {}
+
+/// The `.smithy` model for `T` and the named types it contains
+pub fn to_smithy<T: Schema>(options: &SmithyOptions) -> Result<String, SmithyError> {
+    schema_type_to_smithy(&T::schema(), T::type_name(), options)
+}
+
+/// The `.smithy` model for a SchemaType, its root shape named `type_name`
+/// (or the schema's own name, or `Root`)
+pub fn schema_type_to_smithy(
+    schema: &SchemaType,
+    type_name: Option<&str>,
+    options: &SmithyOptions,
+) -> Result<String, SmithyError> {
+    if !options.namespace.split('.').all(is_identifier) {
+        return Err(SmithyError::InvalidNamespace(options.namespace.clone()));
+    }
+
+    let schema = options.generate.apply(schema);
+    let mut module = Module {
+        taken: PRELUDE.iter().map(|name| name.to_string()).collect(),
+        ..Default::default()
+    };
+    let ident = module.unique(&identifier(
+        type_name.or(schema.name.as_deref()).unwrap_or("Root"),
+    ));
+    if let Some(name) = &schema.name {
+        module.named.push(Named {
+            name: name.clone(),
+            shape: shape(&schema),
+            ident: ident.clone(),
+            first: schema.clone(),
+        });
+    }
+    module.declare(&ident, &schema);
+
+    let mut output = format!("$version: \"2\"\n\nnamespace {}\n", options.namespace);
+    for declaration in &module.output {
+        output.push('\n');
+        output.push_str(declaration);
+    }
+    Ok(output)
+}
+
+/// A named type that has a declaration
+struct Named {
+    name: String,
+    /// Fingerprint without use-site annotations
+    shape: String,
+    ident: String,
+    /// The occurrence the declaration was written from
+    first: SchemaType,
+}
+
+/// One member of an aggregate shape
+struct Member {
+    /// Wire name
+    name: String,
+    target: String,
+    /// Without the `@`
+    traits: Vec<String>,
+    docs: Option<String>,
+}
+
+impl Member {
+    fn new(name: &str, target: String) -> Self {
+        Member {
+            name: name.to_string(),
+            target,
+            traits: vec![],
+            docs: None,
+        }
+    }
+
+    /// The member's lines at one level of indentation, named `ident`, with
+    /// `@jsonName` where that isn't the wire name
+    fn render(&self, ident: &str) -> String {
+        let mut lines = comment(self.docs.as_deref(), "    ");
+        if ident != self.name {
+            lines.push_str(&format!("    @jsonName({})\n", literal(&self.name)));
+        }
+        for tr in &self.traits {
+            lines.push_str(&format!("    @{tr}\n"));
+        }
+        lines.push_str(&format!("    {ident}: {}\n", self.target));
+        lines
+    }
+}
+
+#[derive(Default)]
+struct Module {
+    named: Vec<Named>,
+    /// Every shape name in use
+    taken: BTreeSet<String>,
+    /// Declarations, each after the ones it uses
+    output: Vec<String>,
+}
+
+impl Module {
+    /// `base`, or `base2`, `base3`, ... if something already has the name
+    fn unique(&mut self, base: &str) -> String {
+        let ident = (1..)
+            .map(|n| match n {
+                1 => base.to_string(),
+                n => format!("{base}{n}"),
+            })
+            .find(|ident| !self.taken.contains(ident))
+            .unwrap();
+        self.taken.insert(ident.clone());
+        ident
+    }
+
+    /// The shape `ident` for `schema`; scalars become a simple shape of
+    /// their own
+    fn declare(&mut self, ident: &str, schema: &SchemaType) {
+        if declares(schema) {
+            return self.define(ident, schema);
+        }
+        let keyword = match &schema.kind {
+            TypeKind::String if is_timestamp(schema) => "timestamp",
+            TypeKind::String => "string",
+            TypeKind::Integer(kind) => simple(integer(kind)),
+            TypeKind::Number(NumberKind::F32) => "float",
+            TypeKind::Number(NumberKind::F64) => "double",
+            TypeKind::Boolean => "boolean",
+            TypeKind::Null => return self.aggregate(ident, "structure", schema, vec![]),
+            TypeKind::Ref { .. } => "document",
+            _ => unreachable!("declares() covers the remaining kinds"),
+        };
+        let mut declaration = comment(schema.docs().as_deref(), "");
+        for tr in shape_traits(schema).into_iter().chain(constraints(schema)) {
+            declaration.push_str(&format!("@{tr}\n"));
+        }
+        declaration.push_str(&format!("{keyword} {ident}\n"));
+        self.output.push(declaration);
+    }
+
+    /// The shape for a kind that has one of its own
+    fn define(&mut self, ident: &str, schema: &SchemaType) {
+        match &schema.kind {
+            TypeKind::Object { required, .. } => {
+                let mut members = vec![];
+                for (name, property) in schema.ordered_properties() {
+                    let mut member = self.field(ident, name, property);
+                    if required.iter().any(|r| r == name) && property.default.is_none() {
+                        member.traits.insert(0, "required".to_string());
+                    }
+                    members.push(member);
+                }
+                self.aggregate(ident, "structure", schema, members);
+            }
+            TypeKind::Enum { variants } => {
+                let mut taken = BTreeSet::new();
+                let mut lines = vec![];
+                for variant in variants {
+                    let base = identifier(&Case::ScreamingSnake.apply(variant));
+                    let member = (1..)
+                        .map(|n| match n {
+                            1 => base.clone(),
+                            n => format!("{base}_{n}"),
+                        })
+                        .find(|member| taken.insert(member.clone()))
+                        .unwrap();
+                    match member == *variant {
+                        true => lines.push(format!("    {member}\n")),
+                        false => lines.push(format!("    {member} = {}\n", literal(variant))),
+                    }
+                }
+                let mut declaration = comment(schema.docs().as_deref(), "");
+                for tr in shape_traits(schema) {
+                    declaration.push_str(&format!("@{tr}\n"));
+                }
+                declaration.push_str(&format!("enum {ident} {{\n{}}}\n", lines.concat()));
+                self.output.push(declaration);
+            }
+            TypeKind::TaggedUnion {
+                tag_field,
+                tag_variants,
+                data_fields,
+            } => {
+                let tag = self.unique(&format!("{ident}{}", pascal(tag_field)));
+                let tag_schema = SchemaType {
+                    kind: TypeKind::Enum {
+                        variants: tag_variants.clone(),
+                    },
+                    ..Default::default()
+                };
+                self.define(&tag, &tag_schema);
+
+                let mut member = Member::new(tag_field, tag);
+                member.traits.push("required".to_string());
+                let mut members = vec![member];
+                let mut names: Vec<&String> = data_fields.keys().collect();
+                names.sort();
+                for name in names {
+                    members.push(self.field(ident, name, &data_fields[name]));
+                }
+                self.aggregate(ident, "structure", schema, members);
+            }
+            // Smithy unions need a member
+            TypeKind::Variant { cases } if cases.is_empty() => {
+                self.aggregate(ident, "structure", schema, vec![])
+            }
+            TypeKind::Variant { cases } => {
+                let mut members = vec![];
+                for case in cases {
+                    let target = match &case.data {
+                        Some(data) => {
+                            self.reference(data, &format!("{ident}{}", pascal(&case.name)))
+                        }
+                        None => "Unit".to_string(),
+                    };
+                    let mut member = Member::new(&case.name, target);
+                    if case.deprecated() {
+                        member.traits.push("deprecated".to_string());
+                    }
+                    member.docs = case.description.clone();
+                    members.push(member);
+                }
+                self.aggregate(ident, "union", schema, members);
+            }
+            TypeKind::Result { ok, err } => {
+                let ok = self.reference(ok, &format!("{ident}Ok"));
+                let err = self.reference(err, &format!("{ident}Error"));
+                let members = vec![Member::new("ok", ok), Member::new("error", err)];
+                self.aggregate(ident, "union", schema, members);
+            }
+            TypeKind::Tuple { fields } => {
+                let mut members = vec![];
+                for (i, item) in fields.iter().enumerate() {
+                    let mut member =
+                        self.element(&format!("item{i}"), item, &format!("{ident}Item{i}"));
+                    member.traits.insert(0, "required".to_string());
+                    members.push(member);
+                }
+                self.aggregate(ident, "structure", schema, members);
+            }
+            TypeKind::Array { items } | TypeKind::Set { items, .. } => {
+                let member = self.element("member", items, &format!("{ident}Item"));
+                self.aggregate(ident, "list", schema, vec![member]);
+            }
+            TypeKind::Map { key, value, .. } => match key.kind {
+                TypeKind::String | TypeKind::Enum { .. } => {
+                    let members = vec![
+                        self.element("key", key, &format!("{ident}Key")),
+                        self.element("value", value, &format!("{ident}Value")),
+                    ];
+                    self.aggregate(ident, "map", schema, members);
+                }
+                // Map keys must be strings, so the entries are listed
+                _ => {
+                    let entry = self.unique(&format!("{ident}Entry"));
+                    let mut members = vec![
+                        self.element("key", key, &format!("{entry}Key")),
+                        self.element("value", value, &format!("{entry}Value")),
+                    ];
+                    for member in &mut members {
+                        member.traits.insert(0, "required".to_string());
+                    }
+                    self.aggregate(&entry, "structure", &SchemaType::default(), members);
+                    let member = Member::new("member", entry);
+                    self.aggregate(ident, "list", schema, vec![member]);
+                }
+            },
+            _ => unreachable!("declares() covers the kinds defined here"),
+        }
+    }
+
+    /// A nested schema: its shape's name if it is a named type, otherwise
+    /// written in place, with anonymous shapes named after `context`
+    fn reference(&mut self, schema: &SchemaType, context: &str) -> String {
+        let Some(name) = &schema.name else {
+            return self.expression(schema, context);
+        };
+        if matches!(schema.kind, TypeKind::Ref { .. })
+            || schema.ref_preference() == Some(RefPreference::Inline)
+        {
+            return self.expression(schema, context);
+        }
+
+        let shape = shape(schema);
+        if let Some(named) = self
+            .named
+            .iter()
+            .find(|named| named.name == *name && named.shape == shape)
+        {
+            return named.ident.clone();
+        }
+
+        // A different type may already have taken the name
+        let ident = self.unique(&identifier(name));
+        self.named.push(Named {
+            name: name.clone(),
+            shape,
+            ident: ident.clone(),
+            first: schema.clone(),
+        });
+        self.declare(&ident, schema);
+        ident
+    }
+
+    fn expression(&mut self, schema: &SchemaType, context: &str) -> String {
+        if declares(schema) {
+            let ident = self.unique(context);
+            self.define(&ident, schema);
+            return ident;
+        }
+
+        match &schema.kind {
+            TypeKind::String if is_timestamp(schema) => "Timestamp".to_string(),
+            TypeKind::String => "String".to_string(),
+            TypeKind::Integer(kind) => integer(kind).to_string(),
+            TypeKind::Number(NumberKind::F32) => "Float".to_string(),
+            TypeKind::Number(NumberKind::F64) => "Double".to_string(),
+            TypeKind::Boolean => "Boolean".to_string(),
+            TypeKind::Null => "Unit".to_string(),
+            TypeKind::Ref { name } => self
+                .named
+                .iter()
+                .find(|named| named.name == *name)
+                .map(|named| named.ident.clone())
+                .unwrap_or_else(|| identifier(name)),
+            _ => unreachable!("declares() covers the remaining kinds"),
+        }
+    }
+
+    /// A member of the structure `owner` for the property `name`
+    fn field(&mut self, owner: &str, name: &str, schema: &SchemaType) -> Member {
+        let mut member = self.element(name, schema, &format!("{owner}{}", pascal(name)));
+        if let Some(default) = default_value(schema) {
+            member.traits.insert(0, format!("default({default})"));
+        }
+        if schema.deprecated() {
+            member.traits.push("deprecated".to_string());
+        }
+        member.docs = self.use_site_docs(schema);
+        member
+    }
+
+    /// A member targeting `schema`, carrying its constraints if the target
+    /// is a prelude shape that can't
+    fn element(&mut self, name: &str, schema: &SchemaType, context: &str) -> Member {
+        let target = self.reference(schema, context);
+        let mut member = Member::new(name, target);
+        if !declares(schema) && (schema.name.is_none() || is_inline(schema)) {
+            member.traits = constraints(schema);
+        }
+        member
+    }
+
+    /// Write a `structure`, `union`, `list` or `map` shape
+    fn aggregate(&mut self, ident: &str, keyword: &str, schema: &SchemaType, members: Vec<Member>) {
+        let mut declaration = comment(schema.docs().as_deref(), "");
+        for tr in shape_traits(schema).into_iter().chain(constraints(schema)) {
+            declaration.push_str(&format!("@{tr}\n"));
+        }
+        match members.is_empty() {
+            true => declaration.push_str(&format!("{keyword} {ident} {{}}\n")),
+            false => {
+                // Member names must differ ignoring case
+                let mut taken = BTreeSet::new();
+                let members: String = members
+                    .iter()
+                    .map(|member| {
+                        let base = member_name(&member.name);
+                        let ident = (1..)
+                            .map(|n| match n {
+                                1 => base.clone(),
+                                n => format!("{base}{n}"),
+                            })
+                            .find(|ident| taken.insert(ident.to_lowercase()))
+                            .unwrap();
+                        member.render(&ident)
+                    })
+                    .collect();
+                declaration.push_str(&format!("{keyword} {ident} {{\n{members}}}\n"));
+            }
+        }
+        self.output.push(declaration);
+    }
+
+    /// The docs for a member, unless its target's declaration already has
+    /// them
+    fn use_site_docs(&self, schema: &SchemaType) -> Option<String> {
+        // Anonymous shapes are declared for this member alone
+        if declares(schema) && schema.name.is_none() {
+            return None;
+        }
+        schema.docs().filter(|docs| {
+            let declared = schema.name.as_ref().and_then(|name| {
+                self.named
+                    .iter()
+                    .find(|named| named.name == *name && named.shape == shape(schema))
+            });
+            declared.is_none_or(|named| named.first.docs().as_ref() != Some(docs))
+        })
+    }
+}
+
+/// Whether `schema` is written as a shape other than a simple one
+fn declares(schema: &SchemaType) -> bool {
+    matches!(
+        schema.kind,
+        TypeKind::Object { .. }
+            | TypeKind::Enum { .. }
+            | TypeKind::TaggedUnion { .. }
+            | TypeKind::Variant { .. }
+            | TypeKind::Result { .. }
+            | TypeKind::Tuple { .. }
+            | TypeKind::Array { .. }
+            | TypeKind::Set { .. }
+            | TypeKind::Map { .. }
+    )
+}
+
+/// Whether a named `schema` is written in place rather than as its own shape
+fn is_inline(schema: &SchemaType) -> bool {
+    matches!(schema.kind, TypeKind::Ref { .. })
+        || schema.ref_preference() == Some(RefPreference::Inline)
+}
+
+fn is_timestamp(schema: &SchemaType) -> bool {
+    schema.constraints.format.as_deref() == Some("date-time")
+}
+
+/// Traits a shape declaration carries whatever its kind
+fn shape_traits(schema: &SchemaType) -> Vec<String> {
+    let mut traits = vec![];
+    if schema.deprecated() {
+        traits.push("deprecated".to_string());
+    }
+    let unique = matches!(schema.kind, TypeKind::Set { .. })
+        || matches!(schema.kind, TypeKind::Array { .. }) && schema.constraints.unique_items;
+    if unique {
+        traits.push("uniqueItems".to_string());
+    }
+    traits
+}
+
+/// `@length`, `@range`, `@pattern` and `@timestampFormat` for what
+/// `schema`'s constraints and kind allow
+fn constraints(schema: &SchemaType) -> Vec<String> {
+    let keywords = schema.constraints.json_keywords();
+    let bounds = |min: &str, max: &str, implied: (Option<Value>, Option<Value>)| {
+        let min = keywords.get(min).cloned().or(implied.0);
+        let max = keywords.get(max).cloned().or(implied.1);
+        let bounds: Vec<String> = [("min", min), ("max", max)]
+            .into_iter()
+            .filter_map(|(name, bound)| Some(format!("{name}: {}", bound?)))
+            .collect();
+        (!bounds.is_empty()).then(|| bounds.join(", "))
+    };
+
+    let mut traits = vec![];
+    match &schema.kind {
+        TypeKind::String => {
+            if let Some(length) = bounds("minLength", "maxLength", (None, None)) {
+                traits.push(format!("length({length})"));
+            }
+            if let Some(pattern) = &schema.constraints.pattern {
+                traits.push(format!("pattern({})", literal(pattern)));
+            }
+            if is_timestamp(schema) {
+                traits.push("timestampFormat(\"date-time\")".to_string());
+            }
+        }
+        TypeKind::Integer(kind) => {
+            if let Some(range) = bounds("minimum", "maximum", unsigned_range(kind)) {
+                traits.push(format!("range({range})"));
+            }
+        }
+        TypeKind::Number(_) => {
+            if let Some(range) = bounds("minimum", "maximum", (None, None)) {
+                traits.push(format!("range({range})"));
+            }
+        }
+        TypeKind::Array { .. } | TypeKind::Set { .. } | TypeKind::Map { .. } => {
+            if let Some(length) = bounds("minItems", "maxItems", (None, None)) {
+                traits.push(format!("length({length})"));
+            }
+        }
+        _ => {}
+    }
+    traits
+}
+
+/// The bounds an unsigned kind has beyond those of the signed shape it is
+/// written as
+fn unsigned_range(kind: &IntegerKind) -> (Option<Value>, Option<Value>) {
+    match kind {
+        IntegerKind::I32 | IntegerKind::I64 => (None, None),
+        IntegerKind::U8 => (Some(0.into()), Some(u8::MAX.into())),
+        IntegerKind::U32 => (Some(0.into()), Some(u32::MAX.into())),
+        // Values above i64::MAX don't fit in a Long
+        IntegerKind::U64 | IntegerKind::Usize => (Some(0.into()), None),
+    }
+}
+
+/// The `@default` value for a member, where Smithy allows one: scalars,
+/// enums, and empty lists and maps
+fn default_value(schema: &SchemaType) -> Option<String> {
+    let default = schema.default.as_ref()?;
+    let allowed = match (&schema.kind, default) {
+        (TypeKind::Boolean, Value::Bool(_)) => true,
+        (TypeKind::Integer(_), Value::Number(n)) => !n.is_f64(),
+        (TypeKind::Number(_), Value::Number(_)) => true,
+        (TypeKind::String | TypeKind::Enum { .. }, Value::String(_)) => true,
+        (TypeKind::Array { .. } | TypeKind::Set { .. }, Value::Array(items)) => items.is_empty(),
+        (TypeKind::Map { .. }, Value::Object(entries)) => entries.is_empty(),
+        _ => false,
+    };
+    allowed.then(|| default.to_string())
+}
+
+/// The prelude shape for an integer kind; unsigned ones take the next size
+/// up, bounded with `@range`
+fn integer(kind: &IntegerKind) -> &'static str {
+    match kind {
+        IntegerKind::I32 => "Integer",
+        IntegerKind::I64 => "Long",
+        IntegerKind::U8 => "Short",
+        IntegerKind::U32 | IntegerKind::U64 | IntegerKind::Usize => "Long",
+    }
+}
+
+/// The simple-shape keyword for a prelude shape, `Long` -> `long`
+fn simple(prelude: &str) -> &'static str {
+    match prelude {
+        "Short" => "short",
+        "Integer" => "integer",
+        _ => "long",
+    }
+}
+
+/// `///` lines for `docs` at `indent`, ending in a newline
+fn comment(docs: Option<&str>, indent: &str) -> String {
+    let Some(docs) = docs else {
+        return String::new();
+    };
+    docs.lines()
+        .map(|line| match line {
+            "" => format!("{indent}///\n"),
+            line => format!("{indent}/// {line}\n"),
+        })
+        .collect()
+}
+
+/// A Smithy string literal
+fn literal(s: &str) -> String {
+    Value::from(s).to_string()
+}
+
+/// A shape name for part of another's, `per_role` -> `PerRole`
+fn pascal(name: &str) -> String {
+    identifier(&Case::Pascal.apply(name))
+}
+
+/// A member name in camelCase
+fn member_name(name: &str) -> String {
+    identifier(&Case::Camel.apply(name))
+}
+
+/// A name usable as a Smithy identifier: letters, digits and `_`, starting
+/// with a letter after any underscores
+fn identifier(name: &str) -> String {
+    let ident: String = name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    match is_identifier(&ident) {
+        true => ident,
+        false => format!("N{ident}"),
+    }
+}
+
+fn is_identifier(s: &str) -> bool {
+    s.trim_start_matches('_')
+        .starts_with(|c: char| c.is_ascii_alphabetic())
+        && s.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// What makes two occurrences of a named type the same shape: its
+/// fingerprint without the annotations a field adds to it
+fn shape(schema: &SchemaType) -> String {
+    let mut bare = schema.clone();
+    bare.title = None;
+    bare.description = None;
+    bare.default = None;
+    for key in [
+        schema::DEPRECATED,
+        schema::READ_ONLY,
+        schema::WRITE_ONLY,
+        schema::SINCE,
+        schema::REF_PREFERENCE,
+    ] {
+        bare.extensions.remove(key);
+    }
+    bare.fingerprint()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_required_defaults_and_ranges() {
+        #[derive(Schema)]
+        #[allow(dead_code)]
+        struct Sku(#[schema(pattern = "^[A-Z]{3}-[0-9]+$")] String);
+
+        fn issued_at() -> SchemaType {
+            let mut schema = SchemaType::new(TypeKind::String);
+            schema.constraints.format = Some("date-time".to_string());
+            schema
+        }
+
+        /// Shadows the prelude shape
+        #[derive(Schema)]
+        #[allow(dead_code)]
+        struct Timestamp {
+            sku: Sku,
+            #[schema(with = "issued_at")]
+            issued_at: String,
+            #[schema(minimum = 1)]
+            quantity: u8,
+            total: u64,
+            #[schema(default)]
+            retries: u32,
+            #[schema(default)]
+            notes: Vec<String>,
+            discount: Option<f64>,
+            #[schema(min_length = 4, max_length = 16)]
+            coupon: String,
+            tags: std::collections::BTreeSet<String>,
+            prices: std::collections::HashMap<String, f64>,
+            #[schema(rename = "order_id")]
+            snake: String,
+            #[schema(rename = "orderId")]
+            camel: String,
+        }
+
+        assert_eq!(
+            to_smithy::<Timestamp>(&SmithyOptions::new("example.orders")).unwrap(),
+            r#"$version: "2"
+
+namespace example.orders
+
+@pattern("^[A-Z]{3}-[0-9]+$")
+string Sku
+
+list Timestamp2Notes {
+    member: String
+}
+
+/// Ordered set of unique values
+@uniqueItems
+list Timestamp2Tags {
+    member: String
+}
+
+/// Unordered map/dictionary of key-value pairs
+map Timestamp2Prices {
+    key: String
+    value: Double
+}
+
+/// Shadows the prelude shape
+structure Timestamp2 {
+    @required
+    sku: Sku
+    @jsonName("issued_at")
+    @required
+    @timestampFormat("date-time")
+    issuedAt: Timestamp
+    @required
+    @range(min: 1, max: 255)
+    quantity: Short
+    @required
+    @range(min: 0)
+    total: Long
+    @default(0)
+    @range(min: 0, max: 4294967295)
+    retries: Long
+    @default([])
+    notes: Timestamp2Notes
+    discount: Double
+    @required
+    @length(min: 4, max: 16)
+    coupon: String
+    @required
+    tags: Timestamp2Tags
+    @required
+    prices: Timestamp2Prices
+    @jsonName("order_id")
+    @required
+    orderId: String
+    @jsonName("orderId")
+    @required
+    orderId2: String
+}
+"#
+        );
+    }
+
+    #[test]
+    fn test_variants_become_unions() {
+        #[derive(Schema)]
+        #[allow(dead_code)]
+        enum Event {
+            Start,
+            /// Moved by an offset
+            Move {
+                dx: i32,
+                dy: i32,
+            },
+            Batch(Vec<u64>),
+        }
+
+        let smithy = to_smithy::<Event>(&SmithyOptions::new("example")).unwrap();
+        assert!(smithy.contains(
+            r#"structure EventMove {
+    @required
+    dx: Integer
+    @required
+    dy: Integer
+}
+
+list EventBatch {
+    @range(min: 0)
+    member: Long
+}
+
+union Event {
+    @jsonName("Start")
+    start: Unit
+    /// Moved by an offset
+    @jsonName("Move")
+    move: EventMove
+    @jsonName("Batch")
+    batch: EventBatch
+}
+"#
+        ));
+
+        assert_eq!(
+            to_smithy::<Event>(&SmithyOptions::new("example..events")),
+            Err(SmithyError::InvalidNamespace("example..events".to_string()))
+        );
+    }
+}
@@ -0,0 +1,345 @@
+//! Spark SQL schemas from schemas
+//!
+//! An object schema becomes the JSON form of a `StructType`, which Scala's
+//! `DataType.fromJson` and PySpark's `StructType.fromJson` read back, so a
+//! DataFrame reading events can use the same shape the Rust producer writes.
+//! Properties that can be absent are `nullable`; defaulted ones are always
+//! written, so they aren't. Doc comments become `comment` metadata.
+//!
+//! Type mapping:
+//! - nested objects are `struct`s, arrays and sets `array`s, and maps
+//!   `map`s with `string` keys (JSON object keys are text)
+//! - signed integers are `integer` or `long`; unsigned ones get the next
+//!   wider type, so `u64` is `decimal(20,0)`
+//! - string formats `date-time` and `date` become `timestamp` and `date`
+//! - fields marked with the [`DECIMAL`] extension become `decimal(p,s)`
+//! - enums are `string`s, and results `struct`s with nullable `ok` and
+//!   `error` fields
+//!
+//! Spark has no unions, tuples or recursion, so variants, tuples and
+//! recursive references are `string` columns: Spark's JSON reader keeps a
+//! nested value it can't map as its JSON text.
+
+use schema::{GenerateOptions, IntegerKind, NumberKind, Schema, SchemaType, TypeKind};
+use serde_json::{Map, Value, json};
+use std::fmt;
+
+/// Extension marking a number or string field as a fixed-point decimal:
+/// `#[schema(extension("x-decimal" = { "precision": 18, "scale": 2 }))]`
+pub const DECIMAL: &str = "x-decimal";
+
+/// The widest decimal Spark holds
+const MAX_PRECISION: u64 = 38;
+
+/// Why a schema has no Spark `StructType`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SparkError {
+    /// The schema is not an object, so it has no fields
+    NotAnObject,
+    /// A [`DECIMAL`] extension without a precision and scale Spark accepts
+    InvalidDecimal { column: String },
+}
+
+impl fmt::Display for SparkError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SparkError::NotAnObject => {
+                write!(f, "only object schemas map to Spark struct types")
+            }
+            SparkError::InvalidDecimal { column } => write!(
+                f,
+                "column {:?} needs a decimal precision between 1 and {} and a scale between 0 and the precision",
+                column, MAX_PRECISION
+            ),
+        }
+    }
+}
+
+impl std::error::Error for SparkError {}
+
+/// The Spark `StructType` JSON for `T`
+pub fn to_spark_schema<T: Schema>() -> Result<Value, SparkError> {
+    schema_type_to_spark(&T::schema())
+}
+
+/// The Spark `StructType` JSON for `T`, bounded by `options`
+pub fn to_spark_schema_with_options<T: Schema>(
+    options: &GenerateOptions,
+) -> Result<Value, SparkError> {
+    schema_type_to_spark_with_options(&T::schema(), options)
+}
+
+/// The Spark `StructType` JSON for a SchemaType, bounded by `options`
+pub fn schema_type_to_spark_with_options(
+    schema: &SchemaType,
+    options: &GenerateOptions,
+) -> Result<Value, SparkError> {
+    schema_type_to_spark(&options.apply(schema))
+}
+
+/// The Spark `StructType` JSON for an object SchemaType
+pub fn schema_type_to_spark(schema: &SchemaType) -> Result<Value, SparkError> {
+    if !matches!(schema.kind, TypeKind::Object { .. }) {
+        return Err(SparkError::NotAnObject);
+    }
+    struct_type(schema)
+}
+
+/// A `struct` with a field per property
+fn struct_type(object: &SchemaType) -> Result<Value, SparkError> {
+    let TypeKind::Object { required, .. } = &object.kind else {
+        return Ok(json!("string"));
+    };
+    let mut fields = vec![];
+    for (name, property) in object.ordered_properties() {
+        // Defaulted properties can be left out of input, but the producer
+        // always writes them
+        let nullable = !required.iter().any(|r| r == name) && property.default.is_none();
+        fields.push(field(name, property, nullable)?);
+    }
+    Ok(json!({ "type": "struct", "fields": fields }))
+}
+
+/// A `StructField`, with any doc comment as its `comment`
+fn field(name: &str, schema: &SchemaType, nullable: bool) -> Result<Value, SparkError> {
+    let mut metadata = Map::new();
+    if let Some(docs) = schema.docs() {
+        metadata.insert("comment".to_string(), Value::String(docs));
+    }
+    Ok(json!({
+        "name": name,
+        "type": data_type(name, schema)?,
+        "nullable": nullable || is_null(schema),
+        "metadata": metadata,
+    }))
+}
+
+/// The `DataType` of a value in `column`
+fn data_type(column: &str, schema: &SchemaType) -> Result<Value, SparkError> {
+    if let Some(decimal) = schema.extensions.get(DECIMAL) {
+        let precision = decimal.get("precision").and_then(|p| p.as_u64());
+        let scale = decimal.get("scale").and_then(|s| s.as_u64());
+        return match (precision, scale) {
+            (Some(precision @ 1..=MAX_PRECISION), Some(scale)) if scale <= precision => {
+                Ok(json!(format!("decimal({precision},{scale})")))
+            }
+            _ => Err(SparkError::InvalidDecimal {
+                column: column.to_string(),
+            }),
+        };
+    }
+
+    let ty = match &schema.kind {
+        TypeKind::String => match schema.constraints.format.as_deref() {
+            Some("date-time") => "timestamp",
+            Some("date") => "date",
+            _ => "string",
+        },
+        TypeKind::Integer(kind) => match kind {
+            IntegerKind::I32 => "integer",
+            IntegerKind::I64 => "long",
+            IntegerKind::U8 => "short",
+            IntegerKind::U32 => "long",
+            IntegerKind::U64 | IntegerKind::Usize => "decimal(20,0)",
+        },
+        TypeKind::Number(NumberKind::F32) => "float",
+        TypeKind::Number(NumberKind::F64) => "double",
+        TypeKind::Boolean => "boolean",
+        TypeKind::Null => "void",
+        TypeKind::Enum { .. } => "string",
+        TypeKind::Object { .. } => return struct_type(schema),
+        TypeKind::Array { items } | TypeKind::Set { items, .. } => {
+            return Ok(json!({
+                "type": "array",
+                "elementType": data_type(column, items)?,
+                "containsNull": is_null(items),
+            }));
+        }
+        TypeKind::Map { value, .. } => {
+            return Ok(json!({
+                "type": "map",
+                "keyType": "string",
+                "valueType": data_type(column, value)?,
+                "valueContainsNull": is_null(value),
+            }));
+        }
+        TypeKind::Result { ok, err } => {
+            return Ok(json!({
+                "type": "struct",
+                "fields": [field("ok", ok, true)?, field("error", err, true)?],
+            }));
+        }
+        TypeKind::TaggedUnion {
+            tag_field,
+            data_fields,
+            ..
+        } => {
+            let mut fields = vec![json!({
+                "name": tag_field,
+                "type": "string",
+                "nullable": false,
+                "metadata": {},
+            })];
+            let mut names: Vec<&String> = data_fields.keys().collect();
+            names.sort();
+            for name in names {
+                fields.push(field(name, &data_fields[name], true)?);
+            }
+            return Ok(json!({ "type": "struct", "fields": fields }));
+        }
+        // Kept as JSON text
+        TypeKind::Tuple { .. } | TypeKind::Variant { .. } | TypeKind::Ref { .. } => "string",
+    };
+    Ok(json!(ty))
+}
+
+/// Whether a value is always null
+fn is_null(schema: &SchemaType) -> bool {
+    matches!(schema.kind, TypeKind::Null)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[derive(Schema)]
+    #[allow(dead_code)]
+    enum Kind {
+        Click,
+        View,
+    }
+
+    #[derive(Schema)]
+    #[allow(dead_code)]
+    struct Device {
+        os: String,
+        version: Option<u32>,
+    }
+
+    #[derive(Schema)]
+    #[allow(dead_code)]
+    enum Source {
+        Organic,
+        Campaign { id: String },
+    }
+
+    /// A user interaction
+    #[derive(Schema)]
+    #[allow(dead_code)]
+    struct Event {
+        id: u64,
+        kind: Kind,
+        at: String,
+        /// Free-form note from the client
+        note: Option<String>,
+        #[schema(default)]
+        retries: i32,
+        #[schema(extension("x-decimal" = { "precision": 12, "scale": 2 }))]
+        amount: f64,
+        device: Device,
+        tags: Vec<String>,
+        counts: HashMap<u8, i64>,
+        source: Source,
+        outcome: Result<bool, String>,
+    }
+
+    #[test]
+    fn test_struct_type() {
+        let mut schema = Event::schema();
+        if let TypeKind::Object { properties, .. } = &mut schema.kind {
+            properties.get_mut("at").unwrap().constraints.format = Some("date-time".to_string());
+        }
+        let field = |name: &str, ty: Value, nullable: bool| json!({ "name": name, "type": ty, "nullable": nullable, "metadata": {} });
+        assert_eq!(
+            schema_type_to_spark(&schema).unwrap(),
+            json!({
+                "type": "struct",
+                "fields": [
+                    field("id", json!("decimal(20,0)"), false),
+                    field("kind", json!("string"), false),
+                    field("at", json!("timestamp"), false),
+                    {
+                        "name": "note",
+                        "type": "string",
+                        "nullable": true,
+                        "metadata": { "comment": "Free-form note from the client" },
+                    },
+                    field("retries", json!("integer"), false),
+                    field("amount", json!("decimal(12,2)"), false),
+                    field("device", json!({
+                        "type": "struct",
+                        "fields": [
+                            field("os", json!("string"), false),
+                            field("version", json!("long"), true),
+                        ],
+                    }), false),
+                    field("tags", json!({
+                        "type": "array",
+                        "elementType": "string",
+                        "containsNull": false,
+                    }), false),
+                    {
+                        "name": "counts",
+                        "type": {
+                            "type": "map",
+                            "keyType": "string",
+                            "valueType": "long",
+                            "valueContainsNull": false,
+                        },
+                        "nullable": false,
+                        "metadata": { "comment": "Unordered map/dictionary of key-value pairs" },
+                    },
+                    field("source", json!("string"), false),
+                    field("outcome", json!({
+                        "type": "struct",
+                        "fields": [
+                            field("ok", json!("boolean"), true),
+                            field("error", json!("string"), true),
+                        ],
+                    }), false),
+                ],
+            })
+        );
+    }
+
+    #[test]
+    fn test_nullable_values() {
+        let mut schema = SchemaType::new(TypeKind::Object {
+            properties: [(
+                "gaps".to_string(),
+                SchemaType::new(TypeKind::Array {
+                    items: Box::new(SchemaType::new(TypeKind::Null)),
+                }),
+            )]
+            .into(),
+            required: vec!["gaps".to_string()],
+        });
+        schema.property_order = vec!["gaps".to_string()];
+        assert_eq!(
+            schema_type_to_spark(&schema).unwrap()["fields"][0]["type"]["containsNull"],
+            json!(true)
+        );
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(
+            to_spark_schema::<Vec<String>>(),
+            Err(SparkError::NotAnObject)
+        );
+
+        #[derive(Schema)]
+        #[allow(dead_code)]
+        struct Price {
+            #[schema(extension("x-decimal" = { "precision": 40, "scale": 2 }))]
+            amount: f64,
+        }
+        assert_eq!(
+            to_spark_schema::<Price>(),
+            Err(SparkError::InvalidDecimal {
+                column: "amount".to_string()
+            })
+        );
+    }
+}
@@ -0,0 +1,564 @@
+//! SQL `CREATE TABLE` statements from schemas
+//!
+//! An object schema becomes one table with a column per property; required
+//! properties are `NOT NULL`. Nested objects are either flattened into
+//! `parent_child` columns or stored whole in a JSON column, and lists,
+//! maps, variants and other composite values are always JSON. Enums become
+//! a `CHECK (... IN (...))` constraint or the dialect's native enum type,
+//! and doc comments become table and column comments.
+
+use schema::case::Case;
+use schema::{GenerateOptions, IntegerKind, NumberKind, Schema, SchemaType, TypeKind};
+use serde_json::Value;
+use std::fmt;
+
+/// The database the statements are written for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Dialect {
+    #[default]
+    Postgres,
+    MySql,
+    Sqlite,
+}
+
+/// Where a nested object's properties go
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum NestedObjects {
+    /// One column per leaf property, named `parent_child`
+    #[default]
+    Flatten,
+    /// One JSON column holding the whole object
+    Json,
+}
+
+/// How enum columns restrict their values
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Enums {
+    /// A text column with `CHECK (column IN (...))`
+    #[default]
+    Check,
+    /// Postgres `CREATE TYPE ... AS ENUM` or MySQL `ENUM(...)`; SQLite has
+    /// no enum type and keeps the `CHECK`
+    Native,
+}
+
+/// Options for the generated DDL
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SqlOptions {
+    pub dialect: Dialect,
+    pub nested: NestedObjects,
+    pub enums: Enums,
+    pub generate: GenerateOptions,
+}
+
+impl SqlOptions {
+    pub fn new(dialect: Dialect) -> Self {
+        SqlOptions {
+            dialect,
+            ..Self::default()
+        }
+    }
+
+    pub fn nested(mut self, nested: NestedObjects) -> Self {
+        self.nested = nested;
+        self
+    }
+
+    pub fn enums(mut self, enums: Enums) -> Self {
+        self.enums = enums;
+        self
+    }
+
+    /// Bound the schema before it is written
+    pub fn generate(mut self, generate: GenerateOptions) -> Self {
+        self.generate = generate;
+        self
+    }
+}
+
+/// Why a schema has no table
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SqlError {
+    /// The schema is not an object, so it has no columns
+    NotAnObject,
+}
+
+impl fmt::Display for SqlError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SqlError::NotAnObject => write!(f, "only object schemas map to SQL tables"),
+        }
+    }
+}
+
+impl std::error::Error for SqlError {}
+
+/// The DDL for a table of `T`, named after `T` in snake_case
+pub fn to_create_table<T: Schema>(options: &SqlOptions) -> Result<String, SqlError> {
+    let table = Case::Snake.apply(T::type_name().unwrap_or("root"));
+    schema_type_to_create_table(&T::schema(), &table, options)
+}
+
+/// The DDL for a table named `table` holding an object SchemaType
+pub fn schema_type_to_create_table(
+    schema: &SchemaType,
+    table: &str,
+    options: &SqlOptions,
+) -> Result<String, SqlError> {
+    let schema = options.generate.apply(schema);
+    if !matches!(schema.kind, TypeKind::Object { .. }) {
+        return Err(SqlError::NotAnObject);
+    }
+
+    let mut ddl = Ddl {
+        options,
+        table: table.to_string(),
+        columns: vec![],
+        types: vec![],
+    };
+    ddl.object(&schema, "", true);
+    Ok(ddl.render(schema.docs()))
+}
+
+/// One column of the table
+struct Column {
+    name: String,
+    ty: String,
+    not_null: bool,
+    default: Option<String>,
+    check: Option<String>,
+    docs: Option<String>,
+}
+
+struct Ddl<'a> {
+    options: &'a SqlOptions,
+    table: String,
+    columns: Vec<Column>,
+    /// `CREATE TYPE` statements for Postgres enums, by type name
+    types: Vec<(String, String)>,
+}
+
+impl Ddl<'_> {
+    /// Columns for an object's properties, prefixed with `prefix`; `present`
+    /// is whether the object itself is always there
+    fn object(&mut self, object: &SchemaType, prefix: &str, present: bool) {
+        let TypeKind::Object { required, .. } = &object.kind else {
+            return;
+        };
+        for (name, property) in object.ordered_properties() {
+            let column = format!("{prefix}{name}");
+            let not_null = present && required.iter().any(|r| r == name);
+            match &property.kind {
+                TypeKind::Object { .. } if self.options.nested == NestedObjects::Flatten => {
+                    self.object(property, &format!("{column}_"), not_null)
+                }
+                _ => self.column(column, not_null, property),
+            }
+        }
+    }
+
+    fn column(&mut self, name: String, not_null: bool, schema: &SchemaType) {
+        let dialect = self.options.dialect;
+        let mut check = None;
+        let ty = match &schema.kind {
+            TypeKind::String => match (dialect, schema.constraints.max_length) {
+                (Dialect::Postgres | Dialect::MySql, Some(max)) => format!("VARCHAR({max})"),
+                _ => "TEXT".to_string(),
+            },
+            TypeKind::Integer(kind) => integer(dialect, kind).to_string(),
+            TypeKind::Number(kind) => match (dialect, kind) {
+                (Dialect::Postgres, NumberKind::F32) => "REAL",
+                (Dialect::Postgres, NumberKind::F64) => "DOUBLE PRECISION",
+                (Dialect::MySql, NumberKind::F32) => "FLOAT",
+                (Dialect::MySql, NumberKind::F64) => "DOUBLE",
+                (Dialect::Sqlite, _) => "REAL",
+            }
+            .to_string(),
+            TypeKind::Boolean => match dialect {
+                Dialect::Sqlite => "INTEGER",
+                _ => "BOOLEAN",
+            }
+            .to_string(),
+            TypeKind::Enum { variants } => {
+                let values: Vec<String> = variants.iter().map(|v| self.string(v)).collect();
+                let values = values.join(", ");
+                match (self.options.enums, dialect) {
+                    (Enums::Native, Dialect::Postgres) => {
+                        let ty = match &schema.name {
+                            Some(name) => Case::Snake.apply(name),
+                            None => format!("{}_{}", self.table, name),
+                        };
+                        if !self.types.iter().any(|(existing, _)| *existing == ty) {
+                            self.types.push((ty.clone(), values));
+                        }
+                        self.identifier(&ty)
+                    }
+                    (Enums::Native, Dialect::MySql) => format!("ENUM({values})"),
+                    _ => {
+                        check = Some(format!("{} IN ({values})", self.identifier(&name)));
+                        match dialect {
+                            // MySQL can't constrain or index unbounded TEXT well
+                            Dialect::MySql => {
+                                let longest = variants.iter().map(|v| v.len()).max();
+                                format!("VARCHAR({})", longest.unwrap_or(1).max(1))
+                            }
+                            _ => "TEXT".to_string(),
+                        }
+                    }
+                }
+            }
+            // Everything else is stored as its JSON encoding
+            _ => match dialect {
+                Dialect::Postgres => "JSONB",
+                Dialect::MySql => "JSON",
+                Dialect::Sqlite => "TEXT",
+            }
+            .to_string(),
+        };
+
+        let default = schema.default.as_ref().and_then(|default| match default {
+            Value::String(s) => Some(self.string(s)),
+            Value::Number(n) => Some(n.to_string()),
+            Value::Bool(b) => Some(match (dialect, b) {
+                (Dialect::Sqlite, true) => "1".to_string(),
+                (Dialect::Sqlite, false) => "0".to_string(),
+                (_, true) => "TRUE".to_string(),
+                (_, false) => "FALSE".to_string(),
+            }),
+            // MySQL only takes JSON defaults as expressions
+            Value::Array(_) | Value::Object(_) if dialect != Dialect::MySql => {
+                Some(self.string(&default.to_string()))
+            }
+            _ => None,
+        });
+
+        self.columns.push(Column {
+            name,
+            ty,
+            not_null,
+            default,
+            check,
+            docs: schema.docs(),
+        });
+    }
+
+    fn render(&self, docs: Option<String>) -> String {
+        let dialect = self.options.dialect;
+        let table = self.identifier(&self.table);
+        let mut statements = vec![];
+
+        for (ty, values) in &self.types {
+            statements.push(format!(
+                "CREATE TYPE {} AS ENUM ({});",
+                self.identifier(ty),
+                values
+            ));
+        }
+
+        let mut lines = vec![];
+        for (i, column) in self.columns.iter().enumerate() {
+            let mut line = String::new();
+            if let (Dialect::Sqlite, Some(docs)) = (dialect, &column.docs) {
+                line.push_str(&sql_comment(docs, "    "));
+            }
+            line.push_str(&format!(
+                "    {} {}",
+                self.identifier(&column.name),
+                column.ty
+            ));
+            if column.not_null {
+                line.push_str(" NOT NULL");
+            }
+            if let Some(default) = &column.default {
+                line.push_str(&format!(" DEFAULT {default}"));
+            }
+            if let Some(check) = &column.check {
+                line.push_str(&format!(" CHECK ({check})"));
+            }
+            if let (Dialect::MySql, Some(docs)) = (dialect, &column.docs) {
+                line.push_str(&format!(" COMMENT {}", self.string(docs)));
+            }
+            if i + 1 < self.columns.len() {
+                line.push(',');
+            }
+            lines.push(line);
+        }
+
+        let mut create = String::new();
+        if let (Dialect::Sqlite, Some(docs)) = (dialect, &docs) {
+            create.push_str(&sql_comment(docs, ""));
+        }
+        create.push_str(&format!(
+            "CREATE TABLE {} (\n{}\n)",
+            table,
+            lines.join("\n")
+        ));
+        if let (Dialect::MySql, Some(docs)) = (dialect, &docs) {
+            create.push_str(&format!(" COMMENT={}", self.string(docs)));
+        }
+        create.push(';');
+        statements.push(create);
+
+        if dialect == Dialect::Postgres {
+            let mut comments = vec![];
+            if let Some(docs) = &docs {
+                comments.push(format!(
+                    "COMMENT ON TABLE {} IS {};",
+                    table,
+                    self.string(docs)
+                ));
+            }
+            for column in &self.columns {
+                if let Some(docs) = &column.docs {
+                    comments.push(format!(
+                        "COMMENT ON COLUMN {}.{} IS {};",
+                        table,
+                        self.identifier(&column.name),
+                        self.string(docs)
+                    ));
+                }
+            }
+            if !comments.is_empty() {
+                statements.push(comments.join("\n"));
+            }
+        }
+
+        let mut output = statements.join("\n\n");
+        output.push('\n');
+        output
+    }
+
+    /// A string literal
+    fn string(&self, value: &str) -> String {
+        let mut escaped = value.replace('\'', "''");
+        if self.options.dialect == Dialect::MySql {
+            escaped = escaped.replace('\\', "\\\\");
+        }
+        format!("'{escaped}'")
+    }
+
+    /// A table, column or type name, quoted unless it's a plain lowercase
+    /// word that isn't reserved
+    fn identifier(&self, name: &str) -> String {
+        let plain = name.starts_with(|c: char| c.is_ascii_lowercase() || c == '_')
+            && name
+                .chars()
+                .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_')
+            && !RESERVED.contains(&name);
+        match (plain, self.options.dialect) {
+            (true, _) => name.to_string(),
+            (false, Dialect::MySql) => format!("`{}`", name.replace('`', "``")),
+            (false, _) => format!("\"{}\"", name.replace('"', "\"\"")),
+        }
+    }
+}
+
+/// Words reserved in at least one dialect that make likely column names
+const RESERVED: &[&str] = &[
+    "all",
+    "and",
+    "as",
+    "asc",
+    "between",
+    "by",
+    "case",
+    "check",
+    "column",
+    "constraint",
+    "create",
+    "current_date",
+    "current_time",
+    "current_user",
+    "default",
+    "delete",
+    "desc",
+    "distinct",
+    "drop",
+    "else",
+    "end",
+    "exists",
+    "from",
+    "group",
+    "having",
+    "in",
+    "index",
+    "insert",
+    "into",
+    "is",
+    "join",
+    "key",
+    "like",
+    "limit",
+    "not",
+    "null",
+    "offset",
+    "on",
+    "or",
+    "order",
+    "primary",
+    "references",
+    "select",
+    "set",
+    "table",
+    "then",
+    "to",
+    "union",
+    "unique",
+    "update",
+    "user",
+    "using",
+    "values",
+    "when",
+    "where",
+    "with",
+];
+
+fn integer(dialect: Dialect, kind: &IntegerKind) -> &'static str {
+    match (dialect, kind) {
+        // SQLite integers are all 64-bit signed; u64 values above i64::MAX don't fit
+        (Dialect::Sqlite, _) => "INTEGER",
+        (Dialect::Postgres, IntegerKind::I32) => "INTEGER",
+        (Dialect::Postgres, IntegerKind::U8) => "SMALLINT",
+        (Dialect::Postgres, IntegerKind::I64 | IntegerKind::U32) => "BIGINT",
+        // Postgres has no unsigned types; u64 needs 20 digits
+        (Dialect::Postgres, IntegerKind::U64 | IntegerKind::Usize) => "NUMERIC(20, 0)",
+        (Dialect::MySql, IntegerKind::I32) => "INT",
+        (Dialect::MySql, IntegerKind::I64) => "BIGINT",
+        (Dialect::MySql, IntegerKind::U8) => "TINYINT UNSIGNED",
+        (Dialect::MySql, IntegerKind::U32) => "INT UNSIGNED",
+        (Dialect::MySql, IntegerKind::U64 | IntegerKind::Usize) => "BIGINT UNSIGNED",
+    }
+}
+
+/// `-- ` lines for `docs` at `indent`, ending in a newline
+fn sql_comment(docs: &str, indent: &str) -> String {
+    docs.lines()
+        .map(|line| match line {
+            "" => format!("{indent}--\n"),
+            line => format!("{indent}-- {line}\n"),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Who can't be deleted
+    #[derive(Schema)]
+    #[allow(dead_code)]
+    struct User {
+        #[schema(rename = "Email")]
+        email: String,
+        #[schema(rename = "group")]
+        team: String,
+        #[schema(rename = "say\"hi`")]
+        greeting: Option<String>,
+        /// A path like C:\users
+        home: String,
+    }
+
+    #[test]
+    fn test_identifier_quoting() {
+        assert_eq!(
+            to_create_table::<User>(&SqlOptions::new(Dialect::Postgres)).unwrap(),
+            r#"CREATE TABLE "user" (
+    "Email" TEXT NOT NULL,
+    "group" TEXT NOT NULL,
+    "say""hi`" TEXT,
+    home TEXT NOT NULL
+);
+
+COMMENT ON TABLE "user" IS 'Who can''t be deleted';
+COMMENT ON COLUMN "user".home IS 'A path like C:\users';
+"#
+        );
+        assert_eq!(
+            to_create_table::<User>(&SqlOptions::new(Dialect::MySql)).unwrap(),
+            r#"CREATE TABLE `user` (
+    `Email` TEXT NOT NULL,
+    `group` TEXT NOT NULL,
+    `say"hi``` TEXT,
+    home TEXT NOT NULL COMMENT 'A path like C:\\users'
+) COMMENT='Who can''t be deleted';
+"#
+        );
+    }
+
+    #[derive(Schema)]
+    #[allow(dead_code)]
+    enum Tier {
+        Free,
+        Enterprise,
+    }
+
+    #[derive(Schema)]
+    #[allow(dead_code)]
+    struct Billing {
+        tier: Tier,
+        card_last4: Option<String>,
+    }
+
+    #[derive(Schema)]
+    #[allow(dead_code)]
+    struct Account {
+        id: u64,
+        billing: Option<Billing>,
+        #[schema(default)]
+        tags: Vec<String>,
+    }
+
+    #[test]
+    fn test_optional_nested_objects() {
+        // Billing may be missing, so none of its columns can be NOT NULL
+        assert_eq!(
+            to_create_table::<Account>(&SqlOptions::new(Dialect::Postgres)).unwrap(),
+            r#"CREATE TABLE account (
+    id NUMERIC(20, 0) NOT NULL,
+    billing_tier TEXT CHECK (billing_tier IN ('Free', 'Enterprise')),
+    billing_card_last4 TEXT,
+    tags JSONB DEFAULT '[]'
+);
+"#
+        );
+        // MySQL takes JSON defaults only as expressions
+        assert_eq!(
+            to_create_table::<Account>(
+                &SqlOptions::new(Dialect::MySql).nested(NestedObjects::Json)
+            )
+            .unwrap(),
+            r#"CREATE TABLE account (
+    id BIGINT UNSIGNED NOT NULL,
+    billing JSON,
+    tags JSON
+);
+"#
+        );
+    }
+
+    #[test]
+    fn test_native_enums() {
+        let postgres = SqlOptions::new(Dialect::Postgres).enums(Enums::Native);
+        assert!(
+            to_create_table::<Account>(&postgres)
+                .unwrap()
+                .starts_with("CREATE TYPE tier AS ENUM ('Free', 'Enterprise');\n\n")
+        );
+        let mysql = SqlOptions::new(Dialect::MySql);
+        assert!(to_create_table::<Account>(&mysql).unwrap().contains(
+            "    billing_tier VARCHAR(10) CHECK (billing_tier IN ('Free', 'Enterprise')),\n"
+        ));
+        // SQLite has no enum type
+        let sqlite = SqlOptions::new(Dialect::Sqlite).enums(Enums::Native);
+        assert!(
+            to_create_table::<Account>(&sqlite).unwrap().contains(
+                "    billing_tier TEXT CHECK (billing_tier IN ('Free', 'Enterprise')),\n"
+            )
+        );
+
+        assert_eq!(
+            to_create_table::<Vec<String>>(&sqlite),
+            Err(SqlError::NotAnObject)
+        );
+    }
+}
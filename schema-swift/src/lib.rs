@@ -0,0 +1,1092 @@
+//! Swift `Codable` types from schemas
+//!
+//! The root and every named type nested in it become a Swift declaration,
+//! written after the ones it uses: objects are structs, string enums are
+//! `String`-backed enums, variants are enums with an associated value per
+//! data case, and other named types are a `typealias`. Properties are
+//! camelCase with `CodingKeys` mapping back to the wire names where they
+//! differ, and properties outside the required list are optionals.
+//!
+//! Variants get hand-written `init(from:)` and `encode(to:)` that follow the
+//! schema's serde tagging, since Swift's synthesized enum coding uses a
+//! format of its own. Tuples, and maps whose keys aren't strings, are
+//! encoded as JSON arrays by structs with unkeyed coding. Anonymous types
+//! that need a declaration are named after where they appear (`EventMove`).
+//!
+//! A struct holding itself directly, through an optional or `Box`, is a
+//! `final class` instead, and such a variant an `indirect enum`, since Swift
+//! value types can't contain themselves.
+
+use schema::case::Case;
+use schema::{
+    GenerateOptions, IntegerKind, NumberKind, RefPreference, Schema, SchemaType, Tagging, TypeKind,
+    VariantCase,
+};
+use std::collections::BTreeSet;
+
+/// Swift keywords, which need backticks to name a property or case
+const KEYWORDS: &[&str] = &[
+    "Any",
+    "Self",
+    "as",
+    "associatedtype",
+    "break",
+    "case",
+    "catch",
+    "class",
+    "continue",
+    "default",
+    "defer",
+    "deinit",
+    "do",
+    "else",
+    "enum",
+    "extension",
+    "fallthrough",
+    "false",
+    "fileprivate",
+    "for",
+    "func",
+    "guard",
+    "if",
+    "import",
+    "in",
+    "init",
+    "inout",
+    "internal",
+    "is",
+    "let",
+    "nil",
+    "open",
+    "operator",
+    "private",
+    "protocol",
+    "public",
+    "repeat",
+    "rethrows",
+    "return",
+    "self",
+    "static",
+    "struct",
+    "subscript",
+    "super",
+    "switch",
+    "throw",
+    "throws",
+    "true",
+    "try",
+    "typealias",
+    "var",
+    "where",
+    "while",
+];
+
+/// Types the generated code uses, which a declaration mustn't shadow
+const BUILTINS: &[&str] = &[
+    "Bool",
+    "Codable",
+    "CodingKey",
+    "Decoder",
+    "DecodingError",
+    "Double",
+    "Encoder",
+    "Float",
+    "Int",
+    "Int32",
+    "Int64",
+    "Set",
+    "String",
+    "UInt",
+    "UInt32",
+    "UInt64",
+    "UInt8",
+];
+
+/// The Swift file for `T` and the named types it contains
+pub fn to_swift<T: Schema>() -> String {
+    schema_type_to_swift(&T::schema(), T::type_name())
+}
+
+/// The Swift file for `T` and the named types it contains, bounded by `options`
+pub fn to_swift_with_options<T: Schema>(options: &GenerateOptions) -> String {
+    schema_type_to_swift_with_options(&T::schema(), T::type_name(), options)
+}
+
+/// The Swift file for a SchemaType, bounded by `options`
+pub fn schema_type_to_swift_with_options(
+    schema: &SchemaType,
+    type_name: Option<&str>,
+    options: &GenerateOptions,
+) -> String {
+    schema_type_to_swift(&options.apply(schema), type_name)
+}
+
+/// The Swift file for a SchemaType, named `type_name` (or the schema's own
+/// name, or `Root`)
+pub fn schema_type_to_swift(schema: &SchemaType, type_name: Option<&str>) -> String {
+    let mut module = Module {
+        taken: KEYWORDS
+            .iter()
+            .chain(BUILTINS)
+            .map(|s| s.to_string())
+            .collect(),
+        ..Default::default()
+    };
+    let ident = module.unique(&identifier(
+        type_name.or(schema.name.as_deref()).unwrap_or("Root"),
+    ));
+    if let Some(name) = &schema.name {
+        module.named.push(Named {
+            name: name.clone(),
+            shape: shape(schema),
+            ident: ident.clone(),
+            first: schema.clone(),
+        });
+    }
+    module.declare(&ident, schema);
+
+    let mut output = "import Foundation\n".to_string();
+    for declaration in &module.output {
+        output.push('\n');
+        output.push_str(declaration);
+    }
+    output
+}
+
+/// A named type that has a declaration
+struct Named {
+    name: String,
+    /// Fingerprint without use-site annotations
+    shape: String,
+    ident: String,
+    /// The occurrence the declaration was written from
+    first: SchemaType,
+}
+
+/// One stored property of a struct
+struct Property {
+    /// Wire name
+    name: String,
+    ty: String,
+    optional: bool,
+    docs: Option<String>,
+}
+
+/// One case of a Variant's enum
+struct EnumCase {
+    /// Wire name
+    name: String,
+    /// Swift name, without backticks
+    member: String,
+    /// Associated value type, for data cases
+    payload: Option<String>,
+    docs: Option<String>,
+}
+
+#[derive(Default)]
+struct Module {
+    named: Vec<Named>,
+    /// Every type name in use
+    taken: BTreeSet<String>,
+    /// Declarations, each after the ones it uses
+    output: Vec<String>,
+    /// The `JSONNull` helper, once something needs it
+    null: Option<String>,
+}
+
+impl Module {
+    /// `base`, or `base2`, `base3`, ... if something already has the name
+    fn unique(&mut self, base: &str) -> String {
+        let ident = (1..)
+            .map(|n| match n {
+                1 => base.to_string(),
+                n => format!("{base}{n}"),
+            })
+            .find(|ident| !self.taken.contains(ident))
+            .unwrap();
+        self.taken.insert(ident.clone());
+        ident
+    }
+
+    /// The declaration `ident` for `schema`; types with none of their own
+    /// are a `typealias`
+    fn declare(&mut self, ident: &str, schema: &SchemaType) {
+        if declares(schema) {
+            return self.define(ident, schema);
+        }
+        let ty = self.expression(schema, ident);
+        let mut declaration = comment(schema.docs().as_deref(), "");
+        declaration.push_str(&format!("typealias {ident} = {ty}\n"));
+        self.output.push(declaration);
+    }
+
+    /// The declaration for a kind that has one of its own
+    fn define(&mut self, ident: &str, schema: &SchemaType) {
+        match &schema.kind {
+            TypeKind::Object { required, .. } => {
+                let mut properties = vec![];
+                for (name, property) in schema.ordered_properties() {
+                    let mut property = self.property(ident, name, property);
+                    property.optional = !required.iter().any(|r| r == name);
+                    properties.push(property);
+                }
+                let recursive = schema
+                    .ordered_properties()
+                    .iter()
+                    .any(|(_, property)| holds_ref(property));
+                self.structure(ident, schema.docs(), properties, recursive);
+            }
+            TypeKind::Enum { variants } => {
+                let mut declaration = comment(schema.docs().as_deref(), "");
+                declaration.push_str(&format!("enum {ident}: String, Codable, CaseIterable {{\n"));
+                for (variant, member) in variants.iter().zip(members(variants)) {
+                    let case = member_name(&member);
+                    match member == *variant {
+                        true => declaration.push_str(&format!("    case {case}\n")),
+                        false => declaration
+                            .push_str(&format!("    case {case} = {}\n", literal(variant))),
+                    }
+                }
+                declaration.push_str("}\n");
+                self.output.push(declaration);
+            }
+            TypeKind::TaggedUnion {
+                tag_field,
+                tag_variants,
+                data_fields,
+            } => {
+                let tag = self.unique(&format!("{ident}{}", pascal(tag_field)));
+                let tag_schema = SchemaType {
+                    kind: TypeKind::Enum {
+                        variants: tag_variants.clone(),
+                    },
+                    ..Default::default()
+                };
+                self.define(&tag, &tag_schema);
+
+                let mut properties = vec![Property {
+                    name: tag_field.clone(),
+                    ty: tag,
+                    optional: false,
+                    docs: None,
+                }];
+                let mut names: Vec<&String> = data_fields.keys().collect();
+                names.sort();
+                for name in names {
+                    let mut property = self.property(ident, name, &data_fields[name]);
+                    property.optional = true;
+                    properties.push(property);
+                }
+                self.structure(ident, schema.docs(), properties, false);
+            }
+            TypeKind::Variant { cases } => {
+                let recursive = cases
+                    .iter()
+                    .any(|case| case.data.as_ref().is_some_and(holds_ref));
+                let cases: Vec<EnumCase> =
+                    cases.iter().map(|case| self.case(ident, case)).collect();
+                self.variant(ident, schema, cases, recursive);
+            }
+            TypeKind::Result { ok, err } => {
+                let recursive = holds_ref(ok) || holds_ref(err);
+                let ok = self.reference(ok, &format!("{ident}Ok"));
+                let err = self.reference(err, &format!("{ident}Error"));
+                self.result(ident, schema.docs(), &ok, &err, recursive);
+            }
+            TypeKind::Tuple { fields } => {
+                let items: Vec<String> = fields
+                    .iter()
+                    .enumerate()
+                    .map(|(i, item)| self.reference(item, &format!("{ident}Item{i}")))
+                    .collect();
+                self.tuple(ident, schema.docs(), &items);
+            }
+            _ => unreachable!("declares() covers the kinds defined here"),
+        }
+    }
+
+    /// A nested schema: its declaration's name if it is a named type,
+    /// otherwise written in place, with anonymous declarations named after
+    /// `context`
+    fn reference(&mut self, schema: &SchemaType, context: &str) -> String {
+        let Some(name) = &schema.name else {
+            return self.expression(schema, context);
+        };
+        if matches!(schema.kind, TypeKind::Ref { .. })
+            || schema.ref_preference() == Some(RefPreference::Inline)
+        {
+            return self.expression(schema, context);
+        }
+
+        let shape = shape(schema);
+        if let Some(named) = self
+            .named
+            .iter()
+            .find(|named| named.name == *name && named.shape == shape)
+        {
+            return named.ident.clone();
+        }
+
+        // A different type may already have taken the name
+        let ident = self.unique(&identifier(name));
+        self.named.push(Named {
+            name: name.clone(),
+            shape,
+            ident: ident.clone(),
+            first: schema.clone(),
+        });
+        self.declare(&ident, schema);
+        ident
+    }
+
+    fn expression(&mut self, schema: &SchemaType, context: &str) -> String {
+        if declares(schema) {
+            let ident = self.unique(context);
+            self.define(&ident, schema);
+            return ident;
+        }
+
+        match &schema.kind {
+            TypeKind::String => "String".to_string(),
+            TypeKind::Integer(kind) => integer(kind).to_string(),
+            TypeKind::Number(NumberKind::F32) => "Float".to_string(),
+            TypeKind::Number(NumberKind::F64) => "Double".to_string(),
+            TypeKind::Boolean => "Bool".to_string(),
+            TypeKind::Null => self.null(),
+            TypeKind::Array { items } => {
+                format!("[{}]", self.reference(items, &format!("{context}Item")))
+            }
+            TypeKind::Set { items, .. } => {
+                let hashable = matches!(
+                    items.kind,
+                    TypeKind::String
+                        | TypeKind::Integer(_)
+                        | TypeKind::Number(_)
+                        | TypeKind::Boolean
+                        | TypeKind::Enum { .. }
+                );
+                let items = self.reference(items, &format!("{context}Item"));
+                match hashable {
+                    true => format!("Set<{items}>"),
+                    false => format!("[{items}]"),
+                }
+            }
+            TypeKind::Map { key, value, .. } => {
+                let value = self.reference(value, &format!("{context}Value"));
+                match &key.kind {
+                    // Swift only writes `String`-keyed dictionaries as objects
+                    TypeKind::String | TypeKind::Enum { .. } => format!("[String: {value}]"),
+                    // Other keys are encoded as a list of [key, value] pairs
+                    _ => {
+                        let entry = self.unique(&format!("{context}Entry"));
+                        let key = self.reference(key, &format!("{entry}Key"));
+                        self.tuple(&entry, None, &[key, value]);
+                        format!("[{entry}]")
+                    }
+                }
+            }
+            TypeKind::Ref { name } => self
+                .named
+                .iter()
+                .find(|named| named.name == *name)
+                .map(|named| named.ident.clone())
+                .unwrap_or_else(|| identifier(name)),
+            _ => unreachable!("declares() covers the remaining kinds"),
+        }
+    }
+
+    /// A stored property of the struct `owner`
+    fn property(&mut self, owner: &str, name: &str, schema: &SchemaType) -> Property {
+        Property {
+            name: name.to_string(),
+            ty: self.reference(schema, &format!("{owner}{}", pascal(name))),
+            optional: false,
+            docs: self.use_site_docs(schema),
+        }
+    }
+
+    /// One case of the Variant `owner`, declaring an anonymous payload struct
+    fn case(&mut self, owner: &str, case: &VariantCase) -> EnumCase {
+        EnumCase {
+            name: case.name.clone(),
+            member: member(&case.name),
+            payload: case
+                .data
+                .as_ref()
+                .map(|data| self.reference(data, &format!("{owner}{}", pascal(&case.name)))),
+            docs: case.description.clone(),
+        }
+    }
+
+    /// The `JSONNull` type, which only decodes and encodes `null`
+    fn null(&mut self) -> String {
+        if let Some(null) = &self.null {
+            return null.clone();
+        }
+        let null = self.unique("JSONNull");
+        self.output.push(format!(
+            r#"/// Decodes and encodes only `null`
+struct {null}: Codable, Hashable {{
+    init() {{}}
+
+    init(from decoder: Decoder) throws {{
+        let container = try decoder.singleValueContainer()
+        guard container.decodeNil() else {{
+            throw DecodingError.typeMismatch({null}.self, .init(codingPath: decoder.codingPath, debugDescription: "Expected null"))
+        }}
+    }}
+
+    func encode(to encoder: Encoder) throws {{
+        var container = encoder.singleValueContainer()
+        try container.encodeNil()
+    }}
+}}
+"#
+        ));
+        self.null = Some(null.clone());
+        null
+    }
+
+    /// A struct with `CodingKeys` where a property is renamed; a
+    /// `final class` when `recursive`
+    fn structure(
+        &mut self,
+        ident: &str,
+        docs: Option<String>,
+        properties: Vec<Property>,
+        recursive: bool,
+    ) {
+        let keyword = match recursive {
+            true => "final class",
+            false => "struct",
+        };
+        let mut declaration = comment(docs.as_deref(), "");
+        if properties.is_empty() {
+            declaration.push_str(&format!("{keyword} {ident}: Codable {{}}\n"));
+            self.output.push(declaration);
+            return;
+        }
+        declaration.push_str(&format!("{keyword} {ident}: Codable {{\n"));
+        let members = members(properties.iter().map(|property| &property.name));
+        for (property, member) in properties.iter().zip(&members) {
+            declaration.push_str(&comment(property.docs.as_deref(), "    "));
+            let optional = if property.optional { "?" } else { "" };
+            declaration.push_str(&format!(
+                "    let {}: {}{optional}\n",
+                member_name(member),
+                property.ty
+            ));
+        }
+        if properties
+            .iter()
+            .zip(&members)
+            .any(|(property, member)| *member != property.name)
+        {
+            declaration.push_str("\n    enum CodingKeys: String, CodingKey {\n");
+            for (property, member) in properties.iter().zip(&members) {
+                let case = member_name(member);
+                match *member == property.name {
+                    true => declaration.push_str(&format!("        case {case}\n")),
+                    false => declaration.push_str(&format!(
+                        "        case {case} = {}\n",
+                        literal(&property.name)
+                    )),
+                }
+            }
+            declaration.push_str("    }\n");
+        }
+        declaration.push_str("}\n");
+        self.output.push(declaration);
+    }
+
+    /// A struct with an `itemN` property per item, coded as a JSON array
+    fn tuple(&mut self, ident: &str, docs: Option<String>, items: &[String]) {
+        let mut declaration = comment(docs.as_deref(), "");
+        declaration.push_str(&format!("struct {ident}: Codable {{\n"));
+        for (i, item) in items.iter().enumerate() {
+            declaration.push_str(&format!("    let item{i}: {item}\n"));
+        }
+        declaration.push_str("}\n\n");
+
+        // In an extension, so the memberwise initializer stays
+        declaration.push_str(&format!("extension {ident} {{\n"));
+        declaration.push_str("    init(from decoder: Decoder) throws {\n");
+        let keyword = if items.is_empty() { "let" } else { "var" };
+        declaration.push_str(&format!(
+            "        {keyword} container = try decoder.unkeyedContainer()\n"
+        ));
+        for (i, item) in items.iter().enumerate() {
+            declaration.push_str(&format!(
+                "        item{i} = try container.decode({item}.self)\n"
+            ));
+        }
+        declaration.push_str("    }\n\n");
+        declaration.push_str("    func encode(to encoder: Encoder) throws {\n");
+        let keyword = if items.is_empty() {
+            "_ ="
+        } else {
+            "var container ="
+        };
+        declaration.push_str(&format!("        {keyword} encoder.unkeyedContainer()\n"));
+        for i in 0..items.len() {
+            declaration.push_str(&format!("        try container.encode(item{i})\n"));
+        }
+        declaration.push_str("    }\n}\n");
+        self.output.push(declaration);
+    }
+
+    /// An enum with `Result`'s `{"ok": ...}` / `{"error": ...}` coding
+    fn result(&mut self, ident: &str, docs: Option<String>, ok: &str, err: &str, recursive: bool) {
+        let indirect = if recursive { "indirect " } else { "" };
+        let mut declaration = comment(docs.as_deref(), "");
+        declaration.push_str(&format!(
+            r#"{indirect}enum {ident}: Codable {{
+    case ok({ok})
+    case error({err})
+
+    private enum CodingKeys: String, CodingKey {{
+        case ok
+        case error
+    }}
+
+    init(from decoder: Decoder) throws {{
+        let container = try decoder.container(keyedBy: CodingKeys.self)
+        if container.contains(.ok) {{
+            self = .ok(try container.decode({ok}.self, forKey: .ok))
+        }} else {{
+            self = .error(try container.decode({err}.self, forKey: .error))
+        }}
+    }}
+
+    func encode(to encoder: Encoder) throws {{
+        var container = encoder.container(keyedBy: CodingKeys.self)
+        switch self {{
+        case .ok(let value):
+            try container.encode(value, forKey: .ok)
+        case .error(let value):
+            try container.encode(value, forKey: .error)
+        }}
+    }}
+}}
+"#
+        ));
+        self.output.push(declaration);
+    }
+
+    /// An enum with a case per Variant case, coded the way its tagging says
+    fn variant(
+        &mut self,
+        ident: &str,
+        schema: &SchemaType,
+        mut cases: Vec<EnumCase>,
+        recursive: bool,
+    ) {
+        let members = members(cases.iter().map(|case| &case.name));
+        for (case, member) in cases.iter_mut().zip(members) {
+            case.member = member;
+        }
+        let indirect = if recursive { "indirect " } else { "" };
+        let mut declaration = comment(schema.docs().as_deref(), "");
+        declaration.push_str(&format!("{indirect}enum {ident}: Codable {{\n"));
+        for case in &cases {
+            declaration.push_str(&comment(case.docs.as_deref(), "    "));
+            match &case.payload {
+                Some(payload) => declaration.push_str(&format!(
+                    "    case {}({payload})\n",
+                    member_name(&case.member)
+                )),
+                None => declaration.push_str(&format!("    case {}\n", member_name(&case.member))),
+            }
+        }
+        if !cases.is_empty() {
+            let coding = match schema.tagging() {
+                None => keyed(ident, &cases, "type", Some("data"), true),
+                Some(Tagging::Adjacent { tag, content }) => {
+                    keyed(ident, &cases, &tag, Some(&content), false)
+                }
+                Some(Tagging::Internal { tag }) => keyed(ident, &cases, &tag, None, false),
+                Some(Tagging::Untagged) => untagged(ident, &cases),
+            };
+            declaration.push('\n');
+            declaration.push_str(&coding);
+        }
+        declaration.push_str("}\n");
+        self.output.push(declaration);
+    }
+
+    /// The docs for a property, unless its type's declaration already has
+    /// them
+    fn use_site_docs(&self, schema: &SchemaType) -> Option<String> {
+        schema.docs().filter(|docs| {
+            let declared = schema.name.as_ref().and_then(|name| {
+                self.named
+                    .iter()
+                    .find(|named| named.name == *name && named.shape == shape(schema))
+            });
+            declared.is_none_or(|named| named.first.docs().as_ref() != Some(docs))
+        })
+    }
+}
+
+/// Coding for a Variant whose case name sits in the `tag` property, with
+/// the payload under `content` or, without one, beside the tag; with
+/// `bare_units`, unit cases are just their name
+fn keyed(
+    ident: &str,
+    cases: &[EnumCase],
+    tag: &str,
+    content: Option<&str>,
+    bare_units: bool,
+) -> String {
+    let mut code = "    private enum CodingKeys: String, CodingKey {\n".to_string();
+    code.push_str(&format!("        case tag = {}\n", literal(tag)));
+    if let Some(content) = content {
+        code.push_str(&format!("        case content = {}\n", literal(content)));
+    }
+    code.push_str("    }\n\n");
+
+    code.push_str("    init(from decoder: Decoder) throws {\n");
+    let units: Vec<&EnumCase> = cases.iter().filter(|case| case.payload.is_none()).collect();
+    if bare_units && !units.is_empty() {
+        code.push_str(
+            "        if let name = try? decoder.singleValueContainer().decode(String.self) {\n",
+        );
+        code.push_str("            switch name {\n");
+        for case in &units {
+            code.push_str(&format!(
+                "            case {}:\n                self = .{}\n",
+                literal(&case.name),
+                case.member
+            ));
+        }
+        code.push_str(&format!(
+            "            default:\n                throw DecodingError.dataCorrupted(.init(codingPath: decoder.codingPath, debugDescription: \"Unknown {ident} case \\(name)\"))\n"
+        ));
+        code.push_str("            }\n            return\n        }\n");
+    }
+    code.push_str("        let container = try decoder.container(keyedBy: CodingKeys.self)\n");
+    code.push_str("        let tag = try container.decode(String.self, forKey: .tag)\n");
+    code.push_str("        switch tag {\n");
+    for case in cases {
+        let member = &case.member;
+        let value = match (&case.payload, content) {
+            (None, _) if bare_units => continue,
+            (None, _) => format!(".{member}"),
+            (Some(payload), Some(_)) => {
+                format!(".{member}(try container.decode({payload}.self, forKey: .content))")
+            }
+            (Some(payload), None) => format!(".{member}(try {payload}(from: decoder))"),
+        };
+        code.push_str(&format!(
+            "        case {}:\n            self = {value}\n",
+            literal(&case.name)
+        ));
+    }
+    code.push_str(&format!(
+        "        default:\n            throw DecodingError.dataCorruptedError(forKey: .tag, in: container, debugDescription: \"Unknown {ident} case \\(tag)\")\n"
+    ));
+    code.push_str("        }\n    }\n\n");
+
+    code.push_str("    func encode(to encoder: Encoder) throws {\n");
+    code.push_str("        switch self {\n");
+    for case in cases {
+        let member = &case.member;
+        let name = literal(&case.name);
+        match (&case.payload, content) {
+            (None, _) if bare_units => code.push_str(&format!(
+                "        case .{member}:\n            var container = encoder.singleValueContainer()\n            try container.encode({name})\n"
+            )),
+            (None, _) => code.push_str(&format!(
+                "        case .{member}:\n            var container = encoder.container(keyedBy: CodingKeys.self)\n            try container.encode({name}, forKey: .tag)\n"
+            )),
+            (Some(_), Some(_)) => code.push_str(&format!(
+                "        case .{member}(let value):\n            var container = encoder.container(keyedBy: CodingKeys.self)\n            try container.encode({name}, forKey: .tag)\n            try container.encode(value, forKey: .content)\n"
+            )),
+            // The payload's properties share the object with the tag
+            (Some(_), None) => code.push_str(&format!(
+                "        case .{member}(let value):\n            try value.encode(to: encoder)\n            var container = encoder.container(keyedBy: CodingKeys.self)\n            try container.encode({name}, forKey: .tag)\n"
+            )),
+        }
+    }
+    code.push_str("        }\n    }\n");
+    code
+}
+
+/// Coding for an untagged Variant: the first case whose payload decodes,
+/// with unit cases as `null`
+fn untagged(ident: &str, cases: &[EnumCase]) -> String {
+    let mut code = "    init(from decoder: Decoder) throws {\n".to_string();
+    code.push_str("        let container = try decoder.singleValueContainer()\n");
+    let mut null = false;
+    for case in cases {
+        let member = &case.member;
+        match &case.payload {
+            // serde picks the first unit case for `null`
+            None if null => continue,
+            None => {
+                null = true;
+                code.push_str(&format!(
+                    "        if container.decodeNil() {{\n            self = .{member}\n            return\n        }}\n"
+                ));
+            }
+            Some(payload) => code.push_str(&format!(
+                "        if let value = try? container.decode({payload}.self) {{\n            self = .{member}(value)\n            return\n        }}\n"
+            )),
+        }
+    }
+    code.push_str(&format!(
+        "        throw DecodingError.dataCorruptedError(in: container, debugDescription: \"No {ident} case matches\")\n"
+    ));
+    code.push_str("    }\n\n");
+
+    code.push_str("    func encode(to encoder: Encoder) throws {\n");
+    code.push_str("        var container = encoder.singleValueContainer()\n");
+    code.push_str("        switch self {\n");
+    for case in cases {
+        let member = &case.member;
+        match &case.payload {
+            None => code.push_str(&format!(
+                "        case .{member}:\n            try container.encodeNil()\n"
+            )),
+            Some(_) => code.push_str(&format!(
+                "        case .{member}(let value):\n            try container.encode(value)\n"
+            )),
+        }
+    }
+    code.push_str("        }\n    }\n");
+    code
+}
+
+/// Whether `schema` is a reference held directly, rather than behind a
+/// collection that already boxes it
+fn holds_ref(schema: &SchemaType) -> bool {
+    matches!(schema.kind, TypeKind::Ref { .. })
+}
+
+/// Whether `schema` is written as a declaration other than a `typealias`
+fn declares(schema: &SchemaType) -> bool {
+    matches!(
+        schema.kind,
+        TypeKind::Object { .. }
+            | TypeKind::Enum { .. }
+            | TypeKind::TaggedUnion { .. }
+            | TypeKind::Variant { .. }
+            | TypeKind::Result { .. }
+            | TypeKind::Tuple { .. }
+    )
+}
+
+fn integer(kind: &IntegerKind) -> &'static str {
+    match kind {
+        IntegerKind::I32 => "Int32",
+        IntegerKind::I64 => "Int64",
+        IntegerKind::U8 => "UInt8",
+        IntegerKind::U32 => "UInt32",
+        IntegerKind::U64 => "UInt64",
+        IntegerKind::Usize => "UInt",
+    }
+}
+
+/// `/// ` lines for `docs` at `indent`, ending in a newline
+fn comment(docs: Option<&str>, indent: &str) -> String {
+    let Some(docs) = docs else {
+        return String::new();
+    };
+    docs.lines()
+        .map(|line| match line {
+            "" => format!("{indent}///\n"),
+            line => format!("{indent}/// {line}\n"),
+        })
+        .collect()
+}
+
+/// A Swift string literal
+fn literal(s: &str) -> String {
+    let mut literal = String::from("\"");
+    for c in s.chars() {
+        match c {
+            '"' => literal.push_str("\\\""),
+            '\\' => literal.push_str("\\\\"),
+            '\n' => literal.push_str("\\n"),
+            '\r' => literal.push_str("\\r"),
+            '\t' => literal.push_str("\\t"),
+            c => literal.push(c),
+        }
+    }
+    literal.push('"');
+    literal
+}
+
+/// A type name for part of another's, `per_role` -> `PerRole`
+fn pascal(name: &str) -> String {
+    identifier(&Case::Pascal.apply(name))
+}
+
+/// A type name usable as a Swift identifier
+fn identifier(name: &str) -> String {
+    let ident: String = name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    match ident.starts_with(|c: char| c.is_ascii_digit()) {
+        true => format!("_{ident}"),
+        false => ident,
+    }
+}
+
+/// A property or case name as declared, in backticks if it is a keyword
+fn member_name(member: &str) -> String {
+    match KEYWORDS.contains(&member) {
+        true => format!("`{member}`"),
+        false => member.to_string(),
+    }
+}
+
+/// The members for the wire `names` of one declaration, numbered where two
+/// would have the same camelCase name
+fn members<'a>(names: impl IntoIterator<Item = &'a String>) -> Vec<String> {
+    let mut taken = BTreeSet::new();
+    names
+        .into_iter()
+        .map(|name| {
+            let base = member(name);
+            let member = (1..)
+                .map(|n| match n {
+                    1 => base.clone(),
+                    n => format!("{base}{n}"),
+                })
+                .find(|member| !taken.contains(member))
+                .unwrap();
+            taken.insert(member.clone());
+            member
+        })
+        .collect()
+}
+
+/// A property or case name in camelCase, as it is written after a `.`
+fn member(name: &str) -> String {
+    identifier(&Case::Camel.apply(name))
+}
+
+/// What makes two occurrences of a named type the same declaration: its
+/// fingerprint without the annotations a field adds to it
+fn shape(schema: &SchemaType) -> String {
+    let mut bare = schema.clone();
+    bare.title = None;
+    bare.description = None;
+    bare.default = None;
+    for key in [
+        schema::DEPRECATED,
+        schema::READ_ONLY,
+        schema::WRITE_ONLY,
+        schema::SINCE,
+        schema::REF_PREFERENCE,
+    ] {
+        bare.extensions.remove(key);
+    }
+    bare.fingerprint()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_keywords_and_builtins() {
+        #[derive(Schema)]
+        #[allow(dead_code)]
+        enum Visibility {
+            #[schema(rename = "default")]
+            Standard,
+            #[schema(rename = "private")]
+            Hidden,
+            #[schema(rename = "2x")]
+            Double,
+        }
+
+        #[derive(Schema)]
+        #[allow(dead_code)]
+        struct Set {
+            class: String,
+            #[schema(rename = "extension")]
+            kind: Option<String>,
+            user_id: u64,
+            visibility: Visibility,
+            span: (u32, u32),
+        }
+
+        // Set is taken by Swift's own type
+        assert_eq!(
+            to_swift::<Set>(),
+            r#"import Foundation
+
+enum Visibility: String, Codable, CaseIterable {
+    case `default`
+    case `private`
+    case _2x = "2x"
+}
+
+struct Set2Span: Codable {
+    let item0: UInt32
+    let item1: UInt32
+}
+
+extension Set2Span {
+    init(from decoder: Decoder) throws {
+        var container = try decoder.unkeyedContainer()
+        item0 = try container.decode(UInt32.self)
+        item1 = try container.decode(UInt32.self)
+    }
+
+    func encode(to encoder: Encoder) throws {
+        var container = encoder.unkeyedContainer()
+        try container.encode(item0)
+        try container.encode(item1)
+    }
+}
+
+struct Set2: Codable {
+    let `class`: String
+    let `extension`: String?
+    let userId: UInt64
+    let visibility: Visibility
+    let span: Set2Span
+
+    enum CodingKeys: String, CodingKey {
+        case `class`
+        case `extension`
+        case userId = "user_id"
+        case visibility
+        case span
+    }
+}
+"#
+        );
+    }
+
+    #[test]
+    fn test_member_collisions() {
+        #[derive(Schema)]
+        #[allow(dead_code)]
+        enum Status {
+            #[schema(rename = "in_progress")]
+            Snake,
+            #[schema(rename = "inProgress")]
+            Camel,
+        }
+
+        #[derive(Schema)]
+        #[allow(dead_code)]
+        enum Change {
+            #[schema(rename = "set_value")]
+            Snake(String),
+            #[schema(rename = "setValue")]
+            Camel(String),
+        }
+
+        #[derive(Schema)]
+        #[allow(dead_code)]
+        struct Legacy {
+            #[schema(rename = "user_id")]
+            snake: u64,
+            #[schema(rename = "userId")]
+            camel: u64,
+            status: Status,
+            change: Change,
+        }
+
+        let swift = to_swift::<Legacy>();
+        assert!(swift.contains(
+            r#"enum Status: String, Codable, CaseIterable {
+    case inProgress = "in_progress"
+    case inProgress2 = "inProgress"
+}
+"#
+        ));
+        assert!(swift.contains("    case setValue(String)\n    case setValue2(String)\n"));
+        assert!(swift.contains(
+            "        case \"setValue\":\n            self = .setValue2(try container.decode(String.self, forKey: .content))\n"
+        ));
+        assert!(swift.contains(
+            r#"struct Legacy: Codable {
+    let userId: UInt64
+    let userId2: UInt64
+    let status: Status
+    let change: Change
+
+    enum CodingKeys: String, CodingKey {
+        case userId = "user_id"
+        case userId2 = "userId"
+        case status
+        case change
+    }
+}
+"#
+        ));
+    }
+
+    #[test]
+    fn test_internally_tagged_variant() {
+        #[derive(Schema)]
+        #[allow(dead_code)]
+        #[schema(tag = "kind")]
+        enum Shape {
+            Empty,
+            /// A circle
+            Circle {
+                radius: f64,
+            },
+        }
+
+        let swift = to_swift::<Shape>();
+        assert!(swift.contains(
+            r#"enum Shape: Codable {
+    case empty
+    /// A circle
+    case circle(ShapeCircle)
+
+    private enum CodingKeys: String, CodingKey {
+        case tag = "kind"
+    }
+
+    init(from decoder: Decoder) throws {
+        let container = try decoder.container(keyedBy: CodingKeys.self)
+        let tag = try container.decode(String.self, forKey: .tag)
+        switch tag {
+        case "Empty":
+            self = .empty
+        case "Circle":
+            self = .circle(try ShapeCircle(from: decoder))
+        default:
+            throw DecodingError.dataCorruptedError(forKey: .tag, in: container, debugDescription: "Unknown Shape case \(tag)")
+        }
+    }
+
+    func encode(to encoder: Encoder) throws {
+        switch self {
+        case .empty:
+            var container = encoder.container(keyedBy: CodingKeys.self)
+            try container.encode("Empty", forKey: .tag)
+        case .circle(let value):
+            try value.encode(to: encoder)
+            var container = encoder.container(keyedBy: CodingKeys.self)
+            try container.encode("Circle", forKey: .tag)
+        }
+    }
+}
+"#
+        ));
+    }
+
+    #[test]
+    fn test_recursive_types_are_boxed() {
+        #[derive(Schema)]
+        #[allow(dead_code)]
+        struct Node {
+            value: i32,
+            next: Option<Box<Node>>,
+        }
+
+        assert!(to_swift::<Node>().contains("final class Node: Codable {"));
+    }
+}
@@ -0,0 +1,860 @@
+//! Apache Thrift IDL from schemas
+//!
+//! The root and every named type nested in it become a top-level `struct`,
+//! `union`, `enum` or `typedef`, each after the ones it uses. Required
+//! properties are `required` fields and the rest `optional`; arrays, sets
+//! and maps are `list`, `set` and `map`, and variants a `union` with a field
+//! per case. Anonymous types that need a declaration are named after where
+//! they appear (`EventMove`), and named types Thrift has no declaration for,
+//! such as a newtype around a string, become a `typedef`.
+//!
+//! Field IDs follow declaration order. Adding a field anywhere but the end
+//! renumbers those after it, so pin IDs that are already on the wire with
+//! `#[schema(extension("x-thrift-field" = 3))]` on the field or variant;
+//! automatic IDs skip the pinned ones.
+//!
+//! Thrift's integers are signed, so unsigned ones widen to the next size up;
+//! `u64` has nowhere to go and shares `i64`, wrapping above `i64::MAX`.
+
+use schema::case::Case;
+use schema::{
+    GenerateOptions, IntegerKind, NumberKind, RefPreference, Schema, SchemaType, TypeKind,
+};
+use serde_json::Value;
+use std::collections::BTreeSet;
+use std::fmt;
+
+/// Extension pinning a field's or variant case's ID
+pub const FIELD_ID: &str = "x-thrift-field";
+
+/// Largest field ID Thrift accepts
+const MAX_FIELD_ID: u64 = i16::MAX as u64;
+
+/// Options for the generated `.thrift` file
+#[derive(Debug, Clone, Default)]
+pub struct ThriftOptions {
+    /// `namespace *` declaration, e.g. `acme.billing`
+    pub namespace: Option<String>,
+    pub generate: GenerateOptions,
+}
+
+impl ThriftOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn namespace(mut self, namespace: &str) -> Self {
+        self.namespace = Some(namespace.to_string());
+        self
+    }
+
+    /// Bound the schema before it is written
+    pub fn generate(mut self, generate: GenerateOptions) -> Self {
+        self.generate = generate;
+        self
+    }
+}
+
+/// Why a schema has no Thrift definition
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ThriftError {
+    /// A pinned ID is zero or doesn't fit in an `i16`
+    InvalidFieldId {
+        declaration: String,
+        field: String,
+        id: u64,
+    },
+    /// Two fields of one struct or union are pinned to the same ID
+    DuplicateFieldId { declaration: String, id: u64 },
+}
+
+impl fmt::Display for ThriftError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ThriftError::InvalidFieldId {
+                declaration,
+                field,
+                id,
+            } => write!(
+                f,
+                "{}.{} is pinned to {}, which is not a valid field ID",
+                declaration, field, id
+            ),
+            ThriftError::DuplicateFieldId { declaration, id } => {
+                write!(f, "{} has two fields with ID {}", declaration, id)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ThriftError {}
+
+/// The `.thrift` file for `T` and the named types it contains
+pub fn to_thrift<T: Schema>() -> Result<String, ThriftError> {
+    schema_type_to_thrift(&T::schema(), T::type_name())
+}
+
+/// The `.thrift` file for `T` and the named types it contains, with `options`
+pub fn to_thrift_with_options<T: Schema>(options: &ThriftOptions) -> Result<String, ThriftError> {
+    schema_type_to_thrift_with_options(&T::schema(), T::type_name(), options)
+}
+
+/// The `.thrift` file for a SchemaType, named `type_name` (or the schema's
+/// own name, or `Root`)
+pub fn schema_type_to_thrift(
+    schema: &SchemaType,
+    type_name: Option<&str>,
+) -> Result<String, ThriftError> {
+    schema_type_to_thrift_with_options(schema, type_name, &ThriftOptions::default())
+}
+
+/// The `.thrift` file for a SchemaType, with `options`
+pub fn schema_type_to_thrift_with_options(
+    schema: &SchemaType,
+    type_name: Option<&str>,
+    options: &ThriftOptions,
+) -> Result<String, ThriftError> {
+    let schema = options.generate.apply(schema);
+    let mut module = Module::default();
+    let ident = module.unique(&identifier(
+        type_name.or(schema.name.as_deref()).unwrap_or("Root"),
+    ));
+    if let Some(name) = &schema.name {
+        module.named.push(Named {
+            name: name.clone(),
+            shape: shape(&schema),
+            ident: ident.clone(),
+            first: schema.clone(),
+        });
+    }
+    module.declare(&ident, &schema)?;
+
+    let mut output = String::new();
+    if let Some(namespace) = &options.namespace {
+        output.push_str(&format!("namespace * {}\n", namespace));
+    }
+    for declaration in &module.output {
+        if !output.is_empty() {
+            output.push('\n');
+        }
+        output.push_str(declaration);
+    }
+    Ok(output)
+}
+
+/// A named type that has a declaration
+struct Named {
+    name: String,
+    /// Fingerprint without use-site annotations
+    shape: String,
+    ident: String,
+    /// The occurrence the declaration was written from
+    first: SchemaType,
+}
+
+/// Whether a field must be present
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Requiredness {
+    Required,
+    Optional,
+    /// Union members and tuple items, which take no qualifier
+    Default,
+}
+
+/// One field of a struct or union, before numbering
+struct Field {
+    /// Wire name
+    name: String,
+    ty: String,
+    requiredness: Requiredness,
+    /// ID pinned with [`FIELD_ID`]
+    pinned: Option<u64>,
+    default: Option<String>,
+    docs: Option<String>,
+}
+
+impl Field {
+    fn new(name: &str, ty: String) -> Self {
+        Field {
+            name: name.to_string(),
+            ty,
+            requiredness: Requiredness::Default,
+            pinned: None,
+            default: None,
+            docs: None,
+        }
+    }
+}
+
+#[derive(Default)]
+struct Module {
+    named: Vec<Named>,
+    /// Every type name in use
+    taken: BTreeSet<String>,
+    /// Declarations, each after the ones it uses
+    output: Vec<String>,
+    /// The empty struct unit cases and `null` values are written as
+    empty: Option<String>,
+}
+
+impl Module {
+    /// `base`, or `base2`, `base3`, ... if something already has the name
+    fn unique(&mut self, base: &str) -> String {
+        let ident = (1..)
+            .map(|n| match n {
+                1 => base.to_string(),
+                n => format!("{base}{n}"),
+            })
+            .find(|ident| !self.taken.contains(ident))
+            .unwrap();
+        self.taken.insert(ident.clone());
+        ident
+    }
+
+    /// The declaration `ident` for `schema`; types with none of their own
+    /// are a `typedef`
+    fn declare(&mut self, ident: &str, schema: &SchemaType) -> Result<(), ThriftError> {
+        if declares(schema) {
+            return self.define(ident, schema);
+        }
+        let ty = self.expression(schema, &format!("{ident}Value"))?;
+        let mut declaration = comment(schema.docs().as_deref(), "");
+        declaration.push_str(&format!("typedef {ty} {ident}\n"));
+        self.output.push(declaration);
+        Ok(())
+    }
+
+    /// The declaration for a kind that has one of its own
+    fn define(&mut self, ident: &str, schema: &SchemaType) -> Result<(), ThriftError> {
+        match &schema.kind {
+            TypeKind::Object { required, .. } => {
+                let mut fields = vec![];
+                for (name, property) in schema.ordered_properties() {
+                    let mut field = self.field(ident, name, property)?;
+                    field.requiredness = match required.iter().any(|r| r == name) {
+                        true => Requiredness::Required,
+                        false => Requiredness::Optional,
+                    };
+                    fields.push(field);
+                }
+                self.structure(ident, "struct", schema.docs(), fields)
+            }
+            TypeKind::Enum { variants } => {
+                let mut lines = vec![];
+                for (i, variant) in variants.iter().enumerate() {
+                    lines.push(format!("  {} = {}", identifier(variant), i));
+                }
+                let mut declaration = comment(schema.docs().as_deref(), "");
+                declaration.push_str(&format!("enum {ident} {{\n{}\n}}\n", lines.join("\n")));
+                self.output.push(declaration);
+                Ok(())
+            }
+            TypeKind::TaggedUnion {
+                tag_field,
+                tag_variants,
+                data_fields,
+            } => {
+                let tag = self.unique(&format!("{ident}{}", pascal(tag_field)));
+                let tag_schema = SchemaType {
+                    kind: TypeKind::Enum {
+                        variants: tag_variants.clone(),
+                    },
+                    ..Default::default()
+                };
+                self.define(&tag, &tag_schema)?;
+
+                let mut field = Field::new(tag_field, tag);
+                field.requiredness = Requiredness::Required;
+                let mut fields = vec![field];
+                let mut names: Vec<&String> = data_fields.keys().collect();
+                names.sort();
+                for name in names {
+                    let mut field = self.field(ident, name, &data_fields[name])?;
+                    field.requiredness = Requiredness::Optional;
+                    fields.push(field);
+                }
+                self.structure(ident, "struct", schema.docs(), fields)
+            }
+            TypeKind::Variant { cases } => {
+                let mut fields = vec![];
+                for case in cases {
+                    let ty = match &case.data {
+                        Some(data) => {
+                            self.reference(data, &format!("{ident}{}", pascal(&case.name)))?
+                        }
+                        None => self.empty(),
+                    };
+                    let mut field = Field::new(&case.name, ty);
+                    field.pinned = case.extensions.get(FIELD_ID).and_then(Value::as_u64);
+                    field.docs = case.description.clone();
+                    fields.push(field);
+                }
+                self.structure(ident, "union", schema.docs(), fields)
+            }
+            TypeKind::Result { ok, err } => {
+                let ok = self.reference(ok, &format!("{ident}Ok"))?;
+                let err = self.reference(err, &format!("{ident}Error"))?;
+                let fields = vec![Field::new("ok", ok), Field::new("error", err)];
+                self.structure(ident, "union", schema.docs(), fields)
+            }
+            TypeKind::Tuple { fields: items } => {
+                let mut fields = vec![];
+                for (i, item) in items.iter().enumerate() {
+                    let ty = self.reference(item, &format!("{ident}Item{i}"))?;
+                    let mut field = Field::new(&format!("item{i}"), ty);
+                    field.requiredness = Requiredness::Required;
+                    fields.push(field);
+                }
+                self.structure(ident, "struct", schema.docs(), fields)
+            }
+            _ => unreachable!("declares() covers the kinds defined here"),
+        }
+    }
+
+    /// A nested schema: its declaration's name if it is a named type,
+    /// otherwise written in place, with anonymous declarations named after
+    /// `context`
+    fn reference(&mut self, schema: &SchemaType, context: &str) -> Result<String, ThriftError> {
+        let Some(name) = &schema.name else {
+            return self.expression(schema, context);
+        };
+        if matches!(schema.kind, TypeKind::Ref { .. })
+            || schema.ref_preference() == Some(RefPreference::Inline)
+        {
+            return self.expression(schema, context);
+        }
+
+        let shape = shape(schema);
+        if let Some(named) = self
+            .named
+            .iter()
+            .find(|named| named.name == *name && named.shape == shape)
+        {
+            return Ok(named.ident.clone());
+        }
+
+        // A different type may already have taken the name
+        let ident = self.unique(&identifier(name));
+        self.named.push(Named {
+            name: name.clone(),
+            shape,
+            ident: ident.clone(),
+            first: schema.clone(),
+        });
+        self.declare(&ident, schema)?;
+        Ok(ident)
+    }
+
+    fn expression(&mut self, schema: &SchemaType, context: &str) -> Result<String, ThriftError> {
+        if declares(schema) {
+            let ident = self.unique(context);
+            self.define(&ident, schema)?;
+            return Ok(ident);
+        }
+
+        Ok(match &schema.kind {
+            TypeKind::String => "string".to_string(),
+            TypeKind::Integer(kind) => integer(kind).to_string(),
+            TypeKind::Number(NumberKind::F32 | NumberKind::F64) => "double".to_string(),
+            TypeKind::Boolean => "bool".to_string(),
+            TypeKind::Null => self.empty(),
+            TypeKind::Array { items } => {
+                format!(
+                    "list<{}>",
+                    self.reference(items, &format!("{context}Item"))?
+                )
+            }
+            TypeKind::Set { items, .. } => {
+                format!("set<{}>", self.reference(items, &format!("{context}Item"))?)
+            }
+            TypeKind::Map { key, value, .. } => format!(
+                "map<{}, {}>",
+                self.reference(key, &format!("{context}Key"))?,
+                self.reference(value, &format!("{context}Value"))?
+            ),
+            TypeKind::Ref { name } => self
+                .named
+                .iter()
+                .find(|named| named.name == *name)
+                .map(|named| named.ident.clone())
+                .unwrap_or_else(|| identifier(name)),
+            _ => unreachable!("declares() covers the remaining kinds"),
+        })
+    }
+
+    /// A field of `owner`, pinned if its schema says so
+    fn field(
+        &mut self,
+        owner: &str,
+        name: &str,
+        schema: &SchemaType,
+    ) -> Result<Field, ThriftError> {
+        let ty = self.reference(schema, &format!("{owner}{}", pascal(name)))?;
+        let mut field = Field::new(name, ty);
+        field.pinned = schema.extensions.get(FIELD_ID).and_then(Value::as_u64);
+        field.default = self.default_value(schema);
+        field.docs = self.use_site_docs(schema);
+        Ok(field)
+    }
+
+    /// The `= value` literal for a field's default, where Thrift has one
+    fn default_value(&self, schema: &SchemaType) -> Option<String> {
+        match (&schema.kind, schema.default.as_ref()?) {
+            (TypeKind::Boolean, Value::Bool(b)) => Some(b.to_string()),
+            (TypeKind::Integer(_), Value::Number(n)) if !n.is_f64() => Some(n.to_string()),
+            (TypeKind::Number(_), Value::Number(n)) => Some(n.to_string()),
+            (TypeKind::String, default @ Value::String(_)) => Some(default.to_string()),
+            (TypeKind::Enum { variants }, Value::String(s)) if variants.contains(s) => {
+                let ident = self
+                    .named
+                    .iter()
+                    .find(|named| Some(&named.name) == schema.name.as_ref())?
+                    .ident
+                    .clone();
+                Some(format!("{ident}.{}", identifier(s)))
+            }
+            _ => None,
+        }
+    }
+
+    /// The empty struct standing in for values with no data
+    fn empty(&mut self) -> String {
+        if let Some(empty) = &self.empty {
+            return empty.clone();
+        }
+        let empty = self.unique("Empty");
+        self.output.push(format!("struct {empty} {{}}\n"));
+        self.empty = Some(empty.clone());
+        empty
+    }
+
+    /// Write `struct ident` or `union ident`, numbering its fields
+    fn structure(
+        &mut self,
+        ident: &str,
+        keyword: &str,
+        docs: Option<String>,
+        fields: Vec<Field>,
+    ) -> Result<(), ThriftError> {
+        let ids = ids(ident, &fields)?;
+        let mut lines = vec![];
+        for (field, id) in fields.iter().zip(ids) {
+            let requiredness = match field.requiredness {
+                Requiredness::Required => "required ",
+                Requiredness::Optional => "optional ",
+                Requiredness::Default => "",
+            };
+            let default = match &field.default {
+                Some(default) => format!(" = {default}"),
+                None => String::new(),
+            };
+            lines.push(format!(
+                "{}  {id}: {requiredness}{} {}{default}",
+                comment(field.docs.as_deref(), "  "),
+                field.ty,
+                identifier(&field.name)
+            ));
+        }
+
+        let mut declaration = comment(docs.as_deref(), "");
+        match lines.is_empty() {
+            true => declaration.push_str(&format!("{keyword} {ident} {{}}\n")),
+            false => {
+                declaration.push_str(&format!("{keyword} {ident} {{\n{}\n}}\n", lines.join("\n")))
+            }
+        }
+        self.output.push(declaration);
+        Ok(())
+    }
+
+    /// The docs for a field, unless its type's declaration already has them
+    fn use_site_docs(&self, schema: &SchemaType) -> Option<String> {
+        schema.docs().filter(|docs| {
+            let declared = schema.name.as_ref().and_then(|name| {
+                self.named
+                    .iter()
+                    .find(|named| named.name == *name && named.shape == shape(schema))
+            });
+            declared.is_none_or(|named| named.first.docs().as_ref() != Some(docs))
+        })
+    }
+}
+
+/// Whether `schema` is written as a declaration other than a `typedef`
+fn declares(schema: &SchemaType) -> bool {
+    matches!(
+        schema.kind,
+        TypeKind::Object { .. }
+            | TypeKind::Enum { .. }
+            | TypeKind::TaggedUnion { .. }
+            | TypeKind::Variant { .. }
+            | TypeKind::Result { .. }
+            | TypeKind::Tuple { .. }
+    )
+}
+
+/// Field IDs in order: pinned ones as given, the rest counting up from 1
+/// around them
+fn ids(declaration: &str, fields: &[Field]) -> Result<Vec<u64>, ThriftError> {
+    let mut pinned = BTreeSet::new();
+    for field in fields {
+        let Some(id) = field.pinned else {
+            continue;
+        };
+        if id == 0 || id > MAX_FIELD_ID {
+            return Err(ThriftError::InvalidFieldId {
+                declaration: declaration.to_string(),
+                field: field.name.clone(),
+                id,
+            });
+        }
+        if !pinned.insert(id) {
+            return Err(ThriftError::DuplicateFieldId {
+                declaration: declaration.to_string(),
+                id,
+            });
+        }
+    }
+
+    let mut next = 1;
+    Ok(fields
+        .iter()
+        .map(|field| {
+            field.pinned.unwrap_or_else(|| {
+                while pinned.contains(&next) {
+                    next += 1;
+                }
+                next += 1;
+                next - 1
+            })
+        })
+        .collect())
+}
+
+fn integer(kind: &IntegerKind) -> &'static str {
+    match kind {
+        IntegerKind::I32 => "i32",
+        IntegerKind::I64 => "i64",
+        IntegerKind::U8 => "i16",
+        IntegerKind::U32 | IntegerKind::U64 | IntegerKind::Usize => "i64",
+    }
+}
+
+/// A `/** */` doc comment for `docs` at `indent`, ending in a newline
+fn comment(docs: Option<&str>, indent: &str) -> String {
+    let Some(docs) = docs else {
+        return String::new();
+    };
+    let docs = docs.replace("*/", "* /");
+    let mut comment = format!("{indent}/**\n");
+    for line in docs.lines() {
+        match line {
+            "" => comment.push_str(&format!("{indent} *\n")),
+            line => comment.push_str(&format!("{indent} * {line}\n")),
+        }
+    }
+    comment.push_str(&format!("{indent} */\n"));
+    comment
+}
+
+/// A type name for part of another's, `per_role` -> `PerRole`
+fn pascal(name: &str) -> String {
+    identifier(&Case::Pascal.apply(name))
+}
+
+/// A name usable as a Thrift identifier, with `_` after reserved words
+fn identifier(name: &str) -> String {
+    let ident: String = name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    if ident.starts_with(|c: char| c.is_ascii_digit()) {
+        format!("_{ident}")
+    } else if RESERVED.contains(&ident.as_str()) {
+        format!("{ident}_")
+    } else {
+        ident
+    }
+}
+
+/// Thrift keywords and the words it reserves for target languages
+const RESERVED: &[&str] = &[
+    "BEGIN",
+    "END",
+    "__CLASS__",
+    "__DIR__",
+    "__FILE__",
+    "__FUNCTION__",
+    "__LINE__",
+    "__METHOD__",
+    "__NAMESPACE__",
+    "abstract",
+    "alias",
+    "and",
+    "args",
+    "as",
+    "assert",
+    "begin",
+    "binary",
+    "bool",
+    "break",
+    "byte",
+    "case",
+    "catch",
+    "class",
+    "clone",
+    "const",
+    "continue",
+    "declare",
+    "def",
+    "default",
+    "del",
+    "delete",
+    "do",
+    "double",
+    "dynamic",
+    "elif",
+    "else",
+    "elseif",
+    "elsif",
+    "end",
+    "enddeclare",
+    "endfor",
+    "endforeach",
+    "endif",
+    "endswitch",
+    "endwhile",
+    "ensure",
+    "enum",
+    "except",
+    "exception",
+    "exec",
+    "extends",
+    "false",
+    "final",
+    "finally",
+    "float",
+    "for",
+    "foreach",
+    "from",
+    "function",
+    "global",
+    "goto",
+    "i16",
+    "i32",
+    "i64",
+    "if",
+    "implements",
+    "import",
+    "in",
+    "include",
+    "inline",
+    "instanceof",
+    "interface",
+    "is",
+    "lambda",
+    "list",
+    "map",
+    "module",
+    "namespace",
+    "native",
+    "new",
+    "next",
+    "nil",
+    "not",
+    "oneway",
+    "optional",
+    "or",
+    "pass",
+    "print",
+    "private",
+    "protected",
+    "public",
+    "raise",
+    "redo",
+    "register",
+    "required",
+    "rescue",
+    "retry",
+    "return",
+    "self",
+    "service",
+    "set",
+    "sizeof",
+    "static",
+    "string",
+    "struct",
+    "super",
+    "switch",
+    "synchronized",
+    "then",
+    "this",
+    "throw",
+    "throws",
+    "transient",
+    "true",
+    "try",
+    "typedef",
+    "undef",
+    "union",
+    "unless",
+    "unsigned",
+    "until",
+    "use",
+    "var",
+    "virtual",
+    "void",
+    "volatile",
+    "when",
+    "while",
+    "with",
+    "xor",
+    "yield",
+];
+
+/// What makes two occurrences of a named type the same declaration: its
+/// fingerprint without the annotations a field adds to it
+fn shape(schema: &SchemaType) -> String {
+    let mut bare = schema.clone();
+    bare.title = None;
+    bare.description = None;
+    bare.default = None;
+    for key in [
+        schema::DEPRECATED,
+        schema::READ_ONLY,
+        schema::WRITE_ONLY,
+        schema::SINCE,
+        schema::REF_PREFERENCE,
+        FIELD_ID,
+    ] {
+        bare.extensions.remove(key);
+    }
+    bare.fingerprint()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reserved_words_and_widening() {
+        #[derive(Schema)]
+        #[allow(dead_code)]
+        struct Account(String);
+
+        #[derive(Schema)]
+        #[allow(dead_code)]
+        enum Flag {
+            #[schema(rename = "true")]
+            Set,
+            #[schema(rename = "false")]
+            Unset,
+        }
+
+        /// Moves money; closes with */ inside
+        #[derive(Schema)]
+        #[allow(dead_code)]
+        struct Transfer {
+            from: Account,
+            to: Account,
+            #[schema(rename = "list")]
+            items: Vec<u8>,
+            amount: u32,
+            #[schema(rename = "end")]
+            end_at: Option<u64>,
+            #[schema(rename = "1st")]
+            first: bool,
+            flag: Flag,
+        }
+
+        let thrift = to_thrift_with_options::<Transfer>(&ThriftOptions::new().namespace("acme"));
+        assert_eq!(
+            thrift.unwrap(),
+            r#"namespace * acme
+
+typedef string Account
+
+enum Flag {
+  true_ = 0
+  false_ = 1
+}
+
+/**
+ * Moves money; closes with * / inside
+ */
+struct Transfer {
+  1: required Account from_
+  2: required Account to
+  3: required list<i16> list_
+  4: required i64 amount
+  5: optional i64 end_
+  6: required bool _1st
+  7: required Flag flag
+}
+"#
+        );
+    }
+
+    #[test]
+    fn test_variants_become_unions() {
+        #[derive(Schema)]
+        #[allow(dead_code)]
+        enum Event {
+            Start,
+            Move {
+                x: i64,
+            },
+            #[schema(extension("x-thrift-field" = 1))]
+            Batch(Vec<u32>),
+        }
+
+        assert_eq!(
+            to_thrift::<Event>().unwrap(),
+            r#"struct Empty {}
+
+struct EventMove {
+  1: required i64 x
+}
+
+union Event {
+  2: Empty Start
+  3: EventMove Move
+  1: list<i64> Batch
+}
+"#
+        );
+    }
+
+    #[test]
+    fn test_invalid_pins_are_errors() {
+        #[derive(Schema)]
+        #[allow(dead_code)]
+        struct Clash {
+            #[schema(extension("x-thrift-field" = 1))]
+            a: String,
+            #[schema(extension("x-thrift-field" = 1))]
+            b: String,
+        }
+
+        assert_eq!(
+            to_thrift::<Clash>().unwrap_err(),
+            ThriftError::DuplicateFieldId {
+                declaration: "Clash".to_string(),
+                id: 1
+            }
+        );
+
+        // IDs are i16
+        #[derive(Schema)]
+        #[allow(dead_code)]
+        struct Wide {
+            #[schema(extension("x-thrift-field" = 32768))]
+            a: String,
+        }
+
+        assert_eq!(
+            to_thrift::<Wide>().unwrap_err().to_string(),
+            "Wide.a is pinned to 32768, which is not a valid field ID"
+        );
+    }
+}
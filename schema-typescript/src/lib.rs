@@ -0,0 +1,577 @@
+//! TypeScript declarations (`.d.ts`) from schemas
+//!
+//! The root and every named type nested in it get an exported declaration:
+//! objects become interfaces, everything else a type alias. Variants become
+//! discriminated unions following their tagging, enums string literal
+//! unions, and maps `Record`s. Docs are rendered as TSDoc comments.
+
+use schema::{GenerateOptions, RefPreference, Schema, SchemaType, Tagging, TypeKind, VariantCase};
+
+/// Declarations for `T` and the named types it contains
+pub fn to_typescript<T: Schema>() -> String {
+    schema_type_to_typescript(&T::schema(), T::type_name())
+}
+
+/// Declarations for `T` and the named types it contains, bounded by `options`
+pub fn to_typescript_with_options<T: Schema>(options: &GenerateOptions) -> String {
+    schema_type_to_typescript_with_options(&T::schema(), T::type_name(), options)
+}
+
+/// Declarations for a SchemaType, bounded by `options`
+pub fn schema_type_to_typescript_with_options(
+    schema: &SchemaType,
+    type_name: Option<&str>,
+    options: &GenerateOptions,
+) -> String {
+    schema_type_to_typescript(&options.apply(schema), type_name)
+}
+
+/// Declarations for a SchemaType, named `type_name` (or the schema's own
+/// name, or `Root`), followed by those of the named types it contains
+pub fn schema_type_to_typescript(schema: &SchemaType, type_name: Option<&str>) -> String {
+    let mut declarations = Declarations::default();
+    let ident = identifier(type_name.or(schema.name.as_deref()).unwrap_or("Root"));
+    if let Some(name) = &schema.name {
+        declarations.named.push(Named {
+            name: name.clone(),
+            shape: shape(schema),
+            ident: ident.clone(),
+            first: schema.clone(),
+        });
+    }
+    declarations.declare(&ident, schema);
+
+    let mut output = declarations.output.join("\n\n");
+    output.push('\n');
+    output
+}
+
+/// A named type that has a declaration
+struct Named {
+    name: String,
+    /// Fingerprint without use-site annotations
+    shape: String,
+    ident: String,
+    /// The occurrence the declaration was written from
+    first: SchemaType,
+}
+
+#[derive(Default)]
+struct Declarations {
+    named: Vec<Named>,
+    /// Declarations in the order the types were first reached
+    output: Vec<String>,
+}
+
+impl Declarations {
+    fn declare(&mut self, ident: &str, schema: &SchemaType) {
+        // Claim the slot first, so the root comes before what it contains
+        let slot = self.output.len();
+        self.output.push(String::new());
+
+        let mut declaration = tsdoc(schema.docs().as_deref(), schema.deprecated(), "");
+        match &schema.kind {
+            TypeKind::Object { required, .. } => {
+                declaration.push_str(&format!("export interface {} {{\n", ident));
+                for (name, property) in schema.ordered_properties() {
+                    let optional = !required.iter().any(|r| r == name);
+                    let field = self.field(name, optional, property);
+                    let docs = self.use_site_docs(property);
+                    declaration.push_str(&tsdoc(docs.as_deref(), property.deprecated(), "  "));
+                    declaration.push_str(&format!("  {};\n", field));
+                }
+                declaration.push('}');
+            }
+            _ => {
+                let expression = self.expression(schema);
+                declaration.push_str(&format!("export type {} = {};", ident, expression));
+            }
+        }
+        self.output[slot] = declaration;
+    }
+
+    /// A property's docs, unless they're just its declared type's own
+    fn use_site_docs(&self, schema: &SchemaType) -> Option<String> {
+        let docs = schema.docs()?;
+        let declared = schema.name.as_ref().and_then(|name| {
+            self.named
+                .iter()
+                .find(|named| named.name == *name && named.shape == shape(schema))
+        });
+        match declared {
+            Some(named) if named.first.docs().as_deref() == Some(&docs) => None,
+            _ => Some(docs),
+        }
+    }
+
+    /// A nested schema: the name of its declaration if it is a named type,
+    /// otherwise written in place
+    fn reference(&mut self, schema: &SchemaType) -> String {
+        let Some(name) = &schema.name else {
+            return self.expression(schema);
+        };
+        if matches!(schema.kind, TypeKind::Ref { .. })
+            || schema.ref_preference() == Some(RefPreference::Inline)
+        {
+            return self.expression(schema);
+        }
+
+        let shape = shape(schema);
+        if let Some(named) = self
+            .named
+            .iter()
+            .find(|named| named.name == *name && named.shape == shape)
+        {
+            return named.ident.clone();
+        }
+
+        // A different type already took the name
+        let base = identifier(name);
+        let ident = (1..)
+            .map(|n| match n {
+                1 => base.clone(),
+                n => format!("{base}{n}"),
+            })
+            .find(|ident| !self.named.iter().any(|named| named.ident == *ident))
+            .unwrap();
+        self.named.push(Named {
+            name: name.clone(),
+            shape,
+            ident: ident.clone(),
+            first: schema.clone(),
+        });
+        self.declare(&ident, schema);
+        ident
+    }
+
+    /// The type expression for `schema`, with nested named types by name
+    fn expression(&mut self, schema: &SchemaType) -> String {
+        match &schema.kind {
+            TypeKind::String => "string".to_string(),
+            TypeKind::Integer(_) | TypeKind::Number(_) => "number".to_string(),
+            TypeKind::Boolean => "boolean".to_string(),
+            TypeKind::Null => "null".to_string(),
+            TypeKind::Array { items } | TypeKind::Set { items, .. } => {
+                array_of(&self.reference(items))
+            }
+            TypeKind::Map { key, value, .. } => {
+                let value = self.reference(value);
+                match &key.kind {
+                    TypeKind::String => format!("Record<string, {}>", value),
+                    // Not every key has to be present
+                    TypeKind::Enum { .. } => {
+                        format!("Partial<Record<{}, {}>>", self.reference(key), value)
+                    }
+                    // Non-string keys are encoded as an array of [key, value] pairs
+                    _ => format!("Array<[{}, {}]>", self.reference(key), value),
+                }
+            }
+            TypeKind::Object { required, .. } => {
+                let fields: Vec<String> = schema
+                    .ordered_properties()
+                    .into_iter()
+                    .map(|(name, property)| {
+                        let optional = !required.iter().any(|r| r == name);
+                        self.field(name, optional, property)
+                    })
+                    .collect();
+                object_literal(&fields)
+            }
+            TypeKind::Enum { variants } => union(variants.iter().map(|v| literal(v)).collect()),
+            TypeKind::TaggedUnion {
+                tag_field,
+                tag_variants,
+                data_fields,
+            } => {
+                let mut names: Vec<&String> = data_fields.keys().collect();
+                names.sort();
+                let members = tag_variants
+                    .iter()
+                    .map(|variant| {
+                        let mut fields =
+                            vec![format!("{}: {}", property_key(tag_field), literal(variant))];
+                        for name in &names {
+                            fields.push(self.field(name, true, &data_fields[*name]));
+                        }
+                        object_literal(&fields)
+                    })
+                    .collect();
+                union(members)
+            }
+            TypeKind::Variant { cases } => {
+                let tagging = schema.tagging();
+                let members = cases
+                    .iter()
+                    .map(|case| self.case(case, tagging.as_ref()))
+                    .collect();
+                union(members)
+            }
+            TypeKind::Result { ok, err } => union(vec![
+                format!("{{ ok: {} }}", self.reference(ok)),
+                format!("{{ error: {} }}", self.reference(err)),
+            ]),
+            TypeKind::Tuple { fields } => {
+                let fields: Vec<String> = fields.iter().map(|f| self.reference(f)).collect();
+                format!("[{}]", fields.join(", "))
+            }
+            TypeKind::Ref { name } => self
+                .named
+                .iter()
+                .find(|named| named.name == *name)
+                .map(|named| named.ident.clone())
+                .unwrap_or_else(|| identifier(name)),
+        }
+    }
+
+    /// `name: type` inside an object literal
+    fn field(&mut self, name: &str, optional: bool, schema: &SchemaType) -> String {
+        format!(
+            "{}{}{}: {}",
+            if schema.read_only() { "readonly " } else { "" },
+            property_key(name),
+            if optional { "?" } else { "" },
+            self.reference(schema)
+        )
+    }
+
+    /// One member of a Variant's union
+    fn case(&mut self, case: &VariantCase, tagging: Option<&Tagging>) -> String {
+        let name = literal(&case.name);
+        match (tagging, &case.data) {
+            (None, None) => name,
+            (None, Some(data)) => object_literal(&[
+                format!("type: {}", name),
+                format!("data: {}", self.reference(data)),
+            ]),
+            (Some(Tagging::Untagged), None) => "null".to_string(),
+            (Some(Tagging::Untagged), Some(data)) => self.reference(data),
+            (Some(Tagging::Internal { tag } | Tagging::Adjacent { tag, .. }), None) => {
+                object_literal(&[format!("{}: {}", property_key(tag), name)])
+            }
+            (Some(Tagging::Adjacent { tag, content }), Some(data)) => object_literal(&[
+                format!("{}: {}", property_key(tag), name),
+                format!("{}: {}", property_key(content), self.reference(data)),
+            ]),
+            (Some(Tagging::Internal { tag }), Some(data)) => {
+                let tag = format!("{}: {}", property_key(tag), name);
+                match &data.kind {
+                    // Anonymous struct payloads share the object with the tag
+                    TypeKind::Object { required, .. } if data.name.is_none() => {
+                        let mut fields = vec![tag];
+                        for (field, property) in data.ordered_properties() {
+                            let optional = !required.iter().any(|r| r == field);
+                            fields.push(self.field(field, optional, property));
+                        }
+                        object_literal(&fields)
+                    }
+                    _ => format!("{} & {}", object_literal(&[tag]), self.reference(data)),
+                }
+            }
+        }
+    }
+}
+
+fn object_literal(fields: &[String]) -> String {
+    if fields.is_empty() {
+        "{}".to_string()
+    } else {
+        format!("{{ {} }}", fields.join("; "))
+    }
+}
+
+fn union(members: Vec<String>) -> String {
+    if members.is_empty() {
+        "never".to_string()
+    } else {
+        members.join(" | ")
+    }
+}
+
+/// `T[]`, or `Array<T>` when `T` isn't a single name
+fn array_of(items: &str) -> String {
+    if items
+        .chars()
+        .all(|c| c.is_alphanumeric() || c == '_' || c == '$')
+    {
+        format!("{}[]", items)
+    } else {
+        format!("Array<{}>", items)
+    }
+}
+
+/// A string literal type
+fn literal(value: &str) -> String {
+    serde_json::to_string(value).expect("strings serialize")
+}
+
+/// A property name, quoted unless it's a valid identifier
+fn property_key(name: &str) -> String {
+    let mut chars = name.chars();
+    let valid = chars
+        .next()
+        .is_some_and(|c| c.is_alphabetic() || c == '_' || c == '$')
+        && chars.all(|c| c.is_alphanumeric() || c == '_' || c == '$');
+    if valid {
+        name.to_string()
+    } else {
+        literal(name)
+    }
+}
+
+/// A type name usable as a TypeScript identifier
+fn identifier(name: &str) -> String {
+    name.chars()
+        .map(|c| {
+            if c.is_alphanumeric() || c == '$' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+/// A TSDoc comment for `docs`, ending in a newline, or nothing
+fn tsdoc(docs: Option<&str>, deprecated: bool, indent: &str) -> String {
+    let mut lines: Vec<String> = docs
+        .into_iter()
+        .flat_map(str::lines)
+        .map(|line| line.replace("*/", "*\\/"))
+        .collect();
+    if deprecated {
+        lines.push("@deprecated".to_string());
+    }
+
+    match lines.as_slice() {
+        [] => String::new(),
+        [line] => format!("{indent}/** {line} */\n"),
+        lines => {
+            let mut comment = format!("{indent}/**\n");
+            for line in lines {
+                if line.is_empty() {
+                    comment.push_str(&format!("{indent} *\n"));
+                } else {
+                    comment.push_str(&format!("{indent} * {line}\n"));
+                }
+            }
+            comment.push_str(&format!("{indent} */\n"));
+            comment
+        }
+    }
+}
+
+/// What makes two occurrences of a named type the same declaration: its
+/// fingerprint without the annotations a field adds to it
+fn shape(schema: &SchemaType) -> String {
+    let mut bare = schema.clone();
+    bare.title = None;
+    bare.description = None;
+    bare.default = None;
+    for key in [
+        schema::DEPRECATED,
+        schema::READ_ONLY,
+        schema::WRITE_ONLY,
+        schema::SINCE,
+        schema::REF_PREFERENCE,
+    ] {
+        bare.extensions.remove(key);
+    }
+    bare.fingerprint()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_interface() {
+        /// A registered user
+        #[derive(Schema)]
+        #[allow(dead_code)]
+        struct User {
+            /// Unique handle
+            #[schema(read_only)]
+            id: String,
+            age: Option<u32>,
+            tags: Vec<String>,
+            #[schema(rename = "display-name")]
+            display_name: String,
+        }
+
+        assert_eq!(
+            to_typescript::<User>(),
+            r#"/** A registered user */
+export interface User {
+  /** Unique handle */
+  readonly id: string;
+  age?: number;
+  tags: string[];
+  "display-name": string;
+}
+"#
+        );
+    }
+
+    #[test]
+    fn test_nested_declarations() {
+        /// Where to send things
+        #[derive(Schema)]
+        #[allow(dead_code)]
+        struct Address {
+            city: String,
+        }
+
+        #[derive(Schema)]
+        #[allow(dead_code)]
+        #[schema(rename_all_variants = "lowercase")]
+        enum Status {
+            Active,
+            Closed,
+        }
+
+        #[derive(Schema)]
+        #[allow(dead_code)]
+        struct Account {
+            home: Address,
+            /// Where invoices go
+            billing: Option<Address>,
+            status: Status,
+            limits: HashMap<Status, u32>,
+            notes: HashMap<String, String>,
+        }
+
+        assert_eq!(
+            to_typescript::<Account>(),
+            r#"export interface Account {
+  home: Address;
+  /** Where invoices go */
+  billing?: Address;
+  status: Status;
+  /** Unordered map/dictionary of key-value pairs */
+  limits: Partial<Record<Status, number>>;
+  /** Unordered map/dictionary of key-value pairs */
+  notes: Record<string, string>;
+}
+
+/** Where to send things */
+export interface Address {
+  city: string;
+}
+
+export type Status = "active" | "closed";
+"#
+        );
+    }
+
+    #[test]
+    fn test_variants() {
+        #[derive(Schema)]
+        #[allow(dead_code)]
+        enum Shape {
+            Empty,
+            Circle { radius: f64 },
+        }
+
+        #[derive(Schema)]
+        #[allow(dead_code)]
+        struct Point {
+            x: f64,
+        }
+
+        #[derive(Schema)]
+        #[allow(dead_code)]
+        #[schema(tag = "kind")]
+        enum Event {
+            Start,
+            Move { x: i32 },
+            Jump(Point),
+        }
+
+        #[derive(Schema)]
+        #[allow(dead_code)]
+        #[schema(tag = "t", content = "c")]
+        enum Message {
+            Ping,
+            Text(String),
+        }
+
+        #[derive(Schema)]
+        #[allow(dead_code)]
+        #[schema(untagged)]
+        enum Id {
+            Number(u64),
+            Name(String),
+        }
+
+        assert_eq!(
+            to_typescript::<Shape>(),
+            "export type Shape = \"Empty\" | { type: \"Circle\"; data: { radius: number } };\n"
+        );
+        assert_eq!(
+            to_typescript::<Event>(),
+            "export type Event = { kind: \"Start\" } | { kind: \"Move\"; x: number } | { kind: \"Jump\" } & Point;\n\n\
+             export interface Point {\n  x: number;\n}\n"
+        );
+        assert_eq!(
+            to_typescript::<Message>(),
+            "export type Message = { t: \"Ping\" } | { t: \"Text\"; c: string };\n"
+        );
+        assert_eq!(to_typescript::<Id>(), "export type Id = number | string;\n");
+
+        // `|` binds looser than `[]`, so inline unions use the generic form
+        #[derive(Schema)]
+        #[allow(dead_code)]
+        struct Batch {
+            outcomes: Vec<Result<u8, String>>,
+        }
+
+        assert_eq!(
+            to_typescript::<Batch>(),
+            "export interface Batch {\n  outcomes: Array<{ ok: number } | { error: string }>;\n}\n"
+        );
+    }
+
+    #[test]
+    fn test_recursion_tuples_and_results() {
+        /// A node in a tree
+        ///
+        /// Leaves have no children.
+        #[derive(Schema)]
+        #[allow(dead_code)]
+        struct Node {
+            children: Vec<Node>,
+            span: (u32, u32),
+            #[deprecated = "use span"]
+            offset: Option<u32>,
+        }
+
+        assert_eq!(
+            to_typescript::<Node>(),
+            r#"/**
+ * A node in a tree
+ *
+ * Leaves have no children.
+ */
+export interface Node {
+  children: Node[];
+  span: [number, number];
+  /**
+   * Deprecated: use span
+   * @deprecated
+   */
+  offset?: number;
+}
+"#
+        );
+
+        let result = SchemaType::new(TypeKind::Result {
+            ok: Box::new(Vec::<String>::schema()),
+            err: Box::new(String::schema()),
+        });
+        assert_eq!(
+            schema_type_to_typescript(&result, Some("Outcome")),
+            "export type Outcome = { ok: string[] } | { error: string };\n"
+        );
+    }
+}
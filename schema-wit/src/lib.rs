@@ -1,4 +1,18 @@
-use schema::{IntegerKind, NumberKind, Schema, SchemaType, TypeKind};
+#[cfg(feature = "parse")]
+mod parse;
+
+#[cfg(feature = "parse")]
+pub use parse::{ParseError, parse};
+
+use schema::case::Case;
+use schema::{GenerateOptions, IntegerKind, NumberKind, Schema, SchemaType, TypeKind};
+
+/// Extension key overriding a type's WIT name, set by `#[schema(wit(name = "..."))]`
+pub const WIT_NAME: &str = "x-wit-name";
+
+/// Extension key overriding a record field's WIT name, set by
+/// `#[schema(wit(name = "..."))]` on the field
+pub const WIT_FIELD_NAME: &str = "x-wit-field-name";
 
 /// Convert a Schema to WIT type definition
 pub fn to_wit_type<T: Schema>() -> String {
@@ -6,8 +20,41 @@ pub fn to_wit_type<T: Schema>() -> String {
     schema_type_to_wit(&schema, T::type_name())
 }
 
+/// Convert a Schema to WIT type definition, bounded by `options`
+pub fn to_wit_type_with_options<T: Schema>(options: &GenerateOptions) -> String {
+    schema_type_to_wit_with_options(&T::schema(), T::type_name(), options)
+}
+
+/// Convert a SchemaType to WIT, bounded by `options`
+pub fn schema_type_to_wit_with_options(
+    schema: &SchemaType,
+    type_name: Option<&str>,
+    options: &GenerateOptions,
+) -> String {
+    schema_type_to_wit(&options.apply(schema), type_name)
+}
+
 /// Convert a SchemaType to WIT, optionally with a type name for records/variants/enums
 pub fn schema_type_to_wit(schema: &SchemaType, type_name: Option<&str>) -> String {
+    // An explicit WIT name is used verbatim; Rust names are kebab-cased
+    let type_name = extension_str(schema, WIT_NAME)
+        .map(str::to_string)
+        .or_else(|| type_name.map(to_kebab_case));
+    let type_name = type_name.as_deref();
+
+    if let Some(own_name) = &schema.name
+        && matches!(
+            schema.kind,
+            TypeKind::Object { .. } | TypeKind::Variant { .. }
+        )
+    {
+        let mut recursive = false;
+        let arena = self_refs_to_indices(schema, own_name, &mut recursive);
+        if recursive {
+            return recursive_to_wit(&arena, type_name.unwrap_or(&to_kebab_case(own_name)));
+        }
+    }
+
     match &schema.kind {
         TypeKind::String => "string".to_string(),
         TypeKind::Boolean => "bool".to_string(),
@@ -32,18 +79,14 @@ pub fn schema_type_to_wit(schema: &SchemaType, type_name: Option<&str>) -> Strin
         TypeKind::Object {
             properties,
             required,
-        } => record_to_wit(
-            properties,
-            required,
+        } => record_to_wit(properties, required, type_name, schema.docs().as_deref()),
+        TypeKind::Enum { variants } => enum_to_wit(
+            variants,
+            schema.extensions.get(schema::ENUM_SINCE),
             type_name,
-            schema.description.as_deref(),
+            schema.docs().as_deref(),
         ),
-        TypeKind::Enum { variants } => {
-            enum_to_wit(variants, type_name, schema.description.as_deref())
-        }
-        TypeKind::Variant { cases } => {
-            variant_to_wit(cases, type_name, schema.description.as_deref())
-        }
+        TypeKind::Variant { cases } => variant_to_wit(cases, type_name, schema.docs().as_deref()),
         TypeKind::Result { ok, err } => {
             let ok_type = schema_type_to_wit(ok, None);
             let err_type = schema_type_to_wit(err, None);
@@ -94,7 +137,7 @@ fn record_to_wit(
     }
 
     let name = type_name.unwrap_or("anonymous-record");
-    output.push_str(&format!("record {} {{\n", to_kebab_case(name)));
+    output.push_str(&format!("record {} {{\n", name));
 
     // Sort fields for deterministic output
     let mut fields: Vec<_> = properties.iter().collect();
@@ -102,11 +145,15 @@ fn record_to_wit(
 
     for (field_name, field_schema) in fields {
         // Add field description if present
-        if let Some(desc) = &field_schema.description {
+        if let Some(desc) = &field_schema.docs() {
             for line in desc.lines() {
                 output.push_str(&format!("    /// {}\n", line));
             }
         }
+        // WIT lists have no constraints of their own, so note them instead
+        if let Some(note) = collection_note(&field_schema.constraints) {
+            output.push_str(&format!("    /// {}\n", note));
+        }
 
         let field_type = schema_type_to_wit(field_schema, None);
         let is_optional = !required.contains(field_name);
@@ -117,18 +164,42 @@ fn record_to_wit(
             field_type
         };
 
-        output.push_str(&format!(
-            "    {}: {},\n",
-            to_kebab_case(field_name),
-            final_type
-        ));
+        if let Some(since) = field_schema.since() {
+            output.push_str(&format!("    @since(version = {})\n", since));
+        }
+
+        let wit_field_name = extension_str(field_schema, WIT_FIELD_NAME)
+            .map(str::to_string)
+            .unwrap_or_else(|| to_kebab_case(field_name));
+        output.push_str(&format!("    {}: {},\n", wit_field_name, final_type));
     }
 
     output.push('}');
     output
 }
 
-fn enum_to_wit(variants: &[String], type_name: Option<&str>, description: Option<&str>) -> String {
+/// Item count bounds and uniqueness as a doc line, e.g. `Items: 1 to 5, unique`
+fn collection_note(constraints: &schema::Constraints) -> Option<String> {
+    let mut parts = vec![];
+    match (constraints.min_items, constraints.max_items) {
+        (Some(min), Some(max)) if min == max => parts.push(format!("exactly {}", min)),
+        (Some(min), Some(max)) => parts.push(format!("{} to {}", min, max)),
+        (Some(min), None) => parts.push(format!("at least {}", min)),
+        (None, Some(max)) => parts.push(format!("at most {}", max)),
+        (None, None) => {}
+    }
+    if constraints.unique_items {
+        parts.push("unique".to_string());
+    }
+    (!parts.is_empty()).then(|| format!("Items: {}", parts.join(", ")))
+}
+
+fn enum_to_wit(
+    variants: &[String],
+    since: Option<&serde_json::Value>,
+    type_name: Option<&str>,
+    description: Option<&str>,
+) -> String {
     let mut output = String::new();
 
     if let Some(desc) = description {
@@ -138,9 +209,12 @@ fn enum_to_wit(variants: &[String], type_name: Option<&str>, description: Option
     }
 
     let name = type_name.unwrap_or("anonymous-enum");
-    output.push_str(&format!("enum {} {{\n", to_kebab_case(name)));
+    output.push_str(&format!("enum {} {{\n", name));
 
     for variant in variants {
+        if let Some(version) = since.and_then(|s| s.get(variant)).and_then(|v| v.as_str()) {
+            output.push_str(&format!("    @since(version = {})\n", version));
+        }
         output.push_str(&format!("    {},\n", to_kebab_case(variant)));
     }
 
@@ -162,7 +236,7 @@ fn variant_to_wit(
     }
 
     let name = type_name.unwrap_or("anonymous-variant");
-    output.push_str(&format!("variant {} {{\n", to_kebab_case(name)));
+    output.push_str(&format!("variant {} {{\n", name));
 
     for case in cases {
         // Add case description if present
@@ -172,6 +246,10 @@ fn variant_to_wit(
             }
         }
 
+        if let Some(since) = case.since() {
+            output.push_str(&format!("    @since(version = {})\n", since));
+        }
+
         match &case.data {
             None => {
                 // Unit variant
@@ -202,25 +280,107 @@ fn tuple_to_wit(fields: &[SchemaType]) -> String {
     format!("tuple<{}>", field_types.join(", "))
 }
 
-/// Convert snake_case or PascalCase to kebab-case
-fn to_kebab_case(s: &str) -> String {
-    let mut result = String::new();
-    let mut chars = s.chars().peekable();
+/// WIT has no recursive types, so a type that contains itself is emitted in
+/// arena form: self-references become `u32` indices into the `nodes` list of
+/// a companion `<name>-tree` record
+fn recursive_to_wit(arena: &SchemaType, name: &str) -> String {
+    let note = format!(
+        "Recursive: `u32` references index into `{}-tree.nodes`",
+        name
+    );
+    let docs = match arena.docs() {
+        Some(docs) => format!("{}\n\n{}", docs, note),
+        None => note,
+    };
+
+    let definition = match &arena.kind {
+        TypeKind::Object {
+            properties,
+            required,
+        } => record_to_wit(properties, required, Some(name), Some(&docs)),
+        TypeKind::Variant { cases } => variant_to_wit(cases, Some(name), Some(&docs)),
+        _ => unreachable!("only records and variants are emitted in arena form"),
+    };
+
+    format!(
+        "{}\n\n/// Arena holding a tree of `{}` values\nrecord {}-tree {{\n    nodes: list<{}>,\n    root: u32,\n}}",
+        definition, name, name, name
+    )
+}
 
-    while let Some(ch) = chars.next() {
-        if ch.is_uppercase() {
-            if !result.is_empty() {
-                result.push('-');
-            }
-            result.push(ch.to_lowercase().next().unwrap());
-        } else if ch == '_' {
-            result.push('-');
-        } else {
-            result.push(ch);
+/// Replace `Ref`s to `own_name` with `u32` node indices, noting whether any
+/// were found
+fn self_refs_to_indices(schema: &SchemaType, own_name: &str, found: &mut bool) -> SchemaType {
+    let mut child = |s: &SchemaType| self_refs_to_indices(s, own_name, found);
+
+    let kind = match &schema.kind {
+        TypeKind::Ref { name } if name == own_name => {
+            *found = true;
+            return SchemaType {
+                kind: TypeKind::Integer(IntegerKind::U32),
+                ..schema.clone()
+            };
         }
+        TypeKind::Object {
+            properties,
+            required,
+        } => TypeKind::Object {
+            properties: properties
+                .iter()
+                .map(|(k, v)| (k.clone(), child(v)))
+                .collect(),
+            required: required.clone(),
+        },
+        TypeKind::Array { items } => TypeKind::Array {
+            items: Box::new(child(items)),
+        },
+        TypeKind::Set { items, ordered } => TypeKind::Set {
+            items: Box::new(child(items)),
+            ordered: *ordered,
+        },
+        TypeKind::Map {
+            key,
+            value,
+            ordered,
+        } => TypeKind::Map {
+            key: Box::new(child(key)),
+            value: Box::new(child(value)),
+            ordered: *ordered,
+        },
+        TypeKind::Variant { cases } => TypeKind::Variant {
+            cases: cases
+                .iter()
+                .map(|case| schema::VariantCase {
+                    data: case.data.as_ref().map(&mut child),
+                    ..case.clone()
+                })
+                .collect(),
+        },
+        TypeKind::Result { ok, err } => TypeKind::Result {
+            ok: Box::new(child(ok)),
+            err: Box::new(child(err)),
+        },
+        TypeKind::Tuple { fields } => TypeKind::Tuple {
+            fields: fields.iter().map(child).collect(),
+        },
+        other => other.clone(),
+    };
+
+    SchemaType {
+        kind,
+        ..schema.clone()
     }
+}
+
+fn extension_str<'a>(schema: &'a SchemaType, key: &str) -> Option<&'a str> {
+    schema.extensions.get(key).and_then(|v| v.as_str())
+}
 
-    result
+/// Convert snake_case or PascalCase to kebab-case
+///
+/// Runs of capitals are one word, so `HTTPServer` becomes `http-server`.
+fn to_kebab_case(s: &str) -> String {
+    Case::Kebab.apply(s)
 }
 
 #[cfg(test)]
@@ -233,6 +393,10 @@ mod tests {
         assert_eq!(to_kebab_case("PascalCase"), "pascal-case");
         assert_eq!(to_kebab_case("camelCase"), "camel-case");
         assert_eq!(to_kebab_case("already-kebab"), "already-kebab");
+        assert_eq!(to_kebab_case("HTTPServer"), "http-server");
+        assert_eq!(to_kebab_case("getHTTPResponse"), "get-http-response");
+        assert_eq!(to_kebab_case("userID"), "user-id");
+        assert_eq!(to_kebab_case("Utf8Value"), "utf8-value");
     }
 
     #[test]
@@ -265,6 +429,7 @@ mod tests {
     #[test]
     fn test_record() {
         #[derive(schema::Schema)]
+        #[allow(dead_code)]
         struct Person {
             name: String,
             age: u32,
@@ -279,6 +444,7 @@ mod tests {
     #[test]
     fn test_record_with_optional() {
         #[derive(schema::Schema)]
+        #[allow(dead_code)]
         struct User {
             id: String,
             email: Option<String>,
@@ -292,6 +458,7 @@ mod tests {
     #[test]
     fn test_simple_enum() {
         #[derive(schema::Schema)]
+        #[allow(dead_code)]
         enum Status {
             Active,
             Inactive,
@@ -308,6 +475,7 @@ mod tests {
     #[test]
     fn test_variant() {
         #[derive(schema::Schema)]
+        #[allow(dead_code)]
         enum Message {
             Text { content: String },
             Image { url: String, width: u32 },
@@ -322,6 +490,7 @@ mod tests {
     #[test]
     fn test_variant_unit() {
         #[derive(schema::Schema)]
+        #[allow(dead_code)]
         enum Event {
             Start,
             Stop,
@@ -339,12 +508,14 @@ mod tests {
     #[test]
     fn test_nested() {
         #[derive(schema::Schema)]
+        #[allow(dead_code)]
         struct Address {
             street: String,
             city: String,
         }
 
         #[derive(schema::Schema)]
+        #[allow(dead_code)]
         struct Person {
             name: String,
             address: Address,
@@ -385,4 +556,107 @@ mod tests {
         // WIT has no map type, should be list of tuples
         assert_eq!(wit, "list<tuple<u32, string>>");
     }
+
+    #[test]
+    fn test_max_depth_emits_named_reference() {
+        #[derive(Schema)]
+        #[allow(dead_code)]
+        struct Point {
+            x: i32,
+        }
+
+        #[derive(Schema)]
+        #[allow(dead_code)]
+        struct Shape {
+            origin: Point,
+        }
+
+        let wit = to_wit_type_with_options::<Shape>(&GenerateOptions::new().max_depth(1));
+        assert!(wit.contains("origin: point,"));
+    }
+
+    #[test]
+    fn test_wit_name_overrides() {
+        #[derive(Schema)]
+        #[schema(wit(name = "web-server"))]
+        #[allow(dead_code)]
+        struct HTTPServer {
+            #[schema(wit(name = "listen-port"))]
+            port: u32,
+            max_connections: u32,
+        }
+
+        let wit = to_wit_type::<HTTPServer>();
+        assert!(wit.contains("record web-server {"));
+        assert!(wit.contains("listen-port: u32,"));
+        assert!(wit.contains("max-connections: u32,"));
+    }
+
+    #[test]
+    fn test_recursive_variant_arena_form() {
+        /// Arithmetic expression
+        #[derive(Schema)]
+        #[allow(dead_code)]
+        enum Expr {
+            Lit(i64),
+            Add(Box<Expr>, Box<Expr>),
+        }
+
+        let wit = to_wit_type::<Expr>();
+        assert_eq!(
+            wit,
+            "/// Arithmetic expression\n\
+             /// \n\
+             /// Recursive: `u32` references index into `expr-tree.nodes`\n\
+             variant expr {\n    lit(s64),\n    add(tuple<u32, u32>),\n}\n\n\
+             /// Arena holding a tree of `expr` values\n\
+             record expr-tree {\n    nodes: list<expr>,\n    root: u32,\n}"
+        );
+    }
+
+    #[test]
+    fn test_since_gates() {
+        #[derive(Schema)]
+        #[allow(dead_code)]
+        enum Shape {
+            Circle(f64),
+            #[schema(since = "1.4.0")]
+            Square(f64),
+        }
+
+        #[derive(Schema)]
+        #[allow(dead_code)]
+        struct Config {
+            name: String,
+            #[schema(since = "0.2.0")]
+            retries: u32,
+        }
+
+        let wit = to_wit_type::<Shape>();
+        assert!(wit.contains("    circle(f64),\n    @since(version = 1.4.0)\n    square(f64),"));
+
+        let wit = to_wit_type::<Config>();
+        assert!(wit.contains("    @since(version = 0.2.0)\n    retries: u32,"));
+    }
+
+    #[test]
+    fn test_collection_constraints_as_docs() {
+        #[derive(Schema)]
+        #[allow(dead_code)]
+        struct Poll {
+            /// Answer options
+            #[schema(min_items = 2, max_items = 8, unique_items)]
+            options: Vec<String>,
+            #[schema(min_items = 1)]
+            voters: Vec<String>,
+            title: String,
+        }
+
+        let wit = to_wit_type::<Poll>();
+        assert!(wit.contains(
+            "    /// Answer options\n    /// Items: 2 to 8, unique\n    options: list<string>,"
+        ));
+        assert!(wit.contains("    /// Items: at least 1\n    voters: list<string>,"));
+        assert!(wit.contains("options: list<string>,\n    title: string,"));
+    }
 }
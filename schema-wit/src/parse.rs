@@ -0,0 +1,460 @@
+//! Reading WIT packages back into SchemaTypes
+//!
+//! Every record, variant, enum and flags type defined in the package's
+//! interfaces and worlds becomes a named SchemaType, in declaration order.
+//! Type names and cases are PascalCased and record fields snake_cased, as a
+//! derived Rust type would have them; when that doesn't kebab-case back to
+//! the WIT name, [`WIT_NAME`] or [`WIT_FIELD_NAME`] keeps the original.
+//!
+//! [`WIT_NAME`]: crate::WIT_NAME
+//! [`WIT_FIELD_NAME`]: crate::WIT_FIELD_NAME
+
+use crate::{WIT_FIELD_NAME, WIT_NAME, to_kebab_case};
+use schema::case::Case;
+use schema::{IntegerKind, NumberKind, SchemaType, TypeKind, VariantCase};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fmt;
+use wit_parser::{Resolve, Stability, Type, TypeDefKind, TypeId, WorldItem};
+
+/// Why a WIT package has no SchemaTypes
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseError {
+    /// The source isn't a valid WIT package
+    Wit(String),
+    /// A type SchemaType has no representation for, such as a resource
+    /// handle, inside the named type
+    Unsupported { type_name: String, reason: String },
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::Wit(message) => write!(f, "invalid WIT: {}", message),
+            ParseError::Unsupported { type_name, reason } => {
+                write!(f, "unsupported type in {:?}: {}", type_name, reason)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// Read the types defined in a WIT package
+///
+/// Resources, functions and plain aliases are skipped; a record or variant
+/// holding a resource handle, future or stream is an error.
+pub fn parse(source: &str) -> Result<Vec<SchemaType>, ParseError> {
+    let mut resolve = Resolve::new();
+    let package = resolve
+        .push_source("input.wit", source)
+        .map_err(|e| ParseError::Wit(format!("{:#}", e)))?;
+
+    let package = &resolve.packages[package];
+    let worlds = package.worlds.values().flat_map(|world| {
+        let world = &resolve.worlds[*world];
+        world.imports.values().chain(world.exports.values())
+    });
+    let ids: Vec<TypeId> = package
+        .interfaces
+        .values()
+        .flat_map(|interface| resolve.interfaces[*interface].types.values().copied())
+        .chain(worlds.filter_map(|item| match item {
+            WorldItem::Type { id, .. } => Some(*id),
+            _ => None,
+        }))
+        .collect();
+
+    let mut parser = Parser {
+        resolve: &resolve,
+        types: HashMap::new(),
+    };
+    let mut schemas = vec![];
+    for id in ids {
+        let definition = &resolve.types[id];
+        if !matches!(
+            definition.kind,
+            TypeDefKind::Record(_)
+                | TypeDefKind::Variant(_)
+                | TypeDefKind::Enum(_)
+                | TypeDefKind::Flags(_)
+        ) {
+            continue;
+        }
+        schemas.push(parser.definition(id)?);
+    }
+    Ok(schemas)
+}
+
+struct Parser<'a> {
+    resolve: &'a Resolve,
+    /// Definitions read so far; WIT types are acyclic, so each is read once
+    types: HashMap<TypeId, SchemaType>,
+}
+
+impl Parser<'_> {
+    fn ty(&mut self, ty: &Type, within: &str) -> Result<SchemaType, ParseError> {
+        let kind = match ty {
+            Type::Bool => TypeKind::Boolean,
+            Type::U8 => TypeKind::Integer(IntegerKind::U8),
+            Type::U16 => return Ok(bounded(IntegerKind::U32, 0.0, u16::MAX as f64)),
+            Type::U32 => TypeKind::Integer(IntegerKind::U32),
+            Type::U64 => TypeKind::Integer(IntegerKind::U64),
+            Type::S8 => return Ok(bounded(IntegerKind::I32, i8::MIN as f64, i8::MAX as f64)),
+            Type::S16 => return Ok(bounded(IntegerKind::I32, i16::MIN as f64, i16::MAX as f64)),
+            Type::S32 => TypeKind::Integer(IntegerKind::I32),
+            Type::S64 => TypeKind::Integer(IntegerKind::I64),
+            Type::F32 => TypeKind::Number(NumberKind::F32),
+            Type::F64 => TypeKind::Number(NumberKind::F64),
+            Type::String => TypeKind::String,
+            // A single Unicode scalar value
+            Type::Char => {
+                let mut schema = SchemaType::new(TypeKind::String);
+                schema.constraints.min_length = Some(1);
+                schema.constraints.max_length = Some(1);
+                return Ok(schema);
+            }
+            Type::ErrorContext => return Err(unsupported(within, "error-context")),
+            Type::Id(id) => return self.definition_within(*id, within),
+        };
+        Ok(SchemaType::new(kind))
+    }
+
+    fn definition(&mut self, id: TypeId) -> Result<SchemaType, ParseError> {
+        let name = self.resolve.types[id].name.clone().unwrap_or_default();
+        self.definition_within(id, &name)
+    }
+
+    /// The type `id` refers to; anonymous ones report errors against the
+    /// named type they're `within`
+    fn definition_within(&mut self, id: TypeId, within: &str) -> Result<SchemaType, ParseError> {
+        if let Some(schema) = self.types.get(&id) {
+            return Ok(schema.clone());
+        }
+
+        let definition = &self.resolve.types[id];
+        let within = definition.name.as_deref().unwrap_or(within).to_string();
+        let within = within.as_str();
+        let mut schema = match &definition.kind {
+            TypeDefKind::Record(record) => {
+                let mut properties = HashMap::new();
+                let mut required = vec![];
+                let mut order = vec![];
+                for field in &record.fields {
+                    let name = Case::Snake.apply(&field.name);
+                    // `option<T>` fields are the optional ones, as `Option<T>` is
+                    let (ty, optional) = match optional(self.resolve, &field.ty) {
+                        Some(inner) => (inner, true),
+                        None => (field.ty, false),
+                    };
+                    let mut schema = self.ty(&ty, within)?;
+                    schema.description = field.docs.contents.clone();
+                    if to_kebab_case(&name) != field.name {
+                        schema
+                            .extensions
+                            .insert(WIT_FIELD_NAME.to_string(), Value::from(field.name.as_str()));
+                    }
+                    if !optional {
+                        required.push(name.clone());
+                    }
+                    order.push(name.clone());
+                    properties.insert(name, schema);
+                }
+                let mut schema = SchemaType::new(TypeKind::Object {
+                    properties,
+                    required,
+                });
+                schema.property_order = order;
+                schema
+            }
+            TypeDefKind::Variant(variant) => {
+                let cases = variant
+                    .cases
+                    .iter()
+                    .map(|case| {
+                        Ok(VariantCase {
+                            name: Case::Pascal.apply(&case.name),
+                            data: case.ty.as_ref().map(|ty| self.ty(ty, within)).transpose()?,
+                            description: case.docs.contents.clone(),
+                            ..VariantCase::default()
+                        })
+                    })
+                    .collect::<Result<_, ParseError>>()?;
+                SchemaType::new(TypeKind::Variant { cases })
+            }
+            TypeDefKind::Enum(enumeration) => SchemaType::new(TypeKind::Enum {
+                variants: enumeration
+                    .cases
+                    .iter()
+                    .map(|case| Case::Pascal.apply(&case.name))
+                    .collect(),
+            }),
+            // A set of the flag names that are raised
+            TypeDefKind::Flags(flags) => SchemaType::new(TypeKind::Set {
+                items: Box::new(SchemaType::new(TypeKind::Enum {
+                    variants: flags
+                        .flags
+                        .iter()
+                        .map(|flag| Case::Pascal.apply(&flag.name))
+                        .collect(),
+                })),
+                ordered: false,
+            }),
+            TypeDefKind::Tuple(tuple) => SchemaType::new(TypeKind::Tuple {
+                fields: tuple
+                    .types
+                    .iter()
+                    .map(|ty| self.ty(ty, within))
+                    .collect::<Result<_, _>>()?,
+            }),
+            // Outside a record field there's nowhere to note absence, as with
+            // `Option<T>`
+            TypeDefKind::Option(ty) => self.ty(ty, within)?,
+            TypeDefKind::Result(result) => SchemaType::new(TypeKind::Result {
+                ok: Box::new(self.unit_or(result.ok.as_ref(), within)?),
+                err: Box::new(self.unit_or(result.err.as_ref(), within)?),
+            }),
+            TypeDefKind::List(ty) => SchemaType::new(TypeKind::Array {
+                items: Box::new(self.ty(ty, within)?),
+            }),
+            TypeDefKind::FixedLengthList(ty, length) => {
+                let mut schema = SchemaType::new(TypeKind::Array {
+                    items: Box::new(self.ty(ty, within)?),
+                });
+                schema.constraints.min_items = Some(*length as usize);
+                schema.constraints.max_items = Some(*length as usize);
+                schema
+            }
+            TypeDefKind::Map(key, value) => SchemaType::new(TypeKind::Map {
+                key: Box::new(self.ty(key, within)?),
+                value: Box::new(self.ty(value, within)?),
+                ordered: false,
+            }),
+            // Aliases, including `use`d types, are the type itself
+            TypeDefKind::Type(ty) => {
+                let schema = self.ty(ty, within)?;
+                self.types.insert(id, schema.clone());
+                return Ok(schema);
+            }
+            TypeDefKind::Resource | TypeDefKind::Handle(_) => {
+                return Err(unsupported(within, "resource"));
+            }
+            TypeDefKind::Future(_) => return Err(unsupported(within, "future")),
+            TypeDefKind::Stream(_) => return Err(unsupported(within, "stream")),
+            TypeDefKind::Unknown => return Err(unsupported(within, "unknown type")),
+        };
+
+        if let Some(name) = &definition.name {
+            let rust_name = Case::Pascal.apply(name);
+            if to_kebab_case(&rust_name) != *name {
+                schema
+                    .extensions
+                    .insert(WIT_NAME.to_string(), Value::from(name.as_str()));
+            }
+            schema.name = Some(rust_name);
+            schema.description = definition.docs.contents.clone();
+            if let Stability::Stable {
+                deprecated: Some(version),
+                ..
+            }
+            | Stability::Unstable {
+                deprecated: Some(version),
+                ..
+            } = &definition.stability
+            {
+                schema.deprecate(Some(&format!("since {}", version)));
+            }
+        }
+        self.types.insert(id, schema.clone());
+        Ok(schema)
+    }
+
+    /// A `result` side's type, or `null` when it has none
+    fn unit_or(&mut self, ty: Option<&Type>, within: &str) -> Result<SchemaType, ParseError> {
+        match ty {
+            Some(ty) => self.ty(ty, within),
+            None => Ok(SchemaType::new(TypeKind::Null)),
+        }
+    }
+}
+
+/// The `T` of an `option<T>`, looking through aliases
+fn optional(resolve: &Resolve, ty: &Type) -> Option<Type> {
+    let Type::Id(id) = ty else {
+        return None;
+    };
+    match &resolve.types[*id].kind {
+        TypeDefKind::Option(inner) => Some(*inner),
+        TypeDefKind::Type(aliased) if resolve.types[*id].name.is_none() => {
+            optional(resolve, aliased)
+        }
+        _ => None,
+    }
+}
+
+/// An integer narrower than its kind, bounded to its range
+fn bounded(kind: IntegerKind, minimum: f64, maximum: f64) -> SchemaType {
+    let mut schema = SchemaType::new(TypeKind::Integer(kind));
+    schema.constraints.minimum = Some(minimum);
+    schema.constraints.maximum = Some(maximum);
+    schema
+}
+
+fn unsupported(type_name: &str, reason: &str) -> ParseError {
+    ParseError::Unsupported {
+        type_name: type_name.to_string(),
+        reason: reason.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{schema_type_to_wit, to_wit_type};
+    use schema::Schema;
+
+    fn find<'a>(schemas: &'a [SchemaType], name: &str) -> &'a SchemaType {
+        schemas
+            .iter()
+            .find(|schema| schema.name.as_deref() == Some(name))
+            .unwrap_or_else(|| panic!("no type named {}", name))
+    }
+
+    #[test]
+    fn test_reads_package() {
+        let schemas = parse(
+            "package example:shop;
+
+            interface types {
+                /// Where an order ships to
+                record address {
+                    street: string,
+                    zip-code: option<u16>,
+                }
+
+                enum status { pending, shipped }
+
+                flags perks { gift-wrap, express }
+
+                variant payment {
+                    card(string),
+                    /// Paid on delivery
+                    cash,
+                }
+
+                type sku = string;
+
+                record order {
+                    id: u64,
+                    items: list<tuple<sku, u32>>,
+                    ship-to: address,
+                    status: status,
+                    perks: perks,
+                    payment: payment,
+                    total: result<f64, string>,
+                }
+            }",
+        )
+        .unwrap();
+
+        let names: Vec<_> = schemas.iter().filter_map(|s| s.name.as_deref()).collect();
+        assert_eq!(
+            names,
+            ["Address", "Status", "Perks", "Payment", "Order"],
+            "the alias isn't a type of its own"
+        );
+
+        let address = find(&schemas, "Address");
+        assert_eq!(
+            address.description.as_deref(),
+            Some("Where an order ships to")
+        );
+        let TypeKind::Object {
+            properties,
+            required,
+        } = &address.kind
+        else {
+            panic!("expected an object");
+        };
+        assert_eq!(required, &["street"]);
+        assert_eq!(properties["zip_code"].constraints.maximum, Some(65535.0));
+
+        let TypeKind::Variant { cases } = &find(&schemas, "Payment").kind else {
+            panic!("expected a variant");
+        };
+        assert_eq!(cases[0].name, "Card");
+        assert_eq!(cases[1].description.as_deref(), Some("Paid on delivery"));
+
+        let order = find(&schemas, "Order");
+        assert_eq!(
+            order.property_order,
+            [
+                "id", "items", "ship_to", "status", "perks", "payment", "total"
+            ]
+        );
+        let TypeKind::Object { properties, .. } = &order.kind else {
+            panic!("expected an object");
+        };
+        assert_eq!(properties["ship_to"].name.as_deref(), Some("Address"));
+        assert!(matches!(properties["perks"].kind, TypeKind::Set { .. }));
+    }
+
+    #[test]
+    fn test_round_trips_through_wit() {
+        /// A point on the map
+        #[derive(Schema)]
+        #[allow(dead_code)]
+        struct GeoPoint {
+            /// Degrees north
+            lat: f64,
+            lon: f64,
+            label: Option<String>,
+        }
+
+        /// Something to draw
+        #[derive(Schema)]
+        #[allow(dead_code)]
+        enum Shape {
+            /// No area
+            Dot,
+            Circle(f64),
+            Rect(f64, f64),
+        }
+
+        for (wit, name) in [
+            (to_wit_type::<GeoPoint>(), "geo-point"),
+            (to_wit_type::<Shape>(), "shape"),
+        ] {
+            let source = format!("package a:b;\ninterface types {{\n{}\n}}\n", wit);
+            let schemas = parse(&source).unwrap();
+            let schema = &schemas[0];
+            assert_eq!(
+                schema_type_to_wit(schema, schema.name.as_deref()),
+                wit,
+                "{}",
+                name
+            );
+        }
+    }
+
+    #[test]
+    fn test_errors() {
+        assert!(matches!(
+            parse("package a:b;\ninterface types {\n record r { x: nope }\n}"),
+            Err(ParseError::Wit(_))
+        ));
+        assert_eq!(
+            parse(
+                "package a:b;
+                interface types {
+                    resource file;
+                    record handle-holder { file: file }
+                }"
+            ),
+            Err(ParseError::Unsupported {
+                type_name: "handle-holder".to_string(),
+                reason: "resource".to_string()
+            })
+        );
+    }
+}
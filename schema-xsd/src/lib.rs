@@ -0,0 +1,766 @@
+//! XML Schema (XSD) definitions from schemas
+//!
+//! The root becomes a global element, and it and every named type nested in
+//! it a top-level `xs:complexType` (or `xs:simpleType`), each after the ones
+//! it uses. Objects are an `xs:sequence` of elements, variants an
+//! `xs:choice` of one element per case, and enums a restriction with
+//! `xs:enumeration`s. Optional properties are `minOccurs="0"`; arrays, sets
+//! and maps are the element repeated up to `maxOccurs`, with maps repeating
+//! generated key/value `Entry` types. Anonymous types that need a
+//! declaration are named after where they appear (`EventMove`).
+
+use schema::case::Case;
+use schema::{
+    GenerateOptions, IntegerKind, NumberKind, RefPreference, Schema, SchemaType, TypeKind,
+};
+use serde_json::Value;
+use std::collections::BTreeSet;
+
+/// Options for the generated schema document
+#[derive(Debug, Clone, Default)]
+pub struct XsdOptions {
+    /// `targetNamespace` of the declared types and elements
+    pub target_namespace: Option<String>,
+    pub generate: GenerateOptions,
+}
+
+impl XsdOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn target_namespace(mut self, namespace: &str) -> Self {
+        self.target_namespace = Some(namespace.to_string());
+        self
+    }
+
+    /// Bound the schema before it is written
+    pub fn generate(mut self, generate: GenerateOptions) -> Self {
+        self.generate = generate;
+        self
+    }
+}
+
+/// The XSD document for `T` and the named types it contains
+pub fn to_xsd<T: Schema>() -> String {
+    schema_type_to_xsd(&T::schema(), T::type_name())
+}
+
+/// The XSD document for `T` and the named types it contains, with `options`
+pub fn to_xsd_with_options<T: Schema>(options: &XsdOptions) -> String {
+    schema_type_to_xsd_with_options(&T::schema(), T::type_name(), options)
+}
+
+/// The XSD document for a SchemaType, whose root element and type are named
+/// `type_name` (or the schema's own name, or `Root`)
+pub fn schema_type_to_xsd(schema: &SchemaType, type_name: Option<&str>) -> String {
+    schema_type_to_xsd_with_options(schema, type_name, &XsdOptions::default())
+}
+
+/// The XSD document for a SchemaType, with `options`
+pub fn schema_type_to_xsd_with_options(
+    schema: &SchemaType,
+    type_name: Option<&str>,
+    options: &XsdOptions,
+) -> String {
+    let schema = options.generate.apply(schema);
+    let mut module = Module {
+        prefix: match options.target_namespace {
+            Some(_) => "tns:",
+            None => "",
+        },
+        ..Default::default()
+    };
+    let ident = module.unique(&name(
+        type_name.or(schema.name.as_deref()).unwrap_or("Root"),
+    ));
+    if let Some(name) = &schema.name {
+        module.named.push(Named {
+            name: name.clone(),
+            shape: shape(&schema),
+            ident: ident.clone(),
+            first: schema.clone(),
+        });
+    }
+    module.declare(&ident, &schema);
+
+    let mut output = "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n".to_string();
+    output.push_str("<xs:schema xmlns:xs=\"http://www.w3.org/2001/XMLSchema\"");
+    if let Some(namespace) = &options.target_namespace {
+        let namespace = escape(namespace);
+        output.push_str(&format!(
+            " targetNamespace=\"{namespace}\" xmlns:tns=\"{namespace}\" elementFormDefault=\"qualified\""
+        ));
+    }
+    output.push_str(">\n");
+    output.push_str(&format!(
+        "  <xs:element name=\"{ident}\" type=\"{}{ident}\"/>\n",
+        module.prefix
+    ));
+    for declaration in &module.output {
+        output.push('\n');
+        output.push_str(declaration);
+    }
+    output.push_str("</xs:schema>\n");
+    output
+}
+
+/// A named type that has a declaration
+struct Named {
+    name: String,
+    /// Fingerprint without use-site annotations
+    shape: String,
+    ident: String,
+    /// The occurrence the declaration was written from
+    first: SchemaType,
+}
+
+/// An element's type and how often it may repeat
+struct Xsd {
+    /// Qualified type name, `xs:string` or `tns:Member`
+    ty: String,
+    /// Whether the type is a simple type, usable as a restriction base
+    simple: bool,
+    /// `minOccurs` and `maxOccurs` (`None` for unbounded) of a repeated
+    /// element
+    repeated: Option<(usize, Option<usize>)>,
+}
+
+impl Xsd {
+    fn simple(ty: impl Into<String>) -> Self {
+        Xsd {
+            ty: ty.into(),
+            simple: true,
+            repeated: None,
+        }
+    }
+
+    fn complex(ty: impl Into<String>) -> Self {
+        Xsd {
+            ty: ty.into(),
+            simple: false,
+            repeated: None,
+        }
+    }
+}
+
+/// One element of a sequence or choice
+struct Element {
+    name: String,
+    xsd: Xsd,
+    optional: bool,
+    default: Option<String>,
+    docs: Option<String>,
+}
+
+impl Element {
+    fn new(name: &str, xsd: Xsd) -> Self {
+        Element {
+            name: name.to_string(),
+            xsd,
+            optional: false,
+            default: None,
+            docs: None,
+        }
+    }
+}
+
+#[derive(Default)]
+struct Module {
+    /// Prefix of declared type names, `tns:` with a target namespace
+    prefix: &'static str,
+    named: Vec<Named>,
+    /// Every type name in use
+    taken: BTreeSet<String>,
+    /// Declarations, each after the ones it uses
+    output: Vec<String>,
+    /// The empty type unit cases and `null` values are written as
+    empty: Option<String>,
+}
+
+impl Module {
+    /// `base`, or `base2`, `base3`, ... if something already has the name
+    fn unique(&mut self, base: &str) -> String {
+        let ident = (1..)
+            .map(|n| match n {
+                1 => base.to_string(),
+                n => format!("{base}{n}"),
+            })
+            .find(|ident| !self.taken.contains(ident))
+            .unwrap();
+        self.taken.insert(ident.clone());
+        ident
+    }
+
+    /// The type `ident` for `schema`: a restriction of a simple type, or a
+    /// complex type with one `item` (repeated) or `value` element
+    fn declare(&mut self, ident: &str, schema: &SchemaType) -> Xsd {
+        if declares(schema) {
+            return self.define(ident, schema);
+        }
+        let xsd = self.expression(schema, &format!("{ident}Value"));
+        if xsd.simple && xsd.repeated.is_none() {
+            let body = format!("    <xs:restriction base=\"{}\"/>\n", xsd.ty);
+            self.simple_type(ident, schema.docs(), body);
+            return Xsd::simple(self.qualified(ident));
+        }
+        let element = match xsd.repeated {
+            Some(_) => "item",
+            None => "value",
+        };
+        self.complex_type(
+            ident,
+            schema.docs(),
+            "sequence",
+            vec![Element::new(element, xsd)],
+        )
+    }
+
+    /// The declaration for a kind that has one of its own
+    fn define(&mut self, ident: &str, schema: &SchemaType) -> Xsd {
+        match &schema.kind {
+            TypeKind::Object { required, .. } => {
+                let mut elements = vec![];
+                for (name, property) in schema.ordered_properties() {
+                    let mut element = self.element(ident, name, property);
+                    element.optional = !required.iter().any(|r| r == name);
+                    elements.push(element);
+                }
+                self.complex_type(ident, schema.docs(), "sequence", elements)
+            }
+            TypeKind::Enum { variants } => {
+                let mut body = "    <xs:restriction base=\"xs:string\">\n".to_string();
+                for variant in variants {
+                    body.push_str(&format!(
+                        "      <xs:enumeration value=\"{}\"/>\n",
+                        escape(variant)
+                    ));
+                }
+                body.push_str("    </xs:restriction>\n");
+                self.simple_type(ident, schema.docs(), body);
+                Xsd::simple(self.qualified(ident))
+            }
+            TypeKind::TaggedUnion {
+                tag_field,
+                tag_variants,
+                data_fields,
+            } => {
+                let tag = self.unique(&format!("{ident}{}", pascal(tag_field)));
+                let tag_schema = SchemaType {
+                    kind: TypeKind::Enum {
+                        variants: tag_variants.clone(),
+                    },
+                    ..Default::default()
+                };
+                let tag = self.define(&tag, &tag_schema);
+
+                let mut elements = vec![Element::new(tag_field, tag)];
+                let mut names: Vec<&String> = data_fields.keys().collect();
+                names.sort();
+                for name in names {
+                    let mut element = self.element(ident, name, &data_fields[name]);
+                    element.optional = true;
+                    elements.push(element);
+                }
+                self.complex_type(ident, schema.docs(), "sequence", elements)
+            }
+            TypeKind::Variant { cases } => {
+                let mut elements = vec![];
+                for case in cases {
+                    let xsd = match &case.data {
+                        Some(data) => {
+                            self.reference(data, &format!("{ident}{}", pascal(&case.name)))
+                        }
+                        None => self.empty(),
+                    };
+                    let mut element = Element::new(&case.name, xsd);
+                    element.docs = case.description.clone();
+                    elements.push(element);
+                }
+                self.complex_type(ident, schema.docs(), "choice", elements)
+            }
+            TypeKind::Result { ok, err } => {
+                let ok = self.reference(ok, &format!("{ident}Ok"));
+                let err = self.reference(err, &format!("{ident}Error"));
+                let elements = vec![Element::new("ok", ok), Element::new("error", err)];
+                self.complex_type(ident, schema.docs(), "choice", elements)
+            }
+            TypeKind::Tuple { fields } => {
+                let mut elements = vec![];
+                for (i, item) in fields.iter().enumerate() {
+                    let xsd = self.reference(item, &format!("{ident}Item{i}"));
+                    elements.push(Element::new(&format!("item{i}"), xsd));
+                }
+                self.complex_type(ident, schema.docs(), "sequence", elements)
+            }
+            _ => unreachable!("declares() covers the kinds defined here"),
+        }
+    }
+
+    /// A nested schema: its declaration's name if it is a named type,
+    /// otherwise written in place, with anonymous declarations named after
+    /// `context`
+    fn reference(&mut self, schema: &SchemaType, context: &str) -> Xsd {
+        let Some(name) = &schema.name else {
+            return self.expression(schema, context);
+        };
+        if matches!(schema.kind, TypeKind::Ref { .. })
+            || schema.ref_preference() == Some(RefPreference::Inline)
+        {
+            return self.expression(schema, context);
+        }
+
+        let shape = shape(schema);
+        if let Some(named) = self
+            .named
+            .iter()
+            .find(|named| named.name == *name && named.shape == shape)
+        {
+            return self.declared(named);
+        }
+
+        // A different type may already have taken the name
+        let ident = self.unique(&self::name(name));
+        self.named.push(Named {
+            name: name.clone(),
+            shape,
+            ident: ident.clone(),
+            first: schema.clone(),
+        });
+        self.declare(&ident, schema)
+    }
+
+    fn expression(&mut self, schema: &SchemaType, context: &str) -> Xsd {
+        if declares(schema) {
+            let ident = self.unique(context);
+            return self.define(&ident, schema);
+        }
+
+        match &schema.kind {
+            TypeKind::String => Xsd::simple(match schema.constraints.format.as_deref() {
+                Some("date-time") => "xs:dateTime",
+                Some("date") => "xs:date",
+                Some("time") => "xs:time",
+                Some("uri") => "xs:anyURI",
+                _ => "xs:string",
+            }),
+            TypeKind::Integer(kind) => Xsd::simple(integer(kind)),
+            TypeKind::Number(NumberKind::F32) => Xsd::simple("xs:float"),
+            TypeKind::Number(NumberKind::F64) => Xsd::simple("xs:double"),
+            TypeKind::Boolean => Xsd::simple("xs:boolean"),
+            TypeKind::Null => self.empty(),
+            TypeKind::Array { items } | TypeKind::Set { items, .. } => {
+                let context = format!("{context}Item");
+                let items = self.reference(items, &context);
+                let mut items = self.single(items, &context);
+                items.repeated = Some((
+                    schema.constraints.min_items.unwrap_or(0),
+                    schema.constraints.max_items,
+                ));
+                items
+            }
+            TypeKind::Map { key, value, .. } => {
+                let entry = self.unique(&format!("{context}Entry"));
+                let key = self.reference(key, &format!("{entry}Key"));
+                let value = self.reference(value, &format!("{entry}Value"));
+                let elements = vec![Element::new("key", key), Element::new("value", value)];
+                let mut entry = self.complex_type(&entry, None, "sequence", elements);
+                entry.repeated = Some((0, None));
+                entry
+            }
+            TypeKind::Ref { name } => match self.named.iter().find(|named| named.name == *name) {
+                Some(named) => self.declared(named),
+                None => Xsd::complex(self.qualified(&self::name(name))),
+            },
+            _ => unreachable!("declares() covers the remaining kinds"),
+        }
+    }
+
+    /// The type of an element holding a declared type
+    fn declared(&self, named: &Named) -> Xsd {
+        let ty = self.qualified(&named.ident);
+        match &named.first.kind {
+            TypeKind::Enum { .. } => Xsd::simple(ty),
+            TypeKind::String | TypeKind::Integer(_) | TypeKind::Number(_) | TypeKind::Boolean => {
+                Xsd::simple(ty)
+            }
+            _ => Xsd::complex(ty),
+        }
+    }
+
+    /// `xsd` as a type that occurs once, wrapping a repeated one in a
+    /// complex type named `context` with an `item` element
+    fn single(&mut self, xsd: Xsd, context: &str) -> Xsd {
+        if xsd.repeated.is_none() {
+            return xsd;
+        }
+        let ident = self.unique(context);
+        self.complex_type(&ident, None, "sequence", vec![Element::new("item", xsd)])
+    }
+
+    /// An element of the type `owner`
+    fn element(&mut self, owner: &str, name: &str, schema: &SchemaType) -> Element {
+        let xsd = self.reference(schema, &format!("{owner}{}", pascal(name)));
+        let mut element = Element::new(name, xsd);
+        element.default = default(schema);
+        element.docs = self.use_site_docs(schema);
+        element
+    }
+
+    /// The empty type standing in for values with no data
+    fn empty(&mut self) -> Xsd {
+        if let Some(empty) = &self.empty {
+            return Xsd::complex(self.qualified(empty));
+        }
+        let empty = self.unique("Empty");
+        self.output
+            .push(format!("  <xs:complexType name=\"{empty}\"/>\n"));
+        self.empty = Some(empty.clone());
+        Xsd::complex(self.qualified(&empty))
+    }
+
+    fn qualified(&self, ident: &str) -> String {
+        format!("{}{}", self.prefix, ident)
+    }
+
+    /// Write `<xs:simpleType name="ident">` around `body`
+    fn simple_type(&mut self, ident: &str, docs: Option<String>, body: String) {
+        self.output.push(format!(
+            "  <xs:simpleType name=\"{ident}\">\n{}{body}  </xs:simpleType>\n",
+            annotation(docs.as_deref(), "    ")
+        ));
+    }
+
+    /// Write `<xs:complexType name="ident">` with `elements` in a `group`,
+    /// `sequence` or `choice`
+    fn complex_type(
+        &mut self,
+        ident: &str,
+        docs: Option<String>,
+        group: &str,
+        elements: Vec<Element>,
+    ) -> Xsd {
+        let mut declaration = format!("  <xs:complexType name=\"{ident}\">\n");
+        declaration.push_str(&annotation(docs.as_deref(), "    "));
+        match elements.is_empty() {
+            true => declaration.push_str(&format!("    <xs:{group}/>\n")),
+            false => {
+                declaration.push_str(&format!("    <xs:{group}>\n"));
+                for element in &elements {
+                    declaration.push_str(&write_element(element));
+                }
+                declaration.push_str(&format!("    </xs:{group}>\n"));
+            }
+        }
+        declaration.push_str("  </xs:complexType>\n");
+        self.output.push(declaration);
+        Xsd::complex(self.qualified(ident))
+    }
+
+    /// The docs for an element, unless its type's declaration already has
+    /// them
+    fn use_site_docs(&self, schema: &SchemaType) -> Option<String> {
+        schema.docs().filter(|docs| {
+            let declared = schema.name.as_ref().and_then(|name| {
+                self.named
+                    .iter()
+                    .find(|named| named.name == *name && named.shape == shape(schema))
+            });
+            declared.is_none_or(|named| named.first.docs().as_ref() != Some(docs))
+        })
+    }
+}
+
+/// `<xs:element .../>` inside a sequence or choice
+fn write_element(element: &Element) -> String {
+    let mut attributes = format!(
+        "name=\"{}\" type=\"{}\"",
+        name(&element.name),
+        element.xsd.ty
+    );
+    let (min, max) = match element.xsd.repeated {
+        Some((min, max)) => (min, max.map(|max| max.to_string())),
+        None => (1, Some("1".to_string())),
+    };
+    let min = if element.optional { 0 } else { min };
+    if min != 1 {
+        attributes.push_str(&format!(" minOccurs=\"{min}\""));
+    }
+    match max.as_deref() {
+        Some("1") => {}
+        Some(max) => attributes.push_str(&format!(" maxOccurs=\"{max}\"")),
+        None => attributes.push_str(" maxOccurs=\"unbounded\""),
+    }
+    if let Some(default) = &element.default {
+        attributes.push_str(&format!(" default=\"{}\"", escape(default)));
+    }
+    match &element.docs {
+        Some(docs) => format!(
+            "      <xs:element {attributes}>\n{}      </xs:element>\n",
+            annotation(Some(docs), "        ")
+        ),
+        None => format!("      <xs:element {attributes}/>\n"),
+    }
+}
+
+/// Whether `schema` is written as a type of its own
+fn declares(schema: &SchemaType) -> bool {
+    matches!(
+        schema.kind,
+        TypeKind::Object { .. }
+            | TypeKind::Enum { .. }
+            | TypeKind::TaggedUnion { .. }
+            | TypeKind::Variant { .. }
+            | TypeKind::Result { .. }
+            | TypeKind::Tuple { .. }
+    )
+}
+
+/// The `default` attribute for an element of a simple type
+fn default(schema: &SchemaType) -> Option<String> {
+    match (&schema.kind, schema.default.as_ref()?) {
+        (TypeKind::Boolean, Value::Bool(b)) => Some(b.to_string()),
+        (TypeKind::Integer(_), Value::Number(n)) if !n.is_f64() => Some(n.to_string()),
+        (TypeKind::Number(_), Value::Number(n)) => Some(n.to_string()),
+        (TypeKind::String, Value::String(s)) => Some(s.clone()),
+        (TypeKind::Enum { variants }, Value::String(s)) if variants.contains(s) => Some(s.clone()),
+        _ => None,
+    }
+}
+
+fn integer(kind: &IntegerKind) -> &'static str {
+    match kind {
+        IntegerKind::I32 => "xs:int",
+        IntegerKind::I64 => "xs:long",
+        IntegerKind::U8 => "xs:unsignedByte",
+        IntegerKind::U32 => "xs:unsignedInt",
+        IntegerKind::U64 | IntegerKind::Usize => "xs:unsignedLong",
+    }
+}
+
+/// `<xs:annotation>` holding `docs` at `indent`, ending in a newline
+fn annotation(docs: Option<&str>, indent: &str) -> String {
+    let Some(docs) = docs else {
+        return String::new();
+    };
+    format!(
+        "{indent}<xs:annotation>\n{indent}  <xs:documentation>{}</xs:documentation>\n{indent}</xs:annotation>\n",
+        escape(docs)
+    )
+}
+
+/// `s` escaped for XML text and attribute values
+fn escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// A type name for part of another's, `per_role` -> `PerRole`
+fn pascal(name: &str) -> String {
+    self::name(&Case::Pascal.apply(name))
+}
+
+/// `name` as an XML name: letters, digits, `_`, `-` and `.`, not starting
+/// with a digit, `-` or `.`
+fn name(name: &str) -> String {
+    let ident: String = name
+        .chars()
+        .map(
+            |c| match c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.') {
+                true => c,
+                false => '_',
+            },
+        )
+        .collect();
+    match ident.starts_with(|c: char| c.is_ascii_digit() || c == '-' || c == '.') {
+        true => format!("_{ident}"),
+        false => ident,
+    }
+}
+
+/// What makes two occurrences of a named type the same declaration: its
+/// fingerprint without the annotations a field adds to it
+fn shape(schema: &SchemaType) -> String {
+    let mut bare = schema.clone();
+    bare.title = None;
+    bare.description = None;
+    bare.default = None;
+    for key in [
+        schema::DEPRECATED,
+        schema::READ_ONLY,
+        schema::WRITE_ONLY,
+        schema::SINCE,
+        schema::REF_PREFERENCE,
+    ] {
+        bare.extensions.remove(key);
+    }
+    bare.fingerprint()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_occurrence_bounds() {
+        #[derive(Schema)]
+        #[allow(dead_code)]
+        enum Rating {
+            Clean,
+            Explicit,
+        }
+
+        fn default_volume() -> u8 {
+            7
+        }
+
+        #[derive(Schema)]
+        #[allow(dead_code)]
+        struct Playlist {
+            title: String,
+            subtitle: Option<String>,
+            #[schema(min_items = 1, max_items = 50)]
+            tracks: Vec<String>,
+            #[schema(min_items = 2)]
+            skipped: Option<Vec<u32>>,
+            #[schema(default = "default_volume")]
+            volume: u8,
+            rating: Option<Rating>,
+        }
+
+        // An optional list may be absent, whatever its min_items
+        assert_eq!(
+            to_xsd::<Playlist>(),
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema">
+  <xs:element name="Playlist" type="Playlist"/>
+
+  <xs:simpleType name="Rating">
+    <xs:restriction base="xs:string">
+      <xs:enumeration value="Clean"/>
+      <xs:enumeration value="Explicit"/>
+    </xs:restriction>
+  </xs:simpleType>
+
+  <xs:complexType name="Playlist">
+    <xs:sequence>
+      <xs:element name="title" type="xs:string"/>
+      <xs:element name="subtitle" type="xs:string" minOccurs="0"/>
+      <xs:element name="tracks" type="xs:string" maxOccurs="50"/>
+      <xs:element name="skipped" type="xs:unsignedInt" minOccurs="0" maxOccurs="unbounded"/>
+      <xs:element name="volume" type="xs:unsignedByte" minOccurs="0" default="7"/>
+      <xs:element name="rating" type="Rating" minOccurs="0"/>
+    </xs:sequence>
+  </xs:complexType>
+</xs:schema>
+"#
+        );
+    }
+
+    #[test]
+    fn test_names_and_escaping() {
+        fn timestamp() -> SchemaType {
+            let mut schema = SchemaType::new(TypeKind::String);
+            schema.constraints.format = Some("date-time".to_string());
+            schema
+        }
+
+        fn default_greeting() -> String {
+            "say \"hi\" & <wave>".to_string()
+        }
+
+        #[derive(Schema)]
+        #[allow(dead_code)]
+        enum Department {
+            #[schema(rename = "R&D")]
+            Research,
+            Sales,
+        }
+
+        /// Rows where a < b
+        #[derive(Schema)]
+        #[allow(dead_code)]
+        struct Badge {
+            #[schema(rename = "2nd line")]
+            second_line: String,
+            #[schema(with = "timestamp")]
+            issued: String,
+            #[schema(default = "default_greeting")]
+            greeting: String,
+            department: Department,
+        }
+
+        let options = XsdOptions::new().target_namespace("urn:acme?a&b");
+        assert_eq!(
+            to_xsd_with_options::<Badge>(&options),
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema" targetNamespace="urn:acme?a&amp;b" xmlns:tns="urn:acme?a&amp;b" elementFormDefault="qualified">
+  <xs:element name="Badge" type="tns:Badge"/>
+
+  <xs:simpleType name="Department">
+    <xs:restriction base="xs:string">
+      <xs:enumeration value="R&amp;D"/>
+      <xs:enumeration value="Sales"/>
+    </xs:restriction>
+  </xs:simpleType>
+
+  <xs:complexType name="Badge">
+    <xs:annotation>
+      <xs:documentation>Rows where a &lt; b</xs:documentation>
+    </xs:annotation>
+    <xs:sequence>
+      <xs:element name="_2nd_line" type="xs:string"/>
+      <xs:element name="issued" type="xs:dateTime"/>
+      <xs:element name="greeting" type="xs:string" minOccurs="0" default="say &quot;hi&quot; &amp; &lt;wave&gt;"/>
+      <xs:element name="department" type="tns:Department"/>
+    </xs:sequence>
+  </xs:complexType>
+</xs:schema>
+"#
+        );
+    }
+
+    #[test]
+    fn test_variants_become_choices() {
+        #[derive(Schema)]
+        #[allow(dead_code)]
+        enum Event {
+            Start,
+            Move { x: i64 },
+            Batch(Vec<Vec<u32>>),
+        }
+
+        assert_eq!(
+            to_xsd::<Event>(),
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema">
+  <xs:element name="Event" type="Event"/>
+
+  <xs:complexType name="Empty"/>
+
+  <xs:complexType name="EventMove">
+    <xs:sequence>
+      <xs:element name="x" type="xs:long"/>
+    </xs:sequence>
+  </xs:complexType>
+
+  <xs:complexType name="EventBatchItem">
+    <xs:sequence>
+      <xs:element name="item" type="xs:unsignedInt" minOccurs="0" maxOccurs="unbounded"/>
+    </xs:sequence>
+  </xs:complexType>
+
+  <xs:complexType name="Event">
+    <xs:choice>
+      <xs:element name="Start" type="Empty"/>
+      <xs:element name="Move" type="EventMove"/>
+      <xs:element name="Batch" type="EventBatchItem" minOccurs="0" maxOccurs="unbounded"/>
+    </xs:choice>
+  </xs:complexType>
+</xs:schema>
+"#
+        );
+    }
+}
@@ -0,0 +1,676 @@
+//! Zod validators from schemas
+//!
+//! The root and every named type nested in it become an exported
+//! `<Name>Schema` constant with a matching `z.infer` type, declared before
+//! anything that uses them. Constraints become Zod checks (`.min`, `.max`,
+//! `.regex`, `.email`, ...), integer kinds carry their Rust bounds, and
+//! variants follow their serde tagging, as `z.discriminatedUnion` where every
+//! case is an object.
+//!
+//! Recursive types reference themselves through `z.lazy`, which TypeScript
+//! can't infer through, so their constants are typed `z.ZodTypeAny`.
+
+use schema::{
+    Constraints, GenerateOptions, IntegerKind, RefPreference, Schema, SchemaType, Tagging,
+    TypeKind, VariantCase,
+};
+
+/// The Zod module for `T` and the named types it contains
+pub fn to_zod<T: Schema>() -> String {
+    schema_type_to_zod(&T::schema(), T::type_name())
+}
+
+/// The Zod module for `T` and the named types it contains, bounded by `options`
+pub fn to_zod_with_options<T: Schema>(options: &GenerateOptions) -> String {
+    schema_type_to_zod_with_options(&T::schema(), T::type_name(), options)
+}
+
+/// The Zod module for a SchemaType, bounded by `options`
+pub fn schema_type_to_zod_with_options(
+    schema: &SchemaType,
+    type_name: Option<&str>,
+    options: &GenerateOptions,
+) -> String {
+    schema_type_to_zod(&options.apply(schema), type_name)
+}
+
+/// The Zod module for a SchemaType, named `type_name` (or the schema's own
+/// name, or `Root`)
+pub fn schema_type_to_zod(schema: &SchemaType, type_name: Option<&str>) -> String {
+    let mut module = Module::default();
+    let ident = identifier(type_name.or(schema.name.as_deref()).unwrap_or("Root"));
+    if let Some(name) = &schema.name {
+        module.named.push(Named {
+            name: name.clone(),
+            shape: shape(schema),
+            ident: ident.clone(),
+            first: schema.clone(),
+            object: false,
+        });
+    }
+    module.declare(&ident, schema);
+
+    let mut output = "import { z } from \"zod\";\n\n".to_string();
+    output.push_str(&module.output.join("\n\n"));
+    output.push('\n');
+    output
+}
+
+/// A named type that has a declaration
+struct Named {
+    name: String,
+    /// Fingerprint without use-site annotations
+    shape: String,
+    ident: String,
+    /// The occurrence the declaration was written from
+    first: SchemaType,
+    /// Whether the declaration is a plain `z.object`, usable as a
+    /// discriminated union member
+    object: bool,
+}
+
+/// A Zod expression, and whether it is a plain `z.object(...)`
+struct Zod {
+    code: String,
+    object: bool,
+}
+
+impl Zod {
+    fn other(code: String) -> Self {
+        Zod {
+            code,
+            object: false,
+        }
+    }
+}
+
+#[derive(Default)]
+struct Module {
+    named: Vec<Named>,
+    /// Declarations, each after the ones it uses
+    output: Vec<String>,
+}
+
+impl Module {
+    fn declare(&mut self, ident: &str, schema: &SchemaType) {
+        let mut zod = self.expression(schema);
+        if let Some(docs) = schema.docs() {
+            zod.code.push_str(&format!(".describe({})", literal(&docs)));
+        }
+        if let Some(named) = self.named.iter_mut().find(|named| named.ident == ident) {
+            named.object = zod.object && !zod.code.contains("z.lazy(");
+        }
+
+        let annotation = if zod.code.contains("z.lazy(") {
+            ": z.ZodTypeAny"
+        } else {
+            ""
+        };
+        self.output.push(format!(
+            "export const {ident}Schema{annotation} = {};\nexport type {ident} = z.infer<typeof {ident}Schema>;",
+            zod.code
+        ));
+    }
+
+    /// A nested schema: its declaration's constant if it is a named type,
+    /// otherwise written in place
+    fn reference(&mut self, schema: &SchemaType) -> Zod {
+        let mut zod = self.reference_type(schema);
+        let docs = schema.docs().filter(|docs| {
+            let declared = schema.name.as_ref().and_then(|name| {
+                self.named
+                    .iter()
+                    .find(|named| named.name == *name && named.shape == shape(schema))
+            });
+            declared.is_none_or(|named| named.first.docs().as_ref() != Some(docs))
+        });
+        if let Some(docs) = docs {
+            zod.code.push_str(&format!(".describe({})", literal(&docs)));
+        }
+        zod
+    }
+
+    fn reference_type(&mut self, schema: &SchemaType) -> Zod {
+        let Some(name) = &schema.name else {
+            return self.expression(schema);
+        };
+        if matches!(schema.kind, TypeKind::Ref { .. })
+            || schema.ref_preference() == Some(RefPreference::Inline)
+        {
+            return self.expression(schema);
+        }
+
+        let shape = shape(schema);
+        if let Some(named) = self
+            .named
+            .iter()
+            .find(|named| named.name == *name && named.shape == shape)
+        {
+            return Zod {
+                code: format!("{}Schema", named.ident),
+                object: named.object,
+            };
+        }
+
+        // A different type already took the name
+        let base = identifier(name);
+        let ident = (1..)
+            .map(|n| match n {
+                1 => base.clone(),
+                n => format!("{base}{n}"),
+            })
+            .find(|ident| !self.named.iter().any(|named| named.ident == *ident))
+            .unwrap();
+        self.named.push(Named {
+            name: name.clone(),
+            shape,
+            ident: ident.clone(),
+            first: schema.clone(),
+            object: false,
+        });
+        self.declare(&ident, schema);
+        let object = self.named.last().is_some_and(|named| named.object);
+        Zod {
+            code: format!("{ident}Schema"),
+            object,
+        }
+    }
+
+    /// The Zod expression for `schema`, with its constraints applied
+    fn expression(&mut self, schema: &SchemaType) -> Zod {
+        let constraints = &schema.constraints;
+        match &schema.kind {
+            TypeKind::String => Zod::other(format!("z.string(){}", string_checks(constraints))),
+            TypeKind::Integer(kind) => Zod::other(format!(
+                "z.number().int(){}",
+                number_checks(constraints, integer_bounds(kind))
+            )),
+            TypeKind::Number(_) => Zod::other(format!(
+                "z.number(){}",
+                number_checks(constraints, (None, None))
+            )),
+            TypeKind::Boolean => Zod::other("z.boolean()".to_string()),
+            TypeKind::Null => Zod::other("z.null()".to_string()),
+            TypeKind::Array { items } => {
+                let items = self.reference(items);
+                Zod::other(format!(
+                    "z.array({}){}",
+                    items.code,
+                    array_checks(constraints, constraints.unique_items)
+                ))
+            }
+            TypeKind::Set { items, .. } => {
+                let items = self.reference(items);
+                Zod::other(format!(
+                    "z.array({}){}",
+                    items.code,
+                    array_checks(constraints, true)
+                ))
+            }
+            TypeKind::Map { key, value, .. } => {
+                let value = self.reference(value).code;
+                Zod::other(match &key.kind {
+                    TypeKind::String => format!("z.record(z.string(), {})", value),
+                    TypeKind::Enum { .. } => {
+                        format!("z.record({}, {})", self.reference(key).code, value)
+                    }
+                    // Non-string keys are encoded as an array of [key, value] pairs
+                    _ => format!(
+                        "z.array(z.tuple([{}, {}]))",
+                        self.reference(key).code,
+                        value
+                    ),
+                })
+            }
+            TypeKind::Object { required, .. } => {
+                let fields: Vec<String> = schema
+                    .ordered_properties()
+                    .into_iter()
+                    .map(|(name, property)| {
+                        let optional = !required.iter().any(|r| r == name);
+                        self.field(name, optional, property)
+                    })
+                    .collect();
+                let strict = if schema.denies_unknown_fields() {
+                    ".strict()"
+                } else {
+                    ""
+                };
+                Zod {
+                    code: format!("{}{}", object(&fields), strict),
+                    object: true,
+                }
+            }
+            TypeKind::Enum { variants } => {
+                let variants: Vec<String> = variants.iter().map(|v| literal(v)).collect();
+                Zod::other(match variants.len() {
+                    0 => "z.never()".to_string(),
+                    _ => format!("z.enum([{}])", variants.join(", ")),
+                })
+            }
+            TypeKind::TaggedUnion {
+                tag_field,
+                tag_variants,
+                data_fields,
+            } => {
+                let mut names: Vec<&String> = data_fields.keys().collect();
+                names.sort();
+                let members = tag_variants
+                    .iter()
+                    .map(|variant| {
+                        let mut fields = vec![tag_field_code(tag_field, variant)];
+                        for name in &names {
+                            fields.push(self.field(name, true, &data_fields[*name]));
+                        }
+                        Zod {
+                            code: object(&fields),
+                            object: true,
+                        }
+                    })
+                    .collect();
+                union(members, Some(tag_field))
+            }
+            TypeKind::Variant { cases } => {
+                let tagging = schema.tagging();
+                let members = cases
+                    .iter()
+                    .map(|case| self.case(case, tagging.as_ref()))
+                    .collect();
+                let discriminator = match &tagging {
+                    None => Some("type"),
+                    Some(tagging) => tagging.tag(),
+                };
+                union(members, discriminator)
+            }
+            TypeKind::Result { ok, err } => {
+                let ok = self.reference(ok).code;
+                let err = self.reference(err).code;
+                Zod::other(format!(
+                    "z.union([z.object({{ ok: {} }}), z.object({{ error: {} }})])",
+                    ok, err
+                ))
+            }
+            TypeKind::Tuple { fields } => {
+                let fields: Vec<String> = fields.iter().map(|f| self.reference(f).code).collect();
+                Zod::other(format!("z.tuple([{}])", fields.join(", ")))
+            }
+            TypeKind::Ref { name } => {
+                let ident = self
+                    .named
+                    .iter()
+                    .find(|named| named.name == *name)
+                    .map(|named| named.ident.clone())
+                    .unwrap_or_else(|| identifier(name));
+                Zod::other(format!("z.lazy(() => {}Schema)", ident))
+            }
+        }
+    }
+
+    /// `name: schema` inside a `z.object`
+    fn field(&mut self, name: &str, optional: bool, schema: &SchemaType) -> String {
+        let mut code = self.reference(schema).code;
+        match &schema.default {
+            // serde fills in the default, so parsing does too
+            Some(default) => code.push_str(&format!(".default({})", default)),
+            None if optional => code.push_str(".optional()"),
+            None => {}
+        }
+        format!("{}: {}", property_key(name), code)
+    }
+
+    /// One member of a Variant's union
+    fn case(&mut self, case: &VariantCase, tagging: Option<&Tagging>) -> Zod {
+        let tagged = |tag: &str, fields: Vec<String>| {
+            let mut all = vec![tag_field_code(tag, &case.name)];
+            all.extend(fields);
+            Zod {
+                code: object(&all),
+                object: true,
+            }
+        };
+        match (tagging, &case.data) {
+            (None, None) => Zod::other(format!("z.literal({})", literal(&case.name))),
+            (None, Some(data)) => {
+                let data = self.reference(data).code;
+                tagged("type", vec![format!("data: {}", data)])
+            }
+            (Some(Tagging::Untagged), None) => Zod::other("z.null()".to_string()),
+            (Some(Tagging::Untagged), Some(data)) => self.reference(data),
+            (Some(Tagging::Internal { tag } | Tagging::Adjacent { tag, .. }), None) => {
+                tagged(tag, vec![])
+            }
+            (Some(Tagging::Adjacent { tag, content }), Some(data)) => {
+                let data = self.reference(data).code;
+                tagged(tag, vec![format!("{}: {}", property_key(content), data)])
+            }
+            (Some(Tagging::Internal { tag }), Some(data)) => match &data.kind {
+                // Anonymous struct payloads share the object with the tag
+                TypeKind::Object { required, .. } if data.name.is_none() => {
+                    let fields = data
+                        .ordered_properties()
+                        .into_iter()
+                        .map(|(field, property)| {
+                            let optional = !required.iter().any(|r| r == field);
+                            self.field(field, optional, property)
+                        })
+                        .collect();
+                    tagged(tag, fields)
+                }
+                _ => {
+                    let payload = self.reference(data);
+                    let tag = tag_field_code(tag, &case.name);
+                    if payload.object {
+                        Zod {
+                            code: format!("{}.extend({{ {} }})", payload.code, tag),
+                            object: true,
+                        }
+                    } else {
+                        Zod::other(format!(
+                            "z.intersection(z.object({{ {} }}), {})",
+                            tag, payload.code
+                        ))
+                    }
+                }
+            },
+        }
+    }
+}
+
+/// `z.discriminatedUnion` when every member is an object sharing
+/// `discriminator`, `z.union` otherwise
+fn union(members: Vec<Zod>, discriminator: Option<&str>) -> Zod {
+    let code = match (members.len(), discriminator) {
+        (0, _) => "z.never()".to_string(),
+        (1, _) => members[0].code.clone(),
+        (_, Some(tag)) if members.iter().all(|member| member.object) => format!(
+            "z.discriminatedUnion({}, [{}])",
+            literal(tag),
+            members
+                .iter()
+                .map(|member| member.code.as_str())
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+        _ => format!(
+            "z.union([{}])",
+            members
+                .iter()
+                .map(|member| member.code.as_str())
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+    };
+    Zod::other(code)
+}
+
+fn object(fields: &[String]) -> String {
+    if fields.is_empty() {
+        "z.object({})".to_string()
+    } else {
+        format!("z.object({{ {} }})", fields.join(", "))
+    }
+}
+
+fn tag_field_code(tag: &str, name: &str) -> String {
+    format!("{}: z.literal({})", property_key(tag), literal(name))
+}
+
+fn string_checks(constraints: &Constraints) -> String {
+    let mut checks = String::new();
+    if let Some(min) = constraints.min_length {
+        checks.push_str(&format!(".min({})", min));
+    }
+    if let Some(max) = constraints.max_length {
+        checks.push_str(&format!(".max({})", max));
+    }
+    if let Some(pattern) = &constraints.pattern {
+        checks.push_str(&format!(".regex(new RegExp({}))", literal(pattern)));
+    }
+    match constraints.format.as_deref() {
+        Some("email") => checks.push_str(".email()"),
+        Some("uri" | "url") => checks.push_str(".url()"),
+        Some("uuid") => checks.push_str(".uuid()"),
+        Some("date-time") => checks.push_str(".datetime()"),
+        Some("date") => checks.push_str(".date()"),
+        Some("ipv4") => checks.push_str(".ip({ version: \"v4\" })"),
+        Some("ipv6") => checks.push_str(".ip({ version: \"v6\" })"),
+        _ => {}
+    }
+    checks
+}
+
+/// The integer kind's range, where JavaScript numbers hold it exactly
+fn integer_bounds(kind: &IntegerKind) -> (Option<f64>, Option<f64>) {
+    const SAFE: i128 = (1 << 53) - 1;
+    let bound = |n: i128| (n.abs() <= SAFE).then_some(n as f64);
+    (bound(kind.min()), bound(kind.max()))
+}
+
+/// `.min`/`.max` for the tighter of the kind's bounds and the constraints
+fn number_checks(
+    constraints: &Constraints,
+    (kind_min, kind_max): (Option<f64>, Option<f64>),
+) -> String {
+    let min = match (kind_min, constraints.minimum) {
+        (Some(a), Some(b)) => Some(a.max(b)),
+        (a, b) => a.or(b),
+    };
+    let max = match (kind_max, constraints.maximum) {
+        (Some(a), Some(b)) => Some(a.min(b)),
+        (a, b) => a.or(b),
+    };
+    let mut checks = String::new();
+    if let Some(min) = min {
+        checks.push_str(&format!(".min({})", number(min)));
+    }
+    if let Some(max) = max {
+        checks.push_str(&format!(".max({})", number(max)));
+    }
+    checks
+}
+
+fn array_checks(constraints: &Constraints, unique: bool) -> String {
+    let mut checks = String::new();
+    if let Some(min) = constraints.min_items {
+        checks.push_str(&format!(".min({})", min));
+    }
+    if let Some(max) = constraints.max_items {
+        checks.push_str(&format!(".max({})", max));
+    }
+    if unique {
+        checks.push_str(
+            ".refine((items) => new Set(items.map((item) => JSON.stringify(item))).size === items.length, { message: \"Items must be unique\" })",
+        );
+    }
+    checks
+}
+
+/// Whole numbers print without a fraction
+fn number(n: f64) -> String {
+    if n.fract() == 0.0 {
+        format!("{}", n as i64)
+    } else {
+        n.to_string()
+    }
+}
+
+/// A JavaScript string literal
+fn literal(value: &str) -> String {
+    serde_json::to_string(value).expect("strings serialize")
+}
+
+/// A property name, quoted unless it's a valid identifier
+fn property_key(name: &str) -> String {
+    let mut chars = name.chars();
+    let valid = chars
+        .next()
+        .is_some_and(|c| c.is_alphabetic() || c == '_' || c == '$')
+        && chars.all(|c| c.is_alphanumeric() || c == '_' || c == '$');
+    if valid {
+        name.to_string()
+    } else {
+        literal(name)
+    }
+}
+
+/// A type name usable as a TypeScript identifier
+fn identifier(name: &str) -> String {
+    name.chars()
+        .map(|c| {
+            if c.is_alphanumeric() || c == '$' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+/// What makes two occurrences of a named type the same declaration: its
+/// fingerprint without the annotations a field adds to it
+fn shape(schema: &SchemaType) -> String {
+    let mut bare = schema.clone();
+    bare.title = None;
+    bare.description = None;
+    bare.default = None;
+    for key in [
+        schema::DEPRECATED,
+        schema::READ_ONLY,
+        schema::WRITE_ONLY,
+        schema::SINCE,
+        schema::REF_PREFERENCE,
+    ] {
+        bare.extensions.remove(key);
+    }
+    bare.fingerprint()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_object_with_constraints() {
+        /// A new account
+        #[derive(Schema)]
+        #[allow(dead_code)]
+        #[schema(deny_unknown_fields)]
+        struct Signup {
+            #[schema(min_length = 3, max_length = 20, pattern = "^[a-z]+$")]
+            handle: String,
+            age: Option<u8>,
+            #[schema(minimum = 1)]
+            seats: u32,
+            #[schema(default)]
+            newsletter: bool,
+            #[schema(unique_items)]
+            tags: Vec<String>,
+        }
+
+        assert_eq!(
+            to_zod::<Signup>(),
+            r#"import { z } from "zod";
+
+export const SignupSchema = z.object({ handle: z.string().min(3).max(20).regex(new RegExp("^[a-z]+$")), age: z.number().int().min(0).max(255).optional(), seats: z.number().int().min(1).max(4294967295), newsletter: z.boolean().default(false), tags: z.array(z.string()).refine((items) => new Set(items.map((item) => JSON.stringify(item))).size === items.length, { message: "Items must be unique" }) }).strict().describe("A new account");
+export type Signup = z.infer<typeof SignupSchema>;
+"#
+        );
+    }
+
+    #[test]
+    fn test_discriminated_union_follows_its_case_schemas() {
+        #[derive(Schema)]
+        #[allow(dead_code)]
+        struct Viewport {
+            width: u32,
+        }
+
+        #[derive(Schema)]
+        #[allow(dead_code)]
+        struct Cursor {
+            x: i32,
+        }
+
+        #[derive(Schema)]
+        #[allow(dead_code)]
+        #[schema(tag = "op")]
+        enum Input {
+            Resize(Viewport),
+            Quit,
+            Move(Cursor),
+        }
+
+        assert_eq!(
+            to_zod::<Input>(),
+            r#"import { z } from "zod";
+
+export const ViewportSchema = z.object({ width: z.number().int().min(0).max(4294967295) });
+export type Viewport = z.infer<typeof ViewportSchema>;
+
+export const CursorSchema = z.object({ x: z.number().int().min(-2147483648).max(2147483647) });
+export type Cursor = z.infer<typeof CursorSchema>;
+
+export const InputSchema = z.discriminatedUnion("op", [ViewportSchema.extend({ op: z.literal("Resize") }), z.object({ op: z.literal("Quit") }), CursorSchema.extend({ op: z.literal("Move") })]);
+export type Input = z.infer<typeof InputSchema>;
+"#
+        );
+    }
+
+    #[test]
+    fn test_recursive_union_cases_are_lazy() {
+        #[derive(Schema)]
+        #[allow(dead_code)]
+        #[schema(tag = "op")]
+        enum Expr {
+            Lit { value: f64 },
+            Neg { inner: Box<Expr> },
+        }
+
+        assert!(to_zod::<Expr>().contains(
+            r#"export const ExprSchema: z.ZodTypeAny = z.discriminatedUnion("op", [z.object({ op: z.literal("Lit"), value: z.number() }), z.object({ op: z.literal("Neg"), inner: z.lazy(() => ExprSchema) })]);"#
+        ));
+    }
+
+    #[test]
+    fn test_forward_references_are_lazy() {
+        #[derive(Schema)]
+        #[allow(dead_code)]
+        struct Team {
+            lead: Option<Box<Person>>,
+            name: String,
+        }
+
+        #[derive(Schema)]
+        #[allow(dead_code)]
+        struct Person {
+            team: Option<Team>,
+            name: String,
+        }
+
+        assert_eq!(
+            to_zod::<Person>(),
+            r#"import { z } from "zod";
+
+export const TeamSchema: z.ZodTypeAny = z.object({ lead: z.lazy(() => PersonSchema).optional(), name: z.string() });
+export type Team = z.infer<typeof TeamSchema>;
+
+export const PersonSchema = z.object({ team: TeamSchema.optional(), name: z.string() });
+export type Person = z.infer<typeof PersonSchema>;
+"#
+        );
+    }
+
+    #[test]
+    fn test_recursive_types_are_lazy() {
+        #[derive(Schema)]
+        #[allow(dead_code)]
+        struct Node {
+            children: Vec<Node>,
+            span: (u32, u32),
+        }
+
+        assert!(to_zod::<Node>().contains(
+            "export const NodeSchema: z.ZodTypeAny = z.object({ children: z.array(z.lazy(() => NodeSchema)), span: z.tuple([z.number().int().min(0).max(4294967295), z.number().int().min(0).max(4294967295)]) });"
+        ));
+    }
+}
@@ -0,0 +1,251 @@
+//! Schema statistics for keeping generated schemas within size budgets
+
+use crate::{SchemaPath, SchemaType, TypeKind};
+use std::collections::HashSet;
+
+/// Size and complexity figures for a schema tree
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct SchemaStats {
+    /// Longest chain of nested schemas, counting the root as depth 1
+    pub depth: usize,
+    /// Object properties, TaggedUnion data fields and Variant payload fields
+    pub field_count: usize,
+    /// Every Enum and Variant with its number of allowed values/cases
+    pub enum_cardinalities: Vec<EnumCardinality>,
+    /// Structurally distinct non-empty objects below the root
+    pub distinct_object_types: usize,
+    /// Rough byte size of the schema rendered as compact JSON Schema
+    pub estimated_json_size: usize,
+}
+
+/// Cardinality of a single enum-like node
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EnumCardinality {
+    /// Where the enum lives, e.g. `/properties/status`
+    pub path: String,
+    pub cardinality: usize,
+}
+
+impl SchemaStats {
+    /// Rough LLM token estimate, assuming ~4 bytes of JSON per token
+    pub fn estimated_tokens(&self) -> usize {
+        self.estimated_json_size.div_ceil(4)
+    }
+
+    /// Largest enum cardinality, if the schema has any enums
+    pub fn max_enum_cardinality(&self) -> Option<usize> {
+        self.enum_cardinalities.iter().map(|e| e.cardinality).max()
+    }
+}
+
+/// Compute statistics for a schema
+pub fn analyze(schema: &SchemaType) -> SchemaStats {
+    let mut analyzer = Analyzer {
+        stats: SchemaStats::default(),
+        object_types: HashSet::new(),
+    };
+    analyzer.stats.depth = analyzer.visit(schema, SchemaPath::root(), 1);
+    analyzer.stats.distinct_object_types = analyzer.object_types.len();
+    analyzer.stats.estimated_json_size = estimate_json_size(schema);
+    analyzer.stats
+}
+
+struct Analyzer {
+    stats: SchemaStats,
+    object_types: HashSet<String>,
+}
+
+impl Analyzer {
+    /// Visit a node and return the depth of its subtree
+    fn visit(&mut self, schema: &SchemaType, path: SchemaPath, depth: usize) -> usize {
+        let mut max_depth = depth;
+        let mut descend = |this: &mut Self, child: &SchemaType, path: SchemaPath| {
+            max_depth = max_depth.max(this.visit(child, path, depth + 1));
+        };
+
+        match &schema.kind {
+            TypeKind::Object { properties, .. } => {
+                if depth > 1 && !properties.is_empty() {
+                    self.object_types.insert(schema.fingerprint());
+                }
+                self.stats.field_count += properties.len();
+                for (name, prop) in properties {
+                    descend(self, prop, path.clone().property(name));
+                }
+            }
+            TypeKind::TaggedUnion {
+                tag_variants,
+                data_fields,
+                ..
+            } => {
+                self.record_enum(&path, tag_variants.len());
+                self.stats.field_count += data_fields.len();
+                for (name, field) in data_fields {
+                    descend(self, field, path.clone().property(name));
+                }
+            }
+            TypeKind::Enum { variants } => self.record_enum(&path, variants.len()),
+            TypeKind::Variant { cases } => {
+                self.record_enum(&path, cases.len());
+                for case in cases {
+                    if let Some(data) = &case.data {
+                        descend(self, data, path.clone().case(&case.name));
+                    }
+                }
+            }
+            TypeKind::Array { items } | TypeKind::Set { items, .. } => {
+                descend(self, items, path.items());
+            }
+            TypeKind::Map { key, value, .. } => {
+                descend(self, key, path.clone().key());
+                descend(self, value, path.value());
+            }
+            TypeKind::Result { ok, err } => {
+                descend(self, ok, path.clone().ok());
+                descend(self, err, path.err());
+            }
+            TypeKind::Tuple { fields } => {
+                for (index, field) in fields.iter().enumerate() {
+                    descend(self, field, path.clone().field(index));
+                }
+            }
+            _ => {}
+        }
+
+        max_depth
+    }
+
+    fn record_enum(&mut self, path: &SchemaPath, cardinality: usize) {
+        self.stats.enum_cardinalities.push(EnumCardinality {
+            path: path.to_string(),
+            cardinality,
+        });
+    }
+}
+
+/// Length of a JSON string literal including quotes (ignoring escapes)
+fn quoted(s: &str) -> usize {
+    s.len() + 2
+}
+
+/// Approximate the compact JSON Schema size of a node
+///
+/// Mirrors the shape the JSON backends emit closely enough for budgeting,
+/// without depending on any of them.
+fn estimate_json_size(schema: &SchemaType) -> usize {
+    // `{}` plus `"title":"..."` and `"description":"..."`
+    let mut size = 2
+        + schema
+            .title
+            .as_ref()
+            .map_or(0, |t| quoted("title") + 1 + quoted(t) + 1)
+        + schema
+            .description
+            .as_ref()
+            .map_or(0, |d| quoted("description") + 1 + quoted(d) + 1);
+
+    let type_entry = |name: &str| quoted("type") + 1 + quoted(name);
+    let list = |items: &mut dyn Iterator<Item = usize>| {
+        let (total, count) = items.fold((0usize, 0usize), |(total, count), len| {
+            (total + len, count + 1)
+        });
+        2 + total + count.saturating_sub(1)
+    };
+
+    size += match &schema.kind {
+        TypeKind::String => type_entry("string"),
+        TypeKind::Integer(_) => type_entry("integer"),
+        TypeKind::Number(_) => type_entry("number"),
+        TypeKind::Boolean => type_entry("boolean"),
+        TypeKind::Null => type_entry("null"),
+        TypeKind::Object {
+            properties,
+            required,
+        } => {
+            type_entry("object")
+                + 1
+                + quoted("properties")
+                + 1
+                + list(
+                    &mut properties
+                        .iter()
+                        .map(|(name, prop)| quoted(name) + 1 + estimate_json_size(prop)),
+                )
+                + 1
+                + quoted("required")
+                + 1
+                + list(&mut required.iter().map(|r| quoted(r)))
+        }
+        TypeKind::Array { items } => {
+            type_entry("array") + 1 + quoted("items") + 1 + estimate_json_size(items)
+        }
+        TypeKind::Set { items, .. } => {
+            type_entry("array")
+                + 1
+                + quoted("items")
+                + 1
+                + estimate_json_size(items)
+                + 1
+                + quoted("uniqueItems")
+                + 5
+        }
+        TypeKind::Map { value, .. } => {
+            type_entry("object")
+                + 1
+                + quoted("additionalProperties")
+                + 1
+                + estimate_json_size(value)
+        }
+        TypeKind::Enum { variants } => {
+            type_entry("string")
+                + 1
+                + quoted("enum")
+                + 1
+                + list(&mut variants.iter().map(|v| quoted(v)))
+        }
+        TypeKind::TaggedUnion {
+            tag_field,
+            tag_variants,
+            data_fields,
+        } => {
+            let tag = quoted(tag_field)
+                + 1
+                + 2
+                + type_entry("string")
+                + 1
+                + quoted("enum")
+                + 1
+                + list(&mut tag_variants.iter().map(|v| quoted(v)));
+            let fields: usize = data_fields
+                .iter()
+                .map(|(name, field)| 1 + quoted(name) + 1 + estimate_json_size(field))
+                .sum();
+            type_entry("object") + 1 + quoted("properties") + 1 + 2 + tag + fields
+        }
+        TypeKind::Variant { cases } => {
+            quoted("oneOf")
+                + 1
+                + list(&mut cases.iter().map(|case| {
+                    let tag = quoted("const") + 1 + quoted(&case.name) + 2;
+                    match &case.data {
+                        None => tag,
+                        Some(data) => tag + estimate_json_size(data) + 20,
+                    }
+                }))
+        }
+        TypeKind::Result { ok, err } => {
+            quoted("oneOf") + 1 + estimate_json_size(ok) + estimate_json_size(err) + 60
+        }
+        TypeKind::Tuple { fields } => {
+            type_entry("array")
+                + 1
+                + quoted("prefixItems")
+                + 1
+                + list(&mut fields.iter().map(estimate_json_size))
+                + 30
+        }
+        TypeKind::Ref { name } => quoted("$ref") + 1 + quoted(name) + 20,
+    };
+
+    size
+}
@@ -0,0 +1,224 @@
+//! Identifier case conversion shared by the backends
+//!
+//! Words are split at `_`, `-`, spaces and dots, at lower→upper transitions,
+//! and at the end of a run of capitals (`HTTPRequest` → `HTTP`, `Request`).
+//! Runs of capitals that still contain several known acronyms are split with
+//! the acronym list (`APIURL` → `API`, `URL`).
+//!
+//! ```
+//! use schema::case::Case;
+//!
+//! assert_eq!(Case::Snake.apply("HTTPRequestID"), "http_request_id");
+//! assert_eq!(Case::Camel.apply("HTTPRequestID"), "httpRequestId");
+//! ```
+
+use std::fmt;
+use std::str::FromStr;
+
+/// Acronyms recognised by [`CaseConverter::default`]
+pub const DEFAULT_ACRONYMS: &[&str] = &[
+    "API", "ASCII", "CPU", "CSS", "CSV", "DB", "DNS", "GUID", "HTML", "HTTP", "HTTPS", "ID", "IO",
+    "IP", "JSON", "JWT", "OS", "RPC", "SQL", "SSH", "TCP", "TLS", "TTL", "UDP", "UI", "URI", "URL",
+    "UUID", "VM", "XML", "YAML",
+];
+
+/// Target case for identifiers
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Case {
+    /// `httprequestid`
+    Lower,
+    /// `HTTPREQUESTID`
+    Upper,
+    /// `http_request_id`
+    Snake,
+    /// `HTTP_REQUEST_ID`
+    ScreamingSnake,
+    /// `http-request-id`
+    Kebab,
+    /// `HTTP-REQUEST-ID`
+    ScreamingKebab,
+    /// `httpRequestId`
+    Camel,
+    /// `HttpRequestId`
+    Pascal,
+}
+
+impl Case {
+    /// Convert `s` using the default acronym list
+    pub fn apply(self, s: &str) -> String {
+        CaseConverter::default().convert(s, self)
+    }
+
+    /// The serde `rename_all` spelling of this case, e.g. `"camelCase"`
+    pub fn serde_name(self) -> &'static str {
+        match self {
+            Case::Lower => "lowercase",
+            Case::Upper => "UPPERCASE",
+            Case::Snake => "snake_case",
+            Case::ScreamingSnake => "SCREAMING_SNAKE_CASE",
+            Case::Kebab => "kebab-case",
+            Case::ScreamingKebab => "SCREAMING-KEBAB-CASE",
+            Case::Camel => "camelCase",
+            Case::Pascal => "PascalCase",
+        }
+    }
+}
+
+/// Error returned when parsing an unknown case name
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseCaseError(pub String);
+
+impl fmt::Display for ParseCaseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "unknown case {:?}", self.0)
+    }
+}
+
+impl std::error::Error for ParseCaseError {}
+
+impl FromStr for Case {
+    type Err = ParseCaseError;
+
+    /// Parse a serde `rename_all` spelling such as `"snake_case"`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        [
+            Case::Lower,
+            Case::Upper,
+            Case::Snake,
+            Case::ScreamingSnake,
+            Case::Kebab,
+            Case::ScreamingKebab,
+            Case::Camel,
+            Case::Pascal,
+        ]
+        .into_iter()
+        .find(|case| case.serde_name() == s)
+        .ok_or_else(|| ParseCaseError(s.to_string()))
+    }
+}
+
+/// Case converter with a configurable acronym list
+#[derive(Debug, Clone)]
+pub struct CaseConverter {
+    /// Uppercase acronyms, longest first so splitting prefers longer matches
+    acronyms: Vec<String>,
+}
+
+impl Default for CaseConverter {
+    fn default() -> Self {
+        CaseConverter::empty().with_acronyms(DEFAULT_ACRONYMS.iter().copied())
+    }
+}
+
+impl CaseConverter {
+    /// A converter with the default acronym list
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A converter that knows no acronyms and relies on heuristics alone
+    pub fn empty() -> Self {
+        CaseConverter {
+            acronyms: Vec::new(),
+        }
+    }
+
+    /// Add acronyms to recognise, e.g. `["OAUTH", "GRPC"]`
+    pub fn with_acronyms<'a>(mut self, acronyms: impl IntoIterator<Item = &'a str>) -> Self {
+        for acronym in acronyms {
+            let acronym = acronym.to_uppercase();
+            if !self.acronyms.contains(&acronym) {
+                self.acronyms.push(acronym);
+            }
+        }
+        self.acronyms
+            .sort_by(|a, b| b.len().cmp(&a.len()).then_with(|| a.cmp(b)));
+        self
+    }
+
+    /// Convert `s` to the given case
+    pub fn convert(&self, s: &str, case: Case) -> String {
+        let words = self.words(s);
+        let capitalize = |word: &str| {
+            let mut chars = word.chars();
+            match chars.next() {
+                Some(first) => first.to_uppercase().chain(chars).collect(),
+                None => String::new(),
+            }
+        };
+
+        match case {
+            Case::Lower => words.concat(),
+            Case::Upper => words.concat().to_uppercase(),
+            Case::Snake => words.join("_"),
+            Case::ScreamingSnake => words.join("_").to_uppercase(),
+            Case::Kebab => words.join("-"),
+            Case::ScreamingKebab => words.join("-").to_uppercase(),
+            Case::Camel => words
+                .iter()
+                .enumerate()
+                .map(|(i, word)| {
+                    if i == 0 {
+                        word.clone()
+                    } else {
+                        capitalize(word)
+                    }
+                })
+                .collect(),
+            Case::Pascal => words.iter().map(|word| capitalize(word)).collect(),
+        }
+    }
+
+    /// Split an identifier into lowercase words
+    pub fn words(&self, s: &str) -> Vec<String> {
+        let mut words = Vec::new();
+
+        for chunk in s.split(['_', '-', ' ', '.']).filter(|c| !c.is_empty()) {
+            let chars: Vec<char> = chunk.chars().collect();
+            let mut start = 0;
+
+            for i in 1..chars.len() {
+                let (prev, ch) = (chars[i - 1], chars[i]);
+                let next_is_lower = chars.get(i + 1).is_some_and(|c| c.is_lowercase());
+                let boundary = ch.is_uppercase()
+                    && (prev.is_lowercase()
+                        || prev.is_ascii_digit()
+                        || (prev.is_uppercase() && next_is_lower));
+                if boundary {
+                    self.push_word(&chars[start..i], &mut words);
+                    start = i;
+                }
+            }
+            self.push_word(&chars[start..], &mut words);
+        }
+
+        words
+    }
+
+    fn push_word(&self, chars: &[char], words: &mut Vec<String>) {
+        let word: String = chars.iter().collect();
+        let is_capital_run = chars.len() > 1 && chars.iter().all(|c| !c.is_lowercase());
+
+        match self.segment(&word) {
+            Some(parts) if is_capital_run && parts.len() > 1 => {
+                words.extend(parts.into_iter().map(|p| p.to_lowercase()))
+            }
+            _ => words.push(word.to_lowercase()),
+        }
+    }
+
+    /// Split a run of capitals entirely into known acronyms
+    fn segment(&self, run: &str) -> Option<Vec<String>> {
+        if run.is_empty() {
+            return Some(Vec::new());
+        }
+        self.acronyms
+            .iter()
+            .filter(|acronym| run.starts_with(acronym.as_str()))
+            .find_map(|acronym| {
+                let mut parts = self.segment(&run[acronym.len()..])?;
+                parts.insert(0, acronym.clone());
+                Some(parts)
+            })
+    }
+}
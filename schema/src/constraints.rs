@@ -0,0 +1,98 @@
+//! Validation constraints attached to a schema
+
+use crate::{SchemaType, TypeKind};
+use serde_json::Value;
+
+/// Value constraints beyond the type itself, emitted as JSON Schema keywords
+/// (`minimum`, `maxLength`, `pattern`, ...) by the JSON-based backends
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Constraints {
+    /// Inclusive lower bound for numbers
+    pub minimum: Option<f64>,
+    /// Inclusive upper bound for numbers
+    pub maximum: Option<f64>,
+    /// Minimum string length, in characters
+    pub min_length: Option<usize>,
+    /// Maximum string length, in characters
+    pub max_length: Option<usize>,
+    /// Minimum number of array or set items
+    pub min_items: Option<usize>,
+    /// Maximum number of array or set items
+    pub max_items: Option<usize>,
+    /// Array items must be distinct; sets are unique by kind
+    pub unique_items: bool,
+    /// Regular expression strings must match
+    pub pattern: Option<String>,
+    /// Well-known string format such as `email` or `uri`
+    pub format: Option<String>,
+}
+
+/// Apply a length bound, which means item count for arrays and sets and
+/// character count for everything else
+///
+/// Validation crates use one `length` rule for both, so the derive can't tell
+/// which keyword applies until the field's schema is known.
+pub fn apply_length(schema: &mut SchemaType, min: Option<usize>, max: Option<usize>) {
+    let constraints = &mut schema.constraints;
+    let (min_slot, max_slot) = match schema.kind {
+        TypeKind::Array { .. } | TypeKind::Set { .. } => {
+            (&mut constraints.min_items, &mut constraints.max_items)
+        }
+        _ => (&mut constraints.min_length, &mut constraints.max_length),
+    };
+    if min.is_some() {
+        *min_slot = min;
+    }
+    if max.is_some() {
+        *max_slot = max;
+    }
+}
+
+impl Constraints {
+    /// Whether no constraint is set
+    pub fn is_empty(&self) -> bool {
+        *self == Constraints::default()
+    }
+
+    /// The set constraints as JSON Schema keywords, for backends that emit
+    /// JSON Schema dialects
+    pub fn json_keywords(&self) -> serde_json::Map<String, Value> {
+        let mut keywords = serde_json::Map::new();
+        let bounds = [("minimum", self.minimum), ("maximum", self.maximum)];
+        for (keyword, bound) in bounds {
+            if let Some(bound) = bound {
+                keywords.insert(keyword.to_string(), number(bound));
+            }
+        }
+        let counts = [
+            ("minLength", self.min_length),
+            ("maxLength", self.max_length),
+            ("minItems", self.min_items),
+            ("maxItems", self.max_items),
+        ];
+        for (keyword, count) in counts {
+            if let Some(count) = count {
+                keywords.insert(keyword.to_string(), Value::from(count));
+            }
+        }
+        if self.unique_items {
+            keywords.insert("uniqueItems".to_string(), Value::Bool(true));
+        }
+        if let Some(pattern) = &self.pattern {
+            keywords.insert("pattern".to_string(), Value::from(pattern.as_str()));
+        }
+        if let Some(format) = &self.format {
+            keywords.insert("format".to_string(), Value::from(format.as_str()));
+        }
+        keywords
+    }
+}
+
+/// Whole bounds print as integers (`0`, not `0.0`)
+fn number(n: f64) -> Value {
+    if n.fract() == 0.0 && n.abs() < 2f64.powi(53) {
+        Value::from(n as i64)
+    } else {
+        Value::from(n)
+    }
+}
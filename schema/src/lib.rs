@@ -3,11 +3,393 @@ use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet, LinkedList};
 
 pub use schema_derive::Schema;
 
+pub mod analyze;
+pub mod case;
+mod constraints;
+mod macros;
+mod normalize;
+mod options;
+mod pointer;
+mod type_name;
+pub mod validate;
+mod version;
+
+pub use constraints::Constraints;
+pub use normalize::NormalizedSchema;
+pub use options::{Fallback, GenerateOptions};
+pub use pointer::{ParsePathError, PathSegment, SchemaPath};
+pub use version::{ParseVersionError, Version};
+
+/// Extension key recording the version a field or variant case was added
+/// in, set by `#[schema(since = "1.4.0")]`
+pub const SINCE: &str = "x-since";
+
+/// Extension key recording a type's own semantic version, set by
+/// `#[schema(version = "2.1.0")]`; see [`Schema::schema_version`]
+pub const SCHEMA_VERSION: &str = "x-schema-version";
+
+/// Extension key on a plain enum mapping case names to the version they were
+/// added in, since enum cases carry no metadata of their own
+pub const ENUM_SINCE: &str = "x-enum-since";
+
+/// Extension key on a plain enum mapping case names to their integer
+/// discriminants, recorded when the Rust enum sets any explicitly
+pub const ENUM_DISCRIMINANTS: &str = "x-enum-discriminants";
+
+/// Extension key marking a field that only appears in responses, set by
+/// `#[schema(read_only)]`
+pub const READ_ONLY: &str = "x-read-only";
+
+/// Extension key marking a field that only appears in requests, set by
+/// `#[schema(write_only)]`
+pub const WRITE_ONLY: &str = "x-write-only";
+
+/// Extension key marking a type, field or variant case deprecated, set from
+/// Rust's `#[deprecated]`
+pub const DEPRECATED: &str = "x-deprecated";
+
+/// Extension key on a plain enum listing its deprecated case names
+pub const ENUM_DEPRECATED: &str = "x-enum-deprecated";
+
+/// Extension key holding a type or field's example values as an array, set
+/// by `#[schema(example = ...)]`
+pub const EXAMPLES: &str = "x-examples";
+
+/// Extension key overriding whether [`SchemaType::normalize`] shares a
+/// subtree as a definition, set by `#[schema(inline)]` and
+/// `#[schema(as_ref)]`; see [`RefPreference`]
+pub const REF_PREFERENCE: &str = "x-ref-preference";
+
+/// Extension key on a Variant holding its wire representation, set by
+/// `#[schema(tag = "...", content = "...")]` or `#[schema(untagged)]`; see
+/// [`Tagging`]
+pub const TAGGING: &str = "x-tagging";
+
+/// Extension key marking an Object closed to properties it doesn't declare,
+/// set by `#[schema(deny_unknown_fields)]`
+pub const DENY_UNKNOWN_FIELDS: &str = "x-deny-unknown-fields";
+
+/// Extension keys this crate interprets itself; backends translate them
+/// instead of passing them through as vendor extensions
+pub const RESERVED_EXTENSIONS: &[&str] = &[
+    SINCE,
+    SCHEMA_VERSION,
+    ENUM_SINCE,
+    ENUM_DISCRIMINANTS,
+    READ_ONLY,
+    WRITE_ONLY,
+    DEPRECATED,
+    ENUM_DEPRECATED,
+    EXAMPLES,
+    REF_PREFERENCE,
+    TAGGING,
+    DENY_UNKNOWN_FIELDS,
+];
+
+/// Whether `key` is a user-defined vendor extension, as set by
+/// `#[schema(extension("x-order" = 3))]`, rather than one of
+/// [`RESERVED_EXTENSIONS`] or a backend hint such as `x-wit-name`
+pub fn is_vendor_extension(key: &str) -> bool {
+    !RESERVED_EXTENSIONS.contains(&key) && !key.starts_with("x-wit-")
+}
+
+/// Support code for the derive macro; not part of the public API
+#[doc(hidden)]
+pub mod __private {
+    pub use crate::constraints::apply_length;
+    pub use crate::macros::doc_text;
+    pub use crate::options::recursion_guard;
+    pub use crate::type_name::generic_type_name;
+    pub use serde_json;
+}
+
 /// Core schema representation for types (not values)
 #[derive(Debug, Clone, PartialEq)]
 pub struct SchemaType {
     pub kind: TypeKind,
+    /// Short human-readable label, e.g. the first paragraph of a doc comment
+    pub title: Option<String>,
     pub description: Option<String>,
+    /// Value used when the field is absent, as set by `#[schema(default)]`
+    pub default: Option<serde_json::Value>,
+    /// Bounds, lengths, patterns and formats the value must satisfy
+    pub constraints: Constraints,
+    /// Name of the Rust type this schema was derived from, if any
+    pub name: Option<String>,
+    /// Declaration order of an Object's properties, when known
+    ///
+    /// `properties` is unordered; backends that need a stable order use this
+    /// and fall back to sorting names it doesn't list.
+    pub property_order: Vec<String>,
+    /// Vendor extensions (`x-*` keys), including backend-specific hints
+    /// such as `x-wit-name`
+    pub extensions: BTreeMap<String, serde_json::Value>,
+}
+
+impl SchemaType {
+    /// Create an undescribed, unnamed schema of the given kind
+    pub fn new(kind: TypeKind) -> Self {
+        SchemaType {
+            kind,
+            title: None,
+            description: None,
+            default: None,
+            constraints: Constraints::default(),
+            name: None,
+            property_order: Vec::new(),
+            extensions: BTreeMap::new(),
+        }
+    }
+}
+
+impl SchemaType {
+    /// Title and description joined as a single doc text, for backends
+    /// without a separate title field
+    pub fn docs(&self) -> Option<String> {
+        match (&self.title, &self.description) {
+            (Some(title), Some(description)) => Some(format!("{}\n\n{}", title, description)),
+            (Some(text), None) | (None, Some(text)) => Some(text.clone()),
+            (None, None) => None,
+        }
+    }
+
+    /// Version this field was added in, from `#[schema(since = "...")]`
+    pub fn since(&self) -> Option<&str> {
+        self.extensions.get(SINCE).and_then(|v| v.as_str())
+    }
+
+    /// The type's own version, from `#[schema(version = "...")]`
+    pub fn schema_version(&self) -> Option<&str> {
+        self.extensions.get(SCHEMA_VERSION).and_then(|v| v.as_str())
+    }
+
+    /// A plain enum's cases paired with their integer discriminants, in case
+    /// order, when the Rust enum sets them explicitly
+    ///
+    /// ```
+    /// use schema::Schema;
+    ///
+    /// #[derive(Schema)]
+    /// enum Priority {
+    ///     Low = 1,
+    ///     High = 10,
+    /// }
+    ///
+    /// let schema = Priority::schema();
+    /// let discriminants = schema.enum_discriminants().unwrap();
+    /// assert_eq!(discriminants, [("Low", 1), ("High", 10)]);
+    /// ```
+    pub fn enum_discriminants(&self) -> Option<Vec<(&str, i64)>> {
+        let TypeKind::Enum { variants } = &self.kind else {
+            return None;
+        };
+        let discriminants = self.extensions.get(ENUM_DISCRIMINANTS)?;
+        variants
+            .iter()
+            .map(|variant| Some((variant.as_str(), discriminants.get(variant)?.as_i64()?)))
+            .collect()
+    }
+
+    /// Inline/reference override for normalization, if one was set
+    pub fn ref_preference(&self) -> Option<RefPreference> {
+        match self.extensions.get(REF_PREFERENCE)?.as_str()? {
+            "inline" => Some(RefPreference::Inline),
+            "ref" => Some(RefPreference::AsRef),
+            _ => None,
+        }
+    }
+
+    /// Record an inline/reference override for normalization
+    pub fn set_ref_preference(&mut self, preference: RefPreference) {
+        let value = match preference {
+            RefPreference::Inline => "inline",
+            RefPreference::AsRef => "ref",
+        };
+        self.extensions
+            .insert(REF_PREFERENCE.to_string(), serde_json::Value::from(value));
+    }
+
+    /// User-defined vendor extensions, for backends to pass through
+    pub fn vendor_extensions(&self) -> impl Iterator<Item = (&str, &serde_json::Value)> {
+        self.extensions
+            .iter()
+            .filter(|(key, _)| is_vendor_extension(key))
+            .map(|(key, value)| (key.as_str(), value))
+    }
+
+    /// Whether this field is only sent by the server
+    pub fn read_only(&self) -> bool {
+        self.extensions.get(READ_ONLY) == Some(&serde_json::Value::Bool(true))
+    }
+
+    /// Whether this field is only sent by the client
+    pub fn write_only(&self) -> bool {
+        self.extensions.get(WRITE_ONLY) == Some(&serde_json::Value::Bool(true))
+    }
+
+    /// Whether this type or field is deprecated
+    pub fn deprecated(&self) -> bool {
+        self.extensions.get(DEPRECATED) == Some(&serde_json::Value::Bool(true))
+    }
+
+    /// Mark this type or field deprecated, appending `note` to the
+    /// description
+    pub fn deprecate(&mut self, note: Option<&str>) {
+        self.extensions
+            .insert(DEPRECATED.to_string(), serde_json::Value::Bool(true));
+        append_deprecation_note(&mut self.description, note);
+    }
+
+    /// Example values of this type or field, in the order they were given
+    pub fn examples(&self) -> &[serde_json::Value] {
+        match self.extensions.get(EXAMPLES) {
+            Some(serde_json::Value::Array(examples)) => examples,
+            _ => &[],
+        }
+    }
+
+    /// Wire representation of a Variant's cases, if one was chosen
+    ///
+    /// Without one, JSON backends keep their own default shapes.
+    pub fn tagging(&self) -> Option<Tagging> {
+        let tagging = self.extensions.get(TAGGING)?;
+        if tagging.get("untagged").and_then(|v| v.as_bool()) == Some(true) {
+            return Some(Tagging::Untagged);
+        }
+        let tag = tagging.get("tag")?.as_str()?.to_string();
+        Some(match tagging.get("content").and_then(|v| v.as_str()) {
+            Some(content) => Tagging::Adjacent {
+                tag,
+                content: content.to_string(),
+            },
+            None => Tagging::Internal { tag },
+        })
+    }
+
+    /// Record `tagging` as this schema's wire representation
+    pub fn set_tagging(&mut self, tagging: Tagging) {
+        let value = match tagging {
+            Tagging::Internal { tag } => serde_json::json!({ "tag": tag }),
+            Tagging::Adjacent { tag, content } => {
+                serde_json::json!({ "tag": tag, "content": content })
+            }
+            Tagging::Untagged => serde_json::json!({ "untagged": true }),
+        };
+        self.extensions.insert(TAGGING.to_string(), value);
+    }
+
+    /// Whether an Object rejects properties it doesn't declare
+    pub fn denies_unknown_fields(&self) -> bool {
+        self.extensions.get(DENY_UNKNOWN_FIELDS) == Some(&serde_json::Value::Bool(true))
+    }
+
+    /// Close an Object to properties it doesn't declare, or reopen it
+    pub fn set_deny_unknown_fields(&mut self, deny: bool) {
+        if deny {
+            self.extensions.insert(
+                DENY_UNKNOWN_FIELDS.to_string(),
+                serde_json::Value::Bool(true),
+            );
+        } else {
+            self.extensions.remove(DENY_UNKNOWN_FIELDS);
+        }
+    }
+
+    /// This Object with every property optional, as for a PATCH body or
+    /// incremental tool arguments
+    ///
+    /// Only the top level is relaxed; nested objects keep their required
+    /// properties. A named schema is renamed to `<name>Partial` so it
+    /// doesn't collide with the full one. Other schemas are returned
+    /// unchanged.
+    pub fn partial(&self) -> SchemaType {
+        let TypeKind::Object { properties, .. } = &self.kind else {
+            return self.clone();
+        };
+        SchemaType {
+            kind: TypeKind::Object {
+                properties: properties.clone(),
+                required: Vec::new(),
+            },
+            name: self.name.as_ref().map(|name| format!("{}Partial", name)),
+            ..self.clone()
+        }
+    }
+
+    /// Flatten a Variant into the legacy [`TypeKind::TaggedUnion`]
+    ///
+    /// Case names become the tag's values, under the internal tag if one was
+    /// set and `type` otherwise. Every case's record fields merge into one
+    /// set of data fields (the first case to declare a field wins), and
+    /// non-record payloads sit under `data`. Per-case docs and requiredness
+    /// are lost. Other schemas are returned unchanged.
+    pub fn into_tagged_union(mut self) -> SchemaType {
+        let TypeKind::Variant { cases } = &self.kind else {
+            return self;
+        };
+
+        let tag_field = match self.tagging() {
+            Some(Tagging::Internal { tag }) => tag,
+            _ => "type".to_string(),
+        };
+        let mut data_fields = HashMap::new();
+        for case in cases {
+            match &case.data {
+                Some(data) if matches!(data.kind, TypeKind::Object { .. }) => {
+                    for (name, field) in case.fields() {
+                        data_fields
+                            .entry(name.to_string())
+                            .or_insert_with(|| field.clone());
+                    }
+                }
+                Some(data) => {
+                    data_fields
+                        .entry("data".to_string())
+                        .or_insert_with(|| data.clone());
+                }
+                None => {}
+            }
+        }
+
+        self.kind = TypeKind::TaggedUnion {
+            tag_field,
+            tag_variants: cases.iter().map(|case| case.name.clone()).collect(),
+            data_fields,
+        };
+        self.extensions.remove(TAGGING);
+        self
+    }
+
+    /// Object properties in declaration order, followed by any properties
+    /// missing from `property_order` sorted by name
+    ///
+    /// Empty for non-Object schemas.
+    pub fn ordered_properties(&self) -> Vec<(&str, &SchemaType)> {
+        let TypeKind::Object { properties, .. } = &self.kind else {
+            return Vec::new();
+        };
+
+        let mut ordered: Vec<(&str, &SchemaType)> = self
+            .property_order
+            .iter()
+            .filter_map(|name| properties.get_key_value(name))
+            .map(|(name, schema)| (name.as_str(), schema))
+            .collect();
+        let mut rest: Vec<(&str, &SchemaType)> = properties
+            .iter()
+            .filter(|(name, _)| !self.property_order.contains(name))
+            .map(|(name, schema)| (name.as_str(), schema))
+            .collect();
+        rest.sort_by_key(|(name, _)| *name);
+        ordered.extend(rest);
+        ordered
+    }
+}
+
+impl Default for SchemaType {
+    fn default() -> Self {
+        SchemaType::new(TypeKind::Null)
+    }
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -63,12 +445,109 @@ pub enum TypeKind {
     },
 }
 
+/// Whether a subtree becomes a shared definition when normalizing,
+/// regardless of how often it occurs
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RefPreference {
+    /// Always written out in place
+    Inline,
+    /// Always a `Ref` to a definition, even when used once
+    AsRef,
+}
+
+/// How a Variant's cases are written in JSON, mirroring serde's enum
+/// representations
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Tagging {
+    /// `{"<tag>": "circle", "radius": 1.0}`: the tag sits beside the case's
+    /// fields; unit cases are `{"<tag>": "none"}`
+    Internal { tag: String },
+    /// `{"<tag>": "circle", "<content>": {"radius": 1.0}}`
+    Adjacent { tag: String, content: String },
+    /// `{"radius": 1.0}`: just the payload, with the case inferred from its
+    /// shape; unit cases are `null`
+    Untagged,
+}
+
+impl Tagging {
+    /// Name of the discriminator property, if there is one
+    pub fn tag(&self) -> Option<&str> {
+        match self {
+            Tagging::Internal { tag } | Tagging::Adjacent { tag, .. } => Some(tag),
+            Tagging::Untagged => None,
+        }
+    }
+}
+
+/// Add a `Deprecated: <note>` paragraph to `description`
+fn append_deprecation_note(description: &mut Option<String>, note: Option<&str>) {
+    let Some(note) = note else {
+        return;
+    };
+    *description = Some(match description.take() {
+        Some(description) => format!("{}\n\nDeprecated: {}", description, note),
+        None => format!("Deprecated: {}", note),
+    });
+}
+
 /// A single case in a variant type
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Default)]
 pub struct VariantCase {
     pub name: String,
     pub data: Option<SchemaType>,
     pub description: Option<String>,
+    /// Vendor extensions (`x-*` keys) for this case, such as `x-since`
+    pub extensions: BTreeMap<String, serde_json::Value>,
+}
+
+impl VariantCase {
+    /// Version this case was added in, from `#[schema(since = "...")]`
+    pub fn since(&self) -> Option<&str> {
+        self.extensions.get(SINCE).and_then(|v| v.as_str())
+    }
+
+    /// User-defined vendor extensions, for backends to pass through
+    pub fn vendor_extensions(&self) -> impl Iterator<Item = (&str, &serde_json::Value)> {
+        self.extensions
+            .iter()
+            .filter(|(key, _)| is_vendor_extension(key))
+            .map(|(key, value)| (key.as_str(), value))
+    }
+
+    /// Whether this case is deprecated
+    pub fn deprecated(&self) -> bool {
+        self.extensions.get(DEPRECATED) == Some(&serde_json::Value::Bool(true))
+    }
+
+    /// Mark this case deprecated, appending `note` to the description
+    pub fn deprecate(&mut self, note: Option<&str>) {
+        self.extensions
+            .insert(DEPRECATED.to_string(), serde_json::Value::Bool(true));
+        append_deprecation_note(&mut self.description, note);
+    }
+
+    /// Payload fields in declaration order, when the case carries a record
+    ///
+    /// Empty for unit cases and non-record payloads.
+    pub fn fields(&self) -> Vec<(&str, &SchemaType)> {
+        self.data
+            .as_ref()
+            .map(SchemaType::ordered_properties)
+            .unwrap_or_default()
+    }
+
+    /// Payload fields that must be present when this case is chosen
+    pub fn required(&self) -> &[String] {
+        match self.data.as_ref().map(|data| &data.kind) {
+            Some(TypeKind::Object { required, .. }) => required,
+            _ => &[],
+        }
+    }
+
+    /// Whether `field` must be present when this case is chosen
+    pub fn is_required(&self, field: &str) -> bool {
+        self.required().iter().any(|r| r == field)
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -81,6 +560,45 @@ pub enum IntegerKind {
     Usize,
 }
 
+impl IntegerKind {
+    /// Whether negative values are allowed
+    pub fn is_signed(&self) -> bool {
+        matches!(self, IntegerKind::I32 | IntegerKind::I64)
+    }
+
+    /// Smallest allowed value
+    pub fn min(&self) -> i128 {
+        match self {
+            IntegerKind::I32 => i32::MIN as i128,
+            IntegerKind::I64 => i64::MIN as i128,
+            IntegerKind::U8 | IntegerKind::U32 | IntegerKind::U64 | IntegerKind::Usize => 0,
+        }
+    }
+
+    /// Largest allowed value (usize is treated as u64 for portability)
+    pub fn max(&self) -> i128 {
+        match self {
+            IntegerKind::I32 => i32::MAX as i128,
+            IntegerKind::I64 => i64::MAX as i128,
+            IntegerKind::U8 => u8::MAX as i128,
+            IntegerKind::U32 => u32::MAX as i128,
+            IntegerKind::U64 | IntegerKind::Usize => u64::MAX as i128,
+        }
+    }
+
+    /// Rust name of the kind, e.g. `u8`
+    pub fn rust_name(&self) -> &'static str {
+        match self {
+            IntegerKind::I32 => "i32",
+            IntegerKind::I64 => "i64",
+            IntegerKind::U8 => "u8",
+            IntegerKind::U32 => "u32",
+            IntegerKind::U64 => "u64",
+            IntegerKind::Usize => "usize",
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum NumberKind {
     F32,
@@ -96,108 +614,81 @@ pub trait Schema {
     fn type_name() -> Option<&'static str> {
         None
     }
+
+    /// Optional: the type's semantic version, for artifacts and
+    /// compatibility checks keyed to it
+    fn schema_version() -> Option<&'static str> {
+        None
+    }
 }
 
 // Implement for primitive types
 impl Schema for String {
     fn schema() -> SchemaType {
-        SchemaType {
-            kind: TypeKind::String,
-            description: None,
-        }
+        SchemaType::new(TypeKind::String)
     }
 }
 
 impl Schema for i32 {
     fn schema() -> SchemaType {
-        SchemaType {
-            kind: TypeKind::Integer(IntegerKind::I32),
-            description: None,
-        }
+        SchemaType::new(TypeKind::Integer(IntegerKind::I32))
     }
 }
 
 impl Schema for i64 {
     fn schema() -> SchemaType {
-        SchemaType {
-            kind: TypeKind::Integer(IntegerKind::I64),
-            description: None,
-        }
+        SchemaType::new(TypeKind::Integer(IntegerKind::I64))
     }
 }
 
 impl Schema for u8 {
     fn schema() -> SchemaType {
-        SchemaType {
-            kind: TypeKind::Integer(IntegerKind::U8),
-            description: None,
-        }
+        SchemaType::new(TypeKind::Integer(IntegerKind::U8))
     }
 }
 
 impl Schema for u32 {
     fn schema() -> SchemaType {
-        SchemaType {
-            kind: TypeKind::Integer(IntegerKind::U32),
-            description: None,
-        }
+        SchemaType::new(TypeKind::Integer(IntegerKind::U32))
     }
 }
 
 impl Schema for u64 {
     fn schema() -> SchemaType {
-        SchemaType {
-            kind: TypeKind::Integer(IntegerKind::U64),
-            description: None,
-        }
+        SchemaType::new(TypeKind::Integer(IntegerKind::U64))
     }
 }
 
 impl Schema for usize {
     fn schema() -> SchemaType {
-        SchemaType {
-            kind: TypeKind::Integer(IntegerKind::Usize),
-            description: None,
-        }
+        SchemaType::new(TypeKind::Integer(IntegerKind::Usize))
     }
 }
 
 impl Schema for f32 {
     fn schema() -> SchemaType {
-        SchemaType {
-            kind: TypeKind::Number(NumberKind::F32),
-            description: None,
-        }
+        SchemaType::new(TypeKind::Number(NumberKind::F32))
     }
 }
 
 impl Schema for f64 {
     fn schema() -> SchemaType {
-        SchemaType {
-            kind: TypeKind::Number(NumberKind::F64),
-            description: None,
-        }
+        SchemaType::new(TypeKind::Number(NumberKind::F64))
     }
 }
 
 impl Schema for bool {
     fn schema() -> SchemaType {
-        SchemaType {
-            kind: TypeKind::Boolean,
-            description: None,
-        }
+        SchemaType::new(TypeKind::Boolean)
     }
 }
 
 impl Schema for () {
     fn schema() -> SchemaType {
-        SchemaType {
-            kind: TypeKind::Object {
-                properties: HashMap::new(),
-                required: Vec::new(),
-            },
-            description: None,
-        }
+        SchemaType::new(TypeKind::Object {
+            properties: HashMap::new(),
+            required: Vec::new(),
+        })
     }
 }
 
@@ -206,6 +697,7 @@ impl Schema for std::path::PathBuf {
         SchemaType {
             kind: TypeKind::String,
             description: Some("File system path".to_string()),
+            ..Default::default()
         }
     }
 }
@@ -218,6 +710,7 @@ impl Schema for serde_json::Value {
                 required: Vec::new(),
             },
             description: Some("Dynamic JSON value".to_string()),
+            ..Default::default()
         }
     }
 }
@@ -228,14 +721,126 @@ impl<T: Schema> Schema for Option<T> {
     }
 }
 
+/// Serialized by serde as a unit value
+impl<T: ?Sized> Schema for std::marker::PhantomData<T> {
+    fn schema() -> SchemaType {
+        SchemaType::new(TypeKind::Null)
+    }
+}
+
+impl<T: Schema> Schema for Box<T> {
+    fn schema() -> SchemaType {
+        T::schema()
+    }
+
+    fn type_name() -> Option<&'static str> {
+        T::type_name()
+    }
+
+    fn schema_version() -> Option<&'static str> {
+        T::schema_version()
+    }
+}
+
+impl<T: Schema> Schema for std::rc::Rc<T> {
+    fn schema() -> SchemaType {
+        T::schema()
+    }
+
+    fn type_name() -> Option<&'static str> {
+        T::type_name()
+    }
+
+    fn schema_version() -> Option<&'static str> {
+        T::schema_version()
+    }
+}
+
+impl<T: Schema> Schema for std::sync::Arc<T> {
+    fn schema() -> SchemaType {
+        T::schema()
+    }
+
+    fn type_name() -> Option<&'static str> {
+        T::type_name()
+    }
+
+    fn schema_version() -> Option<&'static str> {
+        T::schema_version()
+    }
+}
+
+impl Schema for str {
+    fn schema() -> SchemaType {
+        String::schema()
+    }
+}
+
+/// Borrowed values have the same schema as the owned type
+impl<T: Schema + ?Sized> Schema for &T {
+    fn schema() -> SchemaType {
+        T::schema()
+    }
+
+    fn type_name() -> Option<&'static str> {
+        T::type_name()
+    }
+
+    fn schema_version() -> Option<&'static str> {
+        T::schema_version()
+    }
+}
+
+impl<T: Schema + ?Sized> Schema for &mut T {
+    fn schema() -> SchemaType {
+        T::schema()
+    }
+
+    fn type_name() -> Option<&'static str> {
+        T::type_name()
+    }
+
+    fn schema_version() -> Option<&'static str> {
+        T::schema_version()
+    }
+}
+
+impl<T: Schema + ToOwned + ?Sized> Schema for std::borrow::Cow<'_, T> {
+    fn schema() -> SchemaType {
+        T::schema()
+    }
+
+    fn type_name() -> Option<&'static str> {
+        T::type_name()
+    }
+
+    fn schema_version() -> Option<&'static str> {
+        T::schema_version()
+    }
+}
+
+impl<T: Schema> Schema for [T] {
+    fn schema() -> SchemaType {
+        Vec::<T>::schema()
+    }
+}
+
 impl<T: Schema> Schema for Vec<T> {
     fn schema() -> SchemaType {
-        SchemaType {
-            kind: TypeKind::Array {
-                items: Box::new(T::schema()),
-            },
-            description: None,
-        }
+        SchemaType::new(TypeKind::Array {
+            items: Box::new(T::schema()),
+        })
+    }
+}
+
+impl<T: Schema, const N: usize> Schema for [T; N] {
+    fn schema() -> SchemaType {
+        let mut schema = SchemaType::new(TypeKind::Array {
+            items: Box::new(T::schema()),
+        });
+        schema.constraints.min_items = Some(N);
+        schema.constraints.max_items = Some(N);
+        schema
     }
 }
 
@@ -248,6 +853,7 @@ impl<K: Schema, V: Schema> Schema for HashMap<K, V> {
                 ordered: false,
             },
             description: Some("Unordered map/dictionary of key-value pairs".to_string()),
+            ..Default::default()
         }
     }
 }
@@ -260,6 +866,7 @@ impl<T: Schema> Schema for HashSet<T> {
                 ordered: false,
             },
             description: Some("Unordered set of unique values".to_string()),
+            ..Default::default()
         }
     }
 }
@@ -273,6 +880,7 @@ impl<K: Schema, V: Schema> Schema for BTreeMap<K, V> {
                 ordered: true,
             },
             description: Some("Ordered map/dictionary of key-value pairs".to_string()),
+            ..Default::default()
         }
     }
 }
@@ -285,6 +893,7 @@ impl<T: Schema> Schema for BTreeSet<T> {
                 ordered: true,
             },
             description: Some("Ordered set of unique values".to_string()),
+            ..Default::default()
         }
     }
 }
@@ -296,63 +905,49 @@ impl<T: Schema> Schema for LinkedList<T> {
                 items: Box::new(T::schema()),
             },
             description: Some("Doubly-linked list".to_string()),
+            ..Default::default()
         }
     }
 }
 
 impl<T: Schema, E: Schema> Schema for Result<T, E> {
     fn schema() -> SchemaType {
-        SchemaType {
-            kind: TypeKind::Result {
-                ok: Box::new(T::schema()),
-                err: Box::new(E::schema()),
-            },
-            description: None,
-        }
+        SchemaType::new(TypeKind::Result {
+            ok: Box::new(T::schema()),
+            err: Box::new(E::schema()),
+        })
     }
 }
 
 // Tuple implementations for common sizes
 impl<T1: Schema> Schema for (T1,) {
     fn schema() -> SchemaType {
-        SchemaType {
-            kind: TypeKind::Tuple {
-                fields: vec![T1::schema()],
-            },
-            description: None,
-        }
+        SchemaType::new(TypeKind::Tuple {
+            fields: vec![T1::schema()],
+        })
     }
 }
 
 impl<T1: Schema, T2: Schema> Schema for (T1, T2) {
     fn schema() -> SchemaType {
-        SchemaType {
-            kind: TypeKind::Tuple {
-                fields: vec![T1::schema(), T2::schema()],
-            },
-            description: None,
-        }
+        SchemaType::new(TypeKind::Tuple {
+            fields: vec![T1::schema(), T2::schema()],
+        })
     }
 }
 
 impl<T1: Schema, T2: Schema, T3: Schema> Schema for (T1, T2, T3) {
     fn schema() -> SchemaType {
-        SchemaType {
-            kind: TypeKind::Tuple {
-                fields: vec![T1::schema(), T2::schema(), T3::schema()],
-            },
-            description: None,
-        }
+        SchemaType::new(TypeKind::Tuple {
+            fields: vec![T1::schema(), T2::schema(), T3::schema()],
+        })
     }
 }
 
 impl<T1: Schema, T2: Schema, T3: Schema, T4: Schema> Schema for (T1, T2, T3, T4) {
     fn schema() -> SchemaType {
-        SchemaType {
-            kind: TypeKind::Tuple {
-                fields: vec![T1::schema(), T2::schema(), T3::schema(), T4::schema()],
-            },
-            description: None,
-        }
+        SchemaType::new(TypeKind::Tuple {
+            fields: vec![T1::schema(), T2::schema(), T3::schema(), T4::schema()],
+        })
     }
 }
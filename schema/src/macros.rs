@@ -0,0 +1,160 @@
+/// Declare a named alias of another schema type
+///
+/// Type aliases can't carry a `Schema` impl of their own, so this declares a
+/// transparent newtype instead. Its schema is the aliased type's, named after
+/// the alias and always shared as a definition by
+/// [`SchemaType::normalize`](crate::SchemaType::normalize), so the alias
+/// shows up by name in generated outputs.
+///
+/// ```
+/// use schema::{Schema, TypeKind, schema_alias};
+///
+/// schema_alias!(pub type UserId = String, "Unique user identifier");
+///
+/// let schema = UserId::schema();
+/// assert_eq!(schema.kind, TypeKind::String);
+/// assert_eq!(schema.name.as_deref(), Some("UserId"));
+/// assert_eq!(schema.description.as_deref(), Some("Unique user identifier"));
+///
+/// let id = UserId::from("u-1".to_string());
+/// assert_eq!(id.len(), 3);
+/// ```
+///
+/// Attributes such as derives go before the visibility:
+/// `schema_alias!(#[derive(Clone)] pub type Tag = String);`.
+#[macro_export]
+macro_rules! schema_alias {
+    ($(#[$meta:meta])* $vis:vis type $name:ident = $ty:ty $(, $description:literal)? $(,)?) => {
+        $(#[doc = $description])?
+        $(#[$meta])*
+        #[repr(transparent)]
+        $vis struct $name(pub $ty);
+
+        impl $crate::Schema for $name {
+            fn schema() -> $crate::SchemaType {
+                let mut schema = <$ty as $crate::Schema>::schema();
+                schema.name = Some(stringify!($name).to_string());
+                $(schema.description = Some($description.to_string());)?
+                schema.set_ref_preference($crate::RefPreference::AsRef);
+                schema
+            }
+
+            fn type_name() -> Option<&'static str> {
+                Some(stringify!($name))
+            }
+        }
+
+        impl From<$ty> for $name {
+            fn from(value: $ty) -> Self {
+                $name(value)
+            }
+        }
+
+        impl std::ops::Deref for $name {
+            type Target = $ty;
+
+            fn deref(&self) -> &$ty {
+                &self.0
+            }
+        }
+    };
+}
+
+/// Implement [`Schema`](crate::Schema) for a struct-like type by listing its
+/// fields, for types the derive can't be used on
+///
+/// Doc comments become descriptions, and `field?: Type` marks a field that
+/// may be omitted. The schema is named after the type's last path segment.
+///
+/// ```
+/// use schema::{Schema, TypeKind, impl_schema};
+///
+/// struct Point {
+///     lat: f64,
+///     lng: f64,
+///     label: Option<String>,
+/// }
+///
+/// impl_schema! {
+///     /// A point on the globe
+///     Point {
+///         /// Latitude in degrees
+///         lat: f64,
+///         lng: f64,
+///         label?: String,
+///     }
+/// }
+///
+/// let schema = Point::schema();
+/// assert_eq!(schema.name.as_deref(), Some("Point"));
+/// assert_eq!(schema.description.as_deref(), Some("A point on the globe"));
+/// let TypeKind::Object { required, .. } = &schema.kind else {
+///     unreachable!()
+/// };
+/// assert_eq!(required, &["lat", "lng"]);
+/// ```
+#[macro_export]
+macro_rules! impl_schema {
+    ($(#[doc = $doc:literal])* $ty:path { $($fields:tt)* }) => {
+        impl $crate::Schema for $ty {
+            fn schema() -> $crate::SchemaType {
+                let mut properties = std::collections::HashMap::new();
+                let mut required = Vec::new();
+                let mut property_order = Vec::new();
+                $crate::impl_schema!(@fields properties required property_order; $($fields)*);
+                $crate::SchemaType {
+                    kind: $crate::TypeKind::Object {
+                        properties,
+                        required,
+                    },
+                    description: $crate::__private::doc_text(&[$($doc),*]),
+                    name: <Self as $crate::Schema>::type_name().map(str::to_string),
+                    property_order,
+                    ..Default::default()
+                }
+            }
+
+            fn type_name() -> Option<&'static str> {
+                std::any::type_name::<Self>().rsplit("::").next()
+            }
+        }
+    };
+
+    (@fields $properties:ident $required:ident $order:ident;) => {};
+    (
+        @fields $properties:ident $required:ident $order:ident;
+        $(#[doc = $doc:literal])* $field:ident ?: $ty:ty $(, $($rest:tt)*)?
+    ) => {
+        $crate::impl_schema!(@field $properties $order; [$($doc)*] $field: $ty);
+        $crate::impl_schema!(@fields $properties $required $order; $($($rest)*)?);
+    };
+    (
+        @fields $properties:ident $required:ident $order:ident;
+        $(#[doc = $doc:literal])* $field:ident : $ty:ty $(, $($rest:tt)*)?
+    ) => {
+        $crate::impl_schema!(@field $properties $order; [$($doc)*] $field: $ty);
+        $required.push(stringify!($field).to_string());
+        $crate::impl_schema!(@fields $properties $required $order; $($($rest)*)?);
+    };
+
+    (@field $properties:ident $order:ident; [$($doc:literal)*] $field:ident : $ty:ty) => {
+        let mut field = <$ty as $crate::Schema>::schema();
+        if let Some(description) = $crate::__private::doc_text(&[$($doc),*]) {
+            field.description = Some(description);
+        }
+        $properties.insert(stringify!($field).to_string(), field);
+        $order.push(stringify!($field).to_string());
+    };
+}
+
+/// Doc comment lines joined like the derive joins them
+#[doc(hidden)]
+pub fn doc_text(lines: &[&str]) -> Option<String> {
+    let joined = lines
+        .iter()
+        .map(|line| line.trim())
+        .collect::<Vec<_>>()
+        .join("\n");
+    let joined = joined.trim();
+    (!joined.is_empty()).then(|| joined.to_string())
+}
@@ -0,0 +1,561 @@
+use crate::{REF_PREFERENCE, RefPreference, SchemaType, Tagging, TypeKind, VariantCase};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+/// A canonicalized schema plus the shared definitions its `Ref`s point at
+#[derive(Debug, Clone, PartialEq)]
+pub struct NormalizedSchema {
+    pub root: SchemaType,
+    /// Shared subtrees keyed by the name used in `TypeKind::Ref`
+    pub definitions: BTreeMap<String, SchemaType>,
+}
+
+impl SchemaType {
+    /// Canonicalize the schema tree
+    ///
+    /// - sorts and deduplicates `required` lists
+    /// - collapses single-case variants into their payload, keeping the
+    ///   tag field a tagged variant requires
+    /// - hash-conses structurally identical composite subtrees (objects,
+    ///   enums, variants) that occur more than once into shared definitions
+    ///   referenced by `TypeKind::Ref`
+    ///
+    /// Definitions are named after `SchemaType::name` when available, and
+    /// every `Ref` resolves to one: a recursive type that is used only once,
+    /// the root included, is defined too. A
+    /// [`RefPreference`] overrides the sharing decision for one subtree:
+    /// `Inline` keeps it in place, `AsRef` makes it a definition even when
+    /// it occurs once.
+    pub fn normalize(&self) -> NormalizedSchema {
+        let mut canonical = canonicalize(self);
+        canonical.extensions.remove(REF_PREFERENCE);
+
+        let mut occurrences = HashMap::new();
+        collect_occurrences(&canonical, &mut occurrences);
+
+        let mut interner = Interner {
+            occurrences,
+            names: HashMap::new(),
+            definitions: BTreeMap::new(),
+        };
+        let root = interner.rewrite_children(canonical);
+        let mut definitions = interner.definitions;
+
+        // A recursive type that isn't shared is still written in place; its
+        // `Ref`s need it as a definition too
+        loop {
+            let mut names = BTreeSet::new();
+            ref_names(&root, &mut names);
+            for definition in definitions.values() {
+                ref_names(definition, &mut names);
+            }
+            let missing: Vec<SchemaType> = names
+                .iter()
+                .filter(|name| !definitions.contains_key(*name))
+                .filter_map(|name| {
+                    std::iter::once(&root)
+                        .chain(definitions.values())
+                        .find_map(|schema| find_named(schema, name))
+                        .cloned()
+                })
+                .collect();
+            if missing.is_empty() {
+                break;
+            }
+            for schema in missing {
+                let name = schema.name.clone().unwrap();
+                definitions.insert(name, schema);
+            }
+        }
+
+        NormalizedSchema { root, definitions }
+    }
+
+    /// Apply only the local canonicalization rules of [`normalize`], without
+    /// sharing subtrees
+    ///
+    /// [`normalize`]: SchemaType::normalize
+    pub fn canonicalize(&self) -> SchemaType {
+        canonicalize(self)
+    }
+
+    /// Deterministic structural fingerprint of the schema
+    ///
+    /// Two schemas with the same fingerprint are structurally identical,
+    /// regardless of `HashMap` iteration order.
+    pub fn fingerprint(&self) -> String {
+        let mut out = String::new();
+        write_key(self, true, &mut out);
+        out
+    }
+}
+
+/// Apply the local canonicalization rules bottom-up
+fn canonicalize(schema: &SchemaType) -> SchemaType {
+    let kind = match &schema.kind {
+        TypeKind::Object {
+            properties,
+            required,
+        } => {
+            let mut required = required.clone();
+            required.sort();
+            required.dedup();
+            TypeKind::Object {
+                properties: properties
+                    .iter()
+                    .map(|(k, v)| (k.clone(), canonicalize(v)))
+                    .collect(),
+                required,
+            }
+        }
+        TypeKind::Array { items } => TypeKind::Array {
+            items: Box::new(canonicalize(items)),
+        },
+        TypeKind::Set { items, ordered } => TypeKind::Set {
+            items: Box::new(canonicalize(items)),
+            ordered: *ordered,
+        },
+        TypeKind::Map {
+            key,
+            value,
+            ordered,
+        } => TypeKind::Map {
+            key: Box::new(canonicalize(key)),
+            value: Box::new(canonicalize(value)),
+            ordered: *ordered,
+        },
+        TypeKind::TaggedUnion {
+            tag_field,
+            tag_variants,
+            data_fields,
+        } => TypeKind::TaggedUnion {
+            tag_field: tag_field.clone(),
+            tag_variants: tag_variants.clone(),
+            data_fields: data_fields
+                .iter()
+                .map(|(k, v)| (k.clone(), canonicalize(v)))
+                .collect(),
+        },
+        TypeKind::Variant { cases } if cases.len() == 1 => {
+            if let Some(collapsed) = collapse(schema, &cases[0]) {
+                return collapsed;
+            }
+            TypeKind::Variant {
+                cases: vec![VariantCase {
+                    data: cases[0].data.as_ref().map(canonicalize),
+                    ..cases[0].clone()
+                }],
+            }
+        }
+        TypeKind::Variant { cases } => TypeKind::Variant {
+            cases: cases
+                .iter()
+                .map(|case| VariantCase {
+                    data: case.data.as_ref().map(canonicalize),
+                    ..case.clone()
+                })
+                .collect(),
+        },
+        TypeKind::Result { ok, err } => TypeKind::Result {
+            ok: Box::new(canonicalize(ok)),
+            err: Box::new(canonicalize(err)),
+        },
+        TypeKind::Tuple { fields } => TypeKind::Tuple {
+            fields: fields.iter().map(canonicalize).collect(),
+        },
+        other => other.clone(),
+    };
+
+    SchemaType {
+        kind,
+        ..schema.clone()
+    }
+}
+
+/// The value a single-case variant accepts, written without the variant,
+/// or `None` if its tagging accepts more than one shape
+fn collapse(schema: &SchemaType, case: &VariantCase) -> Option<SchemaType> {
+    let tag = |field: &str| {
+        let value = SchemaType::new(TypeKind::Enum {
+            variants: vec![case.name.clone()],
+        });
+        (field.to_string(), value)
+    };
+    let mut collapsed = match (schema.tagging(), &case.data) {
+        // Just the payload, or `null` for a unit case
+        (Some(Tagging::Untagged), Some(data)) => canonicalize(data),
+        (Some(Tagging::Untagged), None) => SchemaType::new(TypeKind::Null),
+        // A record payload with the tag beside its fields
+        (Some(Tagging::Internal { tag: field }), Some(data)) => {
+            let mut data = canonicalize(data);
+            let TypeKind::Object {
+                properties,
+                required,
+            } = &mut data.kind
+            else {
+                return None;
+            };
+            properties.extend([tag(&field)]);
+            required.push(field);
+            required.sort();
+            required.dedup();
+            data
+        }
+        (
+            Some(Tagging::Adjacent {
+                tag: field,
+                content,
+            }),
+            Some(data),
+        ) => {
+            let mut required = vec![field.clone(), content.clone()];
+            required.sort();
+            SchemaType::new(TypeKind::Object {
+                properties: HashMap::from([tag(&field), (content, canonicalize(data))]),
+                required,
+            })
+        }
+        (Some(Tagging::Internal { tag: field } | Tagging::Adjacent { tag: field, .. }), None) => {
+            SchemaType::new(TypeKind::Object {
+                properties: HashMap::from([tag(&field)]),
+                required: vec![field],
+            })
+        }
+        // A unit case is written as its name
+        (None, None) => SchemaType {
+            kind: TypeKind::Enum {
+                variants: vec![case.name.clone()],
+            },
+            ..schema.clone()
+        },
+        // The case may be `{"type", "data"}` or flattened beside `type`
+        (None, Some(_)) => return None,
+    };
+    collapsed.description = schema
+        .description
+        .clone()
+        .or_else(|| case.description.clone())
+        .or(collapsed.description);
+    collapsed.name = schema.name.clone().or(collapsed.name);
+    Some(collapsed)
+}
+
+/// Names of the `Ref`s anywhere in `schema`
+fn ref_names(schema: &SchemaType, names: &mut BTreeSet<String>) {
+    if let TypeKind::Ref { name } = &schema.kind {
+        names.insert(name.clone());
+    }
+    for child in children(schema) {
+        ref_names(child, names);
+    }
+}
+
+/// The first subtree named `name` that is written out rather than referenced
+fn find_named<'a>(schema: &'a SchemaType, name: &str) -> Option<&'a SchemaType> {
+    if schema.name.as_deref() == Some(name) && !matches!(schema.kind, TypeKind::Ref { .. }) {
+        return Some(schema);
+    }
+    children(schema)
+        .into_iter()
+        .find_map(|child| find_named(child, name))
+}
+
+/// Only composite types are worth sharing, unless marked `AsRef`
+fn is_shareable(schema: &SchemaType) -> bool {
+    match &schema.kind {
+        TypeKind::Object { properties, .. } => !properties.is_empty(),
+        TypeKind::Enum { .. } | TypeKind::Variant { .. } | TypeKind::TaggedUnion { .. } => true,
+        // Scalars and collections only when asked, e.g. for named aliases
+        TypeKind::Ref { .. } => false,
+        _ => schema.ref_preference() == Some(RefPreference::AsRef),
+    }
+}
+
+/// Record the description of every occurrence of each shareable subtree
+fn collect_occurrences(
+    schema: &SchemaType,
+    occurrences: &mut HashMap<String, Vec<Option<String>>>,
+) {
+    if is_shareable(schema) && schema.ref_preference() != Some(RefPreference::Inline) {
+        occurrences
+            .entry(sharing_key(schema))
+            .or_default()
+            .push(schema.description.clone());
+    }
+    for child in children(schema) {
+        collect_occurrences(child, occurrences);
+    }
+}
+
+fn children(schema: &SchemaType) -> Vec<&SchemaType> {
+    match &schema.kind {
+        TypeKind::Object { properties, .. } => properties.values().collect(),
+        TypeKind::Array { items } | TypeKind::Set { items, .. } => vec![items],
+        TypeKind::Map { key, value, .. } => vec![key, value],
+        TypeKind::TaggedUnion { data_fields, .. } => data_fields.values().collect(),
+        TypeKind::Variant { cases } => cases.iter().filter_map(|c| c.data.as_ref()).collect(),
+        TypeKind::Result { ok, err } => vec![ok, err],
+        TypeKind::Tuple { fields } => fields.iter().collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Structural key ignoring the outermost description, which is usually a
+/// per-field doc comment rather than part of the type
+fn sharing_key(schema: &SchemaType) -> String {
+    let mut out = String::new();
+    write_key(schema, false, &mut out);
+    out
+}
+
+fn write_key(schema: &SchemaType, with_description: bool, out: &mut String) {
+    if with_description && let Some(desc) = &schema.description {
+        out.push_str(&format!("desc{:?}:", desc));
+    }
+    if let Some(name) = &schema.name {
+        out.push_str(&format!("name{:?}:", name));
+    }
+    if let Some(title) = &schema.title {
+        out.push_str(&format!("title{:?}:", title));
+    }
+    if let Some(default) = &schema.default {
+        out.push_str(&format!("default{}:", default));
+    }
+    if !schema.constraints.is_empty() {
+        out.push_str(&format!("constraints{:?}:", schema.constraints));
+    }
+    // A sharing preference says where the type goes, not what it is
+    let extensions: BTreeMap<_, _> = schema
+        .extensions
+        .iter()
+        .filter(|(key, _)| *key != REF_PREFERENCE)
+        .collect();
+    if !extensions.is_empty() {
+        out.push_str(&format!("ext{:?}:", extensions));
+    }
+
+    match &schema.kind {
+        TypeKind::Object {
+            properties,
+            required,
+        } => {
+            let mut props: Vec<_> = properties.iter().collect();
+            props.sort_by_key(|(name, _)| *name);
+            let mut required = required.clone();
+            required.sort();
+            out.push_str("object{");
+            for (name, prop) in props {
+                out.push_str(&format!("{:?}:", name));
+                write_key(prop, true, out);
+                out.push(',');
+            }
+            out.push_str(&format!("}}required{:?}", required));
+            if !schema.property_order.is_empty() {
+                out.push_str(&format!("order{:?}", schema.property_order));
+            }
+        }
+        TypeKind::Array { items } => {
+            out.push_str("array<");
+            write_key(items, true, out);
+            out.push('>');
+        }
+        TypeKind::Set { items, ordered } => {
+            out.push_str(&format!("set{}<", ordered));
+            write_key(items, true, out);
+            out.push('>');
+        }
+        TypeKind::Map {
+            key,
+            value,
+            ordered,
+        } => {
+            out.push_str(&format!("map{}<", ordered));
+            write_key(key, true, out);
+            out.push(',');
+            write_key(value, true, out);
+            out.push('>');
+        }
+        TypeKind::TaggedUnion {
+            tag_field,
+            tag_variants,
+            data_fields,
+        } => {
+            let mut fields: Vec<_> = data_fields.iter().collect();
+            fields.sort_by_key(|(name, _)| *name);
+            out.push_str(&format!("tagged{:?}{:?}{{", tag_field, tag_variants));
+            for (name, field) in fields {
+                out.push_str(&format!("{:?}:", name));
+                write_key(field, true, out);
+                out.push(',');
+            }
+            out.push('}');
+        }
+        TypeKind::Variant { cases } => {
+            out.push_str("variant{");
+            for case in cases {
+                out.push_str(&format!("{:?}{:?}", case.name, case.description));
+                if let Some(data) = &case.data {
+                    out.push('(');
+                    write_key(data, true, out);
+                    out.push(')');
+                }
+                out.push(',');
+            }
+            out.push('}');
+        }
+        TypeKind::Result { ok, err } => {
+            out.push_str("result<");
+            write_key(ok, true, out);
+            out.push(',');
+            write_key(err, true, out);
+            out.push('>');
+        }
+        TypeKind::Tuple { fields } => {
+            out.push_str("tuple<");
+            for field in fields {
+                write_key(field, true, out);
+                out.push(',');
+            }
+            out.push('>');
+        }
+        other => out.push_str(&format!("{:?}", other)),
+    }
+}
+
+struct Interner {
+    /// Sharing key -> descriptions of each occurrence
+    occurrences: HashMap<String, Vec<Option<String>>>,
+    /// Sharing key -> definition name
+    names: HashMap<String, String>,
+    definitions: BTreeMap<String, SchemaType>,
+}
+
+impl Interner {
+    /// Rewrite a subtree, replacing it with a `Ref` if it is shared
+    fn rewrite(&mut self, mut schema: SchemaType) -> SchemaType {
+        let preference = schema.ref_preference();
+        let shareable = is_shareable(&schema);
+        schema.extensions.remove(REF_PREFERENCE);
+        if !shareable || preference == Some(RefPreference::Inline) {
+            return self.rewrite_children(schema);
+        }
+
+        let key = sharing_key(&schema);
+        let min_occurrences = match preference {
+            Some(RefPreference::AsRef) => 1,
+            _ => 2,
+        };
+        let Some(descriptions) = self
+            .occurrences
+            .get(&key)
+            .filter(|d| d.len() >= min_occurrences)
+        else {
+            return self.rewrite_children(schema);
+        };
+
+        // The definition only keeps a description every occurrence agrees on
+        let definition_description = if descriptions.iter().all(|d| *d == descriptions[0]) {
+            descriptions[0].clone()
+        } else {
+            None
+        };
+
+        let description = schema.description.clone();
+        let name = match self.names.get(&key) {
+            Some(name) => name.clone(),
+            None => {
+                let name = self.fresh_name(schema.name.as_deref());
+                self.names.insert(key, name.clone());
+                let mut definition = self.rewrite_children(schema);
+                definition.description = definition_description.clone();
+                self.definitions.insert(name.clone(), definition);
+                name
+            }
+        };
+
+        // Keep per-use descriptions that differ from the shared definition
+        SchemaType {
+            kind: TypeKind::Ref { name },
+            description: if description != definition_description {
+                description
+            } else {
+                None
+            },
+            ..Default::default()
+        }
+    }
+
+    fn rewrite_children(&mut self, schema: SchemaType) -> SchemaType {
+        let kind = match schema.kind {
+            TypeKind::Object {
+                properties,
+                required,
+            } => TypeKind::Object {
+                properties: properties
+                    .into_iter()
+                    .map(|(k, v)| (k, self.rewrite(v)))
+                    .collect(),
+                required,
+            },
+            TypeKind::Array { items } => TypeKind::Array {
+                items: Box::new(self.rewrite(*items)),
+            },
+            TypeKind::Set { items, ordered } => TypeKind::Set {
+                items: Box::new(self.rewrite(*items)),
+                ordered,
+            },
+            TypeKind::Map {
+                key,
+                value,
+                ordered,
+            } => TypeKind::Map {
+                key: Box::new(self.rewrite(*key)),
+                value: Box::new(self.rewrite(*value)),
+                ordered,
+            },
+            TypeKind::TaggedUnion {
+                tag_field,
+                tag_variants,
+                data_fields,
+            } => TypeKind::TaggedUnion {
+                tag_field,
+                tag_variants,
+                data_fields: data_fields
+                    .into_iter()
+                    .map(|(k, v)| (k, self.rewrite(v)))
+                    .collect(),
+            },
+            TypeKind::Variant { cases } => TypeKind::Variant {
+                cases: cases
+                    .into_iter()
+                    .map(|case| VariantCase {
+                        data: case.data.map(|d| self.rewrite(d)),
+                        ..case
+                    })
+                    .collect(),
+            },
+            TypeKind::Result { ok, err } => TypeKind::Result {
+                ok: Box::new(self.rewrite(*ok)),
+                err: Box::new(self.rewrite(*err)),
+            },
+            TypeKind::Tuple { fields } => TypeKind::Tuple {
+                fields: fields.into_iter().map(|f| self.rewrite(f)).collect(),
+            },
+            other => other,
+        };
+
+        SchemaType { kind, ..schema }
+    }
+
+    fn fresh_name(&self, preferred: Option<&str>) -> String {
+        let taken = |candidate: &str| self.names.values().any(|name| name == candidate);
+
+        let base = preferred.unwrap_or("Schema");
+        if preferred.is_some() && !taken(base) {
+            return base.to_string();
+        }
+        (1..)
+            .map(|i| format!("{}{}", base, i))
+            .find(|candidate| !taken(candidate))
+            .expect("unbounded name search")
+    }
+}
@@ -0,0 +1,214 @@
+use crate::{SchemaType, TypeKind, VariantCase};
+use std::collections::HashMap;
+
+/// What to emit in place of a subtree a generator should not expand
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Fallback {
+    /// A `Ref` to the named type, or a generic object when the subtree is unnamed
+    #[default]
+    Ref,
+    /// A generic object with no declared properties
+    AnyObject,
+}
+
+/// Options shared by all generators to keep output bounded
+///
+/// Recursive derived types are already cut into `Ref`s while the schema is
+/// built; `on_cycle` decides how generators render those references.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct GenerateOptions {
+    /// Maximum nesting depth, counting the root as depth 1 (`None` = unlimited)
+    pub max_depth: Option<usize>,
+    pub on_depth_exceeded: Fallback,
+    pub on_cycle: Fallback,
+    /// Enums with more values than this become plain strings that list only
+    /// the first values in their description (`None` = unlimited)
+    ///
+    /// Keeps prompts small for enums like country or currency codes; validate
+    /// input against the original schema to enforce the full set.
+    pub max_enum_variants: Option<usize>,
+}
+
+impl GenerateOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = Some(max_depth);
+        self
+    }
+
+    pub fn on_depth_exceeded(mut self, fallback: Fallback) -> Self {
+        self.on_depth_exceeded = fallback;
+        self
+    }
+
+    pub fn on_cycle(mut self, fallback: Fallback) -> Self {
+        self.on_cycle = fallback;
+        self
+    }
+
+    pub fn max_enum_variants(mut self, max: usize) -> Self {
+        self.max_enum_variants = Some(max);
+        self
+    }
+
+    /// Rewrite a schema so it respects these options
+    ///
+    /// Generators call this before rendering; the result contains no node
+    /// deeper than `max_depth`, and cycle references follow `on_cycle`.
+    pub fn apply(&self, schema: &SchemaType) -> SchemaType {
+        self.apply_at(schema, 1)
+    }
+
+    fn apply_at(&self, schema: &SchemaType, depth: usize) -> SchemaType {
+        if let TypeKind::Ref { .. } = schema.kind {
+            return match self.on_cycle {
+                Fallback::Ref => schema.clone(),
+                Fallback::AnyObject => any_object(schema),
+            };
+        }
+
+        if self.max_depth.is_some_and(|max| depth > max) {
+            return match (self.on_depth_exceeded, &schema.name) {
+                (Fallback::Ref, Some(name)) => SchemaType {
+                    kind: TypeKind::Ref { name: name.clone() },
+                    description: schema.description.clone(),
+                    ..Default::default()
+                },
+                _ => any_object(schema),
+            };
+        }
+
+        let child = |s: &SchemaType| self.apply_at(s, depth + 1);
+        let boxed = |s: &SchemaType| Box::new(child(s));
+        let map = |m: &HashMap<String, SchemaType>| {
+            m.iter()
+                .map(|(k, v)| (k.clone(), child(v)))
+                .collect::<HashMap<_, _>>()
+        };
+
+        let kind = match &schema.kind {
+            TypeKind::Object {
+                properties,
+                required,
+            } => TypeKind::Object {
+                properties: map(properties),
+                required: required.clone(),
+            },
+            TypeKind::Array { items } => TypeKind::Array {
+                items: boxed(items),
+            },
+            TypeKind::Set { items, ordered } => TypeKind::Set {
+                items: boxed(items),
+                ordered: *ordered,
+            },
+            TypeKind::Map {
+                key,
+                value,
+                ordered,
+            } => TypeKind::Map {
+                key: boxed(key),
+                value: boxed(value),
+                ordered: *ordered,
+            },
+            TypeKind::TaggedUnion {
+                tag_field,
+                tag_variants,
+                data_fields,
+            } => TypeKind::TaggedUnion {
+                tag_field: tag_field.clone(),
+                tag_variants: tag_variants.clone(),
+                data_fields: map(data_fields),
+            },
+            TypeKind::Variant { cases } => TypeKind::Variant {
+                cases: cases
+                    .iter()
+                    .map(|case| VariantCase {
+                        data: case.data.as_ref().map(child),
+                        ..case.clone()
+                    })
+                    .collect(),
+            },
+            TypeKind::Result { ok, err } => TypeKind::Result {
+                ok: boxed(ok),
+                err: boxed(err),
+            },
+            TypeKind::Tuple { fields } => TypeKind::Tuple {
+                fields: fields.iter().map(child).collect(),
+            },
+            TypeKind::Enum { variants } => {
+                if let Some(max) = self.max_enum_variants.filter(|max| variants.len() > *max) {
+                    return truncated_enum(schema, variants, max);
+                }
+                schema.kind.clone()
+            }
+            other => other.clone(),
+        };
+
+        SchemaType {
+            kind,
+            ..schema.clone()
+        }
+    }
+}
+
+/// A plain string listing the first `max` allowed values in its description
+fn truncated_enum(schema: &SchemaType, variants: &[String], max: usize) -> SchemaType {
+    let listed = variants[..max].join(", ");
+    let summary = format!("One of: {}, ... ({} more)", listed, variants.len() - max);
+
+    SchemaType {
+        kind: TypeKind::String,
+        description: Some(match &schema.description {
+            Some(description) => format!("{}\n\n{}", description, summary),
+            None => summary,
+        }),
+        ..schema.clone()
+    }
+}
+
+fn any_object(schema: &SchemaType) -> SchemaType {
+    SchemaType {
+        kind: TypeKind::Object {
+            properties: HashMap::new(),
+            required: Vec::new(),
+        },
+        description: schema.description.clone(),
+        ..Default::default()
+    }
+}
+
+thread_local! {
+    static IN_PROGRESS: std::cell::RefCell<Vec<&'static str>> =
+        const { std::cell::RefCell::new(Vec::new()) };
+}
+
+/// Build a derived type's schema, cutting recursion into a `Ref`
+///
+/// `key` identifies the type (including generic arguments); re-entering a
+/// type whose schema is still being built yields `Ref { name }` instead of
+/// recursing forever.
+pub fn recursion_guard(
+    key: &'static str,
+    name: &str,
+    build: impl FnOnce() -> SchemaType,
+) -> SchemaType {
+    if IN_PROGRESS.with(|stack| stack.borrow().contains(&key)) {
+        return SchemaType::new(TypeKind::Ref {
+            name: name.to_string(),
+        });
+    }
+
+    struct Pop;
+    impl Drop for Pop {
+        fn drop(&mut self) {
+            IN_PROGRESS.with(|stack| stack.borrow_mut().pop());
+        }
+    }
+
+    IN_PROGRESS.with(|stack| stack.borrow_mut().push(key));
+    let _pop = Pop;
+    build()
+}
@@ -0,0 +1,250 @@
+use crate::{SchemaType, TypeKind};
+use std::fmt;
+use std::str::FromStr;
+
+/// A single step into a schema tree
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PathSegment {
+    /// Object property (or TaggedUnion data field) by name
+    Property(String),
+    /// Element type of an Array or Set
+    Items,
+    /// Key type of a Map
+    Key,
+    /// Value type of a Map
+    Value,
+    /// Payload of a Variant case by case name
+    Case(String),
+    /// Tuple field by index
+    Field(usize),
+    /// Ok type of a Result
+    Ok,
+    /// Err type of a Result
+    Err,
+}
+
+/// Typed path into a schema tree, rendered as a JSON pointer
+///
+/// ```
+/// use schema::SchemaPath;
+///
+/// let path = SchemaPath::root().property("address").property("city");
+/// assert_eq!(path.to_string(), "/properties/address/properties/city");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct SchemaPath {
+    segments: Vec<PathSegment>,
+}
+
+/// Error returned when a JSON pointer can't be parsed into a SchemaPath
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParsePathError {
+    pub pointer: String,
+    pub reason: String,
+}
+
+impl fmt::Display for ParsePathError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid schema path {:?}: {}", self.pointer, self.reason)
+    }
+}
+
+impl std::error::Error for ParsePathError {}
+
+impl SchemaPath {
+    /// The empty path, pointing at the root schema
+    pub fn root() -> Self {
+        Self::default()
+    }
+
+    pub fn segments(&self) -> &[PathSegment] {
+        &self.segments
+    }
+
+    pub fn push(mut self, segment: PathSegment) -> Self {
+        self.segments.push(segment);
+        self
+    }
+
+    pub fn property(self, name: &str) -> Self {
+        self.push(PathSegment::Property(name.to_string()))
+    }
+
+    pub fn items(self) -> Self {
+        self.push(PathSegment::Items)
+    }
+
+    pub fn key(self) -> Self {
+        self.push(PathSegment::Key)
+    }
+
+    pub fn value(self) -> Self {
+        self.push(PathSegment::Value)
+    }
+
+    pub fn case(self, name: &str) -> Self {
+        self.push(PathSegment::Case(name.to_string()))
+    }
+
+    pub fn field(self, index: usize) -> Self {
+        self.push(PathSegment::Field(index))
+    }
+
+    pub fn ok(self) -> Self {
+        self.push(PathSegment::Ok)
+    }
+
+    pub fn err(self) -> Self {
+        self.push(PathSegment::Err)
+    }
+}
+
+impl fmt::Display for SchemaPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for segment in &self.segments {
+            match segment {
+                PathSegment::Property(name) => write!(f, "/properties/{}", escape(name))?,
+                PathSegment::Items => write!(f, "/items")?,
+                PathSegment::Key => write!(f, "/key")?,
+                PathSegment::Value => write!(f, "/value")?,
+                PathSegment::Case(name) => write!(f, "/cases/{}", escape(name))?,
+                PathSegment::Field(index) => write!(f, "/fields/{}", index)?,
+                PathSegment::Ok => write!(f, "/ok")?,
+                PathSegment::Err => write!(f, "/err")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for SchemaPath {
+    type Err = ParsePathError;
+
+    fn from_str(pointer: &str) -> Result<Self, Self::Err> {
+        let error = |reason: &str| ParsePathError {
+            pointer: pointer.to_string(),
+            reason: reason.to_string(),
+        };
+
+        if pointer.is_empty() {
+            return Ok(SchemaPath::root());
+        }
+        let Some(rest) = pointer.strip_prefix('/') else {
+            return Err(error("must start with '/'"));
+        };
+
+        let mut tokens = rest.split('/').map(unescape);
+        let mut path = SchemaPath::root();
+
+        while let Some(token) = tokens.next() {
+            let mut operand = |what: &str| {
+                tokens
+                    .next()
+                    .ok_or_else(|| error(&format!("'{}' must be followed by a {}", token, what)))
+            };
+
+            let segment = match token.as_str() {
+                "properties" => PathSegment::Property(operand("property name")?),
+                "cases" => PathSegment::Case(operand("case name")?),
+                "fields" => {
+                    let index = operand("field index")?;
+                    PathSegment::Field(index.parse().map_err(|_| error("bad field index"))?)
+                }
+                "items" => PathSegment::Items,
+                "key" => PathSegment::Key,
+                "value" => PathSegment::Value,
+                "ok" => PathSegment::Ok,
+                "err" => PathSegment::Err,
+                other => return Err(error(&format!("unknown segment '{}'", other))),
+            };
+            path.segments.push(segment);
+        }
+
+        Ok(path)
+    }
+}
+
+/// Escape a reference token per RFC 6901
+fn escape(token: &str) -> String {
+    token.replace('~', "~0").replace('/', "~1")
+}
+
+fn unescape(token: &str) -> String {
+    token.replace("~1", "/").replace("~0", "~")
+}
+
+impl SchemaType {
+    /// Sub-schema at a JSON pointer such as `/properties/address/properties/city`
+    ///
+    /// Returns `None` if the pointer is malformed or doesn't exist in this schema.
+    pub fn at_path(&self, pointer: &str) -> Option<&SchemaType> {
+        self.at(&pointer.parse().ok()?)
+    }
+
+    /// Mutable sub-schema at a JSON pointer
+    pub fn at_path_mut(&mut self, pointer: &str) -> Option<&mut SchemaType> {
+        self.at_mut(&pointer.parse().ok()?)
+    }
+
+    /// Sub-schema at a typed path
+    pub fn at(&self, path: &SchemaPath) -> Option<&SchemaType> {
+        path.segments
+            .iter()
+            .try_fold(self, |current, segment| current.child(segment))
+    }
+
+    /// Mutable sub-schema at a typed path
+    pub fn at_mut(&mut self, path: &SchemaPath) -> Option<&mut SchemaType> {
+        path.segments
+            .iter()
+            .try_fold(self, |current, segment| current.child_mut(segment))
+    }
+
+    fn child(&self, segment: &PathSegment) -> Option<&SchemaType> {
+        match (&self.kind, segment) {
+            (TypeKind::Object { properties, .. }, PathSegment::Property(name)) => {
+                properties.get(name)
+            }
+            (TypeKind::TaggedUnion { data_fields, .. }, PathSegment::Property(name)) => {
+                data_fields.get(name)
+            }
+            (TypeKind::Array { items } | TypeKind::Set { items, .. }, PathSegment::Items) => {
+                Some(items)
+            }
+            (TypeKind::Map { key, .. }, PathSegment::Key) => Some(key),
+            (TypeKind::Map { value, .. }, PathSegment::Value) => Some(value),
+            (TypeKind::Variant { cases }, PathSegment::Case(name)) => cases
+                .iter()
+                .find(|case| &case.name == name)
+                .and_then(|case| case.data.as_ref()),
+            (TypeKind::Tuple { fields }, PathSegment::Field(index)) => fields.get(*index),
+            (TypeKind::Result { ok, .. }, PathSegment::Ok) => Some(ok),
+            (TypeKind::Result { err, .. }, PathSegment::Err) => Some(err),
+            _ => None,
+        }
+    }
+
+    fn child_mut(&mut self, segment: &PathSegment) -> Option<&mut SchemaType> {
+        match (&mut self.kind, segment) {
+            (TypeKind::Object { properties, .. }, PathSegment::Property(name)) => {
+                properties.get_mut(name)
+            }
+            (TypeKind::TaggedUnion { data_fields, .. }, PathSegment::Property(name)) => {
+                data_fields.get_mut(name)
+            }
+            (TypeKind::Array { items } | TypeKind::Set { items, .. }, PathSegment::Items) => {
+                Some(items)
+            }
+            (TypeKind::Map { key, .. }, PathSegment::Key) => Some(key),
+            (TypeKind::Map { value, .. }, PathSegment::Value) => Some(value),
+            (TypeKind::Variant { cases }, PathSegment::Case(name)) => cases
+                .iter_mut()
+                .find(|case| &case.name == name)
+                .and_then(|case| case.data.as_mut()),
+            (TypeKind::Tuple { fields }, PathSegment::Field(index)) => fields.get_mut(*index),
+            (TypeKind::Result { ok, .. }, PathSegment::Ok) => Some(ok),
+            (TypeKind::Result { err, .. }, PathSegment::Err) => Some(err),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,56 @@
+use std::collections::HashSet;
+use std::sync::{Mutex, OnceLock};
+
+/// Name of a generic type's instantiation, e.g. `Page_User` for
+/// `Page<User>`, so each instantiation gets its own definition
+///
+/// Names are interned, leaking one string per distinct instantiation.
+pub fn generic_type_name(base: &str, args: &[&str]) -> &'static str {
+    let name = std::iter::once(base.to_string())
+        .chain(args.iter().map(|arg| short_type_name(arg)))
+        .collect::<Vec<_>>()
+        .join("_");
+
+    static NAMES: OnceLock<Mutex<HashSet<&'static str>>> = OnceLock::new();
+    let mut names = NAMES.get_or_init(Default::default).lock().unwrap();
+    match names.get(name.as_str()) {
+        Some(interned) => interned,
+        None => {
+            let interned: &'static str = Box::leak(name.into_boxed_str());
+            names.insert(interned);
+            interned
+        }
+    }
+}
+
+/// `std::any::type_name` output without module paths and with generic
+/// punctuation folded into underscores: `alloc::vec::Vec<app::User>` becomes
+/// `Vec_User`
+fn short_type_name(type_name: &str) -> String {
+    let mut out = String::new();
+    let mut word = String::new();
+    let mut chars = type_name.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c.is_alphanumeric() || c == '_' {
+            word.push(c);
+        } else if c == ':' && chars.peek() == Some(&':') {
+            // Drop the path segment before `::`
+            chars.next();
+            word.clear();
+        } else {
+            flush_word(&mut out, &mut word);
+        }
+    }
+    flush_word(&mut out, &mut word);
+    out
+}
+
+fn flush_word(out: &mut String, word: &mut String) {
+    if !word.is_empty() {
+        if !out.is_empty() {
+            out.push('_');
+        }
+        out.push_str(word);
+        word.clear();
+    }
+}
@@ -0,0 +1,487 @@
+//! Validate JSON values against schemas
+
+use crate::{Constraints, IntegerKind, NormalizedSchema, SchemaType, Tagging, TypeKind};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashSet};
+use std::fmt;
+
+/// A single validation failure
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValidationError {
+    /// JSON pointer into the validated value, e.g. `/items/0/age`
+    pub path: String,
+    pub message: String,
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let path = if self.path.is_empty() {
+            "/"
+        } else {
+            &self.path
+        };
+        write!(f, "{}: {}", path, self.message)
+    }
+}
+
+impl std::error::Error for ValidationError {}
+
+/// Validate a value against a schema, collecting every failure
+///
+/// `Ref` nodes are accepted as-is; use [`validate_normalized`] to resolve them.
+pub fn validate(schema: &SchemaType, value: &Value) -> Result<(), Vec<ValidationError>> {
+    run(schema, &BTreeMap::new(), value)
+}
+
+/// Validate a value against a normalized schema, resolving `Ref`s through
+/// its definitions
+pub fn validate_normalized(
+    schema: &NormalizedSchema,
+    value: &Value,
+) -> Result<(), Vec<ValidationError>> {
+    run(&schema.root, &schema.definitions, value)
+}
+
+fn run(
+    schema: &SchemaType,
+    definitions: &BTreeMap<String, SchemaType>,
+    value: &Value,
+) -> Result<(), Vec<ValidationError>> {
+    let mut validator = Validator {
+        definitions,
+        errors: Vec::new(),
+    };
+    validator.check(schema, value, &mut String::new());
+
+    if validator.errors.is_empty() {
+        Ok(())
+    } else {
+        Err(validator.errors)
+    }
+}
+
+/// Check an integer value against a kind's signedness and width
+pub fn check_integer(kind: IntegerKind, value: &Value) -> Result<(), String> {
+    let in_range = match (value.as_i64(), value.as_u64()) {
+        (Some(v), _) => (kind.min()..=kind.max()).contains(&(v as i128)),
+        (None, Some(v)) => (kind.min()..=kind.max()).contains(&(v as i128)),
+        (None, None) => {
+            return Err(format!(
+                "expected {} integer, found {}",
+                kind.rust_name(),
+                describe(value)
+            ));
+        }
+    };
+
+    if in_range {
+        Ok(())
+    } else {
+        Err(format!(
+            "{} is out of range for {} (allowed {}..={})",
+            value,
+            kind.rust_name(),
+            kind.min(),
+            kind.max()
+        ))
+    }
+}
+
+fn has_duplicates(items: &[Value]) -> bool {
+    let mut seen = HashSet::new();
+    items.iter().any(|item| !seen.insert(item.to_string()))
+}
+
+fn describe(value: &Value) -> String {
+    match value {
+        Value::Null => "null".to_string(),
+        Value::Bool(_) => "boolean".to_string(),
+        Value::Number(n) => format!("number {}", n),
+        Value::String(_) => "string".to_string(),
+        Value::Array(_) => "array".to_string(),
+        Value::Object(_) => "object".to_string(),
+    }
+}
+
+struct Validator<'a> {
+    definitions: &'a BTreeMap<String, SchemaType>,
+    errors: Vec<ValidationError>,
+}
+
+impl Validator<'_> {
+    fn error(&mut self, path: &str, message: String) {
+        self.errors.push(ValidationError {
+            path: path.to_string(),
+            message,
+        });
+    }
+
+    fn mismatch(&mut self, path: &str, expected: &str, value: &Value) {
+        self.error(
+            path,
+            format!("expected {}, found {}", expected, describe(value)),
+        );
+    }
+
+    /// Validate `value` at `path` (a JSON pointer that is restored on return)
+    fn check(&mut self, schema: &SchemaType, value: &Value, path: &mut String) {
+        if !schema.constraints.is_empty() {
+            self.check_constraints(&schema.constraints, value, path);
+        }
+
+        match &schema.kind {
+            TypeKind::String => {
+                if !value.is_string() {
+                    self.mismatch(path, "string", value);
+                }
+            }
+            TypeKind::Integer(kind) => {
+                if let Err(message) = check_integer(*kind, value) {
+                    self.error(path, message);
+                }
+            }
+            TypeKind::Number(_) => {
+                if !value.is_number() {
+                    self.mismatch(path, "number", value);
+                }
+            }
+            TypeKind::Boolean => {
+                if !value.is_boolean() {
+                    self.mismatch(path, "boolean", value);
+                }
+            }
+            TypeKind::Null => {
+                if !value.is_null() {
+                    self.mismatch(path, "null", value);
+                }
+            }
+            TypeKind::Object {
+                properties,
+                required,
+            } => {
+                let Some(obj) = value.as_object() else {
+                    return self.mismatch(path, "object", value);
+                };
+                for name in required {
+                    if !obj.contains_key(name) {
+                        self.error(path, format!("missing required property {:?}", name));
+                    }
+                }
+                for (name, prop_value) in obj {
+                    match properties.get(name) {
+                        Some(prop) => {
+                            self.nested(path, name, |this, path| this.check(prop, prop_value, path))
+                        }
+                        None if schema.denies_unknown_fields() => {
+                            self.nested(path, name, |this, path| {
+                                this.error(path, "unknown property".to_string())
+                            })
+                        }
+                        None => {}
+                    }
+                }
+            }
+            TypeKind::Array { items } => {
+                let Some(array) = value.as_array() else {
+                    return self.mismatch(path, "array", value);
+                };
+                self.check_items(items, array, path);
+            }
+            TypeKind::Set { items, .. } => {
+                let Some(array) = value.as_array() else {
+                    return self.mismatch(path, "array", value);
+                };
+                // `uniqueItems` reports duplicates itself
+                if !schema.constraints.unique_items && has_duplicates(array) {
+                    self.error(path, "expected unique items".to_string());
+                }
+                self.check_items(items, array, path);
+            }
+            TypeKind::Map { key, value: v, .. } => {
+                if matches!(key.kind, TypeKind::String | TypeKind::Enum { .. }) {
+                    let Some(obj) = value.as_object() else {
+                        return self.mismatch(path, "object", value);
+                    };
+                    for (k, entry) in obj {
+                        self.nested(path, k, |this, path| {
+                            this.check(key, &Value::String(k.clone()), path);
+                            this.check(v, entry, path);
+                        });
+                    }
+                } else {
+                    // Non-string keys are encoded as an array of [key, value] pairs
+                    let pair = SchemaType::new(TypeKind::Tuple {
+                        fields: vec![(**key).clone(), (**v).clone()],
+                    });
+                    let Some(array) = value.as_array() else {
+                        return self.mismatch(path, "array of [key, value] pairs", value);
+                    };
+                    self.check_items(&pair, array, path);
+                }
+            }
+            TypeKind::Enum { variants } => match value.as_str() {
+                Some(s) if variants.iter().any(|v| v == s) => {}
+                Some(s) => self.error(
+                    path,
+                    format!("{:?} is not one of {}", s, variants.join(", ")),
+                ),
+                None => self.mismatch(path, "string", value),
+            },
+            TypeKind::TaggedUnion {
+                tag_field,
+                tag_variants,
+                data_fields,
+            } => {
+                let Some(obj) = value.as_object() else {
+                    return self.mismatch(path, "object", value);
+                };
+                match obj.get(tag_field).and_then(Value::as_str) {
+                    Some(tag) if tag_variants.iter().any(|v| v == tag) => {}
+                    _ => self.error(
+                        path,
+                        format!(
+                            "expected {:?} to be one of {}",
+                            tag_field,
+                            tag_variants.join(", ")
+                        ),
+                    ),
+                }
+                for (name, field_value) in obj {
+                    if let Some(field) = data_fields.get(name) {
+                        self.nested(path, name, |this, path| {
+                            this.check(field, field_value, path)
+                        });
+                    }
+                }
+            }
+            TypeKind::Variant { cases } => match schema.tagging() {
+                Some(tagging) => self.check_tagged_variant(cases, &tagging, value, path),
+                None => self.check_variant(cases, value, path),
+            },
+            TypeKind::Result { ok, err } => {
+                let obj = value.as_object().filter(|obj| obj.len() == 1);
+                match obj.and_then(|obj| obj.iter().next()) {
+                    Some((k, v)) if k == "ok" => {
+                        self.nested(path, k, |this, path| this.check(ok, v, path))
+                    }
+                    Some((k, v)) if k == "error" => {
+                        self.nested(path, k, |this, path| this.check(err, v, path))
+                    }
+                    _ => self.mismatch(
+                        path,
+                        "object with exactly one of \"ok\" or \"error\"",
+                        value,
+                    ),
+                }
+            }
+            TypeKind::Tuple { fields } => {
+                let Some(array) = value.as_array() else {
+                    return self.mismatch(path, "array", value);
+                };
+                if array.len() != fields.len() {
+                    return self.error(
+                        path,
+                        format!(
+                            "expected tuple of {} items, found {}",
+                            fields.len(),
+                            array.len()
+                        ),
+                    );
+                }
+                for (index, (field, item)) in fields.iter().zip(array).enumerate() {
+                    self.nested(path, &index.to_string(), |this, path| {
+                        this.check(field, item, path)
+                    });
+                }
+            }
+            TypeKind::Ref { name } => {
+                if let Some(definition) = self.definitions.get(name) {
+                    self.check(definition, value, path);
+                }
+            }
+        }
+    }
+
+    /// Numeric bounds, string lengths, item counts and uniqueness; `pattern`
+    /// and `format` are not checked
+    fn check_constraints(&mut self, constraints: &Constraints, value: &Value, path: &str) {
+        if let Some(n) = value.as_f64() {
+            if let Some(min) = constraints.minimum
+                && n < min
+            {
+                self.error(path, format!("{} is less than the minimum {}", value, min));
+            }
+            if let Some(max) = constraints.maximum
+                && n > max
+            {
+                self.error(
+                    path,
+                    format!("{} is greater than the maximum {}", value, max),
+                );
+            }
+        }
+
+        let (len, unit, min, max) = match value {
+            Value::String(s) => (
+                s.chars().count(),
+                "characters",
+                constraints.min_length,
+                constraints.max_length,
+            ),
+            Value::Array(items) => {
+                if constraints.unique_items && has_duplicates(items) {
+                    self.error(path, "expected unique items".to_string());
+                }
+                (
+                    items.len(),
+                    "items",
+                    constraints.min_items,
+                    constraints.max_items,
+                )
+            }
+            _ => return,
+        };
+        if let Some(min) = min
+            && len < min
+        {
+            self.error(
+                path,
+                format!("expected at least {} {}, found {}", min, unit, len),
+            );
+        }
+        if let Some(max) = max
+            && len > max
+        {
+            self.error(
+                path,
+                format!("expected at most {} {}, found {}", max, unit, len),
+            );
+        }
+    }
+
+    fn check_items(&mut self, items: &SchemaType, array: &[Value], path: &mut String) {
+        for (index, item) in array.iter().enumerate() {
+            self.nested(path, &index.to_string(), |this, path| {
+                this.check(items, item, path)
+            });
+        }
+    }
+
+    /// Accepts the tag-based shapes the backends emit: a bare string for unit
+    /// cases, `{"type": name, "data": payload}`, or `{"type": name, ...fields}`
+    fn check_variant(&mut self, cases: &[crate::VariantCase], value: &Value, path: &mut String) {
+        let names = || {
+            cases
+                .iter()
+                .map(|c| c.name.as_str())
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+
+        let (tag, obj) = match value {
+            Value::String(s) => (s.as_str(), None),
+            Value::Object(obj) => match obj.get("type").and_then(Value::as_str) {
+                Some(tag) => (tag, Some(obj)),
+                None => return self.error(path, "missing \"type\" discriminator".to_string()),
+            },
+            _ => return self.mismatch(path, "string or object", value),
+        };
+
+        let Some(case) = cases.iter().find(|c| c.name == tag) else {
+            return self.error(path, format!("{:?} is not one of {}", tag, names()));
+        };
+
+        match (&case.data, obj) {
+            (None, _) => {}
+            (Some(_), None) => self.error(path, format!("case {:?} requires data", tag)),
+            (Some(data), Some(obj)) => match obj.get("data") {
+                Some(payload) => {
+                    self.nested(path, "data", |this, path| this.check(data, payload, path))
+                }
+                None => {
+                    let mut rest = obj.clone();
+                    rest.remove("type");
+                    self.check(data, &Value::Object(rest), path);
+                }
+            },
+        }
+    }
+
+    /// Checks the one shape `tagging` prescribes
+    fn check_tagged_variant(
+        &mut self,
+        cases: &[crate::VariantCase],
+        tagging: &Tagging,
+        value: &Value,
+        path: &mut String,
+    ) {
+        let (tag_field, content) = match tagging {
+            Tagging::Internal { tag } => (tag, None),
+            Tagging::Adjacent { tag, content } => (tag, Some(content)),
+            Tagging::Untagged => return self.check_untagged_variant(cases, value, path),
+        };
+        let Value::Object(obj) = value else {
+            return self.mismatch(path, "object", value);
+        };
+        let Some(tag) = obj.get(tag_field).and_then(Value::as_str) else {
+            return self.error(path, format!("missing {:?} discriminator", tag_field));
+        };
+        let Some(case) = cases.iter().find(|c| c.name == tag) else {
+            let names: Vec<&str> = cases.iter().map(|c| c.name.as_str()).collect();
+            return self.error(
+                path,
+                format!("{:?} is not one of {}", tag, names.join(", ")),
+            );
+        };
+        let Some(data) = &case.data else {
+            return;
+        };
+
+        match content {
+            None => {
+                let mut rest = obj.clone();
+                rest.remove(tag_field);
+                self.check(data, &Value::Object(rest), path);
+            }
+            Some(content) => match obj.get(content) {
+                Some(payload) => {
+                    self.nested(path, content, |this, path| this.check(data, payload, path))
+                }
+                None => self.error(path, format!("case {:?} requires {:?}", tag, content)),
+            },
+        }
+    }
+
+    /// Accepts the first case whose payload (`null` for unit cases) matches,
+    /// like serde's untagged deserialization
+    fn check_untagged_variant(
+        &mut self,
+        cases: &[crate::VariantCase],
+        value: &Value,
+        path: &mut String,
+    ) {
+        let before = self.errors.len();
+        for case in cases {
+            match &case.data {
+                None if value.is_null() => return,
+                None => {}
+                Some(data) => {
+                    self.check(data, value, path);
+                    if self.errors.len() == before {
+                        return;
+                    }
+                    self.errors.truncate(before);
+                }
+            }
+        }
+        self.error(path, "matches none of the untagged cases".to_string());
+    }
+
+    fn nested(&mut self, path: &mut String, segment: &str, f: impl FnOnce(&mut Self, &mut String)) {
+        let len = path.len();
+        path.push('/');
+        path.push_str(&segment.replace('~', "~0").replace('/', "~1"));
+        f(self, path);
+        path.truncate(len);
+    }
+}
@@ -0,0 +1,263 @@
+use crate::{ENUM_DEPRECATED, ENUM_DISCRIMINANTS, ENUM_SINCE, SchemaType, TypeKind, VariantCase};
+use std::cmp::Ordering;
+use std::fmt;
+use std::str::FromStr;
+
+/// Semver version, compared by semver precedence (build metadata ignored)
+///
+/// ```
+/// use schema::Version;
+///
+/// let rc: Version = "2.0.0-rc.1".parse().unwrap();
+/// assert!(rc < "2.0.0".parse().unwrap());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Version {
+    pub major: u64,
+    pub minor: u64,
+    pub patch: u64,
+    /// Dot-separated pre-release identifiers, e.g. `["beta", "1"]`
+    pub pre: Vec<String>,
+}
+
+/// Error returned when a string isn't a semver version
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseVersionError {
+    pub version: String,
+    pub reason: String,
+}
+
+impl fmt::Display for ParseVersionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid version {:?}: {}", self.version, self.reason)
+    }
+}
+
+impl std::error::Error for ParseVersionError {}
+
+impl Version {
+    pub fn new(major: u64, minor: u64, patch: u64) -> Self {
+        Version {
+            major,
+            minor,
+            patch,
+            pre: Vec::new(),
+        }
+    }
+}
+
+impl FromStr for Version {
+    type Err = ParseVersionError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let err = |reason: &str| ParseVersionError {
+            version: s.to_string(),
+            reason: reason.to_string(),
+        };
+
+        let without_build = s.split('+').next().unwrap_or_default();
+        let (core, pre) = match without_build.split_once('-') {
+            Some((core, pre)) => (core, Some(pre)),
+            None => (without_build, None),
+        };
+
+        let parts = core
+            .split('.')
+            .map(|part| part.parse::<u64>())
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|_| err("expected numeric major.minor.patch"))?;
+        let [major, minor, patch] = parts[..] else {
+            return Err(err("expected major.minor.patch"));
+        };
+
+        let pre = match pre {
+            Some(pre) if pre.split('.').any(str::is_empty) => {
+                return Err(err("empty pre-release identifier"));
+            }
+            Some(pre) => pre.split('.').map(str::to_string).collect(),
+            None => Vec::new(),
+        };
+
+        Ok(Version {
+            major,
+            minor,
+            patch,
+            pre,
+        })
+    }
+}
+
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)?;
+        if !self.pre.is_empty() {
+            write!(f, "-{}", self.pre.join("."))?;
+        }
+        Ok(())
+    }
+}
+
+impl Ord for Version {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.major, self.minor, self.patch)
+            .cmp(&(other.major, other.minor, other.patch))
+            .then_with(|| match (self.pre.is_empty(), other.pre.is_empty()) {
+                // A pre-release sorts before its release
+                (true, true) => Ordering::Equal,
+                (true, false) => Ordering::Greater,
+                (false, true) => Ordering::Less,
+                (false, false) => compare_pre(&self.pre, &other.pre),
+            })
+    }
+}
+
+impl PartialOrd for Version {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Numeric identifiers compare numerically and sort before alphanumeric ones
+fn compare_pre(a: &[String], b: &[String]) -> Ordering {
+    for (a, b) in a.iter().zip(b) {
+        let ordering = match (a.parse::<u64>(), b.parse::<u64>()) {
+            (Ok(a), Ok(b)) => a.cmp(&b),
+            (Ok(_), Err(_)) => Ordering::Less,
+            (Err(_), Ok(_)) => Ordering::Greater,
+            (Err(_), Err(_)) => a.cmp(b),
+        };
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+    }
+    a.len().cmp(&b.len())
+}
+
+impl SchemaType {
+    /// The schema as it was at `version`: fields, variant cases and enum
+    /// cases whose `since` is later than `version` are removed
+    ///
+    /// Entries without `since` (or with one that doesn't parse) are kept.
+    ///
+    /// ```
+    /// use schema::{Schema, Version};
+    ///
+    /// #[derive(Schema)]
+    /// struct Profile {
+    ///     name: String,
+    ///     #[schema(since = "1.4.0")]
+    ///     avatar: Option<String>,
+    /// }
+    ///
+    /// let old = Profile::schema().narrow_to_version(&Version::new(1, 3, 0));
+    /// assert!(old.at_path("/properties/avatar").is_none());
+    /// ```
+    pub fn narrow_to_version(&self, version: &Version) -> SchemaType {
+        let added_later = |since: Option<&str>| {
+            since
+                .and_then(|since| since.parse::<Version>().ok())
+                .is_some_and(|since| since > *version)
+        };
+
+        let mut schema = self.clone();
+        schema.kind = match &self.kind {
+            TypeKind::Object {
+                properties,
+                required,
+            } => {
+                let properties: std::collections::HashMap<_, _> = properties
+                    .iter()
+                    .filter(|(_, field)| !added_later(field.since()))
+                    .map(|(name, field)| (name.clone(), field.narrow_to_version(version)))
+                    .collect();
+                schema
+                    .property_order
+                    .retain(|name| properties.contains_key(name));
+                TypeKind::Object {
+                    required: required
+                        .iter()
+                        .filter(|name| properties.contains_key(*name))
+                        .cloned()
+                        .collect(),
+                    properties,
+                }
+            }
+            TypeKind::Variant { cases } => TypeKind::Variant {
+                cases: cases
+                    .iter()
+                    .filter(|case| !added_later(case.since()))
+                    .map(|case| VariantCase {
+                        data: case.data.as_ref().map(|d| d.narrow_to_version(version)),
+                        ..case.clone()
+                    })
+                    .collect(),
+            },
+            TypeKind::Enum { variants } => {
+                let since = self.extensions.get(ENUM_SINCE);
+                let variant_since =
+                    |variant: &str| since.and_then(|s| s.get(variant)).and_then(|v| v.as_str());
+                let variants: Vec<String> = variants
+                    .iter()
+                    .filter(|variant| !added_later(variant_since(variant)))
+                    .cloned()
+                    .collect();
+                for key in [ENUM_SINCE, ENUM_DISCRIMINANTS] {
+                    if let Some(serde_json::Value::Object(map)) = schema.extensions.get_mut(key) {
+                        map.retain(|variant, _| variants.contains(variant));
+                    }
+                }
+                if let Some(serde_json::Value::Array(deprecated)) =
+                    schema.extensions.get_mut(ENUM_DEPRECATED)
+                {
+                    deprecated.retain(|variant| {
+                        variant
+                            .as_str()
+                            .is_some_and(|v| variants.iter().any(|n| n == v))
+                    });
+                }
+                TypeKind::Enum { variants }
+            }
+            TypeKind::Array { items } => TypeKind::Array {
+                items: Box::new(items.narrow_to_version(version)),
+            },
+            TypeKind::Set { items, ordered } => TypeKind::Set {
+                items: Box::new(items.narrow_to_version(version)),
+                ordered: *ordered,
+            },
+            TypeKind::Map {
+                key,
+                value,
+                ordered,
+            } => TypeKind::Map {
+                key: Box::new(key.narrow_to_version(version)),
+                value: Box::new(value.narrow_to_version(version)),
+                ordered: *ordered,
+            },
+            TypeKind::TaggedUnion {
+                tag_field,
+                tag_variants,
+                data_fields,
+            } => TypeKind::TaggedUnion {
+                tag_field: tag_field.clone(),
+                tag_variants: tag_variants.clone(),
+                data_fields: data_fields
+                    .iter()
+                    .filter(|(_, field)| !added_later(field.since()))
+                    .map(|(name, field)| (name.clone(), field.narrow_to_version(version)))
+                    .collect(),
+            },
+            TypeKind::Result { ok, err } => TypeKind::Result {
+                ok: Box::new(ok.narrow_to_version(version)),
+                err: Box::new(err.narrow_to_version(version)),
+            },
+            TypeKind::Tuple { fields } => TypeKind::Tuple {
+                fields: fields
+                    .iter()
+                    .map(|field| field.narrow_to_version(version))
+                    .collect(),
+            },
+            kind => kind.clone(),
+        };
+        schema
+    }
+}
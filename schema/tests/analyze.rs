@@ -0,0 +1,91 @@
+use schema::Schema;
+use schema::analyze::analyze;
+
+#[derive(Schema)]
+#[allow(dead_code)]
+enum Currency {
+    Usd,
+    Eur,
+    Gbp,
+}
+
+#[derive(Schema)]
+#[allow(dead_code)]
+struct Address {
+    street: String,
+    city: String,
+}
+
+#[derive(Schema)]
+#[allow(dead_code)]
+struct Order {
+    /// Order identifier
+    id: String,
+    currency: Currency,
+    shipping: Address,
+    billing: Address,
+    lines: Vec<Line>,
+}
+
+#[derive(Schema)]
+#[allow(dead_code)]
+struct Line {
+    sku: String,
+    quantity: u32,
+}
+
+#[test]
+fn test_depth_and_fields() {
+    let stats = analyze(&Order::schema());
+
+    // Order -> lines -> items (Line) -> quantity
+    assert_eq!(stats.depth, 4);
+    // 5 on Order, 2 on each Address, 2 on Line
+    assert_eq!(stats.field_count, 11);
+}
+
+#[test]
+fn test_enum_cardinalities() {
+    let stats = analyze(&Order::schema());
+
+    assert_eq!(stats.enum_cardinalities.len(), 1);
+    assert_eq!(stats.enum_cardinalities[0].path, "/properties/currency");
+    assert_eq!(stats.enum_cardinalities[0].cardinality, 3);
+    assert_eq!(stats.max_enum_cardinality(), Some(3));
+}
+
+#[test]
+fn test_distinct_object_types() {
+    // Address appears twice but counts once, Line once
+    assert_eq!(analyze(&Order::schema()).distinct_object_types, 2);
+    assert_eq!(analyze(&Address::schema()).distinct_object_types, 0);
+}
+
+#[test]
+fn test_estimated_size_is_close_to_real_output() {
+    let schema = Order::schema();
+    let stats = analyze(&schema);
+    let actual = serde_json::to_string(&schema_anthropic::to_anthropic_schema(&schema))
+        .unwrap()
+        .len();
+
+    let ratio = stats.estimated_json_size as f64 / actual as f64;
+    assert!(
+        (0.75..1.25).contains(&ratio),
+        "estimate {} vs actual {}",
+        stats.estimated_json_size,
+        actual
+    );
+    assert_eq!(
+        stats.estimated_tokens(),
+        stats.estimated_json_size.div_ceil(4)
+    );
+}
+
+#[test]
+fn test_primitive() {
+    let stats = analyze(&String::schema());
+    assert_eq!(stats.depth, 1);
+    assert_eq!(stats.field_count, 0);
+    assert!(stats.enum_cardinalities.is_empty());
+}
@@ -0,0 +1,719 @@
+use schema::{Schema, SchemaType, TypeKind};
+
+#[derive(Schema)]
+#[allow(dead_code)]
+struct Account {
+    #[schema(rename = "userId")]
+    user_id: String,
+    #[schema(rename = "displayName")]
+    display_name: Option<String>,
+    #[schema(skip)]
+    password_hash: String,
+}
+
+#[derive(Schema)]
+#[allow(dead_code)]
+enum Status {
+    #[schema(rename = "ACTIVE")]
+    Active,
+    Suspended,
+}
+
+#[derive(Schema)]
+#[allow(dead_code)]
+enum Command {
+    #[schema(rename = "move-to")]
+    MoveTo {
+        #[schema(rename = "X")]
+        x: i32,
+    },
+    Stop,
+}
+
+#[test]
+fn test_rename_fields() {
+    let schema = Account::schema();
+    let TypeKind::Object {
+        properties,
+        required,
+    } = &schema.kind
+    else {
+        panic!("Expected Object");
+    };
+
+    assert!(properties.contains_key("userId"));
+    assert!(properties.contains_key("displayName"));
+    assert!(!properties.contains_key("user_id"));
+    assert!(!properties.contains_key("password_hash"));
+    assert_eq!(required, &vec!["userId".to_string()]);
+}
+
+#[test]
+fn test_rename_unit_variants() {
+    let TypeKind::Enum { variants } = Status::schema().kind else {
+        panic!("Expected Enum");
+    };
+    assert_eq!(variants, vec!["ACTIVE", "Suspended"]);
+}
+
+#[test]
+fn test_rename_variant_and_payload_field() {
+    let schema = Command::schema();
+    let TypeKind::Variant { cases } = &schema.kind else {
+        panic!("Expected Variant");
+    };
+
+    assert_eq!(cases[0].name, "move-to");
+    assert!(schema.at_path("/cases/move-to/properties/X").is_some());
+}
+
+/// Internal: persisted in the `invoices` table
+#[derive(Schema)]
+#[schema(description = "An issued invoice")]
+#[allow(dead_code)]
+struct Invoice {
+    /// Internal: surrogate key, never reuse
+    #[schema(description = "Invoice number")]
+    number: u64,
+    /// Total in cents
+    total: u64,
+}
+
+#[derive(Schema)]
+#[schema(description = "Payment outcome")]
+#[allow(dead_code)]
+enum Payment {
+    /// Internal: settled by the nightly batch
+    #[schema(description = "Payment settled")]
+    Settled {
+        amount: u64,
+    },
+    Failed(#[schema(description = "Failure reason")] String),
+}
+
+#[test]
+fn test_skipped_variants() {
+    #[derive(Schema)]
+    #[allow(dead_code)]
+    enum Flag {
+        A,
+        #[schema(skip)]
+        B,
+    }
+
+    let TypeKind::Enum { variants } = Flag::schema().kind else {
+        panic!("Expected Enum");
+    };
+    assert_eq!(variants, vec!["A"]);
+
+    // Skipping the only variant with data leaves a plain enum
+    #[derive(Schema)]
+    #[allow(dead_code)]
+    enum Mode {
+        Fast,
+        #[schema(skip)]
+        Custom(u32),
+    }
+    assert!(matches!(Mode::schema().kind, TypeKind::Enum { .. }));
+
+    #[derive(Schema)]
+    #[allow(dead_code)]
+    enum Shape {
+        Point,
+        #[schema(skip)]
+        Internal {
+            id: u64,
+        },
+        Line(f64, #[schema(skip)] String, f64),
+        Label(#[schema(skip)] String),
+    }
+
+    let TypeKind::Variant { cases } = Shape::schema().kind else {
+        panic!("Expected Variant");
+    };
+    let names: Vec<&str> = cases.iter().map(|case| case.name.as_str()).collect();
+    assert_eq!(names, ["Point", "Line", "Label"]);
+    let Some(TypeKind::Tuple { fields }) = cases[1].data.as_ref().map(|data| &data.kind) else {
+        panic!("Expected Tuple");
+    };
+    assert_eq!(fields.len(), 2);
+    assert!(cases[2].data.is_none());
+}
+
+#[test]
+fn test_description_overrides_doc_comments() {
+    let schema = Invoice::schema();
+    assert_eq!(schema.description.as_deref(), Some("An issued invoice"));
+    assert_eq!(
+        schema
+            .at_path("/properties/number")
+            .unwrap()
+            .description
+            .as_deref(),
+        Some("Invoice number")
+    );
+    // Doc comments still apply where there is no override
+    assert_eq!(
+        schema
+            .at_path("/properties/total")
+            .unwrap()
+            .description
+            .as_deref(),
+        Some("Total in cents")
+    );
+}
+
+#[test]
+fn test_description_on_enum_and_variants() {
+    let schema = Payment::schema();
+    assert_eq!(schema.description.as_deref(), Some("Payment outcome"));
+
+    let TypeKind::Variant { cases } = &schema.kind else {
+        panic!("Expected Variant");
+    };
+    assert_eq!(cases[0].description.as_deref(), Some("Payment settled"));
+    assert_eq!(
+        cases[1].data.as_ref().unwrap().description.as_deref(),
+        Some("Failure reason")
+    );
+}
+
+#[derive(Schema)]
+#[allow(dead_code)]
+struct Pagination {
+    page: u32,
+    per_page: Option<u32>,
+}
+
+#[derive(Schema)]
+#[allow(dead_code)]
+struct Audit {
+    created_by: String,
+}
+
+#[derive(Schema)]
+#[allow(dead_code)]
+struct UserList {
+    users: Vec<String>,
+    #[schema(flatten)]
+    pagination: Pagination,
+    #[schema(flatten)]
+    audit: Option<Audit>,
+}
+
+#[derive(Schema)]
+#[allow(dead_code)]
+enum Query {
+    List {
+        #[schema(flatten)]
+        pagination: Pagination,
+    },
+}
+
+#[test]
+fn test_flatten_merges_properties() {
+    let schema = UserList::schema();
+    let TypeKind::Object {
+        properties,
+        required,
+    } = &schema.kind
+    else {
+        panic!("Expected Object");
+    };
+
+    let mut names: Vec<_> = properties.keys().map(String::as_str).collect();
+    names.sort();
+    assert_eq!(names, vec!["created_by", "page", "per_page", "users"]);
+
+    // Optional flattened fields contribute no required properties
+    assert_eq!(required, &vec!["users".to_string(), "page".to_string()]);
+}
+
+#[test]
+fn test_flatten_in_variant() {
+    let schema = Query::schema();
+    assert!(schema.at_path("/cases/List/properties/page").is_some());
+    assert!(
+        schema
+            .at_path("/cases/List/properties/pagination")
+            .is_none()
+    );
+}
+
+fn default_retries() -> u32 {
+    3
+}
+
+#[derive(Schema)]
+#[allow(dead_code)]
+struct ClientConfig {
+    url: String,
+    #[schema(default)]
+    verbose: bool,
+    #[schema(default = "default_retries")]
+    retries: u32,
+    #[schema(default)]
+    tags: Vec<String>,
+}
+
+#[test]
+fn test_default_fields() {
+    let schema = ClientConfig::schema();
+    let TypeKind::Object { required, .. } = &schema.kind else {
+        panic!("Expected Object");
+    };
+    assert_eq!(required, &vec!["url".to_string()]);
+
+    let default = |path: &str| schema.at_path(path).unwrap().default.clone();
+    assert_eq!(
+        default("/properties/verbose"),
+        Some(serde_json::json!(false))
+    );
+    assert_eq!(default("/properties/retries"), Some(serde_json::json!(3)));
+    assert_eq!(default("/properties/tags"), Some(serde_json::json!([])));
+    assert_eq!(default("/properties/url"), None);
+}
+
+/// A type from another crate that doesn't implement Schema
+#[allow(dead_code)]
+struct Timestamp(i64);
+
+fn timestamp_schema() -> SchemaType {
+    SchemaType {
+        kind: TypeKind::String,
+        description: Some("RFC 3339 timestamp".to_string()),
+        ..Default::default()
+    }
+}
+
+mod money {
+    use schema::{SchemaType, TypeKind};
+
+    pub fn schema() -> SchemaType {
+        SchemaType::new(TypeKind::String)
+    }
+}
+
+#[derive(Schema)]
+#[allow(dead_code)]
+struct Bill {
+    #[schema(with = "timestamp_schema")]
+    issued_at: Timestamp,
+    /// Due date, if any
+    #[schema(with = "timestamp_schema")]
+    due_at: Option<Timestamp>,
+    /// Amount as a decimal string
+    #[schema(with = "money::schema")]
+    total: u64,
+}
+
+#[test]
+fn test_with_override() {
+    let schema = Bill::schema();
+    let TypeKind::Object { required, .. } = &schema.kind else {
+        panic!("Expected Object");
+    };
+    assert_eq!(
+        required,
+        &vec!["issued_at".to_string(), "total".to_string()]
+    );
+
+    let issued = schema.at_path("/properties/issued_at").unwrap();
+    assert_eq!(issued.kind, TypeKind::String);
+    assert_eq!(issued.description.as_deref(), Some("RFC 3339 timestamp"));
+
+    // Doc comments still override the function's description
+    let due = schema.at_path("/properties/due_at").unwrap();
+    assert_eq!(due.description.as_deref(), Some("Due date, if any"));
+
+    assert_eq!(
+        schema.at_path("/properties/total").unwrap().kind,
+        TypeKind::String
+    );
+}
+
+/// Stable identifier of a user
+#[derive(Schema)]
+#[schema(transparent)]
+#[allow(dead_code)]
+struct UserId(String);
+
+#[derive(Schema)]
+#[schema(transparent)]
+#[allow(dead_code)]
+struct Tags {
+    #[schema(min_items = 1)]
+    inner: Vec<String>,
+    #[schema(skip)]
+    cache: (),
+}
+
+#[derive(Schema)]
+#[allow(dead_code)]
+struct Member {
+    id: UserId,
+    tags: Tags,
+}
+
+#[test]
+fn test_transparent_newtypes() {
+    let id = UserId::schema();
+    assert_eq!(id.kind, TypeKind::String);
+    assert_eq!(
+        id.description.as_deref(),
+        Some("Stable identifier of a user")
+    );
+    assert_eq!(id.name, None);
+
+    let tags = Tags::schema();
+    assert!(matches!(tags.kind, TypeKind::Array { .. }));
+    assert_eq!(tags.constraints.min_items, Some(1));
+
+    let member = Member::schema();
+    assert_eq!(
+        member.at_path("/properties/id").unwrap().kind,
+        TypeKind::String
+    );
+}
+
+#[derive(Schema)]
+#[allow(dead_code)]
+struct Profile {
+    name: String,
+    #[schema(since = "1.4.0")]
+    avatar: Option<String>,
+}
+
+#[derive(Schema)]
+#[allow(dead_code)]
+enum Checkout {
+    Card {
+        last4: String,
+    },
+    #[schema(since = "2.0.0-beta.1")]
+    Wallet {
+        provider: String,
+    },
+}
+
+#[derive(Schema)]
+#[allow(dead_code)]
+enum Theme {
+    Light,
+    #[schema(since = "1.2.0")]
+    Dark,
+}
+
+#[test]
+fn test_since() {
+    let TypeKind::Object { properties, .. } = Profile::schema().kind else {
+        panic!("expected object");
+    };
+    assert_eq!(properties["avatar"].since(), Some("1.4.0"));
+    assert_eq!(properties["name"].since(), None);
+
+    let TypeKind::Variant { cases } = Checkout::schema().kind else {
+        panic!("expected variant");
+    };
+    assert_eq!(cases[0].since(), None);
+    assert_eq!(cases[1].since(), Some("2.0.0-beta.1"));
+
+    let theme = Theme::schema();
+    assert_eq!(
+        theme.extensions[schema::ENUM_SINCE],
+        serde_json::json!({ "Dark": "1.2.0" })
+    );
+}
+
+#[derive(Schema)]
+#[schema(deny_unknown_fields)]
+#[allow(dead_code)]
+struct Login {
+    user: String,
+    remember: Option<bool>,
+}
+
+#[derive(Schema)]
+#[schema(tag = "op", deny_unknown_fields)]
+#[allow(dead_code)]
+enum Order {
+    Place { x: i32 },
+    Cancel,
+}
+
+#[test]
+fn test_deny_unknown_fields() {
+    use schema::validate::validate;
+    use serde_json::json;
+
+    let schema = Login::schema();
+    assert!(schema.denies_unknown_fields());
+    assert!(!Member::schema().denies_unknown_fields());
+
+    assert_eq!(validate(&schema, &json!({ "user": "ada" })), Ok(()));
+    let errors = validate(&schema, &json!({ "user": "ada", "admin": true })).unwrap_err();
+    assert_eq!(errors[0].path, "/admin");
+    assert_eq!(errors[0].message, "unknown property");
+
+    // On enums, each struct variant's fields are closed; the tag is not a field
+    let order = Order::schema();
+    assert_eq!(validate(&order, &json!({ "op": "Place", "x": 1 })), Ok(()));
+    assert!(validate(&order, &json!({ "op": "Place", "x": 1, "y": 2 })).is_err());
+}
+
+/// A saved search
+#[derive(Schema)]
+#[deprecated(since = "2.0.0", note = "use `Filter` instead")]
+#[allow(dead_code)]
+struct SavedSearch {
+    /// Query text
+    #[deprecated = "use `terms`"]
+    query: String,
+    #[deprecated]
+    legacy_id: Option<u32>,
+    terms: Vec<String>,
+}
+
+#[derive(Schema)]
+#[allow(dead_code)]
+enum Sort {
+    Newest,
+    #[deprecated(note = "sorts like `newest`")]
+    Relevance,
+}
+
+#[derive(Schema)]
+#[allow(dead_code)]
+enum Source {
+    Upload {
+        path: String,
+    },
+    #[deprecated]
+    Ftp {
+        host: String,
+    },
+}
+
+#[test]
+#[allow(deprecated)]
+fn test_deprecated() {
+    let schema = SavedSearch::schema();
+    assert!(schema.deprecated());
+    assert_eq!(
+        schema.description.as_deref(),
+        Some("A saved search\n\nDeprecated: use `Filter` instead")
+    );
+
+    let query = schema.at_path("/properties/query").unwrap();
+    assert!(query.deprecated());
+    assert_eq!(
+        query.description.as_deref(),
+        Some("Query text\n\nDeprecated: use `terms`")
+    );
+    let legacy_id = schema.at_path("/properties/legacy_id").unwrap();
+    assert!(legacy_id.deprecated());
+    assert_eq!(legacy_id.description, None);
+    assert!(!schema.at_path("/properties/terms").unwrap().deprecated());
+
+    assert_eq!(
+        Sort::schema().extensions.get(schema::ENUM_DEPRECATED),
+        Some(&serde_json::json!(["Relevance"]))
+    );
+
+    let TypeKind::Variant { cases } = Source::schema().kind else {
+        panic!("expected a variant");
+    };
+    assert!(!cases[0].deprecated());
+    assert!(cases[1].deprecated());
+}
+
+#[derive(Schema)]
+#[allow(dead_code)]
+struct Article {
+    #[schema(read_only)]
+    id: u64,
+    title: String,
+    #[schema(write_only)]
+    draft_token: Option<String>,
+}
+
+#[test]
+fn test_read_write_only() {
+    let schema = Article::schema();
+    let id = schema.at_path("/properties/id").unwrap();
+    assert!(id.read_only());
+    assert!(!id.write_only());
+    let token = schema.at_path("/properties/draft_token").unwrap();
+    assert!(token.write_only());
+    assert!(!token.read_only());
+    let title = schema.at_path("/properties/title").unwrap();
+    assert!(!title.read_only() && !title.write_only());
+}
+
+#[derive(Schema)]
+#[schema(example = { "code": "EUR" })]
+#[allow(dead_code)]
+struct Currency {
+    #[schema(example = "EUR", example = "USD")]
+    code: String,
+    symbol: String,
+}
+
+#[test]
+fn test_examples() {
+    let schema = Currency::schema();
+    assert_eq!(schema.examples(), [serde_json::json!({ "code": "EUR" })]);
+    let code = schema.at_path("/properties/code").unwrap();
+    assert_eq!(
+        code.examples(),
+        [serde_json::json!("EUR"), serde_json::json!("USD")]
+    );
+    assert!(
+        schema
+            .at_path("/properties/symbol")
+            .unwrap()
+            .examples()
+            .is_empty()
+    );
+}
+
+#[derive(Schema)]
+#[schema(extension("x-owner" = "billing", "x-audit" = { "level": 2, "tags": ["pii"] }))]
+#[allow(dead_code)]
+struct Receipt {
+    #[schema(extension("x-order" = 1))]
+    number: String,
+    #[schema(extension("x-order" = -1))]
+    total: f64,
+    #[schema(since = "1.1.0")]
+    due: Option<String>,
+}
+
+#[derive(Schema)]
+#[allow(dead_code)]
+enum Delivery {
+    #[schema(extension("x-carrier" = true))]
+    Courier {
+        tracking: String,
+    },
+    Pickup,
+}
+
+#[test]
+fn test_vendor_extensions() {
+    let schema = Receipt::schema();
+    assert_eq!(
+        schema.vendor_extensions().collect::<Vec<_>>(),
+        [
+            (
+                "x-audit",
+                &serde_json::json!({ "level": 2, "tags": ["pii"] })
+            ),
+            ("x-owner", &serde_json::json!("billing")),
+        ]
+    );
+    let number = schema.at_path("/properties/number").unwrap();
+    assert_eq!(number.extensions["x-order"], serde_json::json!(1));
+    let total = schema.at_path("/properties/total").unwrap();
+    assert_eq!(total.extensions["x-order"], serde_json::json!(-1));
+
+    // The crate's own keys aren't vendor extensions
+    let due = schema.at_path("/properties/due").unwrap();
+    assert!(due.since().is_some());
+    assert_eq!(due.vendor_extensions().count(), 0);
+
+    let TypeKind::Variant { cases } = Delivery::schema().kind else {
+        panic!("expected a variant");
+    };
+    assert_eq!(
+        cases[0].vendor_extensions().collect::<Vec<_>>(),
+        [("x-carrier", &serde_json::json!(true))]
+    );
+}
+
+#[derive(Schema)]
+#[schema(partial)]
+#[allow(dead_code)]
+struct Contact {
+    name: String,
+    phone: Option<String>,
+    address: Address,
+}
+
+#[derive(Schema)]
+#[allow(dead_code)]
+struct Address {
+    street: String,
+}
+
+#[test]
+fn test_partial_schema() {
+    let partial = Contact::partial_schema();
+    assert_eq!(partial.name.as_deref(), Some("ContactPartial"));
+    let TypeKind::Object {
+        properties,
+        required,
+    } = &partial.kind
+    else {
+        panic!("expected an object");
+    };
+    assert!(required.is_empty());
+    assert_eq!(properties.len(), 3);
+    assert_eq!(partial.property_order, ["name", "phone", "address"]);
+
+    // Nested objects keep their own required fields
+    let TypeKind::Object { required, .. } = &partial.at_path("/properties/address").unwrap().kind
+    else {
+        panic!("expected an object");
+    };
+    assert_eq!(required, &["street"]);
+
+    // The full schema is unaffected
+    let TypeKind::Object { required, .. } = Contact::schema().kind else {
+        panic!("expected an object");
+    };
+    assert_eq!(required, ["name", "address"]);
+}
+
+/// A sensor "reading"
+#[derive(Schema)]
+#[schema(emit_json_const, deny_unknown_fields)]
+#[allow(dead_code)]
+struct Reading<'a> {
+    /// Sensor id
+    sensor: &'a str,
+    #[schema(rename = "valueC")]
+    value: f64,
+    samples: Vec<Option<u32>>,
+    note: Option<String>,
+    #[schema(skip)]
+    raw: Vec<u8>,
+}
+
+#[derive(Schema)]
+#[schema(emit_json_const, rename_all_variants = "snake_case")]
+#[allow(dead_code)]
+enum Unit {
+    DegreesC,
+    DegreesF,
+    #[schema(skip)]
+    Raw(f64),
+}
+
+#[test]
+fn test_emit_json_const() {
+    let json: serde_json::Value = serde_json::from_str(Reading::JSON_SCHEMA).unwrap();
+    assert_eq!(
+        json,
+        schema_anthropic::to_anthropic_schema(&Reading::schema())
+    );
+    assert_eq!(json["description"], "A sensor \"reading\"");
+    assert_eq!(json["properties"]["valueC"]["type"], "number");
+
+    let json: serde_json::Value = serde_json::from_str(Unit::JSON_SCHEMA).unwrap();
+    assert_eq!(
+        json,
+        serde_json::json!({ "type": "string", "enum": ["degrees_c", "degrees_f"] })
+    );
+    assert_eq!(json, schema_anthropic::to_anthropic_schema(&Unit::schema()));
+}
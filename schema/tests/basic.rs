@@ -55,35 +55,232 @@ fn test_simple_enum_schema() {
     match schema.kind {
         TypeKind::Enum { variants } => {
             assert_eq!(variants.len(), 3);
-            assert!(variants.contains(&"active".to_string()));
-            assert!(variants.contains(&"inactive".to_string()));
-            assert!(variants.contains(&"pending".to_string()));
+            assert!(variants.contains(&"Active".to_string()));
+            assert!(variants.contains(&"Inactive".to_string()));
+            assert!(variants.contains(&"Pending".to_string()));
         }
         _ => panic!("Expected Enum schema"),
     }
 }
 
 #[test]
-fn test_tagged_union_schema() {
+fn test_variant_schema() {
     let schema = Action::schema();
 
     match schema.kind {
-        TypeKind::TaggedUnion {
-            tag_field,
-            tag_variants,
-            data_fields,
-        } => {
-            assert_eq!(tag_field, "type");
-            assert_eq!(tag_variants.len(), 3);
-            assert!(tag_variants.contains(&"click".to_string()));
-            assert!(tag_variants.contains(&"fill".to_string()));
-            assert!(tag_variants.contains(&"select".to_string()));
-
-            // Should have collected all unique data fields
-            assert_eq!(data_fields.len(), 2);
-            assert!(data_fields.contains_key("value"));
-            assert!(data_fields.contains_key("option"));
+        TypeKind::Variant { cases } => {
+            assert_eq!(cases.len(), 3);
+            let names: Vec<_> = cases.iter().map(|c| c.name.as_str()).collect();
+            assert_eq!(names, vec!["Click", "Fill", "Select"]);
+
+            // Unit variants carry no data, struct variants carry an object
+            assert!(cases[0].data.is_none());
+            match &cases[1].data.as_ref().unwrap().kind {
+                TypeKind::Object { properties, .. } => assert!(properties.contains_key("value")),
+                _ => panic!("Expected Object payload"),
+            }
+            match &cases[2].data.as_ref().unwrap().kind {
+                TypeKind::Object { properties, .. } => assert!(properties.contains_key("option")),
+                _ => panic!("Expected Object payload"),
+            }
         }
-        _ => panic!("Expected TaggedUnion schema"),
+        _ => panic!("Expected Variant schema"),
     }
 }
+
+#[derive(Schema)]
+#[allow(dead_code)]
+struct Ordered {
+    zeta: String,
+    alpha: i32,
+    #[schema(rename = "mid")]
+    middle: bool,
+}
+
+#[test]
+fn test_property_declaration_order() {
+    let schema = Ordered::schema();
+    assert_eq!(schema.property_order, vec!["zeta", "alpha", "mid"]);
+
+    let names: Vec<&str> = schema
+        .ordered_properties()
+        .into_iter()
+        .map(|(name, _)| name)
+        .collect();
+    assert_eq!(names, vec!["zeta", "alpha", "mid"]);
+}
+
+#[derive(Schema)]
+#[allow(dead_code)]
+enum Shape {
+    Circle {
+        radius: f64,
+        label: Option<String>,
+    },
+    Rect {
+        width: f64,
+        #[schema(default)]
+        height: f64,
+    },
+    Empty,
+}
+
+#[test]
+fn test_variant_case_fields() {
+    let TypeKind::Variant { cases } = Shape::schema().kind else {
+        panic!("Expected Variant");
+    };
+
+    let names: Vec<_> = cases[0].fields().into_iter().map(|(n, _)| n).collect();
+    assert_eq!(names, vec!["radius", "label"]);
+    assert_eq!(cases[0].required(), &["radius".to_string()]);
+    assert!(!cases[0].is_required("label"));
+
+    assert!(cases[1].is_required("width"));
+    assert!(!cases[1].is_required("height"));
+
+    assert!(cases[2].fields().is_empty());
+    assert!(cases[2].required().is_empty());
+}
+
+#[derive(Schema)]
+#[allow(dead_code)]
+enum Expr {
+    Lit(i64),
+    Neg(Box<Expr>),
+    Add(Box<Expr>, Box<Expr>),
+    Call { name: String, args: Vec<Expr> },
+}
+
+#[test]
+fn test_recursive_enum() {
+    let TypeKind::Variant { cases } = Expr::schema().kind else {
+        panic!("Expected Variant");
+    };
+    let self_ref = TypeKind::Ref {
+        name: "Expr".to_string(),
+    };
+
+    assert_eq!(
+        cases[0].data.as_ref().unwrap().kind,
+        TypeKind::Integer(schema::IntegerKind::I64)
+    );
+    assert_eq!(cases[1].data.as_ref().unwrap().kind, self_ref);
+
+    let Some(TypeKind::Tuple { fields }) = cases[2].data.as_ref().map(|d| &d.kind) else {
+        panic!("Expected tuple payload");
+    };
+    assert_eq!(fields.len(), 2);
+    assert!(fields.iter().all(|f| f.kind == self_ref));
+
+    let args = cases[3].fields()[1].1;
+    assert_eq!(
+        args.kind,
+        TypeKind::Array {
+            items: Box::new(schema::SchemaType::new(self_ref))
+        }
+    );
+}
+
+/// Latitude in degrees
+#[derive(Schema)]
+#[allow(dead_code)]
+struct Latitude(f64);
+
+/// A point on a map
+#[derive(Schema)]
+#[allow(dead_code)]
+struct Point(Latitude, f64, #[schema(skip)] u8);
+
+#[test]
+fn test_tuple_structs() {
+    let lat = Latitude::schema();
+    assert_eq!(lat.kind, TypeKind::Number(schema::NumberKind::F64));
+    assert_eq!(lat.description.as_deref(), Some("Latitude in degrees"));
+    assert_eq!(lat.name.as_deref(), Some("Latitude"));
+
+    let point = Point::schema();
+    assert_eq!(point.description.as_deref(), Some("A point on a map"));
+    let TypeKind::Tuple { fields } = &point.kind else {
+        panic!("Expected Tuple");
+    };
+    assert_eq!(fields.len(), 2);
+    assert_eq!(
+        fields[0].description.as_deref(),
+        Some("Latitude in degrees")
+    );
+    assert_eq!(fields[1].kind, TypeKind::Number(schema::NumberKind::F64));
+}
+
+const URGENT: isize = 100;
+
+#[derive(Schema)]
+#[allow(dead_code)]
+enum Priority {
+    Low = 1,
+    Medium,
+    High = 10,
+    Urgent = URGENT,
+    Lowest = -5,
+}
+
+#[test]
+fn test_enum_discriminants() {
+    let schema = Priority::schema();
+    assert_eq!(
+        schema.enum_discriminants().unwrap(),
+        [
+            ("Low", 1),
+            ("Medium", 2),
+            ("High", 10),
+            ("Urgent", 100),
+            ("Lowest", -5)
+        ]
+    );
+
+    // Without explicit discriminants, none are recorded
+    #[derive(Schema)]
+    #[allow(dead_code)]
+    enum Color {
+        Red,
+        Green,
+    }
+    assert_eq!(Color::schema().enum_discriminants(), None);
+    assert!(
+        !Color::schema()
+            .extensions
+            .contains_key(schema::ENUM_DISCRIMINANTS)
+    );
+}
+
+#[derive(Schema)]
+#[schema(rename_all_variants = "snake_case")]
+#[allow(dead_code)]
+enum Region {
+    UsEast,
+    EuWest,
+    #[schema(rename = "ap")]
+    AsiaPacific,
+}
+
+#[derive(Schema)]
+#[schema(rename_all_variants = "kebab-case")]
+#[allow(dead_code)]
+enum Request {
+    GetItem { id: u32 },
+    ListItems,
+}
+
+#[test]
+fn test_rename_all_variants() {
+    let TypeKind::Enum { variants } = Region::schema().kind else {
+        panic!("Expected Enum schema");
+    };
+    assert_eq!(variants, vec!["us_east", "eu_west", "ap"]);
+
+    let TypeKind::Variant { cases } = Request::schema().kind else {
+        panic!("Expected Variant schema");
+    };
+    let names: Vec<_> = cases.iter().map(|c| c.name.as_str()).collect();
+    assert_eq!(names, vec!["get-item", "list-items"]);
+}
@@ -0,0 +1,61 @@
+use schema::case::{Case, CaseConverter};
+
+#[test]
+fn test_acronym_runs() {
+    assert_eq!(Case::Snake.apply("HTTPRequestID"), "http_request_id");
+    assert_eq!(Case::Camel.apply("HTTPRequestID"), "httpRequestId");
+    assert_eq!(Case::Pascal.apply("http_request_id"), "HttpRequestId");
+    assert_eq!(Case::Kebab.apply("getHTTPResponse"), "get-http-response");
+    assert_eq!(Case::Kebab.apply("IOError"), "io-error");
+}
+
+#[test]
+fn test_all_cases() {
+    let name = "userAccountID";
+    assert_eq!(Case::Lower.apply(name), "useraccountid");
+    assert_eq!(Case::Upper.apply(name), "USERACCOUNTID");
+    assert_eq!(Case::Snake.apply(name), "user_account_id");
+    assert_eq!(Case::ScreamingSnake.apply(name), "USER_ACCOUNT_ID");
+    assert_eq!(Case::Kebab.apply(name), "user-account-id");
+    assert_eq!(Case::ScreamingKebab.apply(name), "USER-ACCOUNT-ID");
+    assert_eq!(Case::Camel.apply(name), "userAccountId");
+    assert_eq!(Case::Pascal.apply(name), "UserAccountId");
+}
+
+#[test]
+fn test_digits_stay_with_word() {
+    assert_eq!(Case::Snake.apply("Utf8Value"), "utf8_value");
+    assert_eq!(Case::Snake.apply("v2Endpoint"), "v2_endpoint");
+}
+
+#[test]
+fn test_acronym_dictionary_splits_runs() {
+    // Two known acronyms back to back
+    assert_eq!(Case::Snake.apply("APIURL"), "api_url");
+    // Unknown runs stay one word
+    assert_eq!(Case::Snake.apply("ABCDef"), "abc_def");
+
+    let converter = CaseConverter::new().with_acronyms(["GRPC", "OAUTH"]);
+    assert_eq!(
+        converter.convert("OAUTHGRPCClient", Case::Snake),
+        "oauth_grpc_client"
+    );
+    assert_eq!(
+        CaseConverter::empty().convert("OAUTHGRPCClient", Case::Snake),
+        "oauthgrpc_client"
+    );
+}
+
+#[test]
+fn test_serde_names_round_trip() {
+    for name in [
+        "snake_case",
+        "camelCase",
+        "SCREAMING-KEBAB-CASE",
+        "lowercase",
+    ] {
+        let case: Case = name.parse().unwrap();
+        assert_eq!(case.serde_name(), name);
+    }
+    assert!("Title Case".parse::<Case>().is_err());
+}
@@ -8,7 +8,7 @@ fn test_hashset_schema() {
     match schema.kind {
         TypeKind::Set { items, ordered } => {
             assert_eq!(items.kind, TypeKind::String);
-            assert_eq!(ordered, false);
+            assert!(!ordered);
             assert_eq!(
                 schema.description,
                 Some("Unordered set of unique values".to_string())
@@ -30,7 +30,7 @@ fn test_btreemap_schema() {
         } => {
             assert_eq!(key.kind, TypeKind::String);
             assert!(matches!(value.kind, TypeKind::Integer(_)));
-            assert_eq!(ordered, true);
+            assert!(ordered);
             assert_eq!(
                 schema.description,
                 Some("Ordered map/dictionary of key-value pairs".to_string())
@@ -47,7 +47,7 @@ fn test_btreeset_schema() {
     match schema.kind {
         TypeKind::Set { items, ordered } => {
             assert!(matches!(items.kind, TypeKind::Integer(_)));
-            assert_eq!(ordered, true);
+            assert!(ordered);
             assert_eq!(
                 schema.description,
                 Some("Ordered set of unique values".to_string())
@@ -82,7 +82,7 @@ fn test_hashmap_schema() {
         } => {
             assert_eq!(key.kind, TypeKind::String);
             assert!(matches!(value.kind, TypeKind::Integer(_)));
-            assert_eq!(ordered, false);
+            assert!(!ordered);
             assert_eq!(
                 schema.description,
                 Some("Unordered map/dictionary of key-value pairs".to_string())
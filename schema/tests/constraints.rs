@@ -0,0 +1,249 @@
+use schema::validate::validate;
+use schema::{Constraints, Schema, TypeKind};
+use schema_anthropic::to_anthropic_schema;
+use serde_json::json;
+
+const MAX_NAME: usize = 64;
+
+#[derive(Schema, garde::Validate)]
+#[allow(dead_code)]
+struct GardeSignup {
+    #[garde(length(chars, min = 1, max = MAX_NAME))]
+    name: String,
+    #[garde(email)]
+    email: String,
+    #[garde(range(min = 13, max = 130))]
+    age: u8,
+    #[garde(pattern(r"^[a-z0-9_]+$"))]
+    handle: String,
+    #[garde(length(min = 1, max = 5), inner(length(min = 1)))]
+    tags: Vec<String>,
+    #[garde(skip)]
+    referrer: Option<String>,
+}
+
+#[derive(Schema, validator::Validate)]
+#[allow(dead_code)]
+struct ValidatorSignup {
+    #[validate(length(min = 1, max = 64))]
+    name: String,
+    #[validate(email)]
+    email: String,
+    #[validate(url)]
+    homepage: Option<String>,
+    #[validate(range(min = -1.5, max = 1.5))]
+    score: f64,
+    #[validate(length(equal = 3))]
+    code: String,
+    #[validate(custom(function = "check_anything"))]
+    note: String,
+}
+
+#[allow(dead_code)]
+fn check_anything(_: &str) -> Result<(), validator::ValidationError> {
+    Ok(())
+}
+
+fn property(schema: &schema::SchemaType, name: &str) -> Constraints {
+    let TypeKind::Object { properties, .. } = &schema.kind else {
+        panic!("expected object");
+    };
+    properties[name].constraints.clone()
+}
+
+#[test]
+fn test_garde_rules() {
+    let schema = GardeSignup::schema();
+
+    let name = property(&schema, "name");
+    assert_eq!(name.min_length, Some(1));
+    assert_eq!(name.max_length, Some(64));
+    assert_eq!(property(&schema, "email").format.as_deref(), Some("email"));
+
+    let age = property(&schema, "age");
+    assert_eq!((age.minimum, age.maximum), (Some(13.0), Some(130.0)));
+    assert_eq!(
+        property(&schema, "handle").pattern.as_deref(),
+        Some("^[a-z0-9_]+$")
+    );
+
+    // `length` on a collection bounds the item count
+    let tags = property(&schema, "tags");
+    assert_eq!((tags.min_items, tags.max_items), (Some(1), Some(5)));
+    assert_eq!(tags.min_length, None);
+
+    assert!(property(&schema, "referrer").is_empty());
+}
+
+#[test]
+fn test_validator_rules() {
+    let schema = ValidatorSignup::schema();
+
+    assert_eq!(property(&schema, "name").max_length, Some(64));
+    assert_eq!(property(&schema, "email").format.as_deref(), Some("email"));
+    assert_eq!(property(&schema, "homepage").format.as_deref(), Some("uri"));
+
+    let score = property(&schema, "score");
+    assert_eq!((score.minimum, score.maximum), (Some(-1.5), Some(1.5)));
+
+    let code = property(&schema, "code");
+    assert_eq!((code.min_length, code.max_length), (Some(3), Some(3)));
+    assert!(property(&schema, "note").is_empty());
+}
+
+#[test]
+fn test_json_keywords() {
+    let json = to_anthropic_schema(&GardeSignup::schema());
+
+    assert_eq!(json["properties"]["name"]["minLength"], 1);
+    assert_eq!(json["properties"]["name"]["maxLength"], 64);
+    assert_eq!(json["properties"]["age"]["minimum"], 13);
+    assert_eq!(json["properties"]["email"]["format"], "email");
+    assert_eq!(json["properties"]["tags"]["maxItems"], 5);
+}
+
+#[test]
+fn test_validate_checks_constraints() {
+    let schema = GardeSignup::schema();
+    let mut value = json!({
+        "name": "Ada",
+        "email": "ada@example.com",
+        "age": 36,
+        "handle": "ada",
+        "tags": ["math"]
+    });
+    assert!(validate(&schema, &value).is_ok());
+
+    value["name"] = json!("");
+    value["age"] = json!(7);
+    value["tags"] = json!([]);
+    let errors = validate(&schema, &value).unwrap_err();
+    let paths: Vec<_> = errors.iter().map(|e| e.path.as_str()).collect();
+    assert_eq!(errors.len(), 3, "{:?}", errors);
+    assert!(paths.contains(&"/name"));
+    assert!(paths.contains(&"/age"));
+    assert!(paths.contains(&"/tags"));
+}
+
+#[derive(Schema, garde::Validate)]
+#[allow(dead_code)]
+struct GardeRepo {
+    #[garde(prefix("https://"), length(max = 200))]
+    remote: String,
+    #[garde(suffix(".git"))]
+    path: String,
+    #[garde(contains("v1.0"))]
+    tag: String,
+    #[garde(ascii)]
+    label: String,
+    #[garde(alphanumeric)]
+    slug: String,
+}
+
+#[derive(Schema, validator::Validate)]
+#[allow(dead_code)]
+struct ValidatorRepo {
+    #[validate(contains(pattern = "@"))]
+    owner: String,
+}
+
+#[test]
+fn test_literal_text_rules() {
+    let schema = GardeRepo::schema();
+    assert_eq!(
+        property(&schema, "remote").pattern.as_deref(),
+        Some("^https://")
+    );
+    assert_eq!(property(&schema, "remote").max_length, Some(200));
+    assert_eq!(
+        property(&schema, "path").pattern.as_deref(),
+        Some(r"\.git$")
+    );
+    assert_eq!(property(&schema, "tag").pattern.as_deref(), Some(r"v1\.0"));
+    assert_eq!(
+        property(&schema, "label").pattern.as_deref(),
+        Some(r"^[\x00-\x7F]*$")
+    );
+    assert_eq!(
+        property(&schema, "slug").pattern.as_deref(),
+        Some("^[a-zA-Z0-9]*$")
+    );
+
+    let schema = ValidatorRepo::schema();
+    assert_eq!(property(&schema, "owner").pattern.as_deref(), Some("@"));
+}
+
+#[derive(Schema, validator::Validate)]
+#[allow(dead_code)]
+struct Listing {
+    #[schema(minimum = 0, maximum = 100)]
+    discount: u8,
+    #[schema(min_length = 3, max_length = 80, pattern = "^[A-Z]")]
+    title: String,
+    #[schema(min_items = 1, max_items = 10)]
+    photos: Vec<String>,
+    /// `#[schema(...)]` wins over validator rules
+    #[validate(length(min = 1, max = 500))]
+    #[schema(max_length = 280)]
+    summary: String,
+}
+
+#[test]
+fn test_schema_constraint_attributes() {
+    let schema = Listing::schema();
+
+    let discount = property(&schema, "discount");
+    assert_eq!(
+        (discount.minimum, discount.maximum),
+        (Some(0.0), Some(100.0))
+    );
+
+    let title = property(&schema, "title");
+    assert_eq!((title.min_length, title.max_length), (Some(3), Some(80)));
+    assert_eq!(title.pattern.as_deref(), Some("^[A-Z]"));
+
+    let photos = property(&schema, "photos");
+    assert_eq!((photos.min_items, photos.max_items), (Some(1), Some(10)));
+
+    let summary = property(&schema, "summary");
+    assert_eq!(
+        (summary.min_length, summary.max_length),
+        (Some(1), Some(280))
+    );
+}
+
+#[test]
+fn test_constraint_attribute_keywords() {
+    let json = to_anthropic_schema(&Listing::schema());
+
+    assert_eq!(json["properties"]["discount"]["minimum"], 0);
+    assert_eq!(json["properties"]["discount"]["maximum"], 100);
+    assert_eq!(json["properties"]["title"]["pattern"], "^[A-Z]");
+    assert_eq!(json["properties"]["photos"]["minItems"], 1);
+    assert_eq!(json["properties"]["photos"]["maxItems"], 10);
+}
+
+#[derive(Schema)]
+#[allow(dead_code)]
+struct Ballot {
+    #[schema(min_items = 1, max_items = 3, unique_items)]
+    choices: Vec<u32>,
+}
+
+#[test]
+fn test_unique_items() {
+    let schema = Ballot::schema();
+    let choices = property(&schema, "choices");
+    assert!(choices.unique_items);
+    assert_eq!((choices.min_items, choices.max_items), (Some(1), Some(3)));
+
+    let json = to_anthropic_schema(&schema);
+    assert_eq!(json["properties"]["choices"]["uniqueItems"], true);
+    assert_eq!(json["properties"]["choices"]["maxItems"], 3);
+
+    assert!(validate(&schema, &json!({ "choices": [1, 2] })).is_ok());
+    let errors = validate(&schema, &json!({ "choices": [1, 1] })).unwrap_err();
+    assert_eq!(errors.len(), 1, "{:?}", errors);
+    assert_eq!(errors[0].path, "/choices");
+    assert_eq!(errors[0].message, "expected unique items");
+}
@@ -99,7 +99,7 @@ fn test_simple_enum_with_docs() {
 }
 
 #[test]
-fn test_tagged_union_with_docs() {
+fn test_variant_with_docs() {
     let schema = Action::schema();
 
     // Check enum description
@@ -109,29 +109,114 @@ fn test_tagged_union_with_docs() {
     );
 
     match schema.kind {
-        TypeKind::TaggedUnion {
-            tag_field,
-            tag_variants,
-            data_fields,
-        } => {
-            assert_eq!(tag_field, "type");
-            assert_eq!(tag_variants.len(), 3);
+        TypeKind::Variant { cases } => {
+            assert_eq!(cases.len(), 3);
 
-            // Check data field descriptions
-            let value_schema = data_fields.get("value").unwrap();
+            // Check per-case descriptions
+            assert_eq!(cases[0].description, None);
+            assert_eq!(cases[1].description, Some("Fill a form field".to_string()));
+            assert_eq!(
+                cases[2].description,
+                Some("Select from a dropdown".to_string())
+            );
+
+            // Check payload field descriptions
+            let TypeKind::Object { properties, .. } = &cases[1].data.as_ref().unwrap().kind else {
+                panic!("Expected Object payload");
+            };
+            let value_schema = properties.get("value").unwrap();
             assert_eq!(
                 value_schema.description,
                 Some("The value to enter".to_string())
             );
             assert!(matches!(value_schema.kind, TypeKind::String));
 
-            let option_schema = data_fields.get("option").unwrap();
+            let TypeKind::Object { properties, .. } = &cases[2].data.as_ref().unwrap().kind else {
+                panic!("Expected Object payload");
+            };
+            let option_schema = properties.get("option").unwrap();
             assert_eq!(
                 option_schema.description,
                 Some("The option to select".to_string())
             );
             assert!(matches!(option_schema.kind, TypeKind::String));
         }
-        _ => panic!("Expected TaggedUnion schema"),
+        _ => panic!("Expected Variant schema"),
     }
 }
+
+/// Shipping address
+///
+/// Used for delivery and billing. Must be a
+/// physical location, not a PO box.
+#[derive(Schema)]
+#[allow(dead_code)]
+struct ShippingAddress {
+    /// Street line
+    ///
+    /// Include the house number.
+    street: String,
+    /// Postal code
+    zip: String,
+}
+
+/// Raw note
+///
+/// Second paragraph stays attached.
+#[derive(Schema)]
+#[schema(title_from_docs = false)]
+#[allow(dead_code)]
+struct Note {
+    text: String,
+}
+
+#[derive(Schema)]
+#[schema(title = "Coordinates")]
+#[allow(dead_code)]
+struct Point {
+    #[schema(title = "Latitude", description = "Degrees north")]
+    lat: f64,
+}
+
+#[test]
+fn test_multi_paragraph_docs_split_into_title() {
+    let schema = ShippingAddress::schema();
+    assert_eq!(schema.title.as_deref(), Some("Shipping address"));
+    assert_eq!(
+        schema.description.as_deref(),
+        Some("Used for delivery and billing. Must be a\nphysical location, not a PO box.")
+    );
+
+    let street = schema.at_path("/properties/street").unwrap();
+    assert_eq!(street.title.as_deref(), Some("Street line"));
+    assert_eq!(
+        street.description.as_deref(),
+        Some("Include the house number.")
+    );
+
+    // Single paragraphs stay descriptions
+    let zip = schema.at_path("/properties/zip").unwrap();
+    assert_eq!(zip.title, None);
+    assert_eq!(zip.description.as_deref(), Some("Postal code"));
+}
+
+#[test]
+fn test_title_from_docs_disabled() {
+    let schema = Note::schema();
+    assert_eq!(schema.title, None);
+    assert_eq!(
+        schema.description.as_deref(),
+        Some("Raw note\n\nSecond paragraph stays attached.")
+    );
+    assert_eq!(schema.docs(), schema.description);
+}
+
+#[test]
+fn test_explicit_title() {
+    let schema = Point::schema();
+    assert_eq!(schema.title.as_deref(), Some("Coordinates"));
+
+    let lat = schema.at_path("/properties/lat").unwrap();
+    assert_eq!(lat.title.as_deref(), Some("Latitude"));
+    assert_eq!(lat.docs().as_deref(), Some("Latitude\n\nDegrees north"));
+}
@@ -0,0 +1,186 @@
+use schema::{Schema, TypeKind};
+use std::marker::PhantomData;
+
+#[derive(Schema)]
+#[allow(dead_code)]
+struct User {
+    name: String,
+}
+
+/// One page of results
+#[derive(Schema)]
+#[allow(dead_code)]
+struct Page<T> {
+    items: Vec<T>,
+    next: Option<String>,
+}
+
+/// Not `Schema`; only ever used as a marker
+#[allow(dead_code)]
+struct Tenant;
+
+#[derive(Schema)]
+#[allow(dead_code)]
+struct Scoped<S, T> {
+    value: T,
+    scope: PhantomData<S>,
+}
+
+#[derive(Schema)]
+#[allow(dead_code)]
+enum Either<L, R> {
+    Left(L),
+    Right(R),
+}
+
+#[test]
+fn test_generic_struct() {
+    let schema = Page::<User>::schema();
+    assert_eq!(schema.name.as_deref(), Some("Page_User"));
+    assert_eq!(schema.description.as_deref(), Some("One page of results"));
+    assert_eq!(
+        schema
+            .at_path("/properties/items/items")
+            .unwrap()
+            .name
+            .as_deref(),
+        Some("User")
+    );
+
+    assert_eq!(Page::<User>::type_name(), Some("Page_User"));
+    assert_eq!(Page::<Page<String>>::type_name(), Some("Page_Page_String"));
+}
+
+#[test]
+fn test_phantom_params_need_no_bound() {
+    let schema = Scoped::<Tenant, u32>::schema();
+    assert_eq!(
+        schema.at_path("/properties/value").unwrap().kind,
+        TypeKind::Integer(schema::IntegerKind::U32)
+    );
+    assert_eq!(
+        Scoped::<Tenant, u32>::type_name(),
+        Some("Scoped_Tenant_u32")
+    );
+}
+
+#[test]
+fn test_generic_instantiations_are_distinct() {
+    let normalized = Page::<Either<User, Page<User>>>::schema().normalize();
+    assert_eq!(
+        normalized.root.name.as_deref(),
+        Some("Page_Either_User_Page_User")
+    );
+    assert_ne!(
+        Either::<User, String>::type_name(),
+        Either::<String, User>::type_name()
+    );
+}
+
+#[derive(Schema)]
+#[allow(dead_code)]
+struct Buf<const N: usize> {
+    data: [u8; N],
+}
+
+#[derive(Schema)]
+#[allow(dead_code)]
+struct Matrix<T, const R: usize, const C: usize> {
+    rows: [[T; C]; R],
+}
+
+#[test]
+fn test_const_generics() {
+    let schema = Buf::<16>::schema();
+    assert_eq!(schema.name.as_deref(), Some("Buf_16"));
+    let data = schema.at_path("/properties/data").unwrap();
+    assert_eq!(
+        (data.constraints.min_items, data.constraints.max_items),
+        (Some(16), Some(16))
+    );
+
+    let schema = Matrix::<f32, 2, 3>::schema();
+    assert_eq!(schema.name.as_deref(), Some("Matrix_f32_2_3"));
+    let rows = schema.at_path("/properties/rows").unwrap();
+    assert_eq!(rows.constraints.max_items, Some(2));
+    let cols = schema.at_path("/properties/rows/items").unwrap();
+    assert_eq!(cols.constraints.max_items, Some(3));
+}
+
+#[derive(Schema)]
+#[allow(dead_code)]
+struct Pinned<T>
+where
+    T: Clone,
+{
+    item: T,
+}
+
+trait Entity {
+    type Id;
+}
+
+impl Entity for Tenant {
+    type Id = u64;
+}
+
+#[derive(Schema)]
+#[schema(bound = "E: Entity, E::Id: schema::Schema")]
+#[allow(dead_code)]
+struct Reference<E: Entity> {
+    id: E::Id,
+}
+
+#[test]
+fn test_existing_where_clause() {
+    let schema = Pinned::<String>::schema();
+    assert_eq!(schema.name.as_deref(), Some("Pinned_String"));
+    assert_eq!(
+        schema.at_path("/properties/item").unwrap().kind,
+        TypeKind::String
+    );
+}
+
+#[test]
+fn test_bound_override() {
+    // `Tenant` isn't `Schema`; only its `Id` needs to be
+    let schema = Reference::<Tenant>::schema();
+    assert_eq!(
+        schema.at_path("/properties/id").unwrap().kind,
+        TypeKind::Integer(schema::IntegerKind::U64)
+    );
+}
+
+#[derive(Schema)]
+#[allow(dead_code)]
+struct Query<'a> {
+    text: &'a str,
+    tags: &'a [String],
+    owner: Option<&'a User>,
+}
+
+#[derive(Schema)]
+#[allow(dead_code)]
+enum Token<'a, T> {
+    Word(std::borrow::Cow<'a, str>),
+    Value(&'a T),
+}
+
+#[test]
+fn test_lifetime_parameters() {
+    let schema = Query::schema();
+    assert_eq!(schema.name.as_deref(), Some("Query"));
+    assert_eq!(
+        schema.at_path("/properties/text").unwrap().kind,
+        TypeKind::String
+    );
+    assert_eq!(
+        schema.at_path("/properties/tags/items").unwrap().kind,
+        TypeKind::String
+    );
+    assert_eq!(
+        schema.at_path("/properties/owner").unwrap().name.as_deref(),
+        Some("User")
+    );
+    assert_eq!(Token::<u32>::type_name(), Some("Token_u32"));
+}
@@ -0,0 +1,109 @@
+use schema::{Schema, TypeKind, impl_schema, schema_alias};
+
+schema_alias!(pub type UserId = String, "Unique user identifier");
+schema_alias!(
+    #[derive(Debug, Clone, PartialEq)]
+    pub type Scores = Vec<u32>
+);
+
+#[derive(Schema)]
+#[allow(dead_code)]
+struct Team {
+    owner: UserId,
+    members: Vec<UserId>,
+    scores: Scores,
+}
+
+#[test]
+fn test_schema_alias() {
+    let schema = UserId::schema();
+    assert_eq!(schema.kind, TypeKind::String);
+    assert_eq!(UserId::type_name(), Some("UserId"));
+    assert_eq!(
+        schema.description.as_deref(),
+        Some("Unique user identifier")
+    );
+
+    let scores = Scores::from(vec![1, 2]);
+    assert_eq!(scores.clone(), Scores(vec![1, 2]));
+    assert_eq!(Scores::schema().description, None);
+}
+
+#[test]
+fn test_aliases_become_definitions() {
+    let normalized = Team::schema().normalize();
+    assert_eq!(normalized.definitions["UserId"].kind, TypeKind::String);
+    assert!(matches!(
+        normalized.definitions["Scores"].kind,
+        TypeKind::Array { .. }
+    ));
+    assert_eq!(
+        normalized
+            .root
+            .at_path("/properties/members/items")
+            .unwrap()
+            .kind,
+        TypeKind::Ref {
+            name: "UserId".to_string()
+        }
+    );
+}
+
+mod vendor {
+    #[allow(dead_code)]
+    pub struct Money {
+        pub amount: i64,
+        pub currency: String,
+        pub memo: Option<String>,
+    }
+}
+
+impl_schema! {
+    /// An amount of money
+    ///
+    /// In minor units.
+    vendor::Money {
+        /// Minor units, e.g. cents
+        amount: i64,
+        currency: String,
+        /// Free-form note
+        memo?: String
+    }
+}
+
+#[derive(Schema)]
+#[allow(dead_code)]
+struct Charge {
+    price: vendor::Money,
+    refunds: Vec<vendor::Money>,
+}
+
+#[test]
+fn test_impl_schema() {
+    let schema = vendor::Money::schema();
+    assert_eq!(schema.name.as_deref(), Some("Money"));
+    assert_eq!(
+        schema.description.as_deref(),
+        Some("An amount of money\n\nIn minor units.")
+    );
+    assert_eq!(schema.property_order, ["amount", "currency", "memo"]);
+    let TypeKind::Object { required, .. } = &schema.kind else {
+        panic!("expected object");
+    };
+    assert_eq!(required, &["amount", "currency"]);
+    assert_eq!(
+        schema
+            .at_path("/properties/amount")
+            .unwrap()
+            .description
+            .as_deref(),
+        Some("Minor units, e.g. cents")
+    );
+    assert_eq!(
+        schema.at_path("/properties/currency").unwrap().description,
+        None
+    );
+
+    let normalized = Charge::schema().normalize();
+    assert!(normalized.definitions.contains_key("Money"));
+}
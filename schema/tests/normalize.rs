@@ -0,0 +1,221 @@
+use schema::validate::validate;
+use schema::{Schema, SchemaType, TypeKind, VariantCase};
+use serde::Serialize;
+use serde_json::json;
+
+#[derive(Schema)]
+#[allow(dead_code)]
+struct Address {
+    street: String,
+    city: String,
+}
+
+#[derive(Schema)]
+#[allow(dead_code)]
+struct Person {
+    name: String,
+    /// Where they live
+    home: Address,
+    /// Where they work
+    work: Address,
+    previous: Vec<Address>,
+}
+
+#[derive(Schema)]
+#[schema(untagged)]
+#[allow(dead_code)]
+enum Wrapper {
+    Only { value: String },
+}
+
+#[derive(Schema, Serialize)]
+#[serde(tag = "kind")]
+enum Drawing {
+    Circle { radius: f64 },
+}
+
+#[derive(Schema, Serialize)]
+#[serde(tag = "kind", content = "data")]
+enum Boxed {
+    Value(u8),
+}
+
+#[test]
+fn test_shared_subtrees_become_refs() {
+    let normalized = Person::schema().normalize();
+
+    assert_eq!(normalized.definitions.len(), 1);
+    let address = normalized.definitions.get("Address").unwrap();
+    assert!(matches!(address.kind, TypeKind::Object { .. }));
+
+    let TypeKind::Object { properties, .. } = &normalized.root.kind else {
+        panic!("Expected Object root");
+    };
+
+    let home = properties.get("home").unwrap();
+    assert_eq!(
+        home.kind,
+        TypeKind::Ref {
+            name: "Address".to_string()
+        }
+    );
+    // Per-field docs survive on the reference
+    assert_eq!(home.description, Some("Where they live".to_string()));
+
+    let TypeKind::Array { items } = &properties.get("previous").unwrap().kind else {
+        panic!("Expected Array");
+    };
+    assert!(matches!(items.kind, TypeKind::Ref { .. }));
+}
+
+#[test]
+fn test_unique_subtrees_stay_inline() {
+    let normalized = Address::schema().normalize();
+    assert!(normalized.definitions.is_empty());
+    assert_eq!(normalized.root.name, Some("Address".to_string()));
+}
+
+#[test]
+fn test_required_sorted_and_deduplicated() {
+    let schema = SchemaType::new(TypeKind::Object {
+        properties: Default::default(),
+        required: vec!["b".to_string(), "a".to_string(), "b".to_string()],
+    });
+
+    let TypeKind::Object { required, .. } = schema.normalize().root.kind else {
+        panic!("Expected Object");
+    };
+    assert_eq!(required, vec!["a", "b"]);
+}
+
+#[test]
+fn test_single_case_variant_collapses() {
+    let normalized = Wrapper::schema().normalize();
+    let TypeKind::Object { properties, .. } = &normalized.root.kind else {
+        panic!("Expected single case to collapse into its payload");
+    };
+    assert!(properties.contains_key("value"));
+    assert_eq!(normalized.root.name, Some("Wrapper".to_string()));
+
+    let unit = SchemaType::new(TypeKind::Variant {
+        cases: vec![VariantCase {
+            name: "only".to_string(),
+            data: None,
+            description: None,
+            ..Default::default()
+        }],
+    });
+    assert_eq!(
+        unit.normalize().root.kind,
+        TypeKind::Enum {
+            variants: vec!["only".to_string()]
+        }
+    );
+}
+
+#[test]
+fn test_single_tagged_case_keeps_its_tag() {
+    let normalized = Drawing::schema().normalize();
+    let TypeKind::Object { required, .. } = &normalized.root.kind else {
+        panic!("Expected the case's record with its tag");
+    };
+    assert_eq!(required, &["kind", "radius"]);
+    let circle = serde_json::to_value(Drawing::Circle { radius: 1.0 }).unwrap();
+    assert!(validate(&normalized.root, &circle).is_ok());
+    assert!(validate(&normalized.root, &json!({ "radius": 1.0 })).is_err());
+    assert!(
+        validate(
+            &normalized.root,
+            &json!({ "kind": "Square", "radius": 1.0 })
+        )
+        .is_err()
+    );
+
+    let normalized = Boxed::schema().normalize();
+    let value = serde_json::to_value(Boxed::Value(7)).unwrap();
+    assert!(validate(&normalized.root, &value).is_ok());
+    assert!(validate(&normalized.root, &json!({ "data": 7 })).is_err());
+
+    // Both `{"type", "data"}` and the flattened form are accepted, so a
+    // default-tagged case isn't collapsed
+    let mut untagged = Wrapper::schema();
+    untagged.extensions.remove(schema::TAGGING);
+    assert!(matches!(
+        untagged.normalize().root.kind,
+        TypeKind::Variant { .. }
+    ));
+}
+
+#[test]
+fn test_fingerprint_is_order_independent() {
+    assert_eq!(
+        Person::schema().fingerprint(),
+        Person::schema().fingerprint()
+    );
+    assert_ne!(
+        Person::schema().fingerprint(),
+        Address::schema().fingerprint()
+    );
+}
+
+/// Shared by reference even where it's used once
+#[derive(Schema)]
+#[schema(as_ref)]
+#[allow(dead_code)]
+struct Catalog {
+    title: String,
+    entries: Vec<String>,
+}
+
+#[derive(Schema)]
+#[allow(dead_code)]
+struct Shelf {
+    catalog: Catalog,
+    /// Written out in place here, though used twice
+    #[schema(inline)]
+    primary: Address,
+    #[schema(inline)]
+    secondary: Address,
+    #[schema(as_ref)]
+    billing: Address,
+}
+
+#[derive(Schema)]
+#[allow(dead_code)]
+struct Desk {
+    #[schema(inline)]
+    catalog: Catalog,
+}
+
+#[test]
+fn test_ref_preferences() {
+    let normalized = Shelf::schema().normalize();
+    assert_eq!(
+        normalized.definitions.keys().collect::<Vec<_>>(),
+        ["Address", "Catalog"]
+    );
+    let property = |name: &str| normalized.root.at_path(&format!("/properties/{name}"));
+    assert!(matches!(
+        property("catalog").unwrap().kind,
+        TypeKind::Ref { .. }
+    ));
+    assert!(matches!(
+        property("billing").unwrap().kind,
+        TypeKind::Ref { .. }
+    ));
+    assert!(matches!(
+        property("primary").unwrap().kind,
+        TypeKind::Object { .. }
+    ));
+    assert!(matches!(
+        property("secondary").unwrap().kind,
+        TypeKind::Object { .. }
+    ));
+
+    // The preference is a sharing hint, not part of the normalized output
+    let catalog = &normalized.definitions["Catalog"];
+    assert!(catalog.ref_preference().is_none());
+
+    // A field-level preference overrides the type's default
+    assert!(Desk::schema().normalize().definitions.is_empty());
+}
@@ -0,0 +1,149 @@
+use schema::{Fallback, GenerateOptions, Schema, SchemaType, TypeKind};
+
+/// A tree node
+#[derive(Schema)]
+#[allow(dead_code)]
+struct TreeNode {
+    label: String,
+    children: Vec<TreeNode>,
+}
+
+#[derive(Schema)]
+#[allow(dead_code)]
+enum Expr {
+    Literal { value: i64 },
+    Neg { operands: Vec<Expr> },
+}
+
+#[derive(Schema)]
+#[allow(dead_code)]
+struct Inner {
+    value: i32,
+}
+
+#[derive(Schema)]
+#[allow(dead_code)]
+struct Middle {
+    inner: Inner,
+}
+
+#[derive(Schema)]
+#[allow(dead_code)]
+struct Outer {
+    middle: Middle,
+}
+
+#[test]
+fn test_recursive_struct_becomes_ref() {
+    let schema = TreeNode::schema();
+    assert_eq!(schema.name, Some("TreeNode".to_string()));
+
+    let children = schema.at_path("/properties/children/items").unwrap();
+    assert_eq!(
+        children.kind,
+        TypeKind::Ref {
+            name: "TreeNode".to_string()
+        }
+    );
+}
+
+#[test]
+fn test_recursive_enum_becomes_ref() {
+    let schema = Expr::schema();
+    let inner = schema
+        .at_path("/cases/Neg/properties/operands/items")
+        .unwrap();
+    assert_eq!(
+        inner.kind,
+        TypeKind::Ref {
+            name: "Expr".to_string()
+        }
+    );
+
+    // The guard is released afterwards, so a second call expands again
+    assert!(matches!(Expr::schema().kind, TypeKind::Variant { .. }));
+}
+
+#[test]
+fn test_default_options_are_identity() {
+    let schema = Outer::schema();
+    assert_eq!(GenerateOptions::default().apply(&schema), schema);
+}
+
+#[test]
+fn test_max_depth_emits_ref_for_named_types() {
+    let schema = GenerateOptions::new().max_depth(1).apply(&Outer::schema());
+
+    let middle = schema.at_path("/properties/middle").unwrap();
+    assert_eq!(
+        middle.kind,
+        TypeKind::Ref {
+            name: "Middle".to_string()
+        }
+    );
+}
+
+#[test]
+fn test_max_depth_any_object() {
+    let schema = GenerateOptions::new()
+        .max_depth(1)
+        .on_depth_exceeded(Fallback::AnyObject)
+        .apply(&Outer::schema());
+
+    let middle = schema.at_path("/properties/middle").unwrap();
+    assert!(matches!(
+        &middle.kind,
+        TypeKind::Object { properties, .. } if properties.is_empty()
+    ));
+}
+
+#[test]
+fn test_max_depth_unnamed_falls_back_to_object() {
+    let schema = SchemaType::new(TypeKind::Array {
+        items: Box::new(SchemaType::new(TypeKind::Array {
+            items: Box::new(SchemaType::new(TypeKind::String)),
+        })),
+    });
+
+    let bounded = GenerateOptions::new().max_depth(1).apply(&schema);
+    let items = bounded.at_path("/items").unwrap();
+    assert!(matches!(items.kind, TypeKind::Object { .. }));
+}
+
+#[test]
+fn test_cycle_any_object() {
+    let schema = GenerateOptions::new()
+        .on_cycle(Fallback::AnyObject)
+        .apply(&TreeNode::schema());
+
+    let children = schema.at_path("/properties/children/items").unwrap();
+    assert!(matches!(children.kind, TypeKind::Object { .. }));
+}
+
+#[test]
+fn test_large_enum_becomes_string() {
+    let codes: Vec<String> = (0..300).map(|i| format!("c{:03}", i)).collect();
+    let schema = SchemaType {
+        kind: TypeKind::Enum {
+            variants: codes.clone(),
+        },
+        description: Some("Country code".to_string()),
+        ..Default::default()
+    };
+
+    let bounded = GenerateOptions::new().max_enum_variants(3).apply(&schema);
+    assert_eq!(bounded.kind, TypeKind::String);
+    assert_eq!(
+        bounded.description.as_deref(),
+        Some("Country code\n\nOne of: c000, c001, c002, ... (297 more)")
+    );
+
+    // Small enums are left alone
+    let small = GenerateOptions::new().max_enum_variants(300).apply(&schema);
+    assert_eq!(small, schema);
+
+    // The original schema still validates against the full set
+    let value = serde_json::json!("c250");
+    assert!(schema::validate::validate(&schema, &value).is_ok());
+    assert!(schema::validate::validate(&schema, &serde_json::json!("zz")).is_err());
+}
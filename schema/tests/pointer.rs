@@ -0,0 +1,111 @@
+use schema::{PathSegment, Schema, SchemaPath, TypeKind};
+use std::collections::HashMap;
+
+#[derive(Schema)]
+#[allow(dead_code)]
+struct Address {
+    city: String,
+    zip: Option<String>,
+}
+
+#[derive(Schema)]
+#[allow(dead_code)]
+enum Contact {
+    Email { address: String },
+    Phone(String),
+}
+
+#[derive(Schema)]
+#[allow(dead_code)]
+struct Person {
+    address: Address,
+    contacts: Vec<Contact>,
+    tags: HashMap<String, u32>,
+    pair: (String, bool),
+}
+
+#[test]
+fn test_at_path() {
+    let schema = Person::schema();
+
+    let city = schema
+        .at_path("/properties/address/properties/city")
+        .unwrap();
+    assert_eq!(city.kind, TypeKind::String);
+
+    let email = schema
+        .at_path("/properties/contacts/items/cases/Email/properties/address")
+        .unwrap();
+    assert_eq!(email.kind, TypeKind::String);
+
+    let tag_value = schema.at_path("/properties/tags/value").unwrap();
+    assert!(matches!(tag_value.kind, TypeKind::Integer(_)));
+
+    let second = schema.at_path("/properties/pair/fields/1").unwrap();
+    assert_eq!(second.kind, TypeKind::Boolean);
+
+    assert_eq!(schema.at_path("").unwrap(), &schema);
+}
+
+#[test]
+fn test_at_path_missing() {
+    let schema = Person::schema();
+
+    assert!(schema.at_path("/properties/nope").is_none());
+    assert!(schema.at_path("/properties/address/items").is_none());
+    assert!(schema.at_path("/properties/pair/fields/9").is_none());
+    assert!(schema.at_path("properties/address").is_none());
+    assert!(schema.at_path("/bogus").is_none());
+}
+
+#[test]
+fn test_at_path_mut() {
+    let mut schema = Person::schema();
+
+    schema
+        .at_path_mut("/properties/address/properties/city")
+        .unwrap()
+        .description = Some("City name".to_string());
+
+    assert_eq!(
+        schema
+            .at_path("/properties/address/properties/city")
+            .unwrap()
+            .description,
+        Some("City name".to_string())
+    );
+}
+
+#[test]
+fn test_typed_path_round_trip() {
+    let path = SchemaPath::root()
+        .property("contacts")
+        .items()
+        .case("Phone");
+    assert_eq!(path.to_string(), "/properties/contacts/items/cases/Phone");
+
+    let parsed: SchemaPath = path.to_string().parse().unwrap();
+    assert_eq!(parsed, path);
+    assert_eq!(Person::schema().at(&path).unwrap().kind, TypeKind::String);
+}
+
+#[test]
+fn test_escaping() {
+    let path = SchemaPath::root().property("a/b~c");
+    assert_eq!(path.to_string(), "/properties/a~1b~0c");
+
+    let parsed: SchemaPath = "/properties/a~1b~0c".parse().unwrap();
+    assert_eq!(
+        parsed.segments(),
+        &[PathSegment::Property("a/b~c".to_string())]
+    );
+}
+
+#[test]
+fn test_parse_errors() {
+    let err = "/properties".parse::<SchemaPath>().unwrap_err();
+    assert!(err.to_string().contains("property name"));
+
+    let err = "/fields/x".parse::<SchemaPath>().unwrap_err();
+    assert!(err.to_string().contains("bad field index"));
+}
@@ -0,0 +1,201 @@
+use schema::validate::validate;
+use schema::{Schema, Tagging, TypeKind};
+use serde::Serialize;
+use serde_json::json;
+
+#[derive(Schema, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Account {
+    user_id: u64,
+    display_name: String,
+    #[serde(rename = "e-mail", skip_serializing_if = "Option::is_none")]
+    email: Option<String>,
+    #[serde(skip)]
+    #[allow(dead_code)]
+    password_hash: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    roles: Vec<String>,
+}
+
+#[derive(Schema, Serialize)]
+#[serde(
+    tag = "kind",
+    rename_all = "snake_case",
+    rename_all_fields = "camelCase"
+)]
+enum Event {
+    UserCreated {
+        user_id: u64,
+    },
+    #[serde(rename = "gone")]
+    UserDeleted {
+        user_id: u64,
+        hard_delete: bool,
+    },
+    Heartbeat,
+}
+
+#[derive(Schema, Serialize)]
+#[serde(tag = "t", content = "c")]
+#[allow(dead_code)]
+enum Reply {
+    Text(String),
+    #[schema(rename = "nothing")]
+    Empty,
+}
+
+#[derive(Schema, Serialize)]
+#[serde(transparent)]
+struct UserId(u64);
+
+#[test]
+fn test_field_renames() {
+    let schema = Account::schema();
+    assert_eq!(
+        schema.property_order,
+        ["userId", "displayName", "e-mail", "roles"]
+    );
+    let TypeKind::Object { required, .. } = &schema.kind else {
+        panic!("expected an object");
+    };
+    assert_eq!(required, &["userId", "displayName"]);
+
+    let account = Account {
+        user_id: 7,
+        display_name: "Ada".to_string(),
+        email: None,
+        password_hash: "x".to_string(),
+        roles: vec![],
+    };
+    assert_eq!(
+        validate(&schema, &serde_json::to_value(&account).unwrap()),
+        Ok(())
+    );
+}
+
+#[test]
+fn test_enum_representation() {
+    let schema = Event::schema();
+    assert_eq!(
+        schema.tagging(),
+        Some(Tagging::Internal {
+            tag: "kind".to_string()
+        })
+    );
+    let TypeKind::Variant { cases } = &schema.kind else {
+        panic!("expected a variant");
+    };
+    let names: Vec<&str> = cases.iter().map(|case| case.name.as_str()).collect();
+    assert_eq!(names, ["user_created", "gone", "heartbeat"]);
+
+    for event in [
+        Event::UserCreated { user_id: 1 },
+        Event::UserDeleted {
+            user_id: 1,
+            hard_delete: true,
+        },
+        Event::Heartbeat,
+    ] {
+        let value = serde_json::to_value(&event).unwrap();
+        assert_eq!(validate(&schema, &value), Ok(()), "{value}");
+    }
+    assert!(validate(&schema, &json!({ "kind": "gone", "user_id": 1 })).is_err());
+}
+
+#[test]
+fn test_schema_attributes_take_precedence() {
+    let schema = Reply::schema();
+    let TypeKind::Variant { cases } = &schema.kind else {
+        panic!("expected a variant");
+    };
+    // Unrenamed variants keep their identifiers, as serde writes them
+    assert_eq!(cases[0].name, "Text");
+    assert_eq!(cases[1].name, "nothing");
+    assert_eq!(
+        validate(
+            &schema,
+            &serde_json::to_value(Reply::Text("hi".into())).unwrap()
+        ),
+        Ok(())
+    );
+}
+
+#[test]
+fn test_transparent() {
+    assert!(matches!(UserId::schema().kind, TypeKind::Integer { .. }));
+    assert_eq!(
+        validate(&UserId::schema(), &serde_json::to_value(UserId(3)).unwrap()),
+        Ok(())
+    );
+}
+
+#[derive(Schema, Serialize)]
+#[serde(tag = "kind", rename = "circle")]
+struct Circle {
+    radius: f64,
+}
+
+#[derive(Schema, Serialize, Default)]
+#[serde(default)]
+struct Limits {
+    max_items: u32,
+    label: String,
+}
+
+#[derive(Schema, Serialize)]
+#[allow(dead_code)]
+enum Visibility {
+    Public,
+    #[serde(skip)]
+    Hidden(String),
+    Team {
+        id: u64,
+    },
+}
+
+#[test]
+fn test_tagged_struct() {
+    let schema = Circle::schema();
+    assert_eq!(schema.property_order, ["kind", "radius"]);
+    let TypeKind::Object {
+        properties,
+        required,
+    } = &schema.kind
+    else {
+        panic!("expected an object");
+    };
+    assert_eq!(required, &["kind", "radius"]);
+    assert!(matches!(
+        &properties["kind"].kind,
+        TypeKind::Enum { variants } if variants == &["circle"]
+    ));
+
+    let value = serde_json::to_value(Circle { radius: 1.5 }).unwrap();
+    assert_eq!(value, json!({ "kind": "circle", "radius": 1.5 }));
+    assert_eq!(validate(&schema, &value), Ok(()));
+    assert!(validate(&schema, &json!({ "kind": "square", "radius": 1.5 })).is_err());
+}
+
+#[test]
+fn test_container_default() {
+    let schema = Limits::schema();
+    let TypeKind::Object { required, .. } = &schema.kind else {
+        panic!("expected an object");
+    };
+    assert!(required.is_empty());
+    assert_eq!(validate(&schema, &json!({})), Ok(()));
+    assert_eq!(
+        validate(&schema, &serde_json::to_value(Limits::default()).unwrap()),
+        Ok(())
+    );
+}
+
+#[test]
+fn test_skipped_variant() {
+    let schema = Visibility::schema();
+    let TypeKind::Variant { cases } = &schema.kind else {
+        panic!("expected a variant");
+    };
+    let names: Vec<&str> = cases.iter().map(|case| case.name.as_str()).collect();
+    assert_eq!(names, ["Public", "Team"]);
+}
@@ -0,0 +1,175 @@
+use schema::validate::validate;
+use schema::{Schema, Tagging, TypeKind};
+use schema_anthropic::to_anthropic_schema;
+use serde_json::json;
+
+#[derive(Schema)]
+#[schema(tag = "kind", rename_all_variants = "lowercase")]
+#[allow(dead_code)]
+enum Shape {
+    Circle { radius: f64 },
+    Rect { width: f64, height: f64 },
+    Empty,
+}
+
+#[derive(Schema)]
+#[schema(tag = "t", content = "c", rename_all_variants = "lowercase")]
+#[allow(dead_code)]
+enum Message {
+    Text(String),
+    Move { x: i32, y: i32 },
+    Ping,
+}
+
+/// Unit-only enums become cases too, since each is an object on the wire
+#[derive(Schema)]
+#[schema(tag = "op")]
+#[allow(dead_code)]
+enum Command {
+    Start,
+    Stop,
+}
+
+#[test]
+fn test_tagging_recorded() {
+    assert_eq!(
+        Shape::schema().tagging(),
+        Some(Tagging::Internal {
+            tag: "kind".to_string()
+        })
+    );
+    assert_eq!(
+        Message::schema().tagging(),
+        Some(Tagging::Adjacent {
+            tag: "t".to_string(),
+            content: "c".to_string()
+        })
+    );
+
+    let command = Command::schema();
+    assert!(matches!(command.kind, TypeKind::Variant { .. }));
+    assert_eq!(command.tagging().unwrap().tag(), Some("op"));
+}
+
+#[test]
+fn test_validate_internal_tagging() {
+    let schema = Shape::schema();
+    assert_eq!(
+        validate(&schema, &json!({ "kind": "circle", "radius": 1.5 })),
+        Ok(())
+    );
+    assert_eq!(validate(&schema, &json!({ "kind": "empty" })), Ok(()));
+
+    let errors = validate(&schema, &json!({ "kind": "rect", "width": 2.0 })).unwrap_err();
+    assert!(errors[0].message.contains("height"), "{:?}", errors);
+    // The default `type`/`data` shape no longer applies
+    assert!(validate(&schema, &json!({ "type": "empty" })).is_err());
+    assert!(validate(&schema, &json!("empty")).is_err());
+}
+
+#[test]
+fn test_validate_adjacent_tagging() {
+    let schema = Message::schema();
+    assert_eq!(
+        validate(&schema, &json!({ "t": "text", "c": "hi" })),
+        Ok(())
+    );
+    assert_eq!(
+        validate(&schema, &json!({ "t": "move", "c": { "x": 1, "y": 2 } })),
+        Ok(())
+    );
+    assert_eq!(validate(&schema, &json!({ "t": "ping" })), Ok(()));
+
+    let errors = validate(&schema, &json!({ "t": "text", "c": 5 })).unwrap_err();
+    assert_eq!(errors[0].path, "/c");
+    assert!(validate(&schema, &json!({ "t": "move" })).is_err());
+}
+
+#[test]
+fn test_anthropic_uses_tag_names() {
+    let shape = to_anthropic_schema(&Shape::schema());
+    assert_eq!(shape["required"], json!(["kind"]));
+    assert_eq!(
+        shape["properties"]["kind"]["enum"],
+        json!(["circle", "rect", "empty"])
+    );
+    assert_eq!(shape["properties"]["radius"]["type"], "number");
+
+    let message = to_anthropic_schema(&Message::schema());
+    assert_eq!(message["required"], json!(["t"]));
+    assert_eq!(message["properties"]["c"]["anyOf"][0]["type"], "string");
+    assert!(message["properties"].get("x").is_none());
+}
+
+#[derive(Schema)]
+#[schema(untagged)]
+#[allow(dead_code)]
+enum Id {
+    Number(u64),
+    Named { name: String },
+    Unknown,
+}
+
+#[test]
+fn test_untagged() {
+    let schema = Id::schema();
+    assert_eq!(schema.tagging(), Some(Tagging::Untagged));
+
+    assert_eq!(validate(&schema, &json!(42)), Ok(()));
+    assert_eq!(validate(&schema, &json!({ "name": "root" })), Ok(()));
+    assert_eq!(validate(&schema, &json!(null)), Ok(()));
+    // No discriminator is expected, or allowed to stand in for the payload
+    assert!(validate(&schema, &json!("number")).is_err());
+    assert!(validate(&schema, &json!({ "type": "number", "data": 1 })).is_err());
+
+    let anthropic = to_anthropic_schema(&schema);
+    assert_eq!(anthropic["anyOf"][0]["type"], "integer");
+    assert_eq!(anthropic["anyOf"][1]["required"], json!(["name"]));
+    assert_eq!(anthropic["anyOf"][2]["type"], "null");
+    assert!(anthropic.get("properties").is_none());
+}
+
+#[derive(Schema)]
+#[schema(legacy_tagged_union)]
+#[allow(dead_code)]
+enum Event {
+    Click { x: i32, y: i32 },
+    Key(String),
+    Close,
+}
+
+#[derive(Schema)]
+#[schema(tag = "op", legacy_tagged_union)]
+#[allow(dead_code)]
+enum Op {
+    Add { value: i64 },
+    Reset,
+}
+
+#[test]
+fn test_legacy_tagged_union() {
+    let TypeKind::TaggedUnion {
+        tag_field,
+        tag_variants,
+        data_fields,
+    } = Event::schema().kind
+    else {
+        panic!("expected a TaggedUnion");
+    };
+    assert_eq!(tag_field, "type");
+    assert_eq!(tag_variants, ["Click", "Key", "Close"]);
+    let mut names: Vec<&str> = data_fields.keys().map(String::as_str).collect();
+    names.sort();
+    assert_eq!(names, ["data", "x", "y"]);
+    assert!(matches!(data_fields["data"].kind, TypeKind::String));
+
+    let op = Op::schema();
+    assert!(op.tagging().is_none());
+    assert!(matches!(
+        &op.kind,
+        TypeKind::TaggedUnion { tag_field, .. } if tag_field == "op"
+    ));
+
+    // Without the opt-out, data enums stay variants
+    assert!(matches!(Shape::schema().kind, TypeKind::Variant { .. }));
+}
@@ -0,0 +1,120 @@
+use schema::validate::{check_integer, validate, validate_normalized};
+use schema::{IntegerKind, Schema};
+use serde_json::json;
+use std::collections::HashSet;
+
+#[derive(Schema)]
+#[allow(dead_code)]
+struct Pixel {
+    x: i32,
+    red: u8,
+    count: u64,
+    label: Option<String>,
+}
+
+#[derive(Schema)]
+#[allow(dead_code)]
+enum Shape {
+    Empty,
+    Circle { radius: f64 },
+}
+
+#[derive(Schema)]
+#[allow(dead_code)]
+struct Canvas {
+    main: Pixel,
+    backup: Pixel,
+}
+
+#[test]
+fn test_valid_value() {
+    let value = json!({"x": -5, "red": 255, "count": 18446744073709551615u64});
+    assert!(validate(&Pixel::schema(), &value).is_ok());
+}
+
+#[test]
+fn test_unsigned_rejects_negative() {
+    let errors = validate(&Pixel::schema(), &json!({"x": 0, "red": -1, "count": 0})).unwrap_err();
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].path, "/red");
+    assert_eq!(
+        errors[0].message,
+        "-1 is out of range for u8 (allowed 0..=255)"
+    );
+}
+
+#[test]
+fn test_width_bounds() {
+    assert!(check_integer(IntegerKind::U8, &json!(256)).is_err());
+    assert!(check_integer(IntegerKind::I32, &json!(2147483648i64)).is_err());
+    assert!(check_integer(IntegerKind::I32, &json!(-2147483648i64)).is_ok());
+    assert!(check_integer(IntegerKind::U32, &json!(4294967295u32)).is_ok());
+    assert!(check_integer(IntegerKind::I64, &json!(u64::MAX)).is_err());
+    assert!(check_integer(IntegerKind::Usize, &json!(u64::MAX)).is_ok());
+
+    let err = check_integer(IntegerKind::U32, &json!(1.5)).unwrap_err();
+    assert_eq!(err, "expected u32 integer, found number 1.5");
+}
+
+#[test]
+fn test_missing_and_mistyped() {
+    let errors = validate(&Pixel::schema(), &json!({"x": "0", "label": 3})).unwrap_err();
+    let rendered: Vec<String> = errors.iter().map(|e| e.to_string()).collect();
+
+    assert!(rendered.contains(&"/: missing required property \"red\"".to_string()));
+    assert!(rendered.contains(&"/: missing required property \"count\"".to_string()));
+    assert!(rendered.contains(&"/x: expected i32 integer, found string".to_string()));
+    assert!(rendered.contains(&"/label: expected string, found number 3".to_string()));
+}
+
+#[test]
+fn test_variant_shapes() {
+    let schema = Shape::schema();
+
+    assert!(validate(&schema, &json!("Empty")).is_ok());
+    assert!(validate(&schema, &json!({"type": "Circle", "radius": 1.0})).is_ok());
+    assert!(validate(&schema, &json!({"type": "Circle", "data": {"radius": 1.0}})).is_ok());
+    assert!(validate(&schema, &json!("Circle")).is_err());
+    assert!(validate(&schema, &json!({"type": "Square"})).is_err());
+}
+
+#[test]
+fn test_set_uniqueness() {
+    let schema = HashSet::<String>::schema();
+    assert!(validate(&schema, &json!(["a", "b"])).is_ok());
+    assert!(validate(&schema, &json!(["a", "a"])).is_err());
+}
+
+#[derive(Schema)]
+#[allow(dead_code)]
+struct Tree {
+    value: u8,
+    children: Vec<Tree>,
+}
+
+#[test]
+fn test_recursive_refs_resolve_to_the_root() {
+    let normalized = Tree::schema().normalize();
+    assert!(normalized.definitions.contains_key("Tree"));
+
+    let leaf = json!({"value": 2, "children": []});
+    assert!(validate_normalized(&normalized, &json!({"value": 1, "children": [leaf]})).is_ok());
+
+    let bad = json!({"value": 1, "children": [{"value": 2, "children": [{"value": 300, "children": []}]}]});
+    let errors = validate_normalized(&normalized, &bad).unwrap_err();
+    assert_eq!(errors[0].path, "/children/0/children/0/value");
+}
+
+#[test]
+fn test_refs_resolved_through_definitions() {
+    let normalized = Canvas::schema().normalize();
+    assert!(!normalized.definitions.is_empty());
+
+    let pixel = json!({"x": 0, "red": 0, "count": 0});
+    let bad = json!({"x": 0, "red": 300, "count": 0});
+    assert!(validate_normalized(&normalized, &json!({"main": pixel, "backup": pixel})).is_ok());
+
+    let errors =
+        validate_normalized(&normalized, &json!({"main": pixel, "backup": bad})).unwrap_err();
+    assert_eq!(errors[0].path, "/backup/red");
+}
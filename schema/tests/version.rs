@@ -0,0 +1,133 @@
+use schema::validate::validate;
+use schema::{Schema, TypeKind, Version};
+use serde_json::json;
+
+#[derive(Schema)]
+#[allow(dead_code)]
+struct Order {
+    id: u64,
+    #[schema(since = "1.2.0")]
+    coupon: String,
+    items: Vec<Item>,
+    status: OrderStatus,
+    payment: Payment,
+}
+
+#[derive(Schema)]
+#[allow(dead_code)]
+struct Item {
+    sku: String,
+    #[schema(since = "2.0.0-beta.1")]
+    gift_wrap: Option<bool>,
+}
+
+#[derive(Schema)]
+#[allow(dead_code)]
+enum OrderStatus {
+    Pending,
+    Shipped,
+    #[schema(since = "1.5.0")]
+    Returned,
+}
+
+#[derive(Schema)]
+#[allow(dead_code)]
+enum Payment {
+    Card {
+        last4: String,
+    },
+    #[schema(since = "2.0.0")]
+    Wallet {
+        provider: String,
+    },
+}
+
+fn v(version: &str) -> Version {
+    version.parse().unwrap()
+}
+
+#[test]
+fn test_version_ordering() {
+    assert!(v("1.2.0") < v("1.10.0"));
+    assert!(v("2.0.0-beta.1") < v("2.0.0-beta.2"));
+    assert!(v("2.0.0-beta.2") < v("2.0.0-beta.11"));
+    assert!(v("2.0.0-alpha") < v("2.0.0-beta"));
+    assert!(v("2.0.0-rc.1") < v("2.0.0"));
+    assert_eq!(v("1.0.0+build.5"), v("1.0.0"));
+    assert_eq!(v("2.0.0-rc.1").to_string(), "2.0.0-rc.1");
+
+    assert!("1.2".parse::<Version>().is_err());
+    assert!("1.x.0".parse::<Version>().is_err());
+    assert!("1.0.0-".parse::<Version>().is_err());
+}
+
+#[test]
+fn test_narrow_removes_later_fields() {
+    let schema = Order::schema();
+
+    let old = schema.narrow_to_version(&v("1.0.0"));
+    let TypeKind::Object {
+        properties,
+        required,
+    } = &old.kind
+    else {
+        panic!("expected object");
+    };
+    assert!(!properties.contains_key("coupon"));
+    assert!(!required.contains(&"coupon".to_string()));
+    assert!(!old.property_order.contains(&"coupon".to_string()));
+
+    assert!(
+        old.at_path("/properties/items/items/properties/gift_wrap")
+            .is_none()
+    );
+    let TypeKind::Enum { variants } = &old.at_path("/properties/status").unwrap().kind else {
+        panic!("expected enum");
+    };
+    assert_eq!(variants.len(), 2);
+    let TypeKind::Variant { cases } = &old.at_path("/properties/payment").unwrap().kind else {
+        panic!("expected variant");
+    };
+    assert_eq!(cases.len(), 1);
+
+    // Pre-releases of 2.0.0 see beta fields but not 2.0.0 ones
+    let beta = schema.narrow_to_version(&v("2.0.0-rc.1"));
+    assert!(
+        beta.at_path("/properties/items/items/properties/gift_wrap")
+            .is_some()
+    );
+    assert!(beta.at_path("/properties/payment/cases/Wallet").is_none());
+
+    assert_eq!(schema.narrow_to_version(&v("2.0.0")), schema);
+}
+
+#[test]
+fn test_narrowed_schema_validates_old_payloads() {
+    let payload = json!({
+        "id": 7,
+        "items": [{ "sku": "A-1" }],
+        "status": "Pending",
+        "payment": { "type": "Card", "data": { "last4": "4242" } }
+    });
+
+    assert!(validate(&Order::schema(), &payload).is_err());
+    let old = Order::schema().narrow_to_version(&v("1.1.0"));
+    assert_eq!(validate(&old, &payload), Ok(()));
+}
+
+#[derive(Schema)]
+#[schema(version = "2.1.0")]
+#[allow(dead_code)]
+struct Invoice {
+    total: u64,
+}
+
+#[test]
+fn test_schema_version() {
+    assert_eq!(Invoice::schema_version(), Some("2.1.0"));
+    assert_eq!(Invoice::schema().schema_version(), Some("2.1.0"));
+    assert_eq!(Box::<Invoice>::schema_version(), Some("2.1.0"));
+
+    assert_eq!(Order::schema_version(), None);
+    assert_eq!(Order::schema().schema_version(), None);
+}